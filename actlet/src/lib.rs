@@ -501,11 +501,19 @@ impl Spawn for Spawner {
 }
 
 /// A type that implements [`Call<M>`] for a particular message.
-#[derive(Clone)]
 pub struct Caller<M> {
     inner: Arc<dyn Call<M> + Send + Sync>,
 }
 
+// Implement manually in order to clone `Caller<M>` even if `M` doesn't implement `Clone`.
+impl<M> Clone for Caller<M> {
+    fn clone(&self) -> Self {
+        Caller {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
 impl<M> Caller<M>
 where
     M: Action,
@@ -8,12 +8,16 @@ use std::time::Instant;
 use actlet::prelude::*;
 use bytes::Bytes;
 use bytes::BytesMut;
+use chrono::DateTime;
+use chrono_jst::jst::Jst;
 use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
 use tokio::sync::mpsc;
 use tokio_stream::Stream;
 use tokio_stream::wrappers::ReceiverStream;
 
+use crate::models::TunerSessionStats;
+use crate::models::TunerSubscriberStats;
 use crate::tuner::TunerSessionId as BroadcasterId;
 use crate::tuner::TunerSubscriptionId as SubscriberId;
 
@@ -43,6 +47,9 @@ struct Subscriber {
     // Used for suppressing noisy logs and reporting the number of bytes dropped
     // while streaming stopped.
     dropped_bytes: Option<usize>,
+    // The total number of dropped chunks and bytes.
+    total_dropped_chunks: u64,
+    total_dropped_bytes: u64,
 }
 
 impl Subscriber {
@@ -53,6 +60,16 @@ impl Subscriber {
             max_stuck_time,
             stuck_start_time: None,
             dropped_bytes: None,
+            total_dropped_chunks: 0,
+            total_dropped_bytes: 0,
+        }
+    }
+
+    fn get_stats(&self) -> TunerSubscriberStats {
+        TunerSubscriberStats {
+            id: self.id.to_string(),
+            dropped_chunks: self.total_dropped_chunks,
+            dropped_bytes: self.total_dropped_bytes,
         }
     }
 }
@@ -64,6 +81,9 @@ pub struct Broadcaster {
     last_received: Instant,
     stream_bound: bool,
     rebuild_mode: bool,
+    stats: StreamStats,
+    // Notified when the bound stream ends.
+    observer: Option<Emitter<SourceEnded>>,
 }
 
 impl Broadcaster {
@@ -81,9 +101,16 @@ impl Broadcaster {
             last_received: Instant::now(),
             stream_bound: false,
             rebuild_mode: crate::timeshift::is_rebuild_mode(),
+            stats: StreamStats::new(),
+            observer: None,
         }
     }
 
+    pub fn with_observer(mut self, observer: Emitter<SourceEnded>) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
     fn bind_stream<R>(&mut self, reader: R, ctx: &mut Context<Self>)
     where
         R: AsyncRead + Send + Unpin + 'static,
@@ -124,6 +151,7 @@ impl Broadcaster {
 
     fn broadcast(&mut self, chunk: Bytes) {
         let chunk_size = chunk.len();
+        self.stats.update(&chunk);
         let active_subscribers = self
             .subscribers
             .iter_mut()
@@ -146,6 +174,8 @@ impl Broadcaster {
                     );
                 }
                 Err(mpsc::error::TrySendError::Full(chunk)) => {
+                    subscriber.total_dropped_chunks += 1;
+                    subscriber.total_dropped_bytes += chunk.len() as u64;
                     if let Some(dropped_bytes) = subscriber.dropped_bytes {
                         // `subscriber.dropped_bytes` might overflow.
                         subscriber.dropped_bytes = Some(dropped_bytes + chunk.len());
@@ -210,6 +240,24 @@ impl Broadcaster {
     fn is_inactive(&self) -> bool {
         self.subscribers.is_empty() && !self.stream_bound
     }
+
+    fn get_stats(&self) -> TunerSessionStats {
+        TunerSessionStats {
+            session_id: self.id.to_string(),
+            start_time: self.stats.start_time,
+            received_bytes: self.stats.received_bytes,
+            bytes_per_sec: self.stats.bytes_per_sec,
+            packets: self.stats.packets.packets,
+            drop_packets: self.stats.packets.drop_packets,
+            error_packets: self.stats.packets.error_packets,
+            signal_level: self.stats.signal_level,
+            subscribers: self
+                .subscribers
+                .iter()
+                .map(|subscriber| subscriber.get_stats())
+                .collect(),
+        }
+    }
 }
 
 #[async_trait]
@@ -319,15 +367,63 @@ impl Handler<MinCapacity> for Broadcaster {
     }
 }
 
+// query stats
+
+#[derive(Message)]
+#[reply(TunerSessionStats)]
+pub struct QueryStats;
+
+#[async_trait]
+impl Handler<QueryStats> for Broadcaster {
+    async fn handle(
+        &mut self,
+        _msg: QueryStats,
+        _ctx: &mut Context<Self>,
+    ) -> <QueryStats as Message>::Reply {
+        tracing::trace!(broadcaster.id = %self.id, msg.name = "QueryStats");
+        self.get_stats()
+    }
+}
+
+// update signal level
+
+#[derive(Message)]
+pub struct UpdateSignalLevel(pub f64);
+
+#[async_trait]
+impl Handler<UpdateSignalLevel> for Broadcaster {
+    async fn handle(&mut self, msg: UpdateSignalLevel, _ctx: &mut Context<Self>) {
+        tracing::trace!(broadcaster.id = %self.id, msg.name = "UpdateSignalLevel", msg.signal_level = msg.0);
+        self.stats.signal_level = Some(msg.0);
+    }
+}
+
 // stream ended
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum StreamEndReason {
+    Eof,
+    // No chunk came within the time limit.
+    TimedOut,
+    Error(String),
+}
+
 #[derive(Message)]
-pub struct StreamEnded;
+pub struct StreamEnded(pub StreamEndReason);
 
 #[async_trait]
 impl Handler<StreamEnded> for Broadcaster {
-    async fn handle(&mut self, _msg: StreamEnded, ctx: &mut Context<Self>) {
-        tracing::debug!(broadcaster.id = %self.id, msg.name = "StreamEnded");
+    async fn handle(&mut self, msg: StreamEnded, ctx: &mut Context<Self>) {
+        tracing::debug!(broadcaster.id = %self.id, msg.name = "StreamEnded", msg.reason = ?msg.0);
+        if let Some(ref observer) = self.observer {
+            observer
+                .emit(SourceEnded {
+                    id: self.id,
+                    reason: msg.0,
+                    received_bytes: self.stats.received_bytes,
+                })
+                .await;
+        }
         self.unbind_stream();
         if self.is_inactive() {
             tracing::debug!(broadcaster.id = %self.id, "Inactive, stop");
@@ -336,6 +432,146 @@ impl Handler<StreamEnded> for Broadcaster {
     }
 }
 
+// source ended
+
+#[derive(Clone, Message)]
+pub struct SourceEnded {
+    pub id: BroadcasterId,
+    pub reason: StreamEndReason,
+    pub received_bytes: u64,
+}
+
+// stats
+
+struct StreamStats {
+    start_time: DateTime<Jst>,
+    received_bytes: u64,
+    // The bitrate is measured in every second.
+    bytes_per_sec: u64,
+    window_start: Instant,
+    window_bytes: u64,
+    packets: TsPacketCounter,
+    signal_level: Option<f64>,
+}
+
+impl StreamStats {
+    const WINDOW: Duration = Duration::from_secs(1);
+
+    fn new() -> Self {
+        StreamStats {
+            start_time: Jst::now(),
+            received_bytes: 0,
+            bytes_per_sec: 0,
+            window_start: Instant::now(),
+            window_bytes: 0,
+            packets: TsPacketCounter::new(),
+            signal_level: None,
+        }
+    }
+
+    fn update(&mut self, chunk: &[u8]) {
+        self.received_bytes += chunk.len() as u64;
+        self.window_bytes += chunk.len() as u64;
+        let elapsed = self.window_start.elapsed();
+        if elapsed >= Self::WINDOW {
+            self.bytes_per_sec = (self.window_bytes as f64 / elapsed.as_secs_f64()) as u64;
+            self.window_start = Instant::now();
+            self.window_bytes = 0;
+        }
+        self.packets.feed(chunk);
+    }
+}
+
+// Counts TS packets passing through the broadcaster.
+//
+// Chunks are not always aligned to TS packet boundaries.  The last incomplete packet in a chunk
+// is kept until the next chunk comes.
+struct TsPacketCounter {
+    pending: Vec<u8>,
+    // The last continuity counter for each PID.
+    last_cc: Vec<u8>,
+    packets: u64,
+    drop_packets: u64,
+    error_packets: u64,
+}
+
+impl TsPacketCounter {
+    const PACKET_SIZE: usize = 188;
+    const SYNC_BYTE: u8 = 0x47;
+    const NUM_PIDS: usize = 0x2000;
+    const NULL_PID: usize = 0x1FFF;
+    const UNKNOWN_CC: u8 = 0xFF;
+
+    fn new() -> Self {
+        TsPacketCounter {
+            pending: Vec::with_capacity(Self::PACKET_SIZE),
+            last_cc: vec![Self::UNKNOWN_CC; Self::NUM_PIDS],
+            packets: 0,
+            drop_packets: 0,
+            error_packets: 0,
+        }
+    }
+
+    fn feed(&mut self, mut data: &[u8]) {
+        if !self.pending.is_empty() {
+            let n = (Self::PACKET_SIZE - self.pending.len()).min(data.len());
+            self.pending.extend_from_slice(&data[..n]);
+            data = &data[n..];
+            if self.pending.len() < Self::PACKET_SIZE {
+                return;
+            }
+            let packet = std::mem::take(&mut self.pending);
+            self.count(&packet);
+            self.pending = packet;
+            self.pending.clear();
+        }
+        while !data.is_empty() {
+            if data[0] != Self::SYNC_BYTE {
+                // Resynchronize.
+                match data.iter().position(|&b| b == Self::SYNC_BYTE) {
+                    Some(pos) => data = &data[pos..],
+                    None => return,
+                }
+                continue;
+            }
+            if data.len() < Self::PACKET_SIZE {
+                self.pending.extend_from_slice(data);
+                return;
+            }
+            self.count(&data[..Self::PACKET_SIZE]);
+            data = &data[Self::PACKET_SIZE..];
+        }
+    }
+
+    fn count(&mut self, packet: &[u8]) {
+        self.packets += 1;
+        if packet[1] & 0x80 != 0 {
+            // The transport error indicator is set.  Other fields are unreliable.
+            self.error_packets += 1;
+            return;
+        }
+        let pid = (((packet[1] & 0x1F) as usize) << 8) | packet[2] as usize;
+        if pid == Self::NULL_PID {
+            return;
+        }
+        let adaptation_field_control = (packet[3] >> 4) & 0x03;
+        let has_payload = adaptation_field_control & 0x01 != 0;
+        let discontinuity =
+            adaptation_field_control & 0x02 != 0 && packet[4] > 0 && packet[5] & 0x80 != 0;
+        let cc = packet[3] & 0x0F;
+        let last_cc = std::mem::replace(&mut self.last_cc[pid], cc);
+        if last_cc == Self::UNKNOWN_CC || discontinuity {
+            return;
+        }
+        if !has_payload || cc == last_cc {
+            // The continuity counter is not incremented, or a duplicate packet.
+            return;
+        }
+        let expected = (last_cc + 1) & 0x0F;
+        self.drop_packets += (cc.wrapping_sub(expected) & 0x0F) as u64;
+    }
+}
+
 // chunk source
 
 struct ChunkSource<R, B> {
@@ -382,7 +618,9 @@ where
                             broadcaster.time_limit = %humantime::format_duration(time_limit),
                             "No packet came from the tuner within the time limit, stop streaming"
                         );
-                        self.broadcaster.emit(StreamEnded).await;
+                        self.broadcaster
+                            .emit(StreamEnded(StreamEndReason::TimedOut))
+                            .await;
                         return;
                     }
                 }
@@ -436,7 +674,9 @@ where
                     broadcaster.id = %self.id,
                     "EOF, unbind stream"
                 );
-                self.broadcaster.emit(StreamEnded).await;
+                self.broadcaster
+                    .emit(StreamEnded(StreamEndReason::Eof))
+                    .await;
                 return true;
             }
             Ok(Some(chunk)) => {
@@ -458,7 +698,9 @@ where
                     broadcaster.id = %self.id,
                     "Error, unbind stream"
                 );
-                self.broadcaster.emit(StreamEnded).await;
+                self.broadcaster
+                    .emit(StreamEnded(StreamEndReason::Error(err.to_string())))
+                    .await;
                 return true;
             }
         }
@@ -571,6 +813,72 @@ mod tests {
         assert!(broadcaster.subscribers[0].stuck_start_time.is_none());
     }
 
+    #[test]
+    fn test_stats() {
+        let mut broadcaster = Broadcaster::new(Default::default(), 0);
+
+        let (sender, _receiver) = mpsc::channel(1);
+        let subscriber = Subscriber::new(
+            SubscriberId::new(Default::default(), 1),
+            sender,
+            Default::default(),
+        );
+        broadcaster.subscribers.push(subscriber);
+
+        broadcaster.broadcast(Bytes::from(ts_packet(0x100, 0, false).to_vec()));
+        // The queue is full.
+        broadcaster.broadcast(Bytes::from(ts_packet(0x100, 1, false).to_vec()));
+        broadcaster.stats.signal_level = Some(1.5);
+
+        assert_matches!(broadcaster.get_stats(), stats => {
+            assert_eq!(stats.received_bytes, 376);
+            assert_eq!(stats.packets, 2);
+            assert_eq!(stats.drop_packets, 0);
+            assert_eq!(stats.error_packets, 0);
+            assert_eq!(stats.signal_level, Some(1.5));
+            assert_eq!(stats.subscribers.len(), 1);
+            assert_eq!(stats.subscribers[0].dropped_chunks, 1);
+            assert_eq!(stats.subscribers[0].dropped_bytes, 188);
+        });
+    }
+
+    #[test]
+    fn test_ts_packet_counter() {
+        let mut data = vec![0xFF, 0xFF]; // garbage
+        data.extend(ts_packet(0x100, 0, false));
+        data.extend(ts_packet(0x100, 1, false));
+        data.extend(ts_packet(0x101, 9, false));
+        // 2 packets dropped.
+        data.extend(ts_packet(0x100, 4, false));
+        // A duplicate packet.
+        data.extend(ts_packet(0x100, 4, false));
+        // Wraps around.
+        data.extend(ts_packet(0x101, 0, false));
+        data.extend(ts_packet(0x100, 5, true));
+        data.extend(ts_packet(0x1FFF, 0, false));
+
+        let mut counter = TsPacketCounter::new();
+        // Chunks are not aligned to TS packet boundaries.
+        for chunk in data.chunks(100) {
+            counter.feed(chunk);
+        }
+        assert_eq!(counter.packets, 8);
+        assert_eq!(counter.drop_packets, 2 + 6);
+        assert_eq!(counter.error_packets, 1);
+    }
+
+    fn ts_packet(pid: u16, cc: u8, error: bool) -> [u8; 188] {
+        let mut packet = [0xFF; 188];
+        packet[0] = 0x47;
+        packet[1] = ((pid >> 8) as u8) & 0x1F;
+        if error {
+            packet[1] |= 0x80;
+        }
+        packet[2] = pid as u8;
+        packet[3] = 0x10 | (cc & 0x0F); // payload only
+        packet
+    }
+
     #[test(tokio::test)]
     async fn test_broadcast() {
         let system = System::new();
@@ -604,7 +912,7 @@ mod tests {
                 .call(Broadcast(Bytes::from("hello")))
                 .await
                 .unwrap();
-            broadcaster.emit(StreamEnded).await;
+            broadcaster.emit(StreamEnded(StreamEndReason::Eof)).await;
 
             let chunk = stream1.next().await;
             assert!(chunk.is_some());
@@ -663,7 +971,7 @@ mod tests {
                 .call(Broadcast(Bytes::from("hello")))
                 .await
                 .unwrap();
-            broadcaster.emit(StreamEnded).await;
+            broadcaster.emit(StreamEnded(StreamEndReason::Eof)).await;
 
             let chunk = stream1.next().await;
             assert!(chunk.is_none());
@@ -689,6 +997,44 @@ mod tests {
         system.shutdown().await;
     }
 
+    #[test(tokio::test)]
+    async fn test_source_ended() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let mut observer = MockSourceEndedObserver::new();
+        observer
+            .expect_emit()
+            .returning(move |msg| tx.send((msg.reason, msg.received_bytes)).unwrap());
+
+        let system = System::new();
+        {
+            let broadcaster = system
+                .spawn_actor(
+                    Broadcaster::new(Default::default(), 1000)
+                        .with_observer(Emitter::new(observer)),
+                )
+                .await;
+
+            broadcaster
+                .inspect(|b| b.stream_bound = true)
+                .await
+                .unwrap();
+
+            broadcaster
+                .call(Broadcast(Bytes::from("hello")))
+                .await
+                .unwrap();
+            broadcaster
+                .emit(StreamEnded(StreamEndReason::TimedOut))
+                .await;
+
+            // The broadcaster stops because there is no subscriber.
+            broadcaster.wait().await;
+        }
+        system.shutdown().await;
+
+        assert_eq!(rx.recv().unwrap(), (StreamEndReason::TimedOut, 5));
+    }
+
     #[test(tokio::test)]
     async fn test_chunk_source_read_chunk() {
         let mut mock = MockBroadcaster::new();
@@ -719,7 +1065,10 @@ mod tests {
         let time_limit = Duration::from_secs(1);
 
         let mut mock = MockBroadcaster::new();
-        mock.expect_emit().times(1).returning(|_| ());
+        mock.expect_emit()
+            .withf(|msg| msg.0 == StreamEndReason::Eof)
+            .times(1)
+            .returning(|_| ());
 
         let mut chunk_source =
             ChunkSource::new(tokio::io::empty(), Default::default(), time_limit, mock);
@@ -737,7 +1086,10 @@ mod tests {
         let (_, output) = pipeline.take_endpoints();
 
         let mut mock = MockBroadcaster::new();
-        mock.expect_emit().times(1).returning(|_| ());
+        mock.expect_emit()
+            .withf(|msg| msg.0 == StreamEndReason::TimedOut)
+            .times(1)
+            .returning(|_| ());
 
         let mut chunk_source = ChunkSource::new(output, Default::default(), time_limit, mock);
 
@@ -755,6 +1107,15 @@ mod tests {
         }
     }
 
+    mockall::mock! {
+        SourceEndedObserver {}
+
+        #[async_trait]
+        impl Emit<SourceEnded> for SourceEndedObserver {
+            async fn emit(&self, msg: SourceEnded);
+        }
+    }
+
     #[async_trait]
     impl Call<Broadcast> for MockBroadcaster {
         async fn call(&self, _msg: Broadcast) -> actlet::Result<<Broadcast as Message>::Reply> {
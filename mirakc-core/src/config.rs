@@ -129,6 +129,8 @@ pub struct Config {
     #[serde(default)]
     pub tuners: Vec<TunerConfig>,
     #[serde(default)]
+    pub tuner_reservations: TunerReservationsConfig,
+    #[serde(default)]
     pub filters: FiltersConfig,
     #[serde(default)]
     pub pre_filters: HashMap<String, PreFilterConfig>,
@@ -144,6 +146,8 @@ pub struct Config {
     #[serde(with = "serde_norway::with::singleton_map_recursive")]
     pub onair_program_trackers: IndexMap<String, OnairProgramTrackerConfig>,
     #[serde(default)]
+    pub webhook: WebhookConfig,
+    #[serde(default)]
     pub resource: ResourceConfig,
 }
 
@@ -276,6 +280,7 @@ impl Config {
                     .count(),
             "config.tuners: `name` must be a unique"
         );
+        self.tuner_reservations.validate();
         self.filters.validate();
         self.pre_filters
             .iter()
@@ -288,6 +293,7 @@ impl Config {
         self.onair_program_trackers
             .iter()
             .for_each(|(name, config)| config.validate(name));
+        self.webhook.validate();
         self.resource.validate();
     }
 }
@@ -585,7 +591,10 @@ pub struct TunerConfig {
     pub name: String,
     #[serde(rename = "types")]
     pub channel_types: Vec<ChannelType>,
+    #[serde(default)]
     pub command: String,
+    #[serde(default)]
+    pub remote: Option<RemoteTunerConfig>,
     #[serde(default = "TunerConfig::default_time_limit")]
     pub time_limit: u64,
     #[serde(default)]
@@ -595,6 +604,10 @@ pub struct TunerConfig {
     #[serde(default)]
     #[serde(with = "serde_norway::with::singleton_map_recursive")]
     pub excluded_channels: Vec<ExcludedChannelConfig>,
+    #[serde(default)]
+    pub health_check: TunerHealthCheckConfig,
+    #[serde(default)]
+    pub signal_level: TunerSignalLevelConfig,
 }
 
 impl TunerConfig {
@@ -614,17 +627,27 @@ impl TunerConfig {
             !self.channel_types.is_empty(),
             "config.tuners[{index}].types: must be a non-empty list"
         );
-        validate!(
-            !self.command.is_empty(),
-            "config.tuners[{index}].command: must be a non-empty string"
-        );
-        validate!(
-            is_valid_command(&self.command),
-            "config.tuners[{index}].command: must be a valid command"
-        );
+        if let Some(ref remote) = self.remote {
+            validate!(
+                self.command.is_empty(),
+                "config.tuners[{index}]: command and remote cannot be specified at the same time"
+            );
+            remote.validate(index);
+        } else {
+            validate!(
+                !self.command.is_empty(),
+                "config.tuners[{index}].command: must be a non-empty string"
+            );
+            validate!(
+                is_valid_command(&self.command),
+                "config.tuners[{index}].command: must be a valid command"
+            );
+        }
         for (i, excluded) in self.excluded_channels.iter().enumerate() {
             excluded.validate(index, i);
         }
+        self.health_check.validate(index);
+        self.signal_level.validate(index);
     }
 }
 
@@ -634,14 +657,220 @@ impl Default for TunerConfig {
             name: "".to_string(),
             channel_types: vec![],
             command: "".to_string(),
+            remote: None,
             time_limit: Self::default_time_limit(),
             disabled: false,
             decoded: false,
             excluded_channels: vec![],
+            health_check: Default::default(),
+            signal_level: Default::default(),
         }
     }
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct TunerHealthCheckConfig {
+    // `0` disables the quarantine.
+    #[serde(default = "TunerHealthCheckConfig::default_max_failures")]
+    pub max_failures: u32,
+    #[serde(
+        default = "TunerHealthCheckConfig::default_quarantine_time",
+        with = "humantime_serde"
+    )]
+    pub quarantine_time: Duration,
+    #[serde(
+        default = "TunerHealthCheckConfig::default_max_quarantine_time",
+        with = "humantime_serde"
+    )]
+    pub max_quarantine_time: Duration,
+    #[serde(default)]
+    pub probe_command: String,
+    #[serde(
+        default = "TunerHealthCheckConfig::default_probe_interval",
+        with = "humantime_serde"
+    )]
+    pub probe_interval: Duration,
+}
+
+impl TunerHealthCheckConfig {
+    fn default_max_failures() -> u32 {
+        3
+    }
+
+    fn default_quarantine_time() -> Duration {
+        Duration::from_secs(30)
+    }
+
+    fn default_max_quarantine_time() -> Duration {
+        Duration::from_secs(600) // 10m
+    }
+
+    fn default_probe_interval() -> Duration {
+        Duration::from_secs(60)
+    }
+
+    fn validate(&self, index: usize) {
+        validate!(
+            !self.quarantine_time.is_zero(),
+            "config.tuners[{index}].health-check.quarantine-time: must be a non-zero duration"
+        );
+        validate!(
+            self.quarantine_time <= self.max_quarantine_time,
+            "config.tuners[{index}].health-check.max-quarantine-time: \
+             must be larger than or equal to quarantine-time"
+        );
+        if !self.probe_command.is_empty() {
+            validate!(
+                is_valid_command(&self.probe_command),
+                "config.tuners[{index}].health-check.probe-command: must be a valid command"
+            );
+            validate!(
+                !self.probe_interval.is_zero(),
+                "config.tuners[{index}].health-check.probe-interval: must be a non-zero duration"
+            );
+        }
+    }
+}
+
+impl Default for TunerHealthCheckConfig {
+    fn default() -> Self {
+        TunerHealthCheckConfig {
+            max_failures: Self::default_max_failures(),
+            quarantine_time: Self::default_quarantine_time(),
+            max_quarantine_time: Self::default_max_quarantine_time(),
+            probe_command: "".to_string(),
+            probe_interval: Self::default_probe_interval(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct TunerSignalLevelConfig {
+    // A Mustache template string of a command to get the signal level.
+    #[serde(default)]
+    pub command: String,
+    #[serde(
+        default = "TunerSignalLevelConfig::default_interval",
+        with = "humantime_serde"
+    )]
+    pub interval: Duration,
+}
+
+impl TunerSignalLevelConfig {
+    fn default_interval() -> Duration {
+        Duration::from_secs(10)
+    }
+
+    fn validate(&self, index: usize) {
+        if self.command.is_empty() {
+            return;
+        }
+        validate!(
+            is_valid_command(&self.command),
+            "config.tuners[{index}].signal-level.command: must be a valid command"
+        );
+        validate!(
+            !self.interval.is_zero(),
+            "config.tuners[{index}].signal-level.interval: must be a non-zero duration"
+        );
+    }
+}
+
+impl Default for TunerSignalLevelConfig {
+    fn default() -> Self {
+        TunerSignalLevelConfig {
+            command: "".to_string(),
+            interval: Self::default_interval(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct TunerReservationsConfig {
+    #[serde(default)]
+    pub basedir: Option<PathBuf>,
+}
+
+impl TunerReservationsConfig {
+    fn validate(&self) {
+        if let Some(ref basedir) = self.basedir {
+            validate!(
+                basedir.is_absolute(),
+                "config.tuner-reservations.basedir: must be an absolute path"
+            );
+            validate!(
+                basedir.is_dir(),
+                "config.tuner-reservations.basedir: must be a path to an existing directory"
+            );
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct RemoteTunerConfig {
+    pub url: Url,
+    #[serde(
+        default = "RemoteTunerConfig::default_polling_interval",
+        with = "humantime_serde"
+    )]
+    pub polling_interval: Duration,
+    #[serde(
+        default = "RemoteTunerConfig::default_response_timeout",
+        with = "humantime_serde"
+    )]
+    pub response_timeout: Duration,
+}
+
+impl RemoteTunerConfig {
+    fn default_polling_interval() -> Duration {
+        Duration::from_secs(10)
+    }
+
+    // Short enough because the tuner manager waits for the response.
+    fn default_response_timeout() -> Duration {
+        Duration::from_secs(3)
+    }
+
+    pub fn tuners_url(&self) -> Url {
+        self.url.join("/api/tuners").unwrap()
+    }
+
+    pub fn stream_url(&self, channel_type: ChannelType, channel: &str, decode: bool) -> Url {
+        let mut url = self.url.join("/api/channels").unwrap();
+        url.path_segments_mut()
+            .unwrap()
+            .push(&channel_type.to_string())
+            .push(channel)
+            .push("stream");
+        url.query_pairs_mut()
+            .append_pair("decode", if decode { "1" } else { "0" });
+        url
+    }
+
+    fn validate(&self, index: usize) {
+        validate!(
+            self.url.scheme() == "http",
+            "config.tuners[{index}].remote.url: must be a HTTP URL"
+        );
+        validate!(
+            !self.polling_interval.is_zero(),
+            "config.tuners[{index}].remote.polling-interval: must be a non-zero duration"
+        );
+        validate!(
+            !self.response_timeout.is_zero(),
+            "config.tuners[{index}].remote.response-timeout: must be a non-zero duration"
+        );
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
@@ -713,7 +942,9 @@ impl FiltersConfig {
         FilterConfig {
             command: "mirakc-arib filter-program --sid={{{sid}}} --eid={{{eid}}} \
                       --clock-pid={{{clock_pid}}} --clock-pcr={{{clock_pcr}}} \
-                      --clock-time={{{clock_time}}} --end-margin=2000\
+                      --clock-time={{{clock_time}}}\
+                      {{#start_margin}} --start-margin={{{.}}}{{/start_margin}} \
+                      --end-margin={{{end_margin}}}\
                       {{#video_tags}} --video-tag={{{.}}}{{/video_tags}}\
                       {{#audio_tags}} --audio-tag={{{.}}}{{/audio_tags}}\
                       {{#wait_until}} --wait-until={{{.}}}{{/wait_until}}\
@@ -987,9 +1218,21 @@ pub struct RecordingConfig {
     pub basedir: Option<PathBuf>,
     pub records_dir: Option<PathBuf>,
     pub log_filter: Option<String>,
+    #[serde(default, with = "humantime_serde")]
+    pub start_margin: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    pub end_margin: Option<Duration>,
+    #[serde(default)]
+    pub retention: RecordingRetentionConfig,
+    #[serde(default)]
+    pub post_process: RecordingPostProcessConfig,
+    #[serde(default)]
+    pub tuner_retry: RecordingTunerRetryConfig,
 }
 
 impl RecordingConfig {
+    pub const MAX_MARGIN: Duration = Duration::from_secs(3600);
+
     pub fn is_enabled(&self) -> bool {
         self.basedir.is_some()
     }
@@ -1020,6 +1263,209 @@ impl RecordingConfig {
                 "config.recording.records-dir: must be a path to an existing directory"
             );
         }
+
+        if let Some(start_margin) = self.start_margin {
+            validate!(
+                start_margin <= Self::MAX_MARGIN,
+                "config.recording.start-margin: must be less than or equal to 1h"
+            );
+        }
+
+        if let Some(end_margin) = self.end_margin {
+            validate!(
+                end_margin <= Self::MAX_MARGIN,
+                "config.recording.end-margin: must be less than or equal to 1h"
+            );
+        }
+
+        if self.retention.is_enabled() {
+            validate!(
+                self.is_records_api_enabled(),
+                "config.recording.retention: requires config.recording.basedir and \
+                 config.recording.records-dir"
+            );
+        }
+        self.retention.validate();
+
+        if self.post_process.is_enabled() {
+            validate!(
+                self.is_records_api_enabled(),
+                "config.recording.post-process: requires config.recording.basedir and \
+                 config.recording.records-dir"
+            );
+        }
+        self.post_process.validate();
+        self.tuner_retry.validate();
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct RecordingRetentionConfig {
+    pub min_free_space: Option<u64>,
+    pub max_total_size: Option<u64>,
+    #[serde(default, with = "humantime_serde")]
+    pub max_age: Option<Duration>,
+    pub keep_newest_per_tag: Option<usize>,
+    pub keep_newest_per_series: Option<usize>,
+    #[serde(
+        default = "RecordingRetentionConfig::default_interval",
+        with = "humantime_serde"
+    )]
+    pub interval: Duration,
+}
+
+impl RecordingRetentionConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.min_free_space.is_some()
+            || self.max_total_size.is_some()
+            || self.max_age.is_some()
+            || self.keep_newest_per_tag.is_some()
+            || self.keep_newest_per_series.is_some()
+    }
+
+    fn default_interval() -> Duration {
+        Duration::from_secs(60)
+    }
+
+    fn validate(&self) {
+        validate!(
+            self.interval >= Duration::from_secs(1),
+            "config.recording.retention.interval: must be larger than or equal to 1s"
+        );
+        if let Some(max_age) = self.max_age {
+            validate!(
+                !max_age.is_zero(),
+                "config.recording.retention.max-age: must be larger than 0"
+            );
+        }
+        if let Some(keep_newest_per_tag) = self.keep_newest_per_tag {
+            validate!(
+                keep_newest_per_tag > 0,
+                "config.recording.retention.keep-newest-per-tag: must be larger than 0"
+            );
+        }
+        if let Some(keep_newest_per_series) = self.keep_newest_per_series {
+            validate!(
+                keep_newest_per_series > 0,
+                "config.recording.retention.keep-newest-per-series: must be larger than 0"
+            );
+        }
+    }
+}
+
+impl Default for RecordingRetentionConfig {
+    fn default() -> Self {
+        RecordingRetentionConfig {
+            min_free_space: None,
+            max_total_size: None,
+            max_age: None,
+            keep_newest_per_tag: None,
+            keep_newest_per_series: None,
+            interval: Self::default_interval(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct RecordingPostProcessConfig {
+    #[serde(default = "RecordingPostProcessConfig::default_concurrency")]
+    pub concurrency: usize,
+    #[serde(default)]
+    pub jobs: IndexMap<String, RecordingPostProcessJobConfig>,
+}
+
+impl RecordingPostProcessConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.jobs.is_empty()
+    }
+
+    fn default_concurrency() -> usize {
+        1
+    }
+
+    fn validate(&self) {
+        validate!(
+            self.concurrency > 0,
+            "config.recording.post-process.concurrency: must be larger than 0"
+        );
+        self.jobs
+            .iter()
+            .for_each(|(name, config)| config.validate(name));
+    }
+}
+
+impl Default for RecordingPostProcessConfig {
+    fn default() -> Self {
+        RecordingPostProcessConfig {
+            concurrency: Self::default_concurrency(),
+            jobs: IndexMap::new(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct RecordingPostProcessJobConfig {
+    pub command: String,
+    #[serde(default)]
+    pub run_on_failure: bool,
+}
+
+impl RecordingPostProcessJobConfig {
+    fn validate(&self, name: &str) {
+        // The name is used in the filename of the log file and the URL of the Web API.
+        validate!(
+            !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+            "config.recording.post-process.jobs[{name}]: \
+             name must consist only of alphanumeric characters, `-` and `_`"
+        );
+        validate!(
+            !self.command.is_empty(),
+            "config.recording.post-process.jobs[{name}]: \
+             `command` must be a non-empty string"
+        );
+        validate!(
+            is_valid_command(&self.command),
+            "config.recording.post-process.jobs[{name}]: \
+             `command` must be a valid command"
+        );
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct RecordingTunerRetryConfig {
+    #[serde(default = "RecordingTunerRetryConfig::default_max_attempts")]
+    pub max_attempts: usize,
+}
+
+impl RecordingTunerRetryConfig {
+    fn default_max_attempts() -> usize {
+        3
+    }
+
+    fn validate(&self) {
+        validate!(
+            self.max_attempts > 0,
+            "config.recording.tuner-retry.max-attempts: must be larger than 0"
+        );
+    }
+}
+
+impl Default for RecordingTunerRetryConfig {
+    fn default() -> Self {
+        RecordingTunerRetryConfig {
+            max_attempts: Self::default_max_attempts(),
+        }
     }
 }
 
@@ -1059,6 +1505,33 @@ impl TimeshiftConfig {
         self.recorders
             .iter()
             .for_each(|(name, config)| config.validate(name));
+
+        // Recorders can share a tuner if they record services on the same channel.  In this case,
+        // a single tuner stream is shared among the recorders and each recorder extracts its
+        // service from the stream.  Each recorder must have its own files.
+        for (i, (name, config)) in self.recorders.iter().enumerate() {
+            for (other_name, other) in self.recorders.iter().take(i) {
+                validate!(
+                    config.ts_file != other.ts_file,
+                    "config.timeshift.recorders[{name}]: \
+                     `ts-file` is already used in recorders[{other_name}]"
+                );
+                validate!(
+                    config.data_file != other.data_file,
+                    "config.timeshift.recorders[{name}]: \
+                     `data-file` is already used in recorders[{other_name}]"
+                );
+                if config.uses.tuner == other.uses.tuner {
+                    validate!(
+                        config.uses.channel_type == other.uses.channel_type
+                            && config.uses.channel == other.uses.channel,
+                        "config.timeshift.recorders[{name}]: \
+                         uses tuner[{}] for a channel different from recorders[{other_name}]",
+                        config.uses.tuner
+                    );
+                }
+            }
+        }
     }
 }
 
@@ -1086,6 +1559,8 @@ pub struct TimeshiftRecorderConfig {
     #[serde(default = "TimeshiftRecorderConfig::default_priority")]
     pub priority: i32,
     pub uses: TimeshiftRecorderUses,
+    #[serde(default)]
+    pub pin: TimeshiftRecorderPinConfig,
 }
 
 impl TimeshiftRecorderConfig {
@@ -1132,20 +1607,17 @@ impl TimeshiftRecorderConfig {
             "config.timeshift.recorders[{name}]: \
              `data-file` must be a path to a regular file"
         );
-        // TODO
-        // ----
-        // We should save data in a binary format in a performance point of view.  However, the
-        // following crates don't work properly (maybe, due to bugs...).
+        // We don't use a binary format for the data-file because the following crates don't
+        // work properly with our data formats (maybe, due to bugs...).
         //
         //   * bincode
         //   * rmp_serde
         //
-        // We may support a binary format in the future if there is a crate that works well with
-        // our data formats.
+        // Instead, the journal format appends only changes to the data-file.
         validate!(
-            self.data_file.extension().is_some() && self.data_file.extension().unwrap() == "json",
+            crate::timeshift::TimeshiftDataFormat::from_path(&self.data_file).is_some(),
             "config.timeshift.recorders[{name}]: \
-             `data-file` must be a JSON file"
+             `data-file` must be a JSON file or a journal file"
         );
         validate!(
             self.chunk_size > 0,
@@ -1205,6 +1677,15 @@ impl TimeshiftRecorderConfig {
     }
 }
 
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct TimeshiftRecorderPinConfig {
+    // Records of TV programs in the specified series are pinned automatically.
+    #[serde(default)]
+    pub series_ids: Vec<u16>,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
@@ -1375,6 +1856,165 @@ pub enum RemoteOnairProgramTrackerUrl {
     Mirakc(Url),
 }
 
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    #[serde(default)]
+    pub basedir: Option<PathBuf>,
+    #[serde(default = "WebhookConfig::default_timeout", with = "humantime_serde")]
+    pub timeout: Duration,
+    #[serde(default = "WebhookConfig::default_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(
+        default = "WebhookConfig::default_retry_interval",
+        with = "humantime_serde"
+    )]
+    pub retry_interval: Duration,
+    #[serde(
+        default = "WebhookConfig::default_max_retry_interval",
+        with = "humantime_serde"
+    )]
+    pub max_retry_interval: Duration,
+    #[serde(default)]
+    pub endpoints: Vec<WebhookEndpointConfig>,
+}
+
+impl WebhookConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.endpoints.is_empty()
+    }
+
+    // Returns `true` if at least one of the endpoints subscribes the event.
+    pub fn subscribes(&self, event: &str) -> bool {
+        self.endpoints
+            .iter()
+            .any(|endpoint| endpoint.subscribes(event))
+    }
+
+    fn default_timeout() -> Duration {
+        Duration::from_secs(10)
+    }
+
+    fn default_max_attempts() -> u32 {
+        5
+    }
+
+    fn default_retry_interval() -> Duration {
+        Duration::from_secs(10)
+    }
+
+    fn default_max_retry_interval() -> Duration {
+        Duration::from_secs(600)
+    }
+
+    fn validate(&self) {
+        if let Some(ref basedir) = self.basedir {
+            validate!(
+                basedir.is_absolute(),
+                "config.webhook.basedir: must be an absolute path"
+            );
+            validate!(
+                basedir.is_dir(),
+                "config.webhook.basedir: must be a path to an existing directory"
+            );
+        } else if self.is_enabled() {
+            tracing::warn!(
+                config = "webhook.basedir",
+                "Not specified, undelivered events will be lost when mirakc stops"
+            );
+        }
+        validate!(
+            !self.timeout.is_zero(),
+            "config.webhook.timeout: must be larger than 0"
+        );
+        validate!(
+            self.max_attempts > 0,
+            "config.webhook.max-attempts: must be larger than 0"
+        );
+        validate!(
+            !self.retry_interval.is_zero(),
+            "config.webhook.retry-interval: must be larger than 0"
+        );
+        validate!(
+            self.max_retry_interval >= self.retry_interval,
+            "config.webhook.max-retry-interval: \
+             must be larger than or equal to config.webhook.retry-interval"
+        );
+        self.endpoints
+            .iter()
+            .enumerate()
+            .for_each(|(i, config)| config.validate(i));
+    }
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        WebhookConfig {
+            basedir: None,
+            timeout: Self::default_timeout(),
+            max_attempts: Self::default_max_attempts(),
+            retry_interval: Self::default_retry_interval(),
+            max_retry_interval: Self::default_max_retry_interval(),
+            endpoints: vec![],
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+#[serde(deny_unknown_fields)]
+pub struct WebhookEndpointConfig {
+    pub url: Url,
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+impl WebhookEndpointConfig {
+    // An empty list subscribes all events.  A pattern ending with `.*` subscribes all events in
+    // the category.
+    pub fn subscribes(&self, event: &str) -> bool {
+        if self.events.is_empty() {
+            return true;
+        }
+        self.events
+            .iter()
+            .any(|pattern| match pattern.strip_suffix(".*") {
+                Some(category) => event
+                    .strip_prefix(category)
+                    .is_some_and(|name| name.starts_with('.')),
+                None => pattern == event,
+            })
+    }
+
+    fn validate(&self, i: usize) {
+        validate!(
+            self.url.scheme() == "http",
+            "config.webhook.endpoints[{i}].url: must be a HTTP URL"
+        );
+        for pattern in self.events.iter() {
+            let valid = match pattern.strip_suffix(".*") {
+                Some(category) => crate::events::EventMessage::NAMES
+                    .iter()
+                    .any(|name| name.split_once('.').unwrap().0 == category),
+                None => crate::events::EventMessage::NAMES.contains(&pattern.as_str()),
+            };
+            validate!(
+                valid,
+                "config.webhook.endpoints[{i}].events: unknown event {pattern}"
+            );
+        }
+        if let Some(ref secret) = self.secret {
+            validate!(
+                !secret.is_empty(),
+                "config.webhook.endpoints[{i}].secret: must be a non-empty string"
+            );
+        }
+    }
+}
+
 #[derive(Clone, Deserialize, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 #[serde(deny_unknown_fields)]
@@ -2572,6 +3212,81 @@ mod tests {
             config
         );
 
+        let mut config = TunerConfig::default();
+        config.name = "x".to_string();
+        config.channel_types = vec![ChannelType::GR];
+        config.remote = Some(RemoteTunerConfig {
+            url: Url::parse("http://upstream:40772/").unwrap(),
+            polling_interval: Duration::from_secs(1),
+            response_timeout: Duration::from_secs(2),
+        });
+        assert_eq!(
+            serde_norway::from_str::<TunerConfig>(
+                r#"
+                name: x
+                types: [GR]
+                remote:
+                  url: http://upstream:40772/
+                  polling-interval: 1s
+                  response-timeout: 2s
+            "#
+            )
+            .unwrap(),
+            config
+        );
+
+        let mut config = TunerConfig::default();
+        config.name = "x".to_string();
+        config.channel_types = vec![ChannelType::GR];
+        config.command = "cat /dev/null".to_string();
+        config.health_check = TunerHealthCheckConfig {
+            max_failures: 1,
+            quarantine_time: Duration::from_secs(1),
+            max_quarantine_time: Duration::from_secs(2),
+            probe_command: "true".to_string(),
+            probe_interval: Duration::from_secs(3),
+        };
+        assert_eq!(
+            serde_norway::from_str::<TunerConfig>(
+                r#"
+                name: x
+                types: [GR]
+                command: cat /dev/null
+                health-check:
+                  max-failures: 1
+                  quarantine-time: 1s
+                  max-quarantine-time: 2s
+                  probe-command: 'true'
+                  probe-interval: 3s
+            "#
+            )
+            .unwrap(),
+            config
+        );
+
+        let mut config = TunerConfig::default();
+        config.name = "x".to_string();
+        config.channel_types = vec![ChannelType::GR];
+        config.command = "cat /dev/null".to_string();
+        config.signal_level = TunerSignalLevelConfig {
+            command: "checksignal {{{channel}}}".to_string(),
+            interval: Duration::from_secs(1),
+        };
+        assert_eq!(
+            serde_norway::from_str::<TunerConfig>(
+                r#"
+                name: x
+                types: [GR]
+                command: cat /dev/null
+                signal-level:
+                  command: checksignal {{{channel}}}
+                  interval: 1s
+            "#
+            )
+            .unwrap(),
+            config
+        );
+
         assert!(
             serde_norway::from_str::<TunerConfig>(
                 r#"
@@ -2602,43 +3317,175 @@ mod tests {
     }
 
     #[test]
-    fn test_tuner_config_validate() {
-        let config = tuner_config();
+    fn test_tuner_config_validate() {
+        let config = tuner_config();
+        config.validate(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "config.tuners[0].name: must be a non-empty string")]
+    fn test_tuner_config_validate_empty_name() {
+        let mut config = tuner_config();
+        config.name = "".to_string();
+        config.validate(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "config.tuners[0].types: must be a non-empty list")]
+    fn test_tuner_config_validate_empty_types() {
+        let mut config = tuner_config();
+        config.channel_types = vec![];
+        config.validate(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "config.tuners[0].command: must be a non-empty string")]
+    fn test_tuner_config_validate_empty_command() {
+        let mut config = tuner_config();
+        config.command = "".to_string();
+        config.validate(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "config.tuners[0].command: must be a valid command")]
+    fn test_tuner_config_validate_command() {
+        let mut config = tuner_config();
+        config.command = "no-such-command".to_string();
+        config.validate(0);
+    }
+
+    #[test]
+    fn test_tuner_config_validate_remote() {
+        let mut config = tuner_config();
+        config.command = "".to_string();
+        config.remote = Some(remote_tuner_config());
+        config.validate(0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "config.tuners[0]: command and remote cannot be specified at the same time"
+    )]
+    fn test_tuner_config_validate_remote_with_command() {
+        let mut config = tuner_config();
+        config.remote = Some(remote_tuner_config());
+        config.validate(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "config.tuners[0].remote.url: must be a HTTP URL")]
+    fn test_tuner_config_validate_remote_url() {
+        let mut config = tuner_config();
+        config.command = "".to_string();
+        config.remote = Some(RemoteTunerConfig {
+            url: Url::parse("ftp://upstream/").unwrap(),
+            ..remote_tuner_config()
+        });
+        config.validate(0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "config.tuners[0].remote.polling-interval: must be a non-zero duration"
+    )]
+    fn test_tuner_config_validate_remote_polling_interval() {
+        let mut config = tuner_config();
+        config.command = "".to_string();
+        config.remote = Some(RemoteTunerConfig {
+            polling_interval: Duration::ZERO,
+            ..remote_tuner_config()
+        });
+        config.validate(0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "config.tuners[0].remote.response-timeout: must be a non-zero duration"
+    )]
+    fn test_tuner_config_validate_remote_response_timeout() {
+        let mut config = tuner_config();
+        config.command = "".to_string();
+        config.remote = Some(RemoteTunerConfig {
+            response_timeout: Duration::ZERO,
+            ..remote_tuner_config()
+        });
+        config.validate(0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "config.tuners[0].health-check.quarantine-time: must be a non-zero duration"
+    )]
+    fn test_tuner_config_validate_health_check_quarantine_time() {
+        let mut config = tuner_config();
+        config.health_check.quarantine_time = Duration::ZERO;
         config.validate(0);
     }
 
     #[test]
-    #[should_panic(expected = "config.tuners[0].name: must be a non-empty string")]
-    fn test_tuner_config_validate_empty_name() {
+    #[should_panic(
+        expected = "config.tuners[0].health-check.max-quarantine-time: must be larger than or equal to quarantine-time"
+    )]
+    fn test_tuner_config_validate_health_check_max_quarantine_time() {
         let mut config = tuner_config();
-        config.name = "".to_string();
+        config.health_check.max_quarantine_time = Duration::from_secs(1);
         config.validate(0);
     }
 
     #[test]
-    #[should_panic(expected = "config.tuners[0].types: must be a non-empty list")]
-    fn test_tuner_config_validate_empty_types() {
+    #[should_panic(
+        expected = "config.tuners[0].health-check.probe-command: must be a valid command"
+    )]
+    fn test_tuner_config_validate_health_check_probe_command() {
         let mut config = tuner_config();
-        config.channel_types = vec![];
+        config.health_check.probe_command = "no-such-command".to_string();
         config.validate(0);
     }
 
     #[test]
-    #[should_panic(expected = "config.tuners[0].command: must be a non-empty string")]
-    fn test_tuner_config_validate_empty_command() {
+    #[should_panic(expected = "config.tuners[0].signal-level.command: must be a valid command")]
+    fn test_tuner_config_validate_signal_level_command() {
         let mut config = tuner_config();
-        config.command = "".to_string();
+        config.signal_level.command = "no-such-command".to_string();
         config.validate(0);
     }
 
     #[test]
-    #[should_panic(expected = "config.tuners[0].command: must be a valid command")]
-    fn test_tuner_config_validate_command() {
+    #[should_panic(
+        expected = "config.tuners[0].signal-level.interval: must be a non-zero duration"
+    )]
+    fn test_tuner_config_validate_signal_level_interval() {
         let mut config = tuner_config();
-        config.command = "no-such-command".to_string();
+        config.signal_level.command = "true".to_string();
+        config.signal_level.interval = Duration::ZERO;
         config.validate(0);
     }
 
+    #[test]
+    fn test_remote_tuner_config_urls() {
+        let config = remote_tuner_config();
+        assert_eq!(
+            config.tuners_url(),
+            Url::parse("http://upstream:40772/api/tuners").unwrap()
+        );
+        assert_eq!(
+            config.stream_url(ChannelType::BS, "BS15_0", false),
+            Url::parse("http://upstream:40772/api/channels/BS/BS15_0/stream?decode=0").unwrap()
+        );
+        assert_eq!(
+            config.stream_url(ChannelType::GR, "a b", true),
+            Url::parse("http://upstream:40772/api/channels/GR/a%20b/stream?decode=1").unwrap()
+        );
+    }
+
+    fn remote_tuner_config() -> RemoteTunerConfig {
+        RemoteTunerConfig {
+            url: Url::parse("http://upstream:40772/").unwrap(),
+            polling_interval: Duration::from_secs(10),
+            response_timeout: Duration::from_secs(10),
+        }
+    }
+
     #[test]
     fn test_tuner_config_validate_disabled() {
         let mut config = tuner_config();
@@ -3155,6 +4002,15 @@ mod tests {
                 ..Default::default()
             }
         );
+
+        assert_eq!(
+            serde_norway::from_str::<RecordingConfig>("start-margin: 1m\nend-margin: 30s").unwrap(),
+            RecordingConfig {
+                start_margin: Some(Duration::from_secs(60)),
+                end_margin: Some(Duration::from_secs(30)),
+                ..Default::default()
+            }
+        );
     }
 
     #[test]
@@ -3235,6 +4091,183 @@ mod tests {
         config.validate();
     }
 
+    #[test]
+    fn test_recording_retention_config() {
+        assert_eq!(
+            serde_norway::from_str::<RecordingRetentionConfig>("{}").unwrap(),
+            Default::default()
+        );
+        assert!(!RecordingRetentionConfig::default().is_enabled());
+
+        let config = serde_norway::from_str::<RecordingRetentionConfig>(
+            r#"
+            min-free-space: 1000
+            max-total-size: 2000
+            max-age: 30d
+            keep-newest-per-tag: 3
+            keep-newest-per-series: 4
+            interval: 5m
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            RecordingRetentionConfig {
+                min_free_space: Some(1000),
+                max_total_size: Some(2000),
+                max_age: Some(Duration::from_secs(30 * 24 * 3600)),
+                keep_newest_per_tag: Some(3),
+                keep_newest_per_series: Some(4),
+                interval: Duration::from_secs(300),
+            }
+        );
+        assert!(config.is_enabled());
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "config.recording.retention: requires config.recording.basedir and \
+                    config.recording.records-dir"
+    )]
+    fn test_recording_config_validate_retention_without_records_dir() {
+        let mut config = RecordingConfig::default();
+        config.basedir = Some("/tmp".into());
+        config.retention.max_age = Some(Duration::from_secs(3600));
+        config.validate();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "config.recording.retention.keep-newest-per-tag: must be larger than 0"
+    )]
+    fn test_recording_retention_config_validate_keep_newest_per_tag() {
+        let mut config = RecordingRetentionConfig::default();
+        config.keep_newest_per_tag = Some(0);
+        config.validate();
+    }
+
+    #[test]
+    fn test_recording_post_process_config() {
+        assert_eq!(
+            serde_norway::from_str::<RecordingPostProcessConfig>("{}").unwrap(),
+            Default::default()
+        );
+        assert!(!RecordingPostProcessConfig::default().is_enabled());
+
+        let config = serde_norway::from_str::<RecordingPostProcessConfig>(
+            r#"
+            concurrency: 2
+            jobs:
+              transcode:
+                command: echo {{{content_path}}}
+              notify:
+                command: echo {{{record_id}}}
+                run-on-failure: true
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            RecordingPostProcessConfig {
+                concurrency: 2,
+                jobs: indexmap! {
+                    "transcode".to_string() => RecordingPostProcessJobConfig {
+                        command: "echo {{{content_path}}}".to_string(),
+                        run_on_failure: false,
+                    },
+                    "notify".to_string() => RecordingPostProcessJobConfig {
+                        command: "echo {{{record_id}}}".to_string(),
+                        run_on_failure: true,
+                    },
+                },
+            }
+        );
+        assert!(config.is_enabled());
+        config.validate();
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "config.recording.post-process: requires config.recording.basedir and \
+                    config.recording.records-dir"
+    )]
+    fn test_recording_config_validate_post_process_without_records_dir() {
+        let mut config = RecordingConfig::default();
+        config.basedir = Some("/tmp".into());
+        config.post_process.jobs.insert(
+            "test".to_string(),
+            RecordingPostProcessJobConfig {
+                command: "true".to_string(),
+                run_on_failure: false,
+            },
+        );
+        config.validate();
+    }
+
+    #[test]
+    #[should_panic(expected = "config.recording.post-process.concurrency: must be larger than 0")]
+    fn test_recording_post_process_config_validate_concurrency() {
+        let mut config = RecordingPostProcessConfig::default();
+        config.concurrency = 0;
+        config.validate();
+    }
+
+    #[test]
+    #[should_panic(expected = "config.recording.post-process.jobs[a/b]: \
+                    name must consist only of alphanumeric characters, `-` and `_`")]
+    fn test_recording_post_process_job_config_validate_name() {
+        let config = RecordingPostProcessJobConfig {
+            command: "true".to_string(),
+            run_on_failure: false,
+        };
+        config.validate("a/b");
+    }
+
+    #[test]
+    fn test_recording_tuner_retry_config() {
+        assert_eq!(
+            serde_norway::from_str::<RecordingTunerRetryConfig>("{}").unwrap(),
+            Default::default()
+        );
+        assert_eq!(RecordingTunerRetryConfig::default().max_attempts, 3);
+
+        let config =
+            serde_norway::from_str::<RecordingTunerRetryConfig>("max-attempts: 1").unwrap();
+        assert_eq!(config, RecordingTunerRetryConfig { max_attempts: 1 });
+        config.validate();
+    }
+
+    #[test]
+    #[should_panic(expected = "config.recording.tuner-retry.max-attempts: must be larger than 0")]
+    fn test_recording_tuner_retry_config_validate_max_attempts() {
+        let config = RecordingTunerRetryConfig { max_attempts: 0 };
+        config.validate();
+    }
+
+    #[test]
+    fn test_recording_config_validate_margins() {
+        let mut config = RecordingConfig::default();
+        config.start_margin = Some(Duration::from_secs(3600));
+        config.end_margin = Some(Duration::from_secs(3600));
+        config.validate();
+    }
+
+    #[test]
+    #[should_panic(expected = "config.recording.start-margin: must be less than or equal to 1h")]
+    fn test_recording_config_validate_start_margin_too_long() {
+        let mut config = RecordingConfig::default();
+        config.start_margin = Some(Duration::from_secs(3601));
+        config.validate();
+    }
+
+    #[test]
+    #[should_panic(expected = "config.recording.end-margin: must be less than or equal to 1h")]
+    fn test_recording_config_validate_end_margin_too_long() {
+        let mut config = RecordingConfig::default();
+        config.end_margin = Some(Duration::from_secs(3601));
+        config.validate();
+    }
+
     #[test]
     fn test_timeshift_config() {
         assert_eq!(
@@ -3344,6 +4377,98 @@ mod tests {
         config.validate(true);
     }
 
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn test_timeshift_config_validate_shared_tuner() {
+        let (config1, _ts_file1, _data_file1) = timeshift_recorder_config_with_files();
+        let (mut config2, _ts_file2, _data_file2) = timeshift_recorder_config_with_files();
+        config2.service_id = 2.into();
+        let config = TimeshiftConfig {
+            recorders: indexmap! {
+                "test1".to_string() => config1,
+                "test2".to_string() => config2,
+            },
+            ..Default::default()
+        };
+        config.validate(false);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    #[should_panic(
+        expected = "config.timeshift.recorders[test2]: uses tuner[tuner] for a channel different from recorders[test1]"
+    )]
+    fn test_timeshift_config_validate_shared_tuner_different_channel() {
+        let (config1, _ts_file1, _data_file1) = timeshift_recorder_config_with_files();
+        let (mut config2, _ts_file2, _data_file2) = timeshift_recorder_config_with_files();
+        config2.uses.channel = "other".to_string();
+        let config = TimeshiftConfig {
+            recorders: indexmap! {
+                "test1".to_string() => config1,
+                "test2".to_string() => config2,
+            },
+            ..Default::default()
+        };
+        config.validate(false);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    #[should_panic(
+        expected = "config.timeshift.recorders[test2]: `ts-file` is already used in recorders[test1]"
+    )]
+    fn test_timeshift_config_validate_shared_ts_file() {
+        let (config1, _ts_file1, _data_file1) = timeshift_recorder_config_with_files();
+        let (mut config2, _ts_file2, _data_file2) = timeshift_recorder_config_with_files();
+        config2.ts_file = config1.ts_file.clone();
+        let config = TimeshiftConfig {
+            recorders: indexmap! {
+                "test1".to_string() => config1,
+                "test2".to_string() => config2,
+            },
+            ..Default::default()
+        };
+        config.validate(false);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    #[should_panic(
+        expected = "config.timeshift.recorders[test2]: `data-file` is already used in recorders[test1]"
+    )]
+    fn test_timeshift_config_validate_shared_data_file() {
+        let (config1, _ts_file1, _data_file1) = timeshift_recorder_config_with_files();
+        let (mut config2, _ts_file2, _data_file2) = timeshift_recorder_config_with_files();
+        config2.data_file = config1.data_file.clone();
+        let config = TimeshiftConfig {
+            recorders: indexmap! {
+                "test1".to_string() => config1,
+                "test2".to_string() => config2,
+            },
+            ..Default::default()
+        };
+        config.validate(false);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn timeshift_recorder_config_with_files()
+    -> (TimeshiftRecorderConfig, NamedTempFile, NamedTempFile) {
+        let chunk_size = TimeshiftRecorderConfig::BUFSIZE;
+        let num_chunks = 3;
+        let ts_file = NamedTempFile::new().unwrap();
+        let ts_file_size = (chunk_size * num_chunks) as libc::off64_t;
+        unsafe {
+            let _ = libc::fallocate64(ts_file.as_raw_fd(), 0, 0, ts_file_size);
+        }
+        let data_file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        let mut config = timeshift_recorder_config();
+        ts_file.path().clone_into(&mut config.ts_file);
+        data_file.path().clone_into(&mut config.data_file);
+        config.chunk_size = chunk_size;
+        config.num_chunks = num_chunks;
+        (config, ts_file, data_file)
+    }
+
     fn timeshift_recorder_config() -> TimeshiftRecorderConfig {
         TimeshiftRecorderConfig {
             service_id: 1.into(),
@@ -3358,6 +4483,7 @@ mod tests {
                 channel_type: ChannelType::GR,
                 channel: "ch".to_string(),
             },
+            pin: Default::default(),
         }
     }
 
@@ -3413,6 +4539,8 @@ mod tests {
                   tuner: x
                   channel-type: CS
                   channel: x
+                pin:
+                  series-ids: [1, 2]
             "#
             )
             .unwrap(),
@@ -3429,6 +4557,9 @@ mod tests {
                     channel_type: ChannelType::CS,
                     channel: "x".to_string(),
                 },
+                pin: TimeshiftRecorderPinConfig {
+                    series_ids: vec![1, 2],
+                },
             }
         );
     }
@@ -3499,7 +4630,9 @@ mod tests {
     }
 
     #[test]
-    #[should_panic(expected = "config.timeshift.recorders[test]: `data-file` must be a JSON file")]
+    #[should_panic(
+        expected = "config.timeshift.recorders[test]: `data-file` must be a JSON file or a journal file"
+    )]
     fn test_timeshift_recorder_config_validate_data_bincode() {
         let ts_file = NamedTempFile::new().unwrap();
         let data_file = tempfile::Builder::new()
@@ -3512,6 +4645,17 @@ mod tests {
         config.validate("test");
     }
 
+    #[test]
+    fn test_timeshift_recorder_config_validate_data_journal() {
+        let (mut config, _ts_file, _data_file) = timeshift_recorder_config_with_files();
+        let data_file = tempfile::Builder::new()
+            .suffix(".journal")
+            .tempfile()
+            .unwrap();
+        data_file.path().clone_into(&mut config.data_file);
+        config.validate("test");
+    }
+
     #[test]
     #[should_panic(
         expected = "config.timeshift.recorders[test]: `chunk-size` must be larger than 0"
@@ -3756,6 +4900,168 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tuner_reservations_config() {
+        assert_eq!(
+            serde_norway::from_str::<TunerReservationsConfig>("{}").unwrap(),
+            Default::default()
+        );
+        assert_eq!(
+            serde_norway::from_str::<TunerReservationsConfig>("basedir: /tmp").unwrap(),
+            TunerReservationsConfig {
+                basedir: Some("/tmp".into()),
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "config.tuner-reservations.basedir: must be an absolute path")]
+    fn test_tuner_reservations_config_validate_basedir_relative() {
+        let config = TunerReservationsConfig {
+            basedir: Some("relative/dir".into()),
+        };
+        config.validate();
+    }
+
+    #[test]
+    fn test_webhook_config() {
+        assert_eq!(
+            serde_norway::from_str::<WebhookConfig>("{}").unwrap(),
+            Default::default()
+        );
+        assert!(!WebhookConfig::default().is_enabled());
+
+        let config = serde_norway::from_str::<WebhookConfig>(
+            r#"
+            basedir: /tmp
+            timeout: 5s
+            max-attempts: 3
+            retry-interval: 1s
+            max-retry-interval: 1m
+            endpoints:
+              - url: http://localhost:8080/hook
+              - url: http://localhost:8081/hook
+                events:
+                  - recording.*
+                  - tuner.status-changed
+                secret: secret
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            WebhookConfig {
+                basedir: Some("/tmp".into()),
+                timeout: Duration::from_secs(5),
+                max_attempts: 3,
+                retry_interval: Duration::from_secs(1),
+                max_retry_interval: Duration::from_secs(60),
+                endpoints: vec![
+                    WebhookEndpointConfig {
+                        url: Url::parse("http://localhost:8080/hook").unwrap(),
+                        events: vec![],
+                        secret: None,
+                    },
+                    WebhookEndpointConfig {
+                        url: Url::parse("http://localhost:8081/hook").unwrap(),
+                        events: vec![
+                            "recording.*".to_string(),
+                            "tuner.status-changed".to_string(),
+                        ],
+                        secret: Some("secret".to_string()),
+                    },
+                ],
+            }
+        );
+        assert!(config.is_enabled());
+        config.validate();
+    }
+
+    #[test]
+    #[should_panic(expected = "config.webhook.max-attempts: must be larger than 0")]
+    fn test_webhook_config_validate_max_attempts() {
+        let mut config = WebhookConfig::default();
+        config.max_attempts = 0;
+        config.validate();
+    }
+
+    #[test]
+    #[should_panic(expected = "config.webhook.max-retry-interval: \
+                               must be larger than or equal to config.webhook.retry-interval")]
+    fn test_webhook_config_validate_max_retry_interval() {
+        let mut config = WebhookConfig::default();
+        config.retry_interval = Duration::from_secs(10);
+        config.max_retry_interval = Duration::from_secs(9);
+        config.validate();
+    }
+
+    #[test]
+    fn test_webhook_endpoint_config_subscribes() {
+        let mut config = WebhookEndpointConfig {
+            url: Url::parse("http://localhost/").unwrap(),
+            events: vec![],
+            secret: None,
+        };
+        assert!(config.subscribes("recording.started"));
+        assert!(config.subscribes("tuner.status-changed"));
+
+        config.events = vec![
+            "recording.*".to_string(),
+            "tuner.status-changed".to_string(),
+        ];
+        assert!(config.subscribes("recording.started"));
+        assert!(config.subscribes("recording.record-saved"));
+        assert!(config.subscribes("tuner.status-changed"));
+        assert!(!config.subscribes("timeshift.started"));
+        assert!(!config.subscribes("recordingx.started"));
+    }
+
+    #[test]
+    fn test_webhook_endpoint_config_validate_timeshift_gap() {
+        let config = WebhookEndpointConfig {
+            url: Url::parse("http://localhost/").unwrap(),
+            events: vec!["timeshift.gap".to_string()],
+            secret: None,
+        };
+        config.validate(0);
+        assert!(config.subscribes("timeshift.gap"));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "config.webhook.endpoints[0].events: unknown event recording.unknown"
+    )]
+    fn test_webhook_endpoint_config_validate_unknown_event() {
+        let config = WebhookEndpointConfig {
+            url: Url::parse("http://localhost/").unwrap(),
+            events: vec!["recording.unknown".to_string()],
+            secret: None,
+        };
+        config.validate(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "config.webhook.endpoints[0].events: unknown event unknown.*")]
+    fn test_webhook_endpoint_config_validate_unknown_category() {
+        let config = WebhookEndpointConfig {
+            url: Url::parse("http://localhost/").unwrap(),
+            events: vec!["unknown.*".to_string()],
+            secret: None,
+        };
+        config.validate(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "config.webhook.endpoints[0].url: must be a HTTP URL")]
+    fn test_webhook_endpoint_config_validate_url() {
+        let config = WebhookEndpointConfig {
+            url: Url::parse("ftp://localhost/").unwrap(),
+            events: vec![],
+            secret: None,
+        };
+        config.validate(0);
+    }
+
     #[test]
     fn test_resource_config() {
         assert_eq!(
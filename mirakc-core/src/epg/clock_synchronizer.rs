@@ -95,6 +95,7 @@ where
                 channel: channel.clone().into(),
                 user,
                 stream_id: None,
+                excluded_tuners: vec![],
             })
             .await??;
 
@@ -199,6 +199,7 @@ where
                 channel: channel.clone(),
                 user,
                 stream_id: None,
+                excluded_tuners: vec![],
             })
             .await??;
 
@@ -94,6 +94,7 @@ where
                 channel: channel.clone().into(),
                 user,
                 stream_id: None,
+                excluded_tuners: vec![],
             })
             .await??;
 
@@ -20,14 +20,24 @@ pub enum Error {
     RecordNotFound,
     #[error("Session not found")]
     SessionNotFound,
+    #[error("Reservation not found")]
+    ReservationNotFound,
     #[error("Schedule not found")]
     ScheduleNotFound,
     #[error("Recorder not found")]
     RecorderNotFound,
+    #[error("Rule not found")]
+    RuleNotFound,
+    #[error("Recurring schedule not found")]
+    RecurringScheduleNotFound,
+    #[error("Post-process job not found")]
+    PostProcessJobNotFound,
     #[error("Out of range")]
     OutOfRange,
     #[error("No content")]
     NoContent,
+    #[error("No space")]
+    NoSpace,
     #[error("No logo data")]
     NoLogoData,
     #[error("Access denied")]
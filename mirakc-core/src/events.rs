@@ -2,21 +2,42 @@ use chrono::DateTime;
 use chrono::Duration;
 use chrono_jst::Jst;
 use chrono_jst::serde::duration_milliseconds;
+use chrono_jst::serde::ts_milliseconds;
 use chrono_jst::serde::ts_milliseconds_option;
 use serde::Deserialize;
 use serde::Serialize;
+use serde_json::Value;
 
+use crate::models::MirakurunTunerUser;
 use crate::models::ProgramId;
 use crate::models::ServiceId;
 use crate::models::TimeshiftRecordId;
+use crate::models::TunerHealth;
+use crate::models::TunerReservation;
 use crate::recording::RecordId;
 use crate::recording::RecordingFailedReason;
+use crate::timeshift::TimeshiftGapReason;
 use crate::web::api::models::WebRecordingStatus;
 
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TunerStatusChanged {
     pub tuner_index: usize,
+    pub health: TunerHealth,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TunerReservationsChanged {
+    pub reservations: Vec<TunerReservation>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TunerPreempted {
+    pub tuner_index: usize,
+    pub user: MirakurunTunerUser,
+    pub replaced_by: MirakurunTunerUser,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -121,8 +142,283 @@ pub struct TimeshiftRecordEnded {
     pub record_id: TimeshiftRecordId,
 }
 
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeshiftGap {
+    pub recorder: String,
+    #[serde(with = "ts_milliseconds")]
+    pub start_time: DateTime<Jst>,
+    #[serde(with = "ts_milliseconds")]
+    pub end_time: DateTime<Jst>,
+    pub reason: TimeshiftGapReason,
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OnairProgramChanged {
     pub service_id: ServiceId,
 }
+
+/// An event with its name.
+///
+/// Events are sent to SSE clients and webhook endpoints in this form.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EventMessage {
+    pub name: String,
+    pub data: Value,
+}
+
+impl EventMessage {
+    /// Names of all events.
+    pub const NAMES: &'static [&'static str] = &[
+        "tuner.status-changed",
+        "tuner.reservations-changed",
+        "tuner.preempted",
+        "epg.programs-updated",
+        "recording.started",
+        "recording.stopped",
+        "recording.failed",
+        "recording.rescheduled",
+        "recording.record-saved",
+        "recording.record-broken",
+        "recording.record-removed",
+        "recording.content-removed",
+        "timeshift.timeline",
+        "timeshift.started",
+        "timeshift.stopped",
+        "timeshift.record-started",
+        "timeshift.record-updated",
+        "timeshift.record-ended",
+        "timeshift.gap",
+        "onair.program-changed",
+    ];
+
+    fn new<T: Serialize>(name: &str, data: T) -> Self {
+        EventMessage {
+            name: name.to_string(),
+            data: serde_json::to_value(data).unwrap(),
+        }
+    }
+}
+
+// tuner events
+
+impl From<crate::tuner::Event> for EventMessage {
+    fn from(val: crate::tuner::Event) -> Self {
+        match val {
+            crate::tuner::Event::StatusChanged(tuner_index, health) => EventMessage::new(
+                "tuner.status-changed",
+                TunerStatusChanged {
+                    tuner_index,
+                    health,
+                },
+            ),
+            crate::tuner::Event::ReservationsChanged(reservations) => EventMessage::new(
+                "tuner.reservations-changed",
+                TunerReservationsChanged { reservations },
+            ),
+            crate::tuner::Event::Preempted {
+                tuner_index,
+                user,
+                replaced_by,
+            } => EventMessage::new(
+                "tuner.preempted",
+                TunerPreempted {
+                    tuner_index,
+                    user: user.get_mirakurun_model(),
+                    replaced_by: replaced_by.get_mirakurun_model(),
+                },
+            ),
+        }
+    }
+}
+
+// epg events
+
+impl From<crate::epg::ProgramsUpdated> for EventMessage {
+    fn from(val: crate::epg::ProgramsUpdated) -> Self {
+        EventMessage::new(
+            "epg.programs-updated",
+            EpgProgramsUpdated {
+                service_id: val.service_id,
+            },
+        )
+    }
+}
+
+// recording events
+
+impl From<crate::recording::RecordingStarted> for EventMessage {
+    fn from(val: crate::recording::RecordingStarted) -> Self {
+        EventMessage::new(
+            "recording.started",
+            RecordingStarted {
+                program_id: val.program_id,
+            },
+        )
+    }
+}
+
+impl From<crate::recording::RecordingStopped> for EventMessage {
+    fn from(val: crate::recording::RecordingStopped) -> Self {
+        EventMessage::new(
+            "recording.stopped",
+            RecordingStopped {
+                program_id: val.program_id,
+            },
+        )
+    }
+}
+
+impl From<crate::recording::RecordingFailed> for EventMessage {
+    fn from(val: crate::recording::RecordingFailed) -> Self {
+        EventMessage::new(
+            "recording.failed",
+            RecordingFailed {
+                program_id: val.program_id,
+                reason: val.reason,
+            },
+        )
+    }
+}
+
+impl From<crate::recording::RecordingRescheduled> for EventMessage {
+    fn from(val: crate::recording::RecordingRescheduled) -> Self {
+        EventMessage::new(
+            "recording.rescheduled",
+            RecordingRescheduled {
+                program_id: val.program_id,
+            },
+        )
+    }
+}
+
+// record events
+
+impl From<crate::recording::RecordSaved> for EventMessage {
+    fn from(val: crate::recording::RecordSaved) -> Self {
+        EventMessage::new(
+            "recording.record-saved",
+            RecordSaved {
+                record_id: val.record_id,
+                recording_status: val.recording_status.into(),
+            },
+        )
+    }
+}
+
+impl From<crate::recording::RecordBroken> for EventMessage {
+    fn from(val: crate::recording::RecordBroken) -> Self {
+        EventMessage::new(
+            "recording.record-broken",
+            RecordBroken {
+                record_id: val.record_id,
+                reason: val.reason,
+            },
+        )
+    }
+}
+
+impl From<crate::recording::RecordRemoved> for EventMessage {
+    fn from(val: crate::recording::RecordRemoved) -> Self {
+        EventMessage::new(
+            "recording.record-removed",
+            RecordRemoved {
+                record_id: val.record_id,
+            },
+        )
+    }
+}
+
+impl From<crate::recording::ContentRemoved> for EventMessage {
+    fn from(val: crate::recording::ContentRemoved) -> Self {
+        EventMessage::new(
+            "recording.content-removed",
+            ContentRemoved {
+                record_id: val.record_id,
+            },
+        )
+    }
+}
+
+// timeshift events
+
+impl From<crate::timeshift::TimeshiftEvent> for EventMessage {
+    fn from(val: crate::timeshift::TimeshiftEvent) -> Self {
+        match val {
+            crate::timeshift::TimeshiftEvent::Timeline {
+                recorder,
+                start_time,
+                end_time,
+                duration,
+            } => EventMessage::new(
+                "timeshift.timeline",
+                TimeshiftTimeline {
+                    recorder,
+                    start_time,
+                    end_time,
+                    duration,
+                },
+            ),
+            crate::timeshift::TimeshiftEvent::Started { recorder } => {
+                EventMessage::new("timeshift.started", TimeshiftStarted { recorder })
+            }
+            crate::timeshift::TimeshiftEvent::Stopped { recorder } => {
+                EventMessage::new("timeshift.stopped", TimeshiftStopped { recorder })
+            }
+            crate::timeshift::TimeshiftEvent::RecordStarted {
+                recorder,
+                record_id,
+            } => EventMessage::new(
+                "timeshift.record-started",
+                TimeshiftRecordStarted {
+                    recorder,
+                    record_id,
+                },
+            ),
+            crate::timeshift::TimeshiftEvent::RecordUpdated {
+                recorder,
+                record_id,
+            } => EventMessage::new(
+                "timeshift.record-updated",
+                TimeshiftRecordUpdated {
+                    recorder,
+                    record_id,
+                },
+            ),
+            crate::timeshift::TimeshiftEvent::RecordEnded {
+                recorder,
+                record_id,
+            } => EventMessage::new(
+                "timeshift.record-ended",
+                TimeshiftRecordEnded {
+                    recorder,
+                    record_id,
+                },
+            ),
+            crate::timeshift::TimeshiftEvent::Gap { recorder, gap } => EventMessage::new(
+                "timeshift.gap",
+                TimeshiftGap {
+                    recorder,
+                    start_time: gap.start_time,
+                    // The event is emitted after the gap ended.
+                    end_time: gap.end_time.unwrap_or_else(Jst::now),
+                    reason: gap.reason,
+                },
+            ),
+        }
+    }
+}
+
+// on-air events
+
+impl From<crate::onair::OnairProgramChanged> for EventMessage {
+    fn from(val: crate::onair::OnairProgramChanged) -> Self {
+        EventMessage::new(
+            "onair.program-changed",
+            OnairProgramChanged {
+                service_id: val.service_id,
+            },
+        )
+    }
+}
@@ -82,6 +82,28 @@ where
     true
 }
 
+// Returns the number of bytes available to unprivileged users on the file
+// system containing the path.
+pub fn available_space<P>(path: P) -> std::io::Result<u64>
+where
+    P: AsRef<Path>,
+{
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_ref().as_os_str().as_bytes())?;
+    // SAFETY: `path` is a valid NUL-terminated string and `stat` is a valid
+    // pointer to a `libc::statvfs` struct.
+    let stat = unsafe {
+        let mut stat = std::mem::MaybeUninit::<libc::statvfs>::uninit();
+        if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        stat.assume_init()
+    };
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
 fn append_extension<P, S>(path: P, ext: S) -> PathBuf
 where
     P: AsRef<Path>,
@@ -152,6 +174,13 @@ mod tests {
         // TODO: need allowing write, but preventing rename
     }
 
+    #[test]
+    fn test_available_space() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_matches!(available_space(temp_dir.path()), Ok(_));
+        assert_matches!(available_space("/no/such/dir"), Err(_));
+    }
+
     #[test]
     fn test_append_extension() {
         assert_eq!(append_extension("foobar", "baz"), Path::new("foobar.baz"));
@@ -5,6 +5,10 @@ use crate::config::PostFilterConfig;
 use crate::config::PreFilterConfig;
 use crate::error::Error;
 
+// The program filter keeps streaming for a while after the end of a TV program
+// in order to avoid losing the last part of the TV program.
+pub const PROGRAM_FILTER_END_MARGIN_MS: i64 = 2000;
+
 pub struct FilterPipelineBuilder {
     data: mustache::Data,
     filters: Vec<String>,
@@ -18,5 +18,6 @@ pub mod timeshift;
 pub mod tracing_ext;
 pub mod tuner;
 pub mod web;
+pub mod webhook;
 
 mod events;
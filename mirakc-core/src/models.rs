@@ -5,6 +5,7 @@ use chrono::DateTime;
 use chrono::Duration;
 use chrono_jst::jst::Jst;
 use chrono_jst::serde::duration_milliseconds_option;
+use chrono_jst::serde::ts_milliseconds;
 use chrono_jst::serde::ts_milliseconds_option;
 use indexmap::IndexMap;
 use serde::Deserialize;
@@ -187,6 +188,10 @@ pub struct ProgramId(u64);
 impl ProgramId {
     const MAGIC_NUMBER: u64 = 100_000;
 
+    // EIDs are 16-bit integers.  So, values in this range never collide with program IDs of TV
+    // programs in EIT.  They are used for TV programs defined by users.
+    const USER_DEFINED_EID_RANGE: std::ops::Range<u64> = 0x1_0000..Self::MAGIC_NUMBER;
+
     pub fn new(nid: Nid, sid: Sid, eid: Eid) -> Self {
         // An unique identifier compatible with Mirakurun.
         // See src/Mirakurun/ProgramItem.ts#L28 in Chinachu/Mirakurun.
@@ -212,6 +217,26 @@ impl ProgramId {
     pub fn eid(&self) -> Eid {
         Eid::from((self.value() % Self::MAGIC_NUMBER) as u16)
     }
+
+    /// Creates a program ID for a TV program defined by users.
+    ///
+    /// `None` is returned if the index is out of range.
+    pub fn new_user_defined(service_id: ServiceId, index: u64) -> Option<Self> {
+        let eid_part = Self::USER_DEFINED_EID_RANGE.start.checked_add(index)?;
+        if !Self::USER_DEFINED_EID_RANGE.contains(&eid_part) {
+            return None;
+        }
+        Some(ProgramId(
+            service_id.value() * Self::MAGIC_NUMBER + eid_part,
+        ))
+    }
+
+    /// Returns `true` if the program ID is for a TV program defined by users.
+    ///
+    /// `eid()` doesn't return a meaningful value for such a program ID.
+    pub fn is_user_defined(&self) -> bool {
+        Self::USER_DEFINED_EID_RANGE.contains(&(self.value() % Self::MAGIC_NUMBER))
+    }
 }
 
 impl fmt::Display for ProgramId {
@@ -438,10 +463,10 @@ pub struct MirakurunTuner {
     #[schema(inline)]
     pub users: Vec<MirakurunTunerUser>,
 
-    /// Always `true`.
+    /// `false` if the upstream server of a remote tuner is unavailable, `true` otherwise.
     pub is_available: bool,
 
-    /// Always `false`.
+    /// `true` if the tuner is a remote tuner, `false` otherwise.
     pub is_remote: bool,
 
     /// `true` if the tuner is free, `false` otherwise.
@@ -450,11 +475,135 @@ pub struct MirakurunTuner {
     /// `false` if the tuner is free, `true` otherwise.
     pub is_using: bool,
 
-    /// Always `false`.
+    /// `true` if the tuner is quarantined, `false` otherwise.
     pub is_fault: bool,
+
+    /// The health state of the tuner.
+    pub health: TunerHealth,
+
+    /// Statistics of the current session of the tuner.
+    ///
+    /// Included only in `/api/tuners/{index}` while the tuner is active.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<TunerSessionStats>,
 }
 
-#[derive(Debug, Serialize, ToSchema)]
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(title = "TunerHealth")]
+pub struct TunerHealth {
+    /// The number of consecutive failures.
+    pub consecutive_failures: u32,
+
+    /// The number of times that no TS packet came from the tuner within the time limit.
+    pub no_data_timeouts: u32,
+
+    /// The last error.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+
+    /// The time when the quarantine ends in UNIX time milliseconds.
+    ///
+    /// `null` if the tuner is not quarantined.
+    #[serde(with = "ts_milliseconds_option")]
+    #[schema(value_type = Option<i64>)]
+    pub quarantined_until: Option<DateTime<Jst>>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(title = "TunerSessionStats")]
+pub struct TunerSessionStats {
+    /// The session ID.
+    pub session_id: String,
+
+    /// The time when the session started in UNIX time milliseconds.
+    #[serde(with = "ts_milliseconds")]
+    #[schema(value_type = i64)]
+    pub start_time: DateTime<Jst>,
+
+    /// The number of bytes received from the tuner.
+    pub received_bytes: u64,
+
+    /// The bitrate in bytes per second measured in the last second.
+    pub bytes_per_sec: u64,
+
+    /// The number of TS packets received from the tuner.
+    pub packets: u64,
+
+    /// The number of TS packets dropped, which is detected by continuity counter errors.
+    pub drop_packets: u64,
+
+    /// The number of TS packets having the transport error indicator.
+    pub error_packets: u64,
+
+    /// The signal level reported by `signal-level.command`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signal_level: Option<f64>,
+
+    /// Statistics of subscribers.
+    #[schema(inline)]
+    pub subscribers: Vec<TunerSubscriberStats>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TunerSubscriberStats {
+    /// The subscription ID.
+    pub id: String,
+
+    /// The number of chunks dropped because the subscriber was stuck.
+    pub dropped_chunks: u64,
+
+    /// The number of bytes dropped because the subscriber was stuck.
+    pub dropped_bytes: u64,
+}
+
+/// A reservation of a tuner for a time range.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(title = "TunerReservation")]
+pub struct TunerReservation {
+    /// The reservation ID.
+    pub id: u64,
+
+    /// The index of the reserved tuner.
+    pub tuner_index: usize,
+
+    /// The ID of the user of the reserved tuner.
+    ///
+    /// The same as the `id` property of a tuner user.  For example, the value of the
+    /// `X-Mirakurun-Tuner-User-Id` header for web clients.
+    pub user_id: String,
+
+    /// The priority of the reservation.
+    ///
+    /// Other users can use the reserved tuner only if their priority is higher than this.
+    #[schema(value_type = i32)]
+    pub priority: TunerUserPriority,
+
+    /// The start time of the reservation in UNIX time (milliseconds).
+    #[serde(with = "ts_milliseconds")]
+    #[schema(value_type = i64)]
+    pub start_at: DateTime<Jst>,
+
+    /// The end time of the reservation in UNIX time (milliseconds).
+    #[serde(with = "ts_milliseconds")]
+    #[schema(value_type = i64)]
+    pub end_at: DateTime<Jst>,
+}
+
+impl TunerReservation {
+    pub fn is_active_at(&self, time: DateTime<Jst>) -> bool {
+        self.start_at <= time && time < self.end_at
+    }
+
+    pub fn overlaps(&self, start_at: DateTime<Jst>, end_at: DateTime<Jst>) -> bool {
+        self.start_at < end_at && start_at < self.end_at
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct MirakurunTunerUser {
     /// User ID.
@@ -968,6 +1117,25 @@ mod tests {
             ProgramId::new(u16::MAX.into(), u16::MAX.into(), u16::MAX.into()).value()
                 <= JS_NUMBER_MAX_SAFE_INTEGER
         );
+        assert!(!id.is_user_defined());
+        assert!(!ProgramId::new(nid, sid, u16::MAX.into()).is_user_defined());
+    }
+
+    #[test]
+    fn test_program_id_user_defined() {
+        let service_id = ServiceId::new(1.into(), 2.into());
+
+        let id = ProgramId::new_user_defined(service_id, 0).unwrap();
+        assert_eq!(ProgramId(1_00002_65536), id);
+        assert_eq!(service_id, ServiceId::from(id));
+        assert!(id.is_user_defined());
+
+        let id = ProgramId::new_user_defined(service_id, 34463).unwrap();
+        assert_eq!(ProgramId(1_00002_99999), id);
+        assert!(id.is_user_defined());
+
+        assert!(ProgramId::new_user_defined(service_id, 34464).is_none());
+        assert!(ProgramId::new_user_defined(service_id, u64::MAX).is_none());
     }
 
     // NOTE
@@ -8,6 +8,8 @@ use tokio::io::AsyncWriteExt;
 use tokio_stream::Stream;
 use tokio_stream::StreamExt;
 
+pub const TS_PACKET_SIZE: u64 = 188;
+
 #[cfg_attr(test, derive(Debug))]
 pub struct MpegTsStream<T, S> {
     id: T,
@@ -208,6 +208,7 @@ where
                 channel: service.channel.clone(),
                 user,
                 stream_id: self.config.stream_id,
+                excluded_tuners: vec![],
             })
             .await??;
 
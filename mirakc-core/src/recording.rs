@@ -1,16 +1,20 @@
 use std::collections::BinaryHeap;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
 use std::path::Path;
 use std::path::PathBuf;
 use std::pin::Pin;
 use std::process::ExitStatus;
+use std::process::Stdio;
+use std::str::FromStr;
 use std::sync::Arc;
 
 use actlet::prelude::*;
 use bytes::Bytes;
 use chrono::DateTime;
 use chrono::Duration;
+use chrono::NaiveTime;
 use chrono_jst::Jst;
 use chrono_jst::serde::duration_milliseconds_option;
 use chrono_jst::serde::ts_milliseconds;
@@ -18,36 +22,54 @@ use chrono_jst::serde::ts_milliseconds_option;
 use indexmap::IndexMap;
 use itertools::Itertools;
 use path_dedot::ParseDot;
+use regex::Regex;
 use serde::Deserialize;
 use serde::Serialize;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
 use tokio::io::BufWriter;
 use tokio_stream::Stream;
 use tokio_stream::StreamExt;
 use tokio_util::io::ReaderStream;
+use tokio_util::io::StreamReader;
 use tokio_util::sync::CancellationToken;
 use utoipa::ToSchema;
 
+use crate::command_util;
 use crate::command_util::CommandPipeline;
 use crate::command_util::CommandPipelineBuilder;
 use crate::command_util::CommandPipelineProcessModel;
 use crate::command_util::spawn_pipeline;
 use crate::config::Config;
+use crate::config::OnairProgramTrackerConfig;
+use crate::config::RecordingConfig;
+use crate::config::RecordingRetentionConfig;
+use crate::config::TunerConfig;
 use crate::epg;
+use crate::epg::EpgChannel;
 use crate::epg::EpgProgram;
 use crate::epg::EpgService;
 use crate::epg::QueryClock;
 use crate::epg::QueryPrograms;
 use crate::epg::QueryService;
+use crate::epg::QueryServices;
 use crate::error::Error;
 use crate::file_util;
 use crate::filter::FilterPipelineBuilder;
+use crate::filter::PROGRAM_FILTER_END_MARGIN_MS;
+use crate::models::ChannelType;
 use crate::models::ContentRange;
 use crate::models::ProgramId;
 use crate::models::ServiceId;
+use crate::models::TunerReservation;
 use crate::models::TunerUser;
 use crate::models::TunerUserInfo;
+use crate::models::TunerUserPriority;
 use crate::mpeg_ts_stream::MpegTsStream;
+use crate::mpeg_ts_stream::TS_PACKET_SIZE;
 use crate::onair;
+use crate::timeshift::TimeshiftRecordExport;
+use crate::tuner;
 use crate::tuner::StartStreaming;
 use crate::tuner::StopStreaming;
 use crate::tuner::TunerSubscriptionId;
@@ -58,6 +80,14 @@ const EXIT_RETRY: i32 = 222;
 // compile-time constants.
 const PREP_SECS: i64 = 15;
 const MAX_DELAY_HOURS: i64 = 15;
+// Recurring schedules are expanded into recording schedules within this period.
+const RECURRING_SCHEDULE_LOOKAHEAD_DAYS: i64 = 3;
+// Recurring schedules are also expanded at this interval because EPG of some services is never
+// updated.
+const RECURRING_SCHEDULE_EXPANSION_INTERVAL: std::time::Duration =
+    std::time::Duration::from_secs(60 * 60);
+// Points are added to the content index of a record at this interval.
+const CONTENT_INDEX_INTERVAL_SECS: i64 = 10;
 
 pub struct RecordingManager<T, E, O> {
     config: Arc<Config>,
@@ -75,7 +105,14 @@ pub struct RecordingManager<T, E, O> {
     queue: BinaryHeap<QueueItem>,
     schedules: HashMap<ProgramId, RecordingSchedule>,
     recorders: HashMap<ProgramId, Recorder>,
+    rules: IndexMap<RecordingRuleId, RecordingRule>,
+    recurring_schedules: IndexMap<RecurringScheduleId, RecurringSchedule>,
+    series_followers: IndexMap<(ServiceId, u16), SeriesFollower>,
     timer_token: Option<CancellationToken>,
+    post_process_queue: VecDeque<(RecordId, String)>,
+    num_running_post_processes: usize,
+    // Used for detecting conflicts.
+    tuner_reservations: Vec<TunerReservation>,
 
     recording_started: EmitterRegistry<RecordingStarted>,
     recording_stopped: EmitterRegistry<RecordingStopped>,
@@ -97,7 +134,13 @@ impl<T, E, O> RecordingManager<T, E, O> {
             queue: Default::default(),
             schedules: Default::default(),
             recorders: Default::default(),
+            rules: Default::default(),
+            recurring_schedules: Default::default(),
+            series_followers: Default::default(),
             timer_token: None,
+            post_process_queue: Default::default(),
+            num_running_post_processes: 0,
+            tuner_reservations: Default::default(),
             recording_started: Default::default(),
             recording_stopped: Default::default(),
             recording_failed: Default::default(),
@@ -159,6 +202,146 @@ impl<T, E, O> RecordingManager<T, E, O> {
         } else {
             tracing::error!("Failed to save schedules");
         }
+
+        // Series followers are updated together with schedules.
+        self.save_series_followers();
+    }
+
+    fn load_rules(&mut self) {
+        fn do_load(path: &Path) -> Result<Vec<RecordingRule>, Error> {
+            let file = std::fs::File::open(path)?;
+            Ok(serde_json::from_reader(file)?)
+        }
+
+        let path = match make_rules_path(&self.config) {
+            Some(path) if path.exists() => path,
+            _ => return,
+        };
+
+        match do_load(&path) {
+            Ok(rules) => {
+                tracing::info!(?path, "Loaded");
+                for rule in rules.into_iter() {
+                    if self.rules.contains_key(&rule.id) {
+                        // This may happen when user changes rules.json by hand.
+                        tracing::warn!(%rule.id, "Already added, ignore");
+                        continue;
+                    }
+                    self.rules.insert(rule.id, rule);
+                }
+                self.rules.sort_unstable_keys();
+            }
+            Err(err) => {
+                tracing::warn!(%err, ?path, "Failed to load");
+            }
+        }
+    }
+
+    fn save_rules(&self) {
+        let path = match make_rules_path(&self.config) {
+            Some(path) => path,
+            None => return,
+        };
+
+        let rules = self.rules.values().collect_vec();
+        if file_util::save_json(&rules, &path) {
+            tracing::info!(rules.len = rules.len(), "Saved rules");
+        } else {
+            tracing::error!("Failed to save rules");
+        }
+    }
+
+    fn load_recurring_schedules(&mut self) {
+        fn do_load(path: &Path) -> Result<Vec<RecurringSchedule>, Error> {
+            let file = std::fs::File::open(path)?;
+            Ok(serde_json::from_reader(file)?)
+        }
+
+        let path = match make_recurring_schedules_path(&self.config) {
+            Some(path) if path.exists() => path,
+            _ => return,
+        };
+
+        match do_load(&path) {
+            Ok(recurring_schedules) => {
+                tracing::info!(?path, "Loaded");
+                for recurring_schedule in recurring_schedules.into_iter() {
+                    if self
+                        .recurring_schedules
+                        .contains_key(&recurring_schedule.id)
+                    {
+                        // This may happen when user changes recurring-schedules.json by hand.
+                        tracing::warn!(%recurring_schedule.id, "Already added, ignore");
+                        continue;
+                    }
+                    self.recurring_schedules
+                        .insert(recurring_schedule.id, recurring_schedule);
+                }
+                self.recurring_schedules.sort_unstable_keys();
+            }
+            Err(err) => {
+                tracing::warn!(%err, ?path, "Failed to load");
+            }
+        }
+    }
+
+    fn save_recurring_schedules(&self) {
+        let path = match make_recurring_schedules_path(&self.config) {
+            Some(path) => path,
+            None => return,
+        };
+
+        let recurring_schedules = self.recurring_schedules.values().collect_vec();
+        if file_util::save_json(&recurring_schedules, &path) {
+            tracing::info!(
+                recurring_schedules.len = recurring_schedules.len(),
+                "Saved recurring schedules"
+            );
+        } else {
+            tracing::error!("Failed to save recurring schedules");
+        }
+    }
+
+    fn load_series_followers(&mut self) {
+        fn do_load(path: &Path) -> Result<Vec<SeriesFollower>, Error> {
+            let file = std::fs::File::open(path)?;
+            Ok(serde_json::from_reader(file)?)
+        }
+
+        let path = match make_series_followers_path(&self.config) {
+            Some(path) if path.exists() => path,
+            _ => return,
+        };
+
+        match do_load(&path) {
+            Ok(followers) => {
+                tracing::info!(?path, "Loaded");
+                for follower in followers.into_iter() {
+                    self.series_followers
+                        .insert((follower.service.id, follower.series_id), follower);
+                }
+            }
+            Err(err) => {
+                tracing::warn!(%err, ?path, "Failed to load");
+            }
+        }
+    }
+
+    fn save_series_followers(&self) {
+        let path = match make_series_followers_path(&self.config) {
+            Some(path) => path,
+            None => return,
+        };
+
+        let followers = self.series_followers.values().collect_vec();
+        if file_util::save_json(&followers, &path) {
+            tracing::debug!(
+                series_followers.len = followers.len(),
+                "Saved series followers"
+            );
+        } else {
+            tracing::error!("Failed to save series followers");
+        }
     }
 
     fn rebuild_queue(&mut self) {
@@ -170,10 +353,32 @@ impl<T, E, O> RecordingManager<T, E, O> {
         for schedule in schedules {
             self.queue.push(QueueItem {
                 program_id: schedule.program.id,
-                start_at: schedule.program.start_at.unwrap(),
+                start_at: schedule
+                    .recording_start_time(&self.config.recording)
+                    .unwrap(),
                 priority: schedule.options.priority,
             });
         }
+        self.update_conflicts();
+    }
+
+    fn update_conflicts(&mut self) {
+        let mut conflicts = detect_conflicts(
+            &self.config,
+            self.schedules.values(),
+            &self.tuner_reservations,
+        );
+        for schedule in self.schedules.values_mut() {
+            let conflict = conflicts.remove(&schedule.program.id);
+            if conflict.is_some() && schedule.conflict.is_none() {
+                tracing::warn!(
+                    %schedule.program.id,
+                    ?conflict,
+                    "No tuner will be available for the schedule",
+                );
+            }
+            schedule.conflict = conflict;
+        }
     }
 
     fn set_timer<C>(&mut self, ctx: &C)
@@ -228,7 +433,9 @@ impl<T, E, O> RecordingManager<T, E, O> {
 
     // Update record aggressively.
     // If the record has been broken, it will be overwritten if possible.
-    async fn update_record(&self, program_id: ProgramId) {
+    //
+    // Returns the record if it has been saved successfully.
+    async fn update_record(&self, program_id: ProgramId) -> Option<Record> {
         let recorder = match self.recorders.get(&program_id) {
             Some(recorder) => recorder,
             None => {
@@ -236,16 +443,33 @@ impl<T, E, O> RecordingManager<T, E, O> {
                     schedule.program.id = %program_id,
                     "INCONSNSTENT: The recorder stopped before the recording stopped",
                 );
-                return;
+                return None;
             }
         };
+        self.update_record_started_at(
+            program_id,
+            recorder.started_at,
+            Some(&recorder.content_type),
+        )
+        .await
+    }
 
-        let record_id = RecordId::from((recorder.started_at, program_id));
+    // Same as `update_record()` except that the record is specified by the
+    // start time of the recording.
+    //
+    // A broken record cannot be overwritten if `content_type` is `None`.
+    async fn update_record_started_at(
+        &self,
+        program_id: ProgramId,
+        started_at: DateTime<Jst>,
+        content_type: Option<&str>,
+    ) -> Option<Record> {
+        let record_id = RecordId::from((started_at, program_id));
         let record_path = match make_record_path(&self.config, &record_id) {
             Some(record_path) => record_path,
             None => {
                 // The records feature is disabled.
-                return;
+                return None;
             }
         };
 
@@ -281,8 +505,8 @@ impl<T, E, O> RecordingManager<T, E, O> {
                 }
                 record
             }
-            Err(err) => match self.schedules.get(&program_id) {
-                Some(schedule) => {
+            Err(err) => match (self.schedules.get(&program_id), content_type) {
+                (Some(schedule), Some(content_type)) => {
                     tracing::error!(
                         ?err,
                         ?record_path,
@@ -291,30 +515,33 @@ impl<T, E, O> RecordingManager<T, E, O> {
                     let content_path =
                         make_content_path_from_schedule(&self.config, &record_id, schedule);
                     let content_path = make_relative_content_path(&self.config, &content_path);
+                    let start_time = make_record_start_time(&self.config, schedule, started_at);
                     Record::new(
                         record_id,
-                        recorder.started_at,
+                        start_time,
                         schedule,
                         content_path,
-                        recorder.content_type.clone(),
+                        content_type.to_string(),
                     )
                 }
-                None => {
+                _ => {
                     tracing::error!(?err, ?record_path, "Broken record, skip updating");
                     self.emit_record_broken(record_id, "Broken record").await;
-                    return;
+                    return None;
                 }
             },
         };
 
         if file_util::save_json(&record, &record_path) {
             tracing::info!(?record_path, "Updated successfully");
-            self.emit_record_saved(record.id, record.recording_status)
+            self.emit_record_saved(record.id.clone(), record.recording_status.clone())
                 .await;
+            Some(record)
         } else {
             tracing::error!(?record_path, "Failed to save");
             self.emit_record_broken(record.id, "Faild to save record")
                 .await;
+            None
         }
     }
 }
@@ -327,6 +554,7 @@ where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -370,9 +598,49 @@ where
             return;
         }
 
+        if let Err(err) = self
+            .tuner_manager
+            .call(tuner::RegisterEmitter(ctx.emitter()))
+            .await
+        {
+            tracing::error!(?err, "Failed to register emitter for tuner::Event");
+            return;
+        }
+
+        // Series followers have to be loaded before schedules in order to keep the latest state.
+        self.load_series_followers();
         self.load_schedules();
+        self.load_rules();
+        self.load_recurring_schedules();
+        if self.expand_all_recurring_schedules(Jst::now()).await {
+            self.save_schedules();
+        }
         self.rebuild_queue();
         self.set_timer(ctx);
+
+        let emitter = ctx.emitter();
+        ctx.spawn_task(async move {
+            loop {
+                tokio::time::sleep(RECURRING_SCHEDULE_EXPANSION_INTERVAL).await;
+                emitter.emit(ExpandRecurringSchedules).await;
+            }
+        });
+
+        if self.config.recording.post_process.is_enabled() {
+            self.resume_post_process_jobs().await;
+            self.dispatch_post_process_jobs(ctx).await;
+        }
+
+        if self.config.recording.retention.is_enabled() {
+            let interval = self.config.recording.retention.interval;
+            let emitter = ctx.emitter();
+            ctx.spawn_task(async move {
+                loop {
+                    emitter.emit(ApplyRetentionPolicies).await;
+                    tokio::time::sleep(interval).await;
+                }
+            });
+        }
     }
 
     async fn stopping(&mut self, _ctx: &mut Context<Self>) {
@@ -396,6 +664,7 @@ where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -450,6 +719,7 @@ where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -491,6 +761,7 @@ where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -570,6 +841,10 @@ impl<T, E, O> RecordingManager<T, E, O> {
             return Err(err);
         }
 
+        if schedule.options.follow_series {
+            self.update_series_follower(&schedule);
+        }
+
         // We don't check the start and end times of the TV program here and
         // simply add a schedule for it.  Additional checks will be performed
         // in later stages.
@@ -583,6 +858,100 @@ impl<T, E, O> RecordingManager<T, E, O> {
     }
 }
 
+// add time-range recording schedule
+
+#[derive(Message)]
+#[reply(Result<RecordingSchedule, Error>)]
+pub struct AddTimeRangeRecordingSchedule {
+    pub service: EpgService,
+    pub start_at: DateTime<Jst>,
+    pub end_at: DateTime<Jst>,
+    pub name: Option<String>,
+    pub options: RecordingOptions,
+    pub tags: HashSet<String>,
+}
+
+#[async_trait]
+impl<T, E, O> Handler<AddTimeRangeRecordingSchedule> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(
+        &mut self,
+        msg: AddTimeRangeRecordingSchedule,
+        ctx: &mut Context<Self>,
+    ) -> <AddTimeRangeRecordingSchedule as Message>::Reply {
+        tracing::debug!(
+            msg.name = "AddTimeRangeRecordingSchedule",
+            %msg.service.id,
+            %msg.start_at,
+            %msg.end_at,
+            ?msg.options.content_path,
+            %msg.options.priority,
+            ?msg.options.pre_filters,
+            ?msg.options.post_filters,
+        );
+        let schedule = self.make_time_range_schedule(Jst::now(), msg)?;
+        let program_id = schedule.program.id;
+        self.add_schedule(schedule)?;
+        self.save_schedules();
+        self.rebuild_queue();
+        self.set_timer(ctx);
+        self.query_schedule(program_id)
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    const MAX_TIME_RANGE_HOURS: i64 = 24;
+
+    fn make_time_range_schedule(
+        &self,
+        now: DateTime<Jst>,
+        msg: AddTimeRangeRecordingSchedule,
+    ) -> Result<RecordingSchedule, Error> {
+        if msg.start_at >= msg.end_at {
+            return Err(Error::InvalidRequest("endAt must be later than startAt"));
+        }
+        if msg.end_at <= now {
+            return Err(Error::ProgramEnded);
+        }
+        if msg.end_at - msg.start_at > Duration::try_hours(Self::MAX_TIME_RANGE_HOURS).unwrap() {
+            return Err(Error::InvalidRequest("Time range must be within 24 hours"));
+        }
+
+        // Find an unused program ID.  An ID used by a recorder which has not stopped yet cannot
+        // be reused.
+        let program_id = (0..)
+            .map_while(|index| ProgramId::new_user_defined(msg.service.id, index))
+            .find(|program_id| {
+                !self.schedules.contains_key(program_id) && !self.recorders.contains_key(program_id)
+            })
+            .ok_or(Error::InvalidRequest("Too many time-range schedules"))?;
+
+        let mut program = EpgProgram::new(program_id);
+        program.start_at = Some(msg.start_at);
+        program.duration = Some(msg.end_at - msg.start_at);
+        program.name = msg.name;
+
+        Ok(RecordingSchedule::new(
+            program,
+            msg.service,
+            msg.options,
+            msg.tags,
+        ))
+    }
+}
+
 // remove recording schedule
 
 #[derive(Message)]
@@ -597,6 +966,7 @@ where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -617,6 +987,9 @@ where
                 if schedule.is_recording() {
                     let _ = self.stop_recorder(program_id);
                 }
+                if let Some(rule_id) = schedule.rule_id {
+                    self.ignore_program_in_rule(rule_id, program_id);
+                }
                 self.save_schedules();
                 self.rebuild_queue();
                 self.set_timer(ctx);
@@ -634,10 +1007,18 @@ where
 }
 
 impl<T, E, O> RecordingManager<T, E, O> {
+    // Prevent the rule from adding the schedule again when EPG is updated.
+    fn ignore_program_in_rule(&mut self, rule_id: RecordingRuleId, program_id: ProgramId) {
+        if let Some(rule) = self.rules.get_mut(&rule_id) {
+            rule.ignored_programs.insert(program_id);
+            self.save_rules();
+        }
+    }
+
     fn stop_recorder(&mut self, program_id: ProgramId) -> Result<(), Error> {
         match self.recorders.get_mut(&program_id) {
             Some(recorder) => {
-                match recorder.stop_trigger.take() {
+                match recorder.stop() {
                     Some(_) => {
                         tracing::info!(
                             schedule.program.id = %program_id,
@@ -686,6 +1067,7 @@ where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -717,6 +1099,7 @@ impl<T, E, O> RecordingManager<T, E, O> {
 
     fn clear_schedules(&mut self) {
         tracing::info!("Remove all schedules");
+        self.series_followers.clear();
         let schedules = std::mem::take(&mut self.schedules);
         for schedule in schedules.into_values() {
             if schedule.is_recording() {
@@ -726,21 +1109,32 @@ impl<T, E, O> RecordingManager<T, E, O> {
     }
 
     fn remove_schedules_by_tag(&mut self, tag: &str, now: DateTime<Jst>) {
+        tracing::info!(tag, "Remove tagged schedules");
+        // Stop following series of the schedules.
+        self.series_followers
+            .retain(|_, follower| !follower.tags.contains(tag));
+        self.remove_pending_schedules(now, |schedule| schedule.tags.contains(tag));
+    }
+
+    fn remove_pending_schedules<F>(&mut self, now: DateTime<Jst>, pred: F)
+    where
+        F: Fn(&RecordingSchedule) -> bool,
+    {
         use RecordingScheduleState::*;
 
         // No notification message will be emitted.
         // Users know what they are doing.
 
         let prep_time = Duration::try_seconds(PREP_SECS).unwrap();
-        tracing::info!(tag, "Remove tagged schedules");
+        let config = &self.config.recording;
         self.schedules.retain(|_, schedule| {
-            if !schedule.tags.contains(tag) {
+            if !pred(schedule) {
                 return true;
             }
             // Schedules in "Tracking" and "Recording" are retained.
             match schedule.state {
                 Scheduled => {
-                    let start_time = schedule.program.start_at.unwrap();
+                    let start_time = schedule.recording_start_time(config).unwrap();
                     // Always retain schedules which will start soon
                     // (or have already started).
                     start_time - now <= prep_time
@@ -758,18 +1152,19 @@ impl<T, E, O> RecordingManager<T, E, O> {
     }
 }
 
-// query recording recorders
+// query recording conflicts
 
 #[derive(Message)]
-#[reply(Vec<RecorderModel>)]
-pub struct QueryRecordingRecorders;
+#[reply(Vec<RecordingSchedule>)]
+pub struct QueryRecordingConflicts;
 
 #[async_trait]
-impl<T, E, O> Handler<QueryRecordingRecorders> for RecordingManager<T, E, O>
+impl<T, E, O> Handler<QueryRecordingConflicts> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -780,31 +1175,30 @@ where
 {
     async fn handle(
         &mut self,
-        _msg: QueryRecordingRecorders,
+        _msg: QueryRecordingConflicts,
         _ctx: &mut Context<Self>,
-    ) -> <QueryRecordingRecorders as Message>::Reply {
-        tracing::debug!(msg.name = "QueryRecordingRecorders");
-        self.recorders
-            .iter()
-            .map(|(&program_id, recorder)| recorder.get_model(program_id))
+    ) -> <QueryRecordingConflicts as Message>::Reply {
+        tracing::debug!(msg.name = "QueryRecordingConflicts");
+        self.query_schedules()
+            .into_iter()
+            .filter(|schedule| schedule.conflict.is_some())
             .collect()
     }
 }
 
-// query recording recorder
+// query recording rules
 
 #[derive(Message)]
-#[reply(Result<RecorderModel, Error>)]
-pub struct QueryRecordingRecorder {
-    pub program_id: ProgramId,
-}
+#[reply(Vec<RecordingRule>)]
+pub struct QueryRecordingRules;
 
 #[async_trait]
-impl<T, E, O> Handler<QueryRecordingRecorder> for RecordingManager<T, E, O>
+impl<T, E, O> Handler<QueryRecordingRules> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -815,31 +1209,29 @@ where
 {
     async fn handle(
         &mut self,
-        msg: QueryRecordingRecorder,
+        _msg: QueryRecordingRules,
         _ctx: &mut Context<Self>,
-    ) -> <QueryRecordingRecorder as Message>::Reply {
-        tracing::debug!(msg.name = "QueryRecordingRecorder", %msg.program_id);
-        self.recorders
-            .get(&msg.program_id)
-            .map(|recorder| recorder.get_model(msg.program_id))
-            .ok_or(Error::RecorderNotFound)
+    ) -> <QueryRecordingRules as Message>::Reply {
+        tracing::debug!(msg.name = "QueryRecordingRules");
+        self.rules.values().cloned().collect()
     }
 }
 
-// start recording
+// query recording rule
 
 #[derive(Message)]
-#[reply(Result<(), Error>)]
-pub struct StartRecording {
-    pub schedule: RecordingSchedule,
+#[reply(Result<RecordingRule, Error>)]
+pub struct QueryRecordingRule {
+    pub id: RecordingRuleId,
 }
 
 #[async_trait]
-impl<T, E, O> Handler<StartRecording> for RecordingManager<T, E, O>
+impl<T, E, O> Handler<QueryRecordingRule> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -850,180 +1242,250 @@ where
 {
     async fn handle(
         &mut self,
-        msg: StartRecording,
-        ctx: &mut Context<Self>,
-    ) -> <StartRecording as Message>::Reply {
-        tracing::debug!(
-            msg.name = "StartRecording",
-            %msg.schedule.program.id,
-            ?msg.schedule.options.content_path,
-            %msg.schedule.options.priority,
-            ?msg.schedule.options.pre_filters,
-            ?msg.schedule.options.post_filters,
-        );
-        let program_id = msg.schedule.program.id;
-        self.add_schedule(msg.schedule)?;
-        self.start_recording(program_id, ctx.address().clone(), ctx)
-            .await;
-        self.save_schedules();
-        Ok(())
+        msg: QueryRecordingRule,
+        _ctx: &mut Context<Self>,
+    ) -> <QueryRecordingRule as Message>::Reply {
+        tracing::debug!(msg.name = "QueryRecordingRule", %msg.id);
+        self.rules.get(&msg.id).cloned().ok_or(Error::RuleNotFound)
     }
 }
 
-// stop recording
+// add recording rule
 
 #[derive(Message)]
-#[reply(Result<(), Error>)]
-pub struct StopRecording {
-    pub program_id: ProgramId,
+#[reply(Result<RecordingRule, Error>)]
+pub struct AddRecordingRule {
+    pub conditions: RecordingRuleConditions,
+    pub options: RecordingOptions,
+    pub tags: HashSet<String>,
 }
 
 #[async_trait]
-impl<T, E, O> Handler<StopRecording> for RecordingManager<T, E, O>
+impl<T, E, O> Handler<AddRecordingRule> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
     E: Call<QueryService>,
+    E: Call<QueryServices>,
     E: Call<epg::RegisterEmitter>,
     O: Clone + Send + Sync + 'static,
     O: Call<onair::RegisterEmitter>,
 {
     async fn handle(
         &mut self,
-        msg: StopRecording,
-        _ctx: &mut Context<Self>,
-    ) -> <StopRecording as Message>::Reply {
+        msg: AddRecordingRule,
+        ctx: &mut Context<Self>,
+    ) -> <AddRecordingRule as Message>::Reply {
         tracing::debug!(
-            msg.name = "StopRecording",
-            %msg.program_id,
+            msg.name = "AddRecordingRule",
+            ?msg.conditions,
+            %msg.options.priority,
+            ?msg.options.pre_filters,
+            ?msg.options.post_filters,
         );
-        // This function doesn't wait for the recorder to stop completely.
-        //
-        // Dropping the stop trigger will stop the tuner pipeline and this
-        // will cause EOF in the recording pipeline.  A `RecordingStopped`
-        // signal will be emitted, and then it will be handled in the handler
-        // as usual.
-        self.recorders
-            .get_mut(&msg.program_id)
-            .ok_or(Error::RecorderNotFound)?
-            .stop_trigger
-            .take();
-        Ok(())
-    }
-}
+        msg.conditions.validate()?;
+        let rule = RecordingRule {
+            id: self.next_rule_id(),
+            conditions: msg.conditions,
+            options: msg.options,
+            tags: msg.tags,
+            ignored_programs: Default::default(),
+        };
+        tracing::info!(%rule.id, "Added rule");
+        self.rules.insert(rule.id, rule.clone());
+        self.save_rules();
+        if self.apply_rule(Jst::now(), &rule).await {
+            self.save_schedules();
+            self.rebuild_queue();
+            self.set_timer(ctx);
+        }
+        Ok(rule)
+    }
+}
 
-// process recording
+impl<T, E, O> RecordingManager<T, E, O> {
+    fn next_rule_id(&self) -> RecordingRuleId {
+        // Rule IDs referred from remaining schedules are never reused.
+        let max = self
+            .rules
+            .keys()
+            .copied()
+            .chain(
+                self.schedules
+                    .values()
+                    .filter_map(|schedule| schedule.rule_id),
+            )
+            .map(|id| id.value())
+            .max()
+            .unwrap_or(0);
+        RecordingRuleId::from(max + 1)
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O>
+where
+    E: Call<QueryPrograms>,
+    E: Call<QueryServices>,
+{
+    async fn apply_rule(&mut self, now: DateTime<Jst>, rule: &RecordingRule) -> bool {
+        let services = match self.epg.call(QueryServices).await {
+            Ok(services) => services,
+            Err(err) => {
+                tracing::error!(%err, %rule.id, "Failed to apply rule");
+                return false;
+            }
+        };
+
+        let mut changed = false;
+        for service in services.values() {
+            if rule.conditions.matches_service(service.id) {
+                changed |= self
+                    .add_schedules_by_rules(now, service, std::slice::from_ref(rule))
+                    .await;
+            }
+        }
+        changed
+    }
+}
+
+// update recording rule
 
 #[derive(Message)]
-struct ProcessRecording;
+#[reply(Result<RecordingRule, Error>)]
+pub struct UpdateRecordingRule {
+    pub id: RecordingRuleId,
+    pub conditions: RecordingRuleConditions,
+    pub options: RecordingOptions,
+    pub tags: HashSet<String>,
+}
 
 #[async_trait]
-impl<T, E, O> Handler<ProcessRecording> for RecordingManager<T, E, O>
+impl<T, E, O> Handler<UpdateRecordingRule> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
     E: Call<QueryService>,
+    E: Call<QueryServices>,
     E: Call<epg::RegisterEmitter>,
     O: Clone + Send + Sync + 'static,
     O: Call<onair::RegisterEmitter>,
 {
-    async fn handle(&mut self, _msg: ProcessRecording, ctx: &mut Context<Self>) {
-        tracing::debug!(msg.name = "ProcessRecording");
+    async fn handle(
+        &mut self,
+        msg: UpdateRecordingRule,
+        ctx: &mut Context<Self>,
+    ) -> <UpdateRecordingRule as Message>::Reply {
+        tracing::debug!(
+            msg.name = "UpdateRecordingRule",
+            %msg.id,
+            ?msg.conditions,
+            %msg.options.priority,
+            ?msg.options.pre_filters,
+            ?msg.options.post_filters,
+        );
+        msg.conditions.validate()?;
+        let rule = match self.rules.get_mut(&msg.id) {
+            Some(rule) => {
+                rule.conditions = msg.conditions;
+                rule.options = msg.options;
+                rule.tags = msg.tags;
+                rule.clone()
+            }
+            None => {
+                tracing::warn!(%msg.id, "No such rule added");
+                return Err(Error::RuleNotFound);
+            }
+        };
+        tracing::info!(%rule.id, "Updated rule");
+        self.save_rules();
+        // Schedules which no longer match the rule will be removed.  Others will be added again
+        // with the new options.
         let now = Jst::now();
-
-        let mut changed = self.maintain_schedules(now).await;
-        if changed {
-            self.rebuild_queue();
-        }
-
-        let program_ids = self.dequeue_next_schedules(now);
-        if !program_ids.is_empty() {
-            changed = true;
-        }
-
-        for program_id in program_ids.into_iter() {
-            self.start_recording(program_id, ctx.address().clone(), ctx)
-                .await;
-        }
-
+        self.series_followers
+            .retain(|_, follower| follower.rule_id != Some(rule.id));
+        self.remove_pending_schedules(now, |schedule| schedule.rule_id == Some(rule.id));
+        self.apply_rule(now, &rule).await;
+        self.save_schedules();
+        self.rebuild_queue();
         self.set_timer(ctx);
-        if changed {
-            self.save_schedules();
-        }
+        Ok(rule)
     }
 }
 
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn maintain_schedules(&mut self, now: DateTime<Jst>) -> bool {
-        use RecordingScheduleState::*;
-
-        let max_delay = Duration::try_hours(MAX_DELAY_HOURS).unwrap();
+// remove recording rule
 
-        let len = self.schedules.len();
-        let mut expired = vec![];
+#[derive(Message)]
+#[reply(Result<RecordingRule, Error>)]
+pub struct RemoveRecordingRule {
+    pub id: RecordingRuleId,
+}
 
-        self.schedules.retain(|_, schedule| {
-            if schedule.is_recording() {
-                return true;
-            }
-            if schedule.program.start_at.unwrap() + max_delay > now {
-                return true;
-            }
-            match schedule.state {
-                Scheduled | Tracking | Rescheduling => {
-                    tracing::error!(
-                        %schedule.program.id,
-                        "Schedule expired",
-                    );
-                    expired.push(schedule.program.id);
-                }
-                _ => {
-                    tracing::debug!(
-                        %schedule.program.id,
-                        "Removed old schedule for maintenance"
-                    );
-                }
+#[async_trait]
+impl<T, E, O> Handler<RemoveRecordingRule> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(
+        &mut self,
+        msg: RemoveRecordingRule,
+        ctx: &mut Context<Self>,
+    ) -> <RemoveRecordingRule as Message>::Reply {
+        tracing::debug!(msg.name = "RemoveRecordingRule", %msg.id);
+        match self.rules.shift_remove(&msg.id) {
+            Some(rule) => {
+                tracing::info!(%rule.id, "Removed rule");
+                self.save_rules();
+                self.series_followers
+                    .retain(|_, follower| follower.rule_id != Some(rule.id));
+                self.remove_pending_schedules(Jst::now(), |schedule| {
+                    schedule.rule_id == Some(rule.id)
+                });
+                self.save_schedules();
+                self.rebuild_queue();
+                self.set_timer(ctx);
+                Ok(rule)
             }
-            false
-        });
-
-        for &program_id in expired.iter() {
-            self.emit_recording_failed(program_id, RecordingFailedReason::ScheduleExpired)
-                .await;
-        }
-
-        self.schedules.len() != len
-    }
-
-    fn dequeue_next_schedules(&mut self, now: DateTime<Jst>) -> Vec<ProgramId> {
-        let mut program_ids = vec![];
-        let prep_secs = Duration::try_seconds(PREP_SECS).unwrap();
-        while let Some(schedule) = self.queue.peek() {
-            if schedule.start_at - now <= prep_secs {
-                let schedule = self.queue.pop().unwrap();
-                debug_assert!(self.schedules.contains_key(&schedule.program_id));
-                program_ids.push(schedule.program_id);
-            } else {
-                break;
+            None => {
+                tracing::warn!(%msg.id, "No such rule added");
+                Err(Error::RuleNotFound)
             }
         }
-        program_ids
     }
 }
 
-impl<T, E, O> RecordingManager<T, E, O>
+// query recurring schedules
+
+#[derive(Message)]
+#[reply(Vec<RecurringSchedule>)]
+pub struct QueryRecurringSchedules;
+
+#[async_trait]
+impl<T, E, O> Handler<QueryRecurringSchedules> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -1032,217 +1494,393 @@ where
     O: Clone + Send + Sync + 'static,
     O: Call<onair::RegisterEmitter>,
 {
-    async fn start_recording<C: Spawn>(
+    async fn handle(
         &mut self,
-        program_id: ProgramId,
-        addr: Address<Self>,
-        ctx: &C,
-    ) {
-        match self.do_start_recording(program_id, addr, ctx).await {
-            Ok(_) => {
-                tracing::info!(
-                    schedule.program.id = %program_id,
-                    "Start recording",
-                );
-            }
-            Err(err) => {
-                tracing::error!(
-                    %err,
-                    schedule.program.id = %program_id,
-                    "Failed to start recording",
-                );
-                let reason = RecordingFailedReason::StartRecordingFailed {
-                    message: format!("{err}"),
-                };
-                if let Some(schedule) = self.schedules.get_mut(&program_id) {
-                    schedule.state = RecordingScheduleState::Failed;
-                    schedule.failed_reason = Some(reason.clone());
-                }
-                self.emit_recording_failed(program_id, reason).await;
-            }
-        }
+        _msg: QueryRecurringSchedules,
+        _ctx: &mut Context<Self>,
+    ) -> <QueryRecurringSchedules as Message>::Reply {
+        tracing::debug!(msg.name = "QueryRecurringSchedules");
+        self.recurring_schedules.values().cloned().collect()
     }
+}
 
-    async fn do_start_recording<C: Spawn>(
-        &mut self,
-        program_id: ProgramId,
-        addr: Address<Self>,
-        ctx: &C,
-    ) -> Result<(), Error> {
-        if self.recorders.contains_key(&program_id) {
-            return Err(Error::AlreadyExists);
-        }
+// query recurring schedule
 
-        // TODO: copied from web::program_stream_g().
+#[derive(Message)]
+#[reply(Result<RecurringSchedule, Error>)]
+pub struct QueryRecurringSchedule {
+    pub id: RecurringScheduleId,
+}
 
-        let service_id = program_id.into();
-        let schedule = self.schedules.get(&program_id).unwrap();
+#[async_trait]
+impl<T, E, O> Handler<QueryRecurringSchedule> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(
+        &mut self,
+        msg: QueryRecurringSchedule,
+        _ctx: &mut Context<Self>,
+    ) -> <QueryRecurringSchedule as Message>::Reply {
+        tracing::debug!(msg.name = "QueryRecurringSchedule", %msg.id);
+        self.recurring_schedules
+            .get(&msg.id)
+            .cloned()
+            .ok_or(Error::RecurringScheduleNotFound)
+    }
+}
 
-        let clock = self.epg.call(QueryClock { service_id }).await??;
+// add recurring schedule
 
-        let stream = self
-            .tuner_manager
-            .call(StartStreaming {
-                channel: schedule.service.channel.clone(),
-                user: TunerUser {
-                    info: TunerUserInfo::Recorder(program_id),
-                    priority: schedule.options.priority.into(),
-                },
-                stream_id: None,
+#[derive(Message)]
+#[reply(Result<RecurringSchedule, Error>)]
+pub struct AddRecurringSchedule {
+    pub service_id: ServiceId,
+    pub cron: String,
+    pub duration: Option<Duration>,
+    pub name: Option<String>,
+    pub options: RecordingOptions,
+    pub tags: HashSet<String>,
+}
+
+#[async_trait]
+impl<T, E, O> Handler<AddRecurringSchedule> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(
+        &mut self,
+        msg: AddRecurringSchedule,
+        ctx: &mut Context<Self>,
+    ) -> <AddRecurringSchedule as Message>::Reply {
+        tracing::debug!(
+            msg.name = "AddRecurringSchedule",
+            %msg.service_id,
+            %msg.cron,
+            ?msg.duration,
+            %msg.options.priority,
+            ?msg.options.pre_filters,
+            ?msg.options.post_filters,
+        );
+        let recurring_schedule = RecurringSchedule {
+            id: self.next_recurring_schedule_id(),
+            service_id: msg.service_id,
+            cron: msg.cron,
+            duration: msg.duration,
+            name: msg.name,
+            options: msg.options,
+            tags: msg.tags,
+            expanded_until: None,
+        };
+        recurring_schedule.validate()?;
+        let service = self
+            .epg
+            .call(QueryService {
+                service_id: recurring_schedule.service_id,
             })
             .await??;
+        tracing::info!(%recurring_schedule.id, "Added recurring schedule");
+        let id = recurring_schedule.id;
+        self.recurring_schedules.insert(id, recurring_schedule);
+        if self
+            .expand_recurring_schedules(Jst::now(), &service, &[id])
+            .await
+        {
+            self.save_schedules();
+            self.rebuild_queue();
+            self.set_timer(ctx);
+        }
+        self.save_recurring_schedules();
+        Ok(self.recurring_schedules[&id].clone())
+    }
+}
 
-        // stop_trigger must be created here in order to stop streaming when
-        // an error occurs.
-        let msg = StopStreaming { id: stream.id() };
-        let stop_trigger = self.tuner_manager.trigger(msg);
+impl<T, E, O> RecordingManager<T, E, O> {
+    fn next_recurring_schedule_id(&self) -> RecurringScheduleId {
+        // IDs referred from remaining schedules are never reused.
+        let max = self
+            .recurring_schedules
+            .keys()
+            .copied()
+            .chain(
+                self.schedules
+                    .values()
+                    .filter_map(|schedule| schedule.recurring_schedule_id),
+            )
+            .map(|id| id.value())
+            .max()
+            .unwrap_or(0);
+        RecurringScheduleId::from(max + 1)
+    }
+}
 
-        let video_tags: Vec<u8> = schedule
-            .program
-            .video
-            .iter()
-            .map(|video| video.component_tag)
-            .collect();
-
-        let audio_tags: Vec<u8> = schedule
-            .program
-            .audios
-            .values()
-            .map(|audio| audio.component_tag)
-            .collect();
+// expand recurring schedules
 
-        let mut builder = mustache::MapBuilder::new();
-        builder = builder
-            .insert_str("channel_name", &schedule.service.channel.name)
-            .insert("channel_type", &schedule.service.channel.channel_type)?
-            .insert_str("channel", &schedule.service.channel.channel)
-            .insert("sid", &program_id.sid().value())?
-            .insert("eid", &program_id.eid().value())?
-            .insert("clock_pid", &clock.pid)?
-            .insert("clock_pcr", &clock.pcr)?
-            .insert("clock_time", &clock.time)?
-            .insert("video_tags", &video_tags)?
-            .insert("audio_tags", &audio_tags)?;
-        let data = builder.build();
+#[derive(Message)]
+struct ExpandRecurringSchedules;
 
-        let mut builder = FilterPipelineBuilder::new(data, false);
-        builder.add_pre_filters(&self.config.pre_filters, &schedule.options.pre_filters)?;
-        if !stream.is_decoded() {
-            builder.add_decode_filter(&self.config.filters.decode_filter)?;
+#[async_trait]
+impl<T, E, O> Handler<ExpandRecurringSchedules> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, _msg: ExpandRecurringSchedules, ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "ExpandRecurringSchedules");
+        if self.expand_all_recurring_schedules(Jst::now()).await {
+            self.save_schedules();
+            self.rebuild_queue();
+            self.set_timer(ctx);
         }
-        builder.add_program_filter(&self.config.filters.program_filter)?;
-        builder.add_post_filters(&self.config.post_filters, &schedule.options.post_filters)?;
-        let (filters, content_type, _) = builder.build();
-
-        let now = Jst::now();
-        let record_id = RecordId::from((now, program_id));
+    }
+}
 
-        let content_path = make_content_path_from_schedule(&self.config, &record_id, schedule);
-        // We assumed that schedule.content_path has already been normalized.
-        if let Some(dir) = content_path.parent() {
-            // Create missing directories if they don't exist.
-            tokio::fs::create_dir_all(dir).await?;
+impl<T, E, O> RecordingManager<T, E, O>
+where
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+{
+    async fn expand_all_recurring_schedules(&mut self, now: DateTime<Jst>) -> bool {
+        let service_ids: HashSet<ServiceId> = self
+            .recurring_schedules
+            .values()
+            .map(|recurring_schedule| recurring_schedule.service_id)
+            .collect();
+        let mut changed = false;
+        for service_id in service_ids.into_iter() {
+            changed |= self
+                .expand_recurring_schedules_for_service(now, service_id)
+                .await;
         }
+        changed
+    }
 
-        let log_filter = schedule.options.log_filter.as_ref().or(self
-            .config
-            .recording
-            .log_filter
-            .as_ref());
+    async fn expand_recurring_schedules_for_service(
+        &mut self,
+        now: DateTime<Jst>,
+        service_id: ServiceId,
+    ) -> bool {
+        let ids = self
+            .recurring_schedules
+            .values()
+            .filter(|recurring_schedule| recurring_schedule.service_id == service_id)
+            .map(|recurring_schedule| recurring_schedule.id)
+            .collect_vec();
+        if ids.is_empty() {
+            return false;
+        }
 
-        let mut builder = CommandPipelineBuilder::new(filters, stream.id(), "recording");
-        match log_filter {
-            Some(log_filter) => {
-                builder.set_log_filter(log_filter);
-                match log_filter.as_str() {
-                    "" | "off" => {
-                        builder.set_logging(false); // Disable logging
-                    }
-                    _ => {
-                        builder.set_logging(true); // Enable logging
-                        let log_path = make_log_path_from_content_path(&content_path);
-                        builder.set_log_file(log_path);
-                    }
-                }
+        let service = match self.epg.call(QueryService { service_id }).await {
+            Ok(Ok(service)) => service,
+            Ok(Err(err)) => {
+                tracing::warn!(%err, %service_id, "Failed to expand recurring schedules");
+                return false;
             }
-            None => {
-                // logging to STDOUT (compatible with 3.x and older).
+            Err(err) => {
+                tracing::error!(%err, %service_id, "Failed to expand recurring schedules");
+                return false;
             }
-        }
-        let mut pipeline = builder.build(ctx)?;
-        let (input, mut output) = pipeline.take_endpoints();
-
-        let fut = async move {
-            let _ = stream.pipe(input).await;
         };
-        ctx.spawn_task(fut);
 
-        // Inner future in order to capture the result in an outer future.
-        let inner_fut = {
-            let content_path = content_path.clone();
-            async move {
-                let record = tokio::fs::File::create(&content_path).await?;
-                let mut writer = BufWriter::new(record);
-                // TODO: use Stdio
-                tokio::io::copy(&mut output, &mut writer).await
-            }
-        };
-        // Outer future emits messages to observers.
-        let fut = {
-            async move {
-                addr.emit(RecordingStarted { program_id }).await;
-                let _stopped = addr.trigger(RecordingStopped { program_id });
-                let result = inner_fut.await;
-                if let Err(err) = result {
-                    addr.emit(RecordingFailed {
-                        program_id,
-                        reason: RecordingFailedReason::IoError {
-                            message: format!("{err}"),
-                            os_error: err.raw_os_error(),
-                        },
-                    })
-                    .await;
+        self.expand_recurring_schedules(now, &service, &ids).await
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O>
+where
+    E: Call<QueryPrograms>,
+{
+    // Adds recording schedules for occurrences of the recurring schedules within the lookahead
+    // period.  Returns `true` if any recording schedule has been added.
+    //
+    // Occurrences are processed in order and `expanded_until` is updated so that each occurrence
+    // is expanded at most once.  Therefore, recording schedules removed by users are never added
+    // again.
+    async fn expand_recurring_schedules(
+        &mut self,
+        now: DateTime<Jst>,
+        service: &EpgService,
+        ids: &[RecurringScheduleId],
+    ) -> bool {
+        let service_id = service.id;
+        let until = now + Duration::try_days(RECURRING_SCHEDULE_LOOKAHEAD_DAYS).unwrap();
+
+        let needs_programs = ids
+            .iter()
+            .filter_map(|id| self.recurring_schedules.get(id))
+            .any(|recurring_schedule| recurring_schedule.duration.is_none());
+        let programs = if needs_programs {
+            match self.epg.call(QueryPrograms { service_id }).await {
+                Ok(programs) => Some(programs),
+                Err(err) => {
+                    tracing::error!(%err, %service_id, "Failed to expand recurring schedules");
+                    return false;
                 }
             }
+        } else {
+            None
         };
 
-        let recorder = Recorder {
-            started_at: now,
-            pipeline,
-            stop_trigger: Some(stop_trigger),
-            content_type: content_type.clone(),
-        };
-        self.recorders.insert(program_id, recorder);
-        self.schedules.get_mut(&program_id).unwrap().state = RecordingScheduleState::Recording;
+        let mut changed = false;
+        let mut recurring_schedules_changed = false;
+        for id in ids.iter() {
+            let recurring_schedule = match self.recurring_schedules.get(id) {
+                Some(recurring_schedule) => recurring_schedule.clone(),
+                None => continue,
+            };
+            let cron = match cron::Schedule::from_str(&recurring_schedule.cron) {
+                Ok(cron) => cron,
+                Err(err) => {
+                    tracing::warn!(%err, %recurring_schedule.id, "Broken recurring schedule, skip");
+                    continue;
+                }
+            };
 
-        // Spawn the following task after the recorder is inserted so that
-        // actors receiving RecordingStarted messages can access the recorder.
-        ctx.spawn_task(fut);
+            let mut expanded_until = recurring_schedule.expanded_until;
+            let occurrences = cron
+                .after(&expanded_until.unwrap_or(now))
+                .take_while(|&time| time <= until);
+            for time in occurrences {
+                if time <= now {
+                    // Occurrences in the past are skipped.
+                    expanded_until = Some(time);
+                    continue;
+                }
 
-        let record_path = make_record_path(&self.config, &record_id);
-        if let Some(record_path) = record_path {
-            let schedule = self.schedules.get(&program_id).unwrap();
-            let content_path = make_relative_content_path(&self.config, &content_path);
-            let record = Record::new(record_id, now, schedule, content_path, content_type);
-            self.create_record(&record_path, &record).await;
+                let schedule = match recurring_schedule.duration {
+                    Some(duration) => {
+                        let msg = AddTimeRangeRecordingSchedule {
+                            service: service.clone(),
+                            start_at: time,
+                            end_at: time + duration,
+                            name: recurring_schedule.name.clone(),
+                            options: recurring_schedule.options.clone(),
+                            tags: recurring_schedule.tags.clone(),
+                        };
+                        match self.make_time_range_schedule(now, msg) {
+                            Ok(schedule) => Some(schedule),
+                            Err(err) => {
+                                tracing::warn!(
+                                    %err,
+                                    %recurring_schedule.id,
+                                    %time,
+                                    "Failed to expand recurring schedule, skip"
+                                );
+                                None
+                            }
+                        }
+                    }
+                    None => {
+                        // Find the TV program on air at the time.
+                        let program = programs.as_ref().unwrap().values().find(|program| {
+                            match (program.start_at, program.end_at()) {
+                                (Some(start_at), Some(end_at)) => start_at <= time && time < end_at,
+                                _ => false,
+                            }
+                        });
+                        let program = match program {
+                            Some(program) => program,
+                            None => {
+                                // The TV program may be added to EPG later.  Try again when EPG is
+                                // updated.
+                                break;
+                            }
+                        };
+                        match program.start_at {
+                            Some(start_at) if start_at > now => (),
+                            _ => {
+                                expanded_until = Some(time);
+                                continue;
+                            }
+                        }
+                        if self.schedules.contains_key(&program.id) {
+                            expanded_until = Some(time);
+                            continue;
+                        }
+                        Some(RecordingSchedule::new(
+                            program.clone(),
+                            service.clone(),
+                            recurring_schedule.options.clone(),
+                            recurring_schedule.tags.clone(),
+                        ))
+                    }
+                };
+
+                if let Some(mut schedule) = schedule {
+                    tracing::info!(
+                        %recurring_schedule.id,
+                        %schedule.program.id,
+                        %time,
+                        "Expanded"
+                    );
+                    schedule.recurring_schedule_id = Some(recurring_schedule.id);
+                    // Never fails because we checked it above.
+                    let _ = self.add_schedule(schedule);
+                    changed = true;
+                }
+                expanded_until = Some(time);
+            }
+
+            if expanded_until != recurring_schedule.expanded_until
+                && let Some(recurring_schedule) = self.recurring_schedules.get_mut(id)
+            {
+                recurring_schedule.expanded_until = expanded_until;
+                recurring_schedules_changed = true;
+            }
         }
 
-        Ok(())
+        if recurring_schedules_changed {
+            self.save_recurring_schedules();
+        }
+
+        changed
     }
 }
 
-// query records
+// remove recurring schedule
 
 #[derive(Message)]
-#[reply(Result<Vec<(Record, Option<u64>)>, Error>)]
-pub struct QueryRecords;
+#[reply(Result<RecurringSchedule, Error>)]
+pub struct RemoveRecurringSchedule {
+    pub id: RecurringScheduleId,
+}
 
 #[async_trait]
-impl<T, E, O> Handler<QueryRecords> for RecordingManager<T, E, O>
+impl<T, E, O> Handler<RemoveRecurringSchedule> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -1253,47 +1891,44 @@ where
 {
     async fn handle(
         &mut self,
-        _msg: QueryRecords,
-        _ctx: &mut Context<Self>,
-    ) -> <QueryRecords as Message>::Reply {
-        tracing::debug!(msg.name = "QueryRecords");
-        self.query_records().await
-    }
-}
-
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn query_records(&self) -> Result<Vec<(Record, Option<u64>)>, Error> {
-        let records_dir = match self.config.recording.records_dir.as_ref() {
-            Some(records_dir) => records_dir,
-            None => return Err(Error::WrongConfig("config.recording.records-dir")),
-        };
-
-        let mut records = vec![];
-        for record_path in glob_records(records_dir) {
-            match load_record(&self.config, &record_path).await {
-                Ok(tuple) => records.push(tuple),
-                Err(err) => tracing::warn!(?record_path, ?err, "Failed to load, skip"),
+        msg: RemoveRecurringSchedule,
+        ctx: &mut Context<Self>,
+    ) -> <RemoveRecurringSchedule as Message>::Reply {
+        tracing::debug!(msg.name = "RemoveRecurringSchedule", %msg.id);
+        match self.recurring_schedules.shift_remove(&msg.id) {
+            Some(recurring_schedule) => {
+                tracing::info!(%recurring_schedule.id, "Removed recurring schedule");
+                self.save_recurring_schedules();
+                // Future schedules added by the recurring schedule are cancelled.
+                self.remove_pending_schedules(Jst::now(), |schedule| {
+                    schedule.recurring_schedule_id == Some(recurring_schedule.id)
+                });
+                self.save_schedules();
+                self.rebuild_queue();
+                self.set_timer(ctx);
+                Ok(recurring_schedule)
+            }
+            None => {
+                tracing::warn!(%msg.id, "No such recurring schedule added");
+                Err(Error::RecurringScheduleNotFound)
             }
         }
-
-        Ok(records)
     }
 }
 
-// query record
+// query recording recorders
 
 #[derive(Message)]
-#[reply(Result<(Record, Option<u64>), Error>)]
-pub struct QueryRecord {
-    pub id: RecordId,
-}
+#[reply(Vec<RecorderModel>)]
+pub struct QueryRecordingRecorders;
 
 #[async_trait]
-impl<T, E, O> Handler<QueryRecord> for RecordingManager<T, E, O>
+impl<T, E, O> Handler<QueryRecordingRecorders> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -1304,40 +1939,32 @@ where
 {
     async fn handle(
         &mut self,
-        msg: QueryRecord,
+        _msg: QueryRecordingRecorders,
         _ctx: &mut Context<Self>,
-    ) -> <QueryRecord as Message>::Reply {
-        tracing::debug!(msg.name = "QueryRecord", %msg.id);
-        self.query_record(&msg.id).await
-    }
-}
-
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn query_record(&self, id: &RecordId) -> Result<(Record, Option<u64>), Error> {
-        let record_path = match make_record_path(&self.config, id) {
-            Some(record_path) => record_path,
-            None => return Err(Error::WrongConfig("config.recording.records-dir")),
-        };
-
-        load_record(&self.config, &record_path).await
+    ) -> <QueryRecordingRecorders as Message>::Reply {
+        tracing::debug!(msg.name = "QueryRecordingRecorders");
+        self.recorders
+            .iter()
+            .map(|(&program_id, recorder)| recorder.get_model(program_id))
+            .collect()
     }
 }
 
-// remove record
+// query recording recorder
 
 #[derive(Message)]
-#[reply(Result<(bool, bool), Error>)]
-pub struct RemoveRecord {
-    pub id: RecordId,
-    pub purge: bool,
+#[reply(Result<RecorderModel, Error>)]
+pub struct QueryRecordingRecorder {
+    pub program_id: ProgramId,
 }
 
 #[async_trait]
-impl<T, E, O> Handler<RemoveRecord> for RecordingManager<T, E, O>
+impl<T, E, O> Handler<QueryRecordingRecorder> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -1348,104 +1975,77 @@ where
 {
     async fn handle(
         &mut self,
-        msg: RemoveRecord,
+        msg: QueryRecordingRecorder,
         _ctx: &mut Context<Self>,
-    ) -> <RemoveRecord as Message>::Reply {
-        tracing::debug!(msg.name = "RemoveRecord", %msg.id, msg.purge);
-        self.remove_record(&msg.id, msg.purge).await
+    ) -> <QueryRecordingRecorder as Message>::Reply {
+        tracing::debug!(msg.name = "QueryRecordingRecorder", %msg.program_id);
+        self.recorders
+            .get(&msg.program_id)
+            .map(|recorder| recorder.get_model(msg.program_id))
+            .ok_or(Error::RecorderNotFound)
     }
 }
 
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn remove_record(&self, id: &RecordId, purge: bool) -> Result<(bool, bool), Error> {
-        let record_path = match make_record_path(&self.config, id) {
-            Some(record_path) => record_path,
-            None => return Err(Error::WrongConfig("config.recording.records-dir")),
-        };
-
-        let (record, _) = load_record(&self.config, &record_path).await?;
-
-        if matches!(record.recording_status, RecordingStatus::Recording) {
-            tracing::warn!(
-                ?record_path,
-                "Cannot remove the record while it's recording"
-            );
-            return Err(Error::InvalidRequest("Now recording"));
-        }
-
-        let mut record_removed = false;
-        let mut content_removed = false;
-
-        if purge {
-            let content_path = make_content_path(&self.config, &record).unwrap();
-            if content_path.exists() {
-                match tokio::fs::remove_file(&content_path).await {
-                    Ok(_) => {
-                        content_removed = true;
-                        self.emit_content_removed(id.clone()).await;
-                    }
-                    Err(err) => tracing::error!(?err, ?content_path, "Failed to remove"),
-                }
-            } else {
-                tracing::warn!(?record_path, ?content_path, "No content file");
-                content_removed = true;
-            }
-
-            let log_path = make_log_path_from_content_path(&content_path);
-            if log_path.exists() {
-                if let Err(err) = tokio::fs::remove_file(&log_path).await {
-                    tracing::error!(?err, ?log_path, "Failed to remove");
-                }
-            }
-        }
+// start recording
 
-        debug_assert!(record_path.exists());
-        match tokio::fs::remove_file(&record_path).await {
-            Ok(_) => {
-                record_removed = true;
-                self.emit_record_removed(id.clone()).await;
-            }
-            Err(err) => tracing::error!(?err, ?record_path),
-        }
+#[derive(Message)]
+#[reply(Result<(), Error>)]
+pub struct StartRecording {
+    pub schedule: RecordingSchedule,
+}
 
-        Ok((record_removed, content_removed))
+#[async_trait]
+impl<T, E, O> Handler<StartRecording> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(
+        &mut self,
+        msg: StartRecording,
+        ctx: &mut Context<Self>,
+    ) -> <StartRecording as Message>::Reply {
+        tracing::debug!(
+            msg.name = "StartRecording",
+            %msg.schedule.program.id,
+            ?msg.schedule.options.content_path,
+            %msg.schedule.options.priority,
+            ?msg.schedule.options.pre_filters,
+            ?msg.schedule.options.post_filters,
+        );
+        let program_id = msg.schedule.program.id;
+        self.add_schedule(msg.schedule)?;
+        self.start_recording(program_id, ctx.address().clone(), ctx)
+            .await;
+        self.save_schedules();
+        Ok(())
     }
 }
 
-// open content
-
-type BoxedStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>;
-type ContentStream = MpegTsStream<RecordId, BoxedStream>;
-type StopTrigger = Trigger<actlet::Stop>;
+// stop recording
 
 #[derive(Message)]
-#[reply(Result<(ContentStream, Option<StopTrigger>), Error>)]
-pub struct OpenContent {
-    pub id: RecordId,
-    pub range: Option<ContentRange>,
-    pub time_limit: u64,
-}
-
-impl OpenContent {
-    // See the `tail` command used in `ContentSource::new()` for the reason why the time limit is
-    // 1500ms.
-    const DEFAULT_TIME_LIMIT: u64 = 1500;
-
-    pub fn new(id: RecordId, range: Option<ContentRange>) -> Self {
-        Self {
-            id,
-            range,
-            time_limit: Self::DEFAULT_TIME_LIMIT,
-        }
-    }
+#[reply(Result<(), Error>)]
+pub struct StopRecording {
+    pub program_id: ProgramId,
 }
 
 #[async_trait]
-impl<T, E, O> Handler<OpenContent> for RecordingManager<T, E, O>
+impl<T, E, O> Handler<StopRecording> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -1456,65 +2056,85 @@ where
 {
     async fn handle(
         &mut self,
-        msg: OpenContent,
-        ctx: &mut Context<Self>,
-    ) -> <OpenContent as Message>::Reply {
-        tracing::debug!(msg.name = "OpenContent", %msg.id);
-        self.open_content(&msg.id, msg.range.as_ref(), msg.time_limit, ctx)
-            .await
+        msg: StopRecording,
+        _ctx: &mut Context<Self>,
+    ) -> <StopRecording as Message>::Reply {
+        tracing::debug!(
+            msg.name = "StopRecording",
+            %msg.program_id,
+        );
+        // This function doesn't wait for the recorder to stop completely.
+        //
+        // Dropping the stop trigger will stop the tuner pipeline and this
+        // will cause EOF in the recording pipeline.  A `RecordingStopped`
+        // signal will be emitted, and then it will be handled in the handler
+        // as usual.
+        self.recorders
+            .get_mut(&msg.program_id)
+            .ok_or(Error::RecorderNotFound)?
+            .stop();
+        Ok(())
     }
 }
 
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn open_content(
-        &self,
-        id: &RecordId,
-        range: Option<&ContentRange>,
-        time_limit: u64,
-        ctx: &Context<Self>,
-    ) -> Result<(ContentStream, Option<StopTrigger>), Error> {
-        let record_path = match make_record_path(&self.config, id) {
-            Some(record_path) => record_path,
-            None => return Err(Error::WrongConfig("config.recording.records-dir")),
-        };
+// process recording
 
-        let (record, content_length) = load_record(&self.config, &record_path).await?;
-        let _content_length = match content_length {
-            Some(content_length) if content_length > 0 => content_length,
-            _ => return Err(Error::NoContent),
-        };
+#[derive(Message)]
+struct ProcessRecording;
 
-        let mut content_source = ContentSource::new(&self.config, &record, range, ctx)?;
-        let stream = content_source.create_stream(time_limit);
+#[async_trait]
+impl<T, E, O> Handler<ProcessRecording> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, _msg: ProcessRecording, ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "ProcessRecording");
+        let now = Jst::now();
 
-        let addr = ctx.spawn_actor(content_source).await;
-        let stop_trigger = Some(addr.trigger(actlet::Stop));
+        let mut changed = self.maintain_schedules(now).await;
+        if changed {
+            self.rebuild_queue();
+        }
 
-        Ok((stream, stop_trigger))
+        let program_ids = self.dequeue_next_schedules(now);
+        if !program_ids.is_empty() {
+            changed = true;
+        }
+
+        for program_id in program_ids.into_iter() {
+            self.start_recording(program_id, ctx.address().clone(), ctx)
+                .await;
+        }
+
+        self.set_timer(ctx);
+        if changed {
+            self.save_schedules();
+        }
     }
 }
 
-// register emitter
+// apply retention policies
 
 #[derive(Message)]
-#[reply(usize)]
-pub enum RegisterEmitter {
-    RecordingStarted(Emitter<RecordingStarted>),
-    RecordingStopped(Emitter<RecordingStopped>),
-    RecordingFailed(Emitter<RecordingFailed>),
-    RecordingRescheduled(Emitter<RecordingRescheduled>),
-    RecordSaved(Emitter<RecordSaved>),
-    RecordRemoved(Emitter<RecordRemoved>),
-    ContentRemoved(Emitter<ContentRemoved>),
-    RecordBroken(Emitter<RecordBroken>),
-}
+struct ApplyRetentionPolicies;
 
 #[async_trait]
-impl<T, E, O> Handler<RegisterEmitter> for RecordingManager<T, E, O>
+impl<T, E, O> Handler<ApplyRetentionPolicies> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -1523,241 +2143,150 @@ where
     O: Clone + Send + Sync + 'static,
     O: Call<onair::RegisterEmitter>,
 {
-    async fn handle(
-        &mut self,
-        msg: RegisterEmitter,
-        ctx: &mut Context<Self>,
-    ) -> <RegisterEmitter as Message>::Reply {
-        debug_assert!(self.config.recording.is_enabled());
-        match msg {
-            RegisterEmitter::RecordingStarted(emitter) => {
-                let id = self.recording_started.register(emitter.clone());
-                tracing::debug!(msg.name = "RegisterEmitter::RecordingStarted", id);
-                if id != 0 {
-                    // Sending many messages in the message handler may cause a dead lock
-                    // when the number of messages to be sent is larger than the capacity
-                    // of the emitter's channel.  See the issue #705 for example.
-                    ctx.set_post_process(RegisterEmitterPostProcess(emitter));
+    async fn handle(&mut self, _msg: ApplyRetentionPolicies, _ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "ApplyRetentionPolicies");
+        self.apply_retention_policies(Jst::now()).await;
+        if let Some(min_free_space) = self.config.recording.retention.min_free_space {
+            let basedir = self.config.recording.basedir.as_ref().unwrap();
+            match file_util::available_space(basedir) {
+                Ok(free_space) if free_space < min_free_space => {
+                    tracing::warn!(
+                        free_space,
+                        min_free_space,
+                        "Free space is still insufficient after applying retention policies",
+                    );
                 }
-                id
+                Ok(_) => (),
+                Err(err) => tracing::error!(%err, ?basedir, "Failed to get free space"),
             }
-            RegisterEmitter::RecordingStopped(emitter) => {
-                let id = self.recording_stopped.register(emitter);
-                tracing::debug!(msg.name = "RegisterEmitter::RecordingStopped", id);
-                id
+        }
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn apply_retention_policies(&self, now: DateTime<Jst>) {
+        let config = &self.config.recording.retention;
+        if !config.is_enabled() {
+            return;
+        }
+
+        let records = match self.query_records().await {
+            Ok(records) => records,
+            Err(err) => {
+                tracing::error!(%err, "Failed to query records");
+                return;
             }
-            RegisterEmitter::RecordingFailed(emitter) => {
-                let id = self.recording_failed.register(emitter);
-                tracing::debug!(msg.name = "RegisterEmitter::RecordingFailed", id);
-                id
-            }
-            RegisterEmitter::RecordingRescheduled(emitter) => {
-                let id = self.recording_rescheduled.register(emitter);
-                tracing::debug!(msg.name = "RegisterEmitter::RecordingRescheduled", id);
-                id
-            }
-            RegisterEmitter::RecordSaved(emitter) => {
-                debug_assert!(self.config.recording.is_records_api_enabled());
-                // Create a task to send messages.
-                //
-                // Sending many messages in the message handler may cause a dead lock
-                // when the number of messages to be sent is larger than the capacity
-                // of the emitter's channel.  See the issue #705 for example.
-                let task =
-                    Self::emit_record_saved_for_each_record(self.config.clone(), emitter.clone());
-                ctx.spawn_task(task);
-                let id = self.record_saved.register(emitter);
-                tracing::debug!(msg.name = "RegisterEmitter::RecordSaved", id);
-                id
-            }
-            RegisterEmitter::RecordBroken(emitter) => {
-                debug_assert!(self.config.recording.is_records_api_enabled());
-                let id = self.record_broken.register(emitter);
-                tracing::debug!(msg.name = "RegisterEmitter::RecordBroken", id);
-                id
-            }
-            RegisterEmitter::RecordRemoved(emitter) => {
-                debug_assert!(self.config.recording.is_records_api_enabled());
-                let id = self.record_removed.register(emitter);
-                tracing::debug!(msg.name = "RegisterEmitter::RecordRemoved", id);
-                id
+        };
+
+        let free_space = match config.min_free_space {
+            Some(_) => {
+                let basedir = self.config.recording.basedir.as_ref().unwrap();
+                match file_util::available_space(basedir) {
+                    Ok(free_space) => Some(free_space),
+                    Err(err) => {
+                        tracing::error!(%err, ?basedir, "Failed to get free space");
+                        None
+                    }
+                }
             }
-            RegisterEmitter::ContentRemoved(emitter) => {
-                debug_assert!(self.config.recording.is_records_api_enabled());
-                let id = self.content_removed.register(emitter);
-                tracing::debug!(msg.name = "RegisterEmitter::ContentRemoved", id);
-                id
+            None => None,
+        };
+
+        for id in select_records_to_purge(config, now, &records, free_space) {
+            // The same as `RemoveRecord { purge: true }`.
+            match self.remove_record(&id, true).await {
+                Ok(_) => tracing::info!(%id, "Purged by retention policies"),
+                Err(err) => tracing::error!(%err, %id, "Failed to purge"),
             }
         }
     }
-}
 
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn emit_record_saved_for_each_record(config: Arc<Config>, emitter: Emitter<RecordSaved>) {
-        let records_dir = config.recording.records_dir.as_ref().unwrap();
-        for record_path in glob_records(records_dir) {
-            match load_record(&config, &record_path).await {
-                Ok((record, _)) => {
-                    let msg = RecordSaved {
-                        record_id: record.id,
-                        recording_status: record.recording_status,
-                    };
-                    emitter.emit(msg).await;
-                }
-                Err(err) => tracing::error!(?record_path, ?err, "Failed to load, skip"),
-            }
+    async fn ensure_free_space(&self) -> Result<(), Error> {
+        let min_free_space = match self.config.recording.retention.min_free_space {
+            Some(min_free_space) => min_free_space,
+            None => return Ok(()),
+        };
+
+        let basedir = self.config.recording.basedir.as_ref().unwrap();
+        if file_util::available_space(basedir)? >= min_free_space {
+            return Ok(());
         }
-    }
-}
 
-#[derive(Message)]
-pub struct RegisterEmitterPostProcess(Emitter<RecordingStarted>);
+        self.apply_retention_policies(Jst::now()).await;
 
-#[async_trait]
-impl<T, E, O> Handler<RegisterEmitterPostProcess> for RecordingManager<T, E, O>
-where
-    T: Clone + Send + Sync + 'static,
-    T: Call<StartStreaming>,
-    T: TriggerFactory<StopStreaming>,
-    E: Send + Sync + 'static,
-    E: Call<QueryClock>,
-    E: Call<QueryPrograms>,
-    E: Call<QueryService>,
-    E: Call<epg::RegisterEmitter>,
-    O: Clone + Send + Sync + 'static,
-    O: Call<onair::RegisterEmitter>,
-{
-    async fn handle(&mut self, msg: RegisterEmitterPostProcess, _ctx: &mut Context<Self>) {
-        let emitter = msg.0;
-        for &program_id in self.recorders.keys() {
-            let msg = RecordingStarted { program_id };
-            emitter.emit(msg).await;
+        let free_space = file_util::available_space(basedir)?;
+        if free_space < min_free_space {
+            tracing::error!(free_space, min_free_space, "No space for recording");
+            return Err(Error::NoSpace);
         }
+        Ok(())
     }
 }
 
-// unregister emitter
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn maintain_schedules(&mut self, now: DateTime<Jst>) -> bool {
+        use RecordingScheduleState::*;
 
-#[derive(Message)]
-pub enum UnregisterEmitter {
-    RecordingStarted(usize),
-    RecordingStopped(usize),
-    RecordingFailed(usize),
-    RecordingRescheduled(usize),
-    RecordSaved(usize),
-    RecordRemoved(usize),
-    ContentRemoved(usize),
-    RecordBroken(usize),
-}
+        let max_delay = Duration::try_hours(MAX_DELAY_HOURS).unwrap();
 
-#[async_trait]
-impl<T, E, O> Handler<UnregisterEmitter> for RecordingManager<T, E, O>
-where
-    T: Clone + Send + Sync + 'static,
-    T: Call<StartStreaming>,
-    T: TriggerFactory<StopStreaming>,
-    E: Send + Sync + 'static,
-    E: Call<QueryClock>,
-    E: Call<QueryPrograms>,
-    E: Call<QueryService>,
-    E: Call<epg::RegisterEmitter>,
-    O: Clone + Send + Sync + 'static,
-    O: Call<onair::RegisterEmitter>,
-{
-    async fn handle(&mut self, msg: UnregisterEmitter, _ctx: &mut Context<Self>) {
-        debug_assert!(self.config.recording.is_enabled());
-        match msg {
-            UnregisterEmitter::RecordingStarted(id) => {
-                tracing::debug!(msg.name = "UnregisterEmitter::RecordingStarted", id);
-                self.recording_started.unregister(id);
-            }
-            UnregisterEmitter::RecordingStopped(id) => {
-                tracing::debug!(msg.name = "UnregisterEmitter::RecordingStopped", id);
-                self.recording_stopped.unregister(id);
-            }
-            UnregisterEmitter::RecordingFailed(id) => {
-                tracing::debug!(msg.name = "UnregisterEmitter::RecordingFailed", id);
-                self.recording_failed.unregister(id);
-            }
-            UnregisterEmitter::RecordingRescheduled(id) => {
-                tracing::debug!(msg.name = "UnregisterEmitter::RecordingRescheduled", id);
-                self.recording_rescheduled.unregister(id);
-            }
-            UnregisterEmitter::RecordSaved(id) => {
-                debug_assert!(self.config.recording.is_records_api_enabled());
-                tracing::debug!(msg.name = "UnregisterEmitter::RecordSaved", id);
-                self.record_saved.unregister(id);
-            }
-            UnregisterEmitter::RecordBroken(id) => {
-                debug_assert!(self.config.recording.is_records_api_enabled());
-                tracing::debug!(msg.name = "UnregisterEmitter::RecordBroken", id);
-                self.record_broken.unregister(id);
+        let len = self.schedules.len();
+        let mut expired = vec![];
+
+        self.schedules.retain(|_, schedule| {
+            if schedule.is_recording() {
+                return true;
             }
-            UnregisterEmitter::RecordRemoved(id) => {
-                debug_assert!(self.config.recording.is_records_api_enabled());
-                tracing::debug!(msg.name = "UnregisterEmitter::RecordRemoved", id);
-                self.record_removed.unregister(id);
+            if schedule.program.start_at.unwrap() + max_delay > now {
+                return true;
             }
-            UnregisterEmitter::ContentRemoved(id) => {
-                debug_assert!(self.config.recording.is_records_api_enabled());
-                tracing::debug!(msg.name = "UnregisterEmitter::ContentRemoved", id);
-                self.content_removed.unregister(id);
+            match schedule.state {
+                Scheduled | Tracking | Rescheduling => {
+                    tracing::error!(
+                        %schedule.program.id,
+                        "Schedule expired",
+                    );
+                    expired.push(schedule.program.id);
+                }
+                _ => {
+                    tracing::debug!(
+                        %schedule.program.id,
+                        "Removed old schedule for maintenance"
+                    );
+                }
             }
-        }
-    }
-}
-
-// recording started
+            false
+        });
 
-#[derive(Clone, Message)]
-pub struct RecordingStarted {
-    pub program_id: ProgramId,
-}
+        for &program_id in expired.iter() {
+            self.emit_recording_failed(program_id, RecordingFailedReason::ScheduleExpired)
+                .await;
+        }
 
-#[async_trait]
-impl<T, E, O> Handler<RecordingStarted> for RecordingManager<T, E, O>
-where
-    T: Clone + Send + Sync + 'static,
-    T: Call<StartStreaming>,
-    T: TriggerFactory<StopStreaming>,
-    E: Send + Sync + 'static,
-    E: Call<QueryClock>,
-    E: Call<QueryPrograms>,
-    E: Call<QueryService>,
-    E: Call<epg::RegisterEmitter>,
-    O: Clone + Send + Sync + 'static,
-    O: Call<onair::RegisterEmitter>,
-{
-    async fn handle(&mut self, msg: RecordingStarted, _ctx: &mut Context<Self>) {
-        tracing::debug!(msg.name = "RecordingStarted", %msg.program_id);
-        self.handle_recording_started(msg.program_id).await;
+        self.schedules.len() != len
     }
-}
 
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn handle_recording_started(&self, program_id: ProgramId) {
-        tracing::info!(
-            schedule.program.id = %program_id,
-            "Recording started",
-        );
-        let msg = RecordingStarted { program_id };
-        self.recording_started.emit(msg).await;
+    fn dequeue_next_schedules(&mut self, now: DateTime<Jst>) -> Vec<ProgramId> {
+        let mut program_ids = vec![];
+        let prep_secs = Duration::try_seconds(PREP_SECS).unwrap();
+        while let Some(schedule) = self.queue.peek() {
+            if schedule.start_at - now <= prep_secs {
+                let schedule = self.queue.pop().unwrap();
+                debug_assert!(self.schedules.contains_key(&schedule.program_id));
+                program_ids.push(schedule.program_id);
+            } else {
+                break;
+            }
+        }
+        program_ids
     }
 }
 
-// recording stopped
-
-#[derive(Clone, Message)]
-pub struct RecordingStopped {
-    pub program_id: ProgramId,
-}
-
-#[async_trait]
-impl<T, E, O> Handler<RecordingStopped> for RecordingManager<T, E, O>
+impl<T, E, O> RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -1766,244 +2295,387 @@ where
     O: Clone + Send + Sync + 'static,
     O: Call<onair::RegisterEmitter>,
 {
-    async fn handle(&mut self, msg: RecordingStopped, _ctx: &mut Context<Self>) {
-        tracing::debug!(msg.name = "RecordingStopped", %msg.program_id);
-        let changed = self.handle_recording_stopped(msg.program_id).await;
-        if changed {
-            self.save_schedules();
+    async fn start_recording<C: Spawn>(
+        &mut self,
+        program_id: ProgramId,
+        addr: Address<Self>,
+        ctx: &C,
+    ) {
+        if let Some(schedule) = self.schedules.get_mut(&program_id) {
+            schedule.attempts.clear();
         }
-    }
-}
-
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn handle_recording_stopped(&mut self, program_id: ProgramId) -> bool {
-        // The schedule is NOT removed for a while so that an external script
-        // listening recording events can access the details of the schedule.
-        //
-        // It will be removed in the `ProcessRecording` message handler.
-
-        // The schedule may have been removed before the recording stops.
-        // For example, `clear_schedules()` clears schedules before the
-        // recordings stop.
-        let maybe_schedule = self.schedules.get_mut(&program_id);
-
-        let mut changed = false;
-
-        let recorder = match self.recorders.get_mut(&program_id) {
-            Some(recorder) => recorder,
-            None => {
+        match self.do_start_recording(program_id, addr, ctx).await {
+            Ok(_) => {
+                tracing::info!(
+                    schedule.program.id = %program_id,
+                    "Start recording",
+                );
+            }
+            Err(err) => {
                 tracing::error!(
+                    %err,
                     schedule.program.id = %program_id,
-                    "INCONSNSTENT: The recorder stopped before the recording stopped",
+                    "Failed to start recording",
                 );
-                let msg = RecordingStopped { program_id };
-                self.recording_stopped.emit(msg).await;
-                return changed;
+                let reason = RecordingFailedReason::StartRecordingFailed {
+                    message: format!("{err}"),
+                };
+                if let Some(schedule) = self.schedules.get_mut(&program_id) {
+                    schedule.state = RecordingScheduleState::Failed;
+                    schedule.failed_reason = Some(reason.clone());
+                }
+                self.emit_recording_failed(program_id, reason).await;
             }
-        };
-
-        // Manually drop the stop trigger so that we get the exit code
-        // from the program-filter without killing its process.
-        if let Some(stop_trigger) = recorder.stop_trigger.take() {
-            drop(stop_trigger);
         }
+    }
 
-        let results = recorder.pipeline.wait().await;
-        if check_retry(&results) {
-            tracing::error!(
-                schedule.program.id = %program_id,
-                "Recording stopped before the TV program starts",
-            );
-            if let Some(schedule) = maybe_schedule {
-                tracing::warn!(
-                    %schedule.program.id,
-                    "Need rescheduling",
+    // Retries the recording on a tuner which has not been used in previous
+    // attempts.  The record of the first attempt is reused.
+    async fn retry_recording<C: Spawn>(
+        &mut self,
+        program_id: ProgramId,
+        addr: Address<Self>,
+        ctx: &C,
+    ) {
+        match self.do_start_recording(program_id, addr, ctx).await {
+            Ok(_) => {
+                tracing::info!(
+                    schedule.program.id = %program_id,
+                    "Retry recording",
                 );
-                schedule.state = RecordingScheduleState::Rescheduling;
-                changed = true;
-                self.emit_recording_failed(program_id, RecordingFailedReason::NeedRescheduling)
-                    .await;
             }
-        } else if let Some(exit_code) = get_first_error(&results) {
-            tracing::error!(
-                %exit_code,
-                schedule.program.id = %program_id,
-                "The recording pipeline terminated abnormally",
-            );
-            let reason = RecordingFailedReason::PipelineError { exit_code };
-            if let Some(schedule) = maybe_schedule {
+            Err(err) => {
+                tracing::error!(
+                    %err,
+                    schedule.program.id = %program_id,
+                    "Failed to retry recording",
+                );
+                let schedule = match self.schedules.get_mut(&program_id) {
+                    Some(schedule) => schedule,
+                    None => return,
+                };
+                // Report the failure of the last attempt which actually
+                // started the recording.
+                let reason = schedule
+                    .attempts
+                    .last()
+                    .and_then(|attempt| attempt.failed_reason.clone())
+                    .unwrap_or_else(|| RecordingFailedReason::StartRecordingFailed {
+                        message: format!("{err}"),
+                    });
                 schedule.state = RecordingScheduleState::Failed;
                 schedule.failed_reason = Some(reason.clone());
-                changed = true;
-            }
-            self.emit_recording_failed(program_id, reason).await;
-        } else {
-            tracing::info!(
-                schedule.program.id = %program_id,
-                "The recording finished successfully",
-            );
-            if let Some(schedule) = maybe_schedule {
-                schedule.state = RecordingScheduleState::Finished;
-                changed = true;
+                let started_at = schedule.attempts.first().map(|attempt| attempt.started_at);
+                self.emit_recording_failed(program_id, reason).await;
+                if let Some(started_at) = started_at {
+                    // The recorder has already been removed.  So, the record
+                    // cannot be overwritten if it has been broken.
+                    let record = self
+                        .update_record_started_at(program_id, started_at, None)
+                        .await;
+                    if let Some(record) = record {
+                        self.enqueue_post_process_jobs(&record).await;
+                    }
+                }
             }
         }
-
-        self.update_record(program_id).await;
-
-        // Unlike the schedule, the recorder should be removed after the
-        // recording stopped.
-        self.recorders.remove(&program_id);
-
-        // TODO: Save recording logs to a file.
-        let msg = RecordingStopped { program_id };
-        self.recording_stopped.emit(msg).await;
-
-        changed
     }
-}
 
-// recording failed
+    async fn do_start_recording<C: Spawn>(
+        &mut self,
+        program_id: ProgramId,
+        addr: Address<Self>,
+        ctx: &C,
+    ) -> Result<(), Error> {
+        if self.recorders.contains_key(&program_id) {
+            return Err(Error::AlreadyExists);
+        }
 
-#[derive(Clone, Message)]
-pub struct RecordingFailed {
-    pub program_id: ProgramId,
-    pub reason: RecordingFailedReason,
-}
+        self.ensure_free_space().await?;
 
-#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
-#[serde(tag = "type", rename_all = "kebab-case")]
-pub enum RecordingFailedReason {
-    #[serde(rename_all = "camelCase")]
-    StartRecordingFailed {
-        message: String,
-    },
-    #[serde(rename_all = "camelCase")]
-    IoError {
-        message: String,
-        os_error: Option<i32>,
-    },
-    #[serde(rename_all = "camelCase")]
-    PipelineError {
-        exit_code: i32,
-    },
-    NeedRescheduling,
-    ScheduleExpired,
-    RemovedFromEpg,
-}
+        // TODO: copied from web::program_stream_g().
 
-#[async_trait]
-impl<T, E, O> Handler<RecordingFailed> for RecordingManager<T, E, O>
-where
-    T: Clone + Send + Sync + 'static,
-    T: Call<StartStreaming>,
-    T: TriggerFactory<StopStreaming>,
-    E: Send + Sync + 'static,
-    E: Call<QueryClock>,
-    E: Call<QueryPrograms>,
-    E: Call<QueryService>,
-    E: Call<epg::RegisterEmitter>,
-    O: Clone + Send + Sync + 'static,
-    O: Call<onair::RegisterEmitter>,
-{
-    async fn handle(&mut self, msg: RecordingFailed, _ctx: &mut Context<Self>) {
-        tracing::debug!(msg.name = "RecordingFailed", %msg.program_id, ?msg.reason);
-        self.emit_recording_failed(msg.program_id, msg.reason).await;
-    }
-}
+        let service_id = program_id.into();
+        let schedule = self.schedules.get(&program_id).unwrap();
 
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn emit_recording_failed(&self, program_id: ProgramId, reason: RecordingFailedReason) {
-        let msg = RecordingFailed {
-            program_id,
-            reason: reason.clone(),
+        // The clock is not needed for time-range recording schedules because
+        // the service-filter is used instead of the program-filter.
+        let clock = if schedule.is_time_range() {
+            None
+        } else {
+            Some(self.epg.call(QueryClock { service_id }).await??)
         };
-        self.recording_failed.emit(msg).await;
-    }
-}
-
-// recording rescheduled
 
-#[derive(Clone, Message)]
-pub struct RecordingRescheduled {
-    pub program_id: ProgramId,
-}
+        // The service-filter never stops by itself.
+        let stop_at = if schedule.is_time_range() {
+            schedule.recording_end_time(&self.config.recording)
+        } else {
+            None
+        };
 
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn emit_recording_rescheduled(&self, program_id: ProgramId) {
-        let msg = RecordingRescheduled { program_id };
-        self.recording_rescheduled.emit(msg).await;
-    }
-}
+        // Tuners used in previous attempts are excluded.
+        let excluded_tuners = schedule
+            .attempts
+            .iter()
+            .map(|attempt| attempt.tuner_index)
+            .collect();
 
-// record saved
+        let stream = self
+            .tuner_manager
+            .call(StartStreaming {
+                channel: schedule.service.channel.clone(),
+                user: TunerUser {
+                    info: TunerUserInfo::Recorder(program_id),
+                    priority: schedule.options.priority.into(),
+                },
+                stream_id: None,
+                excluded_tuners,
+            })
+            .await??;
+        let tuner_index = stream.id().tuner_index();
 
-#[derive(Clone, Message)]
-pub struct RecordSaved {
-    pub record_id: RecordId,
-    pub recording_status: RecordingStatus,
-}
+        // stop_trigger must be created here in order to stop streaming when
+        // an error occurs.
+        let msg = StopStreaming { id: stream.id() };
+        let stop_trigger = self.tuner_manager.trigger(msg);
 
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn emit_record_saved(&self, record_id: RecordId, recording_status: RecordingStatus) {
-        let msg = RecordSaved {
-            record_id,
-            recording_status,
-        };
-        self.record_saved.emit(msg).await;
-    }
-}
+        let mut builder = mustache::MapBuilder::new();
+        builder = builder
+            .insert_str("channel_name", &schedule.service.channel.name)
+            .insert("channel_type", &schedule.service.channel.channel_type)?
+            .insert_str("channel", &schedule.service.channel.channel)
+            .insert("sid", &program_id.sid().value())?;
+        if let Some(ref clock) = clock {
+            let video_tags: Vec<u8> = schedule
+                .program
+                .video
+                .iter()
+                .map(|video| video.component_tag)
+                .collect();
+
+            let audio_tags: Vec<u8> = schedule
+                .program
+                .audios
+                .values()
+                .map(|audio| audio.component_tag)
+                .collect();
+
+            builder = builder
+                .insert("eid", &program_id.eid().value())?
+                .insert("clock_pid", &clock.pid)?
+                .insert("clock_pcr", &clock.pcr)?
+                .insert("clock_time", &clock.time)?
+                .insert("video_tags", &video_tags)?
+                .insert("audio_tags", &audio_tags)?;
+            let start_margin = schedule.options.start_margin(&self.config.recording);
+            if start_margin > Duration::zero() {
+                builder = builder.insert("start_margin", &start_margin.num_milliseconds())?;
+            }
+            let end_margin = schedule.options.end_margin(&self.config.recording);
+            builder = builder.insert(
+                "end_margin",
+                &(end_margin.num_milliseconds() + PROGRAM_FILTER_END_MARGIN_MS),
+            )?;
+        }
+        let data = builder.build();
 
-// record broken
+        let mut builder = FilterPipelineBuilder::new(data, false);
+        builder.add_pre_filters(&self.config.pre_filters, &schedule.options.pre_filters)?;
+        if !stream.is_decoded() {
+            builder.add_decode_filter(&self.config.filters.decode_filter)?;
+        }
+        if schedule.is_time_range() {
+            builder.add_service_filter(&self.config.filters.service_filter)?;
+        } else {
+            builder.add_program_filter(&self.config.filters.program_filter)?;
+        }
+        builder.add_post_filters(&self.config.post_filters, &schedule.options.post_filters)?;
+        let (filters, content_type, _) = builder.build();
 
-#[derive(Clone, Message)]
-pub struct RecordBroken {
-    pub record_id: RecordId,
-    pub reason: &'static str,
-}
+        let now = Jst::now();
+        // The record of the first attempt is used in subsequent attempts.
+        let retry = !schedule.attempts.is_empty();
+        let started_at = schedule
+            .attempts
+            .first()
+            .map_or(now, |attempt| attempt.started_at);
+        let record_id = RecordId::from((started_at, program_id));
 
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn emit_record_broken(&self, record_id: RecordId, reason: &'static str) {
-        let msg = RecordBroken { record_id, reason };
-        self.record_broken.emit(msg).await;
-    }
-}
+        let content_path = make_content_path_from_schedule(&self.config, &record_id, schedule);
+        // We assumed that schedule.content_path has already been normalized.
+        if let Some(dir) = content_path.parent() {
+            // Create missing directories if they don't exist.
+            tokio::fs::create_dir_all(dir).await?;
+        }
 
-// record removed
+        let log_filter = schedule.options.log_filter.as_ref().or(self
+            .config
+            .recording
+            .log_filter
+            .as_ref());
 
-#[derive(Clone, Message)]
-pub struct RecordRemoved {
-    pub record_id: RecordId,
-}
+        let mut builder = CommandPipelineBuilder::new(filters, stream.id(), "recording");
+        let mut log_path = None;
+        match log_filter {
+            Some(log_filter) => {
+                builder.set_log_filter(log_filter);
+                match log_filter.as_str() {
+                    "" | "off" => {
+                        builder.set_logging(false); // Disable logging
+                    }
+                    _ => {
+                        builder.set_logging(true); // Enable logging
+                        let path = make_log_path_from_content_path(&content_path);
+                        builder.set_log_file(&path);
+                        log_path = Some(path);
+                    }
+                }
+            }
+            None => {
+                // logging to STDOUT (compatible with 3.x and older).
+            }
+        }
+        let mut pipeline = builder.build(ctx)?;
+        let (input, mut output) = pipeline.take_endpoints();
 
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn emit_record_removed(&self, record_id: RecordId) {
-        let msg = RecordRemoved { record_id };
-        self.record_removed.emit(msg).await;
-    }
-}
+        let fut = async move {
+            let _ = stream.pipe(input).await;
+        };
+        ctx.spawn_task(fut);
 
-// content removed
+        // Content indexes are built from PCRs in the same way as `rebuild_content_index()`.
+        let index = if content_type == "video/MP2T" {
+            make_index_path(&self.config, &record_id).map(|path| {
+                let start_time = make_record_start_time(&self.config, schedule, started_at);
+                (path, start_time)
+            })
+        } else {
+            None
+        };
 
-#[derive(Clone, Message)]
-pub struct ContentRemoved {
-    pub record_id: RecordId,
-}
+        // Inner future in order to capture the result in an outer future.
+        let inner_fut = {
+            let content_path = content_path.clone();
+            async move {
+                let record = if retry {
+                    // Append to the content recorded in previous attempts.
+                    tokio::fs::OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(&content_path)
+                        .await?
+                } else {
+                    tokio::fs::File::create(&content_path).await?
+                };
+                let mut index_writer = match index {
+                    Some((path, start_time)) => {
+                        Some(ContentIndexWriter::open(path, start_time, &content_path, retry).await)
+                    }
+                    None => None,
+                };
+                let mut writer = BufWriter::new(record);
+                // TODO: use Stdio
+                let mut buf = vec![0; 4096 * 8];
+                loop {
+                    let n = output.read(&mut buf).await?;
+                    if n == 0 {
+                        break;
+                    }
+                    writer.write_all(&buf[..n]).await?;
+                    if let Some(ref mut index_writer) = index_writer {
+                        index_writer.advance(&buf[..n]).await;
+                    }
+                }
+                writer.flush().await?;
+                if let Some(ref mut index_writer) = index_writer {
+                    index_writer.finish().await;
+                }
+                Ok::<_, std::io::Error>(())
+            }
+        };
+        // The timer is cancelled when the recorder stops so that it never stops another
+        // recording of a time-range schedule reusing the same program ID.
+        let stop_timer = stop_at.map(|stop_at| {
+            let duration = (stop_at - now).to_std().unwrap_or_default();
+            let addr = addr.clone();
+            let (_, token) = ctx.spawn_task(async move {
+                tokio::time::sleep(duration).await;
+                tracing::info!(schedule.program.id = %program_id, "Reached the end time");
+                let _ = addr.call(StopRecording { program_id }).await;
+            });
+            token
+        });
 
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn emit_content_removed(&self, record_id: RecordId) {
-        let msg = ContentRemoved { record_id };
-        self.content_removed.emit(msg).await;
+        // Outer future emits messages to observers.
+        let fut = {
+            async move {
+                addr.emit(RecordingStarted { program_id }).await;
+                let _stopped = addr.trigger(RecordingStopped { program_id });
+                let result = inner_fut.await;
+                if let Err(err) = result {
+                    addr.emit(RecordingFailed {
+                        program_id,
+                        reason: RecordingFailedReason::IoError {
+                            message: format!("{err}"),
+                            os_error: err.raw_os_error(),
+                        },
+                    })
+                    .await;
+                }
+            }
+        };
+
+        let recorder = Recorder {
+            started_at,
+            pipeline,
+            stop_trigger: Some(stop_trigger),
+            content_type: content_type.clone(),
+            log_path,
+            preempted: None,
+            stop_timer,
+        };
+        self.recorders.insert(program_id, recorder);
+        let schedule = self.schedules.get_mut(&program_id).unwrap();
+        schedule.state = RecordingScheduleState::Recording;
+        schedule.attempts.push(RecordingAttempt {
+            tuner_index,
+            started_at: now,
+            failed_reason: None,
+        });
+
+        // Spawn the following task after the recorder is inserted so that
+        // actors receiving RecordingStarted messages can access the recorder.
+        ctx.spawn_task(fut);
+
+        let record_path = make_record_path(&self.config, &record_id);
+        if let Some(record_path) = record_path {
+            let schedule = self.schedules.get(&program_id).unwrap();
+            let content_path = make_relative_content_path(&self.config, &content_path);
+            let start_time = make_record_start_time(&self.config, schedule, started_at);
+            let record = Record::new(record_id, start_time, schedule, content_path, content_type);
+            self.create_record(&record_path, &record).await;
+        }
+
+        Ok(())
     }
 }
 
-// services updated
+// run post-process job
+
+#[derive(Message)]
+#[reply(Result<PostProcessJob, Error>)]
+pub struct RunPostProcessJob {
+    pub record_id: RecordId,
+    pub name: String,
+}
 
 #[async_trait]
-impl<T, E, O> Handler<epg::ServicesUpdated> for RecordingManager<T, E, O>
+impl<T, E, O> Handler<RunPostProcessJob> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -2012,66 +2684,228 @@ where
     O: Clone + Send + Sync + 'static,
     O: Call<onair::RegisterEmitter>,
 {
-    async fn handle(&mut self, msg: epg::ServicesUpdated, ctx: &mut Context<Self>) {
-        tracing::debug!(msg.name = "ServicesUpdated");
-        let changed = self.update_schedules_by_epg_services(&msg.services).await;
-        if changed {
-            self.save_schedules();
-            self.rebuild_queue();
-            self.set_timer(ctx);
+    async fn handle(
+        &mut self,
+        msg: RunPostProcessJob,
+        ctx: &mut Context<Self>,
+    ) -> <RunPostProcessJob as Message>::Reply {
+        tracing::debug!(msg.name = "RunPostProcessJob", %msg.record_id, job = msg.name);
+        if !self
+            .config
+            .recording
+            .post_process
+            .jobs
+            .contains_key(&msg.name)
+        {
+            return Err(Error::PostProcessJobNotFound);
+        }
+        let (record, _) = self.query_record(&msg.record_id).await?;
+        if let RecordingStatus::Recording = record.recording_status {
+            return Err(Error::InvalidRequest("The record is being recorded"));
+        }
+        let running = record
+            .post_process_jobs
+            .iter()
+            .find(|job| job.name == msg.name)
+            .is_some_and(|job| job.is_active());
+        if running {
+            return Err(Error::AlreadyExists);
         }
+        let job = self
+            .enqueue_post_process_job(&msg.record_id, &msg.name)
+            .await?;
+        self.dispatch_post_process_jobs(ctx).await;
+        Ok(job)
     }
 }
 
 impl<T, E, O> RecordingManager<T, E, O> {
-    async fn update_schedules_by_epg_services(
-        &mut self,
-        services: &IndexMap<ServiceId, epg::EpgService>,
-    ) -> bool {
-        let mut changed = false;
-        let mut removed = vec![];
-        self.schedules.retain(|&program_id, schedule| {
-            if !schedule.can_be_updated_by_epg() {
-                return true;
+    // Re-enqueue jobs interrupted by the previous shutdown.
+    async fn resume_post_process_jobs(&mut self) {
+        let records = match self.query_records().await {
+            Ok(records) => records,
+            Err(err) => {
+                tracing::error!(%err, "Failed to resume post-process jobs");
+                return;
             }
-            let service_id = ServiceId::from(program_id);
-            match services.get(&service_id) {
-                Some(service) => {
-                    if schedule.service != *service {
-                        schedule.service = service.clone();
-                        changed = true;
-                    }
-                    true
-                }
-                None => {
-                    tracing::warn!(%schedule.program.id, "Removed from EPG");
-                    removed.push(program_id);
-                    changed = true;
-                    false
-                }
+        };
+        let jobs = records
+            .iter()
+            .sorted_by_key(|(record, _)| record.recording_start_time)
+            .flat_map(|(record, _)| {
+                record
+                    .post_process_jobs
+                    .iter()
+                    .filter(|job| job.is_active())
+                    .map(|job| (record.id.clone(), job.name.clone()))
+            })
+            .collect_vec();
+        for (record_id, name) in jobs.into_iter() {
+            tracing::info!(%record_id, job = name, "Resume post-process job");
+            if let Err(err) = self.enqueue_post_process_job(&record_id, &name).await {
+                tracing::error!(%err, %record_id, job = name, "Failed to resume post-process job");
             }
-        });
+        }
+    }
 
-        for &program_id in removed.iter() {
-            if self.recorders.contains_key(&program_id) {
-                let _ = self.stop_recorder(program_id);
+    async fn enqueue_post_process_jobs(&mut self, record: &Record) {
+        let succeeded = matches!(record.recording_status, RecordingStatus::Finished);
+        let names = self
+            .config
+            .recording
+            .post_process
+            .jobs
+            .iter()
+            .filter(|(_, config)| succeeded || config.run_on_failure)
+            .map(|(name, _)| name.clone())
+            .collect_vec();
+        for name in names.into_iter() {
+            if let Err(err) = self.enqueue_post_process_job(&record.id, &name).await {
+                tracing::error!(%err, %record.id, job = name, "Failed to enqueue post-process job");
             }
-            self.emit_recording_failed(program_id, RecordingFailedReason::RemovedFromEpg)
-                .await;
         }
+    }
 
-        changed
+    async fn enqueue_post_process_job(
+        &mut self,
+        record_id: &RecordId,
+        name: &str,
+    ) -> Result<PostProcessJob, Error> {
+        let record = self
+            .update_post_process_job(record_id, name, |job| {
+                *job = PostProcessJob::new(job.name.clone());
+            })
+            .await?;
+        tracing::info!(%record_id, job = name, "Enqueued post-process job");
+        self.post_process_queue
+            .push_back((record_id.clone(), name.to_string()));
+        Ok(record.post_process_job(name).unwrap().clone())
+    }
+
+    async fn dispatch_post_process_jobs<C>(&mut self, ctx: &C)
+    where
+        C: Spawn + EmitterFactory<PostProcessJobFinished>,
+    {
+        let config = &self.config.recording.post_process;
+        while self.num_running_post_processes < config.concurrency {
+            let (record_id, name) = match self.post_process_queue.pop_front() {
+                Some(item) => item,
+                None => break,
+            };
+
+            let template = match config.jobs.get(&name) {
+                Some(config) => config.command.clone(),
+                None => {
+                    // The job may have been removed from the config.
+                    tracing::warn!(%record_id, job = name, "No such post-process job, skip");
+                    continue;
+                }
+            };
+
+            let now = Jst::now();
+            let record = match self
+                .update_post_process_job(&record_id, &name, |job| {
+                    job.state = PostProcessJobState::Running;
+                    job.started_at = Some(now);
+                })
+                .await
+            {
+                Ok(record) => record,
+                Err(err) => {
+                    tracing::error!(%err, %record_id, job = name, "Failed to start post-process job");
+                    continue;
+                }
+            };
+
+            let command = match make_post_process_command(&self.config, &template, &record) {
+                Ok(command) => command,
+                Err(err) => {
+                    tracing::error!(%err, %record_id, job = name, "Failed to render command");
+                    let msg = PostProcessJobFinished {
+                        record_id,
+                        name,
+                        result: Err(err.to_string()),
+                    };
+                    ctx.emitter().emit(msg).await;
+                    continue;
+                }
+            };
+
+            let content_path = make_content_path(&self.config, &record).unwrap();
+            let log_path = make_post_process_log_path(&content_path, &name);
+
+            tracing::info!(%record_id, job = name, command, "Start post-process job");
+            self.num_running_post_processes += 1;
+            let emitter = ctx.emitter();
+            ctx.spawn_task(async move {
+                let result = run_post_process_command(&command, &log_path)
+                    .await
+                    .map_err(|err| err.to_string());
+                let msg = PostProcessJobFinished {
+                    record_id,
+                    name,
+                    result,
+                };
+                emitter.emit(msg).await;
+            });
+        }
+    }
+
+    async fn update_post_process_job<F>(
+        &self,
+        record_id: &RecordId,
+        name: &str,
+        update: F,
+    ) -> Result<Record, Error>
+    where
+        F: FnOnce(&mut PostProcessJob),
+    {
+        let record_path = match make_record_path(&self.config, record_id) {
+            Some(record_path) => record_path,
+            None => return Err(Error::WrongConfig("config.recording.records-dir")),
+        };
+        let (mut record, _) = load_record(&self.config, &record_path).await?;
+        let index = match record
+            .post_process_jobs
+            .iter()
+            .position(|job| job.name == name)
+        {
+            Some(index) => index,
+            None => {
+                record
+                    .post_process_jobs
+                    .push(PostProcessJob::new(name.to_string()));
+                record.post_process_jobs.len() - 1
+            }
+        };
+        update(&mut record.post_process_jobs[index]);
+        if !file_util::save_json(&record, &record_path) {
+            tracing::error!(?record_path, "Failed to save");
+            return Err(Error::IoError(std::io::Error::other(
+                "Failed to save record",
+            )));
+        }
+        Ok(record)
     }
 }
 
-// programs updated
+// post-process job finished
+
+#[derive(Message)]
+struct PostProcessJobFinished {
+    record_id: RecordId,
+    name: String,
+    // The exit code, or an error message if the command failed to run.
+    result: Result<i32, String>,
+}
 
 #[async_trait]
-impl<T, E, O> Handler<epg::ProgramsUpdated> for RecordingManager<T, E, O>
+impl<T, E, O> Handler<PostProcessJobFinished> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -2080,91 +2914,264 @@ where
     O: Clone + Send + Sync + 'static,
     O: Call<onair::RegisterEmitter>,
 {
-    async fn handle(&mut self, msg: epg::ProgramsUpdated, ctx: &mut Context<Self>) {
-        tracing::debug!(msg.name = "ProgramsUpdated", %msg.service_id);
+    async fn handle(&mut self, msg: PostProcessJobFinished, ctx: &mut Context<Self>) {
+        tracing::debug!(
+            msg.name = "PostProcessJobFinished",
+            %msg.record_id,
+            job = msg.name,
+            ?msg.result,
+        );
+        if self.num_running_post_processes > 0 {
+            self.num_running_post_processes -= 1;
+        }
         let now = Jst::now();
-        let changed = self
-            .update_schedules_by_epg_programs(now, msg.service_id)
+        let result = self
+            .update_post_process_job(&msg.record_id, &msg.name, |job| {
+                job.finished_at = Some(now);
+                match msg.result {
+                    Ok(0) => {
+                        job.state = PostProcessJobState::Succeeded;
+                        job.exit_code = Some(0);
+                    }
+                    Ok(exit_code) => {
+                        job.state = PostProcessJobState::Failed;
+                        job.exit_code = Some(exit_code);
+                    }
+                    Err(message) => {
+                        job.state = PostProcessJobState::Failed;
+                        job.message = Some(message);
+                    }
+                }
+            })
             .await;
-        if changed {
-            self.save_schedules();
-            self.rebuild_queue();
-            self.set_timer(ctx);
+        match result {
+            Ok(record) => {
+                let job = record.post_process_job(&msg.name).unwrap();
+                tracing::info!(%msg.record_id, job = msg.name, ?job.state, "Post-process job finished");
+            }
+            Err(err) => {
+                // The record may have been removed while the job is running.
+                tracing::warn!(%err, %msg.record_id, job = msg.name, "Failed to update record");
+            }
         }
+        self.dispatch_post_process_jobs(ctx).await;
     }
 }
 
-impl<T, E, O> RecordingManager<T, E, O>
+// import timeshift record
+
+#[derive(Message)]
+#[reply(Result<Record, Error>)]
+pub struct ImportTimeshiftRecord {
+    pub export: TimeshiftRecordExport,
+    pub options: RecordingOptions,
+    pub tags: HashSet<String>,
+}
+
+#[async_trait]
+impl<T, E, O> Handler<ImportTimeshiftRecord> for RecordingManager<T, E, O>
 where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
     E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
 {
-    async fn update_schedules_by_epg_programs(
+    async fn handle(
         &mut self,
-        now: DateTime<Jst>,
-        service_id: ServiceId,
-    ) -> bool {
-        let programs = match self.epg.call(QueryPrograms { service_id }).await {
-            Ok(programs) => programs,
-            Err(err) => {
-                tracing::error!(%err, %service_id, "Failed to update schedules");
-                return false;
-            }
+        msg: ImportTimeshiftRecord,
+        ctx: &mut Context<Self>,
+    ) -> <ImportTimeshiftRecord as Message>::Reply {
+        tracing::debug!(
+            msg.name = "ImportTimeshiftRecord",
+            %msg.export.record.id,
+            %msg.export.record.program.id,
+            ?msg.options.content_path,
+            ?msg.options.post_filters,
+        );
+        self.import_timeshift_record(msg, ctx).await
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn import_timeshift_record<C>(
+        &mut self,
+        msg: ImportTimeshiftRecord,
+        ctx: &C,
+    ) -> Result<Record, Error>
+    where
+        C: Spawn + EmitterFactory<TimeshiftRecordImported>,
+    {
+        if !self.config.recording.is_records_api_enabled() {
+            return Err(Error::WrongConfig(
+                "config.recording.records-dir must be specified",
+            ));
+        }
+
+        self.ensure_free_space().await?;
+
+        let TimeshiftRecordExport {
+            service,
+            record: timeshift_record,
+            source,
+        } = msg.export;
+        let program_id = timeshift_record.program.id;
+        let schedule = RecordingSchedule::new(
+            timeshift_record.program.clone(),
+            service,
+            msg.options,
+            msg.tags,
+        );
+
+        let video_tags: Vec<u8> = schedule
+            .program
+            .video
+            .iter()
+            .map(|video| video.component_tag)
+            .collect();
+        let audio_tags: Vec<u8> = schedule
+            .program
+            .audios
+            .values()
+            .map(|audio| audio.component_tag)
+            .collect();
+        let data = mustache::MapBuilder::new()
+            .insert_str("channel_name", &schedule.service.channel.name)
+            .insert("channel_type", &schedule.service.channel.channel_type)?
+            .insert_str("channel", &schedule.service.channel.channel)
+            .insert("sid", &program_id.sid().value())?
+            .insert("eid", &program_id.eid().value())?
+            .insert("video_tags", &video_tags)?
+            .insert("audio_tags", &audio_tags)?
+            .build();
+
+        let mut builder = FilterPipelineBuilder::new(data, false);
+        // The stream has already been decoded.
+        builder.add_post_filters(&self.config.post_filters, &schedule.options.post_filters)?;
+        let (filters, content_type, _) = builder.build();
+
+        let record_id = RecordId::from((Jst::now(), program_id));
+        let record_path = make_record_path(&self.config, &record_id).unwrap();
+        let content_path = make_content_path_from_schedule(&self.config, &record_id, &schedule);
+        // We assumed that schedule.content_path has already been normalized.
+        if let Some(dir) = content_path.parent() {
+            // Create missing directories if they don't exist.
+            tokio::fs::create_dir_all(dir).await?;
+        }
+
+        let (stream, stop_trigger) = source.create_stream(false).await?;
+        let pipeline = if filters.is_empty() {
+            None
+        } else {
+            Some(spawn_pipeline(filters, stream.id(), "recording", ctx)?)
         };
 
-        let mut changed = false;
-        let mut removed = vec![];
-        let mut rescheduled = vec![];
-        self.schedules.retain(|&program_id, schedule| {
-            if ServiceId::from(program_id) != service_id {
-                return true;
-            }
-            if !schedule.can_be_updated_by_epg() {
-                return true;
-            }
-            let program = match programs.get(&program_id.eid()) {
-                Some(program) => program,
-                None => {
-                    tracing::warn!(%schedule.program.id, "Removed from EPG");
-                    removed.push(program_id);
-                    changed = true;
-                    return false;
-                }
+        let relative_content_path = make_relative_content_path(&self.config, &content_path);
+        let record = Record::new(
+            record_id.clone(),
+            timeshift_record.start_time,
+            &schedule,
+            relative_content_path,
+            content_type,
+        );
+
+        let emitter = ctx.emitter();
+        let end_time = timeshift_record.end_time;
+        ctx.spawn_task(async move {
+            let result = copy_timeshift_record(stream, pipeline, &content_path).await;
+            drop(stop_trigger);
+            let msg = TimeshiftRecordImported {
+                record_id,
+                end_time,
+                result,
             };
-            if let Some(end_time) = program.end_at() {
-                // REMARK: `programs` contains TV programs already ended.
-                if end_time > now {
-                    if program.start_at != schedule.program.start_at {
-                        schedule.state = RecordingScheduleState::Scheduled;
-                        rescheduled.push(program_id);
-                    }
-                    schedule.program = program.clone();
-                    changed = true;
-                }
-            }
-            true
+            emitter.emit(msg).await;
         });
 
-        for &program_id in removed.iter() {
-            let reason = RecordingFailedReason::RemovedFromEpg;
-            self.emit_recording_failed(program_id, reason).await;
-        }
+        self.create_record(&record_path, &record).await;
 
-        for &program_id in rescheduled.iter() {
-            self.emit_recording_rescheduled(program_id).await;
-        }
+        Ok(record)
+    }
+}
 
-        changed
+// timeshift record imported
+
+#[derive(Message)]
+struct TimeshiftRecordImported {
+    record_id: RecordId,
+    end_time: DateTime<Jst>,
+    result: Result<(), RecordingFailedReason>,
+}
+
+#[async_trait]
+impl<T, E, O> Handler<TimeshiftRecordImported> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, msg: TimeshiftRecordImported, ctx: &mut Context<Self>) {
+        tracing::debug!(
+            msg.name = "TimeshiftRecordImported",
+            %msg.record_id,
+            ?msg.result,
+        );
+        let record_path = make_record_path(&self.config, &msg.record_id).unwrap();
+        let mut record = match load_record(&self.config, &record_path).await {
+            Ok((record, _)) => record,
+            Err(err) => {
+                // The record may have been removed while importing.
+                tracing::warn!(%err, %msg.record_id, "Failed to load record");
+                return;
+            }
+        };
+        record.recording_status = match msg.result {
+            Ok(_) => RecordingStatus::Finished,
+            Err(reason) => RecordingStatus::Failed { reason },
+        };
+        record.recording_end_time = Some(msg.end_time);
+        record.recording_duration = Some(msg.end_time - record.recording_start_time);
+        if file_util::save_json(&record, &record_path) {
+            tracing::info!(%record.id, ?record.recording_status, "Imported timeshift record");
+            self.emit_record_saved(record.id.clone(), record.recording_status.clone())
+                .await;
+            self.enqueue_post_process_jobs(&record).await;
+            self.dispatch_post_process_jobs(ctx).await;
+        } else {
+            tracing::error!(?record_path, "Failed to save");
+            self.emit_record_broken(record.id, "Failed to save record")
+                .await;
+        }
     }
 }
 
-// on-air program changed
+// query records
+
+#[derive(Message)]
+#[reply(Result<Vec<(Record, Option<u64>)>, Error>)]
+pub struct QueryRecords;
 
 #[async_trait]
-impl<T, E, O> Handler<onair::OnairProgramChanged> for RecordingManager<T, E, O>
+impl<T, E, O> Handler<QueryRecords> for RecordingManager<T, E, O>
 where
     T: Clone + Send + Sync + 'static,
     T: Call<StartStreaming>,
     T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
     E: Send + Sync + 'static,
     E: Call<QueryClock>,
     E: Call<QueryPrograms>,
@@ -2173,1060 +3180,5220 @@ where
     O: Clone + Send + Sync + 'static,
     O: Call<onair::RegisterEmitter>,
 {
-    async fn handle(&mut self, msg: onair::OnairProgramChanged, ctx: &mut Context<Self>) {
-        tracing::debug!(msg.name = "OnairProgramChanged");
+    async fn handle(
+        &mut self,
+        _msg: QueryRecords,
+        _ctx: &mut Context<Self>,
+    ) -> <QueryRecords as Message>::Reply {
+        tracing::debug!(msg.name = "QueryRecords");
+        self.query_records().await
+    }
+}
 
-        let mut changed = false;
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn query_records(&self) -> Result<Vec<(Record, Option<u64>)>, Error> {
+        let records_dir = match self.config.recording.records_dir.as_ref() {
+            Some(records_dir) => records_dir,
+            None => return Err(Error::WrongConfig("config.recording.records-dir")),
+        };
 
-        // The start time in EIT[p/f] may be undefined.
-        if let Some(program) = msg.current {
-            if program.start_at.is_some() {
-                changed = self.update_schedule_by_onair_program(program).await;
-            }
-        }
-        if let Some(program) = msg.next {
-            if program.start_at.is_some() {
-                changed = self.update_schedule_by_onair_program(program).await;
+        let mut records = vec![];
+        for record_path in glob_records(records_dir) {
+            match load_record(&self.config, &record_path).await {
+                Ok(tuple) => records.push(tuple),
+                Err(err) => tracing::warn!(?record_path, ?err, "Failed to load, skip"),
             }
         }
 
-        if changed {
-            self.save_schedules();
-            self.rebuild_queue();
-            self.set_timer(ctx);
-        }
+        Ok(records)
     }
 }
 
-impl<T, E, O> RecordingManager<T, E, O> {
-    async fn update_schedule_by_onair_program(&mut self, program: Arc<EpgProgram>) -> bool {
-        use RecordingScheduleState::*;
+// query record
 
-        let mut rescheduled = false;
+#[derive(Message)]
+#[reply(Result<(Record, Option<u64>), Error>)]
+pub struct QueryRecord {
+    pub id: RecordId,
+}
 
-        if let Some(schedule) = self.schedules.get_mut(&program.id) {
-            match schedule.state {
-                Scheduled | Rescheduling => {
-                    rescheduled = schedule.program.start_at != program.start_at;
-                    schedule.program = (*program).clone();
-                    schedule.state = Tracking;
-                    tracing::info!(
-                        %schedule.program.id,
-                        "Start tracking changes of the schedule",
-                    );
-                }
-                Tracking => {
-                    rescheduled = schedule.program.start_at != program.start_at;
-                    schedule.program = (*program).clone();
-                }
-                Recording => {
-                    schedule.program = (*program).clone();
-                    self.update_record(program.id).await;
-                }
-                Finished | Failed => {
-                    // Nothing to do.
-                }
-            }
-        }
+#[async_trait]
+impl<T, E, O> Handler<QueryRecord> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(
+        &mut self,
+        msg: QueryRecord,
+        _ctx: &mut Context<Self>,
+    ) -> <QueryRecord as Message>::Reply {
+        tracing::debug!(msg.name = "QueryRecord", %msg.id);
+        self.query_record(&msg.id).await
+    }
+}
 
-        if rescheduled {
-            self.emit_recording_rescheduled(program.id).await;
-        }
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn query_record(&self, id: &RecordId) -> Result<(Record, Option<u64>), Error> {
+        let record_path = match make_record_path(&self.config, id) {
+            Some(record_path) => record_path,
+            None => return Err(Error::WrongConfig("config.recording.records-dir")),
+        };
 
-        rescheduled
+        load_record(&self.config, &record_path).await
     }
 }
 
-// content source actor
+// query record offset
 
-struct ContentSource {
-    pipeline: CommandPipeline<RecordId>,
+// Maps a time within a record to the byte offset from the beginning of the content file.
+#[derive(Message)]
+#[reply(Result<u64, Error>)]
+pub struct QueryRecordOffset {
+    pub id: RecordId,
+    pub time: DateTime<Jst>,
 }
 
-impl ContentSource {
-    fn new<C: Spawn>(
-        config: &Config,
-        record: &Record,
-        range: Option<&ContentRange>,
-        ctx: &C,
-    ) -> Result<Self, Error> {
-        let content_path = make_content_path(config, record).unwrap();
-        if !content_path.exists() {
-            tracing::warn!(?content_path, "No such file, maybe it has been removed");
-            return Err(Error::NoContent);
-        }
+#[async_trait]
+impl<T, E, O> Handler<QueryRecordOffset> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(
+        &mut self,
+        msg: QueryRecordOffset,
+        _ctx: &mut Context<Self>,
+    ) -> <QueryRecordOffset as Message>::Reply {
+        tracing::debug!(msg.name = "QueryRecordOffset", %msg.id, %msg.time);
+        self.query_record_offset(&msg.id, msg.time).await
+    }
+}
 
-        let content_path_str = content_path.to_str().unwrap();
-        let cmd = match (range, &record.recording_status) {
-            (Some(range), _) => {
-                debug_assert!(range.is_partial());
-                let skip = range.first();
-                let count = range.bytes();
-                format!("dd if='{content_path_str}' ibs=1 skip={skip} count={count}")
-            }
-            (None, RecordingStatus::Recording) => {
-                // We use `tail -f` for streaming during recording in order to send data to be
-                // appended to the content file in the future after the stream reaches EOF at that
-                // point.
-                //
-                // NOTE: `tail` in macOS doesn't support `-s` option.  The default value of the
-                // sleep interval of `tail` in GNU coreutils is 1.0 second.
-                format!("tail -f -c +0 '{content_path_str}'")
-            }
-            _ => format!("cat '{content_path_str}'"),
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn query_record_offset(&self, id: &RecordId, time: DateTime<Jst>) -> Result<u64, Error> {
+        let (record, content_length) = self.query_record(id).await?;
+        let content_length = match content_length {
+            Some(content_length) if content_length > 0 => content_length,
+            _ => return Err(Error::NoContent),
         };
-
-        let pipeline = spawn_pipeline(vec![cmd], record.id.clone(), "content", ctx)?;
-
-        Ok(Self { pipeline })
+        load_content_index(&self.config, &record, content_length)
+            .await
+            .unwrap_or_else(|| ContentIndex::estimate(&record, content_length))
+            .find_offset(time)
+            .ok_or(Error::OutOfRange)
     }
+}
 
-    fn create_stream(&mut self, time_limit: u64) -> ContentStream {
-        // 32 KiB, large enough for 10 ms buffering.
-        const CHUNK_SIZE: usize = 4096 * 8;
-
-        let (_, output) = self.pipeline.take_endpoints();
+// query record content index
 
-        let stream = ReaderStream::with_capacity(output, CHUNK_SIZE)
-            // We set a time limit in order to stop streaming when the stream reaches the *true*
-            // EOF.  Because `tail -f` doesn't terminate when the stream reaches an EOF at that
-            // point.
-            //
-            // We cannot use a RecordingStopped emitter for this purpose.  Because the streaming
-            // has to continue in order to send remaining data until the *true* EOF reaches.
-            .timeout(std::time::Duration::from_millis(time_limit))
-            .map_while(Result::ok);
-        // TODO(#2057): ranges
-        MpegTsStream::new(self.pipeline.id().clone(), Box::pin(stream))
-    }
+// Returns the content index of a record, or `None` if the record has no usable index.
+#[derive(Message)]
+#[reply(Result<Option<ContentIndex>, Error>)]
+pub struct QueryRecordContentIndex {
+    pub id: RecordId,
 }
 
 #[async_trait]
-impl Actor for ContentSource {
-    async fn started(&mut self, _ctx: &mut Context<Self>) {
-        tracing::debug!(content_source.pipeline.id = %self.pipeline.id(), "Started");
-    }
-
-    async fn stopping(&mut self, _ctx: &mut Context<Self>) {
-        tracing::debug!(content_source.pipeline.id = %self.pipeline.id(), "Stopping...");
-        self.pipeline.kill();
+impl<T, E, O> Handler<QueryRecordContentIndex> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(
+        &mut self,
+        msg: QueryRecordContentIndex,
+        _ctx: &mut Context<Self>,
+    ) -> <QueryRecordContentIndex as Message>::Reply {
+        tracing::debug!(msg.name = "QueryRecordContentIndex", %msg.id);
+        self.query_record_content_index(&msg.id).await
     }
+}
 
-    async fn stopped(&mut self, _ctx: &mut Context<Self>) {
-        tracing::debug!(content_source.pipeline.id = %self.pipeline.id(), "Stopped");
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn query_record_content_index(
+        &self,
+        id: &RecordId,
+    ) -> Result<Option<ContentIndex>, Error> {
+        let (record, content_length) = self.query_record(id).await?;
+        let content_length = match content_length {
+            Some(content_length) if content_length > 0 => content_length,
+            _ => return Err(Error::NoContent),
+        };
+        Ok(load_content_index(&self.config, &record, content_length).await)
     }
 }
 
-// models
+// remove record
 
-#[derive(Debug, Eq)]
-struct QueueItem {
-    program_id: ProgramId,
-    start_at: DateTime<Jst>,
-    priority: i32,
+#[derive(Message)]
+#[reply(Result<(bool, bool), Error>)]
+pub struct RemoveRecord {
+    pub id: RecordId,
+    pub purge: bool,
 }
 
-impl Ord for QueueItem {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.start_at
-            .cmp(&other.start_at)
-            .reverse()
-            .then_with(|| self.priority.cmp(&other.priority))
+#[async_trait]
+impl<T, E, O> Handler<RemoveRecord> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(
+        &mut self,
+        msg: RemoveRecord,
+        _ctx: &mut Context<Self>,
+    ) -> <RemoveRecord as Message>::Reply {
+        tracing::debug!(msg.name = "RemoveRecord", %msg.id, msg.purge);
+        self.remove_record(&msg.id, msg.purge).await
     }
 }
 
-impl PartialEq for QueueItem {
-    fn eq(&self, other: &Self) -> bool {
-        self.program_id == other.program_id
-    }
-}
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn remove_record(&self, id: &RecordId, purge: bool) -> Result<(bool, bool), Error> {
+        let record_path = match make_record_path(&self.config, id) {
+            Some(record_path) => record_path,
+            None => return Err(Error::WrongConfig("config.recording.records-dir")),
+        };
 
-impl PartialOrd for QueueItem {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
+        let (record, _) = load_record(&self.config, &record_path).await?;
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct RecordingSchedule {
-    pub state: RecordingScheduleState,
-    pub program: EpgProgram,
-    pub service: EpgService,
-    pub options: RecordingOptions,
-    #[serde(default)]
-    pub tags: HashSet<String>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub failed_reason: Option<RecordingFailedReason>,
-}
+        if matches!(record.recording_status, RecordingStatus::Recording) {
+            tracing::warn!(
+                ?record_path,
+                "Cannot remove the record while it's recording"
+            );
+            return Err(Error::InvalidRequest("Now recording"));
+        }
 
-impl RecordingSchedule {
-    pub fn new(
-        program: EpgProgram,
-        service: EpgService,
-        options: RecordingOptions,
-        tags: HashSet<String>,
-    ) -> Self {
-        RecordingSchedule {
-            state: RecordingScheduleState::Scheduled,
-            program,
-            service,
-            options,
-            tags,
-            failed_reason: None,
+        let mut record_removed = false;
+        let mut content_removed = false;
+
+        if purge {
+            let content_path = make_content_path(&self.config, &record).unwrap();
+            if content_path.exists() {
+                match tokio::fs::remove_file(&content_path).await {
+                    Ok(_) => {
+                        content_removed = true;
+                        self.emit_content_removed(id.clone()).await;
+                    }
+                    Err(err) => tracing::error!(?err, ?content_path, "Failed to remove"),
+                }
+            } else {
+                tracing::warn!(?record_path, ?content_path, "No content file");
+                content_removed = true;
+            }
+
+            let log_path = make_log_path_from_content_path(&content_path);
+            if log_path.exists() {
+                if let Err(err) = tokio::fs::remove_file(&log_path).await {
+                    tracing::error!(?err, ?log_path, "Failed to remove");
+                }
+            }
         }
-    }
 
-    fn can_be_updated_by_epg(&self) -> bool {
-        use RecordingScheduleState::*;
-        matches!(self.state, Scheduled | Rescheduling)
-    }
+        debug_assert!(record_path.exists());
+        match tokio::fs::remove_file(&record_path).await {
+            Ok(_) => {
+                record_removed = true;
+                self.emit_record_removed(id.clone()).await;
+            }
+            Err(err) => tracing::error!(?err, ?record_path),
+        }
 
-    fn is_ready_for_recording(&self) -> bool {
-        use RecordingScheduleState::*;
-        matches!(self.state, Scheduled | Tracking)
-    }
+        let index_path = make_index_path(&self.config, id).unwrap();
+        if index_path.exists()
+            && let Err(err) = tokio::fs::remove_file(&index_path).await
+        {
+            tracing::error!(?err, ?index_path, "Failed to remove");
+        }
 
-    fn is_recording(&self) -> bool {
-        use RecordingScheduleState::*;
-        matches!(self.state, Recording)
+        Ok((record_removed, content_removed))
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[serde(rename_all = "kebab-case")]
-#[derive(ToSchema)]
-#[schema(title = "RecordingScheduleState")]
-pub enum RecordingScheduleState {
-    Scheduled,
-    // This state is used only when on-air program manager is available for the
-    // service of the target TV program.  In this case, the state of the
-    // schedule always transits to `recording` via `tracking`.
-    Tracking,
-    Recording,
-    // When the recording fails, the schedule transits to this state.  If on-air
-    // program tracker is available, the schedule may be rescheduled when an EIT
-    // section ([schedule] or [p/f]) for the target TV program is emitted.
-    // Otherwise, it will be expired eventually.
-    Rescheduling,
-    // The recording finished successfully.
-    Finished,
-    // The recording failed for some reason.
-    Failed,
-}
+// open content
 
-/// Recording options.
-#[derive(Clone, Debug, Deserialize, Serialize)]
-#[serde(rename_all = "camelCase")]
-#[derive(ToSchema)]
-#[schema(title = "RecordingOptions")]
-pub struct RecordingOptions {
-    /// The path of the content file relative to `config.recording.basedir`.
-    ///
-    /// The path must be a valid Unicode string.
-    ///
-    /// ### If `config.recording.records-dir` is NOT specified
-    ///
-    /// This is a required option.  A response with the status code 401 will be replied if this
-    /// option is not specified.
-    ///
-    /// ### If `config.recording.records-dir` is specified
-    ///
-    /// An auto-generated filename will be used for the content file if this option is not
-    /// specified.
-    #[schema(value_type = Option<String>)]
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub content_path: Option<PathBuf>,
-
-    /// A priority of tuner usage.
-    #[serde(default)]
-    pub priority: i32,
-
-    /// A list of pre-filters to use.
-    #[serde(default)]
-    pub pre_filters: Vec<String>,
-
-    /// A list of post-filters to use.
-    #[serde(default)]
-    pub post_filters: Vec<String>,
+type BoxedStream = Pin<Box<dyn Stream<Item = std::io::Result<Bytes>> + Send>>;
+type ContentStream = MpegTsStream<RecordId, BoxedStream>;
+type StopTrigger = Trigger<actlet::Stop>;
 
-    /// Log filter of the recording schedule.
-    ///
-    /// If this option is not specified, the value of `config.recording.log-filter` is used when
-    /// the recording starts.  The log filter will be set to the environment variable
-    /// `MIRAKC_ARIB_LOG` passed to the recording command pipeline.
-    ///
-    /// If the log filter is neither empty nor `off`, logs coming from the recording command
-    /// pipeline will be stored into a log file.  The log file will be placed in the same folder as
-    /// the content file and its name is "<content-file>.log".  The environment variable
-    /// `MIRAKC_ARIB_LOG_NO_TIMESTAMP` will be disabled and each log will have a timestamp.
-    ///
-    /// If the log filter is empty or `off`, no log will come from the recording command pipeline.
-    /// And the log file won't be created.
-    ///
-    /// For backward compatibility with 3.x and older versions, the logs will be output to STDOUT
-    /// if neither this option nor `recording.log-filter` is specified.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub log_filter: Option<String>,
+#[derive(Message)]
+#[reply(Result<(ContentStream, Option<StopTrigger>), Error>)]
+pub struct OpenContent {
+    pub id: RecordId,
+    pub range: Option<ContentRange>,
+    pub offset: u64,
+    pub time_limit: u64,
 }
 
-struct Recorder {
-    started_at: DateTime<Jst>,
-    pipeline: CommandPipeline<TunerSubscriptionId>,
-    stop_trigger: Option<Trigger<StopStreaming>>,
-    content_type: String,
-}
+impl OpenContent {
+    // See the `tail` command used in `ContentSource::new()` for the reason why the time limit is
+    // 1500ms.
+    const DEFAULT_TIME_LIMIT: u64 = 1500;
 
-impl Recorder {
-    fn get_model(&self, program_id: ProgramId) -> RecorderModel {
-        RecorderModel {
-            program_id,
-            started_at: self.started_at,
-            pipeline: self.pipeline.get_model(),
+    pub fn new(id: RecordId, range: Option<ContentRange>) -> Self {
+        Self {
+            id,
+            range,
+            offset: 0,
+            time_limit: Self::DEFAULT_TIME_LIMIT,
+        }
+    }
+
+    // Unlike a range, the stream from the offset continues until the *true* EOF while recording.
+    pub fn with_offset(id: RecordId, offset: u64) -> Self {
+        Self {
+            offset,
+            ..Self::new(id, None)
         }
     }
 }
 
-pub struct RecorderModel {
-    pub program_id: ProgramId,
-    pub started_at: DateTime<Jst>,
-    pub pipeline: Vec<CommandPipelineProcessModel>,
+#[async_trait]
+impl<T, E, O> Handler<OpenContent> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(
+        &mut self,
+        msg: OpenContent,
+        ctx: &mut Context<Self>,
+    ) -> <OpenContent as Message>::Reply {
+        tracing::debug!(msg.name = "OpenContent", %msg.id);
+        self.open_content(&msg.id, msg.range.as_ref(), msg.offset, msg.time_limit, ctx)
+            .await
+    }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
-pub struct RecordId(String);
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn open_content(
+        &self,
+        id: &RecordId,
+        range: Option<&ContentRange>,
+        offset: u64,
+        time_limit: u64,
+        ctx: &Context<Self>,
+    ) -> Result<(ContentStream, Option<StopTrigger>), Error> {
+        let record_path = match make_record_path(&self.config, id) {
+            Some(record_path) => record_path,
+            None => return Err(Error::WrongConfig("config.recording.records-dir")),
+        };
 
-impl RecordId {
-    fn new(timestamp: i64, program_id: u64) -> Self {
-        Self(format!("{timestamp:016X}{program_id:016X}"))
-    }
+        let (record, content_length) = load_record(&self.config, &record_path).await?;
+        let _content_length = match content_length {
+            Some(content_length) if content_length > 0 => content_length,
+            _ => return Err(Error::NoContent),
+        };
 
-    pub fn value(&self) -> &str {
-        &self.0
-    }
-}
+        let mut content_source = ContentSource::new(&self.config, &record, range, offset, ctx)?;
+        let stream = content_source.create_stream(time_limit);
 
-impl From<(DateTime<Jst>, ProgramId)> for RecordId {
-    fn from(value: (DateTime<Jst>, ProgramId)) -> Self {
-        Self::new(value.0.timestamp_millis(), value.1.value())
+        let addr = ctx.spawn_actor(content_source).await;
+        let stop_trigger = Some(addr.trigger(actlet::Stop));
+
+        Ok((stream, stop_trigger))
     }
 }
 
-impl From<String> for RecordId {
-    fn from(value: String) -> Self {
-        Self(value)
-    }
+// register emitter
+
+#[derive(Message)]
+#[reply(usize)]
+pub enum RegisterEmitter {
+    RecordingStarted(Emitter<RecordingStarted>),
+    RecordingStopped(Emitter<RecordingStopped>),
+    RecordingFailed(Emitter<RecordingFailed>),
+    RecordingRescheduled(Emitter<RecordingRescheduled>),
+    RecordSaved(Emitter<RecordSaved>),
+    RecordRemoved(Emitter<RecordRemoved>),
+    ContentRemoved(Emitter<ContentRemoved>),
+    RecordBroken(Emitter<RecordBroken>),
 }
 
-impl std::fmt::Display for RecordId {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "record#{}", self.0)
+#[async_trait]
+impl<T, E, O> Handler<RegisterEmitter> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(
+        &mut self,
+        msg: RegisterEmitter,
+        ctx: &mut Context<Self>,
+    ) -> <RegisterEmitter as Message>::Reply {
+        debug_assert!(self.config.recording.is_enabled());
+        match msg {
+            RegisterEmitter::RecordingStarted(emitter) => {
+                let id = self.recording_started.register(emitter.clone());
+                tracing::debug!(msg.name = "RegisterEmitter::RecordingStarted", id);
+                if id != 0 {
+                    // Sending many messages in the message handler may cause a dead lock
+                    // when the number of messages to be sent is larger than the capacity
+                    // of the emitter's channel.  See the issue #705 for example.
+                    ctx.set_post_process(RegisterEmitterPostProcess(emitter));
+                }
+                id
+            }
+            RegisterEmitter::RecordingStopped(emitter) => {
+                let id = self.recording_stopped.register(emitter);
+                tracing::debug!(msg.name = "RegisterEmitter::RecordingStopped", id);
+                id
+            }
+            RegisterEmitter::RecordingFailed(emitter) => {
+                let id = self.recording_failed.register(emitter);
+                tracing::debug!(msg.name = "RegisterEmitter::RecordingFailed", id);
+                id
+            }
+            RegisterEmitter::RecordingRescheduled(emitter) => {
+                let id = self.recording_rescheduled.register(emitter);
+                tracing::debug!(msg.name = "RegisterEmitter::RecordingRescheduled", id);
+                id
+            }
+            RegisterEmitter::RecordSaved(emitter) => {
+                debug_assert!(self.config.recording.is_records_api_enabled());
+                // Create a task to send messages.
+                //
+                // Sending many messages in the message handler may cause a dead lock
+                // when the number of messages to be sent is larger than the capacity
+                // of the emitter's channel.  See the issue #705 for example.
+                let task =
+                    Self::emit_record_saved_for_each_record(self.config.clone(), emitter.clone());
+                ctx.spawn_task(task);
+                let id = self.record_saved.register(emitter);
+                tracing::debug!(msg.name = "RegisterEmitter::RecordSaved", id);
+                id
+            }
+            RegisterEmitter::RecordBroken(emitter) => {
+                debug_assert!(self.config.recording.is_records_api_enabled());
+                let id = self.record_broken.register(emitter);
+                tracing::debug!(msg.name = "RegisterEmitter::RecordBroken", id);
+                id
+            }
+            RegisterEmitter::RecordRemoved(emitter) => {
+                debug_assert!(self.config.recording.is_records_api_enabled());
+                let id = self.record_removed.register(emitter);
+                tracing::debug!(msg.name = "RegisterEmitter::RecordRemoved", id);
+                id
+            }
+            RegisterEmitter::ContentRemoved(emitter) => {
+                debug_assert!(self.config.recording.is_records_api_enabled());
+                let id = self.content_removed.register(emitter);
+                tracing::debug!(msg.name = "RegisterEmitter::ContentRemoved", id);
+                id
+            }
+        }
     }
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct Record {
-    pub id: RecordId,
-    pub program: EpgProgram,
-    pub service: EpgService,
-    pub options: RecordingOptions,
-    #[serde(default)]
-    pub tags: HashSet<String>,
-    pub recording_status: RecordingStatus,
-    #[serde(with = "ts_milliseconds")]
-    pub recording_start_time: DateTime<Jst>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    #[serde(with = "ts_milliseconds_option")]
-    pub recording_end_time: Option<DateTime<Jst>>,
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    #[serde(with = "duration_milliseconds_option")]
-    pub recording_duration: Option<Duration>,
-    pub content_path: PathBuf,
-    pub content_type: String,
-}
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn emit_record_saved_for_each_record(config: Arc<Config>, emitter: Emitter<RecordSaved>) {
+        let records_dir = config.recording.records_dir.as_ref().unwrap();
+        for record_path in glob_records(records_dir) {
+            match load_record(&config, &record_path).await {
+                Ok((record, _)) => {
+                    let msg = RecordSaved {
+                        record_id: record.id,
+                        recording_status: record.recording_status,
+                    };
+                    emitter.emit(msg).await;
+                }
+                Err(err) => tracing::error!(?record_path, ?err, "Failed to load, skip"),
+            }
+        }
+    }
+}
+
+#[derive(Message)]
+pub struct RegisterEmitterPostProcess(Emitter<RecordingStarted>);
+
+#[async_trait]
+impl<T, E, O> Handler<RegisterEmitterPostProcess> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, msg: RegisterEmitterPostProcess, _ctx: &mut Context<Self>) {
+        let emitter = msg.0;
+        for &program_id in self.recorders.keys() {
+            let msg = RecordingStarted { program_id };
+            emitter.emit(msg).await;
+        }
+    }
+}
+
+// unregister emitter
+
+#[derive(Message)]
+pub enum UnregisterEmitter {
+    RecordingStarted(usize),
+    RecordingStopped(usize),
+    RecordingFailed(usize),
+    RecordingRescheduled(usize),
+    RecordSaved(usize),
+    RecordRemoved(usize),
+    ContentRemoved(usize),
+    RecordBroken(usize),
+}
+
+#[async_trait]
+impl<T, E, O> Handler<UnregisterEmitter> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, msg: UnregisterEmitter, _ctx: &mut Context<Self>) {
+        debug_assert!(self.config.recording.is_enabled());
+        match msg {
+            UnregisterEmitter::RecordingStarted(id) => {
+                tracing::debug!(msg.name = "UnregisterEmitter::RecordingStarted", id);
+                self.recording_started.unregister(id);
+            }
+            UnregisterEmitter::RecordingStopped(id) => {
+                tracing::debug!(msg.name = "UnregisterEmitter::RecordingStopped", id);
+                self.recording_stopped.unregister(id);
+            }
+            UnregisterEmitter::RecordingFailed(id) => {
+                tracing::debug!(msg.name = "UnregisterEmitter::RecordingFailed", id);
+                self.recording_failed.unregister(id);
+            }
+            UnregisterEmitter::RecordingRescheduled(id) => {
+                tracing::debug!(msg.name = "UnregisterEmitter::RecordingRescheduled", id);
+                self.recording_rescheduled.unregister(id);
+            }
+            UnregisterEmitter::RecordSaved(id) => {
+                debug_assert!(self.config.recording.is_records_api_enabled());
+                tracing::debug!(msg.name = "UnregisterEmitter::RecordSaved", id);
+                self.record_saved.unregister(id);
+            }
+            UnregisterEmitter::RecordBroken(id) => {
+                debug_assert!(self.config.recording.is_records_api_enabled());
+                tracing::debug!(msg.name = "UnregisterEmitter::RecordBroken", id);
+                self.record_broken.unregister(id);
+            }
+            UnregisterEmitter::RecordRemoved(id) => {
+                debug_assert!(self.config.recording.is_records_api_enabled());
+                tracing::debug!(msg.name = "UnregisterEmitter::RecordRemoved", id);
+                self.record_removed.unregister(id);
+            }
+            UnregisterEmitter::ContentRemoved(id) => {
+                debug_assert!(self.config.recording.is_records_api_enabled());
+                tracing::debug!(msg.name = "UnregisterEmitter::ContentRemoved", id);
+                self.content_removed.unregister(id);
+            }
+        }
+    }
+}
+
+// recording started
+
+#[derive(Clone, Message)]
+pub struct RecordingStarted {
+    pub program_id: ProgramId,
+}
+
+#[async_trait]
+impl<T, E, O> Handler<RecordingStarted> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, msg: RecordingStarted, _ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "RecordingStarted", %msg.program_id);
+        self.handle_recording_started(msg.program_id).await;
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn handle_recording_started(&self, program_id: ProgramId) {
+        tracing::info!(
+            schedule.program.id = %program_id,
+            "Recording started",
+        );
+        let msg = RecordingStarted { program_id };
+        self.recording_started.emit(msg).await;
+    }
+}
+
+// recording stopped
+
+#[derive(Clone, Message)]
+pub struct RecordingStopped {
+    pub program_id: ProgramId,
+}
+
+#[async_trait]
+impl<T, E, O> Handler<RecordingStopped> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, msg: RecordingStopped, ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "RecordingStopped", %msg.program_id);
+        let mut changed = self.handle_recording_stopped(msg.program_id).await;
+        if self.is_waiting_for_retry(msg.program_id) {
+            self.retry_recording(msg.program_id, ctx.address().clone(), ctx)
+                .await;
+            changed = true;
+        }
+        if changed {
+            self.save_schedules();
+        }
+        self.dispatch_post_process_jobs(ctx).await;
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn handle_recording_stopped(&mut self, program_id: ProgramId) -> bool {
+        // The schedule is NOT removed for a while so that an external script
+        // listening recording events can access the details of the schedule.
+        //
+        // It will be removed in the `ProcessRecording` message handler.
+
+        // The schedule may have been removed before the recording stops.
+        // For example, `clear_schedules()` clears schedules before the
+        // recordings stop.
+        let maybe_schedule = self.schedules.get_mut(&program_id);
+
+        let mut changed = false;
+        let mut retry = false;
+
+        let recorder = match self.recorders.get_mut(&program_id) {
+            Some(recorder) => recorder,
+            None => {
+                tracing::error!(
+                    schedule.program.id = %program_id,
+                    "INCONSNSTENT: The recorder stopped before the recording stopped",
+                );
+                let msg = RecordingStopped { program_id };
+                self.recording_stopped.emit(msg).await;
+                return changed;
+            }
+        };
+
+        // Manually drop the stop trigger so that we get the exit code
+        // from the program-filter without killing its process.
+        //
+        // The stop trigger has already been dropped if the recording was
+        // stopped by a `StopRecording` message.  The recording must not be
+        // retried in this case.
+        let stopped_by_request = match recorder.stop() {
+            Some(stop_trigger) => {
+                drop(stop_trigger);
+                false
+            }
+            None => true,
+        };
+
+        let results = recorder.pipeline.wait().await;
+        let failed_reason = match recorder.preempted.take() {
+            Some(reason) => {
+                tracing::error!(
+                    schedule.program.id = %program_id,
+                    ?reason,
+                    "The recording was preempted",
+                );
+                if let (
+                    Some(log_path),
+                    RecordingFailedReason::Preempted {
+                        tuner_index,
+                        replaced_by,
+                        priority,
+                    },
+                ) = (&recorder.log_path, &reason)
+                {
+                    // Otherwise, logs from the pipeline end without any explanation.
+                    let message = format!(
+                        "Recording stopped: tuner#{tuner_index} was grabbed by {replaced_by} \
+                         (priority: {priority})"
+                    );
+                    append_log(log_path, &message).await;
+                }
+                Some(reason)
+            }
+            None => get_first_error(&results).map(|exit_code| {
+                tracing::error!(
+                    %exit_code,
+                    schedule.program.id = %program_id,
+                    "The recording pipeline terminated abnormally",
+                );
+                RecordingFailedReason::PipelineError { exit_code }
+            }),
+        };
+        if check_retry(&results) {
+            tracing::error!(
+                schedule.program.id = %program_id,
+                "Recording stopped before the TV program starts",
+            );
+            if let Some(schedule) = maybe_schedule {
+                tracing::warn!(
+                    %schedule.program.id,
+                    "Need rescheduling",
+                );
+                schedule.state = RecordingScheduleState::Rescheduling;
+                changed = true;
+                self.emit_recording_failed(program_id, RecordingFailedReason::NeedRescheduling)
+                    .await;
+            }
+        } else if let Some(reason) = failed_reason {
+            if let Some(schedule) = maybe_schedule {
+                if let Some(attempt) = schedule.attempts.last_mut() {
+                    attempt.failed_reason = Some(reason.clone());
+                }
+                if !stopped_by_request && can_retry_recording(&self.config, schedule, Jst::now()) {
+                    tracing::warn!(
+                        %schedule.program.id,
+                        attempts = schedule.attempts.len(),
+                        "Retry recording on another tuner",
+                    );
+                    retry = true;
+                } else {
+                    schedule.state = RecordingScheduleState::Failed;
+                    schedule.failed_reason = Some(reason.clone());
+                }
+                changed = true;
+            }
+            if !retry {
+                self.emit_recording_failed(program_id, reason).await;
+            }
+        } else {
+            tracing::info!(
+                schedule.program.id = %program_id,
+                "The recording finished successfully",
+            );
+            if let Some(schedule) = maybe_schedule {
+                schedule.state = RecordingScheduleState::Finished;
+                changed = true;
+            }
+        }
+
+        if let Some(record) = self.update_record(program_id).await {
+            // Post-process jobs will run after the last attempt.
+            if !retry {
+                self.enqueue_post_process_jobs(&record).await;
+            }
+        }
+
+        // Unlike the schedule, the recorder should be removed after the
+        // recording stopped.
+        self.recorders.remove(&program_id);
+
+        // TODO: Save recording logs to a file.
+        let msg = RecordingStopped { program_id };
+        self.recording_stopped.emit(msg).await;
+
+        changed
+    }
+
+    // A recording schedule in the `Recording` state without a recorder is
+    // waiting for a retry.
+    fn is_waiting_for_retry(&self, program_id: ProgramId) -> bool {
+        self.schedules
+            .get(&program_id)
+            .is_some_and(|schedule| schedule.is_recording())
+            && !self.recorders.contains_key(&program_id)
+    }
+}
+
+// recording failed
+
+#[derive(Clone, Message)]
+pub struct RecordingFailed {
+    pub program_id: ProgramId,
+    pub reason: RecordingFailedReason,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum RecordingFailedReason {
+    #[serde(rename_all = "camelCase")]
+    StartRecordingFailed {
+        message: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    IoError {
+        message: String,
+        os_error: Option<i32>,
+    },
+    #[serde(rename_all = "camelCase")]
+    PipelineError {
+        exit_code: i32,
+    },
+    #[serde(rename_all = "camelCase")]
+    Preempted {
+        tuner_index: usize,
+        replaced_by: String,
+        priority: i32,
+    },
+    NeedRescheduling,
+    ScheduleExpired,
+    RemovedFromEpg,
+}
+
+/// An attempt to start a recording on a tuner.
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(title = "RecordingAttempt")]
+pub struct RecordingAttempt {
+    /// The index of the tuner used in the attempt.
+    pub tuner_index: usize,
+
+    /// The time when the attempt started in UNIX time (milliseconds).
+    #[serde(with = "ts_milliseconds")]
+    #[schema(value_type = i64)]
+    pub started_at: DateTime<Jst>,
+
+    /// The reason for the failure of the attempt.
+    ///
+    /// Undefined if the attempt has not failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed_reason: Option<RecordingFailedReason>,
+}
+
+#[async_trait]
+impl<T, E, O> Handler<RecordingFailed> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, msg: RecordingFailed, _ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "RecordingFailed", %msg.program_id, ?msg.reason);
+        self.emit_recording_failed(msg.program_id, msg.reason).await;
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn emit_recording_failed(&self, program_id: ProgramId, reason: RecordingFailedReason) {
+        let msg = RecordingFailed {
+            program_id,
+            reason: reason.clone(),
+        };
+        self.recording_failed.emit(msg).await;
+    }
+}
+
+// recording rescheduled
+
+#[derive(Clone, Message)]
+pub struct RecordingRescheduled {
+    pub program_id: ProgramId,
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn emit_recording_rescheduled(&self, program_id: ProgramId) {
+        let msg = RecordingRescheduled { program_id };
+        self.recording_rescheduled.emit(msg).await;
+    }
+}
+
+// record saved
+
+#[derive(Clone, Message)]
+pub struct RecordSaved {
+    pub record_id: RecordId,
+    pub recording_status: RecordingStatus,
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn emit_record_saved(&self, record_id: RecordId, recording_status: RecordingStatus) {
+        let msg = RecordSaved {
+            record_id,
+            recording_status,
+        };
+        self.record_saved.emit(msg).await;
+    }
+}
+
+// record broken
+
+#[derive(Clone, Message)]
+pub struct RecordBroken {
+    pub record_id: RecordId,
+    pub reason: &'static str,
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn emit_record_broken(&self, record_id: RecordId, reason: &'static str) {
+        let msg = RecordBroken { record_id, reason };
+        self.record_broken.emit(msg).await;
+    }
+}
+
+// record removed
+
+#[derive(Clone, Message)]
+pub struct RecordRemoved {
+    pub record_id: RecordId,
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn emit_record_removed(&self, record_id: RecordId) {
+        let msg = RecordRemoved { record_id };
+        self.record_removed.emit(msg).await;
+    }
+}
+
+// content removed
+
+#[derive(Clone, Message)]
+pub struct ContentRemoved {
+    pub record_id: RecordId,
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn emit_content_removed(&self, record_id: RecordId) {
+        let msg = ContentRemoved { record_id };
+        self.content_removed.emit(msg).await;
+    }
+}
+
+// tuner events
+
+#[async_trait]
+impl<T, E, O> Handler<tuner::Event> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, msg: tuner::Event, _ctx: &mut Context<Self>) {
+        match msg {
+            tuner::Event::ReservationsChanged(reservations) => {
+                tracing::debug!(
+                    msg.name = "tuner::Event::ReservationsChanged",
+                    reservations.len = reservations.len()
+                );
+                self.tuner_reservations = reservations;
+                self.update_conflicts();
+            }
+            tuner::Event::Preempted {
+                tuner_index,
+                user,
+                replaced_by,
+            } => {
+                let program_id = match user.info {
+                    TunerUserInfo::Recorder(program_id) => program_id,
+                    _ => return,
+                };
+                tracing::debug!(
+                    msg.name = "tuner::Event::Preempted",
+                    tuner.index = tuner_index,
+                    %program_id,
+                    %replaced_by.info,
+                );
+                self.handle_recorder_preempted(program_id, tuner_index, replaced_by);
+            }
+            _ => (),
+        }
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    fn handle_recorder_preempted(
+        &mut self,
+        program_id: ProgramId,
+        tuner_index: usize,
+        replaced_by: TunerUser,
+    ) {
+        let recorder = match self.recorders.get_mut(&program_id) {
+            Some(recorder) => recorder,
+            None => return,
+        };
+        // Ignore events for previous attempts.
+        if recorder.pipeline.id().tuner_index() != tuner_index {
+            return;
+        }
+        tracing::warn!(
+            schedule.program.id = %program_id,
+            tuner.index = tuner_index,
+            %replaced_by.info,
+            %replaced_by.priority,
+            "The tuner was grabbed by another user",
+        );
+        let replaced_by = replaced_by.get_mirakurun_model();
+        recorder.preempted = Some(RecordingFailedReason::Preempted {
+            tuner_index,
+            replaced_by: replaced_by.id,
+            priority: replaced_by.priority,
+        });
+    }
+}
+
+// services updated
+
+#[async_trait]
+impl<T, E, O> Handler<epg::ServicesUpdated> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, msg: epg::ServicesUpdated, ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "ServicesUpdated");
+        let changed = self.update_schedules_by_epg_services(&msg.services).await;
+        if changed {
+            self.save_schedules();
+            self.rebuild_queue();
+            self.set_timer(ctx);
+        }
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn update_schedules_by_epg_services(
+        &mut self,
+        services: &IndexMap<ServiceId, epg::EpgService>,
+    ) -> bool {
+        let mut changed = false;
+        let mut removed = vec![];
+        self.schedules.retain(|&program_id, schedule| {
+            if !schedule.can_be_updated_by_epg() {
+                return true;
+            }
+            let service_id = ServiceId::from(program_id);
+            match services.get(&service_id) {
+                Some(service) => {
+                    if schedule.service != *service {
+                        schedule.service = service.clone();
+                        changed = true;
+                    }
+                    true
+                }
+                None => {
+                    tracing::warn!(%schedule.program.id, "Removed from EPG");
+                    removed.push(program_id);
+                    changed = true;
+                    false
+                }
+            }
+        });
+
+        for &program_id in removed.iter() {
+            if self.recorders.contains_key(&program_id) {
+                let _ = self.stop_recorder(program_id);
+            }
+            self.emit_recording_failed(program_id, RecordingFailedReason::RemovedFromEpg)
+                .await;
+        }
+
+        changed
+    }
+}
+
+// programs updated
+
+#[async_trait]
+impl<T, E, O> Handler<epg::ProgramsUpdated> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, msg: epg::ProgramsUpdated, ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "ProgramsUpdated", %msg.service_id);
+        let now = Jst::now();
+        let mut changed = self
+            .update_schedules_by_epg_programs(now, msg.service_id)
+            .await;
+        changed |= self
+            .add_schedules_by_epg_programs(now, msg.service_id)
+            .await;
+        changed |= self.add_schedules_by_series(now, msg.service_id).await;
+        changed |= self
+            .expand_recurring_schedules_for_service(now, msg.service_id)
+            .await;
+        if changed {
+            self.save_schedules();
+            self.rebuild_queue();
+            self.set_timer(ctx);
+        }
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O>
+where
+    E: Call<QueryPrograms>,
+{
+    async fn update_schedules_by_epg_programs(
+        &mut self,
+        now: DateTime<Jst>,
+        service_id: ServiceId,
+    ) -> bool {
+        let programs = match self.epg.call(QueryPrograms { service_id }).await {
+            Ok(programs) => programs,
+            Err(err) => {
+                tracing::error!(%err, %service_id, "Failed to update schedules");
+                return false;
+            }
+        };
+
+        let mut changed = false;
+        let mut removed = vec![];
+        let mut rescheduled = vec![];
+        self.schedules.retain(|&program_id, schedule| {
+            if ServiceId::from(program_id) != service_id {
+                return true;
+            }
+            if !schedule.can_be_updated_by_epg() {
+                return true;
+            }
+            let program = match programs.get(&program_id.eid()) {
+                Some(program) => program,
+                None => {
+                    tracing::warn!(%schedule.program.id, "Removed from EPG");
+                    removed.push(program_id);
+                    changed = true;
+                    return false;
+                }
+            };
+            if let Some(end_time) = program.end_at() {
+                // REMARK: `programs` contains TV programs already ended.
+                if end_time > now {
+                    if program.start_at != schedule.program.start_at {
+                        schedule.state = RecordingScheduleState::Scheduled;
+                        rescheduled.push(program_id);
+                    }
+                    schedule.program = program.clone();
+                    changed = true;
+                }
+            }
+            true
+        });
+
+        for &program_id in removed.iter() {
+            let reason = RecordingFailedReason::RemovedFromEpg;
+            self.emit_recording_failed(program_id, reason).await;
+        }
+
+        for &program_id in rescheduled.iter() {
+            self.emit_recording_rescheduled(program_id).await;
+        }
+
+        changed
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O>
+where
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+{
+    async fn add_schedules_by_epg_programs(
+        &mut self,
+        now: DateTime<Jst>,
+        service_id: ServiceId,
+    ) -> bool {
+        let rules = self
+            .rules
+            .values()
+            .filter(|rule| rule.conditions.matches_service(service_id))
+            .cloned()
+            .collect_vec();
+        if rules.is_empty() {
+            return false;
+        }
+
+        let service = match self.epg.call(QueryService { service_id }).await {
+            Ok(Ok(service)) => service,
+            Ok(Err(err)) => {
+                tracing::warn!(%err, %service_id, "Failed to apply rules");
+                return false;
+            }
+            Err(err) => {
+                tracing::error!(%err, %service_id, "Failed to apply rules");
+                return false;
+            }
+        };
+
+        self.add_schedules_by_rules(now, &service, &rules).await
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O>
+where
+    E: Call<QueryPrograms>,
+{
+    async fn add_schedules_by_rules(
+        &mut self,
+        now: DateTime<Jst>,
+        service: &EpgService,
+        rules: &[RecordingRule],
+    ) -> bool {
+        let service_id = service.id;
+        let programs = match self.epg.call(QueryPrograms { service_id }).await {
+            Ok(programs) => programs,
+            Err(err) => {
+                tracing::error!(%err, %service_id, "Failed to apply rules");
+                return false;
+            }
+        };
+
+        // Forget ignored programs which have been removed from EPG.
+        let mut rules_changed = false;
+        for rule in rules.iter() {
+            if let Some(rule) = self.rules.get_mut(&rule.id) {
+                let len = rule.ignored_programs.len();
+                rule.ignored_programs.retain(|&program_id| {
+                    ServiceId::from(program_id) != service_id
+                        || programs.contains_key(&program_id.eid())
+                });
+                rules_changed |= rule.ignored_programs.len() != len;
+            }
+        }
+        if rules_changed {
+            self.save_rules();
+        }
+
+        let matchers = rules
+            .iter()
+            .filter_map(|rule| match rule.conditions.matcher() {
+                Ok(matcher) => Some((rule, matcher)),
+                Err(err) => {
+                    tracing::warn!(%err, %rule.id, "Broken rule, skip");
+                    None
+                }
+            })
+            .collect_vec();
+
+        let mut schedules = vec![];
+        for program in programs.values() {
+            match program.start_at {
+                Some(start_at) if start_at > now => (),
+                _ => continue,
+            }
+            if self.schedules.contains_key(&program.id) {
+                continue;
+            }
+            let found = matchers.iter().find(|(rule, matcher)| {
+                !rule.ignored_programs.contains(&program.id) && matcher.matches(program)
+            });
+            if let Some((rule, _)) = found {
+                tracing::info!(%rule.id, %program.id, "Matched");
+                let mut schedule = RecordingSchedule::new(
+                    program.clone(),
+                    service.clone(),
+                    rule.options.clone(),
+                    rule.tags.clone(),
+                );
+                schedule.rule_id = Some(rule.id);
+                schedules.push(schedule);
+            }
+        }
+
+        let changed = !schedules.is_empty();
+        for schedule in schedules.into_iter() {
+            // Never fails because we checked it above.
+            let _ = self.add_schedule(schedule);
+        }
+        changed
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O>
+where
+    E: Call<QueryPrograms>,
+{
+    async fn add_schedules_by_series(&mut self, now: DateTime<Jst>, service_id: ServiceId) -> bool {
+        self.remove_finished_series_followers(now);
+
+        let followers: HashMap<u16, SeriesFollower> = self
+            .series_followers
+            .values()
+            .filter(|follower| follower.service.id == service_id)
+            .map(|follower| (follower.series_id, follower.clone()))
+            .collect();
+        if followers.is_empty() {
+            return false;
+        }
+
+        let programs = match self.epg.call(QueryPrograms { service_id }).await {
+            Ok(programs) => programs,
+            Err(err) => {
+                tracing::error!(%err, %service_id, "Failed to follow series");
+                return false;
+            }
+        };
+
+        let mut schedules = vec![];
+        for program in programs.values() {
+            let start_at = match program.start_at {
+                Some(start_at) if start_at > now => start_at,
+                _ => continue,
+            };
+            if self.schedules.contains_key(&program.id) {
+                continue;
+            }
+            let series = match program.series {
+                Some(ref series) => series,
+                None => continue,
+            };
+            let follower = match followers.get(&series.series_id) {
+                Some(follower) => follower,
+                None => continue,
+            };
+            if follower.start_at >= start_at {
+                // The followed episode and episodes before it are not added again even if the
+                // schedules have been removed.
+                continue;
+            }
+            if series.repeat_label != 0 {
+                tracing::debug!(%program.id, series.repeat_label, "Skip repeat");
+                continue;
+            }
+            if series.last_episode_number != 0 && series.episode_number > series.last_episode_number
+            {
+                continue;
+            }
+            let expire_date = series.expire_date.or(follower.expire_date);
+            if let Some(expire_date) = expire_date
+                && start_at.timestamp_millis() > expire_date
+            {
+                continue;
+            }
+            tracing::info!(%program.id, series.series_id, "Follow series");
+            let mut schedule = RecordingSchedule::new(
+                program.clone(),
+                follower.service.clone(),
+                follower.options.clone(),
+                follower.tags.clone(),
+            );
+            schedule.rule_id = follower.rule_id;
+            schedules.push(schedule);
+        }
+
+        let changed = !schedules.is_empty();
+        for schedule in schedules.into_iter() {
+            // Never fails because we checked it above.
+            let _ = self.add_schedule(schedule);
+        }
+        changed
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    // A series follower is kept even after its recording schedules have been removed so that
+    // following episodes can be added when they appear in EPG.
+    fn update_series_follower(&mut self, schedule: &RecordingSchedule) {
+        let (series, start_at) = match (&schedule.program.series, schedule.program.start_at) {
+            (Some(series), Some(start_at)) => (series, start_at),
+            _ => return,
+        };
+        let key = (schedule.service.id, series.series_id);
+        let prev = self.series_followers.get(&key);
+        if let Some(prev) = prev
+            && prev.start_at >= start_at
+        {
+            // A newer episode has already been followed.
+            return;
+        }
+        let follower = SeriesFollower {
+            service: schedule.service.clone(),
+            series_id: series.series_id,
+            options: schedule.options.clone(),
+            tags: schedule.tags.clone(),
+            rule_id: schedule.rule_id,
+            start_at,
+            episode_number: series.episode_number,
+            last_episode_number: series.last_episode_number,
+            expire_date: series
+                .expire_date
+                .or_else(|| prev.and_then(|prev| prev.expire_date)),
+        };
+        tracing::debug!(%follower.service.id, follower.series_id, %schedule.program.id, "Updated series follower");
+        self.series_followers.insert(key, follower);
+    }
+
+    fn remove_finished_series_followers(&mut self, now: DateTime<Jst>) {
+        let len = self.series_followers.len();
+        self.series_followers.retain(|_, follower| {
+            if follower.is_finished(now) {
+                tracing::info!(%follower.service.id, follower.series_id, "Finished following series");
+                false
+            } else {
+                true
+            }
+        });
+        if self.series_followers.len() != len {
+            self.save_series_followers();
+        }
+    }
+}
+
+// on-air program changed
+
+#[async_trait]
+impl<T, E, O> Handler<onair::OnairProgramChanged> for RecordingManager<T, E, O>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<QueryClock>,
+    E: Call<QueryPrograms>,
+    E: Call<QueryService>,
+    E: Call<epg::RegisterEmitter>,
+    O: Clone + Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, msg: onair::OnairProgramChanged, ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "OnairProgramChanged");
+
+        let mut changed = false;
+
+        // The start time in EIT[p/f] may be undefined.
+        if let Some(program) = msg.current {
+            if program.start_at.is_some() {
+                changed = self.update_schedule_by_onair_program(program).await;
+            }
+        }
+        if let Some(program) = msg.next {
+            if program.start_at.is_some() {
+                changed = self.update_schedule_by_onair_program(program).await;
+            }
+        }
+
+        if changed {
+            self.save_schedules();
+            self.rebuild_queue();
+            self.set_timer(ctx);
+        }
+    }
+}
+
+impl<T, E, O> RecordingManager<T, E, O> {
+    async fn update_schedule_by_onair_program(&mut self, program: Arc<EpgProgram>) -> bool {
+        use RecordingScheduleState::*;
+
+        let mut rescheduled = false;
+
+        if let Some(schedule) = self.schedules.get_mut(&program.id) {
+            match schedule.state {
+                Scheduled | Rescheduling => {
+                    rescheduled = schedule.program.start_at != program.start_at;
+                    schedule.program = (*program).clone();
+                    schedule.state = Tracking;
+                    tracing::info!(
+                        %schedule.program.id,
+                        "Start tracking changes of the schedule",
+                    );
+                }
+                Tracking => {
+                    rescheduled = schedule.program.start_at != program.start_at;
+                    schedule.program = (*program).clone();
+                }
+                Recording => {
+                    schedule.program = (*program).clone();
+                    self.update_record(program.id).await;
+                }
+                Finished | Failed => {
+                    // Nothing to do.
+                }
+            }
+        }
+
+        if rescheduled {
+            self.emit_recording_rescheduled(program.id).await;
+        }
+
+        rescheduled
+    }
+}
+
+// content source actor
+
+struct ContentSource {
+    pipeline: CommandPipeline<RecordId>,
+}
+
+impl ContentSource {
+    fn new<C: Spawn>(
+        config: &Config,
+        record: &Record,
+        range: Option<&ContentRange>,
+        offset: u64,
+        ctx: &C,
+    ) -> Result<Self, Error> {
+        debug_assert!(range.is_none() || offset == 0);
+        let content_path = make_content_path(config, record).unwrap();
+        if !content_path.exists() {
+            tracing::warn!(?content_path, "No such file, maybe it has been removed");
+            return Err(Error::NoContent);
+        }
+
+        let content_path_str = content_path.to_str().unwrap();
+        let cmd = match (range, &record.recording_status) {
+            (Some(range), _) => {
+                debug_assert!(range.is_partial());
+                let skip = range.first();
+                let count = range.bytes();
+                format!("dd if='{content_path_str}' ibs=1 skip={skip} count={count}")
+            }
+            (None, RecordingStatus::Recording) => {
+                // We use `tail -f` for streaming during recording in order to send data to be
+                // appended to the content file in the future after the stream reaches EOF at that
+                // point.
+                //
+                // NOTE: `tail` in macOS doesn't support `-s` option.  The default value of the
+                // sleep interval of `tail` in GNU coreutils is 1.0 second.
+                format!("tail -f -c +{} '{content_path_str}'", offset + 1)
+            }
+            _ if offset > 0 => format!("tail -c +{} '{content_path_str}'", offset + 1),
+            _ => format!("cat '{content_path_str}'"),
+        };
+
+        let pipeline = spawn_pipeline(vec![cmd], record.id.clone(), "content", ctx)?;
+
+        Ok(Self { pipeline })
+    }
+
+    fn create_stream(&mut self, time_limit: u64) -> ContentStream {
+        // 32 KiB, large enough for 10 ms buffering.
+        const CHUNK_SIZE: usize = 4096 * 8;
+
+        let (_, output) = self.pipeline.take_endpoints();
+
+        let stream = ReaderStream::with_capacity(output, CHUNK_SIZE)
+            // We set a time limit in order to stop streaming when the stream reaches the *true*
+            // EOF.  Because `tail -f` doesn't terminate when the stream reaches an EOF at that
+            // point.
+            //
+            // We cannot use a RecordingStopped emitter for this purpose.  Because the streaming
+            // has to continue in order to send remaining data until the *true* EOF reaches.
+            .timeout(std::time::Duration::from_millis(time_limit))
+            .map_while(Result::ok);
+        // TODO(#2057): ranges
+        MpegTsStream::new(self.pipeline.id().clone(), Box::pin(stream))
+    }
+}
+
+#[async_trait]
+impl Actor for ContentSource {
+    async fn started(&mut self, _ctx: &mut Context<Self>) {
+        tracing::debug!(content_source.pipeline.id = %self.pipeline.id(), "Started");
+    }
+
+    async fn stopping(&mut self, _ctx: &mut Context<Self>) {
+        tracing::debug!(content_source.pipeline.id = %self.pipeline.id(), "Stopping...");
+        self.pipeline.kill();
+    }
+
+    async fn stopped(&mut self, _ctx: &mut Context<Self>) {
+        tracing::debug!(content_source.pipeline.id = %self.pipeline.id(), "Stopped");
+    }
+}
+
+// models
+
+#[derive(Debug, Eq)]
+struct QueueItem {
+    program_id: ProgramId,
+    start_at: DateTime<Jst>,
+    priority: i32,
+}
+
+impl Ord for QueueItem {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.start_at
+            .cmp(&other.start_at)
+            .reverse()
+            .then_with(|| self.priority.cmp(&other.priority))
+    }
+}
+
+impl PartialEq for QueueItem {
+    fn eq(&self, other: &Self) -> bool {
+        self.program_id == other.program_id
+    }
+}
+
+impl PartialOrd for QueueItem {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RecordingSchedule {
+    pub state: RecordingScheduleState,
+    pub program: EpgProgram,
+    pub service: EpgService,
+    pub options: RecordingOptions,
+    #[serde(default)]
+    pub tags: HashSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed_reason: Option<RecordingFailedReason>,
+    // The ID of the rule which added this schedule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule_id: Option<RecordingRuleId>,
+    // The ID of the recurring schedule which added this schedule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recurring_schedule_id: Option<RecurringScheduleId>,
+    // Attempts to start the recording, in order.  The recording is retried
+    // on another tuner if the previous attempt failed.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<RecordingAttempt>,
+    // Updated every time the queue is rebuilt.
+    #[serde(skip)]
+    pub conflict: Option<RecordingConflict>,
+}
+
+impl RecordingSchedule {
+    pub fn new(
+        program: EpgProgram,
+        service: EpgService,
+        options: RecordingOptions,
+        tags: HashSet<String>,
+    ) -> Self {
+        RecordingSchedule {
+            state: RecordingScheduleState::Scheduled,
+            program,
+            service,
+            options,
+            tags,
+            failed_reason: None,
+            rule_id: None,
+            recurring_schedule_id: None,
+            attempts: vec![],
+            conflict: None,
+        }
+    }
+
+    fn can_be_updated_by_epg(&self) -> bool {
+        use RecordingScheduleState::*;
+        !self.is_time_range() && matches!(self.state, Scheduled | Rescheduling)
+    }
+
+    /// Returns `true` if the recording schedule is a time-range recording schedule.
+    ///
+    /// The TV program of a time-range recording schedule is defined by users and it doesn't exist
+    /// in EPG.
+    pub fn is_time_range(&self) -> bool {
+        self.program.id.is_user_defined()
+    }
+
+    fn is_ready_for_recording(&self) -> bool {
+        use RecordingScheduleState::*;
+        matches!(self.state, Scheduled | Tracking)
+    }
+
+    // The start time of the recording including the start margin.
+    fn recording_start_time(&self, config: &RecordingConfig) -> Option<DateTime<Jst>> {
+        self.program
+            .start_at
+            .map(|start_at| start_at - self.options.start_margin(config))
+    }
+
+    // The end time of the recording including the end margin.
+    fn recording_end_time(&self, config: &RecordingConfig) -> Option<DateTime<Jst>> {
+        self.program
+            .end_at()
+            .map(|end_at| end_at + self.options.end_margin(config))
+    }
+
+    fn is_recording(&self) -> bool {
+        use RecordingScheduleState::*;
+        matches!(self.state, Recording)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+#[derive(ToSchema)]
+#[schema(title = "RecordingScheduleState")]
+pub enum RecordingScheduleState {
+    Scheduled,
+    // This state is used only when on-air program manager is available for the
+    // service of the target TV program.  In this case, the state of the
+    // schedule always transits to `recording` via `tracking`.
+    Tracking,
+    Recording,
+    // When the recording fails, the schedule transits to this state.  If on-air
+    // program tracker is available, the schedule may be rescheduled when an EIT
+    // section ([schedule] or [p/f]) for the target TV program is emitted.
+    // Otherwise, it will be expired eventually.
+    Rescheduling,
+    // The recording finished successfully.
+    Finished,
+    // The recording failed for some reason.
+    Failed,
+}
+
+/// A conflict of tuner allocation.
+///
+/// Conflicts are detected by simulating tuner allocation for recording schedules.  Tuners used by
+/// other features such as streaming are not taken into account.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(ToSchema)]
+#[schema(title = "RecordingConflict")]
+pub struct RecordingConflict {
+    /// Mirakurun program IDs of recording schedules using tuners which could be used for the
+    /// recording schedule.
+    #[schema(value_type = Vec<u64>)]
+    pub programs: Vec<ProgramId>,
+}
+
+impl RecordingConflict {
+    fn add_programs<I>(&mut self, programs: I)
+    where
+        I: IntoIterator<Item = ProgramId>,
+    {
+        for program_id in programs.into_iter() {
+            if !self.programs.contains(&program_id) {
+                self.programs.push(program_id);
+            }
+        }
+    }
+}
+
+/// Recording options.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(ToSchema)]
+#[schema(title = "RecordingOptions")]
+pub struct RecordingOptions {
+    /// The path of the content file relative to `config.recording.basedir`.
+    ///
+    /// The path must be a valid Unicode string.
+    ///
+    /// ### If `config.recording.records-dir` is NOT specified
+    ///
+    /// This is a required option.  A response with the status code 401 will be replied if this
+    /// option is not specified.
+    ///
+    /// ### If `config.recording.records-dir` is specified
+    ///
+    /// An auto-generated filename will be used for the content file if this option is not
+    /// specified.
+    #[schema(value_type = Option<String>)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_path: Option<PathBuf>,
+
+    /// A priority of tuner usage.
+    #[serde(default)]
+    pub priority: i32,
+
+    /// A list of pre-filters to use.
+    #[serde(default)]
+    pub pre_filters: Vec<String>,
+
+    /// A list of post-filters to use.
+    #[serde(default)]
+    pub post_filters: Vec<String>,
+
+    /// Log filter of the recording schedule.
+    ///
+    /// If this option is not specified, the value of `config.recording.log-filter` is used when
+    /// the recording starts.  The log filter will be set to the environment variable
+    /// `MIRAKC_ARIB_LOG` passed to the recording command pipeline.
+    ///
+    /// If the log filter is neither empty nor `off`, logs coming from the recording command
+    /// pipeline will be stored into a log file.  The log file will be placed in the same folder as
+    /// the content file and its name is "<content-file>.log".  The environment variable
+    /// `MIRAKC_ARIB_LOG_NO_TIMESTAMP` will be disabled and each log will have a timestamp.
+    ///
+    /// If the log filter is empty or `off`, no log will come from the recording command pipeline.
+    /// And the log file won't be created.
+    ///
+    /// For backward compatibility with 3.x and older versions, the logs will be output to STDOUT
+    /// if neither this option nor `recording.log-filter` is specified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_filter: Option<String>,
+
+    /// A margin in milliseconds added before the start time of the TV program.
+    ///
+    /// If this option is not specified, the value of `config.recording.start-margin` is used.
+    /// The recording will start earlier by the margin.
+    #[schema(value_type = Option<u64>)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(with = "duration_milliseconds_option")]
+    pub start_margin: Option<Duration>,
+
+    /// A margin in milliseconds added after the end time of the TV program.
+    ///
+    /// If this option is not specified, the value of `config.recording.end-margin` is used.
+    /// The recording will end later by the margin.
+    #[schema(value_type = Option<u64>)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(with = "duration_milliseconds_option")]
+    pub end_margin: Option<Duration>,
+
+    /// Record all episodes of the series.
+    ///
+    /// If this option is enabled and the TV program has a series descriptor, recording schedules
+    /// will be added for following episodes of the series broadcasted on the same service every
+    /// time EPG is updated.  The recording schedules have the same options and tags.
+    ///
+    /// Repeat broadcasts are skipped.  No recording schedule will be added after the last episode
+    /// or the expiration date of the series.  The series is followed even after the recording
+    /// schedules have been removed.
+    ///
+    /// This option cannot be used together with `contentPath`.  Remove recording schedules by a
+    /// tag in order to stop recording the series.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub follow_series: bool,
+}
+
+impl RecordingOptions {
+    pub fn validate(&self) -> Result<(), Error> {
+        let max_margin = Duration::from_std(RecordingConfig::MAX_MARGIN).unwrap();
+        for margin in [self.start_margin, self.end_margin].into_iter().flatten() {
+            if margin < Duration::zero() || margin > max_margin {
+                return Err(Error::InvalidRequest(
+                    "startMargin and endMargin must be in the range of 0 to 1h",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn start_margin(&self, config: &RecordingConfig) -> Duration {
+        self.start_margin
+            .or_else(|| config.start_margin.and_then(|d| Duration::from_std(d).ok()))
+            .unwrap_or_default()
+    }
+
+    fn end_margin(&self, config: &RecordingConfig) -> Duration {
+        self.end_margin
+            .or_else(|| config.end_margin.and_then(|d| Duration::from_std(d).ok()))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, Eq, Ord, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub struct RecordingRuleId(u32);
+
+impl RecordingRuleId {
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+impl std::fmt::Display for RecordingRuleId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u32> for RecordingRuleId {
+    fn from(value: u32) -> Self {
+        RecordingRuleId(value)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RecordingRule {
+    pub id: RecordingRuleId,
+    pub conditions: RecordingRuleConditions,
+    pub options: RecordingOptions,
+    #[serde(default)]
+    pub tags: HashSet<String>,
+    // TV programs whose schedules have been removed by users.
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub ignored_programs: HashSet<ProgramId>,
+}
+
+/// Conditions of a recording rule.
+///
+/// A TV program matches the rule when it satisfies all of the specified conditions.  At least one
+/// of `title`, `description`, `genres` and `seriesId` must be specified.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(ToSchema)]
+#[schema(title = "RecordingRuleConditions")]
+pub struct RecordingRuleConditions {
+    /// A list of Mirakurun service IDs.
+    ///
+    /// TV programs in any services match if this is empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[schema(value_type = Vec<u64>)]
+    pub services: Vec<ServiceId>,
+
+    /// A regular expression matching with the title of the TV program.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    /// A regular expression matching with the description of the TV program.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// A list of genres.
+    ///
+    /// The TV program matches if it has at least one of the genres.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub genres: Vec<RecordingRuleGenre>,
+
+    /// The series ID in the series descriptor of the TV program.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series_id: Option<u16>,
+
+    /// A time range of the start time of the TV program.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_range: Option<RecordingRuleTimeRange>,
+}
+
+impl RecordingRuleConditions {
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.title.is_none()
+            && self.description.is_none()
+            && self.genres.is_empty()
+            && self.series_id.is_none()
+        {
+            return Err(Error::InvalidRequest(
+                "One of title, description, genres and seriesId is required",
+            ));
+        }
+        self.matcher()?;
+        Ok(())
+    }
+
+    fn matches_service(&self, service_id: ServiceId) -> bool {
+        self.services.is_empty() || self.services.contains(&service_id)
+    }
+
+    fn matcher(&self) -> Result<RecordingRuleMatcher<'_>, Error> {
+        fn compile(pattern: Option<&String>) -> Result<Option<Regex>, Error> {
+            pattern
+                .map(|pattern| Regex::new(pattern))
+                .transpose()
+                .map_err(|_| Error::InvalidRequest("Invalid regular expression"))
+        }
+
+        let time_range = match self.time_range.as_ref() {
+            Some(time_range) => Some(time_range.parse()?),
+            None => None,
+        };
+
+        Ok(RecordingRuleMatcher {
+            conditions: self,
+            title: compile(self.title.as_ref())?,
+            description: compile(self.description.as_ref())?,
+            time_range,
+        })
+    }
+}
+
+/// A genre used in recording rules.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(ToSchema)]
+#[schema(title = "RecordingRuleGenre")]
+pub struct RecordingRuleGenre {
+    /// The major genre.
+    pub lv1: u8,
+
+    /// The minor genre.
+    ///
+    /// Any minor genres in the major genre match if this is not specified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lv2: Option<u8>,
+}
+
+/// A time range in JST.
+///
+/// The range wraps around midnight if `end` is earlier than `start`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[derive(ToSchema)]
+#[schema(title = "RecordingRuleTimeRange")]
+pub struct RecordingRuleTimeRange {
+    /// The start time (inclusive) in the `HH:MM` format.
+    pub start: String,
+
+    /// The end time (exclusive) in the `HH:MM` format.
+    pub end: String,
+}
+
+impl RecordingRuleTimeRange {
+    fn parse(&self) -> Result<(NaiveTime, NaiveTime), Error> {
+        const ERR: Error = Error::InvalidRequest("Invalid time range");
+        let start = NaiveTime::parse_from_str(&self.start, "%H:%M").map_err(|_| ERR)?;
+        let end = NaiveTime::parse_from_str(&self.end, "%H:%M").map_err(|_| ERR)?;
+        if start == end {
+            return Err(ERR);
+        }
+        Ok((start, end))
+    }
+}
+
+struct RecordingRuleMatcher<'a> {
+    conditions: &'a RecordingRuleConditions,
+    title: Option<Regex>,
+    description: Option<Regex>,
+    time_range: Option<(NaiveTime, NaiveTime)>,
+}
+
+impl RecordingRuleMatcher<'_> {
+    fn matches(&self, program: &EpgProgram) -> bool {
+        if !self.conditions.matches_service(program.id.into()) {
+            return false;
+        }
+
+        if let Some(ref regex) = self.title {
+            match program.name {
+                Some(ref name) if regex.is_match(name) => (),
+                _ => return false,
+            }
+        }
+
+        if let Some(ref regex) = self.description {
+            match program.description {
+                Some(ref description) if regex.is_match(description) => (),
+                _ => return false,
+            }
+        }
+
+        if !self.conditions.genres.is_empty() {
+            let genres = match program.genres {
+                Some(ref genres) => genres,
+                None => return false,
+            };
+            let found = self.conditions.genres.iter().any(|rule_genre| {
+                genres.iter().any(|genre| {
+                    genre.lv1 == rule_genre.lv1 && rule_genre.lv2.is_none_or(|lv2| genre.lv2 == lv2)
+                })
+            });
+            if !found {
+                return false;
+            }
+        }
+
+        if let Some(series_id) = self.conditions.series_id {
+            match program.series {
+                Some(ref series) if series.series_id == series_id => (),
+                _ => return false,
+            }
+        }
+
+        if let Some((start, end)) = self.time_range {
+            let time = match program.start_at {
+                Some(start_at) => start_at.time(),
+                None => return false,
+            };
+            let matched = if start < end {
+                start <= time && time < end
+            } else {
+                start <= time || time < end
+            };
+            if !matched {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[derive(Clone, Copy, Debug, Hash, Eq, Ord, PartialEq, PartialOrd, Deserialize, Serialize)]
+pub struct RecurringScheduleId(u32);
+
+impl RecurringScheduleId {
+    pub fn value(&self) -> u32 {
+        self.0
+    }
+}
+
+impl std::fmt::Display for RecurringScheduleId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u32> for RecurringScheduleId {
+    fn from(value: u32) -> Self {
+        RecurringScheduleId(value)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RecurringSchedule {
+    pub id: RecurringScheduleId,
+    pub service_id: ServiceId,
+    // A cron expression in the format supported by the `cron` crate.
+    pub cron: String,
+    // Occurrences are recorded as time-range recording schedules if this is specified.
+    // Otherwise, TV programs on air at the occurrences are recorded.
+    #[serde(default, with = "duration_milliseconds_option")]
+    pub duration: Option<Duration>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub options: RecordingOptions,
+    #[serde(default)]
+    pub tags: HashSet<String>,
+    // The time of the last occurrence which has been expanded.
+    #[serde(default, with = "ts_milliseconds_option")]
+    pub expanded_until: Option<DateTime<Jst>>,
+}
+
+impl RecurringSchedule {
+    const MAX_DURATION_HOURS: i64 = 24;
+
+    fn validate(&self) -> Result<(), Error> {
+        if cron::Schedule::from_str(&self.cron).is_err() {
+            return Err(Error::InvalidRequest("Invalid cron expression"));
+        }
+        if let Some(duration) = self.duration {
+            if duration <= Duration::zero() {
+                return Err(Error::InvalidRequest("duration must be a positive value"));
+            }
+            if duration > Duration::try_hours(Self::MAX_DURATION_HOURS).unwrap() {
+                return Err(Error::InvalidRequest("duration must be within 24 hours"));
+            }
+        }
+        Ok(())
+    }
+}
+
+// The state for following a series on a service.
+//
+// Kept independently of recording schedules until the last episode or the expiration date of the
+// series.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SeriesFollower {
+    service: EpgService,
+    series_id: u16,
+    options: RecordingOptions,
+    #[serde(default)]
+    tags: HashSet<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rule_id: Option<RecordingRuleId>,
+    // The start time of the latest episode followed.
+    #[serde(with = "ts_milliseconds")]
+    start_at: DateTime<Jst>,
+    episode_number: u16,
+    last_episode_number: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expire_date: Option<i64>,
+}
+
+impl SeriesFollower {
+    fn is_finished(&self, now: DateTime<Jst>) -> bool {
+        if self.last_episode_number != 0 && self.episode_number >= self.last_episode_number {
+            // The last episode has already been scheduled.
+            return true;
+        }
+        match self.expire_date {
+            Some(expire_date) => now.timestamp_millis() > expire_date,
+            None => false,
+        }
+    }
+}
+
+struct SimulatedTuner<'a> {
+    // The same as the index of the tuner in `TunerManager`.
+    index: usize,
+    config: &'a TunerConfig,
+    restriction: SimulatedRestriction,
+    channel: Option<(ChannelType, String)>,
+    users: Vec<SimulatedUser>,
+}
+
+enum SimulatedRestriction {
+    None,
+    // Always active for the channel.
+    Channel,
+    Exclusive,
+}
+
+struct SimulatedUser {
+    program_id: ProgramId,
+    priority: TunerUserPriority,
+    end_at: DateTime<Jst>,
+}
+
+impl<'a> SimulatedTuner<'a> {
+    // Tuners dedicated for other features are restricted in the same way as
+    // `TunerManager::load_tuners()`.
+    fn load(config: &'a Config) -> Vec<Self> {
+        config
+            .tuners
+            .iter()
+            .filter(|tuner| !tuner.disabled)
+            .enumerate()
+            .map(|(index, tuner)| {
+                let used_by_tracker = config.onair_program_trackers.values().any(|tracker| {
+                    matches!(tracker, OnairProgramTrackerConfig::Local(tracker)
+                             if tracker.uses.tuner == tuner.name)
+                });
+                let timeshift = config
+                    .timeshift
+                    .recorders
+                    .values()
+                    .find(|recorder| recorder.uses.tuner == tuner.name);
+                let (restriction, channel) = if used_by_tracker {
+                    (SimulatedRestriction::Exclusive, None)
+                } else if let Some(recorder) = timeshift {
+                    let channel = (recorder.uses.channel_type, recorder.uses.channel.clone());
+                    (SimulatedRestriction::Channel, Some(channel))
+                } else {
+                    (SimulatedRestriction::None, None)
+                };
+                SimulatedTuner {
+                    index,
+                    config: tuner,
+                    restriction,
+                    channel,
+                    users: vec![],
+                }
+            })
+            .collect()
+    }
+
+    fn is_usable_for(&self, channel: &EpgChannel) -> bool {
+        let usable = match self.restriction {
+            SimulatedRestriction::None => true,
+            SimulatedRestriction::Channel => self.is_tuned_to(channel),
+            SimulatedRestriction::Exclusive => false,
+        };
+        usable
+            && self.config.channel_types.contains(&channel.channel_type)
+            && !self
+                .config
+                .excluded_channels
+                .iter()
+                .any(|excluded| excluded.matches(channel))
+    }
+
+    fn is_reuseable(&self, channel: &EpgChannel) -> bool {
+        match self.restriction {
+            SimulatedRestriction::None => !self.users.is_empty() && self.is_tuned_to(channel),
+            SimulatedRestriction::Channel => self.is_tuned_to(channel),
+            SimulatedRestriction::Exclusive => false,
+        }
+    }
+
+    fn is_tuned_to(&self, channel: &EpgChannel) -> bool {
+        match self.channel {
+            Some((ch_type, ref ch)) => ch_type == channel.channel_type && *ch == channel.channel,
+            None => false,
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        matches!(self.restriction, SimulatedRestriction::None) && self.users.is_empty()
+    }
+
+    fn can_grab(&self, priority: TunerUserPriority) -> bool {
+        matches!(self.restriction, SimulatedRestriction::None)
+            && (priority.is_grab() || self.users.iter().all(|user| priority > user.priority))
+    }
+
+    fn priority(&self) -> Option<TunerUserPriority> {
+        self.users.iter().map(|user| user.priority).max()
+    }
+
+    fn release(&mut self, now: DateTime<Jst>) {
+        self.users.retain(|user| user.end_at > now);
+    }
+
+    // Returns users evicted from the tuner.
+    fn activate(&mut self, channel: &EpgChannel, user: SimulatedUser) -> Vec<SimulatedUser> {
+        self.channel = Some((channel.channel_type, channel.channel.clone()));
+        std::mem::replace(&mut self.users, vec![user])
+    }
+}
+
+struct Recorder {
+    started_at: DateTime<Jst>,
+    pipeline: CommandPipeline<TunerSubscriptionId>,
+    stop_trigger: Option<Trigger<StopStreaming>>,
+    content_type: String,
+    log_path: Option<PathBuf>,
+    // Set when the tuner used for the recording is grabbed by another user.
+    preempted: Option<RecordingFailedReason>,
+    // Stops a recording of a time-range schedule at the end time.
+    stop_timer: Option<CancellationToken>,
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        if let Some(stop_timer) = self.stop_timer.take() {
+            stop_timer.cancel();
+        }
+    }
+}
+
+impl Recorder {
+    // Returns `None` if the recorder has already been stopped.
+    fn stop(&mut self) -> Option<Trigger<StopStreaming>> {
+        if let Some(stop_timer) = self.stop_timer.take() {
+            stop_timer.cancel();
+        }
+        self.stop_trigger.take()
+    }
+
+    fn get_model(&self, program_id: ProgramId) -> RecorderModel {
+        RecorderModel {
+            program_id,
+            started_at: self.started_at,
+            pipeline: self.pipeline.get_model(),
+        }
+    }
+}
+
+pub struct RecorderModel {
+    pub program_id: ProgramId,
+    pub started_at: DateTime<Jst>,
+    pub pipeline: Vec<CommandPipelineProcessModel>,
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Deserialize, Serialize)]
+pub struct RecordId(String);
+
+impl RecordId {
+    fn new(timestamp: i64, program_id: u64) -> Self {
+        Self(format!("{timestamp:016X}{program_id:016X}"))
+    }
+
+    pub fn value(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<(DateTime<Jst>, ProgramId)> for RecordId {
+    fn from(value: (DateTime<Jst>, ProgramId)) -> Self {
+        Self::new(value.0.timestamp_millis(), value.1.value())
+    }
+}
+
+impl From<String> for RecordId {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl std::fmt::Display for RecordId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "record#{}", self.0)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Record {
+    pub id: RecordId,
+    pub program: EpgProgram,
+    pub service: EpgService,
+    pub options: RecordingOptions,
+    #[serde(default)]
+    pub tags: HashSet<String>,
+    pub recording_status: RecordingStatus,
+    #[serde(with = "ts_milliseconds")]
+    pub recording_start_time: DateTime<Jst>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(with = "ts_milliseconds_option")]
+    pub recording_end_time: Option<DateTime<Jst>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(with = "duration_milliseconds_option")]
+    pub recording_duration: Option<Duration>,
+    pub content_path: PathBuf,
+    pub content_type: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_process_jobs: Vec<PostProcessJob>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<RecordingAttempt>,
+}
+
+impl Record {
+    fn new(
+        id: RecordId,
+        start_time: DateTime<Jst>,
+        schedule: &RecordingSchedule,
+        content_path: PathBuf,
+        content_type: String,
+    ) -> Self {
+        debug_assert!(content_path.is_relative());
+        debug_assert!(!content_type.is_empty());
+        Self {
+            id,
+            program: schedule.program.clone(),
+            service: schedule.service.clone(),
+            options: schedule.options.clone(),
+            tags: schedule.tags.clone(),
+            recording_status: RecordingStatus::Recording,
+            recording_start_time: start_time,
+            recording_end_time: None,
+            recording_duration: None,
+            content_path,
+            content_type,
+            post_process_jobs: vec![],
+            attempts: schedule.attempts.clone(),
+        }
+    }
+
+    fn post_process_job(&self, name: &str) -> Option<&PostProcessJob> {
+        self.post_process_jobs.iter().find(|job| job.name == name)
+    }
+
+    fn update_by_schedule(&mut self, schedule: &RecordingSchedule) {
+        let now = Jst::now();
+
+        self.program = schedule.program.clone();
+        self.options = schedule.options.clone();
+        self.tags = schedule.tags.clone();
+        self.attempts = schedule.attempts.clone();
+
+        self.recording_end_time = Some(now);
+        self.recording_duration = Some(now - self.recording_start_time);
+
+        match schedule.state {
+            RecordingScheduleState::Scheduled | RecordingScheduleState::Tracking => (),
+            RecordingScheduleState::Recording => {
+                self.recording_status = RecordingStatus::Recording;
+                self.recording_end_time = None;
+                self.recording_duration = None;
+            }
+            RecordingScheduleState::Rescheduling => {
+                self.recording_status = RecordingStatus::Failed {
+                    reason: RecordingFailedReason::NeedRescheduling,
+                };
+            }
+            RecordingScheduleState::Finished => {
+                self.recording_status = RecordingStatus::Finished;
+            }
+            RecordingScheduleState::Failed => {
+                self.recording_status = RecordingStatus::Failed {
+                    reason: schedule.failed_reason.clone().unwrap(),
+                };
+            }
+        }
+    }
+}
+
+/// A recording status.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum RecordingStatus {
+    Recording,
+    Finished,
+    Canceled,
+    Failed { reason: RecordingFailedReason },
+}
+
+/// The status of a post-process job for a record.
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(title = "PostProcessJob")]
+pub struct PostProcessJob {
+    /// The name of the job defined in `config.recording.post-process.jobs`.
+    pub name: String,
+
+    /// The current state of the job.
+    pub state: PostProcessJobState,
+
+    /// The time when the job started in UNIX time (milliseconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(with = "ts_milliseconds_option")]
+    #[schema(value_type = Option<i64>)]
+    pub started_at: Option<DateTime<Jst>>,
+
+    /// The time when the job finished in UNIX time (milliseconds).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(with = "ts_milliseconds_option")]
+    #[schema(value_type = Option<i64>)]
+    pub finished_at: Option<DateTime<Jst>>,
+
+    /// The exit code of the command.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+
+    /// An error message when the command couldn't run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl PostProcessJob {
+    fn new(name: String) -> Self {
+        PostProcessJob {
+            name,
+            state: PostProcessJobState::Pending,
+            started_at: None,
+            finished_at: None,
+            exit_code: None,
+            message: None,
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        matches!(
+            self.state,
+            PostProcessJobState::Pending | PostProcessJobState::Running
+        )
+    }
+}
+
+/// The state of a post-process job.
+#[derive(Clone, Debug, Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+#[schema(title = "PostProcessJobState")]
+pub enum PostProcessJobState {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+// A point in the content file of a record.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ContentIndexPoint {
+    #[serde(with = "ts_milliseconds")]
+    pub timestamp: DateTime<Jst>,
+    pub pos: u64,
+}
+
+// A time index of the content file of a record.
+//
+// The index is stored in a sidecar file in the JSON Lines format so that points can be appended
+// while recording.  Points in the index must be sorted in chronological order.
+#[derive(Clone, Debug, Default)]
+pub struct ContentIndex {
+    points: Vec<ContentIndexPoint>,
+}
+
+impl ContentIndex {
+    // 10 seconds in the 90kHz clock.
+    const PCR_INTERVAL: u64 = CONTENT_INDEX_INTERVAL_SECS as u64 * 90_000;
+    // PCR discontinuities larger than this are ignored.
+    const PCR_MAX_DELTA: u64 = Self::PCR_INTERVAL;
+    const PCR_WRAP: u64 = 1 << 33;
+
+    pub fn points(&self) -> &[ContentIndexPoint] {
+        &self.points
+    }
+
+    // Builds an index from the metadata of a record assuming that the bitrate of the content is
+    // constant.
+    fn estimate(record: &Record, content_length: u64) -> Self {
+        let start_time = record.recording_start_time;
+        let duration = record
+            .recording_duration
+            .unwrap_or_else(|| Jst::now() - start_time);
+        Self {
+            points: vec![
+                ContentIndexPoint {
+                    timestamp: start_time,
+                    pos: 0,
+                },
+                ContentIndexPoint {
+                    timestamp: start_time + duration,
+                    pos: content_length,
+                },
+            ],
+        }
+    }
+
+    /// Builds an index by scanning PCRs in TS packets.
+    ///
+    /// The first PCR is mapped to `start_time`.  Points are added at intervals of about 10
+    /// seconds in the PCR clock.  A PCR discontinuity (e.g. contents recorded in multiple
+    /// attempts) is treated as if no time elapsed.
+    ///
+    /// The same builder is used for indexing while recording.  So, a rebuilt index is the same as
+    /// the one created while recording.
+    pub fn build<R: std::io::Read>(
+        mut reader: R,
+        start_time: DateTime<Jst>,
+    ) -> std::io::Result<Self> {
+        let mut index = Self::default();
+        let mut builder = ContentIndexBuilder::new(start_time);
+        let mut points = vec![];
+        let mut buf = vec![0; 4096 * 8];
+        loop {
+            let n = match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+            builder.feed(&buf[..n], &mut points);
+        }
+        points.extend(builder.finish());
+        for point in points.into_iter() {
+            index.push(point);
+        }
+        Ok(index)
+    }
+
+    // Returns the PID and the PCR base (90kHz) if the packet has a PCR.
+    fn parse_pcr(packet: &[u8]) -> Option<(u16, u64)> {
+        if packet[0] != 0x47 {
+            return None;
+        }
+        let pid = (((packet[1] & 0x1F) as u16) << 8) | packet[2] as u16;
+        let has_adaptation_field = packet[3] & 0x20 != 0;
+        if !has_adaptation_field || packet[4] < 7 || packet[5] & 0x10 == 0 {
+            return None;
+        }
+        let pcr = ((packet[6] as u64) << 25)
+            | ((packet[7] as u64) << 17)
+            | ((packet[8] as u64) << 9)
+            | ((packet[9] as u64) << 1)
+            | ((packet[10] as u64) >> 7);
+        Some((pid, pcr))
+    }
+
+    async fn load(path: &Path) -> Option<Self> {
+        let data = match tokio::fs::read_to_string(path).await {
+            Ok(data) => data,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                tracing::warn!(?err, ?path, "Failed to read the content index");
+                return None;
+            }
+        };
+        let mut index = Self::default();
+        for line in data.lines() {
+            // The last line may be broken when the process has been killed while writing it.
+            match serde_json::from_str(line) {
+                Ok(point) => index.push(point),
+                Err(err) => tracing::warn!(%err, ?path, "Broken point in the content index, skip"),
+            }
+        }
+        Some(index)
+    }
+
+    fn save(&self, path: &Path) -> bool {
+        let mut data = vec![];
+        for point in self.points.iter() {
+            // Never fails.
+            serde_json::to_writer(&mut data, point).unwrap();
+            data.push(b'\n');
+        }
+        file_util::save_data(&data, path)
+    }
+
+    // Points not following the last point are ignored.
+    fn push(&mut self, point: ContentIndexPoint) {
+        if let Some(last) = self.points.last()
+            && (point.timestamp <= last.timestamp || point.pos < last.pos)
+        {
+            return;
+        }
+        self.points.push(point);
+    }
+
+    // Maps a time to a byte offset by linear interpolation between adjacent points.
+    //
+    // The offset is aligned to a TS packet boundary.  `None` is returned if the time is out of the
+    // index.
+    fn find_offset(&self, time: DateTime<Jst>) -> Option<u64> {
+        self.points
+            .windows(2)
+            .find(|pair| pair[0].timestamp <= time && time < pair[1].timestamp)
+            .map(|pair| {
+                let size = pair[1].pos - pair[0].pos;
+                let elapsed = (time - pair[0].timestamp).num_milliseconds() as u128;
+                let duration = (pair[1].timestamp - pair[0].timestamp).num_milliseconds() as u128;
+                let pos = pair[0].pos + (size as u128 * elapsed / duration) as u64;
+                pos - pos % TS_PACKET_SIZE
+            })
+    }
+}
+
+// Builds a content index incrementally from the data of a content file.
+//
+// Timestamps of points are computed from PCRs.  See `ContentIndex::build()` for details.
+struct ContentIndexBuilder {
+    start_time: DateTime<Jst>,
+    // The position of the next TS packet.
+    pos: u64,
+    // A TS packet not completed yet.
+    packet: [u8; TS_PACKET_SIZE as usize],
+    len: usize,
+    pcr_pid: Option<u16>,
+    prev_pcr: u64,
+    ticks: u64,
+    next_ticks: u64,
+}
+
+impl ContentIndexBuilder {
+    fn new(start_time: DateTime<Jst>) -> Self {
+        Self::with_state(start_time, 0, 0, 0)
+    }
+
+    // Resumes building from a point which was added at a TS packet having a PCR.
+    //
+    // The PCR clock is restored from the timestamp of the point.  So, timestamps of subsequent
+    // points may be 1ms earlier than timestamps of points in an index built from the beginning.
+    fn resume(start_time: DateTime<Jst>, point: &ContentIndexPoint) -> Self {
+        let ticks = (point.timestamp - start_time).num_milliseconds().max(0) as u64 * 90;
+        Self::with_state(
+            start_time,
+            point.pos,
+            ticks,
+            ticks + ContentIndex::PCR_INTERVAL,
+        )
+    }
+
+    fn with_state(start_time: DateTime<Jst>, pos: u64, ticks: u64, next_ticks: u64) -> Self {
+        ContentIndexBuilder {
+            start_time,
+            pos,
+            packet: [0; TS_PACKET_SIZE as usize],
+            len: 0,
+            pcr_pid: None,
+            prev_pcr: 0,
+            ticks,
+            next_ticks,
+        }
+    }
+
+    fn feed(&mut self, mut data: &[u8], points: &mut Vec<ContentIndexPoint>) {
+        while !data.is_empty() {
+            let n = (self.packet.len() - self.len).min(data.len());
+            self.packet[self.len..(self.len + n)].copy_from_slice(&data[..n]);
+            self.len += n;
+            data = &data[n..];
+            if self.len == self.packet.len() {
+                points.extend(self.process_packet());
+                self.len = 0;
+                self.pos += TS_PACKET_SIZE;
+            }
+        }
+    }
+
+    // Returns the last point if a PCR has been found.
+    fn finish(&self) -> Option<ContentIndexPoint> {
+        self.pcr_pid.map(|_| self.point())
+    }
+
+    fn process_packet(&mut self) -> Option<ContentIndexPoint> {
+        let (pid, pcr) = ContentIndex::parse_pcr(&self.packet)?;
+        match self.pcr_pid {
+            None => {
+                self.pcr_pid = Some(pid);
+                self.prev_pcr = pcr;
+            }
+            Some(pcr_pid) if pcr_pid == pid => {
+                let delta = (pcr + ContentIndex::PCR_WRAP - self.prev_pcr) % ContentIndex::PCR_WRAP;
+                if delta <= ContentIndex::PCR_MAX_DELTA {
+                    self.ticks += delta;
+                }
+                self.prev_pcr = pcr;
+            }
+            _ => return None,
+        }
+        if self.ticks < self.next_ticks {
+            return None;
+        }
+        self.next_ticks = self.ticks + ContentIndex::PCR_INTERVAL;
+        Some(self.point())
+    }
+
+    fn point(&self) -> ContentIndexPoint {
+        ContentIndexPoint {
+            timestamp: self.start_time + Duration::milliseconds((self.ticks / 90) as i64),
+            pos: self.pos,
+        }
+    }
+}
+
+// Appends points to the content index of a record while recording.
+//
+// Errors are logged but never stop the recording.
+struct ContentIndexWriter {
+    path: PathBuf,
+    file: Option<tokio::fs::File>,
+    builder: ContentIndexBuilder,
+    last_pos: Option<u64>,
+}
+
+impl ContentIndexWriter {
+    // Opens the content index.
+    //
+    // When retrying, the content index is rebuilt from the last point having a PCR so that the
+    // PCR clock continues from the previous attempt in the same way as `ContentIndex::build()`.
+    async fn open(
+        path: PathBuf,
+        start_time: DateTime<Jst>,
+        content_path: &Path,
+        retry: bool,
+    ) -> Self {
+        let builder = if retry {
+            Self::resume(&path, start_time, content_path).await
+        } else {
+            Ok(ContentIndexBuilder::new(start_time))
+        };
+        let builder = match builder {
+            Ok(builder) => builder,
+            Err(err) => {
+                tracing::error!(
+                    ?err,
+                    ?path,
+                    "Failed to resume the content index, stop indexing"
+                );
+                return Self {
+                    path,
+                    file: None,
+                    builder: ContentIndexBuilder::new(start_time),
+                    last_pos: None,
+                };
+            }
+        };
+        let result = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await;
+        let file = match result {
+            Ok(file) => Some(file),
+            Err(err) => {
+                tracing::error!(?err, ?path, "Failed to open the content index");
+                None
+            }
+        };
+        Self {
+            path,
+            file,
+            builder,
+            last_pos: None,
+        }
+    }
+
+    // Truncates the content index at the last point having a PCR and rebuilds points after it.
+    async fn resume(
+        path: &Path,
+        start_time: DateTime<Jst>,
+        content_path: &Path,
+    ) -> std::io::Result<ContentIndexBuilder> {
+        use tokio::io::AsyncSeekExt;
+
+        let mut file = tokio::fs::File::open(content_path).await?;
+        let content_length = file.metadata().await?.len();
+        let mut index = ContentIndex::load(path).await.unwrap_or_default();
+        // The last point of each attempt is placed at the end of the content written in the
+        // attempt.  Other points are placed at TS packets having a PCR and every such packet is
+        // contained in the content.
+        index
+            .points
+            .retain(|point| point.pos + TS_PACKET_SIZE <= content_length);
+        // The content is scanned from the beginning if there is no such point.
+        let mut builder = match index.points.last() {
+            Some(point) => ContentIndexBuilder::resume(start_time, point),
+            None => ContentIndexBuilder::new(start_time),
+        };
+        file.seek(std::io::SeekFrom::Start(builder.pos)).await?;
+        let mut points = vec![];
+        let mut buf = vec![0; 4096 * 8];
+        loop {
+            let n = file.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            builder.feed(&buf[..n], &mut points);
+        }
+        for point in points.into_iter() {
+            index.push(point);
+        }
+        if !index.save(path) {
+            return Err(std::io::Error::other("Failed to save the content index"));
+        }
+        Ok(builder)
+    }
+
+    // Called after `data` has been written to the content file.
+    async fn advance(&mut self, data: &[u8]) {
+        let mut points = vec![];
+        self.builder.feed(data, &mut points);
+        for point in points.into_iter() {
+            self.append(point).await;
+        }
+    }
+
+    async fn finish(&mut self) {
+        if let Some(point) = self.builder.finish()
+            && self.last_pos != Some(point.pos)
+        {
+            self.append(point).await;
+        }
+    }
+
+    async fn append(&mut self, point: ContentIndexPoint) {
+        if let Some(ref mut file) = self.file {
+            let mut line = serde_json::to_vec(&point).unwrap();
+            line.push(b'\n');
+            if let Err(err) = file.write_all(&line).await {
+                tracing::error!(?err, path = ?self.path, "Failed to append a point, stop indexing");
+                self.file = None;
+            }
+        }
+        self.last_pos = Some(point.pos);
+    }
+}
+
+// helpers
+
+// Returns `true` if the recording can be retried on a tuner which has not been
+// used in previous attempts.
+fn can_retry_recording(config: &Config, schedule: &RecordingSchedule, now: DateTime<Jst>) -> bool {
+    if schedule.attempts.is_empty() {
+        return false;
+    }
+    if schedule.attempts.len() >= config.recording.tuner_retry.max_attempts {
+        return false;
+    }
+    if let Some(end_time) = schedule.recording_end_time(&config.recording)
+        && now >= end_time
+    {
+        return false;
+    }
+    SimulatedTuner::load(config)
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| {
+            !schedule
+                .attempts
+                .iter()
+                .any(|attempt| attempt.tuner_index == *index)
+        })
+        .any(|(_, tuner)| tuner.is_usable_for(&schedule.service.channel))
+}
+
+fn check_retry(results: &[std::io::Result<ExitStatus>]) -> bool {
+    results.iter().any(|result| match result {
+        Ok(status) => matches!(status.code(), Some(EXIT_RETRY)),
+        _ => false,
+    })
+}
+
+fn get_first_error(results: &[std::io::Result<ExitStatus>]) -> Option<i32> {
+    results
+        .iter()
+        .flat_map(|result| match result {
+            Ok(status) => status.code(),
+            _ => None,
+        })
+        .find(|&code| code != 0)
+}
+
+// Simulates tuner allocation performed in `TunerManager::activate_tuner()` in order to detect
+// recording schedules which will fail to start recording due to lack of tuners.
+//
+// A reserved tuner is never used for a recording schedule whose priority is not higher than the
+// priority of the reservation, even if the tuner can be shared.  Because the user of the
+// reservation may tune it to another channel at any time.
+fn detect_conflicts<'a, I>(
+    config: &Config,
+    schedules: I,
+    reservations: &[TunerReservation],
+) -> HashMap<ProgramId, RecordingConflict>
+where
+    I: Iterator<Item = &'a RecordingSchedule>,
+{
+    let mut tuners = SimulatedTuner::load(config);
+    let mut conflicts: HashMap<ProgramId, RecordingConflict> = HashMap::new();
+
+    let schedules = schedules
+        .filter(|schedule| schedule.is_ready_for_recording() || schedule.is_recording())
+        .filter_map(|schedule| {
+            let start_at = schedule.recording_start_time(&config.recording)?;
+            let end_at = schedule.recording_end_time(&config.recording)?;
+            Some((schedule, start_at, end_at))
+        })
+        .sorted_by(|(s1, t1, _), (s2, t2, _)| {
+            t1.cmp(t2)
+                .then_with(|| s2.options.priority.cmp(&s1.options.priority))
+                .then_with(|| s1.program.id.value().cmp(&s2.program.id.value()))
+        });
+
+    for (schedule, start_at, end_at) in schedules {
+        for tuner in tuners.iter_mut() {
+            tuner.release(start_at);
+        }
+
+        let channel = &schedule.service.channel;
+        let user = SimulatedUser {
+            program_id: schedule.program.id,
+            priority: schedule.options.priority.into(),
+            end_at,
+        };
+        let reserved_tuners = reservations
+            .iter()
+            .filter(|reservation| reservation.overlaps(start_at, end_at))
+            .filter(|reservation| user.priority <= reservation.priority)
+            .map(|reservation| reservation.tuner_index)
+            .collect_vec();
+
+        let found = tuners
+            .iter_mut()
+            .filter(|tuner| !reserved_tuners.contains(&tuner.index))
+            .filter(|tuner| tuner.is_usable_for(channel))
+            .find(|tuner| tuner.is_reuseable(channel));
+        if let Some(tuner) = found {
+            tuner.users.push(user);
+            continue;
+        }
+
+        let found = tuners
+            .iter_mut()
+            .filter(|tuner| !reserved_tuners.contains(&tuner.index))
+            .filter(|tuner| tuner.is_usable_for(channel))
+            .find(|tuner| tuner.is_available());
+        if let Some(tuner) = found {
+            tuner.activate(channel, user);
+            continue;
+        }
+
+        let found = tuners
+            .iter_mut()
+            .filter(|tuner| !reserved_tuners.contains(&tuner.index))
+            .filter(|tuner| tuner.is_usable_for(channel))
+            .filter(|tuner| tuner.can_grab(user.priority))
+            .min_by_key(|tuner| tuner.priority());
+        if let Some(tuner) = found {
+            let program_id = user.program_id;
+            for evicted in tuner.activate(channel, user) {
+                conflicts
+                    .entry(evicted.program_id)
+                    .or_default()
+                    .add_programs([program_id]);
+            }
+            continue;
+        }
+
+        let programs = tuners
+            .iter()
+            .filter(|tuner| !reserved_tuners.contains(&tuner.index))
+            .filter(|tuner| tuner.is_usable_for(channel))
+            .flat_map(|tuner| tuner.users.iter().map(|user| user.program_id))
+            .collect_vec();
+        conflicts
+            .entry(schedule.program.id)
+            .or_default()
+            .add_programs(programs);
+    }
+
+    conflicts
+}
+
+pub fn make_schedules_path(config: &Config) -> Option<PathBuf> {
+    config
+        .recording
+        .basedir
+        .as_ref()
+        .map(|basedir| basedir.join("schedules.v1.json"))
+}
+
+pub fn make_rules_path(config: &Config) -> Option<PathBuf> {
+    config
+        .recording
+        .basedir
+        .as_ref()
+        .map(|basedir| basedir.join("rules.v1.json"))
+}
+
+pub fn make_recurring_schedules_path(config: &Config) -> Option<PathBuf> {
+    config
+        .recording
+        .basedir
+        .as_ref()
+        .map(|basedir| basedir.join("recurring-schedules.v1.json"))
+}
+
+fn make_series_followers_path(config: &Config) -> Option<PathBuf> {
+    config
+        .recording
+        .basedir
+        .as_ref()
+        .map(|basedir| basedir.join("series-followers.v1.json"))
+}
+
+fn make_record_path(config: &Config, record_id: &RecordId) -> Option<PathBuf> {
+    config
+        .recording
+        .records_dir
+        .as_ref()
+        .map(|records_dir| records_dir.join(format!("{}.record.json", record_id.value())))
+}
+
+fn make_index_path(config: &Config, record_id: &RecordId) -> Option<PathBuf> {
+    config
+        .recording
+        .records_dir
+        .as_ref()
+        .map(|records_dir| records_dir.join(format!("{}.index.jsonl", record_id.value())))
+}
+
+fn make_content_filename(record_id: &RecordId) -> String {
+    format!("{}.content", record_id.value())
+}
+
+fn make_content_path(config: &Config, record: &Record) -> Option<PathBuf> {
+    config
+        .recording
+        .basedir
+        .as_ref()
+        .map(|basedir| basedir.join(&record.content_path))
+}
+
+fn make_relative_content_path(config: &Config, content_path: &Path) -> PathBuf {
+    let basedir = config.recording.basedir.as_ref().unwrap();
+    content_path.strip_prefix(basedir).unwrap().to_owned()
+}
+
+fn make_content_path_from_schedule(
+    config: &Config,
+    record_id: &RecordId,
+    schedule: &RecordingSchedule,
+) -> PathBuf {
+    let basedir = config.recording.basedir.as_ref().unwrap();
+
+    match schedule.options.content_path.as_ref() {
+        Some(content_path) => {
+            debug_assert!(content_path.is_relative());
+            basedir.join(content_path).parse_dot().unwrap().into_owned()
+        }
+        None => {
+            assert!(config.recording.is_records_api_enabled());
+            basedir.join(make_content_filename(record_id))
+        }
+    }
+}
+
+// Returns the time when data starts to be written into the content file.
+fn make_record_start_time(
+    config: &Config,
+    schedule: &RecordingSchedule,
+    started_at: DateTime<Jst>,
+) -> DateTime<Jst> {
+    if schedule.is_time_range() {
+        // The service-filter outputs data immediately.
+        return started_at;
+    }
+    // The program-filter outputs no data until the recording start time.
+    schedule
+        .recording_start_time(&config.recording)
+        .map_or(started_at, |start_time| start_time.max(started_at))
+}
+
+fn make_post_process_command(
+    config: &Config,
+    template: &str,
+    record: &Record,
+) -> Result<String, Error> {
+    let template = mustache::compile_str(template)?;
+    let record_path = make_record_path(config, &record.id).unwrap();
+    let content_path = make_content_path(config, record).unwrap();
+    let recording_status = match record.recording_status {
+        RecordingStatus::Recording => "recording",
+        RecordingStatus::Finished => "finished",
+        RecordingStatus::Canceled => "canceled",
+        RecordingStatus::Failed { .. } => "failed",
+    };
+    let data = mustache::MapBuilder::new()
+        .insert_str("record_id", record.id.value())
+        .insert_str("record_path", record_path.to_string_lossy())
+        .insert_str("content_path", content_path.to_string_lossy())
+        .insert_str("content_type", &record.content_type)
+        .insert_str("recording_status", recording_status)
+        .insert("program_id", &record.program.id.value())?
+        .insert("sid", &record.program.id.sid().value())?
+        .insert("eid", &record.program.id.eid().value())?
+        .insert_str(
+            "program_name",
+            record.program.name.as_deref().unwrap_or_default(),
+        )
+        .insert_str("service_name", &record.service.name)
+        .build();
+    Ok(template.render_data_to_string(&data)?)
+}
+
+// Both stdout and stderr of the command are written to the log file.
+async fn run_post_process_command(command: &str, log_path: &Path) -> Result<i32, Error> {
+    let words = match shell_words::split(command) {
+        Ok(words) if !words.is_empty() => words,
+        _ => return Err(command_util::Error::UnableToParse(command.to_string()).into()),
+    };
+    let (prog, args) = words.split_first().unwrap();
+    let log_file = std::fs::File::create(log_path)?;
+    let mut child = tokio::process::Command::new(prog)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(log_file.try_clone()?)
+        .stderr(log_file)
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| command_util::Error::UnableToSpawn(command.to_string(), err))?;
+    let status = child.wait().await?;
+    // The exit code doesn't exist if the process was terminated by a signal.
+    Ok(status.code().unwrap_or(-1))
+}
+
+// Copies a timeshift record into the content file through the post-filters.
+async fn copy_timeshift_record<T, S>(
+    stream: MpegTsStream<T, S>,
+    pipeline: Option<CommandPipeline<T>>,
+    content_path: &Path,
+) -> Result<(), RecordingFailedReason>
+where
+    T: std::fmt::Display + Clone + Unpin,
+    S: Stream<Item = std::io::Result<Bytes>> + Unpin,
+{
+    let to_reason = |err: std::io::Error| RecordingFailedReason::IoError {
+        message: format!("{err}"),
+        os_error: err.raw_os_error(),
+    };
+
+    let file = tokio::fs::File::create(content_path)
+        .await
+        .map_err(to_reason)?;
+    let mut writer = BufWriter::new(file);
+    match pipeline {
+        Some(mut pipeline) => {
+            let (input, mut output) = pipeline.take_endpoints();
+            let (_, result) = tokio::join!(
+                stream.pipe(input),
+                tokio::io::copy(&mut output, &mut writer)
+            );
+            result.map_err(to_reason)?;
+            let results = pipeline.wait().await;
+            if let Some(exit_code) = get_first_error(&results) {
+                return Err(RecordingFailedReason::PipelineError { exit_code });
+            }
+        }
+        None => {
+            let mut reader = StreamReader::new(stream);
+            tokio::io::copy(&mut reader, &mut writer)
+                .await
+                .map_err(to_reason)?;
+        }
+    }
+    Ok(())
+}
+
+// content_path + "." + name + ".log"
+fn make_post_process_log_path(content_path: &Path, name: &str) -> PathBuf {
+    let mut path = content_path.as_os_str().to_owned();
+    path.push(".");
+    path.push(name);
+    path.push(".log");
+    path.into()
+}
+
+// Appends a message with a timestamp to the log file of a recording.
+async fn append_log(log_path: &Path, message: &str) {
+    let result = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+        .await;
+    let mut file = match result {
+        Ok(file) => file,
+        Err(err) => {
+            tracing::error!(?err, ?log_path, "Failed to open");
+            return;
+        }
+    };
+    let log = format!("{} {message}\n", Jst::now().to_rfc3339());
+    if let Err(err) = file.write_all(log.as_bytes()).await {
+        tracing::error!(?err, ?log_path, "Failed to write, the log is lost");
+    }
+}
+
+// content_path + ".log"
+fn make_log_path_from_content_path(content_path: &Path) -> PathBuf {
+    // TODO(refactor): use PathBuf::add_extension() when it's stabilized.
+    let mut path = content_path.to_owned();
+    path.as_mut_os_string().push(".log");
+    path
+}
+
+// Loads the content index of a record.
+//
+// `None` is returned if there is no usable index.
+async fn load_content_index(
+    config: &Config,
+    record: &Record,
+    content_length: u64,
+) -> Option<ContentIndex> {
+    let index = match make_index_path(config, &record.id) {
+        Some(index_path) => ContentIndex::load(&index_path).await,
+        None => None,
+    };
+    let mut index = match index {
+        Some(index) if !index.points.is_empty() => index,
+        _ => return None,
+    };
+    if matches!(record.recording_status, RecordingStatus::Recording) {
+        // Data may have been written after the last point.
+        index.push(ContentIndexPoint {
+            timestamp: Jst::now(),
+            pos: content_length,
+        });
+    }
+    if index.points.len() < 2 {
+        return None;
+    }
+    Some(index)
+}
+
+/// Rebuilds the content index of a record by scanning its content file.
+///
+/// Returns the rebuilt index, or `None` if the record is currently recording.  The content index
+/// of such a record is being built by the recording.
+pub async fn rebuild_content_index(
+    config: &Config,
+    record_id: &RecordId,
+) -> Result<Option<ContentIndex>, Error> {
+    let record_path = match make_record_path(config, record_id) {
+        Some(record_path) => record_path,
+        None => return Err(Error::WrongConfig("config.recording.records-dir")),
+    };
+
+    let (record, content_length) = load_record(config, &record_path).await?;
+    if matches!(record.recording_status, RecordingStatus::Recording) {
+        return Ok(None);
+    }
+    if !matches!(content_length, Some(content_length) if content_length > 0) {
+        return Err(Error::NoContent);
+    }
+    if record.content_type != "video/MP2T" {
+        return Err(Error::InvalidRequest("Not a TS file"));
+    }
+
+    let content_path = make_content_path(config, &record).unwrap();
+    let start_time = record.recording_start_time;
+    let index = tokio::task::spawn_blocking(move || {
+        let file = std::fs::File::open(content_path)?;
+        ContentIndex::build(std::io::BufReader::new(file), start_time)
+    })
+    .await
+    .map_err(anyhow::Error::from)??;
+
+    let index_path = make_index_path(config, record_id).unwrap();
+    if !index.save(&index_path) {
+        return Err(Error::InvalidPath("Failed to save the content index"));
+    }
+    Ok(Some(index))
+}
+
+async fn load_record(config: &Config, record_path: &Path) -> Result<(Record, Option<u64>), Error> {
+    let data = tokio::fs::read(record_path).await?;
+    let record: Record = serde_json::from_slice(&data)?;
+    let content_path = make_content_path(config, &record).unwrap();
+    let size = content_path.is_file().then(|| {
+        content_path
+            .metadata()
+            .ok()
+            .map(|metadata| metadata.len())
+            .unwrap_or(0)
+    });
+    Ok((record, size))
+}
+
+// Selects records to be purged according to the retention policies.
+//
+// Records are returned in order from oldest to newest.  Records currently being recorded are never
+// selected.
+fn select_records_to_purge(
+    config: &RecordingRetentionConfig,
+    now: DateTime<Jst>,
+    records: &[(Record, Option<u64>)],
+    free_space: Option<u64>,
+) -> Vec<RecordId> {
+    fn is_purgeable(record: &Record) -> bool {
+        !matches!(record.recording_status, RecordingStatus::Recording)
+    }
+
+    // From newest to oldest.
+    let records = records
+        .iter()
+        .sorted_by(|(a, _), (b, _)| b.recording_start_time.cmp(&a.recording_start_time))
+        .collect_vec();
+
+    let mut purged: HashSet<&RecordId> = HashSet::new();
+
+    if let Some(max_age) = config.max_age.and_then(|d| Duration::from_std(d).ok()) {
+        for (record, _) in records.iter() {
+            if record.recording_start_time + max_age < now && is_purgeable(record) {
+                purged.insert(&record.id);
+            }
+        }
+    }
+
+    if let Some(n) = config.keep_newest_per_tag {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for (record, _) in records.iter() {
+            if record.tags.is_empty() {
+                continue;
+            }
+            // Kept if it's one of the newest N records in any tag.
+            let mut kept = false;
+            for tag in record.tags.iter() {
+                let count = counts.entry(tag.as_str()).or_default();
+                *count += 1;
+                if *count <= n {
+                    kept = true;
+                }
+            }
+            if !kept && is_purgeable(record) {
+                purged.insert(&record.id);
+            }
+        }
+    }
+
+    if let Some(n) = config.keep_newest_per_series {
+        let mut counts: HashMap<(u16, u16), usize> = HashMap::new();
+        for (record, _) in records.iter() {
+            let series = match record.program.series {
+                Some(ref series) => series,
+                None => continue,
+            };
+            // Series IDs are unique only in each network.
+            let key = (record.program.id.nid().value(), series.series_id);
+            let count = counts.entry(key).or_default();
+            *count += 1;
+            if *count > n && is_purgeable(record) {
+                purged.insert(&record.id);
+            }
+        }
+    }
+
+    // The following policies purge records in order from oldest to newest.
+
+    let mut freed: u64 = records
+        .iter()
+        .filter(|(record, _)| purged.contains(&record.id))
+        .map(|(_, size)| size.unwrap_or(0))
+        .sum();
+
+    if let Some(max_total_size) = config.max_total_size {
+        let mut total_size: u64 = records
+            .iter()
+            .filter(|(record, _)| !purged.contains(&record.id))
+            .map(|(_, size)| size.unwrap_or(0))
+            .sum();
+        for (record, size) in records.iter().rev() {
+            if total_size <= max_total_size {
+                break;
+            }
+            if purged.contains(&record.id) || !is_purgeable(record) {
+                continue;
+            }
+            let size = size.unwrap_or(0);
+            total_size -= size;
+            freed += size;
+            purged.insert(&record.id);
+        }
+    }
+
+    if let (Some(min_free_space), Some(free_space)) = (config.min_free_space, free_space) {
+        for (record, size) in records.iter().rev() {
+            if free_space + freed >= min_free_space {
+                break;
+            }
+            if purged.contains(&record.id) || !is_purgeable(record) {
+                continue;
+            }
+            freed += size.unwrap_or(0);
+            purged.insert(&record.id);
+        }
+    }
+
+    records
+        .iter()
+        .rev()
+        .filter(|(record, _)| purged.contains(&record.id))
+        .map(|(record, _)| record.id.clone())
+        .collect()
+}
+
+fn glob_records(records_dir: &Path) -> impl Iterator<Item = PathBuf> {
+    let record_pattern = format!("{}/*.record.json", records_dir.to_str().unwrap());
+    glob::glob(&record_pattern)
+        .unwrap()
+        .filter_map(|record_path| match record_path {
+            Ok(record_path) if record_path.is_file() => {
+                Some(record_path)
+            }
+            Ok(record_path) => {
+                tracing::warn!(?record_path, "Not a regular file, skip");
+                None
+            }
+            Err(err) => {
+                tracing::warn!(record_path = ?err.path(), err = ?err.error(), "Not accessible, skip");
+                None
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RecordingPostProcessJobConfig;
+    use crate::epg::stub::EpgStub;
+    use crate::onair::stub::OnairProgramManagerStub;
+    use crate::timeshift::TimeshiftRecordModel;
+    use crate::timeshift::TimeshiftRecordStreamSource;
+    use crate::tuner::TunerManager;
+    use crate::tuner::stub::TunerManagerStub;
+    use assert_matches::assert_matches;
+    use indexmap::indexmap;
+    use maplit::hashset;
+    use tempfile::TempDir;
+    use test_log::test;
+    use tokio::io::AsyncReadExt;
+    use tokio::sync::Notify;
+
+    const RECORDING_DIR: &str = "recording";
+    const RECORDS_DIR: &str = ".records";
+
+    #[test]
+    fn test_record_id() {
+        assert_eq!(
+            RecordId::new(0, 0).value(),
+            "00000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let mut manager = recording_manager!(config.clone());
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 1), now, "1h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Tracking,
+            program!((0, 1, 2), now, "1h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("2.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Recording,
+            program!((0, 1, 3), now - Duration::try_hours(1).unwrap(), "2h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("3.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        let num_schedules = manager.schedules.len();
+
+        manager.save_schedules();
+        assert!(make_schedules_path(&config).unwrap().is_file());
+
+        let mut manager = recording_manager!(config.clone());
+        manager.load_schedules();
+        assert_eq!(manager.schedules.len(), num_schedules);
+        assert!(manager.schedules.contains_key(&(0, 1, 1).into()));
+        assert!(manager.schedules.contains_key(&(0, 1, 2).into()));
+        assert!(manager.schedules.contains_key(&(0, 1, 3).into()));
+    }
+
+    #[test]
+    fn test_rebuild_queue() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let mut manager = recording_manager!(config);
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 1), now + Duration::try_hours(1).unwrap()),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 2), now),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("2.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Tracking,
+            program!((0, 1, 3), now),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("3.m2ts", 1)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Recording,
+            program!((0, 1, 4), now - Duration::try_minutes(30).unwrap()),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("4.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        manager.rebuild_queue();
+        assert_matches!(manager.queue.pop(), Some(item) => {
+            assert_eq!(item.program_id, (0, 1, 3).into());
+        });
+        assert_matches!(manager.queue.pop(), Some(item) => {
+            assert_eq!(item.program_id, (0, 1, 2).into());
+        });
+        assert_matches!(manager.queue.pop(), Some(item) => {
+            assert_eq!(item.program_id, (0, 1, 1).into());
+        });
+        assert_matches!(manager.queue.pop(), None);
+    }
+
+    #[test]
+    fn test_select_records_to_purge() {
+        use crate::epg::SeriesDescriptor;
+
+        let now = Jst::now();
+
+        macro_rules! finished {
+            ($id:expr, $hours_ago:expr) => {
+                record!(
+                    $id,
+                    RecordingStatus::Finished,
+                    now - Duration::try_hours($hours_ago).unwrap(),
+                    Some(now)
+                )
+            };
+        }
+
+        let series = SeriesDescriptor {
+            series_id: 1,
+            repeat_label: 0,
+            program_pattern: 0,
+            expire_date: None,
+            episode_number: 0,
+            last_episode_number: 0,
+            series_name: None,
+        };
+
+        let mut records = vec![];
+        let mut record = finished!("1", 50);
+        record.tags.insert("a".to_string());
+        records.push((record, Some(100)));
+        let mut record = finished!("2", 40);
+        record.tags.insert("a".to_string());
+        record.program.series = Some(series.clone());
+        records.push((record, Some(100)));
+        let mut record = finished!("3", 30);
+        record.tags.insert("a".to_string());
+        record.tags.insert("b".to_string());
+        record.program.series = Some(series.clone());
+        records.push((record, Some(100)));
+        let mut record = finished!("4", 20);
+        record.program.series = Some(series.clone());
+        records.push((record, Some(100)));
+        let mut record = finished!("5", 10);
+        record.recording_status = RecordingStatus::Recording;
+        records.push((record, Some(100)));
+
+        let config = RecordingRetentionConfig::default();
+        assert!(select_records_to_purge(&config, now, &records, Some(0)).is_empty());
+
+        let config = RecordingRetentionConfig {
+            max_age: Some(std::time::Duration::from_secs(35 * 3600)),
+            ..Default::default()
+        };
+        assert_eq!(
+            select_records_to_purge(&config, now, &records, None),
+            vec!["1".to_string().into(), "2".to_string().into()]
+        );
+
+        // The record #3 is kept because it's the newest record with the tag "b".
+        let config = RecordingRetentionConfig {
+            keep_newest_per_tag: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            select_records_to_purge(&config, now, &records, None),
+            vec!["1".to_string().into(), "2".to_string().into()]
+        );
+
+        let config = RecordingRetentionConfig {
+            keep_newest_per_series: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            select_records_to_purge(&config, now, &records, None),
+            vec!["2".to_string().into()]
+        );
+
+        let config = RecordingRetentionConfig {
+            max_total_size: Some(250),
+            ..Default::default()
+        };
+        assert_eq!(
+            select_records_to_purge(&config, now, &records, None),
+            vec![
+                "1".to_string().into(),
+                "2".to_string().into(),
+                "3".to_string().into()
+            ]
+        );
+
+        let config = RecordingRetentionConfig {
+            min_free_space: Some(1000),
+            ..Default::default()
+        };
+        assert_eq!(
+            select_records_to_purge(&config, now, &records, Some(1000)),
+            Vec::<RecordId>::new()
+        );
+        assert_eq!(
+            select_records_to_purge(&config, now, &records, Some(850)),
+            vec!["1".to_string().into(), "2".to_string().into()]
+        );
+        // The record #5 being recorded is never purged.
+        assert_eq!(
+            select_records_to_purge(&config, now, &records, Some(0)),
+            vec![
+                "1".to_string().into(),
+                "2".to_string().into(),
+                "3".to_string().into(),
+                "4".to_string().into()
+            ]
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_apply_retention_policies() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = config_for_test(temp_dir.path());
+        Arc::get_mut(&mut config)
+            .unwrap()
+            .recording
+            .retention
+            .max_age = Some(std::time::Duration::from_secs(3600));
+
+        let manager = recording_manager!(config.clone());
+
+        let old = record!(
+            "old",
+            RecordingStatus::Finished,
+            now - Duration::try_hours(2).unwrap(),
+            Some(now)
+        );
+        let new = record!(
+            "new",
+            RecordingStatus::Finished,
+            now - Duration::try_minutes(30).unwrap(),
+            Some(now)
+        );
+        for record in [&old, &new] {
+            let record_path = make_record_path(&config, &record.id).unwrap();
+            assert!(file_util::save_json(record, &record_path));
+            let content_path = make_content_path(&config, record).unwrap();
+            std::fs::write(&content_path, b"content").unwrap();
+        }
+
+        manager.apply_retention_policies(now).await;
+
+        assert!(!make_record_path(&config, &old.id).unwrap().exists());
+        assert!(!make_content_path(&config, &old).unwrap().exists());
+        assert!(make_record_path(&config, &new.id).unwrap().exists());
+        assert!(make_content_path(&config, &new).unwrap().exists());
+    }
+
+    #[test(tokio::test)]
+    async fn test_enqueue_post_process_jobs() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = config_for_test(temp_dir.path());
+        let jobs = &mut Arc::get_mut(&mut config)
+            .unwrap()
+            .recording
+            .post_process
+            .jobs;
+        jobs.insert(
+            "a".to_string(),
+            RecordingPostProcessJobConfig {
+                command: "true".to_string(),
+                run_on_failure: false,
+            },
+        );
+        jobs.insert(
+            "b".to_string(),
+            RecordingPostProcessJobConfig {
+                command: "true".to_string(),
+                run_on_failure: true,
+            },
+        );
+
+        let mut manager = recording_manager!(config.clone());
+
+        let finished = record!("finished", RecordingStatus::Finished, now, Some(now));
+        let failed = record!(
+            "failed",
+            RecordingStatus::Failed {
+                reason: RecordingFailedReason::NeedRescheduling,
+            },
+            now,
+            Some(now)
+        );
+        for record in [&finished, &failed] {
+            let record_path = make_record_path(&config, &record.id).unwrap();
+            assert!(file_util::save_json(record, &record_path));
+        }
+
+        manager.enqueue_post_process_jobs(&finished).await;
+        manager.enqueue_post_process_jobs(&failed).await;
+
+        assert_eq!(
+            manager.post_process_queue,
+            [
+                (finished.id.clone(), "a".to_string()),
+                (finished.id.clone(), "b".to_string()),
+                (failed.id.clone(), "b".to_string()),
+            ]
+        );
+
+        let record_path = make_record_path(&config, &finished.id).unwrap();
+        let (record, _) = load_record(&config, &record_path).await.unwrap();
+        assert_eq!(record.post_process_jobs.len(), 2);
+        assert!(record.post_process_jobs.iter().all(|job| job.is_active()));
+
+        let record_path = make_record_path(&config, &failed.id).unwrap();
+        let (record, _) = load_record(&config, &record_path).await.unwrap();
+        assert_eq!(record.post_process_jobs.len(), 1);
+        assert_eq!(record.post_process_jobs[0].name, "b");
+    }
+
+    #[test(tokio::test)]
+    async fn test_run_post_process_job() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = config_for_test(temp_dir.path());
+        let jobs = &mut Arc::get_mut(&mut config)
+            .unwrap()
+            .recording
+            .post_process
+            .jobs;
+        jobs.insert(
+            "ok".to_string(),
+            RecordingPostProcessJobConfig {
+                command: "sh -c 'echo {{{record_id}}}'".to_string(),
+                run_on_failure: false,
+            },
+        );
+        jobs.insert(
+            "ng".to_string(),
+            RecordingPostProcessJobConfig {
+                command: "sh -c 'exit 2'".to_string(),
+                run_on_failure: false,
+            },
+        );
+
+        let record = record!("record", RecordingStatus::Finished, now, Some(now));
+        let record_path = make_record_path(&config, &record.id).unwrap();
+        assert!(file_util::save_json(&record, &record_path));
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
+
+            let result = manager
+                .call(RunPostProcessJob {
+                    record_id: record.id.clone(),
+                    name: "unknown".to_string(),
+                })
+                .await;
+            assert_matches!(result, Ok(Err(Error::PostProcessJobNotFound)));
+
+            for name in ["ok", "ng"] {
+                let result = manager
+                    .call(RunPostProcessJob {
+                        record_id: record.id.clone(),
+                        name: name.to_string(),
+                    })
+                    .await;
+                assert_matches!(result, Ok(Ok(job)) => {
+                    assert_eq!(job.name, name);
+                    assert_matches!(job.state, PostProcessJobState::Pending);
+                });
+            }
+
+            // Wait for the jobs to finish.
+            let mut record = record.clone();
+            for _ in 0..100 {
+                let result = manager
+                    .call(QueryRecord {
+                        id: record.id.clone(),
+                    })
+                    .await;
+                assert_matches!(result, Ok(Ok((r, _))) => record = r);
+                if record.post_process_jobs.iter().all(|job| !job.is_active()) {
+                    break;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+
+            let ok = record.post_process_job("ok").unwrap();
+            assert_matches!(ok.state, PostProcessJobState::Succeeded);
+            assert_eq!(ok.exit_code, Some(0));
+            assert!(ok.started_at.is_some());
+            assert!(ok.finished_at.is_some());
+
+            let ng = record.post_process_job("ng").unwrap();
+            assert_matches!(ng.state, PostProcessJobState::Failed);
+            assert_eq!(ng.exit_code, Some(2));
+
+            let content_path = make_content_path(&config, &record).unwrap();
+            let log = std::fs::read_to_string(make_post_process_log_path(&content_path, "ok"));
+            assert_eq!(log.unwrap(), format!("{}\n", record.id.value()));
+        }
+        system.shutdown().await;
+    }
+
+    #[test]
+    fn test_make_post_process_command() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let record = record!("record", RecordingStatus::Finished, now, Some(now));
+        let command = make_post_process_command(
+            &config,
+            "cmd {{{record_id}}} {{{content_path}}} {{{recording_status}}}",
+            &record,
+        );
+        let content_path = make_content_path(&config, &record).unwrap();
+        assert_eq!(
+            command.unwrap(),
+            format!("cmd record {} finished", content_path.display())
+        );
+    }
+
+    #[test]
+    fn test_rebuild_queue_with_margins() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = config_for_test(temp_dir.path());
+        Arc::get_mut(&mut config).unwrap().recording.start_margin =
+            Some(std::time::Duration::from_secs(60));
+
+        let mut manager = recording_manager!(config);
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 1), now + Duration::try_hours(1).unwrap()),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        let mut options = recording_options!("2.m2ts", 0);
+        options.start_margin = Some(Duration::try_minutes(5).unwrap());
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 2), now + Duration::try_hours(1).unwrap()),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            options
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        manager.rebuild_queue();
+        assert_matches!(manager.queue.pop(), Some(item) => {
+            assert_eq!(item.program_id, (0, 1, 2).into());
+            assert_eq!(item.start_at, now + Duration::try_minutes(55).unwrap());
+        });
+        assert_matches!(manager.queue.pop(), Some(item) => {
+            assert_eq!(item.program_id, (0, 1, 1).into());
+            assert_eq!(item.start_at, now + Duration::try_minutes(59).unwrap());
+        });
+        assert_matches!(manager.queue.pop(), None);
+    }
+
+    #[test]
+    fn test_recording_options_validate() {
+        let options = recording_options!(0);
+        assert_matches!(options.validate(), Ok(()));
+
+        let mut options = recording_options!(0);
+        options.start_margin = Some(Duration::try_hours(1).unwrap());
+        options.end_margin = Some(Duration::zero());
+        assert_matches!(options.validate(), Ok(()));
+
+        let mut options = recording_options!(0);
+        options.start_margin = Some(Duration::try_seconds(-1).unwrap());
+        assert_matches!(options.validate(), Err(Error::InvalidRequest(_)));
+
+        let mut options = recording_options!(0);
+        options.end_margin =
+            Some(Duration::try_hours(1).unwrap() + Duration::try_seconds(1).unwrap());
+        assert_matches!(options.validate(), Err(Error::InvalidRequest(_)));
+    }
+
+    #[allow(clippy::get_first)]
+    #[test]
+    fn test_query_schedules() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let mut manager = recording_manager!(config);
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 1), now + Duration::try_hours(1).unwrap()),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 2), now),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("2.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Tracking,
+            program!((0, 1, 3), now),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("3.m2ts", 1)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Recording,
+            program!((0, 1, 4), now - Duration::try_minutes(30).unwrap()),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("4.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        assert_eq!(manager.schedules.len(), 4);
+
+        let schedules = manager.query_schedules();
+        assert_eq!(schedules.len(), 4);
+        assert_matches!(schedules.get(0), Some(schedule) => {
+            assert_eq!(schedule.program.id, (0, 1, 4).into());
+        });
+        assert_matches!(schedules.get(1), Some(schedule) => {
+            assert_eq!(schedule.program.id, (0, 1, 3).into());
+        });
+        assert_matches!(schedules.get(2), Some(schedule) => {
+            assert_eq!(schedule.program.id, (0, 1, 2).into());
+        });
+        assert_matches!(schedules.get(3), Some(schedule) => {
+            assert_eq!(schedule.program.id, (0, 1, 1).into());
+        });
+    }
+
+    #[test]
+    fn test_query_schedule() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let mut manager = recording_manager!(config);
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 1), now),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        assert_matches!(manager.query_schedule((0, 1, 1).into()), Ok(schedule) => {
+            assert_eq!(schedule.program.id, (0, 1, 1).into());
+        });
+        assert_matches!(manager.query_schedule((0, 1, 2).into()), Err(err) => {
+            assert_matches!(err, Error::ScheduleNotFound);
+        });
+    }
+
+    #[test]
+    fn test_add_schedule() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let mut manager = recording_manager!(config);
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 1), now, "1h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+        assert_eq!(manager.schedules.len(), 1);
+
+        // Schedule already exists.
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 1), now, "1h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Err(Error::AlreadyExists));
+        assert_eq!(manager.schedules.len(), 1);
+
+        // Adding a schedule for an ended program is allowed.
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 2), now - Duration::try_hours(1).unwrap(), "3h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("2.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+        assert_eq!(manager.schedules.len(), 2);
+
+        // Adding a schedule for a program already started is allowed.
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 3), now - Duration::try_hours(1).unwrap(), "30m"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("3.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+        assert_eq!(manager.schedules.len(), 3);
+    }
+
+    #[test]
+    fn test_make_time_range_schedule() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let mut manager = recording_manager!(config);
+
+        macro_rules! msg {
+            ($start_at:expr, $end_at:expr) => {
+                AddTimeRangeRecordingSchedule {
+                    service: service!((0, 1), "sv", channel_gr!("ch", "ch")),
+                    start_at: $start_at,
+                    end_at: $end_at,
+                    name: Some("test".to_string()),
+                    options: recording_options!(0),
+                    tags: Default::default(),
+                }
+            };
+        }
+
+        let result = manager.make_time_range_schedule(
+            now,
+            msg!(
+                now + Duration::try_hours(1).unwrap(),
+                now + Duration::try_hours(2).unwrap()
+            ),
+        );
+        assert_matches!(result, Ok(schedule) => {
+            assert!(schedule.is_time_range());
+            assert!(schedule.program.id.is_user_defined());
+            assert_eq!(schedule.program.start_at, Some(now + Duration::try_hours(1).unwrap()));
+            assert_eq!(schedule.program.duration, Some(Duration::try_hours(1).unwrap()));
+            assert_eq!(schedule.program.name.as_deref(), Some("test"));
+            assert!(!schedule.can_be_updated_by_epg());
+            assert_matches!(manager.add_schedule(schedule), Ok(()));
+        });
+
+        // Another program ID is allocated.
+        let result = manager.make_time_range_schedule(
+            now,
+            msg!(
+                now + Duration::try_hours(1).unwrap(),
+                now + Duration::try_hours(2).unwrap()
+            ),
+        );
+        assert_matches!(result, Ok(schedule) => {
+            assert!(!manager.schedules.contains_key(&schedule.program.id));
+        });
+
+        // Invalid time range.
+        let result = manager.make_time_range_schedule(
+            now,
+            msg!(
+                now + Duration::try_hours(2).unwrap(),
+                now + Duration::try_hours(1).unwrap()
+            ),
+        );
+        assert_matches!(result, Err(Error::InvalidRequest(_)));
+
+        // Too long.
+        let result = manager
+            .make_time_range_schedule(now, msg!(now, now + Duration::try_hours(25).unwrap()));
+        assert_matches!(result, Err(Error::InvalidRequest(_)));
+
+        // Already ended.
+        let result = manager.make_time_range_schedule(
+            now,
+            msg!(
+                now - Duration::try_hours(2).unwrap(),
+                now - Duration::try_hours(1).unwrap()
+            ),
+        );
+        assert_matches!(result, Err(Error::ProgramEnded));
+
+        // Already started.
+        let result = manager.make_time_range_schedule(
+            now,
+            msg!(
+                now - Duration::try_hours(1).unwrap(),
+                now + Duration::try_hours(1).unwrap()
+            ),
+        );
+        assert_matches!(result, Ok(_));
+    }
+
+    #[test]
+    fn test_remove_schedules() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let mut manager = recording_manager!(config);
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!(
+                (0, 1, 1),
+                now + Duration::try_seconds(PREP_SECS + 1).unwrap(),
+                "1h"
+            ),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0),
+            hashset!["tag1".to_string()]
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!(
+                (0, 1, 2),
+                now + Duration::try_seconds(PREP_SECS + 1).unwrap(),
+                "1h"
+            ),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("2.m2ts", 0),
+            hashset!["tag2".to_string()]
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        // Schedules which will start soon are always retained.
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!(
+                (0, 1, 3),
+                now + Duration::try_seconds(PREP_SECS - 1).unwrap(),
+                "1h"
+            ),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("3.m2ts", 0),
+            hashset!["tag1".to_string()]
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        // Schedules in "Tracking" are always retained.
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Tracking,
+            program!((0, 1, 4), now, "1h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("4.m2ts", 0),
+            hashset!["tag2".to_string()]
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        // Schedules in "Recording" are always retained.
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Recording,
+            program!((0, 1, 5), now, "1h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("5.m2ts", 0),
+            hashset!["tag2".to_string()]
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        // Schedules in "Rescheduling" are always removed.
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Rescheduling,
+            program!((0, 1, 6), now, "1h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("6.m2ts", 0),
+            hashset!["tag2".to_string()]
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+        assert_eq!(manager.schedules.len(), 6);
+
+        manager.remove_schedules(RemovalTarget::Tag("tag2".to_string()), now);
+        assert_eq!(manager.schedules.len(), 4);
+        assert!(manager.schedules.contains_key(&(0, 1, 1).into()));
+        assert!(manager.schedules.contains_key(&(0, 1, 3).into()));
+        assert!(manager.schedules.contains_key(&(0, 1, 4).into()));
+        assert!(manager.schedules.contains_key(&(0, 1, 5).into()));
+
+        manager.remove_schedules(RemovalTarget::All, now);
+        assert!(manager.schedules.is_empty());
+    }
+
+    #[test(tokio::test)]
+    async fn test_start_recording() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let program_id = ProgramId::from((0, 1, 1));
+        let content_filename = "1.m2ts";
+        let log_filename = "1.m2ts.log";
+
+        let mut seq = mockall::Sequence::new();
+        let mut record_saved = MockRecordSavedValidator::new();
+
+        record_saved
+            .expect_emit()
+            .withf(move |msg| {
+                let program_id_part = format!("{:08X}", program_id.value());
+                msg.record_id.value().ends_with(&program_id_part)
+                    && matches!(msg.recording_status, RecordingStatus::Recording)
+            })
+            .returning(|_| ())
+            .once()
+            .in_sequence(&mut seq);
+
+        record_saved
+            .expect_emit()
+            .withf(move |msg| {
+                let program_id_part = format!("{:08X}", program_id.value());
+                msg.record_id.value().ends_with(&program_id_part)
+                    && matches!(msg.recording_status, RecordingStatus::Finished)
+            })
+            .returning(|_| ())
+            .once()
+            .in_sequence(&mut seq);
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
+
+            let result = manager
+                .call(RegisterEmitter::RecordSaved(Emitter::new(record_saved)))
+                .await;
+            assert_matches!(result, Ok(_));
+
+            let result = manager
+                .call(StartRecording {
+                    schedule: recording_schedule!(
+                        RecordingScheduleState::Scheduled,
+                        program!(program_id, now, "1h"),
+                        service!((0, 1), "sv", channel_gr!("ch", "ch")),
+                        recording_options!(content_filename, 0)
+                    ),
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(())));
+
+            let record_pattern = format!(
+                "{}/{RECORDS_DIR}/*{:08X}.record.json",
+                temp_dir.path().to_str().unwrap(),
+                program_id.value()
+            );
+            assert_matches!(glob::glob(&record_pattern).unwrap().next(), Some(Ok(record_path)) => {
+                assert_matches!(load_record(&config, &record_path).await, Ok((record, _)) => {
+                    assert_eq!(record.program.id, program_id);
+                    assert_matches!(record.recording_status, RecordingStatus::Recording);
+                })
+            });
+        }
+        system.shutdown().await;
+
+        let content_path = temp_dir.path().join(RECORDING_DIR).join(content_filename);
+        assert!(content_path.is_file());
+
+        let log_path = temp_dir.path().join(RECORDING_DIR).join(log_filename);
+        assert!(!log_path.exists());
+
+        let record_pattern = format!(
+            "{}/{RECORDS_DIR}/*{:08X}.record.json",
+            temp_dir.path().to_str().unwrap(),
+            program_id.value()
+        );
+        assert_matches!(glob::glob(&record_pattern).unwrap().next(), Some(Ok(record_path)) => {
+            assert_matches!(load_record(&config, &record_path).await, Ok((record, _)) => {
+                assert_eq!(record.program.id, program_id);
+                // The recording stops when the system stops.
+                assert_matches!(record.recording_status, RecordingStatus::Finished);
+            })
+        });
+    }
+
+    #[test(tokio::test)]
+    async fn test_start_recording_without_content_path() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let program_id = ProgramId::from((0, 1, 1));
+
+        let mut seq = mockall::Sequence::new();
+        let mut record_saved = MockRecordSavedValidator::new();
+
+        record_saved
+            .expect_emit()
+            .withf(move |msg| {
+                let program_id_part = format!("{:08X}", program_id.value());
+                msg.record_id.value().ends_with(&program_id_part)
+                    && matches!(msg.recording_status, RecordingStatus::Recording)
+            })
+            .returning(|_| ())
+            .once()
+            .in_sequence(&mut seq);
+
+        record_saved
+            .expect_emit()
+            .withf(move |msg| {
+                let program_id_part = format!("{:08X}", program_id.value());
+                msg.record_id.value().ends_with(&program_id_part)
+                    && matches!(msg.recording_status, RecordingStatus::Finished)
+            })
+            .returning(|_| ())
+            .once()
+            .in_sequence(&mut seq);
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
+
+            let result = manager
+                .call(RegisterEmitter::RecordSaved(Emitter::new(record_saved)))
+                .await;
+            assert_matches!(result, Ok(_));
+
+            let result = manager
+                .call(StartRecording {
+                    schedule: recording_schedule!(
+                        RecordingScheduleState::Scheduled,
+                        program!(program_id, now, "1h"),
+                        service!((0, 1), "sv", channel_gr!("ch", "ch")),
+                        recording_options!(0)
+                    ),
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(())));
+
+            let record_pattern = format!(
+                "{}/{RECORDS_DIR}/*{:08X}.record.json",
+                temp_dir.path().to_str().unwrap(),
+                program_id.value()
+            );
+            assert_matches!(glob::glob(&record_pattern).unwrap().next(), Some(Ok(record_path)) => {
+                assert_matches!(load_record(&config, &record_path).await, Ok((record, _)) => {
+                    assert_eq!(record.program.id, program_id);
+                    assert_matches!(record.recording_status, RecordingStatus::Recording);
+                    assert!(record.content_path.to_str().unwrap().ends_with(".content"));
+                })
+            });
+        }
+        system.shutdown().await;
+
+        let content_pattern = format!(
+            "{}/{RECORDING_DIR}/*{:08X}.content",
+            temp_dir.path().to_str().unwrap(),
+            program_id.value()
+        );
+        assert_matches!(glob::glob(&content_pattern).unwrap().next(), Some(Ok(_)));
+
+        let log_pattern = format!(
+            "{}/{RECORDING_DIR}/*{:08X}.content.log",
+            temp_dir.path().to_str().unwrap(),
+            program_id.value()
+        );
+        assert_matches!(glob::glob(&log_pattern).unwrap().next(), None);
+
+        let record_pattern = format!(
+            "{}/{RECORDS_DIR}/*{:08X}.record.json",
+            temp_dir.path().to_str().unwrap(),
+            program_id.value()
+        );
+        assert_matches!(glob::glob(&record_pattern).unwrap().next(), Some(Ok(record_path)) => {
+            assert_matches!(load_record(&config, &record_path).await, Ok((record, _)) => {
+                assert_eq!(record.program.id, program_id);
+                // The recording stops when the system stops.
+                assert_matches!(record.recording_status, RecordingStatus::Finished);
+            })
+        });
+    }
+
+    #[test(tokio::test)]
+    async fn test_import_timeshift_record() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let program_id = ProgramId::from((0, 1, 1));
+        let content_filename = "1.m2ts";
+
+        let notify = Arc::new(Notify::new());
+        let notify2 = notify.clone();
+
+        let mut seq = mockall::Sequence::new();
+        let mut record_saved = MockRecordSavedValidator::new();
+
+        record_saved
+            .expect_emit()
+            .withf(|msg| matches!(msg.recording_status, RecordingStatus::Recording))
+            .returning(|_| ())
+            .once()
+            .in_sequence(&mut seq);
+
+        record_saved
+            .expect_emit()
+            .withf(|msg| matches!(msg.recording_status, RecordingStatus::Finished))
+            .returning(move |_| notify2.notify_one())
+            .once()
+            .in_sequence(&mut seq);
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
+
+            let result = manager
+                .call(RegisterEmitter::RecordSaved(Emitter::new(record_saved)))
+                .await;
+            assert_matches!(result, Ok(_));
+
+            let export = TimeshiftRecordExport {
+                service: service!((0, 1), "sv", channel_gr!("ch", "ch")),
+                record: TimeshiftRecordModel {
+                    id: 1u32.into(),
+                    program: program!(program_id, now, "1h"),
+                    start_time: now,
+                    end_time: now + Duration::hours(1),
+                    size: 1,
+                    recording: false,
+                    pinned: false,
+                    gaps: vec![],
+                },
+                source: TimeshiftRecordStreamSource::new_for_test("test"),
+            };
+            let result = manager
+                .call(ImportTimeshiftRecord {
+                    export,
+                    options: recording_options!(content_filename, 0),
+                    tags: hashset!["tag".to_string()],
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(record)) => {
+                assert_eq!(record.program.id, program_id);
+                assert_eq!(record.recording_start_time, now);
+                assert_matches!(record.recording_status, RecordingStatus::Recording);
+                assert!(record.tags.contains("tag"));
+            });
+
+            notify.notified().await;
 
-impl Record {
-    fn new(
-        id: RecordId,
-        start_time: DateTime<Jst>,
-        schedule: &RecordingSchedule,
-        content_path: PathBuf,
-        content_type: String,
-    ) -> Self {
-        debug_assert!(content_path.is_relative());
-        debug_assert!(!content_type.is_empty());
-        Self {
-            id,
-            program: schedule.program.clone(),
-            service: schedule.service.clone(),
-            options: schedule.options.clone(),
-            tags: schedule.tags.clone(),
-            recording_status: RecordingStatus::Recording,
-            recording_start_time: start_time,
-            recording_end_time: None,
-            recording_duration: None,
-            content_path,
-            content_type,
+            let result = manager.call(QueryRecords).await;
+            assert_matches!(result, Ok(Ok(records)) => {
+                assert_eq!(records.len(), 1);
+                assert_matches!(records[0].0.recording_status, RecordingStatus::Finished);
+                assert_eq!(records[0].1, Some(1));
+            });
         }
+        system.shutdown().await;
+
+        let content_path = temp_dir.path().join(RECORDING_DIR).join(content_filename);
+        assert_eq!(std::fs::metadata(content_path).unwrap().len(), 1);
     }
 
-    fn update_by_schedule(&mut self, schedule: &RecordingSchedule) {
+    #[test(tokio::test)]
+    async fn test_stop_recording() {
         let now = Jst::now();
 
-        self.program = schedule.program.clone();
-        self.options = schedule.options.clone();
-        self.tags = schedule.tags.clone();
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
 
-        self.recording_end_time = Some(now);
-        self.recording_duration = Some(now - self.recording_start_time);
+        let program_id = ProgramId::from((0, 1, 1));
+        let content_filename = "1.m2ts";
 
-        match schedule.state {
-            RecordingScheduleState::Scheduled | RecordingScheduleState::Tracking => (),
-            RecordingScheduleState::Recording => {
-                self.recording_status = RecordingStatus::Recording;
-                self.recording_end_time = None;
-                self.recording_duration = None;
-            }
-            RecordingScheduleState::Rescheduling => {
-                self.recording_status = RecordingStatus::Failed {
-                    reason: RecordingFailedReason::NeedRescheduling,
-                };
-            }
-            RecordingScheduleState::Finished => {
-                self.recording_status = RecordingStatus::Finished;
-            }
-            RecordingScheduleState::Failed => {
-                self.recording_status = RecordingStatus::Failed {
-                    reason: schedule.failed_reason.clone().unwrap(),
-                };
-            }
-        }
-    }
-}
+        let notify = Arc::new(Notify::new());
+        let notify2 = notify.clone();
 
-/// A recording status.
-#[derive(Clone, Debug, Deserialize, Serialize)]
-pub enum RecordingStatus {
-    Recording,
-    Finished,
-    Canceled,
-    Failed { reason: RecordingFailedReason },
-}
+        let mut seq = mockall::Sequence::new();
+        let mut stopped = MockRecordingStoppedValidator::new();
+        let mut record_saved = MockRecordSavedValidator::new();
 
-// helpers
+        record_saved
+            .expect_emit()
+            .withf(move |msg| {
+                let program_id_part = format!("{:08X}", program_id.value());
+                msg.record_id.value().ends_with(&program_id_part)
+                    && matches!(msg.recording_status, RecordingStatus::Recording)
+            })
+            .returning(|_| ())
+            .once()
+            .in_sequence(&mut seq);
 
-fn check_retry(results: &[std::io::Result<ExitStatus>]) -> bool {
-    results.iter().any(|result| match result {
-        Ok(status) => matches!(status.code(), Some(EXIT_RETRY)),
-        _ => false,
-    })
-}
+        record_saved
+            .expect_emit()
+            .withf(move |msg| {
+                let program_id_part = format!("{:08X}", program_id.value());
+                msg.record_id.value().ends_with(&program_id_part)
+                    && matches!(msg.recording_status, RecordingStatus::Finished)
+            })
+            .returning(|_| ())
+            .once()
+            .in_sequence(&mut seq);
 
-fn get_first_error(results: &[std::io::Result<ExitStatus>]) -> Option<i32> {
-    results
-        .iter()
-        .flat_map(|result| match result {
-            Ok(status) => status.code(),
-            _ => None,
-        })
-        .find(|&code| code != 0)
-}
+        stopped
+            .expect_emit()
+            .returning(move |_| notify2.notify_one())
+            .once()
+            .in_sequence(&mut seq);
 
-pub fn make_schedules_path(config: &Config) -> Option<PathBuf> {
-    config
-        .recording
-        .basedir
-        .as_ref()
-        .map(|basedir| basedir.join("schedules.v1.json"))
-}
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
 
-fn make_record_path(config: &Config, record_id: &RecordId) -> Option<PathBuf> {
-    config
-        .recording
-        .records_dir
-        .as_ref()
-        .map(|records_dir| records_dir.join(format!("{}.record.json", record_id.value())))
-}
+            let result = manager
+                .call(RegisterEmitter::RecordingStopped(Emitter::new(stopped)))
+                .await;
+            assert_matches!(result, Ok(_));
 
-fn make_content_filename(record_id: &RecordId) -> String {
-    format!("{}.content", record_id.value())
-}
+            let result = manager
+                .call(RegisterEmitter::RecordSaved(Emitter::new(record_saved)))
+                .await;
+            assert_matches!(result, Ok(_));
 
-fn make_content_path(config: &Config, record: &Record) -> Option<PathBuf> {
-    config
-        .recording
-        .basedir
-        .as_ref()
-        .map(|basedir| basedir.join(&record.content_path))
-}
+            let result = manager
+                .call(StartRecording {
+                    schedule: recording_schedule!(
+                        RecordingScheduleState::Scheduled,
+                        program!(program_id, now, "1h"),
+                        service!((0, 1), "sv", channel_gr!("ch", "ch")),
+                        recording_options!(content_filename, 0)
+                    ),
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(())));
 
-fn make_relative_content_path(config: &Config, content_path: &Path) -> PathBuf {
-    let basedir = config.recording.basedir.as_ref().unwrap();
-    content_path.strip_prefix(basedir).unwrap().to_owned()
-}
+            let result = manager.call(StopRecording { program_id }).await;
+            assert_matches!(result, Ok(Ok(())));
 
-fn make_content_path_from_schedule(
-    config: &Config,
-    record_id: &RecordId,
-    schedule: &RecordingSchedule,
-) -> PathBuf {
-    let basedir = config.recording.basedir.as_ref().unwrap();
+            notify.notified().await;
 
-    match schedule.options.content_path.as_ref() {
-        Some(content_path) => {
-            debug_assert!(content_path.is_relative());
-            basedir.join(content_path).parse_dot().unwrap().into_owned()
-        }
-        None => {
-            assert!(config.recording.is_records_api_enabled());
-            basedir.join(make_content_filename(record_id))
+            let record_pattern = format!(
+                "{}/{RECORDS_DIR}/*{:08X}.record.json",
+                temp_dir.path().to_str().unwrap(),
+                program_id.value()
+            );
+            assert_matches!(glob::glob(&record_pattern).unwrap().next(), Some(Ok(record_path)) => {
+                assert_matches!(load_record(&config, &record_path).await, Ok((record, _)) => {
+                    assert_eq!(record.program.id, program_id);
+                    assert_matches!(record.recording_status, RecordingStatus::Finished);
+                })
+            });
         }
+        system.shutdown().await;
+
+        let content_path = temp_dir.path().join(RECORDING_DIR).join(content_filename);
+        assert!(content_path.is_file());
     }
-}
 
-// content_path + ".log"
-fn make_log_path_from_content_path(content_path: &Path) -> PathBuf {
-    // TODO(refactor): use PathBuf::add_extension() when it's stabilized.
-    let mut path = content_path.to_owned();
-    path.as_mut_os_string().push(".log");
-    path
-}
+    #[test(tokio::test)]
+    async fn test_time_range_stop_timer() {
+        let now = Jst::now();
 
-async fn load_record(config: &Config, record_path: &Path) -> Result<(Record, Option<u64>), Error> {
-    let data = tokio::fs::read(record_path).await?;
-    let record: Record = serde_json::from_slice(&data)?;
-    let content_path = make_content_path(config, &record).unwrap();
-    let size = content_path.is_file().then(|| {
-        content_path
-            .metadata()
-            .ok()
-            .map(|metadata| metadata.len())
-            .unwrap_or(0)
-    });
-    Ok((record, size))
-}
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_with_tuner_for_test(temp_dir.path());
+
+        let service = service!((0, 1), "sv", channel_gr!("ch", "ch"));
+        let program_id = ProgramId::new_user_defined(service.id, 0).unwrap();
+
+        let notify = Arc::new(Notify::new());
+        let notify2 = notify.clone();
+        let mut stopped = MockRecordingStoppedValidator::new();
+        stopped
+            .expect_emit()
+            .returning(move |_| notify2.notify_one());
 
-fn glob_records(records_dir: &Path) -> impl Iterator<Item = PathBuf> {
-    let record_pattern = format!("{}/*.record.json", records_dir.to_str().unwrap());
-    glob::glob(&record_pattern)
-        .unwrap()
-        .filter_map(|record_path| match record_path {
-            Ok(record_path) if record_path.is_file() => {
-                Some(record_path)
-            }
-            Ok(record_path) => {
-                tracing::warn!(?record_path, "Not a regular file, skip");
-                None
-            }
-            Err(err) => {
-                tracing::warn!(record_path = ?err.path(), err = ?err.error(), "Not accessible, skip");
-                None
-            }
-        })
-}
+        let system = System::new();
+        {
+            let tuner_manager = system.spawn_actor(TunerManager::new(config.clone())).await;
+            let manager = system
+                .spawn_actor(recording_manager!(
+                    config.clone(),
+                    tuner_manager,
+                    EpgStub,
+                    OnairProgramManagerStub
+                ))
+                .await;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::epg::stub::EpgStub;
-    use crate::onair::stub::OnairProgramManagerStub;
-    use crate::tuner::stub::TunerManagerStub;
-    use assert_matches::assert_matches;
-    use indexmap::indexmap;
-    use maplit::hashset;
-    use tempfile::TempDir;
-    use test_log::test;
-    use tokio::io::AsyncReadExt;
-    use tokio::sync::Notify;
+            let result = manager
+                .call(RegisterEmitter::RecordingStopped(Emitter::new(stopped)))
+                .await;
+            assert_matches!(result, Ok(_));
 
-    const RECORDING_DIR: &str = "recording";
-    const RECORDS_DIR: &str = ".records";
+            let result = manager
+                .call(StartRecording {
+                    schedule: recording_schedule!(
+                        RecordingScheduleState::Scheduled,
+                        program!(program_id, now, "1s"),
+                        service.clone(),
+                        recording_options!("1.m2ts", 0)
+                    ),
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(())));
 
-    #[test]
-    fn test_record_id() {
-        assert_eq!(
-            RecordId::new(0, 0).value(),
-            "00000000000000000000000000000000"
-        );
+            let result = manager.call(RemoveRecordingSchedule { program_id }).await;
+            assert_matches!(result, Ok(Ok(_)));
+            notify.notified().await;
+
+            // Reuse the program ID.
+            let result = manager
+                .call(StartRecording {
+                    schedule: recording_schedule!(
+                        RecordingScheduleState::Scheduled,
+                        program!(program_id, now, "1h"),
+                        service.clone(),
+                        recording_options!("2.m2ts", 0)
+                    ),
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(())));
+
+            // The stop timer for the first recording must not stop the second recording.
+            tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+            let result = manager.call(QueryRecordingRecorder { program_id }).await;
+            assert!(matches!(result, Ok(Ok(_))));
+
+            let result = manager.call(StopRecording { program_id }).await;
+            assert_matches!(result, Ok(Ok(())));
+            notify.notified().await;
+        }
+        system.shutdown().await;
     }
 
-    #[test]
-    fn test_save_and_load() {
+    #[test(tokio::test)]
+    async fn test_maintain_schedules() {
         let now = Jst::now();
 
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let mut manager = recording_manager!(config.clone());
+        let max_delay = Duration::try_hours(MAX_DELAY_HOURS).unwrap();
 
+        let mut manager = recording_manager!(config.clone());
         let schedule = recording_schedule!(
             RecordingScheduleState::Scheduled,
-            program!((0, 1, 1), now, "1h"),
+            program!((0, 1, 1), now + Duration::try_hours(1).unwrap(), "1h"),
             service!((0, 1), "sv", channel_gr!("ch", "ch")),
             recording_options!("1.m2ts", 0)
         );
         let result = manager.add_schedule(schedule);
         assert_matches!(result, Ok(()));
+        let changed = manager.maintain_schedules(now + max_delay).await;
+        assert!(!changed);
+        assert_eq!(manager.schedules.len(), 1);
+        manager.schedules.clear();
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Tracking,
-            program!((0, 1, 2), now, "1h"),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("2.m2ts", 0)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
-
+        let mut manager = recording_manager!(config.clone());
         let schedule = recording_schedule!(
             RecordingScheduleState::Recording,
-            program!((0, 1, 3), now - Duration::try_hours(1).unwrap(), "2h"),
+            program!((0, 1, 1), now, "1h"),
             service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("3.m2ts", 0)
+            recording_options!("1.m2ts", 0)
         );
         let result = manager.add_schedule(schedule);
         assert_matches!(result, Ok(()));
+        let changed = manager.maintain_schedules(now + max_delay).await;
+        assert!(!changed);
+        assert_eq!(manager.schedules.len(), 1);
+        manager.schedules.clear();
 
-        let num_schedules = manager.schedules.len();
-
-        manager.save_schedules();
-        assert!(make_schedules_path(&config).unwrap().is_file());
+        let states = [
+            RecordingScheduleState::Scheduled,
+            RecordingScheduleState::Tracking,
+            RecordingScheduleState::Rescheduling,
+        ];
+        for state in states {
+            let mut manager = recording_manager!(config.clone());
+            let mut failed = MockRecordingFailedValidator::new();
+            failed.expect_emit().times(1).returning(|msg| {
+                assert_eq!(msg.program_id, (0, 1, 1).into());
+            });
+            manager.recording_failed.register(Emitter::new(failed));
+            let schedule = recording_schedule!(
+                state,
+                program!((0, 1, 1), now, "1h"),
+                service!((0, 1), "sv", channel_gr!("ch", "ch")),
+                recording_options!("1.m2ts", 0)
+            );
+            let result = manager.add_schedule(schedule);
+            assert_matches!(result, Ok(()));
+            let changed = manager.maintain_schedules(now + max_delay).await;
+            assert!(changed);
+            assert!(manager.schedules.is_empty());
+        }
 
-        let mut manager = recording_manager!(config.clone());
-        manager.load_schedules();
-        assert_eq!(manager.schedules.len(), num_schedules);
-        assert!(manager.schedules.contains_key(&(0, 1, 1).into()));
-        assert!(manager.schedules.contains_key(&(0, 1, 2).into()));
-        assert!(manager.schedules.contains_key(&(0, 1, 3).into()));
+        let states = [
+            RecordingScheduleState::Finished,
+            RecordingScheduleState::Failed,
+        ];
+        for state in states {
+            let mut manager = recording_manager!(config.clone());
+            let mut failed = MockRecordingFailedValidator::new();
+            failed.expect_emit().never();
+            manager.recording_failed.register(Emitter::new(failed));
+            let schedule = recording_schedule!(
+                state,
+                program!((0, 1, 1), now, "1h"),
+                service!((0, 1), "sv", channel_gr!("ch", "ch")),
+                recording_options!("1.m2ts", 0)
+            );
+            let result = manager.add_schedule(schedule);
+            assert_matches!(result, Ok(()));
+            let changed = manager.maintain_schedules(now + max_delay).await;
+            assert!(changed);
+            assert!(manager.schedules.is_empty());
+        }
     }
 
-    #[test]
-    fn test_rebuild_queue() {
+    #[test(tokio::test)]
+    async fn test_remove_record() {
         let now = Jst::now();
 
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let mut manager = recording_manager!(config);
+        let program_id = ProgramId::from((0, 1, 1));
+        let content_filename = "1.m2ts";
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!((0, 1, 1), now + Duration::try_hours(1).unwrap()),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+        let notify = Arc::new(Notify::new());
+        let notify2 = notify.clone();
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!((0, 1, 2), now),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("2.m2ts", 0)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+        let mut seq = mockall::Sequence::new();
+        let mut stopped = MockRecordingStoppedValidator::new();
+        let mut record_removed = MockRecordRemovedValidator::new();
+        let mut content_removed = MockContentRemovedValidator::new();
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Tracking,
-            program!((0, 1, 3), now),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("3.m2ts", 1)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+        stopped
+            .expect_emit()
+            .returning(move |_| notify2.notify_one())
+            .once()
+            .in_sequence(&mut seq);
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Recording,
-            program!((0, 1, 4), now - Duration::try_minutes(30).unwrap()),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("4.m2ts", 0)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+        content_removed
+            .expect_emit()
+            .withf(move |msg| {
+                let program_id_part = format!("{:08X}", program_id.value());
+                msg.record_id.value().ends_with(&program_id_part)
+            })
+            .returning(|_| ())
+            .once()
+            .in_sequence(&mut seq);
 
-        manager.rebuild_queue();
-        assert_matches!(manager.queue.pop(), Some(item) => {
-            assert_eq!(item.program_id, (0, 1, 3).into());
-        });
-        assert_matches!(manager.queue.pop(), Some(item) => {
-            assert_eq!(item.program_id, (0, 1, 2).into());
-        });
-        assert_matches!(manager.queue.pop(), Some(item) => {
-            assert_eq!(item.program_id, (0, 1, 1).into());
-        });
-        assert_matches!(manager.queue.pop(), None);
-    }
+        record_removed
+            .expect_emit()
+            .withf(move |msg| {
+                let program_id_part = format!("{:08X}", program_id.value());
+                msg.record_id.value().ends_with(&program_id_part)
+            })
+            .returning(|_| ())
+            .once()
+            .in_sequence(&mut seq);
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
+
+            let result = manager
+                .call(RegisterEmitter::RecordingStopped(Emitter::new(stopped)))
+                .await;
+            assert_matches!(result, Ok(_));
+
+            let result = manager
+                .call(RegisterEmitter::RecordRemoved(Emitter::new(record_removed)))
+                .await;
+            assert_matches!(result, Ok(_));
+
+            let result = manager
+                .call(RegisterEmitter::ContentRemoved(Emitter::new(
+                    content_removed,
+                )))
+                .await;
+            assert_matches!(result, Ok(_));
 
-    #[allow(clippy::get_first)]
-    #[test]
-    fn test_query_schedules() {
-        let now = Jst::now();
+            let result = manager
+                .call(StartRecording {
+                    schedule: recording_schedule!(
+                        RecordingScheduleState::Scheduled,
+                        program!(program_id, now, "1h"),
+                        service!((0, 1), "sv", channel_gr!("ch", "ch")),
+                        recording_options!(content_filename, 0)
+                    ),
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(())));
 
-        let temp_dir = TempDir::new().unwrap();
-        let config = config_for_test(temp_dir.path());
+            let result = manager.call(StopRecording { program_id }).await;
+            assert_matches!(result, Ok(Ok(())));
 
-        let mut manager = recording_manager!(config);
+            notify.notified().await;
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!((0, 1, 1), now + Duration::try_hours(1).unwrap()),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+            let result = manager.call(QueryRecords).await;
+            let id = assert_matches!(result, Ok(Ok(tuples)) => {
+                assert_eq!(tuples.len(), 1);
+                tuples[0].0.id.clone()
+            });
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!((0, 1, 2), now),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("2.m2ts", 0)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+            let record_path = temp_dir
+                .path()
+                .join(RECORDS_DIR)
+                .join(format!("{}.record.json", id.value()));
+            assert!(record_path.exists());
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Tracking,
-            program!((0, 1, 3), now),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("3.m2ts", 1)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+            let index_path = temp_dir
+                .path()
+                .join(RECORDS_DIR)
+                .join(format!("{}.index.jsonl", id.value()));
+            assert!(index_path.exists());
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Recording,
-            program!((0, 1, 4), now - Duration::try_minutes(30).unwrap()),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("4.m2ts", 0)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+            let content_path = temp_dir.path().join(RECORDING_DIR).join(content_filename);
+            assert!(content_path.exists());
 
-        assert_eq!(manager.schedules.len(), 4);
+            let result = manager.call(RemoveRecord { id, purge: true }).await;
+            assert_matches!(result, Ok(Ok((true, true))));
 
-        let schedules = manager.query_schedules();
-        assert_eq!(schedules.len(), 4);
-        assert_matches!(schedules.get(0), Some(schedule) => {
-            assert_eq!(schedule.program.id, (0, 1, 4).into());
-        });
-        assert_matches!(schedules.get(1), Some(schedule) => {
-            assert_eq!(schedule.program.id, (0, 1, 3).into());
-        });
-        assert_matches!(schedules.get(2), Some(schedule) => {
-            assert_eq!(schedule.program.id, (0, 1, 2).into());
-        });
-        assert_matches!(schedules.get(3), Some(schedule) => {
-            assert_eq!(schedule.program.id, (0, 1, 1).into());
-        });
+            assert!(!record_path.exists());
+            assert!(!index_path.exists());
+            assert!(!content_path.exists());
+        }
+        system.shutdown().await;
     }
 
-    #[test]
-    fn test_query_schedule() {
-        let now = Jst::now();
-
+    #[test(tokio::test)]
+    async fn test_open_content() {
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let mut manager = recording_manager!(config);
+        let id = RecordId("1".to_string());
+        let record = record!(finished: id.value());
+        let record_path = make_record_path(&config, &id).unwrap();
+        assert!(file_util::save_json(&record, &record_path));
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!((0, 1, 1), now),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+        let content_path = make_content_path(&config, &record).unwrap();
+        assert!(file_util::save_data(b"0123456789", &content_path));
 
-        assert_matches!(manager.query_schedule((0, 1, 1).into()), Ok(schedule) => {
-            assert_eq!(schedule.program.id, (0, 1, 1).into());
-        });
-        assert_matches!(manager.query_schedule((0, 1, 2).into()), Err(err) => {
-            assert_matches!(err, Error::ScheduleNotFound);
-        });
-    }
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
 
-    #[test]
-    fn test_add_schedule() {
-        let now = Jst::now();
+            let result = manager.call(OpenContent::new(id.clone(), None)).await;
+            let (stream, stop_trigger) = match result {
+                Ok(Ok(tuple)) => tuple,
+                _ => panic!(),
+            };
 
-        let temp_dir = TempDir::new().unwrap();
-        let config = config_for_test(temp_dir.path());
+            let mut reader = tokio_util::io::StreamReader::new(stream);
 
-        let mut manager = recording_manager!(config);
+            let mut buf = [0; 10];
+            reader.read_exact(&mut buf).await.unwrap(); // EOF reaches.
+            assert_eq!(&buf, b"0123456789");
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!((0, 1, 1), now, "1h"),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
-        assert_eq!(manager.schedules.len(), 1);
+            append(&content_path, b"abc").await;
 
-        // Schedule already exists.
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!((0, 1, 1), now, "1h"),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Err(Error::AlreadyExists));
-        assert_eq!(manager.schedules.len(), 1);
+            assert_matches!(reader.read_exact(&mut buf).await, Err(err) => {
+                assert_matches!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+            });
 
-        // Adding a schedule for an ended program is allowed.
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!((0, 1, 2), now - Duration::try_hours(1).unwrap(), "3h"),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("2.m2ts", 0)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
-        assert_eq!(manager.schedules.len(), 2);
+            drop(stop_trigger);
+        }
+        system.shutdown().await;
 
-        // Adding a schedule for a program already started is allowed.
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!((0, 1, 3), now - Duration::try_hours(1).unwrap(), "30m"),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("3.m2ts", 0)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
-        assert_eq!(manager.schedules.len(), 3);
+        // TODO(#2057): range request
     }
 
-    #[test]
-    fn test_remove_schedules() {
-        let now = Jst::now();
-
+    #[test(tokio::test)]
+    async fn test_open_content_during_recording() {
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let mut manager = recording_manager!(config);
-
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!(
-                (0, 1, 1),
-                now + Duration::try_seconds(PREP_SECS + 1).unwrap(),
-                "1h"
-            ),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0),
-            hashset!["tag1".to_string()]
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+        let id = RecordId("1".to_string());
+        let record = record!(recording: id.clone());
+        let record_path = make_record_path(&config, &id).unwrap();
+        assert!(file_util::save_json(&record, &record_path));
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!(
-                (0, 1, 2),
-                now + Duration::try_seconds(PREP_SECS + 1).unwrap(),
-                "1h"
-            ),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("2.m2ts", 0),
-            hashset!["tag2".to_string()]
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+        let content_path = make_content_path(&config, &record).unwrap();
+        tokio::fs::write(&content_path, b"0123456789")
+            .await
+            .unwrap();
 
-        // Schedules which will start soon are always retained.
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!(
-                (0, 1, 3),
-                now + Duration::try_seconds(PREP_SECS - 1).unwrap(),
-                "1h"
-            ),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("3.m2ts", 0),
-            hashset!["tag1".to_string()]
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
 
-        // Schedules in "Tracking" are always retained.
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Tracking,
-            program!((0, 1, 4), now, "1h"),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("4.m2ts", 0),
-            hashset!["tag2".to_string()]
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+            let result = manager.call(OpenContent::new(id.clone(), None)).await;
+            let (stream, stop_trigger) = match result {
+                Ok(Ok(tuple)) => tuple,
+                _ => panic!(),
+            };
 
-        // Schedules in "Recording" are always retained.
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Recording,
-            program!((0, 1, 5), now, "1h"),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("5.m2ts", 0),
-            hashset!["tag2".to_string()]
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+            let mut reader = tokio_util::io::StreamReader::new(stream);
 
-        // Schedules in "Rescheduling" are always removed.
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Rescheduling,
-            program!((0, 1, 6), now, "1h"),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("6.m2ts", 0),
-            hashset!["tag2".to_string()]
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
-        assert_eq!(manager.schedules.len(), 6);
+            let mut buf = [0; 10];
+            reader.read_exact(&mut buf).await.unwrap(); // EOF reaches.
+            assert_eq!(&buf, b"0123456789");
 
-        manager.remove_schedules(RemovalTarget::Tag("tag2".to_string()), now);
-        assert_eq!(manager.schedules.len(), 4);
-        assert!(manager.schedules.contains_key(&(0, 1, 1).into()));
-        assert!(manager.schedules.contains_key(&(0, 1, 3).into()));
-        assert!(manager.schedules.contains_key(&(0, 1, 4).into()));
-        assert!(manager.schedules.contains_key(&(0, 1, 5).into()));
+            append(&content_path, b"abc").await;
 
-        manager.remove_schedules(RemovalTarget::All, now);
-        assert!(manager.schedules.is_empty());
+            let mut buf = [0; 3];
+            reader.read_exact(&mut buf).await.unwrap(); // EOF reaches again.
+            assert_eq!(&buf, b"abc");
+
+            // The streaming will stop within 100ms without explicit `drop(stop_trigger)`.
+            assert_matches!(reader.read_exact(&mut buf).await, Err(err) => {
+                assert_matches!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+            });
+
+            drop(stop_trigger);
+        }
+        system.shutdown().await;
+
+        // TODO(#2057): range request
     }
 
     #[test(tokio::test)]
-    async fn test_start_recording() {
-        let now = Jst::now();
-
+    async fn test_open_content_stop_trigger() {
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let program_id = ProgramId::from((0, 1, 1));
-        let content_filename = "1.m2ts";
-        let log_filename = "1.m2ts.log";
-
-        let mut seq = mockall::Sequence::new();
-        let mut record_saved = MockRecordSavedValidator::new();
-
-        record_saved
-            .expect_emit()
-            .withf(move |msg| {
-                let program_id_part = format!("{:08X}", program_id.value());
-                msg.record_id.value().ends_with(&program_id_part)
-                    && matches!(msg.recording_status, RecordingStatus::Recording)
-            })
-            .returning(|_| ())
-            .once()
-            .in_sequence(&mut seq);
+        let id = RecordId("1".to_string());
+        let record = record!(recording: id.clone());
+        let record_path = make_record_path(&config, &id).unwrap();
+        assert!(file_util::save_json(&record, &record_path));
 
-        record_saved
-            .expect_emit()
-            .withf(move |msg| {
-                let program_id_part = format!("{:08X}", program_id.value());
-                msg.record_id.value().ends_with(&program_id_part)
-                    && matches!(msg.recording_status, RecordingStatus::Finished)
-            })
-            .returning(|_| ())
-            .once()
-            .in_sequence(&mut seq);
+        let content_path = make_content_path(&config, &record).unwrap();
+        tokio::fs::write(&content_path, b"0123456789")
+            .await
+            .unwrap();
 
         let system = System::new();
         {
             let manager = system.spawn_actor(recording_manager!(config.clone())).await;
 
-            let result = manager
-                .call(RegisterEmitter::RecordSaved(Emitter::new(record_saved)))
-                .await;
-            assert_matches!(result, Ok(_));
+            let result = manager.call(OpenContent::new(id.clone(), None)).await;
+            let (stream, stop_trigger) = match result {
+                Ok(Ok(tuple)) => tuple,
+                _ => panic!(),
+            };
 
-            let result = manager
-                .call(StartRecording {
-                    schedule: recording_schedule!(
-                        RecordingScheduleState::Scheduled,
-                        program!(program_id, now, "1h"),
-                        service!((0, 1), "sv", channel_gr!("ch", "ch")),
-                        recording_options!(content_filename, 0)
-                    ),
-                })
-                .await;
-            assert_matches!(result, Ok(Ok(())));
+            let mut reader = tokio_util::io::StreamReader::new(stream);
 
-            let record_pattern = format!(
-                "{}/{RECORDS_DIR}/*{:08X}.record.json",
-                temp_dir.path().to_str().unwrap(),
-                program_id.value()
-            );
-            assert_matches!(glob::glob(&record_pattern).unwrap().next(), Some(Ok(record_path)) => {
-                assert_matches!(load_record(&config, &record_path).await, Ok((record, _)) => {
-                    assert_eq!(record.program.id, program_id);
-                    assert_matches!(record.recording_status, RecordingStatus::Recording);
-                })
+            let mut buf = [0; 10];
+            reader.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"0123456789");
+
+            drop(stop_trigger);
+
+            // The streaming will stop soon before the timeout.
+            assert_matches!(reader.read_exact(&mut buf).await, Err(err) => {
+                assert_matches!(err.kind(), std::io::ErrorKind::UnexpectedEof);
             });
         }
         system.shutdown().await;
 
-        let content_path = temp_dir.path().join(RECORDING_DIR).join(content_filename);
-        assert!(content_path.is_file());
-
-        let log_path = temp_dir.path().join(RECORDING_DIR).join(log_filename);
-        assert!(!log_path.exists());
-
-        let record_pattern = format!(
-            "{}/{RECORDS_DIR}/*{:08X}.record.json",
-            temp_dir.path().to_str().unwrap(),
-            program_id.value()
-        );
-        assert_matches!(glob::glob(&record_pattern).unwrap().next(), Some(Ok(record_path)) => {
-            assert_matches!(load_record(&config, &record_path).await, Ok((record, _)) => {
-                assert_eq!(record.program.id, program_id);
-                // The recording stops when the system stops.
-                assert_matches!(record.recording_status, RecordingStatus::Finished);
-            })
-        });
+        // TODO(#2057): range request
     }
 
     #[test(tokio::test)]
-    async fn test_start_recording_without_content_path() {
+    async fn test_recording_with_log_filter_info() {
         let now = Jst::now();
 
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
         let program_id = ProgramId::from((0, 1, 1));
+        let content_filename = "1.m2ts";
+        let log_filename = "1.m2ts.log";
 
-        let mut seq = mockall::Sequence::new();
-        let mut record_saved = MockRecordSavedValidator::new();
+        let notify = Arc::new(Notify::new());
+        let notify2 = notify.clone();
 
-        record_saved
-            .expect_emit()
-            .withf(move |msg| {
-                let program_id_part = format!("{:08X}", program_id.value());
-                msg.record_id.value().ends_with(&program_id_part)
-                    && matches!(msg.recording_status, RecordingStatus::Recording)
-            })
-            .returning(|_| ())
-            .once()
-            .in_sequence(&mut seq);
+        let mut stopped = MockRecordingStoppedValidator::new();
 
-        record_saved
+        stopped
             .expect_emit()
-            .withf(move |msg| {
-                let program_id_part = format!("{:08X}", program_id.value());
-                msg.record_id.value().ends_with(&program_id_part)
-                    && matches!(msg.recording_status, RecordingStatus::Finished)
-            })
-            .returning(|_| ())
-            .once()
-            .in_sequence(&mut seq);
+            .returning(move |_| notify2.notify_one())
+            .once();
 
         let system = System::new();
         {
             let manager = system.spawn_actor(recording_manager!(config.clone())).await;
 
             let result = manager
-                .call(RegisterEmitter::RecordSaved(Emitter::new(record_saved)))
+                .call(RegisterEmitter::RecordingStopped(Emitter::new(stopped)))
                 .await;
             assert_matches!(result, Ok(_));
 
@@ -3236,57 +8403,36 @@ mod tests {
                         RecordingScheduleState::Scheduled,
                         program!(program_id, now, "1h"),
                         service!((0, 1), "sv", channel_gr!("ch", "ch")),
-                        recording_options!(0)
+                        recording_options!(content_filename, 0, "info")
                     ),
                 })
                 .await;
             assert_matches!(result, Ok(Ok(())));
 
-            let record_pattern = format!(
-                "{}/{RECORDS_DIR}/*{:08X}.record.json",
-                temp_dir.path().to_str().unwrap(),
-                program_id.value()
-            );
-            assert_matches!(glob::glob(&record_pattern).unwrap().next(), Some(Ok(record_path)) => {
-                assert_matches!(load_record(&config, &record_path).await, Ok((record, _)) => {
-                    assert_eq!(record.program.id, program_id);
-                    assert_matches!(record.recording_status, RecordingStatus::Recording);
-                    assert!(record.content_path.to_str().unwrap().ends_with(".content"));
-                })
-            });
-        }
-        system.shutdown().await;
+            let result = manager.call(StopRecording { program_id }).await;
+            assert_matches!(result, Ok(Ok(())));
 
-        let content_pattern = format!(
-            "{}/{RECORDING_DIR}/*{:08X}.content",
-            temp_dir.path().to_str().unwrap(),
-            program_id.value()
-        );
-        assert_matches!(glob::glob(&content_pattern).unwrap().next(), Some(Ok(_)));
+            notify.notified().await;
 
-        let log_pattern = format!(
-            "{}/{RECORDING_DIR}/*{:08X}.content.log",
-            temp_dir.path().to_str().unwrap(),
-            program_id.value()
-        );
-        assert_matches!(glob::glob(&log_pattern).unwrap().next(), None);
+            let log_path = temp_dir.path().join(RECORDING_DIR).join(log_filename);
+            assert!(log_path.is_file());
 
-        let record_pattern = format!(
-            "{}/{RECORDS_DIR}/*{:08X}.record.json",
-            temp_dir.path().to_str().unwrap(),
-            program_id.value()
-        );
-        assert_matches!(glob::glob(&record_pattern).unwrap().next(), Some(Ok(record_path)) => {
-            assert_matches!(load_record(&config, &record_path).await, Ok((record, _)) => {
-                assert_eq!(record.program.id, program_id);
-                // The recording stops when the system stops.
-                assert_matches!(record.recording_status, RecordingStatus::Finished);
-            })
-        });
+            let result = manager.call(QueryRecords).await;
+            let id = assert_matches!(result, Ok(Ok(tuples)) => {
+                assert_eq!(tuples.len(), 1);
+                tuples[0].0.id.clone()
+            });
+
+            let result = manager.call(RemoveRecord { id, purge: true }).await;
+            assert_matches!(result, Ok(Ok((true, true))));
+
+            assert!(!log_path.exists());
+        }
+        system.shutdown().await;
     }
 
     #[test(tokio::test)]
-    async fn test_stop_recording() {
+    async fn test_recording_with_log_filter_off() {
         let now = Jst::now();
 
         let temp_dir = TempDir::new().unwrap();
@@ -3294,41 +8440,17 @@ mod tests {
 
         let program_id = ProgramId::from((0, 1, 1));
         let content_filename = "1.m2ts";
+        let log_filename = "1.m2ts.log";
 
         let notify = Arc::new(Notify::new());
         let notify2 = notify.clone();
 
-        let mut seq = mockall::Sequence::new();
-        let mut stopped = MockRecordingStoppedValidator::new();
-        let mut record_saved = MockRecordSavedValidator::new();
-
-        record_saved
-            .expect_emit()
-            .withf(move |msg| {
-                let program_id_part = format!("{:08X}", program_id.value());
-                msg.record_id.value().ends_with(&program_id_part)
-                    && matches!(msg.recording_status, RecordingStatus::Recording)
-            })
-            .returning(|_| ())
-            .once()
-            .in_sequence(&mut seq);
-
-        record_saved
-            .expect_emit()
-            .withf(move |msg| {
-                let program_id_part = format!("{:08X}", program_id.value());
-                msg.record_id.value().ends_with(&program_id_part)
-                    && matches!(msg.recording_status, RecordingStatus::Finished)
-            })
-            .returning(|_| ())
-            .once()
-            .in_sequence(&mut seq);
+        let mut stopped = MockRecordingStoppedValidator::new();
 
         stopped
             .expect_emit()
             .returning(move |_| notify2.notify_one())
-            .once()
-            .in_sequence(&mut seq);
+            .once();
 
         let system = System::new();
         {
@@ -3339,18 +8461,13 @@ mod tests {
                 .await;
             assert_matches!(result, Ok(_));
 
-            let result = manager
-                .call(RegisterEmitter::RecordSaved(Emitter::new(record_saved)))
-                .await;
-            assert_matches!(result, Ok(_));
-
             let result = manager
                 .call(StartRecording {
                     schedule: recording_schedule!(
                         RecordingScheduleState::Scheduled,
                         program!(program_id, now, "1h"),
                         service!((0, 1), "sv", channel_gr!("ch", "ch")),
-                        recording_options!(content_filename, 0)
+                        recording_options!(content_filename, 0, "off")
                     ),
                 })
                 .await;
@@ -3361,171 +8478,189 @@ mod tests {
 
             notify.notified().await;
 
-            let record_pattern = format!(
-                "{}/{RECORDS_DIR}/*{:08X}.record.json",
-                temp_dir.path().to_str().unwrap(),
-                program_id.value()
-            );
-            assert_matches!(glob::glob(&record_pattern).unwrap().next(), Some(Ok(record_path)) => {
-                assert_matches!(load_record(&config, &record_path).await, Ok((record, _)) => {
-                    assert_eq!(record.program.id, program_id);
-                    assert_matches!(record.recording_status, RecordingStatus::Finished);
-                })
+            let log_path = temp_dir.path().join(RECORDING_DIR).join(log_filename);
+            assert!(!log_path.exists());
+
+            let result = manager.call(QueryRecords).await;
+            let id = assert_matches!(result, Ok(Ok(tuples)) => {
+                assert_eq!(tuples.len(), 1);
+                tuples[0].0.id.clone()
             });
+
+            let result = manager.call(RemoveRecord { id, purge: true }).await;
+            assert_matches!(result, Ok(Ok((true, true))));
         }
         system.shutdown().await;
-
-        let content_path = temp_dir.path().join(RECORDING_DIR).join(content_filename);
-        assert!(content_path.is_file());
     }
 
     #[test(tokio::test)]
-    async fn test_maintain_schedules() {
+    async fn test_handle_recording_stopped() {
         let now = Jst::now();
 
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let max_delay = Duration::try_hours(MAX_DELAY_HOURS).unwrap();
+        let mut manager = recording_manager!(config);
+
+        let mut stopped = MockRecordingStoppedValidator::new();
+        stopped.expect_emit().times(1).returning(|msg| {
+            assert_eq!(msg.program_id, (0, 1, 1).into());
+        });
+        manager.recording_stopped.register(Emitter::new(stopped));
+
+        let mut failed = MockRecordingFailedValidator::new();
+        failed.expect_emit().never();
+        manager.recording_failed.register(Emitter::new(failed));
+
+        let start_time = now - Duration::try_minutes(30).unwrap();
 
-        let mut manager = recording_manager!(config.clone());
         let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!((0, 1, 1), now + Duration::try_hours(1).unwrap(), "1h"),
+            RecordingScheduleState::Recording,
+            program!((0, 1, 1), start_time, "1h"),
             service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0)
+            recording_options!("1.m2ts", 0),
+            hashset!["tag1".to_string()]
         );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
-        let changed = manager.maintain_schedules(now + max_delay).await;
-        assert!(!changed);
-        assert_eq!(manager.schedules.len(), 1);
-        manager.schedules.clear();
+        manager.schedules.insert((0, 1, 1).into(), schedule);
+
+        let recorder = recorder!(start_time, pipeline!["true"]);
+        manager.recorders.insert((0, 1, 1).into(), recorder);
+
+        let changed = manager.handle_recording_stopped((0, 1, 1).into()).await;
+        assert!(changed);
+        assert!(!manager.recorders.contains_key(&(0, 1, 1).into()));
+        assert_matches!(manager.schedules.get(&(0, 1, 1).into()), Some(schedule) => {
+            assert_matches!(schedule.state, RecordingScheduleState::Finished);
+        });
+    }
+
+    #[test(tokio::test)]
+    async fn test_handle_recording_stopped_retry() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let mut manager = recording_manager!(config);
+
+        let mut stopped = MockRecordingStoppedValidator::new();
+        stopped.expect_emit().times(1).returning(|msg| {
+            assert_eq!(msg.program_id, (0, 1, 1).into());
+        });
+        manager.recording_stopped.register(Emitter::new(stopped));
+
+        let mut failed = MockRecordingFailedValidator::new();
+        failed.expect_emit().times(1).returning(|msg| {
+            assert_eq!(msg.program_id, (0, 1, 1).into());
+            assert_matches!(msg.reason, RecordingFailedReason::NeedRescheduling);
+        });
+        manager.recording_failed.register(Emitter::new(failed));
+
+        let start_time = now - Duration::try_minutes(30).unwrap();
 
-        let mut manager = recording_manager!(config.clone());
         let schedule = recording_schedule!(
             RecordingScheduleState::Recording,
-            program!((0, 1, 1), now, "1h"),
+            program!((0, 1, 1), start_time, "1h"),
             service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0)
+            recording_options!("1.m2ts", 0),
+            hashset!["tag1".to_string()]
         );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
-        let changed = manager.maintain_schedules(now + max_delay).await;
-        assert!(!changed);
-        assert_eq!(manager.schedules.len(), 1);
-        manager.schedules.clear();
+        manager.schedules.insert((0, 1, 1).into(), schedule);
 
-        let states = [
-            RecordingScheduleState::Scheduled,
-            RecordingScheduleState::Tracking,
-            RecordingScheduleState::Rescheduling,
-        ];
-        for state in states {
-            let mut manager = recording_manager!(config.clone());
-            let mut failed = MockRecordingFailedValidator::new();
-            failed.expect_emit().times(1).returning(|msg| {
-                assert_eq!(msg.program_id, (0, 1, 1).into());
-            });
-            manager.recording_failed.register(Emitter::new(failed));
-            let schedule = recording_schedule!(
-                state,
-                program!((0, 1, 1), now, "1h"),
-                service!((0, 1), "sv", channel_gr!("ch", "ch")),
-                recording_options!("1.m2ts", 0)
-            );
-            let result = manager.add_schedule(schedule);
-            assert_matches!(result, Ok(()));
-            let changed = manager.maintain_schedules(now + max_delay).await;
-            assert!(changed);
-            assert!(manager.schedules.is_empty());
-        }
+        let recorder = recorder!(start_time, pipeline![format!("sh -c 'exit {EXIT_RETRY}'")]);
+        manager.recorders.insert((0, 1, 1).into(), recorder);
 
-        let states = [
-            RecordingScheduleState::Finished,
-            RecordingScheduleState::Failed,
-        ];
-        for state in states {
-            let mut manager = recording_manager!(config.clone());
-            let mut failed = MockRecordingFailedValidator::new();
-            failed.expect_emit().never();
-            manager.recording_failed.register(Emitter::new(failed));
-            let schedule = recording_schedule!(
-                state,
-                program!((0, 1, 1), now, "1h"),
-                service!((0, 1), "sv", channel_gr!("ch", "ch")),
-                recording_options!("1.m2ts", 0)
-            );
-            let result = manager.add_schedule(schedule);
-            assert_matches!(result, Ok(()));
-            let changed = manager.maintain_schedules(now + max_delay).await;
-            assert!(changed);
-            assert!(manager.schedules.is_empty());
-        }
+        let changed = manager.handle_recording_stopped((0, 1, 1).into()).await;
+        assert!(changed);
+        assert!(!manager.recorders.contains_key(&(0, 1, 1).into()));
+        assert_matches!(manager.schedules.get(&(0, 1, 1).into()), Some(schedule) => {
+            assert_matches!(schedule.state, RecordingScheduleState::Rescheduling);
+        });
     }
 
     #[test(tokio::test)]
-    async fn test_remove_record() {
+    async fn test_handle_recording_stopped_pipeline_error() {
         let now = Jst::now();
 
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let program_id = ProgramId::from((0, 1, 1));
-        let content_filename = "1.m2ts";
-
-        let notify = Arc::new(Notify::new());
-        let notify2 = notify.clone();
+        let mut manager = recording_manager!(config);
 
-        let mut seq = mockall::Sequence::new();
         let mut stopped = MockRecordingStoppedValidator::new();
-        let mut record_removed = MockRecordRemovedValidator::new();
-        let mut content_removed = MockContentRemovedValidator::new();
+        stopped.expect_emit().times(1).returning(|msg| {
+            assert_eq!(msg.program_id, (0, 1, 1).into());
+        });
+        manager.recording_stopped.register(Emitter::new(stopped));
 
-        stopped
-            .expect_emit()
-            .returning(move |_| notify2.notify_one())
-            .once()
-            .in_sequence(&mut seq);
+        let mut failed = MockRecordingFailedValidator::new();
+        failed.expect_emit().times(1).returning(|msg| {
+            assert_eq!(msg.program_id, (0, 1, 1).into());
+            assert_matches!(
+                msg.reason,
+                RecordingFailedReason::PipelineError { exit_code: 1 }
+            );
+        });
+        manager.recording_failed.register(Emitter::new(failed));
 
-        content_removed
-            .expect_emit()
-            .withf(move |msg| {
-                let program_id_part = format!("{:08X}", program_id.value());
-                msg.record_id.value().ends_with(&program_id_part)
-            })
-            .returning(|_| ())
-            .once()
-            .in_sequence(&mut seq);
+        let start_time = now - Duration::try_minutes(30).unwrap();
 
-        record_removed
-            .expect_emit()
-            .withf(move |msg| {
-                let program_id_part = format!("{:08X}", program_id.value());
-                msg.record_id.value().ends_with(&program_id_part)
-            })
-            .returning(|_| ())
-            .once()
-            .in_sequence(&mut seq);
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Recording,
+            program!((0, 1, 1), start_time, "1h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0),
+            hashset!["tag1".to_string()]
+        );
+        manager.schedules.insert((0, 1, 1).into(), schedule);
+
+        let recorder = recorder!(start_time, pipeline!["false"]);
+        manager.recorders.insert((0, 1, 1).into(), recorder);
+
+        let changed = manager.handle_recording_stopped((0, 1, 1).into()).await;
+        assert!(changed);
+        assert!(!manager.recorders.contains_key(&(0, 1, 1).into()));
+        assert_matches!(manager.schedules.get(&(0, 1, 1).into()), Some(schedule) => {
+            assert_matches!(schedule.state, RecordingScheduleState::Failed);
+        });
+    }
+
+    #[test(tokio::test)]
+    async fn test_recording_preempted() {
+        let now = Jst::now();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_with_tuner_for_test(temp_dir.path());
+
+        let service = service!((0, 1), "sv", channel_gr!("ch", "ch"));
+        let program_id = ProgramId::new_user_defined(service.id, 0).unwrap();
+
+        let notify = Arc::new(Notify::new());
+        let notify2 = notify.clone();
+        let mut failed = MockRecordingFailedValidator::new();
+        failed.expect_emit().times(1).returning(move |msg| {
+            assert_eq!(msg.program_id, program_id);
+            assert_matches!(
+                msg.reason,
+                RecordingFailedReason::Preempted { tuner_index: 0, .. }
+            );
+            notify2.notify_one();
+        });
 
         let system = System::new();
         {
-            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
-
-            let result = manager
-                .call(RegisterEmitter::RecordingStopped(Emitter::new(stopped)))
-                .await;
-            assert_matches!(result, Ok(_));
-
-            let result = manager
-                .call(RegisterEmitter::RecordRemoved(Emitter::new(record_removed)))
+            let tuner_manager = system.spawn_actor(TunerManager::new(config.clone())).await;
+            let manager = system
+                .spawn_actor(recording_manager!(
+                    config.clone(),
+                    tuner_manager.clone(),
+                    EpgStub,
+                    OnairProgramManagerStub
+                ))
                 .await;
-            assert_matches!(result, Ok(_));
 
             let result = manager
-                .call(RegisterEmitter::ContentRemoved(Emitter::new(
-                    content_removed,
-                )))
+                .call(RegisterEmitter::RecordingFailed(Emitter::new(failed)))
                 .await;
             assert_matches!(result, Ok(_));
 
@@ -3534,483 +8669,913 @@ mod tests {
                     schedule: recording_schedule!(
                         RecordingScheduleState::Scheduled,
                         program!(program_id, now, "1h"),
-                        service!((0, 1), "sv", channel_gr!("ch", "ch")),
-                        recording_options!(content_filename, 0)
+                        service.clone(),
+                        recording_options!("1.m2ts", 0)
                     ),
                 })
                 .await;
             assert_matches!(result, Ok(Ok(())));
 
-            let result = manager.call(StopRecording { program_id }).await;
-            assert_matches!(result, Ok(Ok(())));
-
+            // A user having a higher priority grabs the tuner used for the recording.
+            let result = tuner_manager
+                .call(StartStreaming {
+                    channel: channel_gr!("other", "other"),
+                    user: tuner_user!(1, job; "test"),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(_)));
             notify.notified().await;
 
-            let result = manager.call(QueryRecords).await;
-            let id = assert_matches!(result, Ok(Ok(tuples)) => {
-                assert_eq!(tuples.len(), 1);
-                tuples[0].0.id.clone()
+            let result = manager.call(QueryRecordingSchedule { program_id }).await;
+            let started_at = assert_matches!(result, Ok(Ok(schedule)) => {
+                assert_matches!(schedule.state, RecordingScheduleState::Failed);
+                assert_matches!(schedule.failed_reason, Some(RecordingFailedReason::Preempted { tuner_index: 0, replaced_by, priority: 1 }) => {
+                    assert_eq!(replaced_by, "job:test");
+                });
+                schedule.attempts[0].started_at
             });
 
-            let record_path = temp_dir
-                .path()
-                .join(RECORDS_DIR)
-                .join(format!("{}.record.json", id.value()));
-            assert!(record_path.exists());
-
-            let content_path = temp_dir.path().join(RECORDING_DIR).join(content_filename);
-            assert!(content_path.exists());
-
-            let result = manager.call(RemoveRecord { id, purge: true }).await;
-            assert_matches!(result, Ok(Ok((true, true))));
-
-            assert!(!record_path.exists());
-            assert!(!content_path.exists());
+            let id = RecordId::from((started_at, program_id));
+            let result = manager.call(QueryRecord { id }).await;
+            assert_matches!(result, Ok(Ok((record, _))) => {
+                assert_matches!(record.recording_status, RecordingStatus::Failed {
+                    reason: RecordingFailedReason::Preempted { tuner_index: 0, .. },
+                });
+            });
         }
         system.shutdown().await;
     }
 
     #[test(tokio::test)]
-    async fn test_open_content() {
+    async fn test_handle_recording_stopped_preempted() {
+        let now = Jst::now();
+
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let id = RecordId("1".to_string());
-        let record = record!(finished: id.value());
-        let record_path = make_record_path(&config, &id).unwrap();
-        assert!(file_util::save_json(&record, &record_path));
+        let mut manager = recording_manager!(config);
 
-        let content_path = make_content_path(&config, &record).unwrap();
-        assert!(file_util::save_data(b"0123456789", &content_path));
+        let mut stopped = MockRecordingStoppedValidator::new();
+        stopped.expect_emit().times(1).returning(|msg| {
+            assert_eq!(msg.program_id, (0, 1, 1).into());
+        });
+        manager.recording_stopped.register(Emitter::new(stopped));
 
-        let system = System::new();
-        {
-            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
+        let mut failed = MockRecordingFailedValidator::new();
+        failed.expect_emit().times(1).returning(|msg| {
+            assert_eq!(msg.program_id, (0, 1, 1).into());
+            assert_matches!(msg.reason, RecordingFailedReason::Preempted { tuner_index: 0, replaced_by, priority: 1 } => {
+                assert_eq!(replaced_by, "job:test");
+            });
+        });
+        manager.recording_failed.register(Emitter::new(failed));
 
-            let result = manager.call(OpenContent::new(id.clone(), None)).await;
-            let (stream, stop_trigger) = match result {
-                Ok(Ok(tuple)) => tuple,
-                _ => panic!(),
-            };
+        let start_time = now - Duration::try_minutes(30).unwrap();
 
-            let mut reader = tokio_util::io::StreamReader::new(stream);
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Recording,
+            program!((0, 1, 1), start_time, "1h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0),
+            hashset!["tag1".to_string()]
+        );
+        manager.schedules.insert((0, 1, 1).into(), schedule);
 
-            let mut buf = [0; 10];
-            reader.read_exact(&mut buf).await.unwrap(); // EOF reaches.
-            assert_eq!(&buf, b"0123456789");
+        let log_path = temp_dir.path().join("1.m2ts.log");
+        let mut recorder = recorder!(start_time, pipeline!["true"]);
+        recorder.log_path = Some(log_path.clone());
+        manager.recorders.insert((0, 1, 1).into(), recorder);
 
-            append(&content_path, b"abc").await;
+        // Ignored.
+        manager.handle_recorder_preempted((0, 1, 1).into(), 1, tuner_user!(1, job; "test"));
+        assert!(manager.recorders[&(0, 1, 1).into()].preempted.is_none());
 
-            assert_matches!(reader.read_exact(&mut buf).await, Err(err) => {
-                assert_matches!(err.kind(), std::io::ErrorKind::UnexpectedEof);
-            });
+        manager.handle_recorder_preempted((0, 1, 1).into(), 0, tuner_user!(1, job; "test"));
 
-            drop(stop_trigger);
-        }
-        system.shutdown().await;
+        let changed = manager.handle_recording_stopped((0, 1, 1).into()).await;
+        assert!(changed);
+        assert!(!manager.recorders.contains_key(&(0, 1, 1).into()));
+        assert_matches!(manager.schedules.get(&(0, 1, 1).into()), Some(schedule) => {
+            assert_matches!(schedule.state, RecordingScheduleState::Failed);
+            assert_matches!(schedule.failed_reason, Some(RecordingFailedReason::Preempted { .. }));
+        });
 
-        // TODO(#2057): range request
+        let log = std::fs::read_to_string(&log_path).unwrap();
+        assert!(log.contains("tuner#0 was grabbed by job:test (priority: 1)"));
     }
 
     #[test(tokio::test)]
-    async fn test_open_content_during_recording() {
+    async fn test_handle_recording_stopped_pipeline_error_retry() {
+        let now = Jst::now();
+
         let temp_dir = TempDir::new().unwrap();
-        let config = config_for_test(temp_dir.path());
+        let mut config = config_for_test(temp_dir.path());
+        Arc::get_mut(&mut config).unwrap().tuners = vec![
+            TunerConfig {
+                name: "t0".to_string(),
+                channel_types: vec![ChannelType::GR],
+                command: "true".to_string(),
+                ..Default::default()
+            },
+            TunerConfig {
+                name: "t1".to_string(),
+                channel_types: vec![ChannelType::GR],
+                command: "true".to_string(),
+                ..Default::default()
+            },
+        ];
 
-        let id = RecordId("1".to_string());
-        let record = record!(recording: id.clone());
-        let record_path = make_record_path(&config, &id).unwrap();
-        assert!(file_util::save_json(&record, &record_path));
+        let mut manager = recording_manager!(config);
 
-        let content_path = make_content_path(&config, &record).unwrap();
-        tokio::fs::write(&content_path, b"0123456789")
-            .await
-            .unwrap();
+        let mut stopped = MockRecordingStoppedValidator::new();
+        stopped.expect_emit().times(1).returning(|msg| {
+            assert_eq!(msg.program_id, (0, 1, 1).into());
+        });
+        manager.recording_stopped.register(Emitter::new(stopped));
 
-        let system = System::new();
-        {
-            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
+        let mut failed = MockRecordingFailedValidator::new();
+        failed.expect_emit().never();
+        manager.recording_failed.register(Emitter::new(failed));
 
-            let result = manager.call(OpenContent::new(id.clone(), None)).await;
-            let (stream, stop_trigger) = match result {
-                Ok(Ok(tuple)) => tuple,
-                _ => panic!(),
-            };
+        let start_time = now - Duration::try_minutes(30).unwrap();
 
-            let mut reader = tokio_util::io::StreamReader::new(stream);
+        let mut schedule = recording_schedule!(
+            RecordingScheduleState::Recording,
+            program!((0, 1, 1), start_time, "1h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0),
+            hashset!["tag1".to_string()]
+        );
+        schedule.attempts.push(RecordingAttempt {
+            tuner_index: 0,
+            started_at: start_time,
+            failed_reason: None,
+        });
+        manager.schedules.insert((0, 1, 1).into(), schedule);
 
-            let mut buf = [0; 10];
-            reader.read_exact(&mut buf).await.unwrap(); // EOF reaches.
-            assert_eq!(&buf, b"0123456789");
+        let mut recorder = recorder!(start_time, pipeline!["false"]);
+        let stop_trigger = manager.tuner_manager.trigger(StopStreaming {
+            id: Default::default(),
+        });
+        recorder.stop_trigger = Some(stop_trigger);
+        manager.recorders.insert((0, 1, 1).into(), recorder);
 
-            append(&content_path, b"abc").await;
+        let changed = manager.handle_recording_stopped((0, 1, 1).into()).await;
+        assert!(changed);
+        assert!(!manager.recorders.contains_key(&(0, 1, 1).into()));
+        assert!(manager.is_waiting_for_retry((0, 1, 1).into()));
+        assert_matches!(manager.schedules.get(&(0, 1, 1).into()), Some(schedule) => {
+            assert_matches!(schedule.state, RecordingScheduleState::Recording);
+            assert_matches!(schedule.failed_reason, None);
+            assert_eq!(schedule.attempts.len(), 1);
+            assert_matches!(
+                schedule.attempts[0].failed_reason,
+                Some(RecordingFailedReason::PipelineError { exit_code: 1 })
+            );
+        });
+    }
 
-            let mut buf = [0; 3];
-            reader.read_exact(&mut buf).await.unwrap(); // EOF reaches again.
-            assert_eq!(&buf, b"abc");
+    #[test]
+    fn test_can_retry_recording() {
+        let now = Jst::now();
 
-            // The streaming will stop within 100ms without explicit `drop(stop_trigger)`.
-            assert_matches!(reader.read_exact(&mut buf).await, Err(err) => {
-                assert_matches!(err.kind(), std::io::ErrorKind::UnexpectedEof);
-            });
+        let mut config = Config {
+            tuners: vec![
+                TunerConfig {
+                    name: "t0".to_string(),
+                    channel_types: vec![ChannelType::GR],
+                    command: "true".to_string(),
+                    ..Default::default()
+                },
+                TunerConfig {
+                    name: "t1".to_string(),
+                    channel_types: vec![ChannelType::GR],
+                    command: "true".to_string(),
+                    ..Default::default()
+                },
+                TunerConfig {
+                    name: "t2".to_string(),
+                    channel_types: vec![ChannelType::BS],
+                    command: "true".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
 
-            drop(stop_trigger);
-        }
-        system.shutdown().await;
+        let mut schedule = recording_schedule!(
+            RecordingScheduleState::Recording,
+            program!((0, 1, 1), now, "1h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0)
+        );
+        assert!(!can_retry_recording(&config, &schedule, now));
 
-        // TODO(#2057): range request
-    }
+        schedule.attempts.push(RecordingAttempt {
+            tuner_index: 0,
+            started_at: now,
+            failed_reason: None,
+        });
+        assert!(can_retry_recording(&config, &schedule, now));
 
-    #[test(tokio::test)]
-    async fn test_open_content_stop_trigger() {
-        let temp_dir = TempDir::new().unwrap();
-        let config = config_for_test(temp_dir.path());
+        let end_time = now + Duration::try_hours(1).unwrap();
+        assert!(!can_retry_recording(&config, &schedule, end_time));
 
-        let id = RecordId("1".to_string());
-        let record = record!(recording: id.clone());
-        let record_path = make_record_path(&config, &id).unwrap();
-        assert!(file_util::save_json(&record, &record_path));
+        schedule.attempts.push(RecordingAttempt {
+            tuner_index: 1,
+            started_at: now,
+            failed_reason: None,
+        });
+        assert!(!can_retry_recording(&config, &schedule, now));
 
-        let content_path = make_content_path(&config, &record).unwrap();
-        tokio::fs::write(&content_path, b"0123456789")
-            .await
-            .unwrap();
+        schedule.attempts.pop();
+        config.recording.tuner_retry.max_attempts = 1;
+        assert!(!can_retry_recording(&config, &schedule, now));
+    }
 
-        let system = System::new();
-        {
-            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
+    #[test(tokio::test)]
+    async fn test_update_schedules_by_epg_services() {
+        let now = Jst::now();
 
-            let result = manager.call(OpenContent::new(id.clone(), None)).await;
-            let (stream, stop_trigger) = match result {
-                Ok(Ok(tuple)) => tuple,
-                _ => panic!(),
-            };
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
 
-            let mut reader = tokio_util::io::StreamReader::new(stream);
+        let mut manager = recording_manager!(config);
+        let mut mock = MockRecordingFailedValidator::new();
+        mock.expect_emit().times(1).returning(|msg| {
+            assert_eq!(msg.program_id, (0, 2, 1).into());
+            assert_matches!(msg.reason, RecordingFailedReason::RemovedFromEpg);
+        });
+        manager.recording_failed.register(Emitter::new(mock));
 
-            let mut buf = [0; 10];
-            reader.read_exact(&mut buf).await.unwrap();
-            assert_eq!(&buf, b"0123456789");
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 1), now),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
 
-            drop(stop_trigger);
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 2, 1), now),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("2.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
 
-            // The streaming will stop soon before the timeout.
-            assert_matches!(reader.read_exact(&mut buf).await, Err(err) => {
-                assert_matches!(err.kind(), std::io::ErrorKind::UnexpectedEof);
-            });
-        }
-        system.shutdown().await;
+        let services = indexmap! {
+            (0, 1).into() => service!((0, 1), "test", channel_gr!("gr", "1")),
+        };
+        let changed = manager.update_schedules_by_epg_services(&services).await;
+        assert!(changed);
+        assert_eq!(manager.schedules.len(), 1);
+        assert!(manager.schedules.contains_key(&(0, 1, 1).into()));
+        assert!(!manager.schedules.contains_key(&(0, 2, 1).into()));
 
-        // TODO(#2057): range request
+        let changed = manager.update_schedules_by_epg_services(&services).await;
+        assert!(!changed);
     }
 
     #[test(tokio::test)]
-    async fn test_recording_with_log_filter_info() {
+    async fn test_update_schedules_by_epg_programs() {
         let now = Jst::now();
 
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let program_id = ProgramId::from((0, 1, 1));
-        let content_filename = "1.m2ts";
-        let log_filename = "1.m2ts.log";
-
-        let notify = Arc::new(Notify::new());
-        let notify2 = notify.clone();
-
-        let mut stopped = MockRecordingStoppedValidator::new();
-
-        stopped
-            .expect_emit()
-            .returning(move |_| notify2.notify_one())
-            .once();
-
-        let system = System::new();
-        {
-            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
-
-            let result = manager
-                .call(RegisterEmitter::RecordingStopped(Emitter::new(stopped)))
-                .await;
-            assert_matches!(result, Ok(_));
-
-            let result = manager
-                .call(StartRecording {
-                    schedule: recording_schedule!(
-                        RecordingScheduleState::Scheduled,
-                        program!(program_id, now, "1h"),
-                        service!((0, 1), "sv", channel_gr!("ch", "ch")),
-                        recording_options!(content_filename, 0, "info")
-                    ),
-                })
-                .await;
-            assert_matches!(result, Ok(Ok(())));
+        let mut epg = MockEpg::new();
+        epg.expect_call().returning(move |_| {
+            Ok(Arc::new(indexmap! {
+                1.into() => program!((0, 1, 1), now, "1h"),
+            }))
+        });
 
-            let result = manager.call(StopRecording { program_id }).await;
-            assert_matches!(result, Ok(Ok(())));
+        let mut manager = recording_manager!(
+            config,
+            TunerManagerStub::default(),
+            epg,
+            OnairProgramManagerStub
+        );
+        let mut failed_mock = MockRecordingFailedValidator::new();
+        failed_mock.expect_emit().times(1).returning(|msg| {
+            assert_eq!(msg.program_id, (0, 1, 2).into());
+            assert_matches!(msg.reason, RecordingFailedReason::RemovedFromEpg);
+        });
+        manager.recording_failed.register(Emitter::new(failed_mock));
 
-            notify.notified().await;
+        let mut rescheduled_mock = MockRecordingRescheduledValidator::new();
+        rescheduled_mock.expect_emit().never();
+        manager
+            .recording_rescheduled
+            .register(Emitter::new(rescheduled_mock));
 
-            let log_path = temp_dir.path().join(RECORDING_DIR).join(log_filename);
-            assert!(log_path.is_file());
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Rescheduling,
+            program!((0, 1, 1), now),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
 
-            let result = manager.call(QueryRecords).await;
-            let id = assert_matches!(result, Ok(Ok(tuples)) => {
-                assert_eq!(tuples.len(), 1);
-                tuples[0].0.id.clone()
-            });
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program!((0, 1, 2), now),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("2.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
 
-            let result = manager.call(RemoveRecord { id, purge: true }).await;
-            assert_matches!(result, Ok(Ok((true, true))));
+        let changed = manager
+            .update_schedules_by_epg_programs(now, (0, 1).into())
+            .await;
+        assert!(changed);
+        assert_eq!(manager.schedules.len(), 1);
+        assert!(manager.schedules.contains_key(&(0, 1, 1).into()));
+        assert!(!manager.schedules.contains_key(&(0, 1, 2).into()));
 
-            assert!(!log_path.exists());
-        }
-        system.shutdown().await;
+        let changed = manager
+            .update_schedules_by_epg_programs(now, (0, 0).into())
+            .await;
+        assert!(!changed);
     }
 
     #[test(tokio::test)]
-    async fn test_recording_with_log_filter_off() {
+    async fn test_update_schedules_by_epg_programs_rescheduled() {
         let now = Jst::now();
 
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let program_id = ProgramId::from((0, 1, 1));
-        let content_filename = "1.m2ts";
-        let log_filename = "1.m2ts.log";
+        let mut epg = MockEpg::new();
+        epg.expect_call().returning(move |_| {
+            Ok(Arc::new(indexmap! {
+                1.into() => program!((0, 1, 1), now, "1h"),
+            }))
+        });
 
-        let notify = Arc::new(Notify::new());
-        let notify2 = notify.clone();
+        let mut manager = recording_manager!(
+            config,
+            TunerManagerStub::default(),
+            epg,
+            OnairProgramManagerStub
+        );
 
-        let mut stopped = MockRecordingStoppedValidator::new();
+        let mut mock = MockRecordingRescheduledValidator::new();
+        mock.expect_emit().times(1).returning(|msg| {
+            assert_eq!(msg.program_id, (0, 1, 1).into());
+        });
+        manager.recording_rescheduled.register(Emitter::new(mock));
 
-        stopped
-            .expect_emit()
-            .returning(move |_| notify2.notify_one())
-            .once();
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Rescheduling,
+            program!((0, 1, 1), now - Duration::try_minutes(30).unwrap(), "1h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!("1.m2ts", 0)
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
 
-        let system = System::new();
-        {
-            let manager = system.spawn_actor(recording_manager!(config.clone())).await;
+        let changed = manager
+            .update_schedules_by_epg_programs(now, (0, 1).into())
+            .await;
+        assert!(changed);
+        assert_eq!(manager.schedules.len(), 1);
+        assert_matches!(manager.schedules.get(&(0, 1, 1).into()), Some(schedule) => {
+            assert_matches!(schedule.state, RecordingScheduleState::Scheduled);
+        });
+    }
 
-            let result = manager
-                .call(RegisterEmitter::RecordingStopped(Emitter::new(stopped)))
-                .await;
-            assert_matches!(result, Ok(_));
+    #[test]
+    fn test_save_and_load_rules() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
 
-            let result = manager
-                .call(StartRecording {
-                    schedule: recording_schedule!(
-                        RecordingScheduleState::Scheduled,
-                        program!(program_id, now, "1h"),
-                        service!((0, 1), "sv", channel_gr!("ch", "ch")),
-                        recording_options!(content_filename, 0, "off")
-                    ),
-                })
-                .await;
-            assert_matches!(result, Ok(Ok(())));
+        let mut manager = recording_manager!(config.clone());
+        let rule = recording_rule!(1, title: "test");
+        manager.rules.insert(rule.id, rule);
+        let mut rule = recording_rule!(2, title: "test");
+        rule.ignored_programs.insert((0, 1, 1).into());
+        manager.rules.insert(rule.id, rule);
 
-            let result = manager.call(StopRecording { program_id }).await;
-            assert_matches!(result, Ok(Ok(())));
+        manager.save_rules();
+        assert!(make_rules_path(&config).unwrap().is_file());
 
-            notify.notified().await;
+        let mut manager = recording_manager!(config.clone());
+        manager.load_rules();
+        assert_eq!(manager.rules.len(), 2);
+        assert_matches!(manager.rules.get(&RecordingRuleId::from(1)), Some(rule) => {
+            assert!(rule.ignored_programs.is_empty());
+        });
+        assert_matches!(manager.rules.get(&RecordingRuleId::from(2)), Some(rule) => {
+            assert!(rule.ignored_programs.contains(&(0, 1, 1).into()));
+        });
+    }
 
-            let log_path = temp_dir.path().join(RECORDING_DIR).join(log_filename);
-            assert!(!log_path.exists());
+    #[test]
+    fn test_rule_conditions_validate() {
+        let conditions = RecordingRuleConditions::default();
+        assert_matches!(conditions.validate(), Err(Error::InvalidRequest(_)));
 
-            let result = manager.call(QueryRecords).await;
-            let id = assert_matches!(result, Ok(Ok(tuples)) => {
-                assert_eq!(tuples.len(), 1);
-                tuples[0].0.id.clone()
-            });
+        let conditions = RecordingRuleConditions {
+            title: Some("test".to_string()),
+            ..Default::default()
+        };
+        assert_matches!(conditions.validate(), Ok(()));
 
-            let result = manager.call(RemoveRecord { id, purge: true }).await;
-            assert_matches!(result, Ok(Ok((true, true))));
-        }
-        system.shutdown().await;
+        let conditions = RecordingRuleConditions {
+            title: Some("(".to_string()),
+            ..Default::default()
+        };
+        assert_matches!(conditions.validate(), Err(Error::InvalidRequest(_)));
+
+        let conditions = RecordingRuleConditions {
+            series_id: Some(1),
+            time_range: Some(RecordingRuleTimeRange {
+                start: "23:00".to_string(),
+                end: "01:00".to_string(),
+            }),
+            ..Default::default()
+        };
+        assert_matches!(conditions.validate(), Ok(()));
+
+        let conditions = RecordingRuleConditions {
+            series_id: Some(1),
+            time_range: Some(RecordingRuleTimeRange {
+                start: "23:00".to_string(),
+                end: "23:00".to_string(),
+            }),
+            ..Default::default()
+        };
+        assert_matches!(conditions.validate(), Err(Error::InvalidRequest(_)));
+
+        let conditions = RecordingRuleConditions {
+            series_id: Some(1),
+            time_range: Some(RecordingRuleTimeRange {
+                start: "25:00".to_string(),
+                end: "01:00".to_string(),
+            }),
+            ..Default::default()
+        };
+        assert_matches!(conditions.validate(), Err(Error::InvalidRequest(_)));
+    }
+
+    #[test]
+    fn test_rule_matcher() {
+        let mut program = program!((0, 1, 1), jst!("2025-01-01T23:30:00+09:00"), "1h");
+        program.name = Some("News 23".to_string());
+        program.description = Some("Today's news".to_string());
+        program.genres = Some(vec![crate::models::EpgGenre::new((0, 1, 0, 0))]);
+        program.series = Some(epg::SeriesDescriptor {
+            series_id: 10,
+            repeat_label: 0,
+            program_pattern: 0,
+            expire_date: None,
+            episode_number: 1,
+            last_episode_number: 0,
+            series_name: None,
+        });
+
+        let matches = |conditions: RecordingRuleConditions| -> bool {
+            conditions.matcher().unwrap().matches(&program)
+        };
+
+        assert!(matches(RecordingRuleConditions {
+            title: Some("^News".to_string()),
+            ..Default::default()
+        }));
+        assert!(!matches(RecordingRuleConditions {
+            title: Some("^Sports".to_string()),
+            ..Default::default()
+        }));
+        assert!(matches(RecordingRuleConditions {
+            services: vec![(0, 1).into()],
+            description: Some("news".to_string()),
+            ..Default::default()
+        }));
+        assert!(!matches(RecordingRuleConditions {
+            services: vec![(0, 2).into()],
+            description: Some("news".to_string()),
+            ..Default::default()
+        }));
+        assert!(matches(RecordingRuleConditions {
+            genres: vec![RecordingRuleGenre { lv1: 0, lv2: None }],
+            ..Default::default()
+        }));
+        assert!(matches(RecordingRuleConditions {
+            genres: vec![
+                RecordingRuleGenre { lv1: 1, lv2: None },
+                RecordingRuleGenre {
+                    lv1: 0,
+                    lv2: Some(1)
+                },
+            ],
+            ..Default::default()
+        }));
+        assert!(!matches(RecordingRuleConditions {
+            genres: vec![RecordingRuleGenre {
+                lv1: 0,
+                lv2: Some(2)
+            }],
+            ..Default::default()
+        }));
+        assert!(matches(RecordingRuleConditions {
+            series_id: Some(10),
+            ..Default::default()
+        }));
+        assert!(!matches(RecordingRuleConditions {
+            series_id: Some(11),
+            ..Default::default()
+        }));
+        assert!(matches(RecordingRuleConditions {
+            title: Some("News".to_string()),
+            time_range: Some(RecordingRuleTimeRange {
+                start: "23:00".to_string(),
+                end: "01:00".to_string(),
+            }),
+            ..Default::default()
+        }));
+        assert!(!matches(RecordingRuleConditions {
+            title: Some("News".to_string()),
+            time_range: Some(RecordingRuleTimeRange {
+                start: "20:00".to_string(),
+                end: "23:30".to_string(),
+            }),
+            ..Default::default()
+        }));
     }
 
     #[test(tokio::test)]
-    async fn test_handle_recording_stopped() {
+    async fn test_add_schedules_by_rules() {
         let now = Jst::now();
 
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let mut manager = recording_manager!(config);
-
-        let mut stopped = MockRecordingStoppedValidator::new();
-        stopped.expect_emit().times(1).returning(|msg| {
-            assert_eq!(msg.program_id, (0, 1, 1).into());
+        let mut epg = MockEpg::new();
+        epg.expect_call().returning(move |_| {
+            let mut program1 = program!((0, 1, 1), now - Duration::try_hours(1).unwrap(), "1h");
+            program1.name = Some("test".to_string());
+            let mut program2 = program!((0, 1, 2), now + Duration::try_hours(1).unwrap(), "1h");
+            program2.name = Some("test".to_string());
+            let mut program3 = program!((0, 1, 3), now + Duration::try_hours(2).unwrap(), "1h");
+            program3.name = Some("test".to_string());
+            let mut program4 = program!((0, 1, 4), now + Duration::try_hours(3).unwrap(), "1h");
+            program4.name = Some("other".to_string());
+            Ok(Arc::new(indexmap! {
+                1.into() => program1,
+                2.into() => program2,
+                3.into() => program3,
+                4.into() => program4,
+            }))
         });
-        manager.recording_stopped.register(Emitter::new(stopped));
-
-        let mut failed = MockRecordingFailedValidator::new();
-        failed.expect_emit().never();
-        manager.recording_failed.register(Emitter::new(failed));
-
-        let start_time = now - Duration::try_minutes(30).unwrap();
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Recording,
-            program!((0, 1, 1), start_time, "1h"),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0),
-            hashset!["tag1".to_string()]
+        let mut manager = recording_manager!(
+            config,
+            TunerManagerStub::default(),
+            epg,
+            OnairProgramManagerStub
         );
-        manager.schedules.insert((0, 1, 1).into(), schedule);
 
-        let recorder = recorder!(start_time, pipeline!["true"]);
-        manager.recorders.insert((0, 1, 1).into(), recorder);
+        let mut rule = recording_rule!(1, title: "^test$");
+        rule.tags.insert("tag".to_string());
+        rule.ignored_programs.insert((0, 1, 3).into());
+        rule.ignored_programs.insert((0, 1, 5).into());
+        manager.rules.insert(rule.id, rule.clone());
 
-        let changed = manager.handle_recording_stopped((0, 1, 1).into()).await;
+        let service = service!((0, 1), "sv", channel_gr!("ch", "ch"));
+        let changed = manager.add_schedules_by_rules(now, &service, &[rule]).await;
         assert!(changed);
-        assert!(!manager.recorders.contains_key(&(0, 1, 1).into()));
-        assert_matches!(manager.schedules.get(&(0, 1, 1).into()), Some(schedule) => {
-            assert_matches!(schedule.state, RecordingScheduleState::Finished);
+        assert_eq!(manager.schedules.len(), 1);
+        assert_matches!(manager.schedules.get(&(0, 1, 2).into()), Some(schedule) => {
+            assert_eq!(schedule.rule_id, Some(1.into()));
+            assert!(schedule.tags.contains("tag"));
+        });
+        assert_matches!(manager.rules.get(&RecordingRuleId::from(1)), Some(rule) => {
+            assert_eq!(rule.ignored_programs, hashset![(0, 1, 3).into()]);
         });
+
+        // Schedules already added are not changed.
+        let rule = manager
+            .rules
+            .get(&RecordingRuleId::from(1))
+            .cloned()
+            .unwrap();
+        let changed = manager.add_schedules_by_rules(now, &service, &[rule]).await;
+        assert!(!changed);
+        assert_eq!(manager.schedules.len(), 1);
     }
 
     #[test(tokio::test)]
-    async fn test_handle_recording_stopped_retry() {
+    async fn test_add_schedules_by_series() {
+        use crate::epg::SeriesDescriptor;
+
         let now = Jst::now();
 
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let mut manager = recording_manager!(config);
-
-        let mut stopped = MockRecordingStoppedValidator::new();
-        stopped.expect_emit().times(1).returning(|msg| {
-            assert_eq!(msg.program_id, (0, 1, 1).into());
-        });
-        manager.recording_stopped.register(Emitter::new(stopped));
-
-        let mut failed = MockRecordingFailedValidator::new();
-        failed.expect_emit().times(1).returning(|msg| {
-            assert_eq!(msg.program_id, (0, 1, 1).into());
-            assert_matches!(msg.reason, RecordingFailedReason::NeedRescheduling);
+        macro_rules! series {
+            ($episode:expr, $last:expr, $repeat:expr) => {
+                Some(SeriesDescriptor {
+                    series_id: 1,
+                    repeat_label: $repeat,
+                    program_pattern: 0,
+                    expire_date: None,
+                    episode_number: $episode,
+                    last_episode_number: $last,
+                    series_name: None,
+                })
+            };
+        }
+
+        let mut epg = MockEpg::new();
+        epg.expect_call().returning(move |_| {
+            let mut program1 = program!((0, 1, 1), now + Duration::try_hours(1).unwrap(), "1h");
+            program1.series = series!(1, 4, 0);
+            // Repeat broadcast.
+            let mut program2 = program!((0, 1, 2), now + Duration::try_hours(2).unwrap(), "1h");
+            program2.series = series!(1, 4, 1);
+            let mut program3 = program!((0, 1, 3), now + Duration::try_hours(3).unwrap(), "1h");
+            program3.series = series!(2, 4, 0);
+            // Other series.
+            let mut program4 = program!((0, 1, 4), now + Duration::try_hours(4).unwrap(), "1h");
+            program4.series = series!(3, 4, 0);
+            program4.series.as_mut().unwrap().series_id = 2;
+            // No series.
+            let program5 = program!((0, 1, 5), now + Duration::try_hours(5).unwrap(), "1h");
+            Ok(Arc::new(indexmap! {
+                1.into() => program1,
+                2.into() => program2,
+                3.into() => program3,
+                4.into() => program4,
+                5.into() => program5,
+            }))
         });
-        manager.recording_failed.register(Emitter::new(failed));
 
-        let start_time = now - Duration::try_minutes(30).unwrap();
+        let mut manager = recording_manager!(
+            config,
+            TunerManagerStub::default(),
+            epg,
+            OnairProgramManagerStub
+        );
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Recording,
-            program!((0, 1, 1), start_time, "1h"),
+        let mut program = program!((0, 1, 1), now + Duration::try_hours(1).unwrap(), "1h");
+        program.series = series!(1, 4, 0);
+        let mut options = recording_options!(1);
+        options.follow_series = true;
+        let mut schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program,
             service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0),
-            hashset!["tag1".to_string()]
+            options
         );
-        manager.schedules.insert((0, 1, 1).into(), schedule);
-
-        let recorder = recorder!(start_time, pipeline![format!("sh -c 'exit {EXIT_RETRY}'")]);
-        manager.recorders.insert((0, 1, 1).into(), recorder);
+        schedule.tags.insert("tag".to_string());
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
 
-        let changed = manager.handle_recording_stopped((0, 1, 1).into()).await;
+        let changed = manager.add_schedules_by_series(now, (0, 1).into()).await;
         assert!(changed);
-        assert!(!manager.recorders.contains_key(&(0, 1, 1).into()));
-        assert_matches!(manager.schedules.get(&(0, 1, 1).into()), Some(schedule) => {
-            assert_matches!(schedule.state, RecordingScheduleState::Rescheduling);
+        assert_eq!(manager.schedules.len(), 2);
+        assert_matches!(manager.schedules.get(&(0, 1, 3).into()), Some(schedule) => {
+            assert!(schedule.options.follow_series);
+            assert_eq!(schedule.options.priority, 1);
+            assert!(schedule.tags.contains("tag"));
         });
+
+        // Schedules already added are not changed.
+        let changed = manager.add_schedules_by_series(now, (0, 1).into()).await;
+        assert!(!changed);
+        assert_eq!(manager.schedules.len(), 2);
+
+        // No schedule is added once the last episode has been scheduled.
+        manager.schedules.clear();
+        manager.series_followers.clear();
+        let mut program = program!((0, 1, 1), now + Duration::try_hours(1).unwrap(), "1h");
+        program.series = series!(4, 4, 0);
+        let mut options = recording_options!(1);
+        options.follow_series = true;
+        let schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program,
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            options
+        );
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+        let changed = manager.add_schedules_by_series(now, (0, 1).into()).await;
+        assert!(!changed);
+        assert_eq!(manager.schedules.len(), 1);
+        assert!(manager.series_followers.is_empty());
     }
 
     #[test(tokio::test)]
-    async fn test_handle_recording_stopped_pipeline_error() {
+    async fn test_add_schedules_by_series_after_purged() {
+        use crate::epg::SeriesDescriptor;
+        use std::sync::atomic::AtomicBool;
+        use std::sync::atomic::Ordering;
+
         let now = Jst::now();
 
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let mut manager = recording_manager!(config);
-
-        let mut stopped = MockRecordingStoppedValidator::new();
-        stopped.expect_emit().times(1).returning(|msg| {
-            assert_eq!(msg.program_id, (0, 1, 1).into());
-        });
-        manager.recording_stopped.register(Emitter::new(stopped));
+        macro_rules! series {
+            ($episode:expr) => {
+                Some(SeriesDescriptor {
+                    series_id: 1,
+                    repeat_label: 0,
+                    program_pattern: 0,
+                    expire_date: None,
+                    episode_number: $episode,
+                    last_episode_number: 4,
+                    series_name: None,
+                })
+            };
+        }
 
-        let mut failed = MockRecordingFailedValidator::new();
-        failed.expect_emit().times(1).returning(|msg| {
-            assert_eq!(msg.program_id, (0, 1, 1).into());
-            assert_matches!(
-                msg.reason,
-                RecordingFailedReason::PipelineError { exit_code: 1 }
-            );
-        });
-        manager.recording_failed.register(Emitter::new(failed));
+        // The next episode appears in EPG later.
+        let next_episode = Arc::new(AtomicBool::new(false));
+        let mut epg = MockEpg::new();
+        {
+            let next_episode = next_episode.clone();
+            epg.expect_call().returning(move |_| {
+                let mut program = program!((0, 1, 2), now + Duration::try_days(7).unwrap(), "1h");
+                program.series = series!(2);
+                if next_episode.load(Ordering::SeqCst) {
+                    Ok(Arc::new(indexmap! { 2.into() => program }))
+                } else {
+                    Ok(Default::default())
+                }
+            });
+        }
 
-        let start_time = now - Duration::try_minutes(30).unwrap();
+        let mut manager = recording_manager!(
+            config.clone(),
+            TunerManagerStub::default(),
+            epg,
+            OnairProgramManagerStub
+        );
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Recording,
-            program!((0, 1, 1), start_time, "1h"),
+        let mut program = program!((0, 1, 1), now + Duration::try_hours(1).unwrap(), "1h");
+        program.series = series!(1);
+        let mut options = recording_options!(1);
+        options.follow_series = true;
+        let mut schedule = recording_schedule!(
+            RecordingScheduleState::Scheduled,
+            program,
             service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0),
-            hashset!["tag1".to_string()]
+            options
         );
-        manager.schedules.insert((0, 1, 1).into(), schedule);
+        schedule.tags.insert("tag".to_string());
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+        manager.save_schedules();
 
-        let recorder = recorder!(start_time, pipeline!["false"]);
-        manager.recorders.insert((0, 1, 1).into(), recorder);
+        let changed = manager.add_schedules_by_series(now, (0, 1).into()).await;
+        assert!(!changed);
 
-        let changed = manager.handle_recording_stopped((0, 1, 1).into()).await;
+        // The schedule has been purged and the state is restored from the file.
+        manager.schedules.clear();
+        manager.series_followers.clear();
+        manager.load_series_followers();
+        assert_eq!(manager.series_followers.len(), 1);
+
+        next_episode.store(true, Ordering::SeqCst);
+        let changed = manager.add_schedules_by_series(now, (0, 1).into()).await;
         assert!(changed);
-        assert!(!manager.recorders.contains_key(&(0, 1, 1).into()));
-        assert_matches!(manager.schedules.get(&(0, 1, 1).into()), Some(schedule) => {
-            assert_matches!(schedule.state, RecordingScheduleState::Failed);
+        assert_matches!(manager.schedules.get(&(0, 1, 2).into()), Some(schedule) => {
+            assert!(schedule.options.follow_series);
+            assert_eq!(schedule.options.priority, 1);
+            assert!(schedule.tags.contains("tag"));
+        });
+        assert_matches!(manager.series_followers.get(&((0, 1).into(), 1)), Some(follower) => {
+            assert_eq!(follower.episode_number, 2);
         });
+
+        // The schedule removed by users is not added again, but the series is still followed.
+        manager.schedules.clear();
+        let changed = manager.add_schedules_by_series(now, (0, 1).into()).await;
+        assert!(!changed);
+        assert_eq!(manager.series_followers.len(), 1);
+
+        // Removing schedules by a tag stops following the series.
+        manager.remove_schedules_by_tag("tag", now);
+        assert!(manager.series_followers.is_empty());
+        let changed = manager.add_schedules_by_series(now, (0, 1).into()).await;
+        assert!(!changed);
     }
 
-    #[test(tokio::test)]
-    async fn test_update_schedules_by_epg_services() {
+    #[test]
+    fn test_remove_pending_schedules_by_rule() {
         let now = Jst::now();
 
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
         let mut manager = recording_manager!(config);
-        let mut mock = MockRecordingFailedValidator::new();
-        mock.expect_emit().times(1).returning(|msg| {
-            assert_eq!(msg.program_id, (0, 2, 1).into());
-            assert_matches!(msg.reason, RecordingFailedReason::RemovedFromEpg);
-        });
-        manager.recording_failed.register(Emitter::new(mock));
 
-        let schedule = recording_schedule!(
+        let mut schedule = recording_schedule!(
             RecordingScheduleState::Scheduled,
-            program!((0, 1, 1), now),
+            program!((0, 1, 1), now + Duration::try_hours(1).unwrap(), "1h"),
             service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0)
+            recording_options!(0)
+        );
+        schedule.rule_id = Some(1.into());
+        let result = manager.add_schedule(schedule);
+        assert_matches!(result, Ok(()));
+
+        let mut schedule = recording_schedule!(
+            RecordingScheduleState::Recording,
+            program!((0, 1, 2), now, "1h"),
+            service!((0, 1), "sv", channel_gr!("ch", "ch")),
+            recording_options!(0)
         );
+        schedule.rule_id = Some(1.into());
         let result = manager.add_schedule(schedule);
         assert_matches!(result, Ok(()));
 
         let schedule = recording_schedule!(
             RecordingScheduleState::Scheduled,
-            program!((0, 2, 1), now),
+            program!((0, 1, 3), now + Duration::try_hours(1).unwrap(), "1h"),
             service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("2.m2ts", 0)
+            recording_options!(0)
         );
         let result = manager.add_schedule(schedule);
         assert_matches!(result, Ok(()));
 
-        let services = indexmap! {
-            (0, 1).into() => service!((0, 1), "test", channel_gr!("gr", "1")),
-        };
-        let changed = manager.update_schedules_by_epg_services(&services).await;
-        assert!(changed);
-        assert_eq!(manager.schedules.len(), 1);
-        assert!(manager.schedules.contains_key(&(0, 1, 1).into()));
-        assert!(!manager.schedules.contains_key(&(0, 2, 1).into()));
+        assert_eq!(manager.next_rule_id(), 2.into());
 
-        let changed = manager.update_schedules_by_epg_services(&services).await;
-        assert!(!changed);
+        manager.remove_pending_schedules(now, |schedule| schedule.rule_id == Some(1.into()));
+        assert_eq!(manager.schedules.len(), 2);
+        assert!(!manager.schedules.contains_key(&(0, 1, 1).into()));
+        assert!(manager.schedules.contains_key(&(0, 1, 2).into()));
+        assert!(manager.schedules.contains_key(&(0, 1, 3).into()));
+    }
+
+    #[test]
+    fn test_save_and_load_recurring_schedules() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let mut manager = recording_manager!(config.clone());
+        let recurring_schedule = recurring_schedule!(1, (0, 1), "0 0 21 * * *");
+        manager
+            .recurring_schedules
+            .insert(recurring_schedule.id, recurring_schedule);
+        let mut recurring_schedule = recurring_schedule!(2, (0, 1), "0 0 21 * * *", "30m");
+        recurring_schedule.expanded_until = Some(jst!("2025-01-01T21:00:00+09:00"));
+        manager
+            .recurring_schedules
+            .insert(recurring_schedule.id, recurring_schedule);
+
+        manager.save_recurring_schedules();
+        assert!(make_recurring_schedules_path(&config).unwrap().is_file());
+
+        let mut manager = recording_manager!(config.clone());
+        manager.load_recurring_schedules();
+        assert_eq!(manager.recurring_schedules.len(), 2);
+        let recurring_schedule = &manager.recurring_schedules[&RecurringScheduleId::from(1)];
+        assert_eq!(recurring_schedule.duration, None);
+        assert_eq!(recurring_schedule.expanded_until, None);
+        let recurring_schedule = &manager.recurring_schedules[&RecurringScheduleId::from(2)];
+        assert_eq!(
+            recurring_schedule.duration,
+            Some(Duration::try_minutes(30).unwrap())
+        );
+        assert_eq!(
+            recurring_schedule.expanded_until,
+            Some(jst!("2025-01-01T21:00:00+09:00"))
+        );
+    }
+
+    #[test]
+    fn test_recurring_schedule_validate() {
+        let recurring_schedule = recurring_schedule!(1, (0, 1), "0 0 21 * * *");
+        assert_matches!(recurring_schedule.validate(), Ok(()));
+
+        let recurring_schedule = recurring_schedule!(1, (0, 1), "0 0 21 * * *", "24h");
+        assert_matches!(recurring_schedule.validate(), Ok(()));
+
+        let recurring_schedule = recurring_schedule!(1, (0, 1), "invalid");
+        assert_matches!(recurring_schedule.validate(), Err(Error::InvalidRequest(_)));
+
+        let recurring_schedule = recurring_schedule!(1, (0, 1), "0 0 21 * * *", "0s");
+        assert_matches!(recurring_schedule.validate(), Err(Error::InvalidRequest(_)));
+
+        let recurring_schedule = recurring_schedule!(1, (0, 1), "0 0 21 * * *", "25h");
+        assert_matches!(recurring_schedule.validate(), Err(Error::InvalidRequest(_)));
     }
 
     #[test(tokio::test)]
-    async fn test_update_schedules_by_epg_programs() {
-        let now = Jst::now();
+    async fn test_expand_recurring_schedules() {
+        let now = jst!("2025-01-01T10:30:00+09:00");
 
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
@@ -4018,7 +9583,9 @@ mod tests {
         let mut epg = MockEpg::new();
         epg.expect_call().returning(move |_| {
             Ok(Arc::new(indexmap! {
-                1.into() => program!((0, 1, 1), now, "1h"),
+                1.into() => program!((0, 1, 1), jst!("2025-01-01T11:00:00+09:00"), "1h"),
+                2.into() => program!((0, 1, 2), jst!("2025-01-01T12:00:00+09:00"), "30m"),
+                3.into() => program!((0, 1, 3), jst!("2025-01-01T12:30:00+09:00"), "30m"),
             }))
         });
 
@@ -4028,95 +9595,308 @@ mod tests {
             epg,
             OnairProgramManagerStub
         );
-        let mut failed_mock = MockRecordingFailedValidator::new();
-        failed_mock.expect_emit().times(1).returning(|msg| {
-            assert_eq!(msg.program_id, (0, 1, 2).into());
-            assert_matches!(msg.reason, RecordingFailedReason::RemovedFromEpg);
-        });
-        manager.recording_failed.register(Emitter::new(failed_mock));
 
-        let mut rescheduled_mock = MockRecordingRescheduledValidator::new();
-        rescheduled_mock.expect_emit().never();
+        // TV programs on air at 11:00 and 12:00 are recorded.  Expansion stops at 13:00 because
+        // no TV program exists in EPG.
+        let recurring_schedule = recurring_schedule!(1, (0, 1), "0 0 * * * *");
         manager
-            .recording_rescheduled
-            .register(Emitter::new(rescheduled_mock));
+            .recurring_schedules
+            .insert(recurring_schedule.id, recurring_schedule);
+        // Time-range recording schedules are added for 3 days.
+        let recurring_schedule = recurring_schedule!(2, (0, 1), "0 0 12 * * *", "30m");
+        manager
+            .recurring_schedules
+            .insert(recurring_schedule.id, recurring_schedule);
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Rescheduling,
-            program!((0, 1, 1), now),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0)
+        let service = service!((0, 1), "sv", channel_gr!("ch", "ch"));
+        let ids = [1.into(), 2.into()];
+        let changed = manager
+            .expand_recurring_schedules(now, &service, &ids)
+            .await;
+        assert!(changed);
+        assert_eq!(manager.schedules.len(), 5);
+        assert_matches!(manager.schedules.get(&(0, 1, 1).into()), Some(schedule) => {
+            assert_eq!(schedule.recurring_schedule_id, Some(1.into()));
+        });
+        assert_matches!(manager.schedules.get(&(0, 1, 2).into()), Some(schedule) => {
+            assert_eq!(schedule.recurring_schedule_id, Some(1.into()));
+        });
+        let time_range_schedules = manager
+            .schedules
+            .values()
+            .filter(|schedule| schedule.is_time_range())
+            .collect_vec();
+        assert_eq!(time_range_schedules.len(), 3);
+        assert!(time_range_schedules.iter().all(|schedule| {
+            schedule.recurring_schedule_id == Some(2.into())
+                && schedule.program.duration == Some(Duration::try_minutes(30).unwrap())
+        }));
+        assert_eq!(
+            manager.recurring_schedules[&RecurringScheduleId::from(1)].expanded_until,
+            Some(jst!("2025-01-01T12:00:00+09:00"))
         );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
-
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Scheduled,
-            program!((0, 1, 2), now),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("2.m2ts", 0)
+        assert_eq!(
+            manager.recurring_schedules[&RecurringScheduleId::from(2)].expanded_until,
+            Some(jst!("2025-01-03T12:00:00+09:00"))
         );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
 
+        // Schedules removed by users are never added again.
+        manager.schedules.remove(&(0, 1, 1).into());
         let changed = manager
-            .update_schedules_by_epg_programs(now, (0, 1).into())
+            .expand_recurring_schedules(now, &service, &ids)
             .await;
-        assert!(changed);
-        assert_eq!(manager.schedules.len(), 1);
-        assert!(manager.schedules.contains_key(&(0, 1, 1).into()));
-        assert!(!manager.schedules.contains_key(&(0, 1, 2).into()));
+        assert!(!changed);
+        assert_eq!(manager.schedules.len(), 4);
 
+        // Remaining occurrences are expanded later.
+        let now = jst!("2025-01-02T10:30:00+09:00");
         let changed = manager
-            .update_schedules_by_epg_programs(now, (0, 0).into())
+            .expand_recurring_schedules(now, &service, &ids)
             .await;
-        assert!(!changed);
+        assert!(changed);
+        assert_eq!(manager.schedules.len(), 5);
+        assert_eq!(
+            manager.recurring_schedules[&RecurringScheduleId::from(2)].expanded_until,
+            Some(jst!("2025-01-04T12:00:00+09:00"))
+        );
     }
 
     #[test(tokio::test)]
-    async fn test_update_schedules_by_epg_programs_rescheduled() {
-        let now = Jst::now();
+    async fn test_expand_all_recurring_schedules() {
+        let now = jst!("2025-01-01T10:30:00+09:00");
 
         let temp_dir = TempDir::new().unwrap();
         let config = config_for_test(temp_dir.path());
 
-        let mut epg = MockEpg::new();
-        epg.expect_call().returning(move |_| {
-            Ok(Arc::new(indexmap! {
-                1.into() => program!((0, 1, 1), now, "1h"),
-            }))
+        let mut manager = recording_manager!(
+            config,
+            TunerManagerStub::default(),
+            EpgStub,
+            OnairProgramManagerStub
+        );
+
+        // ProgramsUpdated is never sent for a service without EIT.
+        let recurring_schedule = recurring_schedule!(1, (0, 1), "0 0 12 * * *", "30m");
+        manager
+            .recurring_schedules
+            .insert(recurring_schedule.id, recurring_schedule);
+
+        let changed = manager.expand_all_recurring_schedules(now).await;
+        assert!(changed);
+        assert_eq!(manager.schedules.len(), 3);
+
+        // Nothing changes until the lookahead period advances.
+        let changed = manager.expand_all_recurring_schedules(now).await;
+        assert!(!changed);
+        assert_eq!(manager.schedules.len(), 3);
+
+        // Children are added for the lookahead period after it passes.
+        let now = now + Duration::try_days(RECURRING_SCHEDULE_LOOKAHEAD_DAYS + 1).unwrap();
+        let changed = manager.expand_all_recurring_schedules(now).await;
+        assert!(changed);
+        assert_eq!(manager.schedules.len(), 6);
+        assert!(manager.schedules.values().all(|schedule| {
+            schedule.recurring_schedule_id == Some(1.into()) && schedule.is_time_range()
+        }));
+        assert_eq!(
+            manager.recurring_schedules[&RecurringScheduleId::from(1)].expanded_until,
+            Some(jst!("2025-01-07T12:00:00+09:00"))
+        );
+    }
+
+    #[test]
+    fn test_detect_conflicts() {
+        use crate::config::ExcludedChannelConfig;
+        use crate::models::ChannelType;
+
+        let config = Config {
+            tuners: vec![
+                TunerConfig {
+                    name: "t0".to_string(),
+                    channel_types: vec![ChannelType::GR],
+                    command: "true".to_string(),
+                    ..Default::default()
+                },
+                TunerConfig {
+                    name: "t1".to_string(),
+                    channel_types: vec![ChannelType::GR],
+                    command: "true".to_string(),
+                    excluded_channels: vec![ExcludedChannelConfig::Params {
+                        channel_type: ChannelType::GR,
+                        channel: "3".to_string(),
+                    }],
+                    ..Default::default()
+                },
+                TunerConfig {
+                    name: "t2".to_string(),
+                    channel_types: vec![ChannelType::GR],
+                    command: "true".to_string(),
+                    disabled: true,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        macro_rules! schedule {
+            ($eid:expr, $ch:expr, $start:literal, $duration:literal, $priority:expr) => {
+                recording_schedule!(
+                    RecordingScheduleState::Scheduled,
+                    program!((0, 1, $eid), jst!($start), $duration),
+                    service!((0, 1), "sv", channel_gr!("ch", $ch)),
+                    recording_options!($priority)
+                )
+            };
+        }
+
+        let schedules = [
+            schedule!(1, "1", "2025-01-01T10:00:00+09:00", "1h", 0),
+            // Share the tuner with the schedule #1.
+            schedule!(2, "1", "2025-01-01T10:00:00+09:00", "1h", 0),
+            schedule!(3, "2", "2025-01-01T10:30:00+09:00", "1h", 0),
+            // No tuner is available.
+            schedule!(4, "3", "2025-01-01T10:45:00+09:00", "30m", 0),
+            // Available after the schedule #1 and #2 end.
+            schedule!(5, "3", "2025-01-01T11:00:00+09:00", "30m", 0),
+            // Grab the tuner used for the schedule #5.
+            schedule!(6, "4", "2025-01-01T11:15:00+09:00", "30m", 1),
+        ];
+
+        let conflicts = detect_conflicts(&config, schedules.iter(), &[]);
+        assert_eq!(conflicts.len(), 2);
+        assert_matches!(conflicts.get(&(0, 1, 4).into()), Some(conflict) => {
+            // The tuner used for the schedule #3 cannot be used for the channel.
+            assert_eq!(conflict.programs, vec![(0, 1, 1).into(), (0, 1, 2).into()]);
+        });
+        assert_matches!(conflicts.get(&(0, 1, 5).into()), Some(conflict) => {
+            assert_eq!(conflict.programs, vec![(0, 1, 6).into()]);
         });
 
-        let mut manager = recording_manager!(
-            config,
-            TunerManagerStub::default(),
-            epg,
-            OnairProgramManagerStub
-        );
+        // Schedules which have finished are ignored.
+        let mut schedules = schedules;
+        schedules[2].state = RecordingScheduleState::Finished;
+        let conflicts = detect_conflicts(&config, schedules.iter(), &[]);
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts.contains_key(&(0, 1, 4).into()));
+    }
+
+    #[test]
+    fn test_detect_conflicts_dedicated_tuners() {
+        let config = serde_norway::from_str::<Config>(
+            r#"
+            tuners:
+              - name: t0
+                types: [GR]
+                command: 'true'
+              - name: t1
+                types: [GR]
+                command: 'true'
+            timeshift:
+              recorders:
+                test:
+                  service-id: 1
+                  ts-file: /dev/null
+                  data-file: /dev/null
+                  num-chunks: 100
+                  uses:
+                    tuner: t0
+                    channel-type: GR
+                    channel: '1'
+            "#,
+        )
+        .unwrap();
+
+        let schedules = [
+            recording_schedule!(
+                RecordingScheduleState::Scheduled,
+                program!((0, 1, 1), jst!("2025-01-01T10:00:00+09:00"), "1h"),
+                service!((0, 1), "sv", channel_gr!("ch", "1")),
+                recording_options!(0)
+            ),
+            recording_schedule!(
+                RecordingScheduleState::Scheduled,
+                program!((0, 1, 2), jst!("2025-01-01T10:00:00+09:00"), "1h"),
+                service!((0, 1), "sv", channel_gr!("ch", "2")),
+                recording_options!(0)
+            ),
+            recording_schedule!(
+                RecordingScheduleState::Scheduled,
+                program!((0, 1, 3), jst!("2025-01-01T10:00:00+09:00"), "1h"),
+                service!((0, 1), "sv", channel_gr!("ch", "3")),
+                recording_options!(0)
+            ),
+        ];
 
-        let mut mock = MockRecordingRescheduledValidator::new();
-        mock.expect_emit().times(1).returning(|msg| {
-            assert_eq!(msg.program_id, (0, 1, 1).into());
+        let conflicts = detect_conflicts(&config, schedules.iter(), &[]);
+        assert_eq!(conflicts.len(), 1);
+        assert_matches!(conflicts.get(&(0, 1, 3).into()), Some(conflict) => {
+            assert_eq!(conflict.programs, vec![(0, 1, 2).into()]);
         });
-        manager.recording_rescheduled.register(Emitter::new(mock));
+    }
 
-        let schedule = recording_schedule!(
-            RecordingScheduleState::Rescheduling,
-            program!((0, 1, 1), now - Duration::try_minutes(30).unwrap(), "1h"),
-            service!((0, 1), "sv", channel_gr!("ch", "ch")),
-            recording_options!("1.m2ts", 0)
-        );
-        let result = manager.add_schedule(schedule);
-        assert_matches!(result, Ok(()));
+    #[test]
+    fn test_detect_conflicts_tuner_reservations() {
+        let config = serde_norway::from_str::<Config>(
+            r#"
+            tuners:
+              - name: t0
+                types: [GR]
+                command: 'true'
+              - name: t1
+                types: [GR]
+                command: 'true'
+            "#,
+        )
+        .unwrap();
+
+        let reservations = [TunerReservation {
+            id: 1,
+            tuner_index: 0,
+            user_id: "test".to_string(),
+            priority: 1.into(),
+            start_at: jst!("2025-01-01T10:30:00+09:00"),
+            end_at: jst!("2025-01-01T11:00:00+09:00"),
+        }];
+
+        let schedules = [
+            recording_schedule!(
+                RecordingScheduleState::Scheduled,
+                program!((0, 1, 1), jst!("2025-01-01T10:00:00+09:00"), "1h"),
+                service!((0, 1), "sv", channel_gr!("ch", "1")),
+                recording_options!(0)
+            ),
+            // The reserved tuner cannot be used.
+            recording_schedule!(
+                RecordingScheduleState::Scheduled,
+                program!((0, 1, 2), jst!("2025-01-01T10:00:00+09:00"), "1h"),
+                service!((0, 1), "sv", channel_gr!("ch", "2")),
+                recording_options!(0)
+            ),
+            // The reserved tuner can be used after the reservation ends.
+            recording_schedule!(
+                RecordingScheduleState::Scheduled,
+                program!((0, 1, 3), jst!("2025-01-01T11:00:00+09:00"), "1h"),
+                service!((0, 1), "sv", channel_gr!("ch", "3")),
+                recording_options!(0)
+            ),
+            // Higher priority than the reservation.
+            recording_schedule!(
+                RecordingScheduleState::Scheduled,
+                program!((0, 1, 4), jst!("2025-01-01T10:00:00+09:00"), "1h"),
+                service!((0, 1), "sv", channel_gr!("ch", "4")),
+                recording_options!(2)
+            ),
+        ];
 
-        let changed = manager
-            .update_schedules_by_epg_programs(now, (0, 1).into())
-            .await;
-        assert!(changed);
-        assert_eq!(manager.schedules.len(), 1);
-        assert_matches!(manager.schedules.get(&(0, 1, 1).into()), Some(schedule) => {
-            assert_matches!(schedule.state, RecordingScheduleState::Scheduled);
+        let conflicts = detect_conflicts(&config, schedules[0..3].iter(), &reservations);
+        assert_eq!(conflicts.len(), 1);
+        assert_matches!(conflicts.get(&(0, 1, 2).into()), Some(conflict) => {
+            assert_eq!(conflict.programs, vec![(0, 1, 1).into()]);
         });
+
+        let conflicts = detect_conflicts(&config, schedules.iter(), &reservations);
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts.contains_key(&(0, 1, 2).into()));
     }
 
     #[test(tokio::test)]
@@ -4136,11 +9916,11 @@ mod tests {
             .unwrap();
 
         // recording, w/o range
-        let mut source = ContentSource::new(&config, &record, None, &ctx).unwrap();
+        let mut source = ContentSource::new(&config, &record, None, 0, &ctx).unwrap();
         let models = source.pipeline.get_model();
         assert_eq!(models.len(), 1);
         assert_matches!(models[0], CommandPipelineProcessModel { ref command, pid } => {
-            assert_eq!(*command, format!("tail -f -c +0 '{content_path_str}'"));
+            assert_eq!(*command, format!("tail -f -c +1 '{content_path_str}'"));
             assert!(pid.is_some());
         });
         let stream = source.create_stream(1000);
@@ -4151,9 +9931,25 @@ mod tests {
             assert_eq!(content, "0123456789");
         });
 
+        // recording, w/ offset
+        let mut source = ContentSource::new(&config, &record, None, 3, &ctx).unwrap();
+        let models = source.pipeline.get_model();
+        assert_eq!(models.len(), 1);
+        assert_matches!(models[0], CommandPipelineProcessModel { ref command, pid } => {
+            assert_eq!(*command, format!("tail -f -c +4 '{content_path_str}'"));
+            assert!(pid.is_some());
+        });
+        let stream = source.create_stream(1000);
+        let mut reader = tokio_util::io::StreamReader::new(stream);
+        let mut content = String::new();
+        assert_matches!(reader.read_to_string(&mut content).await, Ok(size) => {
+            assert_eq!(size, 7);
+            assert_eq!(content, "3456789");
+        });
+
         // recording, w/ range
         let range = Some(ContentRange::without_size(1, 3).unwrap());
-        let mut source = ContentSource::new(&config, &record, range.as_ref(), &ctx).unwrap();
+        let mut source = ContentSource::new(&config, &record, range.as_ref(), 0, &ctx).unwrap();
         let models = source.pipeline.get_model();
         assert_eq!(models.len(), 1);
         assert_matches!(models[0], CommandPipelineProcessModel { ref command, pid } => {
@@ -4171,7 +9967,7 @@ mod tests {
         let record = record!(finished: id.value());
 
         // finished, w/o range
-        let mut source = ContentSource::new(&config, &record, None, &ctx).unwrap();
+        let mut source = ContentSource::new(&config, &record, None, 0, &ctx).unwrap();
         let models = source.pipeline.get_model();
         assert_eq!(models.len(), 1);
         assert_matches!(models[0], CommandPipelineProcessModel { ref command, pid } => {
@@ -4186,9 +9982,25 @@ mod tests {
             assert_eq!(content, "0123456789");
         });
 
+        // finished, w/ offset
+        let mut source = ContentSource::new(&config, &record, None, 3, &ctx).unwrap();
+        let models = source.pipeline.get_model();
+        assert_eq!(models.len(), 1);
+        assert_matches!(models[0], CommandPipelineProcessModel { ref command, pid } => {
+            assert_eq!(*command, format!("tail -c +4 '{content_path_str}'"));
+            assert!(pid.is_some());
+        });
+        let stream = source.create_stream(1000);
+        let mut reader = tokio_util::io::StreamReader::new(stream);
+        let mut content = String::new();
+        assert_matches!(reader.read_to_string(&mut content).await, Ok(size) => {
+            assert_eq!(size, 7);
+            assert_eq!(content, "3456789");
+        });
+
         // finished, w/ range
         let range = Some(ContentRange::with_size(1, 3, 10).unwrap());
-        let mut source = ContentSource::new(&config, &record, range.as_ref(), &ctx).unwrap();
+        let mut source = ContentSource::new(&config, &record, range.as_ref(), 0, &ctx).unwrap();
         let models = source.pipeline.get_model();
         assert_eq!(models.len(), 1);
         assert_matches!(models[0], CommandPipelineProcessModel { ref command, pid } => {
@@ -4204,6 +10016,207 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_content_index_find_offset() {
+        let mut record = record!(finished: "1");
+        record.recording_start_time = jst!("2021-01-01T00:00:00+09:00");
+        record.recording_duration = Some(Duration::seconds(100));
+
+        let time = |sec: i64| jst!("2021-01-01T00:00:00+09:00") + Duration::seconds(sec);
+        let index = ContentIndex::estimate(&record, TS_PACKET_SIZE * 100);
+        assert_eq!(index.find_offset(time(-1)), None);
+        assert_eq!(index.find_offset(time(0)), Some(0));
+        assert_eq!(index.find_offset(time(50)), Some(TS_PACKET_SIZE * 50));
+        assert_eq!(index.find_offset(time(99)), Some(TS_PACKET_SIZE * 99));
+        assert_eq!(index.find_offset(time(100)), None);
+
+        // Aligned to a TS packet boundary.
+        let index = ContentIndex::estimate(&record, TS_PACKET_SIZE * 10);
+        assert_eq!(index.find_offset(time(55)), Some(TS_PACKET_SIZE * 5));
+    }
+
+    fn ts_packet(pid: u16, pcr: Option<u64>) -> Vec<u8> {
+        let mut packet = vec![0xFF; TS_PACKET_SIZE as usize];
+        packet[0] = 0x47;
+        packet[1] = (pid >> 8) as u8;
+        packet[2] = pid as u8;
+        match pcr {
+            Some(pcr) => {
+                packet[3] = 0x30;
+                packet[4] = 7;
+                packet[5] = 0x10;
+                packet[6] = (pcr >> 25) as u8;
+                packet[7] = (pcr >> 17) as u8;
+                packet[8] = (pcr >> 9) as u8;
+                packet[9] = (pcr >> 1) as u8;
+                packet[10] = ((pcr & 1) << 7) as u8;
+            }
+            None => packet[3] = 0x10,
+        }
+        packet
+    }
+
+    #[test]
+    fn test_content_index_build() {
+        let packet = ts_packet;
+
+        let start_time = jst!("2021-01-01T00:00:00+09:00");
+        let time = |sec: i64| start_time + Duration::seconds(sec);
+
+        let index = ContentIndex::build(&[][..], start_time).unwrap();
+        assert!(index.points().is_empty());
+
+        let mut data = vec![];
+        for sec in 0..=25 {
+            data.extend(packet(0x1FF, Some((1 << 33) - 90_000 + sec * 90_000)));
+            // PCRs on other PIDs are ignored.
+            data.extend(packet(0x100, Some(1000)));
+        }
+        // A PCR discontinuity is ignored.
+        data.extend(packet(0x1FF, Some(1_000_000_000)));
+        data.extend(packet(0x1FF, Some(1_000_000_000 + 90_000)));
+        data.extend(packet(0x100, None));
+
+        let index = ContentIndex::build(&data[..], start_time).unwrap();
+        let points: Vec<_> = index
+            .points()
+            .iter()
+            .map(|point| (point.timestamp, point.pos))
+            .collect();
+        let pos = |n: u64| TS_PACKET_SIZE * n;
+        assert_eq!(
+            points,
+            vec![
+                (time(0), pos(0)),
+                (time(10), pos(20)),
+                (time(20), pos(40)),
+                (time(26), pos(55)),
+            ]
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_content_index_writer() {
+        let temp_dir = TempDir::new().unwrap();
+        let content_path = temp_dir.path().join("test.m2ts");
+        let path = temp_dir.path().join("test.index.jsonl");
+        let start_time = jst!("2021-01-01T00:00:00+09:00");
+
+        let mut data = vec![];
+        for sec in 0..=25 {
+            data.extend(ts_packet(0x1FF, Some(sec * 90_000 + 1)));
+            data.extend(ts_packet(0x100, None));
+        }
+        // Data written in the second attempt.
+        for sec in 0..=25 {
+            data.extend(ts_packet(0x1FF, Some(1_000_000_000 + sec * 90_000)));
+        }
+        let expected = ContentIndex::build(&data[..], start_time).unwrap();
+        let points = |index: &ContentIndex| -> Vec<_> {
+            index
+                .points()
+                .iter()
+                .map(|point| (point.timestamp, point.pos))
+                .collect()
+        };
+
+        // Data is written in chunks which are not aligned to TS packets.
+        let mut writer =
+            ContentIndexWriter::open(path.clone(), start_time, &content_path, false).await;
+        for chunk in data.chunks(1000) {
+            writer.advance(chunk).await;
+        }
+        writer.finish().await;
+        assert_matches!(ContentIndex::load(&path).await, Some(index) => {
+            assert_eq!(points(&index), points(&expected));
+        });
+
+        // Retry.
+        let (first, second) = data.split_at(TS_PACKET_SIZE as usize * 52 + 100);
+        std::fs::remove_file(&path).unwrap();
+        let mut writer =
+            ContentIndexWriter::open(path.clone(), start_time, &content_path, false).await;
+        writer.advance(first).await;
+        writer.finish().await;
+        std::fs::write(&content_path, first).unwrap();
+        let mut writer =
+            ContentIndexWriter::open(path.clone(), start_time, &content_path, true).await;
+        for chunk in second.chunks(1000) {
+            writer.advance(chunk).await;
+        }
+        writer.finish().await;
+        assert_matches!(ContentIndex::load(&path).await, Some(index) => {
+            assert_eq!(points(&index), points(&expected));
+        });
+    }
+
+    #[test(tokio::test)]
+    async fn test_content_index_load_save() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("test.index.jsonl");
+        let time = |sec: i64| jst!("2021-01-01T00:00:00+09:00") + Duration::seconds(sec);
+
+        assert_matches!(ContentIndex::load(&path).await, None);
+
+        let mut index = ContentIndex::default();
+        index.push(ContentIndexPoint {
+            timestamp: time(0),
+            pos: 0,
+        });
+        index.push(ContentIndexPoint {
+            timestamp: time(10),
+            pos: 100,
+        });
+        // Ignored.
+        index.push(ContentIndexPoint {
+            timestamp: time(5),
+            pos: 200,
+        });
+        assert_eq!(index.points().len(), 2);
+        assert!(index.save(&path));
+
+        // A broken line is skipped.
+        let mut data = std::fs::read_to_string(&path).unwrap();
+        data.push_str(r#"{"timestamp":"#);
+        std::fs::write(&path, data).unwrap();
+
+        assert_matches!(ContentIndex::load(&path).await, Some(index) => {
+            assert_eq!(index.points().len(), 2);
+            assert_eq!(index.points()[1].timestamp, time(10));
+            assert_eq!(index.points()[1].pos, 100);
+        });
+    }
+
+    #[test(tokio::test)]
+    async fn test_rebuild_content_index() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for_test(temp_dir.path());
+
+        let id = RecordId("recording".to_string());
+        let record = record!(recording: id.value());
+        let record_path = make_record_path(&config, &id).unwrap();
+        assert!(file_util::save_json(&record, &record_path));
+        let content_path = make_content_path(&config, &record).unwrap();
+        assert!(file_util::save_data(b"0123456789", &content_path));
+
+        // Skipped.
+        assert_matches!(rebuild_content_index(&config, &id).await, Ok(None));
+        assert!(!make_index_path(&config, &id).unwrap().exists());
+
+        let id = RecordId("finished".to_string());
+        let record = record!(finished: id.value());
+        let record_path = make_record_path(&config, &id).unwrap();
+        assert!(file_util::save_json(&record, &record_path));
+        let content_path = make_content_path(&config, &record).unwrap();
+        assert!(file_util::save_data(b"0123456789", &content_path));
+
+        assert_matches!(rebuild_content_index(&config, &id).await, Ok(Some(_)));
+        assert!(make_index_path(&config, &id).unwrap().exists());
+
+        let id = RecordId("unknown".to_string());
+        assert_matches!(rebuild_content_index(&config, &id).await, Err(_));
+    }
+
     #[test(tokio::test)]
     async fn test_check_retry() {
         // exit(0)
@@ -4256,6 +10269,32 @@ mod tests {
         Arc::new(config)
     }
 
+    // Recordings started with this config use a tuner which keeps streaming until it's
+    // deactivated.
+    fn config_with_tuner_for_test<P: AsRef<Path>>(dir: P) -> Arc<Config> {
+        let mut config = Config::default();
+
+        let recording_dir = dir.as_ref().join(RECORDING_DIR);
+        std::fs::create_dir(&recording_dir).unwrap();
+        config.recording.basedir = Some(recording_dir);
+
+        let records_dir = dir.as_ref().join(RECORDS_DIR);
+        std::fs::create_dir(&records_dir).unwrap();
+        config.recording.records_dir = Some(records_dir);
+
+        config.tuners = vec![TunerConfig {
+            name: "tuner".to_string(),
+            channel_types: vec![ChannelType::GR],
+            command: "sleep 10".to_string(),
+            time_limit: 10 * 1000,
+            ..Default::default()
+        }];
+        config.filters.program_filter.command = "cat".to_string();
+        config.filters.service_filter.command = "cat".to_string();
+
+        Arc::new(config)
+    }
+
     async fn append(path: &Path, data: &[u8]) {
         use tokio::io::AsyncWriteExt;
         tokio::fs::OpenOptions::new()
@@ -4363,6 +10402,28 @@ pub(crate) mod stub {
         }
     }
 
+    #[async_trait]
+    impl Call<AddTimeRangeRecordingSchedule> for RecordingManagerStub {
+        async fn call(
+            &self,
+            msg: AddTimeRangeRecordingSchedule,
+        ) -> actlet::Result<<AddTimeRangeRecordingSchedule as Message>::Reply> {
+            if msg.end_at <= Jst::now() {
+                return Ok(Err(Error::ProgramEnded));
+            }
+            let program_id = ProgramId::new_user_defined(msg.service.id, 0).unwrap();
+            let mut program = EpgProgram::new(program_id);
+            program.start_at = Some(msg.start_at);
+            program.duration = Some(msg.end_at - msg.start_at);
+            Ok(Ok(RecordingSchedule::new(
+                program,
+                msg.service,
+                msg.options,
+                msg.tags,
+            )))
+        }
+    }
+
     #[async_trait]
     impl Call<QueryRecordingSchedule> for RecordingManagerStub {
         async fn call(
@@ -4431,12 +10492,56 @@ pub(crate) mod stub {
         }
     }
 
+    #[async_trait]
+    impl Call<RunPostProcessJob> for RecordingManagerStub {
+        async fn call(
+            &self,
+            msg: RunPostProcessJob,
+        ) -> actlet::Result<<RunPostProcessJob as Message>::Reply> {
+            match (msg.record_id.value(), msg.name.as_str()) {
+                ("recording", _) => Ok(Err(Error::InvalidRequest(""))),
+                (_, "running") => Ok(Err(Error::AlreadyExists)),
+                (_, "unknown") => Ok(Err(Error::PostProcessJobNotFound)),
+                ("finished" | "no-content", _) => Ok(Ok(PostProcessJob::new(msg.name))),
+                _ => Ok(Err(Error::RecordNotFound)),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Call<ImportTimeshiftRecord> for RecordingManagerStub {
+        async fn call(
+            &self,
+            msg: ImportTimeshiftRecord,
+        ) -> actlet::Result<<ImportTimeshiftRecord as Message>::Reply> {
+            let schedule = RecordingSchedule::new(
+                msg.export.record.program,
+                msg.export.service,
+                msg.options,
+                msg.tags,
+            );
+            let record_id = RecordId::from((msg.export.record.start_time, schedule.program.id));
+            let content_path = make_content_filename(&record_id).into();
+            Ok(Ok(Record::new(
+                record_id,
+                msg.export.record.start_time,
+                &schedule,
+                content_path,
+                "video/MP2T".to_string(),
+            )))
+        }
+    }
+
     #[async_trait]
     impl Call<OpenContent> for RecordingManagerStub {
         async fn call(&self, msg: OpenContent) -> actlet::Result<<OpenContent as Message>::Reply> {
             match msg.id.value() {
                 "recording" | "finished" => {
-                    let range = msg.range.as_ref().map(ContentRange::range).unwrap_or(0..10);
+                    let range = msg
+                        .range
+                        .as_ref()
+                        .map(ContentRange::range)
+                        .unwrap_or(msg.offset as usize..10);
                     let chunk = Bytes::from_static(b"0123456789".get(range).unwrap());
                     let stream: BoxedStream = Box::pin(tokio_stream::once(Ok(chunk)));
                     Ok(Ok((MpegTsStream::new(msg.id.clone(), stream), None)))
@@ -4447,6 +10552,47 @@ pub(crate) mod stub {
         }
     }
 
+    #[async_trait]
+    impl Call<QueryRecordOffset> for RecordingManagerStub {
+        async fn call(
+            &self,
+            msg: QueryRecordOffset,
+        ) -> actlet::Result<<QueryRecordOffset as Message>::Reply> {
+            match msg.id.value() {
+                "recording" | "finished" => Ok(Ok(5)),
+                "no-content" => Ok(Err(Error::NoContent)),
+                _ => Ok(Err(Error::RecordNotFound)),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Call<QueryRecordContentIndex> for RecordingManagerStub {
+        async fn call(
+            &self,
+            msg: QueryRecordContentIndex,
+        ) -> actlet::Result<<QueryRecordContentIndex as Message>::Reply> {
+            match msg.id.value() {
+                "finished" => {
+                    let start_time = Jst::now();
+                    let mut index = ContentIndex::default();
+                    index.push(ContentIndexPoint {
+                        timestamp: start_time,
+                        pos: 0,
+                    });
+                    index.push(ContentIndexPoint {
+                        timestamp: start_time + Duration::seconds(10),
+                        pos: 6,
+                    });
+                    Ok(Ok(Some(index)))
+                }
+                "recording" => Ok(Ok(None)),
+                "no-content" => Ok(Err(Error::NoContent)),
+                _ => Ok(Err(Error::RecordNotFound)),
+            }
+        }
+    }
+
     #[async_trait]
     impl Call<RegisterEmitter> for RecordingManagerStub {
         async fn call(
@@ -4496,6 +10642,157 @@ pub(crate) mod stub {
         }
     }
 
+    #[async_trait]
+    impl Call<QueryRecordingConflicts> for RecordingManagerStub {
+        async fn call(
+            &self,
+            _msg: QueryRecordingConflicts,
+        ) -> actlet::Result<<QueryRecordingConflicts as Message>::Reply> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait]
+    impl Call<QueryRecordingRules> for RecordingManagerStub {
+        async fn call(
+            &self,
+            _msg: QueryRecordingRules,
+        ) -> actlet::Result<<QueryRecordingRules as Message>::Reply> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait]
+    impl Call<QueryRecordingRule> for RecordingManagerStub {
+        async fn call(
+            &self,
+            msg: QueryRecordingRule,
+        ) -> actlet::Result<<QueryRecordingRule as Message>::Reply> {
+            match msg.id.value() {
+                0 => Ok(Err(Error::RuleNotFound)),
+                _ => Ok(Ok(RecordingRule {
+                    id: msg.id,
+                    conditions: Default::default(),
+                    options: recording_options!(1),
+                    tags: Default::default(),
+                    ignored_programs: Default::default(),
+                })),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Call<AddRecordingRule> for RecordingManagerStub {
+        async fn call(
+            &self,
+            msg: AddRecordingRule,
+        ) -> actlet::Result<<AddRecordingRule as Message>::Reply> {
+            Ok(Ok(RecordingRule {
+                id: 1.into(),
+                conditions: msg.conditions,
+                options: msg.options,
+                tags: msg.tags,
+                ignored_programs: Default::default(),
+            }))
+        }
+    }
+
+    #[async_trait]
+    impl Call<UpdateRecordingRule> for RecordingManagerStub {
+        async fn call(
+            &self,
+            msg: UpdateRecordingRule,
+        ) -> actlet::Result<<UpdateRecordingRule as Message>::Reply> {
+            match msg.id.value() {
+                0 => Ok(Err(Error::RuleNotFound)),
+                _ => Ok(Ok(RecordingRule {
+                    id: msg.id,
+                    conditions: msg.conditions,
+                    options: msg.options,
+                    tags: msg.tags,
+                    ignored_programs: Default::default(),
+                })),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Call<RemoveRecordingRule> for RecordingManagerStub {
+        async fn call(
+            &self,
+            msg: RemoveRecordingRule,
+        ) -> actlet::Result<<RemoveRecordingRule as Message>::Reply> {
+            match msg.id.value() {
+                0 => Ok(Err(Error::RuleNotFound)),
+                _ => Ok(Ok(RecordingRule {
+                    id: msg.id,
+                    conditions: Default::default(),
+                    options: recording_options!(1),
+                    tags: Default::default(),
+                    ignored_programs: Default::default(),
+                })),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Call<QueryRecurringSchedules> for RecordingManagerStub {
+        async fn call(
+            &self,
+            _msg: QueryRecurringSchedules,
+        ) -> actlet::Result<<QueryRecurringSchedules as Message>::Reply> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait]
+    impl Call<QueryRecurringSchedule> for RecordingManagerStub {
+        async fn call(
+            &self,
+            msg: QueryRecurringSchedule,
+        ) -> actlet::Result<<QueryRecurringSchedule as Message>::Reply> {
+            match msg.id.value() {
+                0 => Ok(Err(Error::RecurringScheduleNotFound)),
+                _ => Ok(Ok(recurring_schedule!(msg.id, (0, 1), "0 0 0 * * *"))),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Call<AddRecurringSchedule> for RecordingManagerStub {
+        async fn call(
+            &self,
+            msg: AddRecurringSchedule,
+        ) -> actlet::Result<<AddRecurringSchedule as Message>::Reply> {
+            match msg.service_id.sid().value() {
+                0 => Ok(Err(Error::ServiceNotFound)),
+                _ => Ok(Ok(RecurringSchedule {
+                    id: 1.into(),
+                    service_id: msg.service_id,
+                    cron: msg.cron,
+                    duration: msg.duration,
+                    name: msg.name,
+                    options: msg.options,
+                    tags: msg.tags,
+                    expanded_until: None,
+                })),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Call<RemoveRecurringSchedule> for RecordingManagerStub {
+        async fn call(
+            &self,
+            msg: RemoveRecurringSchedule,
+        ) -> actlet::Result<<RemoveRecurringSchedule as Message>::Reply> {
+            match msg.id.value() {
+                0 => Ok(Err(Error::RecurringScheduleNotFound)),
+                _ => Ok(Ok(recurring_schedule!(msg.id, (0, 1), "0 0 0 * * *"))),
+            }
+        }
+    }
+
     #[async_trait]
     impl Call<QueryRecordingRecorder> for RecordingManagerStub {
         async fn call(
@@ -20,6 +20,8 @@ macro_rules! tuner {
             is_free: true,
             is_using: false,
             is_fault: false,
+            health: Default::default(),
+            stats: None,
         }
     };
 }
@@ -144,6 +146,9 @@ macro_rules! recording_options {
             pre_filters: vec![],
             post_filters: vec![],
             log_filter: None,
+            start_margin: None,
+            end_margin: None,
+            follow_series: false,
         }
     };
     ($content_path:expr, $priority:expr) => {
@@ -153,6 +158,9 @@ macro_rules! recording_options {
             pre_filters: vec![],
             post_filters: vec![],
             log_filter: None,
+            start_margin: None,
+            end_margin: None,
+            follow_series: false,
         }
     };
     ($content_path:expr, $priority:expr, $log_filter:expr) => {
@@ -162,6 +170,9 @@ macro_rules! recording_options {
             pre_filters: vec![],
             post_filters: vec![],
             log_filter: Some($log_filter.into()),
+            start_margin: None,
+            end_margin: None,
+            follow_series: false,
         }
     };
 }
@@ -175,6 +186,10 @@ macro_rules! recording_schedule {
             options: $options,
             tags: Default::default(),
             failed_reason: None,
+            rule_id: None,
+            recurring_schedule_id: None,
+            attempts: vec![],
+            conflict: None,
         }
     };
     ($state:expr, $program:expr, $service:expr, $options:expr, $tags:expr) => {
@@ -185,6 +200,48 @@ macro_rules! recording_schedule {
             options: $options,
             tags: $tags,
             failed_reason: None,
+            rule_id: None,
+            recurring_schedule_id: None,
+            attempts: vec![],
+            conflict: None,
+        }
+    };
+}
+
+macro_rules! recording_rule {
+    ($id:expr, title: $title:expr) => {
+        RecordingRule {
+            id: $id.into(),
+            conditions: RecordingRuleConditions {
+                title: Some($title.to_string()),
+                ..Default::default()
+            },
+            options: recording_options!(0),
+            tags: Default::default(),
+            ignored_programs: Default::default(),
+        }
+    };
+}
+
+macro_rules! recurring_schedule {
+    ($id:expr, $service_id:expr, $cron:expr) => {
+        RecurringSchedule {
+            id: $id.into(),
+            service_id: $service_id.into(),
+            cron: $cron.to_string(),
+            duration: None,
+            name: None,
+            options: recording_options!(0),
+            tags: Default::default(),
+            expanded_until: None,
+        }
+    };
+    ($id:expr, $service_id:expr, $cron:expr, $duration:literal) => {
+        RecurringSchedule {
+            duration: Some(
+                chrono::Duration::from_std(humantime::parse_duration($duration).unwrap()).unwrap(),
+            ),
+            ..recurring_schedule!($id, $service_id, $cron)
         }
     };
 }
@@ -210,6 +267,9 @@ macro_rules! recorder {
             pipeline: $pipeline,
             stop_trigger: None,
             content_type: "video/MP2T".to_owned(),
+            log_path: None,
+            preempted: None,
+            stop_timer: None,
         }
     };
 }
@@ -239,6 +299,8 @@ macro_rules! record {
             recording_duration: $end_time.map(|t| t - $start_time),
             content_path: format!("{}.m2ts", $id).into(),
             content_type: "video/MP2T".to_owned(),
+            post_process_jobs: vec![],
+            attempts: vec![],
         }
     };
 }
@@ -0,0 +1,766 @@
+use std::collections::VecDeque;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+use serde::Serialize;
+
+use super::models::*;
+use crate::epg::EpgService;
+use crate::error::Error;
+use crate::file_util;
+use crate::models::TimeshiftRecordId;
+
+// The journal is compacted when its size exceeds the size of the last snapshot or this value.
+const MIN_JOURNAL_SIZE: u64 = 64 * 1024;
+
+/// Formats of `config.timeshift.recorders[].data-file`.
+///
+/// The format is selected by the extension of the file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimeshiftDataFormat {
+    /// `*.json`
+    ///
+    /// The whole data is rewritten every time it's saved.
+    Json,
+
+    /// `*.journal`
+    ///
+    /// Each line is a JSON array of operations and changes are appended to the file.  The file
+    /// is compacted into a snapshot when the journal grows.
+    Journal,
+}
+
+impl TimeshiftDataFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "journal" => Some(Self::Journal),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Journal => "journal",
+        }
+    }
+}
+
+/// Loads timeshift recorder data from a data-file.
+///
+/// `None` is returned if the file doesn't exist or is empty.
+pub fn load_recorder_data(path: &Path) -> Result<Option<TimeshiftRecorderData>, Error> {
+    let format = match TimeshiftDataFormat::from_path(path) {
+        Some(format) => format,
+        None => return Err(Error::InvalidPath("Unsupported data-file format")),
+    };
+
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let file = std::fs::File::open(path)?;
+    // If the file is empty, serde_json::from_reader() always causes a parse
+    // error even though serde_json reads no data actually.
+    if file.metadata()?.len() == 0 {
+        return Ok(None);
+    }
+
+    let reader = BufReader::new(file);
+    match format {
+        TimeshiftDataFormat::Json => Ok(Some(serde_json::from_reader(reader)?)),
+        TimeshiftDataFormat::Journal => load_journal(path, reader),
+    }
+}
+
+/// Saves timeshift recorder data to a data-file in the format selected by its extension.
+///
+/// The whole data is written to the file.
+pub fn save_recorder_data(path: &Path, data: &TimeshiftRecorderData) -> bool {
+    TimeshiftDataWriter::new(path.to_owned()).write(data.for_save())
+}
+
+fn load_journal<R: BufRead>(
+    path: &Path,
+    reader: R,
+) -> Result<Option<TimeshiftRecorderData>, Error> {
+    let mut data: Option<TimeshiftRecorderData> = None;
+    let mut lines = reader.split(b'\n').peekable();
+    while let Some(line) = lines.next() {
+        let line = line?;
+        let ops: Vec<JournalOp> = match serde_json::from_slice(&line) {
+            Ok(ops) => ops,
+            Err(err) if lines.peek().is_none() => {
+                // The last line may be broken when the process has been killed while writing it.
+                // The operations in the line are discarded as a whole.
+                tracing::warn!(%err, ?path, "Broken operations at the end of the journal, skip");
+                break;
+            }
+            Err(err) => return Err(err.into()),
+        };
+        for op in ops.into_iter() {
+            match (op, data.as_mut()) {
+                (
+                    JournalOp::Header {
+                        service,
+                        chunk_size,
+                        max_chunks,
+                    },
+                    _,
+                ) => {
+                    data = Some(TimeshiftRecorderData {
+                        service,
+                        chunk_size,
+                        max_chunks,
+                        records: Default::default(),
+                        points: Default::default(),
+                        gaps: Default::default(),
+                        paused: false,
+                    });
+                }
+                (JournalOp::Record(record), Some(data)) => {
+                    data.records.insert(record.id, *record);
+                }
+                (JournalOp::RemoveRecord(id), Some(data)) => {
+                    data.records.shift_remove(&id);
+                }
+                (JournalOp::Point(point), Some(data)) => {
+                    data.points.push_back(point);
+                }
+                (JournalOp::DropPoints { front, back }, Some(data)) => {
+                    if front + back > data.points.len() {
+                        return Err(anyhow::anyhow!("Too many points dropped").into());
+                    }
+                    data.points.truncate(data.points.len() - back);
+                    data.points.drain(0..front);
+                }
+                (JournalOp::Gaps(gaps), Some(data)) => {
+                    data.gaps = gaps;
+                }
+                (JournalOp::Paused(paused), Some(data)) => {
+                    data.paused = paused;
+                }
+                (_, None) => return Err(anyhow::anyhow!("No header in the journal").into()),
+            }
+        }
+    }
+    Ok(data)
+}
+
+#[derive(Serialize)]
+pub(crate) struct TimeshiftRecorderDataForSave<'a> {
+    pub(crate) service: &'a EpgService,
+    pub(crate) chunk_size: usize,
+    pub(crate) max_chunks: usize,
+    pub(crate) records: &'a IndexMap<TimeshiftRecordId, TimeshiftRecord>,
+    pub(crate) points: &'a VecDeque<TimeshiftPoint>,
+    #[serde(skip_serializing_if = "VecDeque::is_empty")]
+    pub(crate) gaps: &'a VecDeque<TimeshiftGap>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) paused: bool,
+}
+
+impl TimeshiftRecorderData {
+    fn for_save(&self) -> TimeshiftRecorderDataForSave<'_> {
+        TimeshiftRecorderDataForSave {
+            service: &self.service,
+            chunk_size: self.chunk_size,
+            max_chunks: self.max_chunks,
+            records: &self.records,
+            points: &self.points,
+            gaps: &self.gaps,
+            paused: self.paused,
+        }
+    }
+}
+
+// Writes timeshift recorder data to a data-file.
+//
+// In the journal format, only differences from the previously written data are appended to the
+// file.  A snapshot of the whole data is written at first and when the journal grows.
+pub(crate) struct TimeshiftDataWriter {
+    path: PathBuf,
+    format: Option<TimeshiftDataFormat>,
+    journal: Option<JournalState>,
+}
+
+impl TimeshiftDataWriter {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        let format = TimeshiftDataFormat::from_path(&path);
+        TimeshiftDataWriter {
+            path,
+            format,
+            journal: None,
+        }
+    }
+
+    pub(crate) fn write(&mut self, data: TimeshiftRecorderDataForSave) -> bool {
+        match self.format {
+            Some(TimeshiftDataFormat::Json) => file_util::save_json(data, &self.path),
+            Some(TimeshiftDataFormat::Journal) => self.write_journal(data),
+            None => {
+                tracing::error!(path = ?self.path, "Unsupported data-file format");
+                false
+            }
+        }
+    }
+
+    fn write_journal(&mut self, data: TimeshiftRecorderDataForSave) -> bool {
+        if let Some(mut journal) = self.journal.take() {
+            match journal.update(&data) {
+                Some(line) if line.is_empty() => {
+                    self.journal = Some(journal);
+                    return true;
+                }
+                Some(line) if journal.has_room_for(&line) && Self::append(&self.path, &line) => {
+                    journal.journal_size += line.len() as u64;
+                    self.journal = Some(journal);
+                    return true;
+                }
+                // The data-file may end with a broken line if the append failed.  It's replaced
+                // with a snapshot.
+                _ => (),
+            }
+        }
+        self.write_snapshot(data)
+    }
+
+    fn write_snapshot(&mut self, data: TimeshiftRecorderDataForSave) -> bool {
+        let mut journal = JournalState::default();
+        let line = journal.snapshot(&data);
+        if !file_util::save_data(&line, &self.path) {
+            return false;
+        }
+        journal.snapshot_size = line.len() as u64;
+        self.journal = Some(journal);
+        true
+    }
+
+    fn append(path: &Path, line: &[u8]) -> bool {
+        let result = std::fs::OpenOptions::new()
+            .append(true)
+            .open(path)
+            .and_then(|mut file| {
+                file.write_all(line)?;
+                file.sync_data()
+            });
+        match result {
+            Ok(_) => {
+                tracing::debug!(nwritten = line.len(), ?path, "Appended data to the journal");
+                true
+            }
+            Err(err) => {
+                tracing::error!(%err, ?path, "Failed to append data to the journal");
+                false
+            }
+        }
+    }
+}
+
+// The data written to the journal so far.
+#[derive(Default)]
+struct JournalState {
+    header: Vec<u8>,
+    records: IndexMap<TimeshiftRecordId, Vec<u8>>,
+    points: VecDeque<TimeshiftPoint>,
+    gaps: Vec<u8>,
+    paused: bool,
+    snapshot_size: u64,
+    journal_size: u64,
+}
+
+impl JournalState {
+    fn has_room_for(&self, line: &[u8]) -> bool {
+        self.journal_size + line.len() as u64 <= self.snapshot_size.max(MIN_JOURNAL_SIZE)
+    }
+
+    // Returns a line containing operations to rebuild the whole data.
+    fn snapshot(&mut self, data: &TimeshiftRecorderDataForSave) -> Vec<u8> {
+        self.header = Self::serialize_header(data);
+        self.records = data
+            .records
+            .iter()
+            .map(|(id, record)| (*id, Self::serialize(JournalOpRef::Record(record))))
+            .collect();
+        self.points = data.points.clone();
+        self.gaps = Self::serialize(JournalOpRef::Gaps(data.gaps));
+        self.paused = data.paused;
+        let ops = std::iter::once(&self.header)
+            .chain(self.records.values())
+            .cloned()
+            .chain(
+                self.points
+                    .iter()
+                    .map(|point| Self::serialize(JournalOpRef::Point(point))),
+            )
+            .chain((!data.gaps.is_empty()).then(|| self.gaps.clone()))
+            .chain(
+                data.paused
+                    .then(|| Self::serialize(JournalOpRef::Paused(true))),
+            );
+        Self::make_line(ops)
+    }
+
+    // Returns a line containing operations to update the previous data to `data`.
+    //
+    // An empty line is returned if nothing changed.  `None` is returned if the changes cannot be
+    // represented by the operations.
+    fn update(&mut self, data: &TimeshiftRecorderDataForSave) -> Option<Vec<u8>> {
+        if Self::serialize_header(data) != self.header {
+            return None;
+        }
+
+        let mut ops = vec![];
+
+        // Records are only appended or removed, and updated in place.
+        let removed: Vec<TimeshiftRecordId> = self
+            .records
+            .keys()
+            .filter(|id| !data.records.contains_key(*id))
+            .cloned()
+            .collect();
+        let kept = self.records.len() - removed.len();
+        let in_order = self
+            .records
+            .keys()
+            .filter(|id| data.records.contains_key(*id))
+            .zip(data.records.keys())
+            .all(|(prev, id)| prev == id);
+        if !in_order {
+            return None;
+        }
+        for id in removed.into_iter() {
+            self.records.shift_remove(&id);
+            ops.push(Self::serialize(JournalOpRef::RemoveRecord(id)));
+        }
+        for (i, (id, record)) in data.records.iter().enumerate() {
+            let op = Self::serialize(JournalOpRef::Record(record));
+            if i < kept {
+                if self.records[i] == op {
+                    continue;
+                }
+                self.records[i] = op.clone();
+            } else {
+                self.records.insert(*id, op.clone());
+            }
+            ops.push(op);
+        }
+
+        // Points are popped from the both ends and pushed to the back.
+        let front = data
+            .points
+            .front()
+            .and_then(|first| self.points.iter().position(|point| point == first))
+            .unwrap_or(self.points.len());
+        let common = self
+            .points
+            .iter()
+            .skip(front)
+            .zip(data.points.iter())
+            .take_while(|(prev, point)| prev == point)
+            .count();
+        let back = self.points.len() - front - common;
+        if front > 0 || back > 0 {
+            ops.push(Self::serialize(JournalOpRef::DropPoints { front, back }));
+        }
+        for point in data.points.iter().skip(common) {
+            ops.push(Self::serialize(JournalOpRef::Point(point)));
+        }
+        self.points = data.points.clone();
+
+        // Gaps are rarely changed.  So, the whole gaps are written when changed.
+        let gaps = Self::serialize(JournalOpRef::Gaps(data.gaps));
+        if gaps != self.gaps {
+            self.gaps = gaps.clone();
+            ops.push(gaps);
+        }
+
+        if data.paused != self.paused {
+            self.paused = data.paused;
+            ops.push(Self::serialize(JournalOpRef::Paused(data.paused)));
+        }
+
+        if ops.is_empty() {
+            Some(vec![])
+        } else {
+            Some(Self::make_line(ops.into_iter()))
+        }
+    }
+
+    fn serialize_header(data: &TimeshiftRecorderDataForSave) -> Vec<u8> {
+        Self::serialize(JournalOpRef::Header {
+            service: data.service,
+            chunk_size: data.chunk_size,
+            max_chunks: data.max_chunks,
+        })
+    }
+
+    fn serialize(op: JournalOpRef) -> Vec<u8> {
+        // Never fails.
+        serde_json::to_vec(&op).unwrap()
+    }
+
+    fn make_line<I>(ops: I) -> Vec<u8>
+    where
+        I: Iterator<Item = Vec<u8>>,
+    {
+        let mut line = vec![b'['];
+        for (i, op) in ops.enumerate() {
+            if i > 0 {
+                line.push(b',');
+            }
+            line.extend(op);
+        }
+        line.extend(b"]\n");
+        line
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum JournalOp {
+    Header {
+        service: EpgService,
+        chunk_size: usize,
+        max_chunks: usize,
+    },
+    Record(Box<TimeshiftRecord>),
+    RemoveRecord(TimeshiftRecordId),
+    Point(TimeshiftPoint),
+    DropPoints {
+        front: usize,
+        back: usize,
+    },
+    Gaps(VecDeque<TimeshiftGap>),
+    Paused(bool),
+}
+
+// Serialized in the same format as `JournalOp`.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum JournalOpRef<'a> {
+    Header {
+        service: &'a EpgService,
+        chunk_size: usize,
+        max_chunks: usize,
+    },
+    Record(&'a TimeshiftRecord),
+    RemoveRecord(TimeshiftRecordId),
+    Point(&'a TimeshiftPoint),
+    DropPoints {
+        front: usize,
+        back: usize,
+    },
+    Gaps(&'a VecDeque<TimeshiftGap>),
+    Paused(bool),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::epg::EpgProgram;
+    use crate::models::ProgramId;
+    use chrono::Duration;
+    use tempfile::TempDir;
+    use test_log::test;
+
+    struct TestData {
+        service: EpgService,
+        records: IndexMap<TimeshiftRecordId, TimeshiftRecord>,
+        points: VecDeque<TimeshiftPoint>,
+        gaps: VecDeque<TimeshiftGap>,
+        paused: bool,
+    }
+
+    impl TestData {
+        fn new() -> Self {
+            TestData {
+                service: service!(1, "Service", channel_gr!("ch", "ch")),
+                records: Default::default(),
+                points: Default::default(),
+                gaps: Default::default(),
+                paused: false,
+            }
+        }
+
+        fn for_save(&self) -> TimeshiftRecorderDataForSave<'_> {
+            TimeshiftRecorderDataForSave {
+                service: &self.service,
+                chunk_size: 10,
+                max_chunks: 100,
+                records: &self.records,
+                points: &self.points,
+                gaps: &self.gaps,
+                paused: self.paused,
+            }
+        }
+
+        fn add_record(&mut self, id: u32, start: u64, end: u64) {
+            let record = TimeshiftRecord {
+                id: id.into(),
+                program: EpgProgram::new(ProgramId::from((0, 1, id as u16))),
+                start: point(start),
+                end: point(end),
+                recording: false,
+                pinned: false,
+            };
+            self.records.insert(record.id, record);
+        }
+
+        fn assert_loaded(&self, path: &Path) {
+            let loaded = load_recorder_data(path).unwrap().unwrap();
+            assert_eq!(
+                serde_json::to_value(loaded.for_save()).unwrap(),
+                serde_json::to_value(self.for_save()).unwrap()
+            );
+        }
+    }
+
+    fn point(i: u64) -> TimeshiftPoint {
+        TimeshiftPoint {
+            timestamp: jst!("2021-01-01T00:00:00+09:00") + Duration::minutes(i as i64),
+            pos: i * 10,
+        }
+    }
+
+    fn count_lines(path: &Path) -> usize {
+        std::fs::read_to_string(path).unwrap().lines().count()
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            TimeshiftDataFormat::from_path(Path::new("/data.json")),
+            Some(TimeshiftDataFormat::Json)
+        );
+        assert_eq!(
+            TimeshiftDataFormat::from_path(Path::new("/data.journal")),
+            Some(TimeshiftDataFormat::Journal)
+        );
+        assert_eq!(TimeshiftDataFormat::from_path(Path::new("/data.bin")), None);
+        assert_eq!(TimeshiftDataFormat::from_path(Path::new("/data")), None);
+    }
+
+    #[test]
+    fn test_load_recorder_data_no_file() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let path = temp_dir.path().join("data.journal");
+        assert!(matches!(load_recorder_data(&path), Ok(None)));
+
+        std::fs::write(&path, b"").unwrap();
+        assert!(matches!(load_recorder_data(&path), Ok(None)));
+
+        let path = temp_dir.path().join("data.bin");
+        assert!(matches!(
+            load_recorder_data(&path),
+            Err(Error::InvalidPath(_))
+        ));
+    }
+
+    #[test]
+    fn test_json() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.json");
+
+        let mut data = TestData::new();
+        data.add_record(1, 0, 2);
+        data.points = (0..3).map(point).collect();
+
+        let mut writer = TimeshiftDataWriter::new(path.clone());
+        assert!(writer.write(data.for_save()));
+        data.assert_loaded(&path);
+
+        data.points.push_back(point(3));
+        assert!(writer.write(data.for_save()));
+        data.assert_loaded(&path);
+        assert_eq!(count_lines(&path), 1);
+    }
+
+    #[test]
+    fn test_journal() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.journal");
+
+        let mut data = TestData::new();
+        let mut writer = TimeshiftDataWriter::new(path.clone());
+
+        // A snapshot is written at first.
+        assert!(writer.write(data.for_save()));
+        data.assert_loaded(&path);
+        assert_eq!(count_lines(&path), 1);
+
+        // Nothing is appended if nothing changed.
+        assert!(writer.write(data.for_save()));
+        assert_eq!(count_lines(&path), 1);
+
+        data.add_record(1, 0, 1);
+        data.points = (0..2).map(point).collect();
+        assert!(writer.write(data.for_save()));
+        data.assert_loaded(&path);
+        assert_eq!(count_lines(&path), 2);
+
+        // Update a record in place.
+        data.records[0].end = point(2);
+        data.add_record(2, 2, 3);
+        data.points.push_back(point(2));
+        assert!(writer.write(data.for_save()));
+        data.assert_loaded(&path);
+        assert_eq!(count_lines(&path), 3);
+
+        // Drop points from the both ends.
+        data.records.shift_remove_index(0);
+        data.points.pop_front();
+        data.points.pop_back();
+        data.points.push_back(point(4));
+        assert!(writer.write(data.for_save()));
+        data.assert_loaded(&path);
+        assert_eq!(count_lines(&path), 4);
+
+        // A snapshot is written if the service changes.
+        data.service.name = "Changed".to_string();
+        assert!(writer.write(data.for_save()));
+        data.assert_loaded(&path);
+        assert_eq!(count_lines(&path), 1);
+    }
+
+    #[test]
+    fn test_journal_gaps() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.journal");
+
+        let mut data = TestData::new();
+        data.add_record(1, 0, 1);
+        data.points = (0..2).map(point).collect();
+        let mut writer = TimeshiftDataWriter::new(path.clone());
+        assert!(writer.write(data.for_save()));
+
+        data.gaps.push_back(TimeshiftGap {
+            start_time: point(1).timestamp,
+            end_time: None,
+            reason: TimeshiftGapReason::PipelineStopped,
+        });
+        assert!(writer.write(data.for_save()));
+        data.assert_loaded(&path);
+        assert_eq!(count_lines(&path), 2);
+
+        data.gaps[0].end_time = Some(point(2).timestamp);
+        assert!(writer.write(data.for_save()));
+        data.assert_loaded(&path);
+        assert_eq!(count_lines(&path), 3);
+
+        // Nothing is appended if the gaps are not changed.
+        assert!(writer.write(data.for_save()));
+        assert_eq!(count_lines(&path), 3);
+
+        data.gaps.pop_front();
+        assert!(writer.write(data.for_save()));
+        data.assert_loaded(&path);
+        assert_eq!(count_lines(&path), 4);
+    }
+
+    #[test]
+    fn test_journal_paused() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.journal");
+
+        let mut data = TestData::new();
+        data.add_record(1, 0, 1);
+        data.points = (0..2).map(point).collect();
+        let mut writer = TimeshiftDataWriter::new(path.clone());
+        assert!(writer.write(data.for_save()));
+        assert!(!load_recorder_data(&path).unwrap().unwrap().paused);
+
+        data.paused = true;
+        assert!(writer.write(data.for_save()));
+        assert!(load_recorder_data(&path).unwrap().unwrap().paused);
+        assert_eq!(count_lines(&path), 2);
+
+        // Nothing is appended if the paused state is not changed.
+        assert!(writer.write(data.for_save()));
+        assert_eq!(count_lines(&path), 2);
+
+        // A snapshot keeps the paused state.
+        let mut writer = TimeshiftDataWriter::new(path.clone());
+        assert!(writer.write(data.for_save()));
+        assert!(load_recorder_data(&path).unwrap().unwrap().paused);
+        assert_eq!(count_lines(&path), 1);
+
+        data.paused = false;
+        assert!(writer.write(data.for_save()));
+        assert!(!load_recorder_data(&path).unwrap().unwrap().paused);
+        assert_eq!(count_lines(&path), 2);
+    }
+
+    #[test]
+    fn test_journal_compaction() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.journal");
+
+        let mut data = TestData::new();
+        let mut writer = TimeshiftDataWriter::new(path.clone());
+        for i in 0..5000 {
+            if data.points.len() == 100 {
+                data.points.pop_front();
+            }
+            data.points.push_back(point(i));
+            assert!(writer.write(data.for_save()));
+        }
+        data.assert_loaded(&path);
+        assert!(count_lines(&path) < 5000);
+    }
+
+    #[test]
+    fn test_journal_broken_last_line() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.journal");
+
+        let mut data = TestData::new();
+        data.add_record(1, 0, 1);
+        data.points = (0..2).map(point).collect();
+        let mut writer = TimeshiftDataWriter::new(path.clone());
+        assert!(writer.write(data.for_save()));
+
+        // The process was killed while writing the line.
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(br#"[{"point":{"timestamp":"#).unwrap();
+        data.assert_loaded(&path);
+
+        // A broken line in the middle of the journal is an error.
+        file.write_all(b"\n[]\n").unwrap();
+        assert!(load_recorder_data(&path).is_err());
+
+        // A new writer always writes a snapshot at first.
+        let mut writer = TimeshiftDataWriter::new(path.clone());
+        data.points.push_back(point(2));
+        assert!(writer.write(data.for_save()));
+        data.assert_loaded(&path);
+        assert_eq!(count_lines(&path), 1);
+    }
+
+    #[test]
+    fn test_save_recorder_data() {
+        let temp_dir = TempDir::new().unwrap();
+        let json_path = temp_dir.path().join("data.json");
+        let journal_path = temp_dir.path().join("data.journal");
+
+        let mut data = TestData::new();
+        data.add_record(1, 0, 1);
+        data.points = (0..2).map(point).collect();
+        let mut writer = TimeshiftDataWriter::new(json_path.clone());
+        assert!(writer.write(data.for_save()));
+
+        let loaded = load_recorder_data(&json_path).unwrap().unwrap();
+        assert!(save_recorder_data(&journal_path, &loaded));
+        data.assert_loaded(&journal_path);
+    }
+}
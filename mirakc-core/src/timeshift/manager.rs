@@ -11,6 +11,7 @@ use crate::config::Config;
 use crate::epg;
 use crate::error::Error;
 use crate::models::*;
+use crate::recording::ImportTimeshiftRecord;
 use crate::tuner::StartStreaming;
 use crate::tuner::StopStreaming;
 
@@ -18,16 +19,24 @@ pub struct TimeshiftManager<T, E> {
     config: Arc<Config>,
     tuner_manager: T,
     epg: E,
+    // Used for exporting pinned records going to be overwritten.
+    record_importer: Option<Caller<ImportTimeshiftRecord>>,
     recorders: IndexMap<String, RecorderHolder<T>>,
     event_emitters: EmitterRegistry<TimeshiftEvent>,
 }
 
 impl<T, E> TimeshiftManager<T, E> {
-    pub fn new(config: Arc<Config>, tuner_manager: T, epg: E) -> Self {
+    pub fn new(
+        config: Arc<Config>,
+        tuner_manager: T,
+        epg: E,
+        record_importer: Option<Caller<ImportTimeshiftRecord>>,
+    ) -> Self {
         TimeshiftManager {
             config,
             tuner_manager,
             epg,
+            record_importer,
             recorders: IndexMap::new(),
             event_emitters: Default::default(),
         }
@@ -72,6 +81,7 @@ where
                     self.config.clone(),
                     self.tuner_manager.clone(),
                     ctx.emitter(),
+                    self.record_importer.clone(),
                 ))
                 .await;
             let holder = RecorderHolder::new(addr);
@@ -267,8 +277,14 @@ macro_rules! impl_proxy_handler {
 impl_proxy_handler!(QueryTimeshiftRecorder);
 impl_proxy_handler!(QueryTimeshiftRecords);
 impl_proxy_handler!(QueryTimeshiftRecord);
+impl_proxy_handler!(QueryTimeshiftRecordSegments);
+impl_proxy_handler!(QueryTimeshiftRecordOffset);
 impl_proxy_handler!(CreateTimeshiftLiveStreamSource);
 impl_proxy_handler!(CreateTimeshiftRecordStreamSource);
+impl_proxy_handler!(ExportTimeshiftRecord);
+impl_proxy_handler!(PinTimeshiftRecord);
+impl_proxy_handler!(StopTimeshiftRecorder);
+impl_proxy_handler!(StartTimeshiftRecorder);
 
 // health check
 
@@ -319,6 +335,7 @@ where
                         self.config.clone(),
                         self.tuner_manager.clone(),
                         ctx.emitter(),
+                        self.record_importer.clone(),
                     ))
                     .await;
                 holder.addr = addr;
@@ -406,7 +423,7 @@ where
                     holder.current_record_id = Some(record_id);
                 }
             }
-            TimeshiftEvent::RecordUpdated { .. } => {
+            TimeshiftEvent::RecordUpdated { .. } | TimeshiftEvent::Gap { .. } => {
                 // Nothing to do.
             }
             TimeshiftEvent::RecordEnded { ref recorder, .. } => {
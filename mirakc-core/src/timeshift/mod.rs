@@ -2,6 +2,7 @@
 #[macro_use]
 mod vecdeque_lit;
 
+mod data;
 mod filereader;
 mod manager;
 mod models;
@@ -20,6 +21,9 @@ use crate::error::Error;
 use crate::models::ContentRange;
 use crate::models::TimeshiftRecordId;
 
+pub use data::TimeshiftDataFormat;
+pub use data::load_recorder_data;
+pub use data::save_recorder_data;
 pub use manager::TimeshiftManager;
 pub use models::*;
 
@@ -68,11 +72,28 @@ pub struct QueryTimeshiftRecord {
     pub record_id: TimeshiftRecordId,
 }
 
+#[derive(Message)]
+#[reply(Result<Vec<TimeshiftRecordSegment>, Error>)]
+pub struct QueryTimeshiftRecordSegments {
+    pub recorder: TimeshiftRecorderQuery,
+    pub record_id: TimeshiftRecordId,
+}
+
+// Maps a time within a record to the byte offset from the beginning of the record.
+#[derive(Message)]
+#[reply(Result<u64, Error>)]
+pub struct QueryTimeshiftRecordOffset {
+    pub recorder: TimeshiftRecorderQuery,
+    pub record_id: TimeshiftRecordId,
+    pub time: DateTime<Jst>,
+}
+
 #[derive(Message)]
 #[reply(Result<TimeshiftLiveStreamSource, Error>)]
 pub struct CreateTimeshiftLiveStreamSource {
     pub recorder: TimeshiftRecorderQuery,
     pub record_id: Option<TimeshiftRecordId>,
+    pub start_time: Option<DateTime<Jst>>,
 }
 
 #[derive(Message)]
@@ -83,6 +104,35 @@ pub struct CreateTimeshiftRecordStreamSource {
     pub range: Option<ContentRange>,
 }
 
+#[derive(Message)]
+#[reply(Result<TimeshiftRecordExport, Error>)]
+pub struct ExportTimeshiftRecord {
+    pub recorder: TimeshiftRecorderQuery,
+    pub record_id: TimeshiftRecordId,
+}
+
+#[derive(Message)]
+#[reply(Result<TimeshiftRecordModel, Error>)]
+pub struct PinTimeshiftRecord {
+    pub recorder: TimeshiftRecorderQuery,
+    pub record_id: TimeshiftRecordId,
+    pub pinned: bool,
+}
+
+// Stops recording and releases the tuner until `StartTimeshiftRecorder` is sent.
+#[derive(Message)]
+#[reply(Result<TimeshiftRecorderModel, Error>)]
+pub struct StopTimeshiftRecorder {
+    pub recorder: TimeshiftRecorderQuery,
+}
+
+// Resumes recording stopped by `StopTimeshiftRecorder`.
+#[derive(Message)]
+#[reply(Result<TimeshiftRecorderModel, Error>)]
+pub struct StartTimeshiftRecorder {
+    pub recorder: TimeshiftRecorderQuery,
+}
+
 #[derive(Clone, Message)]
 pub enum TimeshiftEvent {
     Timeline {
@@ -109,6 +159,11 @@ pub enum TimeshiftEvent {
         recorder: String,
         record_id: TimeshiftRecordId,
     },
+    // Emitted when the recording restarts after a gap.
+    Gap {
+        recorder: String,
+        gap: TimeshiftGap,
+    },
 }
 
 // private messages
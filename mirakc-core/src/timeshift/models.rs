@@ -5,6 +5,7 @@ use chrono::DateTime;
 use chrono::Duration;
 use chrono_jst::Jst;
 use chrono_jst::serde::ts_milliseconds;
+use chrono_jst::serde::ts_milliseconds_option;
 use indexmap::IndexMap;
 use serde::Deserialize;
 use serde::Serialize;
@@ -12,6 +13,7 @@ use tokio::io::AsyncReadExt;
 use tokio::io::Take;
 use tokio::sync::oneshot;
 use tokio_util::io::ReaderStream;
+use utoipa::ToSchema;
 
 use super::filereader::TimeshiftFileReader;
 use crate::command_util::CommandPipelineProcessModel;
@@ -22,6 +24,7 @@ use crate::error::Error;
 use crate::models::ContentRange;
 use crate::models::TimeshiftRecordId;
 use crate::mpeg_ts_stream::MpegTsStream;
+use crate::mpeg_ts_stream::TS_PACKET_SIZE;
 
 #[derive(Debug)]
 pub struct TimeshiftRecorderModel {
@@ -34,7 +37,9 @@ pub struct TimeshiftRecorderModel {
     pub num_records: usize,
     pub pipeline: Vec<CommandPipelineProcessModel>,
     pub recording: bool,
+    pub paused: bool,
     pub current_record_id: Option<TimeshiftRecordId>,
+    pub gaps: Vec<TimeshiftGap>,
 }
 
 pub struct TimeshiftRecordModel {
@@ -44,6 +49,26 @@ pub struct TimeshiftRecordModel {
     pub end_time: DateTime<Jst>,
     pub size: u64,
     pub recording: bool,
+    pub pinned: bool,
+    // Gaps which interrupted the recording of the program.
+    pub gaps: Vec<TimeshiftGap>,
+}
+
+// A part of a timeshift record between adjacent chunk points.
+pub struct TimeshiftRecordSegment {
+    pub start_time: DateTime<Jst>,
+    pub duration: Duration,
+    // The position relative to the beginning of the record.
+    pub offset: u64,
+    pub size: u64,
+}
+
+// A snapshot of a timeshift record used for exporting it as a record of the
+// recording.
+pub struct TimeshiftRecordExport {
+    pub service: EpgService,
+    pub record: TimeshiftRecordModel,
+    pub source: TimeshiftRecordStreamSource,
 }
 
 // 32 KiB, large enough for 10 ms buffering.
@@ -153,7 +178,7 @@ impl Drop for TimeshiftStreamStopTrigger {
     }
 }
 
-#[derive(Clone, Deserialize, Serialize)]
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
 pub struct TimeshiftPoint {
     #[serde(with = "ts_milliseconds")]
     pub timestamp: DateTime<Jst>,
@@ -166,6 +191,27 @@ impl std::fmt::Display for TimeshiftPoint {
     }
 }
 
+// Maps a time to a position in the timeshift file by linear interpolation between adjacent points.
+//
+// `points` must be sorted in chronological order.  The position is aligned to a TS packet boundary
+// relative to the preceding point.  `None` is returned if the time is out of the points.
+pub(super) fn find_position<'a, I>(points: I, file_size: u64, time: DateTime<Jst>) -> Option<u64>
+where
+    I: IntoIterator<Item = &'a TimeshiftPoint>,
+{
+    let points: Vec<&TimeshiftPoint> = points.into_iter().collect();
+    points
+        .windows(2)
+        .find(|pair| pair[0].timestamp <= time && time < pair[1].timestamp)
+        .map(|pair| {
+            let size = (pair[1].pos + file_size - pair[0].pos) % file_size;
+            let elapsed = (time - pair[0].timestamp).num_milliseconds() as u128;
+            let duration = (pair[1].timestamp - pair[0].timestamp).num_milliseconds() as u128;
+            let delta = (size as u128 * elapsed / duration) as u64;
+            (pair[0].pos + delta - delta % TS_PACKET_SIZE) % file_size
+        })
+}
+
 #[derive(Clone, Deserialize, Serialize)]
 pub struct TimeshiftRecord {
     pub id: TimeshiftRecordId,
@@ -174,6 +220,10 @@ pub struct TimeshiftRecord {
     pub end: TimeshiftPoint,
     #[serde(skip)]
     pub recording: bool,
+    // A pinned record is never purged nor cropped.  The recorder exports the pinned record before
+    // overwriting chunks of it, or stops the recording if it cannot be exported.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
 }
 
 impl TimeshiftRecord {
@@ -184,6 +234,7 @@ impl TimeshiftRecord {
             start: point.clone(),
             end: point.clone(),
             recording: true,
+            pinned: false,
         }
     }
 
@@ -218,7 +269,11 @@ impl TimeshiftRecord {
         })
     }
 
-    pub(super) fn get_model(&self, config: &TimeshiftRecorderConfig) -> TimeshiftRecordModel {
+    pub(super) fn get_model(
+        &self,
+        config: &TimeshiftRecorderConfig,
+        gaps: &VecDeque<TimeshiftGap>,
+    ) -> TimeshiftRecordModel {
         TimeshiftRecordModel {
             id: self.id,
             program: self.program.clone(),
@@ -226,9 +281,75 @@ impl TimeshiftRecord {
             end_time: self.end.timestamp,
             size: self.get_size(config.max_file_size()),
             recording: self.recording,
+            pinned: self.pinned,
+            gaps: self.get_gaps(gaps),
         }
     }
 
+    // Collects gaps overlapping the broadcast time of the program.
+    //
+    // The recording of a program interrupted by a gap is split into multiple records.  Each of
+    // them has the gap because the broadcast time of the program contains it.
+    pub(super) fn get_gaps(&self, gaps: &VecDeque<TimeshiftGap>) -> Vec<TimeshiftGap> {
+        let start_time = match self.program.start_at {
+            Some(start_at) => start_at.min(self.start.timestamp),
+            None => self.start.timestamp,
+        };
+        let end_time = match self.program.end_at() {
+            Some(end_at) => end_at.max(self.end.timestamp),
+            None => self.end.timestamp,
+        };
+        gaps.iter()
+            .filter(|gap| gap.start_time < end_time)
+            .filter(|gap| {
+                gap.end_time
+                    .is_none_or(|gap_end_time| start_time < gap_end_time)
+            })
+            .cloned()
+            .collect()
+    }
+
+    // Splits the record into segments at chunk points within the record.
+    pub(super) fn get_segments(
+        &self,
+        points: &VecDeque<TimeshiftPoint>,
+        file_size: u64,
+    ) -> Vec<TimeshiftRecordSegment> {
+        self.get_boundaries(points)
+            .windows(2)
+            .map(|pair| TimeshiftRecordSegment {
+                start_time: pair[0].timestamp,
+                duration: pair[1].timestamp - pair[0].timestamp,
+                offset: (pair[0].pos + file_size - self.start.pos) % file_size,
+                size: (pair[1].pos + file_size - pair[0].pos) % file_size,
+            })
+            .filter(|segment| segment.size > 0)
+            .collect()
+    }
+
+    // Computes the offset from the beginning of the record at a specified time.
+    pub(super) fn get_offset(
+        &self,
+        points: &VecDeque<TimeshiftPoint>,
+        file_size: u64,
+        time: DateTime<Jst>,
+    ) -> Option<u64> {
+        find_position(self.get_boundaries(points), file_size, time)
+            .map(|pos| (pos + file_size - self.start.pos) % file_size)
+    }
+
+    pub(super) fn get_boundaries<'a>(
+        &'a self,
+        points: &'a VecDeque<TimeshiftPoint>,
+    ) -> Vec<&'a TimeshiftPoint> {
+        std::iter::once(&self.start)
+            .chain(points.iter().filter(|point| {
+                self.start.timestamp < point.timestamp && point.timestamp < self.end.timestamp
+            }))
+            .chain(std::iter::once(&self.end))
+            .collect()
+    }
+
     pub fn get_size(&self, file_size: u64) -> u64 {
         if self.end.pos < self.start.pos {
             file_size - self.start.pos + self.end.pos
@@ -238,6 +359,37 @@ impl TimeshiftRecord {
     }
 }
 
+/// A discontinuity in the timeshift timeline.
+///
+/// No data is recorded between `start_time` and `end_time`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeshiftGap {
+    #[serde(with = "ts_milliseconds")]
+    pub start_time: DateTime<Jst>,
+    // `None` until the recording restarts.
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_time: Option<DateTime<Jst>>,
+    pub reason: TimeshiftGapReason,
+}
+
+/// The reason why the timeshift recording stopped.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, ToSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimeshiftGapReason {
+    /// The timeshift pipeline stopped unexpectedly.  For example, the tuner was lost.
+    PipelineStopped,
+    /// The recording was stopped by `/timeshift/{recorder}/stop`.
+    Paused,
+    /// The recording was stopped in order to keep pinned records.
+    PinnedRecords,
+    /// The service became unavailable.
+    ServiceUnavailable,
+    /// mirakc was not running.
+    Shutdown,
+}
+
 #[derive(Deserialize)]
 pub struct TimeshiftRecorderData {
     pub service: EpgService,
@@ -245,4 +397,135 @@ pub struct TimeshiftRecorderData {
     pub max_chunks: usize,
     pub records: IndexMap<TimeshiftRecordId, TimeshiftRecord>,
     pub points: VecDeque<TimeshiftPoint>,
+    // Older versions don't save gaps.
+    #[serde(default)]
+    pub gaps: VecDeque<TimeshiftGap>,
+    // Saved only when the recorder is stopped by `/timeshift/{recorder}/stop`.
+    #[serde(default)]
+    pub paused: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProgramId;
+
+    #[test]
+    fn test_get_segments() {
+        let point = |min: i64, pos: u64| TimeshiftPoint {
+            timestamp: jst!("2021-01-01T00:00:00+09:00") + Duration::minutes(min),
+            pos,
+        };
+        let points: VecDeque<TimeshiftPoint> =
+            [point(0, 0), point(1, 10), point(2, 20), point(3, 0)]
+                .into_iter()
+                .collect();
+
+        let record = TimeshiftRecord {
+            id: 1u32.into(),
+            program: EpgProgram::new(ProgramId::from((0, 1, 1))),
+            start: point(0, 0),
+            end: point(0, 0),
+            recording: true,
+            pinned: false,
+        };
+        assert!(record.get_segments(&points, 30).is_empty());
+
+        // The record wraps around the ring buffer.
+        let record = TimeshiftRecord {
+            start: point(1, 15),
+            end: point(4, 5),
+            ..record
+        };
+        let segments = record.get_segments(&points, 30);
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].start_time, point(1, 0).timestamp);
+        assert_eq!(segments[0].duration, Duration::minutes(1));
+        assert_eq!(segments[0].offset, 0);
+        assert_eq!(segments[0].size, 5);
+        assert_eq!(segments[1].offset, 5);
+        assert_eq!(segments[1].size, 10);
+        assert_eq!(segments[2].offset, 15);
+        assert_eq!(segments[2].size, 5);
+        assert_eq!(
+            segments.iter().map(|segment| segment.size).sum::<u64>(),
+            record.get_size(30)
+        );
+    }
+
+    #[test]
+    fn test_find_position() {
+        let point = |min: i64, pos: u64| TimeshiftPoint {
+            timestamp: jst!("2021-01-01T00:00:00+09:00") + Duration::minutes(min),
+            pos,
+        };
+        let time = |sec: i64| jst!("2021-01-01T00:00:00+09:00") + Duration::seconds(sec);
+        let file_size = TS_PACKET_SIZE * 30;
+        let points = [
+            point(0, 0),
+            point(1, TS_PACKET_SIZE * 10),
+            point(2, TS_PACKET_SIZE * 20),
+            point(3, 0),
+        ];
+
+        assert_eq!(find_position(&points, file_size, time(-1)), None);
+        assert_eq!(find_position(&points, file_size, time(0)), Some(0));
+        assert_eq!(
+            find_position(&points, file_size, time(30)),
+            Some(TS_PACKET_SIZE * 5)
+        );
+        // Aligned to a TS packet boundary.
+        assert_eq!(
+            find_position(&points, file_size, time(31)),
+            Some(TS_PACKET_SIZE * 5)
+        );
+        assert_eq!(
+            find_position(&points, file_size, time(60)),
+            Some(TS_PACKET_SIZE * 10)
+        );
+        // Wraps around the ring buffer.
+        assert_eq!(
+            find_position(&points, file_size, time(150)),
+            Some(TS_PACKET_SIZE * 25)
+        );
+        assert_eq!(find_position(&points, file_size, time(180)), None);
+    }
+
+    #[test]
+    fn test_get_offset() {
+        let point = |min: i64, pos: u64| TimeshiftPoint {
+            timestamp: jst!("2021-01-01T00:00:00+09:00") + Duration::minutes(min),
+            pos,
+        };
+        let time = |sec: i64| jst!("2021-01-01T00:00:00+09:00") + Duration::seconds(sec);
+        let file_size = TS_PACKET_SIZE * 30;
+        let points: VecDeque<TimeshiftPoint> = [
+            point(0, 0),
+            point(1, TS_PACKET_SIZE * 10),
+            point(2, TS_PACKET_SIZE * 20),
+            point(3, 0),
+        ]
+        .into_iter()
+        .collect();
+
+        let record = TimeshiftRecord {
+            id: 1u32.into(),
+            program: EpgProgram::new(ProgramId::from((0, 1, 1))),
+            start: point(2, TS_PACKET_SIZE * 20),
+            end: point(4, TS_PACKET_SIZE * 10),
+            recording: false,
+            pinned: false,
+        };
+        assert_eq!(record.get_offset(&points, file_size, time(119)), None);
+        assert_eq!(record.get_offset(&points, file_size, time(120)), Some(0));
+        assert_eq!(
+            record.get_offset(&points, file_size, time(180)),
+            Some(TS_PACKET_SIZE * 10)
+        );
+        assert_eq!(
+            record.get_offset(&points, file_size, time(210)),
+            Some(TS_PACKET_SIZE * 15)
+        );
+        assert_eq!(record.get_offset(&points, file_size, time(240)), None);
+    }
 }
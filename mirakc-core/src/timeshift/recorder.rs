@@ -1,5 +1,4 @@
 use std::collections::VecDeque;
-use std::path::Path;
 use std::sync::Arc;
 
 use actlet::prelude::*;
@@ -8,10 +7,11 @@ use chrono::Duration;
 use chrono_jst::Jst;
 use indexmap::IndexMap;
 use serde::Deserialize;
-use serde::Serialize;
 use tokio::io::AsyncBufReadExt;
 use tokio::io::BufReader;
 
+use super::data::TimeshiftDataWriter;
+use super::data::TimeshiftRecorderDataForSave;
 use super::*;
 use crate::command_util::*;
 use crate::config::*;
@@ -20,11 +20,18 @@ use crate::epg::EpgChannel;
 use crate::epg::EpgProgram;
 use crate::epg::EpgService;
 use crate::error::Error;
-use crate::file_util;
 use crate::filter::*;
 use crate::models::*;
+use crate::recording::ImportTimeshiftRecord;
+use crate::recording::Record;
+use crate::recording::RecordingOptions;
 use crate::tuner::*;
 
+// A pinned record is exported when it will be overwritten within this number of chunks.  Reading
+// the timeshift file is much faster than writing it at the bitrate of the broadcast, so the copy
+// finishes before the chunks of the record are overwritten.
+const PINNED_RECORD_EXPORT_MARGIN: usize = 2;
+
 // recorder
 
 pub struct TimeshiftRecorder<T> {
@@ -42,13 +49,21 @@ pub struct TimeshiftRecorder<T> {
     // `points` contains some kind of "garbage" items.  See comments in
     // `save_data()` and `handle_start_recording()` for details.
     points: VecDeque<TimeshiftPoint>,
+    // The last item is open while the recording is stopped.
+    gaps: VecDeque<TimeshiftGap>,
+    data_writer: TimeshiftDataWriter,
 
     service_available: bool,
     recording: bool,
+    // `true` while the recording is stopped by `StopTimeshiftRecorder`.
+    paused: bool,
+    // The reason why the session was dropped by the recorder itself.
+    stop_reason: Option<TimeshiftGapReason>,
     session: Option<TimeshiftRecorderSession>,
     current_record_id: Option<TimeshiftRecordId>,
 
     event_emitter: Emitter<TimeshiftEvent>,
+    record_importer: Option<Caller<ImportTimeshiftRecord>>,
 }
 
 impl<T> TimeshiftRecorder<T> {
@@ -58,6 +73,7 @@ impl<T> TimeshiftRecorder<T> {
         config: Arc<Config>,
         tuner_manager: T,
         event_emitter: E,
+        record_importer: Option<Caller<ImportTimeshiftRecord>>,
     ) -> Self
     where
         E: Emit<TimeshiftEvent> + Send + Sync + 'static,
@@ -81,6 +97,7 @@ impl<T> TimeshiftRecorder<T> {
             },
         };
         let max_chunks = recorder_config.max_chunks();
+        let data_writer = TimeshiftDataWriter::new(recorder_config.data_file.clone());
         TimeshiftRecorder {
             index,
             name,
@@ -89,11 +106,16 @@ impl<T> TimeshiftRecorder<T> {
             service,
             records: IndexMap::new(),
             points: VecDeque::with_capacity(max_chunks),
+            gaps: VecDeque::new(),
+            data_writer,
             recording: false,
+            paused: false,
+            stop_reason: None,
             service_available: false,
             session: None,
             current_record_id: None,
             event_emitter: Emitter::new(event_emitter),
+            record_importer,
         }
     }
 
@@ -131,18 +153,10 @@ impl<T> TimeshiftRecorder<T> {
     }
 
     fn do_load_data(&mut self) -> Result<usize, Error> {
-        if !Path::new(&self.config().data_file).exists() {
-            return Ok(0);
-        }
-
-        let file = std::fs::File::open(&self.config().data_file)?;
-        // If the file is empty, serde_json::from_reader() always causes a parse
-        // error even though serde_json reads no data actually.
-        if file.metadata()?.len() == 0 {
-            return Ok(0);
-        }
-
-        let data: TimeshiftRecorderData = serde_json::from_reader(file)?;
+        let data = match load_recorder_data(&self.config().data_file)? {
+            Some(data) => data,
+            None => return Ok(0),
+        };
         let mut invalid = false;
         if self.config().service_id != data.service.id {
             tracing::error!(
@@ -177,16 +191,24 @@ impl<T> TimeshiftRecorder<T> {
         self.service = data.service;
         self.records = data.records;
         self.points = data.points; // Don't remove the last item here.
+        self.gaps = data.gaps;
+        self.paused = data.paused;
+        if self.paused {
+            tracing::info!(recorder.name = self.name, "Paused");
+        }
+        // The recording stopped without saving a gap when mirakc was terminated.
+        self.open_gap(TimeshiftGapReason::Shutdown);
         Ok(self.records.len())
     }
 
-    fn save_data(&self) {
+    fn save_data(&mut self) {
         let service = &self.service;
         let chunk_size = self.config().chunk_size;
         let max_chunks = self.config().max_chunks();
 
         let records = &self.records;
-        if records.is_empty() {
+        // The paused state has to be saved even if there is no record.
+        if records.is_empty() && !self.paused && !self.config().data_file.exists() {
             tracing::debug!(recorder.name = self.name, "No records to save");
             return;
         }
@@ -195,12 +217,14 @@ impl<T> TimeshiftRecorder<T> {
         // timestamp is newer than the timestamp of the last record in
         // `records`.  However, serde has no built-in support for `Iterator`.
         // So, we have to create a new `Vec<TimeshiftPoint>` every time
-        // the `points` is saved.
+        // the `points` is saved.  In addition, removing them would make
+        // the journal format append useless operations.
         //
         // We select a better way to save all items in `points` here, and
         // remove "garbage" items before starting a new recording session.
         // See `handle_start_recording()`.
         let points = &self.points;
+        let gaps = &self.gaps;
 
         let data = TimeshiftRecorderDataForSave {
             service,
@@ -208,6 +232,8 @@ impl<T> TimeshiftRecorder<T> {
             max_chunks,
             records,
             points,
+            gaps,
+            paused: self.paused,
         };
 
         // issue#676
@@ -217,6 +243,10 @@ impl<T> TimeshiftRecorder<T> {
         //   1. Create <data-file>.new file and write the serialized data to it
         //   2. Rename <data-file>.new to <data-file>
         //
+        // In the journal format, changes are appended to <data-file> and synced to the disk.
+        // A line broken by a crash is discarded as a whole when loading, and then a snapshot
+        // is written by the steps above.
+        //
         // If this function fails, inconsistency between <data-file> and <ts-file> happens.
         // mirakc cannot recover this situation by itself and this must be resolved by
         // the user.  For example, the user might have to remove some files in order to
@@ -226,7 +256,7 @@ impl<T> TimeshiftRecorder<T> {
         // start timeshift recording based on the *old* data file.  As a result, newer
         // records will be lost.  Additionally, TS packets for older records will be
         // lost if a wrap-around occurred in the TS file.
-        if !file_util::save_json(data, &self.config().data_file) {
+        if !self.data_writer.write(data) {
             tracing::error!(
                 recorder.name = self.name,
                 "Sync between <ts-file> and <data-file> was lost"
@@ -257,7 +287,9 @@ impl<T> TimeshiftRecorder<T> {
             num_records: self.records.len(),
             pipeline,
             recording: self.recording,
+            paused: self.paused,
             current_record_id: self.current_record_id,
+            gaps: self.gaps.iter().cloned().collect(),
         }
     }
 
@@ -290,17 +322,35 @@ where
     fn create_live_stream_source(
         &self,
         record_id: Option<TimeshiftRecordId>,
+        start_time: Option<DateTime<Jst>>,
     ) -> Result<TimeshiftLiveStreamSource, Error> {
         if self.points.len() < 2 {
             return Err(Error::RecordNotFound);
         }
         let name = self.name.clone();
         let file = self.config().ts_file.clone();
-        let point = if let Some(id) = record_id {
-            let record = self.records.get(&id).ok_or(Error::ProgramNotFound)?;
-            record.start.clone()
-        } else {
-            self.points[0].clone()
+        let record = match record_id {
+            Some(id) => Some(self.records.get(&id).ok_or(Error::ProgramNotFound)?),
+            None => None,
+        };
+        let point = match (record, start_time) {
+            (Some(record), Some(timestamp)) => {
+                // The start time must be within the record.
+                let pos = find_position(
+                    record.get_boundaries(&self.points),
+                    self.config().max_file_size(),
+                    timestamp,
+                )
+                .ok_or(Error::OutOfRange)?;
+                TimeshiftPoint { timestamp, pos }
+            }
+            (None, Some(timestamp)) => {
+                let pos = find_position(&self.points, self.config().max_file_size(), timestamp)
+                    .ok_or(Error::OutOfRange)?;
+                TimeshiftPoint { timestamp, pos }
+            }
+            (Some(record), None) => record.start.clone(),
+            (None, None) => self.points[0].clone(),
         };
         Ok(TimeshiftLiveStreamSource { name, file, point })
     }
@@ -345,6 +395,7 @@ where
                 channel: channel.clone(),
                 user,
                 stream_id: None,
+                excluded_tuners: vec![],
             })
             .await??;
 
@@ -429,15 +480,6 @@ where
     }
 }
 
-#[derive(Serialize)]
-struct TimeshiftRecorderDataForSave<'a> {
-    service: &'a EpgService,
-    chunk_size: usize,
-    max_chunks: usize,
-    records: &'a IndexMap<TimeshiftRecordId, TimeshiftRecord>,
-    points: &'a VecDeque<TimeshiftPoint>,
-}
-
 #[async_trait]
 impl<T> Handler<ServiceUpdated> for TimeshiftRecorder<T>
 where
@@ -450,7 +492,7 @@ where
             self.service = service;
             self.service_available = true;
             tracing::info!(recorder.name = self.name, "Service is now available");
-            if self.session.is_none() {
+            if self.session.is_none() && !self.paused && !self.is_blocked_by_pinned_records() {
                 self.start_recording(ctx).await;
             }
         } else {
@@ -491,7 +533,7 @@ where
         let records = self
             .records
             .values()
-            .map(|record| record.get_model(self.config()))
+            .map(|record| record.get_model(self.config(), &self.gaps))
             .collect();
         Ok(records)
     }
@@ -511,11 +553,52 @@ where
     ) -> <QueryTimeshiftRecord as Message>::Reply {
         self.records
             .get(&msg.record_id)
-            .map(|record| record.get_model(self.config()))
+            .map(|record| record.get_model(self.config(), &self.gaps))
             .ok_or(Error::RecordNotFound)
     }
 }
 
+#[async_trait]
+impl<T> Handler<QueryTimeshiftRecordSegments> for TimeshiftRecorder<T>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+{
+    async fn handle(
+        &mut self,
+        msg: QueryTimeshiftRecordSegments,
+        _ctx: &mut Context<Self>,
+    ) -> <QueryTimeshiftRecordSegments as Message>::Reply {
+        self.records
+            .get(&msg.record_id)
+            .map(|record| record.get_segments(&self.points, self.config().max_file_size()))
+            .ok_or(Error::RecordNotFound)
+    }
+}
+
+#[async_trait]
+impl<T> Handler<QueryTimeshiftRecordOffset> for TimeshiftRecorder<T>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+{
+    async fn handle(
+        &mut self,
+        msg: QueryTimeshiftRecordOffset,
+        _ctx: &mut Context<Self>,
+    ) -> <QueryTimeshiftRecordOffset as Message>::Reply {
+        let record = self
+            .records
+            .get(&msg.record_id)
+            .ok_or(Error::RecordNotFound)?;
+        record
+            .get_offset(&self.points, self.config().max_file_size(), msg.time)
+            .ok_or(Error::OutOfRange)
+    }
+}
+
 #[async_trait]
 impl<T> Handler<CreateTimeshiftLiveStreamSource> for TimeshiftRecorder<T>
 where
@@ -528,7 +611,7 @@ where
         msg: CreateTimeshiftLiveStreamSource,
         _ctx: &mut Context<Self>,
     ) -> <CreateTimeshiftLiveStreamSource as Message>::Reply {
-        self.create_live_stream_source(msg.record_id)
+        self.create_live_stream_source(msg.record_id, msg.start_time)
     }
 }
 
@@ -548,6 +631,121 @@ where
     }
 }
 
+#[async_trait]
+impl<T> Handler<ExportTimeshiftRecord> for TimeshiftRecorder<T>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+{
+    async fn handle(
+        &mut self,
+        msg: ExportTimeshiftRecord,
+        _ctx: &mut Context<Self>,
+    ) -> <ExportTimeshiftRecord as Message>::Reply {
+        self.export_record(msg.record_id)
+    }
+}
+
+#[async_trait]
+impl<T> Handler<PinTimeshiftRecord> for TimeshiftRecorder<T>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+{
+    async fn handle(
+        &mut self,
+        msg: PinTimeshiftRecord,
+        ctx: &mut Context<Self>,
+    ) -> <PinTimeshiftRecord as Message>::Reply {
+        let record = self
+            .records
+            .get_mut(&msg.record_id)
+            .ok_or(Error::RecordNotFound)?;
+        if record.pinned != msg.pinned {
+            record.pinned = msg.pinned;
+            if record.pinned {
+                tracing::info!(recorder.name = self.name, %record.id, %record.program.id, "Record pinned");
+            } else {
+                tracing::info!(recorder.name = self.name, %record.id, %record.program.id, "Record unpinned");
+            }
+            self.save_data();
+        }
+        let model = self.records[&msg.record_id].get_model(self.config(), &self.gaps);
+
+        // Restart the recording stopped due to pinned records if possible.
+        if self.session.is_none()
+            && self.service_available
+            && !self.paused
+            && !self.is_blocked_by_pinned_records()
+        {
+            self.start_recording(ctx).await;
+        }
+
+        Ok(model)
+    }
+}
+
+#[async_trait]
+impl<T> Handler<StopTimeshiftRecorder> for TimeshiftRecorder<T>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+{
+    async fn handle(
+        &mut self,
+        _msg: StopTimeshiftRecorder,
+        _ctx: &mut Context<Self>,
+    ) -> <StopTimeshiftRecorder as Message>::Reply {
+        if !self.paused {
+            self.paused = true;
+            tracing::info!(recorder.name = self.name, "Paused");
+            // The recorder stays paused after mirakc restarts.
+            self.save_data();
+        }
+        // Dropping the session terminates the pipeline and releases the tuner.  The `Stopped`
+        // event will be emitted when the pipeline stops.
+        if self.session.take().is_some() {
+            tracing::info!(recorder.name = self.name, "Stop recording");
+            self.stop_reason = Some(TimeshiftGapReason::Paused);
+        }
+        Ok(self.get_model())
+    }
+}
+
+#[async_trait]
+impl<T> Handler<StartTimeshiftRecorder> for TimeshiftRecorder<T>
+where
+    T: Clone + Send + Sync + 'static,
+    T: Call<StartStreaming>,
+    T: TriggerFactory<StopStreaming>,
+{
+    async fn handle(
+        &mut self,
+        _msg: StartTimeshiftRecorder,
+        ctx: &mut Context<Self>,
+    ) -> <StartTimeshiftRecorder as Message>::Reply {
+        if self.paused {
+            self.paused = false;
+            tracing::info!(recorder.name = self.name, "Resumed");
+            self.save_data();
+        }
+        // The pipeline previously stopped may not have terminated yet.  In this case, the
+        // recording will be started in the HealthCheck handler.
+        if self.session.is_none()
+            && !self.recording
+            && self.service_available
+            && !self.is_blocked_by_pinned_records()
+        {
+            // The recording resumes at the end of the last chunk.
+            self.start_recording(ctx).await;
+        }
+        Ok(self.get_model())
+    }
+}
+
 #[derive(Message)]
 struct PipelineStarted;
 
@@ -582,6 +780,14 @@ where
         tracing::debug!(recorder.name = self.name, msg.name = "PipelineStopped");
         self.recording = false;
         self.session = None;
+        let reason = match self.stop_reason.take() {
+            Some(reason) => reason,
+            None if !self.service_available => TimeshiftGapReason::ServiceUnavailable,
+            None => TimeshiftGapReason::PipelineStopped,
+        };
+        if self.open_gap(reason) {
+            self.save_data();
+        }
         let msg = TimeshiftEvent::Stopped {
             recorder: self.name.clone(),
         };
@@ -614,6 +820,18 @@ where
             return;
         }
 
+        if self.paused {
+            // Will start recording when the recorder is resumed.
+            return;
+        }
+
+        // Retry exporting pinned records which could not be exported while recording.
+        self.export_pinned_records().await;
+        if self.is_blocked_by_pinned_records() {
+            // Will start recording when the pinned records are unpinned.
+            return;
+        }
+
         self.start_recording(ctx).await;
     }
 }
@@ -705,6 +923,57 @@ impl<T> TimeshiftRecorder<T> {
         // for safety.
         self.remove_garbage_points();
         self.check_consistency();
+
+        self.close_gap().await;
+    }
+
+    // Opens a gap at the end of the last record.
+    //
+    // Returns `true` if a new gap is opened.
+    fn open_gap(&mut self, reason: TimeshiftGapReason) -> bool {
+        if self.gaps.back().is_some_and(|gap| gap.end_time.is_none()) {
+            // Already opened.
+            return false;
+        }
+        let start_time = match self.records.last() {
+            Some((_, record)) => record.end.timestamp,
+            None => return false, // no data recorded
+        };
+        tracing::info!(
+            recorder.name = self.name,
+            gap.start_time = %start_time.to_rfc3339(),
+            ?reason,
+            "Gap started",
+        );
+        self.gaps.push_back(TimeshiftGap {
+            start_time,
+            end_time: None,
+            reason,
+        });
+        true
+    }
+
+    // Closes the open gap.
+    //
+    // The gap is saved when the next chunk is saved.
+    async fn close_gap(&mut self) {
+        let gap = match self.gaps.back_mut() {
+            Some(gap) if gap.end_time.is_none() => gap,
+            _ => return,
+        };
+        gap.end_time = Some(Jst::now());
+        tracing::info!(
+            recorder.name = self.name,
+            gap.start_time = %gap.start_time.to_rfc3339(),
+            gap.end_time = %gap.end_time.unwrap().to_rfc3339(),
+            ?gap.reason,
+            "Gap ended",
+        );
+        let msg = TimeshiftEvent::Gap {
+            recorder: self.name.clone(),
+            gap: gap.clone(),
+        };
+        self.event_emitter.emit(msg).await;
     }
 
     fn remove_garbage_points(&mut self) {
@@ -756,6 +1025,22 @@ impl<T> TimeshiftRecorder<T> {
     }
 
     async fn handle_chunk(&mut self, point: TimeshiftPoint) {
+        self.export_pinned_records().await;
+        if self.is_blocked_by_pinned_records() {
+            // The pinned records could not be exported.
+            //
+            // The chunk for `point` is one of the reserved chunks and no data in it is used.
+            // Stopping the recording here protects the pinned records from being overwritten
+            // because there is at least one reserved chunk between the head and the tail of the
+            // ring buffer.
+            tracing::warn!(
+                recorder.name = self.name,
+                "Stop recording in order to keep pinned records"
+            );
+            self.session = None;
+            self.stop_reason = Some(TimeshiftGapReason::PinnedRecords);
+            return;
+        }
         self.maintain();
         self.append_point(&point);
         self.save_data();
@@ -782,12 +1067,14 @@ impl<T> TimeshiftRecorder<T> {
 
         let mut program = EpgProgram::new(program_id);
         program.update(&event);
-        let record = TimeshiftRecord::new(record_id, program, point);
+        let mut record = TimeshiftRecord::new(record_id, program, point);
+        record.pinned = self.matches_pin_rules(&record.program);
         tracing::info!(
             recorder.name = self.name,
             %record.id,
             %record.program.id,
             %record.start,
+            record.pinned,
             "Record started"
         );
         self.records.insert(record_id, record);
@@ -830,6 +1117,119 @@ impl<T> TimeshiftRecorder<T> {
         self.current_record_id = None;
     }
 
+    fn export_record(&self, record_id: TimeshiftRecordId) -> Result<TimeshiftRecordExport, Error> {
+        let record = self.records.get(&record_id).ok_or(Error::RecordNotFound)?;
+        if record.recording {
+            return Err(Error::InvalidRequest("The record is being recorded"));
+        }
+        Ok(TimeshiftRecordExport {
+            service: self.service.clone(),
+            record: record.get_model(self.config(), &self.gaps),
+            source: record.create_record_stream_source(self.name.clone(), self.config(), &None)?,
+        })
+    }
+
+    // Exports pinned records going to be overwritten into records of the recording manager.
+    //
+    // Exported records are unpinned so that the recording can continue.  Pinned records are kept
+    // if they cannot be exported.
+    async fn export_pinned_records(&mut self) {
+        if !self.config.recording.is_records_api_enabled() {
+            return;
+        }
+        let record_importer = match self.record_importer {
+            Some(ref record_importer) => record_importer.clone(),
+            None => return,
+        };
+        while let Some(record_id) = self.find_pinned_record_to_be_exported() {
+            match self.export_pinned_record(&record_importer, record_id).await {
+                Ok(record) => {
+                    tracing::info!(
+                        recorder.name = self.name,
+                        %record_id,
+                        exported.id = %record.id,
+                        "Exported pinned record going to be overwritten"
+                    );
+                }
+                Err(err) => {
+                    tracing::warn!(
+                        %err,
+                        recorder.name = self.name,
+                        %record_id,
+                        "Failed to export pinned record"
+                    );
+                    return;
+                }
+            }
+            if let Some(record) = self.records.get_mut(&record_id) {
+                record.pinned = false;
+            }
+            self.save_data();
+            let msg = TimeshiftEvent::RecordUpdated {
+                recorder: self.name.clone(),
+                record_id,
+            };
+            self.event_emitter.emit(msg).await;
+        }
+    }
+
+    async fn export_pinned_record(
+        &self,
+        record_importer: &Caller<ImportTimeshiftRecord>,
+        record_id: TimeshiftRecordId,
+    ) -> Result<Record, Error> {
+        let msg = ImportTimeshiftRecord {
+            export: self.export_record(record_id)?,
+            options: RecordingOptions {
+                content_path: None,
+                priority: 0,
+                pre_filters: vec![],
+                post_filters: vec![],
+                log_filter: None,
+                start_margin: None,
+                end_margin: None,
+                follow_series: false,
+            },
+            tags: Default::default(),
+        };
+        record_importer.call(msg).await?
+    }
+
+    // Returns the ID of a pinned record which will be overwritten within
+    // `PINNED_RECORD_EXPORT_MARGIN` chunks.
+    fn find_pinned_record_to_be_exported(&self) -> Option<TimeshiftRecordId> {
+        let n = (self.points.len() + PINNED_RECORD_EXPORT_MARGIN)
+            .checked_sub(self.config().max_chunks())
+            .filter(|n| *n > 0)?;
+        // `points[n]` will be the first point after `n` chunks are invalidated.
+        let timestamp = self.points.get(n).or(self.points.back())?.timestamp;
+        self.records
+            .values()
+            .take_while(|record| record.start.timestamp < timestamp)
+            .find(|record| record.pinned && !record.recording)
+            .map(|record| record.id)
+    }
+
+    // Returns `true` if the next chunk will overwrite a pinned record.
+    fn is_blocked_by_pinned_records(&self) -> bool {
+        if self.points.len() < self.config().max_chunks() {
+            return false;
+        }
+        // `points[1]` will be the first point after the first chunk is invalidated.
+        let timestamp = self.points[1].timestamp;
+        self.records
+            .values()
+            .take_while(|record| record.start.timestamp < timestamp)
+            .any(|record| record.pinned)
+    }
+
+    fn matches_pin_rules(&self, program: &EpgProgram) -> bool {
+        match program.series {
+            Some(ref series) => self.config().pin.series_ids.contains(&series.series_id),
+            None => false,
+        }
+    }
+
     fn maintain(&mut self) {
         if self.points.len() < self.config().max_chunks() {
             return;
@@ -837,6 +1237,7 @@ impl<T> TimeshiftRecorder<T> {
         self.invalidate_first_chunk();
         self.purge_expired_records();
         self.crop_record_to_be_overwritten();
+        self.purge_expired_gaps();
     }
 
     fn invalidate_first_chunk(&mut self) {
@@ -884,6 +1285,24 @@ impl<T> TimeshiftRecorder<T> {
         }
     }
 
+    // Purge gaps which ended before the first timestamp.
+    fn purge_expired_gaps(&mut self) {
+        assert!(!self.points.is_empty());
+        let timestamp = self.points[0].timestamp;
+        while let Some(gap) = self.gaps.front() {
+            match gap.end_time {
+                Some(end_time) if end_time <= timestamp => (),
+                _ => break,
+            }
+            let gap = self.gaps.pop_front().unwrap();
+            tracing::debug!(
+                recorder.name = self.name,
+                gap.start_time = %gap.start_time.to_rfc3339(),
+                "Gap purged"
+            );
+        }
+    }
+
     fn append_point(&mut self, point: &TimeshiftPoint) {
         let index = point.pos / (self.config().chunk_size as u64);
         assert!(point.pos.is_multiple_of(self.config().chunk_size as u64));
@@ -893,8 +1312,14 @@ impl<T> TimeshiftRecorder<T> {
     }
 
     fn update_last_record(&mut self, program: EpgProgram, point: TimeshiftPoint, end: bool) {
+        // The series descriptor may be available after the record started.
+        let pinned = self.matches_pin_rules(&program);
         match self.records.values_mut().last() {
             Some(record) => {
+                if pinned && !record.pinned {
+                    record.pinned = true;
+                    tracing::info!(recorder.name = self.name, %record.id, %record.program.id, "Record pinned");
+                }
                 record.update(program, point, end);
                 if end {
                     tracing::info!(recorder.name = self.name, %record.id, %record.program.id, %record.end, "Record ended");
@@ -989,9 +1414,12 @@ where
 mod tests {
     use super::*;
     use crate::broadcaster::BroadcasterStream;
+    use crate::epg::SeriesDescriptor;
     use crate::mpeg_ts_stream::MpegTsStream;
+    use crate::recording::stub::RecordingManagerStub;
     use crate::tuner::stub::TunerManagerStub;
     use assert_matches::assert_matches;
+    use std::path::Path;
     use tempfile::TempDir;
     use test_log::test;
     use tokio::sync::Notify;
@@ -1013,6 +1441,7 @@ mod tests {
                 start: $start,
                 end: $end,
                 recording: false,
+                pinned: false,
             }
         };
     }
@@ -1025,6 +1454,7 @@ mod tests {
                 $config,
                 TunerManagerStub::default(),
                 EventEmitterStub,
+                None,
             )
         };
         ($config:expr, $tuner_manager:expr, $event_emitter:expr) => {
@@ -1034,6 +1464,7 @@ mod tests {
                 $config,
                 $tuner_manager,
                 $event_emitter,
+                None,
             )
         };
     }
@@ -1082,6 +1513,276 @@ mod tests {
         assert_eq!(recorder.records[0].program.id, (0, 1, 3).into());
     }
 
+    #[test(tokio::test)]
+    async fn test_timeshift_recorder_gaps() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = create_config(temp_dir.path());
+
+        let mut recorder = recorder!(config.clone());
+        assert!(!recorder.open_gap(TimeshiftGapReason::PipelineStopped));
+        assert!(recorder.gaps.is_empty());
+
+        recorder.records = indexmap::indexmap! {
+            1u32.into() => record! {
+                1u32,
+                program!((0, 1, 1), jst!("2021-01-01T00:00:00+09:00"), "1h"),
+                point!("2021-01-01T00:00:00+09:00", 0),
+                point!("2021-01-01T00:10:00+09:00", 0)
+            },
+        };
+        assert!(recorder.open_gap(TimeshiftGapReason::PipelineStopped));
+        // Already opened.
+        assert!(!recorder.open_gap(TimeshiftGapReason::Paused));
+        assert_eq!(recorder.gaps.len(), 1);
+        assert_eq!(
+            recorder.gaps[0].start_time,
+            jst!("2021-01-01T00:10:00+09:00")
+        );
+        assert_eq!(recorder.gaps[0].end_time, None);
+        assert_eq!(recorder.gaps[0].reason, TimeshiftGapReason::PipelineStopped);
+
+        recorder.close_gap().await;
+        assert!(recorder.gaps[0].end_time.is_some());
+
+        // The record interrupted by the gap has it.
+        let model = recorder.records[0].get_model(recorder.config(), &recorder.gaps);
+        assert_eq!(
+            model.gaps,
+            recorder.gaps.iter().cloned().collect::<Vec<_>>()
+        );
+
+        // A record for another program doesn't have the gap.
+        let record = record! {
+            2u32,
+            program!((0, 1, 2), jst!("2021-01-01T00:00:00+09:00"), "10m"),
+            point!("2021-01-01T00:00:00+09:00", 0),
+            point!("2021-01-01T00:10:00+09:00", 0)
+        };
+        assert!(record.get_gaps(&recorder.gaps).is_empty());
+
+        recorder.points = vecdeque![point!("2021-01-01T00:00:00+09:00", 0)];
+        recorder.purge_expired_gaps();
+        assert_eq!(recorder.gaps.len(), 1);
+
+        recorder.points = vecdeque![TimeshiftPoint {
+            timestamp: recorder.gaps[0].end_time.unwrap(),
+            pos: 0,
+        }];
+        recorder.purge_expired_gaps();
+        assert!(recorder.gaps.is_empty());
+    }
+
+    #[test(tokio::test)]
+    async fn test_timeshift_recorder_create_live_stream_source() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = create_config(temp_dir.path());
+
+        let chunk_size = config.timeshift.recorders["test"].chunk_size as u64;
+        let base = jst!("2021-01-01T00:00:00+09:00");
+        let point = |i: usize| TimeshiftPoint {
+            timestamp: base + Duration::minutes(i as i64),
+            pos: (i as u64) * chunk_size,
+        };
+
+        let mut recorder = recorder!(config.clone());
+        recorder.records = indexmap::indexmap! {
+            1u32.into() => record! {
+                1u32,
+                program!((0, 1, 1)),
+                point(1),
+                point(3)
+            },
+        };
+        recorder.points = (0..5).map(point).collect();
+
+        let result = recorder
+            .create_live_stream_source(None, None)
+            .map(|source| source.point.pos);
+        assert_matches!(result, Ok(pos) => {
+            assert_eq!(pos, point(0).pos);
+        });
+
+        let result = recorder
+            .create_live_stream_source(Some(1u32.into()), None)
+            .map(|source| source.point.pos);
+        assert_matches!(result, Ok(pos) => {
+            assert_eq!(pos, point(1).pos);
+        });
+
+        let result = recorder
+            .create_live_stream_source(None, Some(point(3).timestamp))
+            .map(|source| source.point.pos);
+        assert_matches!(result, Ok(pos) => {
+            assert_eq!(pos, point(3).pos);
+        });
+
+        let result = recorder
+            .create_live_stream_source(Some(1u32.into()), Some(point(2).timestamp))
+            .map(|source| source.point.pos);
+        assert_matches!(result, Ok(pos) => {
+            assert_eq!(pos, point(2).pos);
+        });
+
+        // Outside the record.
+        let result = recorder
+            .create_live_stream_source(Some(1u32.into()), Some(point(0).timestamp))
+            .map(|source| source.point.pos);
+        assert_matches!(result, Err(Error::OutOfRange));
+        let result = recorder
+            .create_live_stream_source(Some(1u32.into()), Some(point(3).timestamp))
+            .map(|source| source.point.pos);
+        assert_matches!(result, Err(Error::OutOfRange));
+
+        let result = recorder
+            .create_live_stream_source(Some(2u32.into()), None)
+            .map(|source| source.point.pos);
+        assert_matches!(result, Err(Error::ProgramNotFound));
+    }
+
+    #[test(tokio::test)]
+    async fn test_timeshift_recorder_pinned_records() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = create_config(temp_dir.path());
+        Arc::get_mut(&mut config)
+            .unwrap()
+            .timeshift
+            .recorders
+            .get_mut("test")
+            .unwrap()
+            .pin
+            .series_ids = vec![1];
+
+        let chunk_size = config.timeshift.recorders["test"].chunk_size as u64;
+        let max_chunks = config.timeshift.recorders["test"].max_chunks();
+        let base = jst!("2021-01-01T00:00:00+09:00");
+        let point = |i: usize| TimeshiftPoint {
+            timestamp: base + Duration::minutes(i as i64),
+            pos: (i as u64) * chunk_size,
+        };
+
+        let mut recorder = recorder!(config.clone());
+        recorder.records = indexmap::indexmap! {
+            1u32.into() => record! {
+                1u32,
+                program!((0, 1, 1)),
+                point(0),
+                point(2)
+            },
+        };
+        recorder.points = (0..max_chunks).map(point).collect();
+        recorder.records[0].pinned = true;
+        assert!(recorder.is_blocked_by_pinned_records());
+
+        recorder.handle_chunk(point(max_chunks)).await;
+        assert_eq!(recorder.points.len(), max_chunks);
+        assert_eq!(recorder.points[0].timestamp, point(0).timestamp);
+        assert_eq!(recorder.records[0].start.timestamp, point(0).timestamp);
+
+        // The pinned state is saved in the data-file.
+        recorder.save_data();
+        let mut loaded = recorder!(config.clone());
+        assert_matches!(loaded.do_load_data(), Ok(1));
+        assert!(loaded.records[0].pinned);
+
+        recorder.records[0].pinned = false;
+        assert!(!recorder.is_blocked_by_pinned_records());
+
+        recorder.handle_chunk(point(max_chunks)).await;
+        assert_eq!(recorder.points.len(), max_chunks);
+        assert_eq!(recorder.points[0].timestamp, point(1).timestamp);
+        assert_eq!(recorder.records[0].start.timestamp, point(1).timestamp);
+
+        let mut program = EpgProgram::new((0, 1, 2).into());
+        assert!(!recorder.matches_pin_rules(&program));
+        program.series = Some(SeriesDescriptor {
+            series_id: 1,
+            repeat_label: 0,
+            program_pattern: 0,
+            expire_date: None,
+            episode_number: 1,
+            last_episode_number: 10,
+            series_name: None,
+        });
+        assert!(recorder.matches_pin_rules(&program));
+        program.series.as_mut().unwrap().series_id = 2;
+        assert!(!recorder.matches_pin_rules(&program));
+    }
+
+    #[test(tokio::test)]
+    async fn test_timeshift_recorder_export_pinned_records() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = create_config(temp_dir.path());
+        {
+            let config = Arc::get_mut(&mut config).unwrap();
+            config.recording.basedir = Some(temp_dir.path().to_owned());
+            config.recording.records_dir = Some(temp_dir.path().to_owned());
+        }
+
+        let chunk_size = config.timeshift.recorders["test"].chunk_size as u64;
+        let max_chunks = config.timeshift.recorders["test"].max_chunks();
+        let base = jst!("2021-01-01T00:00:00+09:00");
+        let point = |i: usize| TimeshiftPoint {
+            timestamp: base + Duration::minutes(i as i64),
+            pos: (i as u64) * chunk_size,
+        };
+
+        let mut recorder = recorder!(config.clone());
+        recorder.record_importer = Some(Caller::new(RecordingManagerStub));
+        recorder.records = indexmap::indexmap! {
+            1u32.into() => record! {
+                1u32,
+                program!((0, 1, 1)),
+                point(0),
+                point(2)
+            },
+            2u32.into() => record! {
+                2u32,
+                program!((0, 1, 2)),
+                point(2),
+                point(max_chunks - 1)
+            },
+        };
+        recorder.points = (0..(max_chunks - PINNED_RECORD_EXPORT_MARGIN))
+            .map(point)
+            .collect();
+        recorder.records[0].pinned = true;
+        recorder.records[1].pinned = true;
+        assert_matches!(recorder.find_pinned_record_to_be_exported(), None);
+
+        recorder.points.push_back(point(max_chunks - 1));
+        assert_matches!(recorder.find_pinned_record_to_be_exported(), Some(id) => {
+            assert_eq!(id, 1u32.into());
+        });
+
+        // The pinned record going to be overwritten is exported and unpinned.
+        recorder.handle_chunk(point(max_chunks)).await;
+        assert!(!recorder.records[0].pinned);
+        assert!(recorder.records[1].pinned);
+        assert!(recorder.session.is_none());
+        assert_eq!(recorder.stop_reason, None);
+        assert_eq!(recorder.points.len(), max_chunks);
+        assert_eq!(recorder.points[0].timestamp, point(0).timestamp);
+
+        // The recording continues.
+        recorder.handle_chunk(point(max_chunks + 1)).await;
+        assert_eq!(recorder.stop_reason, None);
+        assert_eq!(recorder.points.len(), max_chunks);
+        assert_eq!(recorder.points[0].timestamp, point(1).timestamp);
+
+        // A pinned record currently recording cannot be exported.
+        recorder.records[1].recording = true;
+        recorder.handle_chunk(point(max_chunks + 2)).await;
+        assert_eq!(recorder.stop_reason, None);
+        recorder.handle_chunk(point(max_chunks + 3)).await;
+        // The first record has been purged.
+        assert_eq!(recorder.records.len(), 1);
+        assert!(recorder.records[0].pinned);
+        assert_eq!(
+            recorder.stop_reason,
+            Some(TimeshiftGapReason::PinnedRecords)
+        );
+    }
+
     #[test(tokio::test)]
     async fn test_timeshift_recorder_broken_pipeline() {
         let system = System::new();
@@ -1173,6 +1874,80 @@ mod tests {
         system.shutdown().await;
     }
 
+    #[test(tokio::test)]
+    async fn test_timeshift_recorder_pause() {
+        let system = System::new();
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = create_config(temp_dir.path());
+
+        let notify = Arc::new(Notify::new());
+        let tuner_manager = BrokenTunerManagerStub(notify.clone());
+
+        let (observer, started, stopped) = Observer::new();
+
+        let recorder = system
+            .spawn_actor(recorder!(config.clone(), tuner_manager, observer))
+            .await;
+
+        recorder
+            .emit(ServiceUpdated {
+                service: Some(create_epg_service()),
+            })
+            .await;
+        started.notified().await;
+
+        let msg = StopTimeshiftRecorder {
+            recorder: TimeshiftRecorderQuery::ByIndex(0), // dummy
+        };
+        let result = recorder.call(msg).await;
+        assert_matches!(result, Ok(Ok(model)) => {
+            assert!(model.paused);
+            assert!(model.pipeline.is_empty());
+        });
+        stopped.notified().await;
+
+        // Never restart while paused.
+        recorder.call(HealthCheck).await.unwrap();
+        let msg = QueryTimeshiftRecorder {
+            recorder: TimeshiftRecorderQuery::ByIndex(0), // dummy
+        };
+        let result = recorder.call(msg).await;
+        assert_matches!(result, Ok(Ok(model)) => {
+            assert!(!model.recording);
+            assert!(model.paused);
+            assert!(model.pipeline.is_empty());
+        });
+
+        // The paused state is kept after restart.
+        let mut loaded = recorder!(config.clone());
+        assert_matches!(loaded.do_load_data(), Ok(0));
+        assert!(loaded.paused);
+
+        let msg = StartTimeshiftRecorder {
+            recorder: TimeshiftRecorderQuery::ByIndex(0), // dummy
+        };
+        let result = recorder.call(msg).await;
+        assert_matches!(result, Ok(Ok(model)) => {
+            assert!(!model.paused);
+            assert!(!model.pipeline.is_empty());
+        });
+        started.notified().await;
+        let msg = QueryTimeshiftRecorder {
+            recorder: TimeshiftRecorderQuery::ByIndex(0), // dummy
+        };
+        let result = recorder.call(msg).await;
+        assert_matches!(result, Ok(Ok(model)) => {
+            assert!(model.recording);
+        });
+
+        let mut loaded = recorder!(config.clone());
+        assert_matches!(loaded.do_load_data(), Ok(0));
+        assert!(!loaded.paused);
+
+        system.shutdown().await;
+    }
+
     fn create_config<P: AsRef<Path>>(dir: P) -> Arc<Config> {
         let ts_file = dir.as_ref().join("ts-file.m2ts");
         let data_file = dir.as_ref().join("data-file.json");
@@ -38,13 +38,15 @@ impl Call<QueryTimeshiftRecorder> for TimeshiftManagerStub {
                     index: 0,
                     name: name.clone(),
                     service: service!((1, 2), "test", channel_gr!("test", "test")),
-                    start_time: None,
-                    end_time: None,
+                    start_time: Some(Jst::now()),
+                    end_time: Some(Jst::now()),
                     duration: Duration::zero(),
                     num_records: 0,
                     pipeline: vec![],
                     recording: true,
+                    paused: false,
                     current_record_id: None,
+                    gaps: vec![],
                 }))
             }
             _ => Ok(Err(Error::RecordNotFound)),
@@ -76,6 +78,8 @@ impl Call<QueryTimeshiftRecord> for TimeshiftManagerStub {
                 end_time: Jst::now(),
                 size: 10,
                 recording: true,
+                pinned: false,
+                gaps: vec![],
             })),
             1 => Ok(Ok(TimeshiftRecordModel {
                 id: msg.record_id,
@@ -84,6 +88,8 @@ impl Call<QueryTimeshiftRecord> for TimeshiftManagerStub {
                 end_time: Jst::now(),
                 size: 10,
                 recording: false,
+                pinned: false,
+                gaps: vec![],
             })),
             _ => Ok(Err(Error::RecordNotFound)),
         }
@@ -126,3 +132,133 @@ impl Call<CreateTimeshiftRecordStreamSource> for TimeshiftManagerStub {
         }
     }
 }
+
+#[async_trait]
+impl Call<ExportTimeshiftRecord> for TimeshiftManagerStub {
+    async fn call(
+        &self,
+        msg: ExportTimeshiftRecord,
+    ) -> actlet::Result<<ExportTimeshiftRecord as Message>::Reply> {
+        match msg.recorder {
+            TimeshiftRecorderQuery::ByName(ref name) if name == "test" => {
+                match msg.record_id.value() {
+                    0 => Ok(Err(Error::InvalidRequest("The record is being recorded"))),
+                    1 => Ok(Ok(TimeshiftRecordExport {
+                        service: service!((1, 2), "test", channel_gr!("test", "test")),
+                        record: TimeshiftRecordModel {
+                            id: msg.record_id,
+                            program: program!((1, 2, 3)),
+                            start_time: Jst::now(),
+                            end_time: Jst::now(),
+                            size: 1,
+                            recording: false,
+                            pinned: false,
+                            gaps: vec![],
+                        },
+                        source: TimeshiftRecordStreamSource::new_for_test(name),
+                    })),
+                    _ => Ok(Err(Error::RecordNotFound)),
+                }
+            }
+            _ => Ok(Err(Error::RecordNotFound)),
+        }
+    }
+}
+
+#[async_trait]
+impl Call<PinTimeshiftRecord> for TimeshiftManagerStub {
+    async fn call(
+        &self,
+        msg: PinTimeshiftRecord,
+    ) -> actlet::Result<<PinTimeshiftRecord as Message>::Reply> {
+        match msg.recorder {
+            TimeshiftRecorderQuery::ByName(ref name) if name == "test" => {
+                match msg.record_id.value() {
+                    0 | 1 => Ok(Ok(TimeshiftRecordModel {
+                        id: msg.record_id,
+                        program: program!((0, 0, 0)),
+                        start_time: Jst::now(),
+                        end_time: Jst::now(),
+                        size: 10,
+                        recording: msg.record_id.value() == 0,
+                        pinned: msg.pinned,
+                        gaps: vec![],
+                    })),
+                    _ => Ok(Err(Error::RecordNotFound)),
+                }
+            }
+            _ => Ok(Err(Error::RecordNotFound)),
+        }
+    }
+}
+
+#[async_trait]
+impl Call<StopTimeshiftRecorder> for TimeshiftManagerStub {
+    async fn call(
+        &self,
+        msg: StopTimeshiftRecorder,
+    ) -> actlet::Result<<StopTimeshiftRecorder as Message>::Reply> {
+        let mut model = self
+            .call(QueryTimeshiftRecorder {
+                recorder: msg.recorder,
+            })
+            .await?;
+        if let Ok(ref mut model) = model {
+            model.recording = false;
+            model.paused = true;
+        }
+        Ok(model)
+    }
+}
+
+#[async_trait]
+impl Call<StartTimeshiftRecorder> for TimeshiftManagerStub {
+    async fn call(
+        &self,
+        msg: StartTimeshiftRecorder,
+    ) -> actlet::Result<<StartTimeshiftRecorder as Message>::Reply> {
+        self.call(QueryTimeshiftRecorder {
+            recorder: msg.recorder,
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl Call<QueryTimeshiftRecordSegments> for TimeshiftManagerStub {
+    async fn call(
+        &self,
+        msg: QueryTimeshiftRecordSegments,
+    ) -> actlet::Result<<QueryTimeshiftRecordSegments as Message>::Reply> {
+        match msg.record_id.value() {
+            0 | 1 => Ok(Ok(vec![
+                TimeshiftRecordSegment {
+                    start_time: Jst::now(),
+                    duration: Duration::seconds(10),
+                    offset: 0,
+                    size: 5,
+                },
+                TimeshiftRecordSegment {
+                    start_time: Jst::now(),
+                    duration: Duration::seconds(10),
+                    offset: 5,
+                    size: 5,
+                },
+            ])),
+            _ => Ok(Err(Error::RecordNotFound)),
+        }
+    }
+}
+
+#[async_trait]
+impl Call<QueryTimeshiftRecordOffset> for TimeshiftManagerStub {
+    async fn call(
+        &self,
+        msg: QueryTimeshiftRecordOffset,
+    ) -> actlet::Result<<QueryTimeshiftRecordOffset as Message>::Reply> {
+        match msg.record_id.value() {
+            0 | 1 => Ok(Ok(5)),
+            _ => Ok(Err(Error::RecordNotFound)),
+        }
+    }
+}
@@ -1,21 +1,38 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
 use std::sync::Arc;
 use std::sync::atomic::AtomicU32;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 
 use actlet::prelude::*;
+use chrono::DateTime;
+use chrono_jst::jst::Jst;
+use serde::Deserialize;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+use url::Url;
 
 use crate::broadcaster::*;
+use crate::command_util::CommandBuilder;
 use crate::command_util::CommandPipeline;
 use crate::command_util::spawn_pipeline;
 use crate::config::Config;
 use crate::config::ExcludedChannelConfig;
 use crate::config::FilterConfig;
 use crate::config::OnairProgramTrackerConfig;
+use crate::config::RemoteTunerConfig;
 use crate::config::TunerConfig;
+use crate::config::TunerHealthCheckConfig;
+use crate::config::TunerSignalLevelConfig;
 use crate::epg::EpgChannel;
 use crate::error::Error;
+use crate::file_util;
 use crate::models::*;
 use crate::mpeg_ts_stream::MpegTsStream;
 
@@ -61,6 +78,10 @@ impl TunerSubscriptionId {
             serial_number,
         }
     }
+
+    pub fn tuner_index(&self) -> usize {
+        self.session_id.tuner_index
+    }
 }
 
 impl fmt::Display for TunerSubscriptionId {
@@ -76,6 +97,9 @@ pub struct TunerManager {
     tuners: Vec<Tuner>,
     event_emitters: EmitterRegistry<Event>,
     stopping: bool,
+    // Time-boxed reservations of tuners requested via the web API.
+    reservations: Vec<TunerReservation>,
+    next_reservation_id: u64,
 }
 
 struct TunerSubscription {
@@ -107,6 +131,8 @@ impl TunerManager {
             tuners: Vec::new(),
             event_emitters: Default::default(),
             stopping: false,
+            reservations: vec![],
+            next_reservation_id: 1,
         }
     }
 
@@ -137,25 +163,21 @@ impl TunerManager {
                 {
                     let user = TunerUserInfo::OnairProgramTracker(name.clone());
                     tuner.set_exclusive_user(user);
-                } else if let Some((name, timeshift)) = self
-                    .config
-                    .timeshift
-                    .recorders
-                    .iter()
-                    .find_map(|(name, timeshift)| {
-                        if timeshift.uses.tuner == config.name {
-                            Some((name, timeshift))
-                        } else {
-                            None
-                        }
-                    })
-                {
-                    let user = TunerUserInfo::TimeshiftRecorder(name.to_string());
-                    tuner.set_channel_user(
-                        user,
-                        timeshift.uses.channel_type,
-                        timeshift.uses.channel.clone(),
-                    );
+                } else {
+                    for (name, timeshift) in self
+                        .config
+                        .timeshift
+                        .recorders
+                        .iter()
+                        .filter(|(_, timeshift)| timeshift.uses.tuner == config.name)
+                    {
+                        let user = TunerUserInfo::TimeshiftRecorder(name.to_string());
+                        tuner.add_channel_user(
+                            user,
+                            timeshift.uses.channel_type,
+                            timeshift.uses.channel.clone(),
+                        );
+                    }
                 }
                 tuner
             })
@@ -169,6 +191,7 @@ impl TunerManager {
         channel: &EpgChannel,
         user: &TunerUser,
         stream_id: &Option<TunerSubscriptionId>,
+        excluded_tuners: &[usize],
         ctx: &C,
     ) -> Result<TunerSubscription, Error>
     where
@@ -178,9 +201,7 @@ impl TunerManager {
             let tuner = &mut self.tuners[stream_id.session_id.tuner_index];
             if tuner.is_subscribed(stream_id) {
                 tracing::debug!(tuner.index, %channel, %user.info, stream.id = %stream_id, "Reuse specified tuner");
-                self.event_emitters
-                    .emit(Event::StatusChanged(tuner.index))
-                    .await;
+                self.event_emitters.emit(tuner.status_changed()).await;
                 return Ok(tuner.subscribe(user));
             }
             tracing::error!(tuner.index, %channel, %user.info, stream.id = %stream_id, "Specified tuner is unavailable");
@@ -194,43 +215,106 @@ impl TunerManager {
         if let Some(tuner) = found {
             tracing::debug!(tuner.index, %channel, %user.info, "Use reserved tuner");
             if !tuner.is_active() {
+                if tuner.is_quarantined() {
+                    tracing::warn!(tuner.index, %channel, %user.info, "Reserved tuner is quarantined");
+                    return Err(Error::TunerUnavailable);
+                }
                 let filters =
                     Self::make_filter_commands(tuner, channel, &self.config.filters.tuner_filter)?;
-                tuner.activate(channel, filters, ctx).await?;
+                if let Err(err) = tuner.activate(channel, user.priority, filters, ctx).await {
+                    if tuner.record_activation_error(&err) {
+                        self.event_emitters.emit(tuner.status_changed()).await;
+                    }
+                    return Err(err);
+                }
             }
-            self.event_emitters
-                .emit(Event::StatusChanged(tuner.index))
-                .await;
+            self.event_emitters.emit(tuner.status_changed()).await;
             return Ok(tuner.subscribe(user));
         }
 
+        let now = Jst::now();
+        if let Some(reservation) = self.find_reservation(user, now) {
+            let priority = reservation.priority.max(user.priority);
+            let tuner = &mut self.tuners[reservation.tuner_index];
+            if !excluded_tuners.contains(&tuner.index)
+                && tuner.is_supported_type(channel)
+                && !tuner.is_excluded_for(channel)
+            {
+                if tuner.is_reuseable(channel) {
+                    tracing::debug!(tuner.index, %channel, %user.info, "Reuse reserved tuner");
+                    self.event_emitters.emit(tuner.status_changed()).await;
+                    return Ok(tuner.subscribe(user));
+                }
+                if tuner.can_grab(priority) {
+                    tracing::debug!(tuner.index, %channel, %user.info, %priority, "Use reserved tuner");
+                    let filters = Self::make_filter_commands(
+                        tuner,
+                        channel,
+                        &self.config.filters.tuner_filter,
+                    )?;
+                    if tuner.is_active() {
+                        self.preempt_tuner(reservation.tuner_index, user).await;
+                    }
+                    let tuner = &mut self.tuners[reservation.tuner_index];
+                    if let Err(err) = tuner.activate(channel, user.priority, filters, ctx).await {
+                        if tuner.record_activation_error(&err) {
+                            self.event_emitters.emit(tuner.status_changed()).await;
+                        }
+                        return Err(err);
+                    }
+                    self.event_emitters.emit(tuner.status_changed()).await;
+                    return Ok(tuner.subscribe(user));
+                }
+            }
+            tracing::warn!(tuner.index = reservation.tuner_index, %channel, %user.info, "Reserved tuner is unavailable, try other tuners");
+        }
+
+        // Tuners reserved for other users cannot be used unless the priority of the user is
+        // higher than the priority of the reservation.
+        let reserved_tuners = self.reserved_tuners(user, now);
+
         let found = self
             .tuners
             .iter_mut()
+            .filter(|tuner| !excluded_tuners.contains(&tuner.index))
             .filter(|tuner| !tuner.is_excluded_for(channel))
             .find(|tuner| tuner.is_reuseable(channel));
         if let Some(tuner) = found {
             tracing::debug!(tuner.index, %channel, %user.info, "Reuse active tuner");
-            self.event_emitters
-                .emit(Event::StatusChanged(tuner.index))
-                .await;
+            self.event_emitters.emit(tuner.status_changed()).await;
             return Ok(tuner.subscribe(user));
         }
 
-        let found = self
-            .tuners
-            .iter_mut()
-            .filter(|tuner| !tuner.is_excluded_for(channel))
-            .find(|tuner| tuner.is_available_for(channel));
-        if let Some(tuner) = found {
+        // Tuners which failed to activate.  Other tuners are used instead of them.
+        let mut failed_tuners = vec![];
+        loop {
+            let found = self
+                .tuners
+                .iter_mut()
+                .filter(|tuner| !excluded_tuners.contains(&tuner.index))
+                .filter(|tuner| !failed_tuners.contains(&tuner.index))
+                .filter(|tuner| !reserved_tuners.contains(&tuner.index))
+                .filter(|tuner| !tuner.is_excluded_for(channel))
+                .find(|tuner| tuner.is_available_for(channel));
+            let Some(tuner) = found else {
+                break;
+            };
             tracing::debug!(tuner.index, %channel, %user.info, "Use tuner");
             let filters =
                 Self::make_filter_commands(tuner, channel, &self.config.filters.tuner_filter)?;
-            tuner.activate(channel, filters, ctx).await?;
-            self.event_emitters
-                .emit(Event::StatusChanged(tuner.index))
-                .await;
-            return Ok(tuner.subscribe(user));
+            match tuner.activate(channel, user.priority, filters, ctx).await {
+                Ok(_) => {
+                    self.event_emitters.emit(tuner.status_changed()).await;
+                    return Ok(tuner.subscribe(user));
+                }
+                Err(err) => {
+                    tracing::warn!(%err, tuner.index, %channel, %user.info, "Failed to activate, try another tuner");
+                    if tuner.record_activation_error(&err) {
+                        self.event_emitters.emit(tuner.status_changed()).await;
+                    }
+                    failed_tuners.push(tuner.index);
+                }
+            }
         }
 
         // No available tuner at this point.
@@ -239,6 +323,9 @@ impl TunerManager {
             .tuners
             .iter_mut()
             .filter(|tuner| tuner.is_supported_type(channel))
+            .filter(|tuner| !excluded_tuners.contains(&tuner.index))
+            .filter(|tuner| !failed_tuners.contains(&tuner.index))
+            .filter(|tuner| !reserved_tuners.contains(&tuner.index))
             .filter(|tuner| !tuner.is_excluded_for(channel))
             .filter(|tuner| tuner.can_grab(user.priority))
             .min_by(|a, b| a.priority().cmp(&b.priority()));
@@ -246,11 +333,15 @@ impl TunerManager {
             tracing::debug!(tuner.index, %channel, %user.info, %user.priority, "Grab tuner");
             let filters =
                 Self::make_filter_commands(tuner, channel, &self.config.filters.tuner_filter)?;
-            tuner.deactivate();
-            self.event_emitters
-                .emit(Event::StatusChanged(tuner.index))
-                .await;
-            tuner.activate(channel, filters, ctx).await?;
+            let tuner_index = tuner.index;
+            self.preempt_tuner(tuner_index, user).await;
+            let tuner = &mut self.tuners[tuner_index];
+            if let Err(err) = tuner.activate(channel, user.priority, filters, ctx).await {
+                if tuner.record_activation_error(&err) {
+                    self.event_emitters.emit(tuner.status_changed()).await;
+                }
+                return Err(err);
+            }
             return Ok(tuner.subscribe(user));
         }
 
@@ -258,6 +349,168 @@ impl TunerManager {
         Err(Error::TunerUnavailable)
     }
 
+    fn find_reservation(&self, user: &TunerUser, now: DateTime<Jst>) -> Option<TunerReservation> {
+        let user_id = user.get_mirakurun_model().id;
+        self.reservations
+            .iter()
+            .find(|reservation| reservation.is_active_at(now) && reservation.user_id == user_id)
+            .cloned()
+    }
+
+    fn reserved_tuners(&self, user: &TunerUser, now: DateTime<Jst>) -> Vec<usize> {
+        let user_id = user.get_mirakurun_model().id;
+        self.reservations
+            .iter()
+            .filter(|reservation| reservation.is_active_at(now))
+            .filter(|reservation| reservation.user_id != user_id)
+            .filter(|reservation| user.priority <= reservation.priority)
+            .map(|reservation| reservation.tuner_index)
+            .collect()
+    }
+
+    fn load_reservations(&mut self) {
+        fn do_load(path: &Path) -> Result<Vec<TunerReservation>, Error> {
+            let file = std::fs::File::open(path)?;
+            Ok(serde_json::from_reader(file)?)
+        }
+
+        let path = match make_reservations_path(&self.config) {
+            Some(path) if path.exists() => path,
+            _ => return,
+        };
+
+        match do_load(&path) {
+            Ok(reservations) => {
+                tracing::info!(?path, "Loaded");
+                let now = Jst::now();
+                for reservation in reservations.into_iter() {
+                    self.next_reservation_id = self.next_reservation_id.max(reservation.id + 1);
+                    if reservation.end_at <= now {
+                        tracing::debug!(reservation.id, "Expired, discard");
+                        continue;
+                    }
+                    if reservation.tuner_index >= self.tuners.len() {
+                        // The tuner has been removed from config.yml.
+                        tracing::warn!(
+                            reservation.id,
+                            reservation.tuner_index,
+                            "No such tuner, discard"
+                        );
+                        continue;
+                    }
+                    self.reservations.push(reservation);
+                }
+            }
+            Err(err) => {
+                tracing::warn!(%err, ?path, "Failed to load");
+            }
+        }
+    }
+
+    fn save_reservations(&self) {
+        let path = match make_reservations_path(&self.config) {
+            Some(path) => path,
+            None => return,
+        };
+
+        if file_util::save_json(&self.reservations, &path) {
+            tracing::debug!(
+                reservations.len = self.reservations.len(),
+                "Saved reservations"
+            );
+        } else {
+            tracing::error!("Failed to save reservations");
+        }
+    }
+
+    fn remove_expired_reservations(&mut self, now: DateTime<Jst>) {
+        self.reservations
+            .retain(|reservation| reservation.end_at > now);
+    }
+
+    fn add_reservation(&mut self, msg: AddTunerReservation) -> Result<TunerReservation, Error> {
+        let now = Jst::now();
+        self.remove_expired_reservations(now);
+
+        let tuner = self
+            .tuners
+            .get(msg.tuner_index)
+            .ok_or(Error::TunerNotFound)?;
+        if !matches!(tuner.restriction, Restriction::None) {
+            let err = Error::InvalidRequest("The tuner is dedicated to another feature");
+            tracing::error!(%err, tuner.index);
+            return Err(err);
+        }
+        if msg.start_at >= msg.end_at {
+            let err = Error::InvalidRequest("endAt must be later than startAt");
+            tracing::error!(%err, %msg.start_at, %msg.end_at);
+            return Err(err);
+        }
+        if msg.end_at <= now {
+            let err = Error::InvalidRequest("endAt must be a future time");
+            tracing::error!(%err, %msg.end_at);
+            return Err(err);
+        }
+        let overlapped = self.reservations.iter().any(|reservation| {
+            reservation.tuner_index == msg.tuner_index
+                && reservation.overlaps(msg.start_at, msg.end_at)
+        });
+        if overlapped {
+            tracing::error!(tuner.index, %msg.start_at, %msg.end_at, "Overlapped with another reservation");
+            return Err(Error::AlreadyExists);
+        }
+
+        let reservation = TunerReservation {
+            id: self.next_reservation_id,
+            tuner_index: msg.tuner_index,
+            user_id: msg.user_id,
+            priority: msg.priority,
+            start_at: msg.start_at,
+            end_at: msg.end_at,
+        };
+        self.next_reservation_id += 1;
+        tracing::info!(
+            reservation.id,
+            reservation.tuner_index,
+            reservation.user_id,
+            %reservation.priority,
+            %reservation.start_at,
+            %reservation.end_at,
+            "Added a tuner reservation"
+        );
+        self.reservations.push(reservation.clone());
+        self.save_reservations();
+        Ok(reservation)
+    }
+
+    fn remove_reservation(&mut self, id: u64) -> Result<TunerReservation, Error> {
+        self.remove_expired_reservations(Jst::now());
+        let index = self
+            .reservations
+            .iter()
+            .position(|reservation| reservation.id == id)
+            .ok_or(Error::ReservationNotFound)?;
+        let reservation = self.reservations.remove(index);
+        tracing::info!(reservation.id, "Removed a tuner reservation");
+        self.save_reservations();
+        Ok(reservation)
+    }
+
+    fn reservations_changed(&self) -> Event {
+        Event::ReservationsChanged(self.reservations.clone())
+    }
+
+    // The users of the tuner must be notified of the preemption before deactivating the tuner.
+    // Otherwise, the users may handle the end of the stream before the preemption.
+    async fn preempt_tuner(&mut self, tuner_index: usize, user: &TunerUser) {
+        for event in self.tuners[tuner_index].preemption_events(user) {
+            self.event_emitters.emit(event).await;
+        }
+        let tuner = &mut self.tuners[tuner_index];
+        tuner.deactivate();
+        self.event_emitters.emit(tuner.status_changed()).await;
+    }
+
     fn deactivate_tuner(&mut self, id: TunerSubscriptionId) {
         self.tuners[id.session_id.tuner_index].deactivate();
     }
@@ -267,9 +520,7 @@ impl TunerManager {
         id: TunerSubscriptionId,
     ) -> Result<Option<TunerUser>, Error> {
         let tuner = &mut self.tuners[id.session_id.tuner_index];
-        self.event_emitters
-            .emit(Event::StatusChanged(tuner.index))
-            .await;
+        self.event_emitters.emit(tuner.status_changed()).await;
         tuner.stop_streaming(id).await
     }
 
@@ -317,10 +568,29 @@ impl TunerManager {
 
 #[async_trait]
 impl Actor for TunerManager {
-    async fn started(&mut self, _ctx: &mut Context<Self>) {
+    async fn started(&mut self, ctx: &mut Context<Self>) {
         // It's guaranteed that no response is sent before tuners are loaded.
         tracing::debug!("Started");
         self.load_tuners();
+        self.load_reservations();
+        for tuner in self.tuners.iter_mut() {
+            tuner.observer = Some(ctx.emitter());
+            if let Some(ref remote) = tuner.remote {
+                ctx.spawn_task(poll_upstream_tuners(
+                    tuner.index,
+                    remote.config.clone(),
+                    remote.client.clone(),
+                    ctx.address().clone(),
+                ));
+            }
+            if !tuner.health.config.probe_command.is_empty() {
+                ctx.spawn_task(schedule_probes(
+                    tuner.index,
+                    tuner.health.config.probe_interval,
+                    ctx.address().clone(),
+                ));
+            }
+        }
     }
 
     async fn stopping(&mut self, _ctx: &mut Context<Self>) {
@@ -374,7 +644,10 @@ impl Handler<RegisterEmitterPostProcess> for TunerManager {
     async fn handle(&mut self, msg: RegisterEmitterPostProcess, _ctx: &mut Context<Self>) {
         let emitter = msg.0;
         for tuner in self.tuners.iter().filter(|tuner| tuner.is_active()) {
-            emitter.emit(Event::StatusChanged(tuner.index)).await;
+            emitter.emit(tuner.status_changed()).await;
+        }
+        if !self.reservations.is_empty() {
+            emitter.emit(self.reservations_changed()).await;
         }
     }
 }
@@ -427,10 +700,195 @@ impl Handler<QueryTuner> for TunerManager {
         _ctx: &mut Context<Self>,
     ) -> <QueryTuner as Message>::Reply {
         tracing::debug!(msg.name = "QueryTuner", msg.index = msg.0);
-        self.tuners
-            .get(msg.0)
-            .map(|tuner| tuner.get_mirakurun_model())
-            .ok_or(Error::TunerNotFound)
+        let tuner = self.tuners.get(msg.0).ok_or(Error::TunerNotFound)?;
+        let mut model = tuner.get_mirakurun_model();
+        model.stats = tuner.activity.query_stats().await;
+        Ok(model)
+    }
+}
+
+// query tuner stats
+
+#[derive(Message)]
+#[reply(Result<TunerSessionStats, Error>)]
+pub struct QueryTunerStats(pub usize);
+
+#[async_trait]
+impl Handler<QueryTunerStats> for TunerManager {
+    async fn handle(
+        &mut self,
+        msg: QueryTunerStats,
+        _ctx: &mut Context<Self>,
+    ) -> <QueryTunerStats as Message>::Reply {
+        tracing::debug!(msg.name = "QueryTunerStats", msg.index = msg.0);
+        let tuner = self.tuners.get(msg.0).ok_or(Error::TunerNotFound)?;
+        tuner.activity.query_stats().await.ok_or(Error::NoContent)
+    }
+}
+
+// query tuner reservations
+
+#[derive(Message)]
+#[reply(Vec<TunerReservation>)]
+pub struct QueryTunerReservations;
+
+#[async_trait]
+impl Handler<QueryTunerReservations> for TunerManager {
+    async fn handle(
+        &mut self,
+        _msg: QueryTunerReservations,
+        _ctx: &mut Context<Self>,
+    ) -> <QueryTunerReservations as Message>::Reply {
+        tracing::debug!(msg.name = "QueryTunerReservations");
+        self.remove_expired_reservations(Jst::now());
+        self.reservations.clone()
+    }
+}
+
+// add tuner reservation
+
+#[derive(Message)]
+#[reply(Result<TunerReservation, Error>)]
+pub struct AddTunerReservation {
+    pub tuner_index: usize,
+    pub user_id: String,
+    pub priority: TunerUserPriority,
+    pub start_at: DateTime<Jst>,
+    pub end_at: DateTime<Jst>,
+}
+
+#[async_trait]
+impl Handler<AddTunerReservation> for TunerManager {
+    async fn handle(
+        &mut self,
+        msg: AddTunerReservation,
+        _ctx: &mut Context<Self>,
+    ) -> <AddTunerReservation as Message>::Reply {
+        tracing::debug!(
+            msg.name = "AddTunerReservation",
+            msg.tuner_index,
+            msg.user_id,
+            %msg.priority,
+            %msg.start_at,
+            %msg.end_at,
+        );
+        let reservation = self.add_reservation(msg)?;
+        self.event_emitters.emit(self.reservations_changed()).await;
+        Ok(reservation)
+    }
+}
+
+// remove tuner reservation
+
+#[derive(Message)]
+#[reply(Result<TunerReservation, Error>)]
+pub struct RemoveTunerReservation {
+    pub id: u64,
+}
+
+#[async_trait]
+impl Handler<RemoveTunerReservation> for TunerManager {
+    async fn handle(
+        &mut self,
+        msg: RemoveTunerReservation,
+        _ctx: &mut Context<Self>,
+    ) -> <RemoveTunerReservation as Message>::Reply {
+        tracing::debug!(msg.name = "RemoveTunerReservation", msg.id);
+        let reservation = self.remove_reservation(msg.id)?;
+        self.event_emitters.emit(self.reservations_changed()).await;
+        Ok(reservation)
+    }
+}
+
+// source ended
+
+#[async_trait]
+impl Handler<SourceEnded> for TunerManager {
+    async fn handle(&mut self, msg: SourceEnded, _ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "SourceEnded", %msg.id, ?msg.reason, msg.received_bytes);
+        let tuner = &mut self.tuners[msg.id.tuner_index];
+        if tuner.handle_source_ended(msg) {
+            self.event_emitters.emit(tuner.status_changed()).await;
+        }
+    }
+}
+
+// probe tuner
+
+#[derive(Message)]
+struct ProbeTuner(usize);
+
+#[async_trait]
+impl Handler<ProbeTuner> for TunerManager {
+    async fn handle(&mut self, msg: ProbeTuner, ctx: &mut Context<Self>) {
+        tracing::trace!(msg.name = "ProbeTuner", msg.index = msg.0);
+        let tuner = &self.tuners[msg.0];
+        if tuner.is_active() {
+            // The tuner is working.
+            return;
+        }
+        let index = tuner.index;
+        let command = tuner.health.config.probe_command.clone();
+        let addr = ctx.address().clone();
+        ctx.spawn_task(async move {
+            let result = run_probe_command(&command).await;
+            addr.emit(ProbeTunerDone { index, result }).await;
+        });
+    }
+}
+
+#[derive(Message)]
+struct ProbeTunerDone {
+    index: usize,
+    result: Result<(), String>,
+}
+
+#[async_trait]
+impl Handler<ProbeTunerDone> for TunerManager {
+    async fn handle(&mut self, msg: ProbeTunerDone, _ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "ProbeTunerDone", msg.index, ?msg.result);
+        let tuner = &mut self.tuners[msg.index];
+        if tuner.is_active() {
+            // The result may be affected by the activation.
+            return;
+        }
+        let changed = match msg.result {
+            Ok(_) => tuner.health.record_success(),
+            Err(err) => {
+                tuner.health.record_failure(tuner.index, err);
+                true
+            }
+        };
+        if changed {
+            self.event_emitters.emit(tuner.status_changed()).await;
+        }
+    }
+}
+
+// update upstream tuners
+
+#[derive(Message)]
+struct UpdateUpstreamTuners {
+    index: usize,
+    tuners: Option<Vec<UpstreamTuner>>,
+}
+
+#[async_trait]
+impl Handler<UpdateUpstreamTuners> for TunerManager {
+    async fn handle(&mut self, msg: UpdateUpstreamTuners, _ctx: &mut Context<Self>) {
+        tracing::trace!(msg.name = "UpdateUpstreamTuners", msg.index);
+        let tuner = &mut self.tuners[msg.index];
+        let remote = tuner.remote.as_mut().unwrap();
+        let was_available = remote.is_available();
+        remote.upstream_tuners = msg.tuners;
+        if remote.is_available() != was_available {
+            tracing::info!(
+                tuner.index,
+                available = remote.is_available(),
+                "Availability of the upstream server changed"
+            );
+            self.event_emitters.emit(tuner.status_changed()).await;
+        }
     }
 }
 
@@ -442,6 +900,8 @@ pub struct StartStreaming {
     pub channel: EpgChannel,
     pub user: TunerUser,
     pub stream_id: Option<TunerSubscriptionId>,
+    // Indexes of tuners which must not be used for the streaming.
+    pub excluded_tuners: Vec<usize>,
 }
 
 #[async_trait]
@@ -459,7 +919,13 @@ impl Handler<StartStreaming> for TunerManager {
         }
 
         let subscription = self
-            .activate_tuner(&msg.channel, &msg.user, &msg.stream_id, ctx)
+            .activate_tuner(
+                &msg.channel,
+                &msg.user,
+                &msg.stream_id,
+                &msg.excluded_tuners,
+                ctx,
+            )
             .await?;
 
         let result = subscription
@@ -526,7 +992,14 @@ impl Handler<StopStreaming> for TunerManager {
 
 #[derive(Clone, Message)]
 pub enum Event {
-    StatusChanged(usize),
+    StatusChanged(usize, TunerHealth),
+    ReservationsChanged(Vec<TunerReservation>),
+    // Sent for each user of a tuner grabbed by another user having a higher priority.
+    Preempted {
+        tuner_index: usize,
+        user: TunerUser,
+        replaced_by: TunerUser,
+    },
 }
 
 // tuner
@@ -536,12 +1009,18 @@ struct Tuner {
     name: String,
     channel_types: Vec<ChannelType>,
     command: String,
+    remote: Option<RemoteTuner>,
     time_limit: u64,
     decoded: bool,
     excluded_channels: Vec<ExcludedChannelConfig>,
-    reserved_for: Option<TunerUserInfo>,
+    // Multiple timeshift recorders recording services on the same channel can share a tuner.
+    reserved_for: Vec<TunerUserInfo>,
     restriction: Restriction,
     activity: TunerActivity,
+    health: TunerHealthState,
+    signal_level: TunerSignalLevelConfig,
+    // Notified when a stream from the tuner ends.
+    observer: Option<Emitter<SourceEnded>>,
 }
 
 enum Restriction {
@@ -557,30 +1036,41 @@ impl Tuner {
             name: config.name.clone(),
             channel_types: config.channel_types.clone(),
             command: config.command.clone(),
+            remote: config.remote.clone().map(RemoteTuner::new),
             time_limit: config.time_limit,
             decoded: config.decoded,
             excluded_channels: config.excluded_channels.clone(),
-            reserved_for: None,
+            reserved_for: vec![],
             restriction: Restriction::None,
             activity: TunerActivity::Inactive,
+            health: TunerHealthState::new(config.health_check.clone()),
+            signal_level: config.signal_level.clone(),
+            observer: None,
         }
     }
 
-    fn set_channel_user(
+    fn add_channel_user(
         &mut self,
         user: TunerUserInfo,
         channel_type: ChannelType,
         channel: String,
     ) {
-        assert!(self.reserved_for.is_none());
         assert!(self.channel_types.contains(&channel_type));
-        self.reserved_for = Some(user);
-        self.restriction = Restriction::Channel(channel_type, channel);
+        match self.restriction {
+            Restriction::None => {
+                self.restriction = Restriction::Channel(channel_type, channel);
+            }
+            Restriction::Channel(ch_type, ref ch) => {
+                assert!(ch_type == channel_type && *ch == channel);
+            }
+            Restriction::Exclusive => unreachable!(),
+        }
+        self.reserved_for.push(user);
     }
 
     fn set_exclusive_user(&mut self, user: TunerUserInfo) {
-        assert!(self.reserved_for.is_none());
-        self.reserved_for = Some(user);
+        assert!(self.reserved_for.is_empty());
+        self.reserved_for.push(user);
         self.restriction = Restriction::Exclusive;
     }
 
@@ -597,7 +1087,20 @@ impl Tuner {
     }
 
     fn is_supported_type(&self, channel: &EpgChannel) -> bool {
-        self.channel_types.contains(&channel.channel_type)
+        if !self.channel_types.contains(&channel.channel_type) {
+            return false;
+        }
+        match self.remote {
+            Some(ref remote) => remote.is_supported_type(channel),
+            None => true,
+        }
+    }
+
+    fn is_upstream_free_for(&self, channel: &EpgChannel) -> bool {
+        match self.remote {
+            Some(ref remote) => remote.is_free_for(channel),
+            None => true,
+        }
     }
 
     fn is_excluded_for(&self, channel: &EpgChannel) -> bool {
@@ -606,13 +1109,24 @@ impl Tuner {
             .any(|excluded| excluded.matches(channel))
     }
 
+    fn is_quarantined(&self) -> bool {
+        self.health.is_quarantined()
+    }
+
     fn is_available_for(&self, channel: &EpgChannel) -> bool {
+        if self.is_quarantined() {
+            return false;
+        }
         match self.restriction {
             Restriction::Channel(ch_type, ref ch) => {
                 ch_type == channel.channel_type && ch.as_str() == channel.channel
             }
             Restriction::Exclusive => false,
-            _ => self.is_available() && self.is_supported_type(channel),
+            _ => {
+                self.is_available()
+                    && self.is_supported_type(channel)
+                    && self.is_upstream_free_for(channel)
+            }
         }
     }
 
@@ -624,13 +1138,13 @@ impl Tuner {
     }
 
     fn is_reserved_for(&self, user: &TunerUser) -> bool {
-        match self.reserved_for {
-            Some(ref user_info) => user_info.eq(&user.info),
-            None => false,
-        }
+        self.reserved_for.contains(&user.info)
     }
 
     fn can_grab(&self, priority: TunerUserPriority) -> bool {
+        if self.is_quarantined() {
+            return false;
+        }
         match self.restriction {
             Restriction::None => priority.is_grab() || self.activity.can_grab(priority),
             _ => false,
@@ -644,22 +1158,116 @@ impl Tuner {
     async fn activate<C>(
         &mut self,
         channel: &EpgChannel,
+        priority: TunerUserPriority,
         filters: Vec<String>,
         ctx: &C,
     ) -> Result<(), Error>
     where
         C: Spawn,
     {
-        let command = match self.make_command(channel) {
-            Ok(command) => command,
-            Err(err) => {
-                tracing::error!(%err, tuner.index = self.index, %channel, "Failed to render the tuner command");
-                return Err(err);
+        let input = match self.remote {
+            Some(ref mut remote) => {
+                remote
+                    .open(self.index, channel, self.decoded, priority)
+                    .await?
             }
+            None => match self.make_command(channel) {
+                Ok(command) => TunerInput::Command(command),
+                Err(err) => {
+                    tracing::error!(%err, tuner.index = self.index, %channel, "Failed to render the tuner command");
+                    return Err(err);
+                }
+            },
         };
         self.activity
-            .activate(self.index, channel, command, filters, self.time_limit, ctx)
-            .await
+            .activate(
+                self.index,
+                channel,
+                input,
+                filters,
+                self.time_limit,
+                self.observer.clone(),
+                ctx,
+            )
+            .await?;
+        if !self.signal_level.command.is_empty() {
+            match self.render_command(&self.signal_level.command, channel) {
+                Ok(command) => {
+                    self.activity
+                        .monitor_signal_level(command, self.signal_level.interval, ctx);
+                }
+                Err(err) => {
+                    tracing::warn!(%err, tuner.index = self.index, %channel, "Failed to render the signal-level command");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Returns `true` if the health state changed.
+    fn record_activation_error(&mut self, err: &Error) -> bool {
+        match err {
+            // No tuner is available in the upstream server.  This is not a failure of the tuner.
+            Error::TunerUnavailable => false,
+            _ => {
+                self.health.record_failure(self.index, err.to_string());
+                true
+            }
+        }
+    }
+
+    // Returns `true` if the health state changed.
+    fn handle_source_ended(&mut self, msg: SourceEnded) -> bool {
+        if self.activity.session_id() != Some(msg.id) {
+            // Deactivated before the stream ended.
+            if msg.received_bytes > 0 {
+                return self.health.record_success();
+            }
+            return false;
+        }
+        match msg.reason {
+            StreamEndReason::Eof if msg.received_bytes > 0 => self.health.record_success(),
+            StreamEndReason::Eof => {
+                let err = "The tuner stream ended without data".to_string();
+                self.health.record_failure(self.index, err);
+                true
+            }
+            StreamEndReason::TimedOut => {
+                self.health.record_no_data_timeout(self.index);
+                true
+            }
+            StreamEndReason::Error(err) => {
+                self.health.record_failure(self.index, err);
+                true
+            }
+        }
+    }
+
+    fn status_changed(&self) -> Event {
+        Event::StatusChanged(self.index, self.health.get_model())
+    }
+
+    // Returns events to notify the users of the tuner that the tuner is grabbed by `user`.
+    fn preemption_events(&self, user: &TunerUser) -> Vec<Event> {
+        self.activity
+            .users()
+            .into_iter()
+            .map(|preempted| {
+                tracing::info!(
+                    tuner.index = self.index,
+                    %preempted.info,
+                    %preempted.priority,
+                    %user.info,
+                    %user.priority,
+                    "Preempted",
+                );
+                Event::Preempted {
+                    tuner_index: self.index,
+                    user: preempted,
+                    replaced_by: user.clone(),
+                }
+            })
+            .collect()
     }
 
     fn deactivate(&mut self) {
@@ -689,16 +1297,26 @@ impl Tuner {
             command,
             pid,
             users,
-            is_available: true,
-            is_remote: false,
+            is_available: self
+                .remote
+                .as_ref()
+                .is_none_or(|remote| remote.is_available()),
+            is_remote: self.remote.is_some(),
             is_free: self.is_available(),
             is_using: !self.is_available(),
-            is_fault: false,
+            is_fault: self.is_quarantined(),
+            health: self.health.get_model(),
+            // Filled only when a single tuner is queried.
+            stats: None,
         }
     }
 
     fn make_command(&self, channel: &EpgChannel) -> Result<String, Error> {
-        let template = mustache::compile_str(&self.command)?;
+        self.render_command(&self.command, channel)
+    }
+
+    fn render_command(&self, template: &str, channel: &EpgChannel) -> Result<String, Error> {
+        let template = mustache::compile_str(template)?;
         let data = mustache::MapBuilder::new()
             .insert("channel_type", &channel.channel_type)?
             .insert_str("channel", &channel.channel)
@@ -738,13 +1356,15 @@ enum TunerActivity {
 }
 
 impl TunerActivity {
+    #[allow(clippy::too_many_arguments)]
     async fn activate<C>(
         &mut self,
         tuner_index: usize,
         channel: &EpgChannel,
-        command: String,
+        input: TunerInput,
         filters: Vec<String>,
         time_limit: u64,
+        observer: Option<Emitter<SourceEnded>>,
         ctx: &C,
     ) -> Result<(), Error>
     where
@@ -752,9 +1372,16 @@ impl TunerActivity {
     {
         match self {
             Self::Inactive => {
-                let session =
-                    TunerSession::new(tuner_index, channel, command, filters, time_limit, ctx)
-                        .await?;
+                let session = TunerSession::new(
+                    tuner_index,
+                    channel,
+                    input,
+                    filters,
+                    time_limit,
+                    observer,
+                    ctx,
+                )
+                .await?;
                 *self = Self::Active(Box::new(session));
                 Ok(())
             }
@@ -773,6 +1400,13 @@ impl TunerActivity {
         }
     }
 
+    fn session_id(&self) -> Option<TunerSessionId> {
+        match self {
+            Self::Inactive => None,
+            Self::Active(session) => Some(session.id),
+        }
+    }
+
     fn is_active(&self) -> bool {
         match self {
             Self::Inactive => false,
@@ -834,57 +1468,146 @@ impl TunerActivity {
             Self::Active(session) => session.get_mirakurun_models(),
         }
     }
+
+    fn users(&self) -> Vec<TunerUser> {
+        match self {
+            Self::Inactive => vec![],
+            Self::Active(session) => session.subscribers.values().cloned().collect(),
+        }
+    }
+
+    fn monitor_signal_level<C>(&mut self, command: String, interval: Duration, ctx: &C)
+    where
+        C: Spawn,
+    {
+        match self {
+            Self::Inactive => panic!("Must be activated before monitoring the signal level"),
+            Self::Active(session) => session.monitor_signal_level(command, interval, ctx),
+        }
+    }
+
+    async fn query_stats(&self) -> Option<TunerSessionStats> {
+        match self {
+            Self::Inactive => None,
+            Self::Active(session) => session.query_stats().await,
+        }
+    }
 }
 
 // session
 
+enum TunerInput {
+    Command(String),
+    Remote(Url, reqwest::Response),
+}
+
+enum TunerSource {
+    Command(CommandPipeline<TunerSessionId>),
+    Remote {
+        url: Url,
+        // A pipeline of tuner filters, if any.  Held until the session is deactivated.
+        _filters: Option<CommandPipeline<TunerSessionId>>,
+        // Used for closing the connection to the upstream server.
+        relay: CancellationToken,
+    },
+}
+
 struct TunerSession {
     id: TunerSessionId,
     channel: EpgChannel,
     // Used for closing the tuner in order to take over the right to use it.
-    pipeline: CommandPipeline<TunerSessionId>,
+    source: TunerSource,
     broadcaster: Address<Broadcaster>,
     subscribers: HashMap<u32, TunerUser>,
     next_serial_number: u32,
+    // Used for stopping the periodic execution of the signal-level command.
+    signal_level_monitor: Option<CancellationToken>,
 }
 
 impl TunerSession {
+    // The size of the buffer used for relaying data from an upstream server.
+    const RELAY_BUFFER_SIZE: usize = 4096 * 8;
+
     async fn new<C>(
         tuner_index: usize,
         channel: &EpgChannel,
-        command: String,
+        input: TunerInput,
         mut filters: Vec<String>,
         time_limit: u64,
+        observer: Option<Emitter<SourceEnded>>,
         ctx: &C,
     ) -> Result<TunerSession, Error>
     where
         C: Spawn,
     {
         let id = TunerSessionId::new(tuner_index);
-        let mut commands = vec![command];
-        commands.append(&mut filters);
-        let mut pipeline = match spawn_pipeline(commands, id, "tuner", ctx) {
-            Ok(pipeline) => pipeline,
-            Err(err) => {
-                tracing::error!(%err, session.id = %id, %channel, "Failed to spawn a tuner pipeline");
-                return Err(err.into());
+        let (source, output): (_, Box<dyn AsyncRead + Send + Unpin>) = match input {
+            TunerInput::Command(command) => {
+                let mut commands = vec![command];
+                commands.append(&mut filters);
+                let mut pipeline = Self::spawn_pipeline(commands, id, channel, ctx)?;
+                let (_, output) = pipeline.take_endpoints();
+                (TunerSource::Command(pipeline), Box::new(output))
+            }
+            TunerInput::Remote(url, response) if filters.is_empty() => {
+                let (output, input) = tokio::io::simplex(Self::RELAY_BUFFER_SIZE);
+                let (_, relay) = ctx.spawn_task(relay_upstream_stream(id, response, input));
+                let source = TunerSource::Remote {
+                    url,
+                    _filters: None,
+                    relay,
+                };
+                (source, Box::new(output))
+            }
+            TunerInput::Remote(url, response) => {
+                let mut pipeline = Self::spawn_pipeline(filters, id, channel, ctx)?;
+                let (input, output) = pipeline.take_endpoints();
+                let (_, relay) = ctx.spawn_task(relay_upstream_stream(id, response, input));
+                let source = TunerSource::Remote {
+                    url,
+                    _filters: Some(pipeline),
+                    relay,
+                };
+                (source, Box::new(output))
             }
         };
-        let (_, output) = pipeline.take_endpoints();
-        let broadcaster = ctx.spawn_actor(Broadcaster::new(id, time_limit)).await;
+        let broadcaster = match observer {
+            Some(observer) => Broadcaster::new(id, time_limit).with_observer(observer),
+            None => Broadcaster::new(id, time_limit),
+        };
+        let broadcaster = ctx.spawn_actor(broadcaster).await;
         broadcaster.emit(BindStream(output)).await;
         tracing::debug!(session.id = %id, %channel, "Activated");
 
         Ok(TunerSession {
             id,
             channel: channel.clone(),
-            pipeline,
+            source,
             broadcaster,
             subscribers: HashMap::new(),
             next_serial_number: 1,
+            signal_level_monitor: None,
         })
     }
 
+    fn spawn_pipeline<C>(
+        commands: Vec<String>,
+        id: TunerSessionId,
+        channel: &EpgChannel,
+        ctx: &C,
+    ) -> Result<CommandPipeline<TunerSessionId>, Error>
+    where
+        C: Spawn,
+    {
+        match spawn_pipeline(commands, id, "tuner", ctx) {
+            Ok(pipeline) => Ok(pipeline),
+            Err(err) => {
+                tracing::error!(%err, session.id = %id, %channel, "Failed to spawn a tuner pipeline");
+                Err(err.into())
+            }
+        }
+    }
+
     fn is_subscribed(&self, id: &TunerSubscriptionId) -> bool {
         self.subscribers.contains_key(&id.serial_number)
     }
@@ -940,8 +1663,13 @@ impl TunerSession {
     }
 
     fn get_mirakurun_models(&self) -> (Option<String>, Option<u32>, Vec<MirakurunTunerUser>) {
-        let command = self.pipeline.get_command(0).map(|s| s.to_string());
-        let pids = self.pipeline.pids().iter().cloned().next().flatten();
+        let (command, pids) = match self.source {
+            TunerSource::Command(ref pipeline) => (
+                pipeline.get_command(0).map(|s| s.to_string()),
+                pipeline.pids().iter().cloned().next().flatten(),
+            ),
+            TunerSource::Remote { ref url, .. } => (Some(url.to_string()), None),
+        };
         let users = self
             .subscribers
             .values()
@@ -949,16 +1677,371 @@ impl TunerSession {
             .collect();
         (command, pids, users)
     }
+
+    fn monitor_signal_level<C>(&mut self, command: String, interval: Duration, ctx: &C)
+    where
+        C: Spawn,
+    {
+        let task = monitor_signal_level(self.id, command, interval, self.broadcaster.clone());
+        let (_, monitor) = ctx.spawn_task(task);
+        self.signal_level_monitor = Some(monitor);
+    }
+
+    async fn query_stats(&self) -> Option<TunerSessionStats> {
+        match self.broadcaster.call(QueryStats).await {
+            Ok(stats) => Some(stats),
+            Err(err) => {
+                // The broadcaster has already stopped.
+                tracing::debug!(%err, session.id = %self.id, "Failed to query stats");
+                None
+            }
+        }
+    }
 }
 
 impl Drop for TunerSession {
     fn drop(&mut self) {
+        if let TunerSource::Remote { ref relay, .. } = self.source {
+            relay.cancel();
+        }
+        if let Some(ref monitor) = self.signal_level_monitor {
+            monitor.cancel();
+        }
         tracing::debug!(session.id = %self.id, "Deactivated");
     }
 }
 
+async fn relay_upstream_stream<W>(
+    id: TunerSessionId,
+    mut response: reqwest::Response,
+    mut output: W,
+) where
+    W: AsyncWrite + Unpin,
+{
+    loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                if let Err(err) = output.write_all(&chunk).await {
+                    tracing::debug!(%err, session.id = %id, "Stopped relaying");
+                    break;
+                }
+            }
+            Ok(None) => {
+                tracing::debug!(session.id = %id, "Upstream stream ended");
+                break;
+            }
+            Err(err) => {
+                tracing::error!(%err, session.id = %id, "Upstream stream broken");
+                break;
+            }
+        }
+    }
+}
+
+async fn monitor_signal_level(
+    id: TunerSessionId,
+    command: String,
+    interval: Duration,
+    broadcaster: Address<Broadcaster>,
+) {
+    loop {
+        match run_signal_level_command(&command).await {
+            Ok(level) => broadcaster.emit(UpdateSignalLevel(level)).await,
+            Err(err) => {
+                tracing::warn!(%err, session.id = %id, "Failed to get the signal level");
+            }
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+async fn run_signal_level_command(command: &str) -> Result<f64, String> {
+    let mut builder = CommandBuilder::new(command).map_err(|err| err.to_string())?;
+    let child = builder
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| err.to_string())?;
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|err| err.to_string())?;
+    if !output.status.success() {
+        return Err(format!(
+            "The signal-level command failed: {}",
+            output.status
+        ));
+    }
+    let output = String::from_utf8_lossy(&output.stdout);
+    parse_signal_level(&output)
+        .ok_or_else(|| format!("No signal level in the output: {}", output.trim()))
+}
+
+// Parses the first number in the output of a signal-level command.
+//
+// Output formats vary between commands.  For example, "25.50dB", "Signal: 25.50 dB" and
+// "SignalLevel 25.50".
+fn parse_signal_level(output: &str) -> Option<f64> {
+    output
+        .split(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .filter(|s| !s.is_empty())
+        .find_map(|s| s.parse::<f64>().ok())
+}
+
+fn make_reservations_path(config: &Config) -> Option<PathBuf> {
+    config
+        .tuner_reservations
+        .basedir
+        .as_ref()
+        .map(|basedir| basedir.join("reservations.v1.json"))
+}
+
+// health
+
+struct TunerHealthState {
+    config: TunerHealthCheckConfig,
+    consecutive_failures: u32,
+    no_data_timeouts: u32,
+    last_error: Option<String>,
+    // The number of quarantines since the last success.  Used for the exponential backoff.
+    num_quarantines: u32,
+    quarantined_until: Option<DateTime<Jst>>,
+}
+
+impl TunerHealthState {
+    fn new(config: TunerHealthCheckConfig) -> Self {
+        TunerHealthState {
+            config,
+            consecutive_failures: 0,
+            no_data_timeouts: 0,
+            last_error: None,
+            num_quarantines: 0,
+            quarantined_until: None,
+        }
+    }
+
+    fn is_quarantined(&self) -> bool {
+        self.quarantined_until
+            .is_some_and(|until| Jst::now() < until)
+    }
+
+    // Returns `true` if the health state changed.
+    fn record_success(&mut self) -> bool {
+        let changed = self.consecutive_failures > 0 || self.quarantined_until.is_some();
+        self.consecutive_failures = 0;
+        self.num_quarantines = 0;
+        self.quarantined_until = None;
+        changed
+    }
+
+    fn record_failure(&mut self, tuner_index: usize, err: String) {
+        self.consecutive_failures += 1;
+        tracing::warn!(
+            tuner.index = tuner_index,
+            err,
+            consecutive_failures = self.consecutive_failures,
+            "Tuner failure"
+        );
+        self.last_error = Some(err);
+        if self.config.max_failures == 0 || self.consecutive_failures < self.config.max_failures {
+            return;
+        }
+        let backoff = self
+            .config
+            .quarantine_time
+            .saturating_mul(2u32.saturating_pow(self.num_quarantines))
+            .min(self.config.max_quarantine_time);
+        self.quarantined_until = Some(Jst::now() + backoff);
+        self.num_quarantines += 1;
+        tracing::warn!(
+            tuner.index = tuner_index,
+            quarantine_time = %humantime::format_duration(backoff),
+            "Quarantined"
+        );
+    }
+
+    fn record_no_data_timeout(&mut self, tuner_index: usize) {
+        self.no_data_timeouts += 1;
+        let err = "No packet came from the tuner within the time limit".to_string();
+        self.record_failure(tuner_index, err);
+    }
+
+    fn get_model(&self) -> TunerHealth {
+        TunerHealth {
+            consecutive_failures: self.consecutive_failures,
+            no_data_timeouts: self.no_data_timeouts,
+            last_error: self.last_error.clone(),
+            quarantined_until: self.quarantined_until.filter(|_| self.is_quarantined()),
+        }
+    }
+}
+
+async fn schedule_probes(index: usize, interval: Duration, addr: Address<TunerManager>) {
+    loop {
+        tokio::time::sleep(interval).await;
+        addr.emit(ProbeTuner(index)).await;
+    }
+}
+
+async fn run_probe_command(command: &str) -> Result<(), String> {
+    let mut builder = CommandBuilder::new(command).map_err(|err| err.to_string())?;
+    let mut child = builder
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|err| err.to_string())?;
+    match child.wait().await {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("The probe command failed: {status}")),
+        Err(err) => Err(err.to_string()),
+    }
+}
+
+// remote tuner
+
+// A tuner provided by an upstream Mirakurun-compatible server.
+struct RemoteTuner {
+    config: RemoteTunerConfig,
+    client: reqwest::Client,
+    // `None` until the first polling succeeds or after the last polling failed.
+    upstream_tuners: Option<Vec<UpstreamTuner>>,
+}
+
+impl RemoteTuner {
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+    const POLLING_TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn new(config: RemoteTunerConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .connect_timeout(Self::CONNECT_TIMEOUT)
+            .build()
+            .unwrap();
+        RemoteTuner {
+            config,
+            client,
+            upstream_tuners: None,
+        }
+    }
+
+    fn is_available(&self) -> bool {
+        self.usable_upstream_tuners().next().is_some()
+    }
+
+    fn is_supported_type(&self, channel: &EpgChannel) -> bool {
+        self.usable_upstream_tuners()
+            .any(|tuner| tuner.channel_types.contains(&channel.channel_type))
+    }
+
+    fn is_free_for(&self, channel: &EpgChannel) -> bool {
+        self.usable_upstream_tuners()
+            .filter(|tuner| tuner.is_free)
+            .any(|tuner| tuner.channel_types.contains(&channel.channel_type))
+    }
+
+    fn usable_upstream_tuners(&self) -> impl Iterator<Item = &UpstreamTuner> {
+        self.upstream_tuners
+            .iter()
+            .flatten()
+            .filter(|tuner| tuner.is_available && !tuner.is_fault)
+    }
+
+    // This function is called in the tuner manager and blocks other messages until the upstream
+    // server responds or the response timeout expires.
+    async fn open(
+        &mut self,
+        tuner_index: usize,
+        channel: &EpgChannel,
+        decode: bool,
+        priority: TunerUserPriority,
+    ) -> Result<TunerInput, Error> {
+        let url = self
+            .config
+            .stream_url(channel.channel_type, &channel.channel, decode);
+        // The timeout must not be applied to the response body which is the stream.
+        let result = tokio::time::timeout(
+            self.config.response_timeout,
+            self.client
+                .get(url.clone())
+                .header("X-Mirakurun-Priority", priority.to_string())
+                .send(),
+        )
+        .await;
+        let result = match result {
+            Ok(result) => result,
+            Err(_) => {
+                tracing::error!(tuner.index = tuner_index, %channel, "No response from the upstream server");
+                // The tuner is unavailable until the next polling succeeds so that following
+                // requests don't wait for the upstream server again.
+                self.upstream_tuners = None;
+                return Err(anyhow::anyhow!("No response from the upstream server").into());
+            }
+        };
+        match result {
+            Ok(response) if response.status().is_success() => Ok(TunerInput::Remote(url, response)),
+            Ok(response) if response.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE => {
+                tracing::warn!(tuner.index = tuner_index, %channel, %priority, "No tuner available in the upstream server");
+                Err(Error::TunerUnavailable)
+            }
+            Ok(response) => {
+                let status = response.status();
+                tracing::error!(%status, tuner.index = tuner_index, %channel, "Unexpected response from the upstream server");
+                Err(
+                    anyhow::anyhow!("Unexpected response from the upstream server: {status}")
+                        .into(),
+                )
+            }
+            Err(err) => {
+                tracing::error!(%err, tuner.index = tuner_index, %channel, "Failed to connect to the upstream server");
+                Err(anyhow::anyhow!(err).into())
+            }
+        }
+    }
+}
+
+// A subset of properties of a tuner in `/api/tuners` of an upstream server.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpstreamTuner {
+    #[serde(rename = "types")]
+    channel_types: Vec<ChannelType>,
+    is_available: bool,
+    is_free: bool,
+    is_fault: bool,
+}
+
+async fn poll_upstream_tuners(
+    index: usize,
+    config: RemoteTunerConfig,
+    client: reqwest::Client,
+    addr: Address<TunerManager>,
+) {
+    let url = config.tuners_url();
+    loop {
+        let result = async {
+            client
+                .get(url.clone())
+                .timeout(RemoteTuner::POLLING_TIMEOUT)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Vec<UpstreamTuner>>()
+                .await
+        }
+        .await;
+        let tuners = match result {
+            Ok(tuners) => Some(tuners),
+            Err(err) => {
+                tracing::debug!(%err, tuner.index = index, %url, "Failed to get tuners from the upstream server");
+                None
+            }
+        };
+        addr.emit(UpdateUpstreamTuners { index, tuners }).await;
+        tokio::time::sleep(config.polling_interval).await;
+    }
+}
+
 impl ExcludedChannelConfig {
-    fn matches(&self, epg: &EpgChannel) -> bool {
+    pub(crate) fn matches(&self, epg: &EpgChannel) -> bool {
         match self {
             Self::Name(name) => epg.name == *name,
             Self::Params {
@@ -1039,6 +2122,7 @@ mod tests {
                     channel: create_channel("0"),
                     user: create_user(0.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             let stream1 = assert_matches!(result, Ok(Ok(stream)) => {
@@ -1052,6 +2136,7 @@ mod tests {
                     channel: create_channel("0"),
                     user: create_user(1.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             assert_matches!(result, Ok(Ok(stream)) => {
@@ -1065,6 +2150,7 @@ mod tests {
                     channel: create_channel("1"),
                     user: create_user(1.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             assert_matches!(result, Ok(Err(Error::TunerUnavailable)));
@@ -1075,6 +2161,7 @@ mod tests {
                     channel: create_channel("1"),
                     user: create_user(2.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             assert_matches!(result, Ok(Ok(stream)) => {
@@ -1091,6 +2178,7 @@ mod tests {
                         priority: 0.into(),
                     },
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             assert_matches!(result, Ok(Ok(stream)) => {
@@ -1135,6 +2223,7 @@ mod tests {
                         priority: 0.into(),
                     },
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             assert_matches!(result, Ok(Ok(stream)) => {
@@ -1152,6 +2241,7 @@ mod tests {
                     channel: create_channel("0"),
                     user: create_user(0.into()), // not allowed
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             assert_matches!(result, Ok(Err(Error::TunerUnavailable)));
@@ -1194,6 +2284,7 @@ mod tests {
                     channel: create_channel("0"), // allowed
                     user: create_user(0.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             assert_matches!(result, Ok(Ok(stream)) => {
@@ -1211,6 +2302,7 @@ mod tests {
                     channel: create_channel("1"), // not allowed
                     user: create_user(0.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             assert_matches!(result, Ok(Err(Error::TunerUnavailable)));
@@ -1218,6 +2310,80 @@ mod tests {
         system.shutdown().await;
     }
 
+    #[test(tokio::test)]
+    async fn test_shared_by_timeshift_recorders() {
+        let config: Arc<Config> = Arc::new(
+            serde_norway::from_str(
+                r#"
+                tuners:
+                  - name: timeshift
+                    types: [GR]
+                    command: >-
+                      sleep 1
+                timeshift:
+                  recorders:
+                    nhk:
+                      service-id: 1
+                      ts-file: /dev/null
+                      data-file: /dev/null
+                      num-chunks: 10
+                      uses:
+                        tuner: timeshift
+                        channel-type: GR
+                        channel: '0'
+                    etv:
+                      service-id: 2
+                      ts-file: /dev/null
+                      data-file: /dev/null
+                      num-chunks: 10
+                      uses:
+                        tuner: timeshift
+                        channel-type: GR
+                        channel: '0'
+                "#,
+            )
+            .unwrap(),
+        );
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(TunerManager::new(config.clone())).await;
+
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("0"),
+                    user: TunerUser {
+                        info: TunerUserInfo::TimeshiftRecorder("etv".to_string()),
+                        priority: 0.into(),
+                    },
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            let stream1 = assert_matches!(result, Ok(Ok(stream)) => {
+                assert_eq!(stream.id().session_id.tuner_index, 0);
+                stream
+            });
+
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("0"),
+                    user: TunerUser {
+                        info: TunerUserInfo::TimeshiftRecorder("nhk".to_string()),
+                        priority: 0.into(),
+                    },
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            // The tuner stream is shared.
+            assert_matches!(result, Ok(Ok(stream)) => {
+                assert_eq!(stream.id().session_id, stream1.id().session_id);
+            });
+        }
+        system.shutdown().await;
+    }
+
     #[test(tokio::test)]
     async fn test_start_streaming_grab_lowest_priority_tuner() {
         let config: Arc<Config> = Arc::new(
@@ -1246,6 +2412,7 @@ mod tests {
                     channel: create_channel("1"),
                     user: create_user(1.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             let _stream0 = assert_matches!(result, Ok(Ok(stream)) => {
@@ -1258,6 +2425,7 @@ mod tests {
                     channel: create_channel("0"),
                     user: create_user(0.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             let _stream1 = assert_matches!(result, Ok(Ok(stream)) => {
@@ -1271,6 +2439,7 @@ mod tests {
                     channel: create_channel("2"),
                     user: create_user(2.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             assert_matches!(result, Ok(Ok(stream)) => {
@@ -1288,6 +2457,7 @@ mod tests {
                     channel: create_channel("0"),
                     user: create_user(0.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             let _stream0 = assert_matches!(result, Ok(Ok(stream)) => {
@@ -1300,6 +2470,7 @@ mod tests {
                     channel: create_channel("1"),
                     user: create_user(1.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             let _stream1 = assert_matches!(result, Ok(Ok(stream)) => {
@@ -1313,6 +2484,7 @@ mod tests {
                     channel: create_channel("2"),
                     user: create_user(2.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             assert_matches!(result, Ok(Ok(stream)) => {
@@ -1331,6 +2503,7 @@ mod tests {
                     channel: create_channel("0"),
                     user: create_user(0.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             let _stream0 = assert_matches!(result, Ok(Ok(stream)) => {
@@ -1343,6 +2516,7 @@ mod tests {
                     channel: create_channel("1"),
                     user: create_user(0.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             let _stream1 = assert_matches!(result, Ok(Ok(stream)) => {
@@ -1355,6 +2529,7 @@ mod tests {
                     channel: create_channel("0"),
                     user: create_user(0.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             let _stream2 = assert_matches!(result, Ok(Ok(stream)) => {
@@ -1368,6 +2543,7 @@ mod tests {
                     channel: create_channel("2"),
                     user: create_user(2.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             assert_matches!(result, Ok(Ok(stream)) => {
@@ -1386,6 +2562,7 @@ mod tests {
                     channel: create_channel("0"),
                     user: create_user(0.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             let _stream0 = assert_matches!(result, Ok(Ok(stream)) => {
@@ -1398,6 +2575,7 @@ mod tests {
                     channel: create_channel("1"),
                     user: create_user(0.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             let _stream1 = assert_matches!(result, Ok(Ok(stream)) => {
@@ -1411,6 +2589,7 @@ mod tests {
                     channel: create_channel("2"),
                     user: create_user(2.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
             assert_matches!(result, Ok(Ok(stream)) => {
@@ -1421,59 +2600,526 @@ mod tests {
     }
 
     #[test(tokio::test)]
-    async fn test_excluded_channel() {
-        let system = System::new();
-
-        {
-            let config: Arc<Config> = Arc::new(
-                serde_norway::from_str(
-                    r#"
+    async fn test_add_tuner_reservation() {
+        let config: Arc<Config> = Arc::new(
+            serde_norway::from_str(
+                r#"
                 tuners:
                   - name: gr
                     types: [GR]
                     command: >-
                       sleep 1
-                    excluded-channels:
-                      - name: excluded
-                      - params:
-                          channel-type: GR
-                          channel: excluded
+                  - name: tracker
+                    types: [GR]
+                    command: >-
+                      sleep 1
+                onair-program-trackers:
+                  tracker:
+                    local:
+                      channel-types: [GR]
+                      uses:
+                        tuner: tracker
                 "#,
-                )
-                .unwrap(),
-            );
+            )
+            .unwrap(),
+        );
 
-            let manager = system.spawn_actor(TunerManager::new(config)).await;
+        let now = Jst::now();
+        let hour = chrono::Duration::try_hours(1).unwrap();
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(TunerManager::new(config.clone())).await;
 
             let result = manager
-                .call(StartStreaming {
-                    channel: channel_gr!("excluded", "channel"),
-                    user: create_user(0.into()),
-                    stream_id: None,
+                .call(AddTunerReservation {
+                    tuner_index: 0,
+                    user_id: "job:test".to_string(),
+                    priority: 0.into(),
+                    start_at: now,
+                    end_at: now + hour,
                 })
                 .await;
-            assert_matches!(result, Ok(Err(Error::TunerUnavailable)));
+            assert_matches!(result, Ok(Ok(reservation)) => {
+                assert_eq!(reservation.id, 1);
+                assert_eq!(reservation.tuner_index, 0);
+            });
 
+            // Overlapped.
             let result = manager
-                .call(StartStreaming {
-                    channel: channel_gr!("channel", "excluded"),
-                    user: create_user(0.into()),
-                    stream_id: None,
+                .call(AddTunerReservation {
+                    tuner_index: 0,
+                    user_id: "job:test".to_string(),
+                    priority: 0.into(),
+                    start_at: now + hour / 2,
+                    end_at: now + hour * 2,
                 })
                 .await;
-            assert_matches!(result, Ok(Err(Error::TunerUnavailable)));
+            assert_matches!(result, Ok(Err(Error::AlreadyExists)));
 
+            // Adjacent.
             let result = manager
-                .call(StartStreaming {
-                    channel: channel_gr!("channel", "channel"),
-                    user: create_user(0.into()),
-                    stream_id: None,
+                .call(AddTunerReservation {
+                    tuner_index: 0,
+                    user_id: "job:test".to_string(),
+                    priority: 0.into(),
+                    start_at: now + hour,
+                    end_at: now + hour * 2,
                 })
                 .await;
-            assert_matches!(result, Ok(Ok(_)));
-        }
-        system.shutdown().await;
-    }
+            assert_matches!(result, Ok(Ok(reservation)) => {
+                assert_eq!(reservation.id, 2);
+            });
+
+            // Dedicated to an on-air program tracker.
+            let result = manager
+                .call(AddTunerReservation {
+                    tuner_index: 1,
+                    user_id: "job:test".to_string(),
+                    priority: 0.into(),
+                    start_at: now,
+                    end_at: now + hour,
+                })
+                .await;
+            assert_matches!(result, Ok(Err(Error::InvalidRequest(_))));
+
+            let result = manager
+                .call(AddTunerReservation {
+                    tuner_index: 2,
+                    user_id: "job:test".to_string(),
+                    priority: 0.into(),
+                    start_at: now,
+                    end_at: now + hour,
+                })
+                .await;
+            assert_matches!(result, Ok(Err(Error::TunerNotFound)));
+
+            let result = manager
+                .call(AddTunerReservation {
+                    tuner_index: 0,
+                    user_id: "job:test".to_string(),
+                    priority: 0.into(),
+                    start_at: now + hour * 3,
+                    end_at: now + hour * 3,
+                })
+                .await;
+            assert_matches!(result, Ok(Err(Error::InvalidRequest(_))));
+
+            let result = manager
+                .call(AddTunerReservation {
+                    tuner_index: 0,
+                    user_id: "job:test".to_string(),
+                    priority: 0.into(),
+                    start_at: now - hour * 2,
+                    end_at: now - hour,
+                })
+                .await;
+            assert_matches!(result, Ok(Err(Error::InvalidRequest(_))));
+
+            let result = manager.call(QueryTunerReservations).await;
+            assert_matches!(result, Ok(reservations) => {
+                assert_eq!(reservations.len(), 2);
+            });
+
+            let result = manager.call(RemoveTunerReservation { id: 1 }).await;
+            assert_matches!(result, Ok(Ok(reservation)) => {
+                assert_eq!(reservation.id, 1);
+            });
+
+            let result = manager.call(RemoveTunerReservation { id: 1 }).await;
+            assert_matches!(result, Ok(Err(Error::ReservationNotFound)));
+
+            let result = manager.call(QueryTunerReservations).await;
+            assert_matches!(result, Ok(reservations) => {
+                assert_eq!(reservations.len(), 1);
+                assert_eq!(reservations[0].id, 2);
+            });
+        }
+        system.shutdown().await;
+    }
+
+    #[test(tokio::test)]
+    async fn test_tuner_reservations_persistence() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config: Arc<Config> = Arc::new(
+            serde_norway::from_str(&format!(
+                r#"
+                tuners:
+                  - name: gr
+                    types: [GR]
+                    command: >-
+                      sleep 1
+                tuner-reservations:
+                  basedir: {}
+                "#,
+                temp_dir.path().display()
+            ))
+            .unwrap(),
+        );
+
+        let now = Jst::now();
+        let hour = chrono::Duration::try_hours(1).unwrap();
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(TunerManager::new(config.clone())).await;
+            for i in 0..2 {
+                let result = manager
+                    .call(AddTunerReservation {
+                        tuner_index: 0,
+                        user_id: "job:test".to_string(),
+                        priority: 0.into(),
+                        start_at: now + hour * i,
+                        end_at: now + hour * (i + 1),
+                    })
+                    .await;
+                assert_matches!(result, Ok(Ok(_)));
+            }
+            let result = manager.call(RemoveTunerReservation { id: 1 }).await;
+            assert_matches!(result, Ok(Ok(_)));
+        }
+        system.shutdown().await;
+
+        let path = temp_dir.path().join("reservations.v1.json");
+        let mut reservations: Vec<TunerReservation> =
+            serde_json::from_reader(std::fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(reservations.len(), 1);
+        assert_eq!(reservations[0].id, 2);
+
+        // Expired reservations are discarded when loaded.
+        reservations.push(TunerReservation {
+            id: 3,
+            tuner_index: 0,
+            user_id: "job:expired".to_string(),
+            priority: 0.into(),
+            start_at: now - hour * 2,
+            end_at: now - hour,
+        });
+        std::fs::write(&path, serde_json::to_vec(&reservations).unwrap()).unwrap();
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(TunerManager::new(config.clone())).await;
+            let result = manager.call(QueryTunerReservations).await;
+            assert_matches!(result, Ok(reservations) => {
+                assert_eq!(reservations.len(), 1);
+                assert_eq!(reservations[0].id, 2);
+            });
+
+            // IDs are not reused.
+            let result = manager
+                .call(AddTunerReservation {
+                    tuner_index: 0,
+                    user_id: "job:test".to_string(),
+                    priority: 0.into(),
+                    start_at: now + hour * 2,
+                    end_at: now + hour * 3,
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(reservation)) => {
+                assert_eq!(reservation.id, 4);
+            });
+        }
+        system.shutdown().await;
+    }
+
+    #[test(tokio::test)]
+    async fn test_start_streaming_with_reservation() {
+        let config: Arc<Config> = Arc::new(
+            serde_norway::from_str(
+                r#"
+                tuners:
+                  - name: gr1
+                    types: [GR]
+                    command: >-
+                      sleep 1
+                  - name: gr2
+                    types: [GR]
+                    command: >-
+                      sleep 1
+                "#,
+            )
+            .unwrap(),
+        );
+
+        let now = Jst::now();
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(TunerManager::new(config.clone())).await;
+
+            let result = manager
+                .call(AddTunerReservation {
+                    tuner_index: 0,
+                    user_id: "job:owner".to_string(),
+                    priority: 1.into(),
+                    start_at: now,
+                    end_at: now + chrono::Duration::try_hours(1).unwrap(),
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(_)));
+
+            // The reserved tuner is skipped.
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("0"),
+                    user: create_user(1.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            let _stream1 = assert_matches!(result, Ok(Ok(stream)) => {
+                assert_eq!(stream.id().session_id.tuner_index, 1);
+                stream
+            });
+
+            // No tuner is available for users having a priority lower than or equal to the
+            // priority of the reservation.
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("1"),
+                    user: create_user(0.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            assert_matches!(result, Ok(Err(Error::TunerUnavailable)));
+
+            // The owner of the reservation can use the reserved tuner.
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("1"),
+                    user: tuner_user!(0, job; "owner"),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            let _stream0 = assert_matches!(result, Ok(Ok(stream)) => {
+                assert_eq!(stream.id().session_id.tuner_index, 0);
+                stream
+            });
+        }
+        system.shutdown().await;
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(TunerManager::new(config.clone())).await;
+
+            let result = manager
+                .call(AddTunerReservation {
+                    tuner_index: 0,
+                    user_id: "job:owner".to_string(),
+                    priority: 1.into(),
+                    start_at: now,
+                    end_at: now + chrono::Duration::try_hours(1).unwrap(),
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(_)));
+
+            // Users having a higher priority can use the reserved tuner.
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("0"),
+                    user: create_user(2.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(stream)) => {
+                assert_eq!(stream.id().session_id.tuner_index, 0);
+            });
+        }
+        system.shutdown().await;
+    }
+
+    #[test(tokio::test)]
+    async fn test_start_streaming_preempted() {
+        let config: Arc<Config> = Arc::new(
+            serde_norway::from_str(
+                r#"
+                tuners:
+                  - name: gr
+                    types: [GR]
+                    command: >-
+                      sleep 1
+                "#,
+            )
+            .unwrap(),
+        );
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(TunerManager::new(config.clone())).await;
+
+            let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+            let result = manager
+                .call(RegisterEmitter(Emitter::new(EventCollector(tx))))
+                .await;
+            assert_matches!(result, Ok(_));
+
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("0"),
+                    user: tuner_user!(0, job; "low"),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            let _stream0 = assert_matches!(result, Ok(Ok(stream)) => stream);
+
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("1"),
+                    user: tuner_user!(1, job; "high"),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            let _stream1 = assert_matches!(result, Ok(Ok(stream)) => stream);
+
+            let preempted = loop {
+                match rx.recv().await {
+                    Some(Event::Preempted {
+                        tuner_index,
+                        user,
+                        replaced_by,
+                    }) => break (tuner_index, user, replaced_by),
+                    Some(_) => continue,
+                    None => panic!("No preemption event"),
+                }
+            };
+            assert_eq!(preempted.0, 0);
+            assert_matches!(preempted.1.info, TunerUserInfo::Job(name) => {
+                assert_eq!(name, "low");
+            });
+            assert_matches!(preempted.2.info, TunerUserInfo::Job(name) => {
+                assert_eq!(name, "high");
+            });
+        }
+        system.shutdown().await;
+    }
+
+    #[test(tokio::test)]
+    async fn test_excluded_channel() {
+        let system = System::new();
+
+        {
+            let config: Arc<Config> = Arc::new(
+                serde_norway::from_str(
+                    r#"
+                tuners:
+                  - name: gr
+                    types: [GR]
+                    command: >-
+                      sleep 1
+                    excluded-channels:
+                      - name: excluded
+                      - params:
+                          channel-type: GR
+                          channel: excluded
+                "#,
+                )
+                .unwrap(),
+            );
+
+            let manager = system.spawn_actor(TunerManager::new(config)).await;
+
+            let result = manager
+                .call(StartStreaming {
+                    channel: channel_gr!("excluded", "channel"),
+                    user: create_user(0.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            assert_matches!(result, Ok(Err(Error::TunerUnavailable)));
+
+            let result = manager
+                .call(StartStreaming {
+                    channel: channel_gr!("channel", "excluded"),
+                    user: create_user(0.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            assert_matches!(result, Ok(Err(Error::TunerUnavailable)));
+
+            let result = manager
+                .call(StartStreaming {
+                    channel: channel_gr!("channel", "channel"),
+                    user: create_user(0.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(_)));
+        }
+        system.shutdown().await;
+    }
+
+    #[test(tokio::test)]
+    async fn test_excluded_tuners() {
+        let system = System::new();
+
+        {
+            let config: Arc<Config> = Arc::new(
+                serde_norway::from_str(
+                    r#"
+                tuners:
+                  - name: gr1
+                    types: [GR]
+                    command: >-
+                      sleep 1
+                  - name: gr2
+                    types: [GR]
+                    command: >-
+                      sleep 1
+                "#,
+                )
+                .unwrap(),
+            );
+
+            let manager = system.spawn_actor(TunerManager::new(config)).await;
+
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("0"),
+                    user: create_user(0.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![0],
+                })
+                .await;
+            let stream1 = assert_matches!(result, Ok(Ok(stream)) => {
+                assert_eq!(stream.id().tuner_index(), 1);
+                stream
+            });
+
+            // The active tuner is not reused if it's excluded.
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("0"),
+                    user: create_user(0.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![1],
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(stream)) => {
+                assert_eq!(stream.id().tuner_index(), 0);
+                assert_ne!(stream.id().session_id, stream1.id().session_id);
+            });
+
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("1"),
+                    user: create_user(1.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![0, 1],
+                })
+                .await;
+            assert_matches!(result, Ok(Err(Error::TunerUnavailable)));
+        }
+        system.shutdown().await;
+    }
 
     #[test(tokio::test)]
     async fn test_tuner_is_subscribed() {
@@ -1486,7 +3132,9 @@ mod tests {
 
             assert!(!tuner.is_subscribed(&dummy_id));
 
-            let result = tuner.activate(&create_channel("1"), vec![], &system).await;
+            let result = tuner
+                .activate(&create_channel("1"), 0.into(), vec![], &system)
+                .await;
             assert!(result.is_ok());
             assert!(!tuner.is_subscribed(&dummy_id));
 
@@ -1515,7 +3163,9 @@ mod tests {
 
             assert!(!tuner.is_active());
 
-            let result = tuner.activate(&create_channel("1"), vec![], &system).await;
+            let result = tuner
+                .activate(&create_channel("1"), 0.into(), vec![], &system)
+                .await;
             assert!(result.is_ok());
             assert!(tuner.is_active());
         }
@@ -1528,7 +3178,9 @@ mod tests {
         {
             let config = create_config("true".to_string());
             let mut tuner = Tuner::new(0, &config);
-            let result = tuner.activate(&create_channel("1"), vec![], &system).await;
+            let result = tuner
+                .activate(&create_channel("1"), 0.into(), vec![], &system)
+                .await;
             assert!(result.is_ok());
             tokio::task::yield_now().await;
         }
@@ -1536,7 +3188,9 @@ mod tests {
         {
             let config = create_config("'".to_string());
             let mut tuner = Tuner::new(0, &config);
-            let result = tuner.activate(&create_channel("1"), vec![], &system).await;
+            let result = tuner
+                .activate(&create_channel("1"), 0.into(), vec![], &system)
+                .await;
             assert_matches!(
                 result,
                 Err(Error::CommandFailed(CommandUtilError::UnableToParse(_)))
@@ -1554,7 +3208,9 @@ mod tests {
         if !skip {
             let config = create_config("no-such-command".to_string());
             let mut tuner = Tuner::new(0, &config);
-            let result = tuner.activate(&create_channel("1"), vec![], &system).await;
+            let result = tuner
+                .activate(&create_channel("1"), 0.into(), vec![], &system)
+                .await;
             assert_matches!(
                 result,
                 Err(Error::CommandFailed(CommandUtilError::UnableToSpawn(..)))
@@ -1565,169 +3221,676 @@ mod tests {
     }
 
     #[test(tokio::test)]
-    async fn test_tuner_stop_streaming() {
+    async fn test_tuner_stop_streaming() {
+        let system = System::new();
+        {
+            let config = create_config("true".to_string());
+            let mut tuner = Tuner::new(1, &config);
+            let result = tuner.stop_streaming(Default::default()).await;
+            assert_matches!(result, Err(Error::SessionNotFound));
+
+            let result = tuner
+                .activate(&create_channel("1"), 0.into(), vec![], &system)
+                .await;
+            assert!(result.is_ok());
+            let subscription = tuner.subscribe(&TunerUser {
+                info: TunerUserInfo::Web {
+                    id: "".to_string(),
+                    agent: None,
+                },
+                priority: 0.into(),
+            });
+
+            let result = tuner.stop_streaming(Default::default()).await;
+            assert_matches!(result, Err(Error::SessionNotFound));
+
+            let result = tuner.stop_streaming(subscription.id).await;
+            assert_matches!(result, Ok(Some(user)) => {
+                assert_matches!(user.info, TunerUserInfo::Web { .. });
+            });
+
+            tokio::task::yield_now().await;
+        }
+        system.shutdown().await;
+    }
+
+    #[test(tokio::test)]
+    async fn test_tuner_can_grab() {
+        let system = System::new();
+        {
+            let config = create_config("true".to_string());
+            let mut tuner = Tuner::new(0, &config);
+            assert!(tuner.can_grab(0.into()));
+
+            tuner
+                .activate(&create_channel("1"), 0.into(), vec![], &system)
+                .await
+                .unwrap();
+            tuner.subscribe(&create_user(0.into()));
+
+            assert!(!tuner.can_grab(0.into()));
+            assert!(tuner.can_grab(1.into()));
+            assert!(tuner.can_grab(2.into()));
+            assert!(tuner.can_grab(TunerUserPriority::GRAB));
+
+            tuner.subscribe(&create_user(1.into()));
+
+            assert!(!tuner.can_grab(0.into()));
+            assert!(!tuner.can_grab(1.into()));
+            assert!(tuner.can_grab(2.into()));
+            assert!(tuner.can_grab(TunerUserPriority::GRAB));
+
+            tuner.subscribe(&create_user(TunerUserPriority::GRAB));
+
+            assert!(!tuner.can_grab(0.into()));
+            assert!(!tuner.can_grab(1.into()));
+            assert!(!tuner.can_grab(2.into()));
+            assert!(tuner.can_grab(TunerUserPriority::GRAB));
+
+            tokio::task::yield_now().await;
+        }
+        system.shutdown().await;
+    }
+
+    #[test(tokio::test)]
+    async fn test_tuner_priority() {
+        let system = System::new();
+        {
+            let config = create_config("true".to_string());
+            let mut tuner = Tuner::new(0, &config);
+            assert_matches!(tuner.priority(), None);
+
+            tuner
+                .activate(&create_channel("1"), 0.into(), vec![], &system)
+                .await
+                .unwrap();
+
+            tuner.subscribe(&create_user(0.into()));
+            assert_matches!(tuner.priority(), Some(prio) => {
+                assert_eq!(prio.highest_user_priority, 0.into());
+                assert_eq!(prio.num_users, 1);
+            });
+
+            tuner.subscribe(&create_user(10.into()));
+            assert_matches!(tuner.priority(), Some(prio) => {
+                assert_eq!(prio.highest_user_priority, 10.into());
+                assert_eq!(prio.num_users, 2);
+            });
+
+            tuner.subscribe(&create_user(5.into()));
+            assert_matches!(tuner.priority(), Some(prio) => {
+                assert_eq!(prio.highest_user_priority, 10.into());
+                assert_eq!(prio.num_users, 3);
+            });
+        }
+        system.shutdown().await;
+    }
+
+    #[test(tokio::test)]
+    async fn test_tuner_reactivate() {
+        let system = System::new();
+        {
+            let config = create_config("true".to_string());
+            let mut tuner = Tuner::new(0, &config);
+            tuner
+                .activate(&create_channel("1"), 0.into(), vec![], &system)
+                .await
+                .ok();
+
+            tokio::task::yield_now().await;
+
+            tuner.deactivate();
+            let result = tuner
+                .activate(&create_channel("2"), 0.into(), vec![], &system)
+                .await;
+            assert!(result.is_ok());
+
+            tokio::task::yield_now().await;
+        }
+        system.shutdown().await;
+    }
+
+    #[test(tokio::test)]
+    async fn test_tuner_time_limit() {
+        let system = System::new();
+
+        {
+            let config: Arc<Config> = Arc::new(
+                serde_norway::from_str(
+                    r#"
+                tuners:
+                  - name: gr
+                    types: [GR]
+                    command: sleep 10 # 10s
+                    time-limit: 10    # 10ms
+                "#,
+                )
+                .unwrap(),
+            );
+
+            let manager = system.spawn_actor(TunerManager::new(config)).await;
+
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("0"),
+                    user: create_user(0.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            let mut stream = assert_matches!(result, Ok(Ok(stream)) => {
+                assert_eq!(stream.id().session_id.tuner_index, 0);
+                stream
+            });
+
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+
+            let data = stream.next().await;
+            assert_matches!(data, None);
+        }
+        system.shutdown().await;
+    }
+
+    #[test]
+    fn test_tuner_health_state() {
+        let mut health = TunerHealthState::new(TunerHealthCheckConfig {
+            max_failures: 2,
+            quarantine_time: std::time::Duration::from_secs(100),
+            max_quarantine_time: std::time::Duration::from_secs(300),
+            ..Default::default()
+        });
+        assert!(!health.record_success());
+
+        health.record_failure(0, "1".to_string());
+        assert!(!health.is_quarantined());
+
+        health.record_failure(0, "2".to_string());
+        assert!(health.is_quarantined());
+        assert_matches!(health.get_model(), model => {
+            assert_eq!(model.consecutive_failures, 2);
+            assert_eq!(model.last_error, Some("2".to_string()));
+            let quarantine_time = model.quarantined_until.unwrap() - Jst::now();
+            assert!(quarantine_time.num_seconds() > 90);
+            assert!(quarantine_time.num_seconds() <= 100);
+        });
+
+        // Exponential backoff.
+        health.record_failure(0, "3".to_string());
+        let quarantine_time = health.quarantined_until.unwrap() - Jst::now();
+        assert!(quarantine_time.num_seconds() > 190);
+        assert!(quarantine_time.num_seconds() <= 200);
+
+        // Limited by `max-quarantine-time`.
+        health.record_failure(0, "4".to_string());
+        let quarantine_time = health.quarantined_until.unwrap() - Jst::now();
+        assert!(quarantine_time.num_seconds() > 290);
+        assert!(quarantine_time.num_seconds() <= 300);
+
+        health.record_no_data_timeout(0);
+        assert_eq!(health.get_model().no_data_timeouts, 1);
+
+        assert!(health.record_success());
+        assert!(!health.is_quarantined());
+        assert_matches!(health.get_model(), model => {
+            assert_eq!(model.consecutive_failures, 0);
+            assert_eq!(model.no_data_timeouts, 1);
+            assert!(model.last_error.is_some());
+            assert_eq!(model.quarantined_until, None);
+        });
+
+        // The quarantine is disabled.
+        let mut health = TunerHealthState::new(TunerHealthCheckConfig {
+            max_failures: 0,
+            ..Default::default()
+        });
+        health.record_failure(0, "1".to_string());
+        assert!(!health.is_quarantined());
+    }
+
+    #[test(tokio::test)]
+    async fn test_tuner_failover() {
+        let config: Arc<Config> = Arc::new(
+            serde_norway::from_str(
+                r#"
+                tuners:
+                  - name: broken
+                    types: [GR]
+                    command: "'"
+                    health-check:
+                      max-failures: 1
+                  - name: gr
+                    types: [GR]
+                    command: sleep 1
+                "#,
+            )
+            .unwrap(),
+        );
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(TunerManager::new(config)).await;
+
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("0"),
+                    user: create_user(0.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(stream)) => {
+                assert_eq!(stream.id().session_id.tuner_index, 1);
+            });
+
+            let result = manager.call(QueryTuner(0)).await;
+            assert_matches!(result, Ok(Ok(tuner)) => {
+                assert!(tuner.is_fault);
+                assert_eq!(tuner.health.consecutive_failures, 1);
+                assert!(tuner.health.last_error.is_some());
+                assert!(tuner.health.quarantined_until.is_some());
+            });
+
+            // The quarantined tuner is not used.
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("1"),
+                    user: create_user(1.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            assert_matches!(result, Ok(Ok(stream)) => {
+                assert_eq!(stream.id().session_id.tuner_index, 1);
+            });
+        }
+        system.shutdown().await;
+    }
+
+    #[test(tokio::test)]
+    async fn test_tuner_no_data_timeout() {
+        let config: Arc<Config> = Arc::new(
+            serde_norway::from_str(
+                r#"
+                tuners:
+                  - name: gr
+                    types: [GR]
+                    command: sleep 10 # 10s
+                    time-limit: 10    # 10ms
+                    health-check:
+                      max-failures: 1
+                "#,
+            )
+            .unwrap(),
+        );
+
+        let system = System::new();
+        {
+            let manager = system.spawn_actor(TunerManager::new(config)).await;
+
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("0"),
+                    user: create_user(0.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            let mut stream = assert_matches!(result, Ok(Ok(stream)) => stream);
+            assert_matches!(stream.next().await, None);
+
+            let health = wait_for_health(&manager, |health| health.no_data_timeouts > 0).await;
+            assert_eq!(health.consecutive_failures, 1);
+            assert!(health.quarantined_until.is_some());
+        }
+        system.shutdown().await;
+    }
+
+    #[test(tokio::test)]
+    async fn test_tuner_probe() {
+        let config: Arc<Config> = Arc::new(
+            serde_norway::from_str(
+                r#"
+                tuners:
+                  - name: gr
+                    types: [GR]
+                    command: sleep 1
+                    health-check:
+                      max-failures: 1
+                      probe-command: 'false'
+                      probe-interval: 10ms
+                "#,
+            )
+            .unwrap(),
+        );
+
         let system = System::new();
         {
-            let config = create_config("true".to_string());
-            let mut tuner = Tuner::new(1, &config);
-            let result = tuner.stop_streaming(Default::default()).await;
-            assert_matches!(result, Err(Error::SessionNotFound));
-
-            let result = tuner.activate(&create_channel("1"), vec![], &system).await;
-            assert!(result.is_ok());
-            let subscription = tuner.subscribe(&TunerUser {
-                info: TunerUserInfo::Web {
-                    id: "".to_string(),
-                    agent: None,
-                },
-                priority: 0.into(),
-            });
-
-            let result = tuner.stop_streaming(Default::default()).await;
-            assert_matches!(result, Err(Error::SessionNotFound));
+            let manager = system.spawn_actor(TunerManager::new(config)).await;
 
-            let result = tuner.stop_streaming(subscription.id).await;
-            assert_matches!(result, Ok(Some(user)) => {
-                assert_matches!(user.info, TunerUserInfo::Web { .. });
-            });
+            let health = wait_for_health(&manager, |health| health.consecutive_failures > 0).await;
+            assert!(health.quarantined_until.is_some());
 
-            tokio::task::yield_now().await;
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("0"),
+                    user: create_user(0.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            assert_matches!(result, Ok(Err(Error::TunerUnavailable)));
         }
         system.shutdown().await;
     }
 
     #[test(tokio::test)]
-    async fn test_tuner_can_grab() {
+    async fn test_tuner_stats() {
+        let config: Arc<Config> = Arc::new(
+            serde_norway::from_str(
+                r#"
+                tuners:
+                  - name: gr
+                    types: [GR]
+                    command: cat /dev/zero
+                    signal-level:
+                      command: "echo 'Signal: 25.50dB'"
+                      interval: 10ms
+                "#,
+            )
+            .unwrap(),
+        );
+
         let system = System::new();
         {
-            let config = create_config("true".to_string());
-            let mut tuner = Tuner::new(0, &config);
-            assert!(tuner.can_grab(0.into()));
+            let manager = system.spawn_actor(TunerManager::new(config)).await;
 
-            tuner
-                .activate(&create_channel("1"), vec![], &system)
-                .await
-                .unwrap();
-            tuner.subscribe(&create_user(0.into()));
+            let result = manager.call(QueryTunerStats(0)).await;
+            assert_matches!(result, Ok(Err(Error::NoContent)));
 
-            assert!(!tuner.can_grab(0.into()));
-            assert!(tuner.can_grab(1.into()));
-            assert!(tuner.can_grab(2.into()));
-            assert!(tuner.can_grab(TunerUserPriority::GRAB));
+            let result = manager.call(QueryTunerStats(1)).await;
+            assert_matches!(result, Ok(Err(Error::TunerNotFound)));
 
-            tuner.subscribe(&create_user(1.into()));
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("0"),
+                    user: create_user(0.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            let stream = assert_matches!(result, Ok(Ok(stream)) => stream);
 
-            assert!(!tuner.can_grab(0.into()));
-            assert!(!tuner.can_grab(1.into()));
-            assert!(tuner.can_grab(2.into()));
-            assert!(tuner.can_grab(TunerUserPriority::GRAB));
+            let stats = loop {
+                let result = manager.call(QueryTunerStats(0)).await;
+                let stats = assert_matches!(result, Ok(Ok(stats)) => stats);
+                if stats.received_bytes > 0 && stats.signal_level.is_some() {
+                    break stats;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            };
+            assert_eq!(stats.signal_level, Some(25.5));
+            assert_eq!(stats.subscribers.len(), 1);
+            assert_eq!(stats.subscribers[0].id, stream.id().to_string());
+
+            let result = manager.call(QueryTuner(0)).await;
+            assert_matches!(result, Ok(Ok(tuner)) => {
+                assert_matches!(tuner.stats, Some(stats) => {
+                    assert_eq!(stats.session_id, stream.id().session_id.to_string());
+                });
+            });
 
-            tuner.subscribe(&create_user(TunerUserPriority::GRAB));
+            let result = manager.call(QueryTuners).await;
+            assert_matches!(result, Ok(tuners) => {
+                assert!(tuners[0].stats.is_none());
+            });
+        }
+        system.shutdown().await;
+    }
 
-            assert!(!tuner.can_grab(0.into()));
-            assert!(!tuner.can_grab(1.into()));
-            assert!(!tuner.can_grab(2.into()));
-            assert!(tuner.can_grab(TunerUserPriority::GRAB));
+    #[test]
+    fn test_parse_signal_level() {
+        assert_eq!(parse_signal_level("25.50dB"), Some(25.5));
+        assert_eq!(parse_signal_level("Signal: 25.50 dB\n"), Some(25.5));
+        assert_eq!(parse_signal_level("SignalLevel -3"), Some(-3.0));
+        assert_eq!(parse_signal_level("no signal"), None);
+        assert_eq!(parse_signal_level(""), None);
+    }
 
-            tokio::task::yield_now().await;
+    async fn wait_for_health<F>(manager: &Address<TunerManager>, pred: F) -> TunerHealth
+    where
+        F: Fn(&TunerHealth) -> bool,
+    {
+        loop {
+            let result = manager.call(QueryTuner(0)).await;
+            let health = assert_matches!(result, Ok(Ok(tuner)) => tuner.health);
+            if pred(&health) {
+                return health;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
         }
-        system.shutdown().await;
     }
 
     #[test(tokio::test)]
-    async fn test_tuner_priority() {
+    async fn test_remote_tuner() {
+        let (url, priorities) = spawn_upstream(r#"["GR"]"#).await;
+        let config: Arc<Config> = Arc::new(
+            serde_norway::from_str(&format!(
+                r#"
+                tuners:
+                  - name: remote
+                    types: [GR]
+                    remote:
+                      url: {url}
+                      polling-interval: 10ms
+                "#
+            ))
+            .unwrap(),
+        );
+
         let system = System::new();
         {
-            let config = create_config("true".to_string());
-            let mut tuner = Tuner::new(0, &config);
-            assert_matches!(tuner.priority(), None);
-
-            tuner
-                .activate(&create_channel("1"), vec![], &system)
-                .await
-                .unwrap();
+            let manager = system.spawn_actor(TunerManager::new(config)).await;
+            wait_for_upstream(&manager).await;
 
-            tuner.subscribe(&create_user(0.into()));
-            assert_matches!(tuner.priority(), Some(prio) => {
-                assert_eq!(prio.highest_user_priority, 0.into());
-                assert_eq!(prio.num_users, 1);
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("0"),
+                    user: create_user(1.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            let mut stream = assert_matches!(result, Ok(Ok(stream)) => stream);
+            assert_matches!(stream.next().await, Some(Ok(chunk)) => {
+                assert!(chunk.iter().all(|&b| b == 0x47));
             });
-
-            tuner.subscribe(&create_user(10.into()));
-            assert_matches!(tuner.priority(), Some(prio) => {
-                assert_eq!(prio.highest_user_priority, 10.into());
-                assert_eq!(prio.num_users, 2);
+            assert_eq!(*priorities.lock().unwrap(), ["1"]);
+
+            let result = manager.call(QueryTuner(0)).await;
+            assert_matches!(result, Ok(Ok(tuner)) => {
+                assert!(tuner.is_remote);
+                assert!(tuner.is_using);
+                assert_eq!(
+                    tuner.command.unwrap(),
+                    format!("{url}api/channels/GR/0/stream?decode=0")
+                );
             });
 
-            tuner.subscribe(&create_user(5.into()));
-            assert_matches!(tuner.priority(), Some(prio) => {
-                assert_eq!(prio.highest_user_priority, 10.into());
-                assert_eq!(prio.num_users, 3);
-            });
+            // HTTP 503 is mapped to `TunerUnavailable`.
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("busy"),
+                    user: create_user(2.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            assert_matches!(result, Ok(Err(Error::TunerUnavailable)));
+            assert_eq!(*priorities.lock().unwrap(), ["1", "2"]);
         }
         system.shutdown().await;
     }
 
     #[test(tokio::test)]
-    async fn test_tuner_reactivate() {
+    async fn test_remote_tuner_no_response() {
+        let (url, priorities) = spawn_upstream(r#"["GR"]"#).await;
+        let config: Arc<Config> = Arc::new(
+            serde_norway::from_str(&format!(
+                r#"
+                tuners:
+                  - name: remote
+                    types: [GR]
+                    remote:
+                      url: {url}
+                      polling-interval: 1h
+                      response-timeout: 100ms
+                "#
+            ))
+            .unwrap(),
+        );
+
         let system = System::new();
         {
-            let config = create_config("true".to_string());
-            let mut tuner = Tuner::new(0, &config);
-            tuner
-                .activate(&create_channel("1"), vec![], &system)
-                .await
-                .ok();
+            let manager = system.spawn_actor(TunerManager::new(config)).await;
+            wait_for_upstream(&manager).await;
 
-            tokio::task::yield_now().await;
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("silent"),
+                    user: create_user(1.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            assert_matches!(result, Ok(Err(_)));
+            assert_eq!(*priorities.lock().unwrap(), ["1"]);
 
-            tuner.deactivate();
-            let result = tuner.activate(&create_channel("2"), vec![], &system).await;
-            assert!(result.is_ok());
+            // The upstream server is not requested again until the next polling succeeds.
+            let result = manager
+                .call(StartStreaming {
+                    channel: create_channel("silent"),
+                    user: create_user(1.into()),
+                    stream_id: None,
+                    excluded_tuners: vec![],
+                })
+                .await;
+            assert_matches!(result, Ok(Err(Error::TunerUnavailable)));
+            assert_eq!(*priorities.lock().unwrap(), ["1"]);
 
-            tokio::task::yield_now().await;
+            // The tuner manager still works.
+            let result = manager.call(QueryTuner(0)).await;
+            assert_matches!(result, Ok(Ok(tuner)) => {
+                assert!(!tuner.is_using);
+            });
         }
         system.shutdown().await;
     }
 
     #[test(tokio::test)]
-    async fn test_tuner_time_limit() {
-        let system = System::new();
-
-        {
-            let config: Arc<Config> = Arc::new(
-                serde_norway::from_str(
-                    r#"
+    async fn test_remote_tuner_unsupported_type() {
+        let (url, priorities) = spawn_upstream(r#"["BS"]"#).await;
+        let config: Arc<Config> = Arc::new(
+            serde_norway::from_str(&format!(
+                r#"
                 tuners:
-                  - name: gr
-                    types: [GR]
-                    command: sleep 10 # 10s
-                    time-limit: 10    # 10ms
-                "#,
-                )
-                .unwrap(),
-            );
+                  - name: remote
+                    types: [GR, BS]
+                    remote:
+                      url: {url}
+                      polling-interval: 10ms
+                "#
+            ))
+            .unwrap(),
+        );
 
+        let system = System::new();
+        {
             let manager = system.spawn_actor(TunerManager::new(config)).await;
+            wait_for_upstream(&manager).await;
 
+            // No request is sent because the upstream server has no GR tuner.
             let result = manager
                 .call(StartStreaming {
                     channel: create_channel("0"),
                     user: create_user(0.into()),
                     stream_id: None,
+                    excluded_tuners: vec![],
                 })
                 .await;
-            let mut stream = assert_matches!(result, Ok(Ok(stream)) => {
-                assert_eq!(stream.id().session_id.tuner_index, 0);
-                stream
-            });
+            assert_matches!(result, Ok(Err(Error::TunerUnavailable)));
+            assert!(priorities.lock().unwrap().is_empty());
+        }
+        system.shutdown().await;
+    }
 
-            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    // Spawns a stub server of an upstream server which has a tuner supporting `types`.
+    //
+    // Values of `X-Mirakurun-Priority` are recorded in the returned list.
+    async fn spawn_upstream(types: &'static str) -> (Url, Arc<std::sync::Mutex<Vec<String>>>) {
+        use axum::extract::Path;
+        use axum::http::HeaderMap;
+        use axum::http::StatusCode;
+        use axum::response::IntoResponse;
+        use axum::routing::get;
+
+        let priorities: Arc<std::sync::Mutex<Vec<String>>> = Default::default();
+        let recorded = priorities.clone();
+        let app = axum::Router::new()
+            .route(
+                "/api/tuners",
+                get(move || async move {
+                    format!(
+                        r#"[{{"types":{types},"isAvailable":true,"isFree":true,"isFault":false}}]"#
+                    )
+                }),
+            )
+            .route(
+                "/api/channels/{channel_type}/{channel}/stream",
+                get(
+                    move |Path((_, channel)): Path<(String, String)>, headers: HeaderMap| async move {
+                        let priority = headers["x-mirakurun-priority"].to_str().unwrap();
+                        recorded.lock().unwrap().push(priority.to_string());
+                        if channel == "silent" {
+                            // Never respond.
+                            std::future::pending::<()>().await;
+                        }
+                        if channel == "busy" {
+                            StatusCode::SERVICE_UNAVAILABLE.into_response()
+                        } else {
+                            let chunk = bytes::Bytes::from_static(&[0x47; 4096]);
+                            let stream = tokio_stream::iter(std::iter::repeat(chunk))
+                                .map(Ok::<_, std::io::Error>)
+                                .throttle(std::time::Duration::from_millis(1));
+                            axum::body::Body::from_stream(stream).into_response()
+                        }
+                    },
+                ),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, app).await.unwrap();
+        });
+        (url, priorities)
+    }
 
-            let data = stream.next().await;
-            assert_matches!(data, None);
+    async fn wait_for_upstream(manager: &Address<TunerManager>) {
+        loop {
+            let result = manager.call(QueryTuner(0)).await;
+            if assert_matches!(result, Ok(Ok(tuner)) => tuner.is_available) {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    }
+
+    struct EventCollector(tokio::sync::mpsc::UnboundedSender<Event>);
+
+    #[async_trait]
+    impl Emit<Event> for EventCollector {
+        async fn emit(&self, msg: Event) {
+            let _ = self.0.send(msg);
         }
-        system.shutdown().await;
     }
 
     fn create_config(command: String) -> TunerConfig {
@@ -1798,6 +3961,81 @@ pub(crate) mod stub {
         }
     }
 
+    #[async_trait]
+    impl Call<QueryTunerStats> for TunerManagerStub {
+        async fn call(
+            &self,
+            msg: QueryTunerStats,
+        ) -> actlet::Result<<QueryTunerStats as Message>::Reply> {
+            match msg.0 {
+                0 => Ok(Err(Error::TunerNotFound)),
+                1 => Ok(Err(Error::NoContent)),
+                _ => Ok(Ok(TunerSessionStats {
+                    session_id: format!("{}.0", msg.0),
+                    start_time: Jst::now(),
+                    received_bytes: 0,
+                    bytes_per_sec: 0,
+                    packets: 0,
+                    drop_packets: 0,
+                    error_packets: 0,
+                    signal_level: None,
+                    subscribers: vec![],
+                })),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Call<QueryTunerReservations> for TunerManagerStub {
+        async fn call(
+            &self,
+            _msg: QueryTunerReservations,
+        ) -> actlet::Result<<QueryTunerReservations as Message>::Reply> {
+            Ok(vec![])
+        }
+    }
+
+    #[async_trait]
+    impl Call<AddTunerReservation> for TunerManagerStub {
+        async fn call(
+            &self,
+            msg: AddTunerReservation,
+        ) -> actlet::Result<<AddTunerReservation as Message>::Reply> {
+            match msg.tuner_index {
+                0 => Ok(Err(Error::TunerNotFound)),
+                1 => Ok(Err(Error::AlreadyExists)),
+                _ => Ok(Ok(TunerReservation {
+                    id: 1,
+                    tuner_index: msg.tuner_index,
+                    user_id: msg.user_id,
+                    priority: msg.priority,
+                    start_at: msg.start_at,
+                    end_at: msg.end_at,
+                })),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl Call<RemoveTunerReservation> for TunerManagerStub {
+        async fn call(
+            &self,
+            msg: RemoveTunerReservation,
+        ) -> actlet::Result<<RemoveTunerReservation as Message>::Reply> {
+            match msg.id {
+                0 => Ok(Err(Error::ReservationNotFound)),
+                _ => Ok(Ok(TunerReservation {
+                    id: msg.id,
+                    tuner_index: 2,
+                    user_id: "test".to_string(),
+                    priority: 0.into(),
+                    start_at: Jst::now(),
+                    end_at: Jst::now(),
+                })),
+            }
+        }
+    }
+
     #[async_trait]
     impl Call<StartStreaming> for TunerManagerStub {
         async fn call(
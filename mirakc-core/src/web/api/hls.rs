@@ -0,0 +1,224 @@
+use super::*;
+
+use std::fmt::Write as _;
+
+use crate::mpeg_ts_stream::TS_PACKET_SIZE;
+use crate::recording::ContentIndexPoint;
+use crate::timeshift::TimeshiftRecordSegment;
+
+// The duration of each segment in a playlist for a record without content index.
+//
+// We split such a record into segments assuming that its bitrate is constant.
+const RECORD_SEGMENT_DURATION: i64 = 10; // seconds
+
+// A segment is a byte range of a stream provided by a streaming endpoint for a record.
+pub(in crate::web::api) struct HlsSegment {
+    pub duration: Duration,
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl From<TimeshiftRecordSegment> for HlsSegment {
+    fn from(segment: TimeshiftRecordSegment) -> Self {
+        HlsSegment {
+            duration: segment.duration,
+            offset: segment.offset,
+            size: segment.size,
+        }
+    }
+}
+
+// Splits content into segments which have the same size except for the last one.
+pub(in crate::web::api) fn split_into_segments(duration: Duration, size: u64) -> Vec<HlsSegment> {
+    if size == 0 {
+        return vec![];
+    }
+    let num_segments = (duration.num_seconds() / RECORD_SEGMENT_DURATION).max(1) as u64;
+    let num_packets = size.div_ceil(TS_PACKET_SIZE);
+    let segment_size = num_packets.div_ceil(num_segments) * TS_PACKET_SIZE;
+    let duration_ms = duration.num_milliseconds().max(0) as u64;
+    let mut segments = vec![];
+    let mut offset = 0;
+    while offset < size {
+        let segment_size = segment_size.min(size - offset);
+        let segment_duration = duration_ms * segment_size / size;
+        segments.push(HlsSegment {
+            duration: Duration::milliseconds(segment_duration as i64),
+            offset,
+            size: segment_size,
+        });
+        offset += segment_size;
+    }
+    segments
+}
+
+// Splits content into segments at points in its content index.
+//
+// The first segment starts at the beginning of the content and the last segment ends at the end
+// of the content.  Points beyond the end of the content are ignored.
+pub(in crate::web::api) fn split_into_segments_by_index(
+    points: &[ContentIndexPoint],
+    size: u64,
+) -> Vec<HlsSegment> {
+    let mut segments: Vec<HlsSegment> = vec![];
+    let mut offset = 0;
+    let mut duration = Duration::zero();
+    let points: Vec<&ContentIndexPoint> = points.iter().take_while(|p| p.pos <= size).collect();
+    for pair in points.windows(2) {
+        duration += pair[1].timestamp - pair[0].timestamp;
+        // Points at the same position are merged.
+        if pair[1].pos > offset {
+            segments.push(HlsSegment {
+                duration,
+                offset,
+                size: pair[1].pos - offset,
+            });
+            offset = pair[1].pos;
+            duration = Duration::zero();
+        }
+    }
+    if let Some(last) = segments.last_mut() {
+        last.duration += duration;
+        last.size = size - last.offset;
+    }
+    segments
+}
+
+// Builds an HLS playlist using byte-range segments of the stream located at `uri`.
+//
+// The playlist for an incomplete stream has no `#EXT-X-ENDLIST` tag so that players reload it.
+pub(in crate::web::api) fn build_playlist(
+    uri: &str,
+    segments: &[HlsSegment],
+    incomplete: bool,
+) -> Result<Response<String>, Error> {
+    let target_duration = segments
+        .iter()
+        .map(|segment| (segment.duration.num_milliseconds() + 999) / 1000)
+        .max()
+        .unwrap_or_default()
+        .max(1);
+
+    let mut buf = String::new();
+    writeln!(buf, "#EXTM3U")?;
+    // EXT-X-BYTERANGE requires the version 4 or later.
+    writeln!(buf, "#EXT-X-VERSION:4")?;
+    writeln!(buf, "#EXT-X-TARGETDURATION:{target_duration}")?;
+    writeln!(buf, "#EXT-X-MEDIA-SEQUENCE:0")?;
+    if incomplete {
+        writeln!(buf, "#EXT-X-PLAYLIST-TYPE:EVENT")?;
+    } else {
+        writeln!(buf, "#EXT-X-PLAYLIST-TYPE:VOD")?;
+    }
+    for segment in segments.iter() {
+        let duration = segment.duration.num_milliseconds() as f64 / 1000.0;
+        writeln!(buf, "#EXTINF:{duration:.3},")?;
+        writeln!(buf, "#EXT-X-BYTERANGE:{}@{}", segment.size, segment.offset)?;
+        writeln!(buf, "{uri}")?;
+    }
+    if !incomplete {
+        writeln!(buf, "#EXT-X-ENDLIST")?;
+    }
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, "application/vnd.apple.mpegurl")
+        .body(buf)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_jst::Jst;
+
+    #[test]
+    fn test_split_into_segments() {
+        assert!(split_into_segments(Duration::seconds(10), 0).is_empty());
+
+        let segments = split_into_segments(Duration::zero(), 100);
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].duration, Duration::zero());
+        assert_eq!(segments[0].offset, 0);
+        assert_eq!(segments[0].size, 100);
+
+        let size = TS_PACKET_SIZE * 10;
+        let segments = split_into_segments(Duration::seconds(30), size);
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].offset, 0);
+        assert_eq!(segments[0].size, TS_PACKET_SIZE * 4);
+        assert_eq!(segments[0].duration, Duration::seconds(12));
+        assert_eq!(segments[1].offset, TS_PACKET_SIZE * 4);
+        assert_eq!(segments[1].size, TS_PACKET_SIZE * 4);
+        assert_eq!(segments[2].offset, TS_PACKET_SIZE * 8);
+        assert_eq!(segments[2].size, TS_PACKET_SIZE * 2);
+        assert_eq!(segments[2].duration, Duration::seconds(6));
+    }
+
+    #[test]
+    fn test_split_into_segments_by_index() {
+        let base = Jst::now();
+        let point = |secs: i64, pos: u64| ContentIndexPoint {
+            timestamp: base + Duration::seconds(secs),
+            pos,
+        };
+
+        assert!(split_into_segments_by_index(&[], 100).is_empty());
+        assert!(split_into_segments_by_index(&[point(0, 0)], 100).is_empty());
+
+        let points = [
+            point(0, 188),
+            point(10, 188 * 10),
+            point(15, 188 * 10),
+            point(20, 188 * 20),
+            point(30, 188 * 40),
+        ];
+        let segments = split_into_segments_by_index(&points, 188 * 30);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].offset, 0);
+        assert_eq!(segments[0].size, 188 * 10);
+        assert_eq!(segments[0].duration, Duration::seconds(10));
+        assert_eq!(segments[1].offset, 188 * 10);
+        assert_eq!(segments[1].size, 188 * 20);
+        assert_eq!(segments[1].duration, Duration::seconds(10));
+    }
+
+    #[test]
+    fn test_build_playlist() {
+        let segments = vec![
+            HlsSegment {
+                duration: Duration::milliseconds(10500),
+                offset: 0,
+                size: 188,
+            },
+            HlsSegment {
+                duration: Duration::seconds(5),
+                offset: 188,
+                size: 376,
+            },
+        ];
+
+        let res = build_playlist("stream", &segments, false).unwrap();
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            "application/vnd.apple.mpegurl"
+        );
+        assert_eq!(
+            res.body(),
+            "#EXTM3U\n\
+             #EXT-X-VERSION:4\n\
+             #EXT-X-TARGETDURATION:11\n\
+             #EXT-X-MEDIA-SEQUENCE:0\n\
+             #EXT-X-PLAYLIST-TYPE:VOD\n\
+             #EXTINF:10.500,\n\
+             #EXT-X-BYTERANGE:188@0\n\
+             stream\n\
+             #EXTINF:5.000,\n\
+             #EXT-X-BYTERANGE:376@188\n\
+             stream\n\
+             #EXT-X-ENDLIST\n"
+        );
+
+        let res = build_playlist("stream", &segments, true).unwrap();
+        assert!(res.body().contains("#EXT-X-PLAYLIST-TYPE:EVENT\n"));
+        assert!(!res.body().contains("#EXT-X-ENDLIST"));
+    }
+}
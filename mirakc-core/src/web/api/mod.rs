@@ -16,9 +16,17 @@ use utoipa::openapi::Server;
 use crate::epg;
 use crate::filter::FilterPipelineBuilder;
 use crate::models::*;
+use crate::recording::PostProcessJob;
+use crate::recording::PostProcessJobState;
+use crate::recording::RecordingAttempt;
+use crate::recording::RecordingConflict;
 use crate::recording::RecordingFailedReason;
 use crate::recording::RecordingOptions;
+use crate::recording::RecordingRuleConditions;
+use crate::recording::RecordingRuleGenre;
+use crate::recording::RecordingRuleTimeRange;
 use crate::recording::RecordingScheduleState;
+use crate::timeshift::TimeshiftGapReason;
 use crate::tuner;
 
 use super::AppState;
@@ -28,6 +36,7 @@ use super::qs::Qs;
 use super::server_name;
 
 mod channels;
+mod hls;
 mod iptv;
 mod onair;
 mod programs;
@@ -49,7 +58,11 @@ pub(super) fn build_api<T, E, R, S, O, W>(
 where
     T: Clone + Send + Sync + 'static,
     T: Call<crate::tuner::QueryTuner>,
+    T: Call<crate::tuner::AddTunerReservation>,
+    T: Call<crate::tuner::QueryTunerReservations>,
+    T: Call<crate::tuner::QueryTunerStats>,
     T: Call<crate::tuner::QueryTuners>,
+    T: Call<crate::tuner::RemoveTunerReservation>,
     T: Call<crate::tuner::StartStreaming>,
     T: TriggerFactory<crate::tuner::StopStreaming>,
     E: Clone + Send + Sync + 'static,
@@ -61,26 +74,47 @@ where
     E: Call<crate::epg::QueryService>,
     E: Call<crate::epg::QueryServices>,
     R: Clone + Send + Sync + 'static,
+    R: Call<crate::recording::AddRecordingRule>,
     R: Call<crate::recording::AddRecordingSchedule>,
+    R: Call<crate::recording::AddRecurringSchedule>,
+    R: Call<crate::recording::AddTimeRangeRecordingSchedule>,
+    R: Call<crate::recording::ImportTimeshiftRecord>,
     R: Call<crate::recording::OpenContent>,
     R: Call<crate::recording::QueryRecord>,
+    R: Call<crate::recording::QueryRecordOffset>,
+    R: Call<crate::recording::QueryRecordContentIndex>,
+    R: Call<crate::recording::QueryRecordingConflicts>,
     R: Call<crate::recording::QueryRecordingRecorder>,
     R: Call<crate::recording::QueryRecordingRecorders>,
+    R: Call<crate::recording::QueryRecordingRule>,
+    R: Call<crate::recording::QueryRecordingRules>,
     R: Call<crate::recording::QueryRecordingSchedule>,
     R: Call<crate::recording::QueryRecordingSchedules>,
     R: Call<crate::recording::QueryRecords>,
+    R: Call<crate::recording::QueryRecurringSchedule>,
+    R: Call<crate::recording::QueryRecurringSchedules>,
     R: Call<crate::recording::RemoveRecord>,
+    R: Call<crate::recording::RemoveRecordingRule>,
     R: Call<crate::recording::RemoveRecordingSchedule>,
     R: Call<crate::recording::RemoveRecordingSchedules>,
+    R: Call<crate::recording::RemoveRecurringSchedule>,
+    R: Call<crate::recording::RunPostProcessJob>,
     R: Call<crate::recording::StartRecording>,
     R: Call<crate::recording::StopRecording>,
+    R: Call<crate::recording::UpdateRecordingRule>,
     S: Clone + Send + Sync + 'static,
     S: Call<crate::timeshift::CreateTimeshiftLiveStreamSource>,
     S: Call<crate::timeshift::CreateTimeshiftRecordStreamSource>,
+    S: Call<crate::timeshift::ExportTimeshiftRecord>,
+    S: Call<crate::timeshift::PinTimeshiftRecord>,
     S: Call<crate::timeshift::QueryTimeshiftRecord>,
+    S: Call<crate::timeshift::QueryTimeshiftRecordOffset>,
+    S: Call<crate::timeshift::QueryTimeshiftRecordSegments>,
     S: Call<crate::timeshift::QueryTimeshiftRecords>,
     S: Call<crate::timeshift::QueryTimeshiftRecorder>,
     S: Call<crate::timeshift::QueryTimeshiftRecorders>,
+    S: Call<crate::timeshift::StartTimeshiftRecorder>,
+    S: Call<crate::timeshift::StopTimeshiftRecorder>,
     O: Clone + Send + Sync + 'static,
     O: Call<crate::onair::QueryOnairProgram>,
     O: Call<crate::onair::QueryOnairPrograms>,
@@ -98,7 +132,16 @@ where
         .route("/version", routing::get(version::get))
         .route("/status", routing::get(status::get))
         .route("/tuners", routing::get(tuners::list))
+        .route(
+            "/tuners/reservations",
+            routing::get(tuners::list_reservations).post(tuners::create_reservation),
+        )
+        .route(
+            "/tuners/reservations/{id}",
+            routing::delete(tuners::delete_reservation),
+        )
         .route("/tuners/{index}", routing::get(tuners::get))
+        .route("/tuners/{index}/stats", routing::get(tuners::stats))
         .route("/channels", routing::get(channels::list))
         .route(
             "/channels/{channel_type}/{channel}/stream",
@@ -146,6 +189,10 @@ where
                 "/recording/schedules",
                 routing::delete(recording::schedules::clear),
             )
+            .route(
+                "/recording/schedules/time-range",
+                routing::post(recording::schedules::create_time_range),
+            )
             .route(
                 "/recording/schedules/{id}",
                 routing::get(recording::schedules::get),
@@ -154,6 +201,10 @@ where
                 "/recording/schedules/{id}",
                 routing::delete(recording::schedules::delete),
             )
+            .route(
+                "/recording/conflicts",
+                routing::get(recording::conflicts::list),
+            )
             .route(
                 "/recording/recorders",
                 routing::get(recording::recorders::list),
@@ -173,8 +224,35 @@ where
     };
 
     if config.recording.is_records_api_enabled() {
-        tracing::info!("Enable endpoints for records");
+        tracing::info!("Enable endpoints for records, rules and recurring schedules");
         router = router
+            .route("/recording/rules", routing::get(recording::rules::list))
+            .route("/recording/rules", routing::post(recording::rules::create))
+            .route("/recording/rules/{id}", routing::get(recording::rules::get))
+            .route(
+                "/recording/rules/{id}",
+                routing::put(recording::rules::update),
+            )
+            .route(
+                "/recording/rules/{id}",
+                routing::delete(recording::rules::delete),
+            )
+            .route(
+                "/recording/recurring-schedules",
+                routing::get(recording::recurring_schedules::list),
+            )
+            .route(
+                "/recording/recurring-schedules",
+                routing::post(recording::recurring_schedules::create),
+            )
+            .route(
+                "/recording/recurring-schedules/{id}",
+                routing::get(recording::recurring_schedules::get),
+            )
+            .route(
+                "/recording/recurring-schedules/{id}",
+                routing::delete(recording::recurring_schedules::delete),
+            )
             .route("/recording/records", routing::get(recording::records::list))
             .route(
                 "/recording/records/{id}",
@@ -184,6 +262,14 @@ where
                 "/recording/records/{id}",
                 routing::delete(recording::records::delete),
             )
+            .route(
+                "/recording/records/{id}/post-processes",
+                routing::get(recording::records::post_processes::list),
+            )
+            .route(
+                "/recording/records/{id}/post-processes/{name}",
+                routing::post(recording::records::post_processes::run),
+            )
             // The following endpoints won't allocate any tuner.
             .route(
                 "/recording/records/{id}/stream",
@@ -192,6 +278,10 @@ where
             .route(
                 "/recording/records/{id}/stream",
                 routing::head(recording::records::stream::head),
+            )
+            .route(
+                "/recording/records/{id}/playlist.m3u8",
+                routing::get(recording::records::playlist),
             );
     }
 
@@ -200,6 +290,11 @@ where
         router = router
             .route("/timeshift", routing::get(timeshift::list))
             .route("/timeshift/{recorder}", routing::get(timeshift::get))
+            .route(
+                "/timeshift/{recorder}/start",
+                routing::post(timeshift::start),
+            )
+            .route("/timeshift/{recorder}/stop", routing::post(timeshift::stop))
             .route(
                 "/timeshift/{recorder}/records",
                 routing::get(timeshift::records::list),
@@ -208,6 +303,14 @@ where
                 "/timeshift/{recorder}/records/{id}",
                 routing::get(timeshift::records::get),
             )
+            .route(
+                "/timeshift/{recorder}/records/{id}/pin",
+                routing::put(timeshift::records::pin),
+            )
+            .route(
+                "/timeshift/{recorder}/records/{id}/pin",
+                routing::delete(timeshift::records::unpin),
+            )
             // The following two endpoints won't allocate any tuner.
             .route(
                 "/timeshift/{recorder}/stream",
@@ -216,7 +319,17 @@ where
             .route(
                 "/timeshift/{recorder}/records/{id}/stream",
                 routing::get(timeshift::records::stream),
+            )
+            .route(
+                "/timeshift/{recorder}/records/{id}/playlist.m3u8",
+                routing::get(timeshift::records::playlist),
             );
+        if config.recording.is_records_api_enabled() {
+            router = router.route(
+                "/timeshift/{recorder}/records/{id}/export",
+                routing::post(timeshift::records::export),
+            );
+        }
     }
 
     router
@@ -231,6 +344,10 @@ where
         status::get,
         tuners::list,
         tuners::get,
+        tuners::stats,
+        tuners::list_reservations,
+        tuners::create_reservation,
+        tuners::delete_reservation,
         channels::list,
         channels::stream::get,
         channels::stream::head,
@@ -254,23 +371,43 @@ where
         recording::schedules::list,
         recording::schedules::get,
         recording::schedules::create,
+        recording::schedules::create_time_range,
         recording::schedules::delete,
         recording::schedules::clear,
+        recording::rules::list,
+        recording::rules::get,
+        recording::rules::create,
+        recording::rules::update,
+        recording::rules::delete,
+        recording::recurring_schedules::list,
+        recording::recurring_schedules::get,
+        recording::recurring_schedules::create,
+        recording::recurring_schedules::delete,
+        recording::conflicts::list,
         recording::recorders::list,
         recording::recorders::get,
         recording::recorders::create,
         recording::recorders::delete,
         recording::records::list,
         recording::records::get,
+        recording::records::playlist,
         recording::records::delete,
+        recording::records::post_processes::list,
+        recording::records::post_processes::run,
         recording::records::stream::get,
         recording::records::stream::head,
         timeshift::list,
         timeshift::get,
+        timeshift::start,
+        timeshift::stop,
         timeshift::stream,
         timeshift::records::list,
         timeshift::records::get,
+        timeshift::records::pin,
+        timeshift::records::unpin,
+        timeshift::records::export,
         timeshift::records::stream,
+        timeshift::records::playlist,
     ),
     components(
         schemas(
@@ -279,18 +416,37 @@ where
             models::WebOnairProgram,
             models::WebProcessModel,
             models::WebRecordingRecorder,
+            models::WebRecordingRule,
+            models::WebRecordingRuleInput,
             models::WebRecordingSchedule,
             models::WebRecordingScheduleInput,
+            models::WebRecurringSchedule,
+            models::WebRecurringScheduleInput,
+            models::WebTimeRangeRecordingScheduleInput,
+            models::WebTimeshiftGap,
             models::WebTimeshiftRecord,
+            models::WebTimeshiftRecordExportInput,
             models::WebTimeshiftRecorder,
+            models::WebTunerReservationInput,
             ChannelType,
             MirakurunChannel,
             MirakurunProgram,
             MirakurunService,
             MirakurunTuner,
+            PostProcessJob,
+            PostProcessJobState,
+            RecordingAttempt,
+            RecordingConflict,
             RecordingFailedReason,
             RecordingOptions,
+            RecordingRuleConditions,
+            RecordingRuleGenre,
+            RecordingRuleTimeRange,
             RecordingScheduleState,
+            TimeshiftGapReason,
+            TunerHealth,
+            TunerReservation,
+            TunerSessionStats,
         ),
     ),
     modifiers(
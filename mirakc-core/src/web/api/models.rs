@@ -2,6 +2,7 @@ use std::collections::HashSet;
 use std::convert::Infallible;
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use axum::extract::FromRequestParts;
 use axum::http::header::USER_AGENT;
@@ -34,12 +35,20 @@ use crate::models::TunerUserInfo;
 use crate::models::TunerUserPriority;
 use crate::onair::OnairProgram;
 use crate::recording;
+use crate::recording::PostProcessJob;
 use crate::recording::Record;
 use crate::recording::RecordId;
+use crate::recording::RecordingAttempt;
+use crate::recording::RecordingConflict;
 use crate::recording::RecordingFailedReason;
 use crate::recording::RecordingOptions;
+use crate::recording::RecordingRuleConditions;
+use crate::recording::RecordingRuleId;
 use crate::recording::RecordingScheduleState;
 use crate::recording::RecordingStatus;
+use crate::recording::RecurringScheduleId;
+use crate::timeshift::TimeshiftGap;
+use crate::timeshift::TimeshiftGapReason;
 use crate::timeshift::TimeshiftRecordModel;
 use crate::timeshift::TimeshiftRecorderModel;
 
@@ -57,6 +66,27 @@ pub(in crate::web) struct Version {
 #[derive(Serialize, ToSchema)]
 pub(in crate::web) struct Status {}
 
+/// Input data used when creating a tuner reservation.
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(title = "TunerReservationInput")]
+pub(in crate::web) struct WebTunerReservationInput {
+    /// The index of the tuner to reserve.
+    pub tuner_index: usize,
+    /// The ID of the user of the reserved tuner.
+    pub user_id: String,
+    /// The priority of the reservation.
+    pub priority: i32,
+    /// The start time of the reservation in UNIX time (milliseconds).
+    #[serde(with = "ts_milliseconds")]
+    #[schema(value_type = i64)]
+    pub start_at: DateTime<Jst>,
+    /// The end time of the reservation in UNIX time (milliseconds).
+    #[serde(with = "ts_milliseconds")]
+    #[schema(value_type = i64)]
+    pub end_at: DateTime<Jst>,
+}
+
 /// A recording schedule model.
 #[derive(Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -76,16 +106,51 @@ pub(in crate::web) struct WebRecordingSchedule {
     /// This property exists only when the recording failed.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub failed_reason: Option<RecordingFailedReason>,
+    /// The ID of the recording rule which added the recording schedule.
+    ///
+    /// This property exists only when the recording schedule was added by a recording rule.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<u32>)]
+    pub rule_id: Option<RecordingRuleId>,
+    /// The ID of the recurring schedule which added the recording schedule.
+    ///
+    /// This property exists only when the recording schedule was added by a recurring schedule.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<u32>)]
+    pub recurring_schedule_id: Option<RecurringScheduleId>,
+    /// A list of attempts to start the recording.
+    ///
+    /// The recording is retried on another tuner when the recording pipeline terminated
+    /// abnormally.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<RecordingAttempt>,
+    /// A conflict of tuner allocation.
+    ///
+    /// This property exists only when no tuner is expected to be available for the recording
+    /// schedule.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflict: Option<RecordingConflict>,
+    /// `true` if the recording schedule is a time-range recording schedule.
+    ///
+    /// The TV program of a time-range recording schedule doesn't exist in EPG and its `eventId`
+    /// is meaningless.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub time_range: bool,
 }
 
 impl From<recording::RecordingSchedule> for WebRecordingSchedule {
     fn from(value: recording::RecordingSchedule) -> Self {
         WebRecordingSchedule {
+            time_range: value.is_time_range(),
             state: value.state,
             program: value.program.clone().into(),
             options: value.options,
             tags: value.tags,
             failed_reason: value.failed_reason,
+            rule_id: value.rule_id,
+            recurring_schedule_id: value.recurring_schedule_id,
+            attempts: value.attempts,
+            conflict: value.conflict,
         }
     }
 }
@@ -108,44 +173,249 @@ pub(in crate::web) struct WebRecordingScheduleInput {
 
 impl WebRecordingScheduleInput {
     pub fn validate(&self, config: &Config) -> Result<(), Error> {
-        match self.options.content_path.as_ref() {
-            Some(content_path) => {
-                if content_path.to_str().is_none() {
-                    let err = Error::InvalidPath("Must be a valid Unicode string");
-                    tracing::error!(
-                        %err,
-                        input.options.content_path = ?content_path
-                    );
-                    return Err(err);
-                }
-
-                if content_path.is_absolute() {
-                    let err = Error::InvalidPath("Must be a relative path");
-                    tracing::error!(
-                        %err,
-                        input.options.content_path = ?content_path
-                    );
-                    return Err(err);
-                }
-
-                let basedir = config.recording.basedir.as_ref().unwrap();
-                if !basedir.join(content_path).parse_dot()?.starts_with(basedir) {
-                    let err = Error::InvalidPath("Must be under config.recording.basedir");
-                    tracing::error!(
-                        %err,
-                        input.options.content_path = ?content_path
-                    );
-                    return Err(err);
-                }
+        validate_recording_options(&self.options, config)
+    }
+}
+
+/// Input data used when creating a time-range recording schedule.
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(title = "TimeRangeRecordingScheduleInput")]
+pub(in crate::web) struct WebTimeRangeRecordingScheduleInput {
+    /// A Mirakurun service ID of the target service.
+    #[schema(value_type = u64)]
+    pub service_id: ServiceId,
+    /// The start time of the recording in UNIX time (milliseconds).
+    #[serde(with = "ts_milliseconds")]
+    #[schema(value_type = i64)]
+    pub start_at: DateTime<Jst>,
+    /// The end time of the recording in UNIX time (milliseconds).
+    #[serde(with = "ts_milliseconds")]
+    #[schema(value_type = i64)]
+    pub end_at: DateTime<Jst>,
+    /// A name used as the title of the TV program.
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Recording options.
+    pub options: RecordingOptions,
+    /// A list of tags.
+    #[serde(default)]
+    #[schema(value_type = Vec<String>)]
+    pub tags: HashSet<String>,
+}
+
+impl WebTimeRangeRecordingScheduleInput {
+    pub fn validate(&self, config: &Config) -> Result<(), Error> {
+        if self.start_at >= self.end_at {
+            let err = Error::InvalidRequest("endAt must be later than startAt");
+            tracing::error!(%err, input.start_at = %self.start_at, input.end_at = %self.end_at);
+            return Err(err);
+        }
+        if self.options.follow_series {
+            let err = Error::InvalidRequest("followSeries cannot be used");
+            tracing::error!(%err);
+            return Err(err);
+        }
+        validate_recording_options(&self.options, config)
+    }
+}
+
+fn validate_recording_options(options: &RecordingOptions, config: &Config) -> Result<(), Error> {
+    options.validate()?;
+    match options.content_path.as_ref() {
+        Some(content_path) => {
+            if content_path.to_str().is_none() {
+                let err = Error::InvalidPath("Must be a valid Unicode string");
+                tracing::error!(
+                    %err,
+                    input.options.content_path = ?content_path
+                );
+                return Err(err);
             }
-            None => {
-                if !config.recording.is_records_api_enabled() {
-                    return Err(Error::InvalidRequest("contentPath is required"));
-                }
+
+            if content_path.is_absolute() {
+                let err = Error::InvalidPath("Must be a relative path");
+                tracing::error!(
+                    %err,
+                    input.options.content_path = ?content_path
+                );
+                return Err(err);
+            }
+
+            let basedir = config.recording.basedir.as_ref().unwrap();
+            if !basedir.join(content_path).parse_dot()?.starts_with(basedir) {
+                let err = Error::InvalidPath("Must be under config.recording.basedir");
+                tracing::error!(
+                    %err,
+                    input.options.content_path = ?content_path
+                );
+                return Err(err);
             }
+
+            // Following episodes cannot be recorded into the same file.
+            if options.follow_series {
+                return Err(Error::InvalidRequest(
+                    "contentPath cannot be specified with followSeries",
+                ));
+            }
+        }
+        None => {
+            if !config.recording.is_records_api_enabled() {
+                return Err(Error::InvalidRequest("contentPath is required"));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// A recording rule model.
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(title = "RecordingRule")]
+pub(in crate::web) struct WebRecordingRule {
+    /// The rule ID.
+    #[schema(value_type = u32)]
+    pub id: RecordingRuleId,
+    /// Conditions of TV programs to be recorded.
+    pub conditions: RecordingRuleConditions,
+    /// Recording options used for recording schedules added by the rule.
+    pub options: RecordingOptions,
+    /// A list of tags copied to recording schedules added by the rule.
+    #[schema(value_type = Vec<String>)]
+    pub tags: HashSet<String>,
+}
+
+impl From<recording::RecordingRule> for WebRecordingRule {
+    fn from(value: recording::RecordingRule) -> Self {
+        WebRecordingRule {
+            id: value.id,
+            conditions: value.conditions,
+            options: value.options,
+            tags: value.tags,
+        }
+    }
+}
+
+/// Input data used when creating or updating a recording rule.
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(title = "RecordingRuleInput")]
+pub(in crate::web) struct WebRecordingRuleInput {
+    /// Conditions of TV programs to be recorded.
+    pub conditions: RecordingRuleConditions,
+    /// Recording options used for recording schedules added by the rule.
+    ///
+    /// `contentPath` must not be specified.
+    pub options: RecordingOptions,
+    /// A list of tags copied to recording schedules added by the rule.
+    #[serde(default)]
+    #[schema(value_type = Vec<String>)]
+    pub tags: HashSet<String>,
+}
+
+impl WebRecordingRuleInput {
+    pub fn validate(&self) -> Result<(), Error> {
+        // Content files are always created with auto-generated filenames.
+        if self.options.content_path.is_some() {
+            return Err(Error::InvalidRequest("contentPath cannot be specified"));
+        }
+        self.options.validate()?;
+        self.conditions.validate()
+    }
+}
+
+/// A recurring schedule model.
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(title = "RecurringSchedule")]
+pub(in crate::web) struct WebRecurringSchedule {
+    /// The recurring schedule ID.
+    #[schema(value_type = u32)]
+    pub id: RecurringScheduleId,
+    /// A Mirakurun service ID of the target service.
+    #[schema(value_type = u64)]
+    pub service_id: ServiceId,
+    /// A cron expression specifying the start times of recordings.
+    pub cron: String,
+    /// The duration of each recording in milliseconds.
+    ///
+    /// TV programs on air at the start times are recorded if this property doesn't exist.
+    #[serde(with = "duration_milliseconds_option")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<u64>)]
+    pub duration: Option<Duration>,
+    /// A name used as the title of each time-range recording schedule.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Recording options used for recording schedules added by the recurring schedule.
+    pub options: RecordingOptions,
+    /// A list of tags copied to recording schedules added by the recurring schedule.
+    #[schema(value_type = Vec<String>)]
+    pub tags: HashSet<String>,
+}
+
+impl From<recording::RecurringSchedule> for WebRecurringSchedule {
+    fn from(value: recording::RecurringSchedule) -> Self {
+        WebRecurringSchedule {
+            id: value.id,
+            service_id: value.service_id,
+            cron: value.cron,
+            duration: value.duration,
+            name: value.name,
+            options: value.options,
+            tags: value.tags,
         }
+    }
+}
 
-        Ok(())
+/// Input data used when creating a recurring schedule.
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(title = "RecurringScheduleInput")]
+pub(in crate::web) struct WebRecurringScheduleInput {
+    /// A Mirakurun service ID of the target service.
+    #[schema(value_type = u64)]
+    pub service_id: ServiceId,
+    /// A cron expression specifying the start times of recordings.
+    ///
+    /// The format is `sec min hour day-of-month month day-of-week [year]`.
+    pub cron: String,
+    /// The duration of each recording in milliseconds.
+    ///
+    /// When this property is specified, a time-range recording schedule is added for each start
+    /// time.  Otherwise, a recording schedule for the TV program on air at each start time is
+    /// added.
+    #[serde(default, with = "duration_milliseconds_option")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<u64>)]
+    pub duration: Option<Duration>,
+    /// A name used as the title of each time-range recording schedule.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Recording options used for recording schedules added by the recurring schedule.
+    ///
+    /// `contentPath` and `followSeries` must not be specified.
+    pub options: RecordingOptions,
+    /// A list of tags copied to recording schedules added by the recurring schedule.
+    #[serde(default)]
+    #[schema(value_type = Vec<String>)]
+    pub tags: HashSet<String>,
+}
+
+impl WebRecurringScheduleInput {
+    pub fn validate(&self) -> Result<(), Error> {
+        // Content files are always created with auto-generated filenames.
+        if self.options.content_path.is_some() {
+            return Err(Error::InvalidRequest("contentPath cannot be specified"));
+        }
+        if self.options.follow_series {
+            return Err(Error::InvalidRequest("followSeries cannot be specified"));
+        }
+        if cron::Schedule::from_str(&self.cron).is_err() {
+            return Err(Error::InvalidRequest("Invalid cron expression"));
+        }
+        self.options.validate()
     }
 }
 
@@ -206,6 +476,12 @@ pub(in crate::web) struct WebRecord {
 
     /// Information about the content.
     pub content: WebContentInfo,
+
+    /// A list of post-process jobs for the record.
+    ///
+    /// Jobs are listed only when `config.recording.post-process` is configured.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_process_jobs: Vec<PostProcessJob>,
 }
 
 impl From<(Record, Option<u64>)> for WebRecord {
@@ -228,12 +504,14 @@ impl From<(Record, Option<u64>)> for WebRecord {
                 end_time: record.recording_end_time,
                 duration: record.recording_duration,
                 failed_reason,
+                attempts: record.attempts,
             },
             content: WebContentInfo {
                 path: record.content_path,
                 r#type: record.content_type,
                 length: content_length,
             },
+            post_process_jobs: record.post_process_jobs,
         }
     }
 }
@@ -285,6 +563,14 @@ pub(in crate::web) struct WebRecordingInfo {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub failed_reason: Option<RecordingFailedReason>,
+
+    /// A list of attempts to start the recording.
+    ///
+    /// The recording is retried on another tuner when the recording pipeline terminated
+    /// abnormally.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub attempts: Vec<RecordingAttempt>,
 }
 
 /// A recording status.
@@ -423,9 +709,17 @@ pub(in crate::web) struct WebTimeshiftRecorder {
     /// Users can still access the records even if this property returns
     /// `false`.
     pub recording: bool,
+    /// `true` while the recorder is stopped by
+    /// [/timeshift/{recorder}/stop](#/timeshift/stopTimeshiftRecorder).
+    pub paused: bool,
     /// An ID of the record currently being recorded.
     #[schema(value_type = Option<u32>)]
     pub current_record_id: Option<TimeshiftRecordId>,
+    /// A list of gaps in the timeshift timeline in chronological order.
+    ///
+    /// No data is recorded in a gap.  The last gap has no `endTime` while the
+    /// recording is stopped.
+    pub gaps: Vec<WebTimeshiftGap>,
 }
 
 impl From<TimeshiftRecorderModel> for WebTimeshiftRecorder {
@@ -443,7 +737,38 @@ impl From<TimeshiftRecorderModel> for WebTimeshiftRecorder {
                 .map(WebProcessModel::from)
                 .collect(),
             recording: model.recording,
+            paused: model.paused,
             current_record_id: model.current_record_id,
+            gaps: model.gaps.into_iter().map(WebTimeshiftGap::from).collect(),
+        }
+    }
+}
+
+/// A gap in the timeshift timeline.
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(title = "TimeshiftGap")]
+pub(in crate::web) struct WebTimeshiftGap {
+    /// The start time of the gap in UNIX time (milliseconds).
+    #[serde(with = "ts_milliseconds")]
+    #[schema(value_type = i64)]
+    pub start_time: DateTime<Jst>,
+    /// The end time of the gap in UNIX time (milliseconds).
+    ///
+    /// `null` while the recording is stopped.
+    #[serde(with = "ts_milliseconds_option")]
+    #[schema(value_type = Option<i64>)]
+    pub end_time: Option<DateTime<Jst>>,
+    /// The reason why the recording stopped.
+    pub reason: TimeshiftGapReason,
+}
+
+impl From<TimeshiftGap> for WebTimeshiftGap {
+    fn from(gap: TimeshiftGap) -> Self {
+        Self {
+            start_time: gap.start_time,
+            end_time: gap.end_time,
+            reason: gap.reason,
         }
     }
 }
@@ -490,6 +815,16 @@ pub(in crate::web) struct WebTimeshiftRecord {
     pub size: u64,
     /// `true` while recording, `false` otherwise.
     pub recording: bool,
+    /// `true` if the record is pinned, `false` otherwise.
+    ///
+    /// A pinned record is never purged.  The timeshift recorder exports the pinned record into a
+    /// record before overwriting it, or stops recording if it cannot be exported.
+    pub pinned: bool,
+    /// A list of gaps within the broadcast time of the TV program.
+    ///
+    /// The record is incomplete if this list is not empty.  The recording of the TV program
+    /// interrupted by a gap is split into multiple records.
+    pub gaps: Vec<WebTimeshiftGap>,
 }
 
 impl From<TimeshiftRecordModel> for WebTimeshiftRecord {
@@ -501,6 +836,47 @@ impl From<TimeshiftRecordModel> for WebTimeshiftRecord {
             duration: model.end_time - model.start_time,
             size: model.size,
             recording: model.recording,
+            pinned: model.pinned,
+            gaps: model.gaps.into_iter().map(WebTimeshiftGap::from).collect(),
+        }
+    }
+}
+
+/// Input data used when exporting a timeshift record into a record.
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+#[schema(title = "TimeshiftRecordExportInput")]
+pub(in crate::web) struct WebTimeshiftRecordExportInput {
+    /// The path of the content file relative to `config.recording.basedir`.
+    ///
+    /// The path is generated from `config.recording.content-path` if it's not specified.
+    #[serde(default)]
+    #[schema(value_type = Option<String>)]
+    pub content_path: Option<PathBuf>,
+    /// A list of post-filters applied to the exported stream.
+    #[serde(default)]
+    pub post_filters: Vec<String>,
+    /// A list of tags.
+    #[serde(default)]
+    #[schema(value_type = Vec<String>)]
+    pub tags: HashSet<String>,
+}
+
+impl WebTimeshiftRecordExportInput {
+    pub fn validate(&self, config: &Config) -> Result<(), Error> {
+        validate_recording_options(&self.recording_options(), config)
+    }
+
+    pub fn recording_options(&self) -> RecordingOptions {
+        RecordingOptions {
+            content_path: self.content_path.clone(),
+            priority: 0,
+            pre_filters: vec![],
+            post_filters: self.post_filters.clone(),
+            log_filter: None,
+            start_margin: None,
+            end_margin: None,
+            follow_series: false,
         }
     }
 }
@@ -570,6 +946,43 @@ pub(in crate::web) struct TimeshiftRecordQuery {
     pub record: Option<TimeshiftRecordId>,
 }
 
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[serde(rename_all = "kebab-case")]
+#[into_params(parameter_in = Query)]
+pub(in crate::web) struct SeekQuery {
+    /// Starts streaming at the specified time in Unix time (milliseconds).
+    #[serde(default, with = "ts_milliseconds_option")]
+    #[param(value_type = Option<i64>)]
+    pub start_time: Option<DateTime<Jst>>,
+
+    /// Starts streaming at the specified offset (milliseconds) from the beginning of the stream.
+    #[serde(default, with = "duration_milliseconds_option")]
+    #[param(value_type = Option<i64>)]
+    pub offset: Option<Duration>,
+}
+
+impl SeekQuery {
+    /// Resolves the time to seek.
+    ///
+    /// `base` is used as the beginning of the stream when `offset` is specified.
+    pub fn resolve(&self, base: Option<DateTime<Jst>>) -> Result<Option<DateTime<Jst>>, Error> {
+        match (self.start_time, self.offset) {
+            (Some(_), Some(_)) => Err(Error::InvalidRequest(
+                "start-time and offset cannot be specified at the same time",
+            )),
+            (Some(start_time), None) => Ok(Some(start_time)),
+            (None, Some(offset)) if offset < Duration::zero() => {
+                Err(Error::InvalidRequest("offset must not be negative"))
+            }
+            (None, Some(offset)) => match base {
+                Some(base) => Ok(Some(base + offset)),
+                None => Err(Error::RecordNotFound),
+            },
+            (None, None) => Ok(None),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, IntoParams)]
 #[serde(rename_all = "kebab-case")]
 #[into_params(parameter_in = Query)]
@@ -2,6 +2,7 @@ use super::*;
 
 use crate::epg::EpgProgram;
 use crate::epg::EpgService;
+use crate::filter::PROGRAM_FILTER_END_MARGIN_MS;
 use crate::models::Clock;
 use crate::web::api::stream::StreamingHeaderParams;
 use crate::web::api::stream::do_head_stream;
@@ -76,6 +77,7 @@ where
             channel: service.channel.clone(),
             user: user.clone(),
             stream_id: None,
+            excluded_tuners: vec![],
         })
         .await??;
 
@@ -221,7 +223,8 @@ fn build_filters(
         .insert("clock_pcr", &clock.pcr)?
         .insert("clock_time", &clock.time)?
         .insert("video_tags", &video_tags)?
-        .insert("audio_tags", &audio_tags)?;
+        .insert("audio_tags", &audio_tags)?
+        .insert("end_margin", &PROGRAM_FILTER_END_MARGIN_MS)?;
     if let Some(max_start_delay) = config.server.program_stream_max_start_delay {
         // Round off the fractional (nanosecond) part of the duration.
         //
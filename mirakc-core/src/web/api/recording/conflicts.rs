@@ -0,0 +1,34 @@
+use super::*;
+
+/// Lists recording schedules which will fail to start recording due to lack of tuners.
+///
+/// Conflicts are detected by simulating tuner allocation for recording schedules in the
+/// `scheduled`, `tracking` and `recording` states.  The simulation takes into account the
+/// configuration of each tuner, tuners dedicated for timeshift recorders and on-air program
+/// trackers, sharing a tuner between recording schedules for the same channel and priorities of
+/// recording schedules.  Tuners used for streaming are not taken into account.
+#[utoipa::path(
+    get,
+    path = "/recording/conflicts",
+    responses(
+        (status = 200, description = "OK", body = [WebRecordingSchedule]),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "getRecordingConflicts",
+)]
+pub(in crate::web::api) async fn list<R>(
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+) -> Result<Json<Vec<WebRecordingSchedule>>, Error>
+where
+    R: Call<recording::QueryRecordingConflicts>,
+{
+    let schedules = recording_manager
+        .call(recording::QueryRecordingConflicts)
+        .await?;
+    Ok(Json(
+        schedules
+            .into_iter()
+            .map(WebRecordingSchedule::from)
+            .collect(),
+    ))
+}
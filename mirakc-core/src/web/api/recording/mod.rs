@@ -2,6 +2,9 @@ use super::*;
 
 use crate::recording;
 
+pub(super) mod conflicts;
 pub(super) mod recorders;
 pub(super) mod records;
+pub(super) mod recurring_schedules;
+pub(super) mod rules;
 pub(super) mod schedules;
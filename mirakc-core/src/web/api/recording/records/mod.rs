@@ -1,9 +1,15 @@
+pub(in crate::web::api) mod post_processes;
 pub(in crate::web::api) mod stream;
 
 use super::*;
 
+use chrono_jst::Jst;
+
 use crate::recording::RecordId;
 use crate::recording::RecordingStatus;
+use crate::web::api::hls::build_playlist;
+use crate::web::api::hls::split_into_segments;
+use crate::web::api::hls::split_into_segments_by_index;
 
 // NOTE: Record files can be directly accessible in this module, but we send messages to the
 // `RecordingManager` actor in order to serialize all requests and process them one by one.
@@ -71,6 +77,69 @@ where
     Ok(Json(record))
 }
 
+/// Gets an HLS playlist of the content of a record.
+///
+/// Each segment in the playlist is a byte range of the stream provided by `getRecordStream`.  No
+/// re-encoding is performed.  The content is split into segments at points in the content index
+/// of the record.  Each segment is about 10 seconds.  If the record has no content index, the
+/// content is split into segments of about 10 seconds assuming that its bitrate is constant.
+///
+/// The playlist has no `#EXT-X-ENDLIST` tag while the record is being recorded.
+///
+/// A request for a record without content file always returns status code 204.
+#[utoipa::path(
+    get,
+    path = "/recording/records/{id}/playlist.m3u8",
+    params(
+        ("id" = String, Path, description = "Record ID"),
+    ),
+    responses(
+        (status = 200, description = "OK", content_type = "application/vnd.apple.mpegurl", body = String),
+        (status = 204, description = "No Content"),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "getRecordPlaylist",
+)]
+pub(in crate::web::api) async fn playlist<R>(
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+    Path(id): Path<RecordId>,
+) -> Result<Response<String>, Error>
+where
+    R: Call<recording::QueryRecord>,
+    R: Call<recording::QueryRecordContentIndex>,
+{
+    let (record, content_length) = recording_manager
+        .call(recording::QueryRecord { id: id.clone() })
+        .await??;
+
+    let content_length = match content_length {
+        Some(content_length) if content_length > 0 => content_length,
+        _ => return Err(Error::NoContent),
+    };
+
+    let incomplete = matches!(record.recording_status, RecordingStatus::Recording);
+    let duration = match record.recording_duration {
+        Some(duration) => duration,
+        None => Jst::now() - record.recording_start_time,
+    };
+
+    let segments = match recording_manager
+        .call(recording::QueryRecordContentIndex { id })
+        .await??
+    {
+        Some(index) => split_into_segments_by_index(index.points(), content_length),
+        None => vec![],
+    };
+    let segments = if segments.is_empty() {
+        split_into_segments(duration, content_length)
+    } else {
+        segments
+    };
+    // The URI is relative to the URL of the playlist.
+    build_playlist("stream", &segments, incomplete)
+}
+
 /// Removes a record.
 ///
 /// The record cannot be removed while it's recording.  Firstly stop the recording, then remove.
@@ -0,0 +1,67 @@
+use super::*;
+
+use crate::recording::PostProcessJob;
+
+/// Lists post-process jobs for a record.
+#[utoipa::path(
+    get,
+    path = "/recording/records/{id}/post-processes",
+    params(
+        ("id" = String, Path, description = "Record ID"),
+    ),
+    responses(
+        (status = 200, description = "OK", body = [PostProcessJob]),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "getRecordPostProcesses",
+)]
+pub(in crate::web::api) async fn list<R>(
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+    Path(id): Path<RecordId>,
+) -> Result<Json<Vec<PostProcessJob>>, Error>
+where
+    R: Call<recording::QueryRecord>,
+{
+    let (record, _) = recording_manager
+        .call(recording::QueryRecord { id })
+        .await??;
+    Ok(Json(record.post_process_jobs))
+}
+
+/// Runs a post-process job for a record again.
+///
+/// The job is queued and runs when a slot is available.  This is typically used for retrying a
+/// failed job.
+///
+/// The request fails if the job is already pending or running.
+#[utoipa::path(
+    post,
+    path = "/recording/records/{id}/post-processes/{name}",
+    params(
+        ("id" = String, Path, description = "Record ID"),
+        ("name" = String, Path, description = "Job name"),
+    ),
+    responses(
+        (status = 200, description = "OK", body = PostProcessJob),
+        (status = 400, description = "Bad Request"),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "runRecordPostProcess",
+)]
+pub(in crate::web::api) async fn run<R>(
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+    Path((id, name)): Path<(RecordId, String)>,
+) -> Result<Json<PostProcessJob>, Error>
+where
+    R: Call<recording::RunPostProcessJob>,
+{
+    let job = recording_manager
+        .call(recording::RunPostProcessJob {
+            record_id: id,
+            name,
+        })
+        .await??;
+    Ok(Json(job))
+}
@@ -4,6 +4,7 @@ use crate::recording::Record;
 use crate::web::api::stream::StreamingHeaderParams;
 use crate::web::api::stream::compute_content_length;
 use crate::web::api::stream::compute_content_range;
+use crate::web::api::stream::compute_seek_range;
 use crate::web::api::stream::do_head_stream;
 use crate::web::api::stream::streaming;
 
@@ -16,6 +17,13 @@ use crate::web::api::stream::streaming;
 /// A request for a record without content file always returns status code 204.
 ///
 /// A range request with filters always causes an error response with status code 400.
+///
+/// The `start-time` or `offset` query parameter can be used for starting the stream at a specific
+/// time.  `offset` is relative to the start time of the recording.  The time is mapped to a
+/// position in the content file by using the content index of the record.  If the record has no
+/// content index, the position is estimated assuming that its bitrate is constant.  The response for such a request is not seekable and a range request for it always causes an
+/// error response with status code 400.
+#[allow(clippy::too_many_arguments)]
 #[utoipa::path(
     get,
     path = "/recording/records/{id}/stream",
@@ -23,12 +31,15 @@ use crate::web::api::stream::streaming;
         ("id" = String, Path, description = "Record ID"),
         ("pre-filters" = Option<[String]>, Query, description = "pre-filters"),
         ("post-filters" = Option<[String]>, Query, description = "post-filters"),
+        SeekQuery,
     ),
     responses(
         (status = 200, description = "OK"),
         (status = 204, description = "No Content"),
+        (status = 206, description = "Partial Content"),
         (status = 400, description = "Bad Request"),
         (status = 404, description = "Not Found"),
+        (status = 416, description = "Range Not Satisfiable"),
         (status = 500, description = "Internal Server Error"),
     ),
     operation_id = "getRecordStream",
@@ -41,10 +52,12 @@ pub(in crate::web::api) async fn get<R, W>(
     ranges: Option<TypedHeader<axum_extra::headers::Range>>,
     user: TunerUser,
     Qs(filter_setting): Qs<FilterSetting>,
+    Query(seek): Query<SeekQuery>,
 ) -> Result<Response, Error>
 where
     R: Call<recording::OpenContent>,
     R: Call<recording::QueryRecord>,
+    R: Call<recording::QueryRecordOffset>,
     W: Spawn,
 {
     let (record, content_length) = recording_manager
@@ -58,23 +71,46 @@ where
 
     let (filters, content_type, seekable) = build_filters(&config, &filter_setting, &record)?;
     let incomplete = matches!(record.recording_status, RecordingStatus::Recording);
-    let range = compute_content_range(&ranges, content_length, incomplete, seekable)?;
-    let length = compute_content_length(content_length, incomplete, range.as_ref());
-
-    let params = StreamingHeaderParams {
-        seekable,
-        content_type,
-        length,
-        range,
-        user,
+
+    let (msg, params) = match seek.resolve(Some(record.recording_start_time))? {
+        Some(time) => {
+            let msg = recording::QueryRecordOffset {
+                id: id.clone(),
+                time,
+            };
+            let pos = recording_manager.call(msg).await??;
+            let offset = compute_seek_range(&ranges, pos, content_length, incomplete)?
+                .map_or(0, |range| range.first());
+            let params = StreamingHeaderParams {
+                seekable: false,
+                content_type,
+                length: None,
+                range: None,
+                user,
+            };
+            (
+                recording::OpenContent::with_offset(id.clone(), offset),
+                params,
+            )
+        }
+        None => {
+            let range = compute_content_range(&ranges, content_length, incomplete, seekable)?;
+            let length = compute_content_length(content_length, incomplete, range.as_ref());
+            let params = StreamingHeaderParams {
+                seekable,
+                content_type,
+                length,
+                range,
+                user,
+            };
+            (
+                recording::OpenContent::new(id.clone(), params.range.clone()),
+                params,
+            )
+        }
     };
 
-    let (stream, stop_trigger) = recording_manager
-        .call(recording::OpenContent::new(
-            id.clone(),
-            params.range.clone(),
-        ))
-        .await??;
+    let (stream, stop_trigger) = recording_manager.call(msg).await??;
 
     streaming(&config, &spawner, stream, filters, &params, stop_trigger).await
 }
@@ -86,11 +122,15 @@ where
         ("id" = String, Path, description = "Record ID"),
         ("pre-filters" = Option<[String]>, Query, description = "pre-filters"),
         ("post-filters" = Option<[String]>, Query, description = "post-filters"),
+        SeekQuery,
     ),
     responses(
         (status = 200, description = "OK"),
         (status = 204, description = "No Content"),
+        (status = 206, description = "Partial Content"),
+        (status = 400, description = "Bad Request"),
         (status = 404, description = "Not Found"),
+        (status = 416, description = "Range Not Satisfiable"),
         (status = 500, description = "Internal Server Error"),
     ),
     operation_id = "checkRecordStream",
@@ -102,9 +142,11 @@ pub(in crate::web::api) async fn head<R>(
     ranges: Option<TypedHeader<axum_extra::headers::Range>>,
     user: TunerUser,
     Qs(filter_setting): Qs<FilterSetting>,
+    Query(seek): Query<SeekQuery>,
 ) -> Result<Response, Error>
 where
     R: Call<recording::QueryRecord>,
+    R: Call<recording::QueryRecordOffset>,
 {
     let (record, content_length) = recording_manager
         .call(recording::QueryRecord { id: id.clone() })
@@ -117,15 +159,34 @@ where
 
     let (_, content_type, seekable) = build_filters(&config, &filter_setting, &record)?;
     let incomplete = matches!(record.recording_status, RecordingStatus::Recording);
-    let range = compute_content_range(&ranges, content_length, incomplete, seekable)?;
-    let length = compute_content_length(content_length, incomplete, range.as_ref());
-
-    let params = StreamingHeaderParams {
-        seekable,
-        content_type,
-        length,
-        range,
-        user,
+
+    let params = match seek.resolve(Some(record.recording_start_time))? {
+        Some(time) => {
+            let msg = recording::QueryRecordOffset {
+                id: id.clone(),
+                time,
+            };
+            let pos = recording_manager.call(msg).await??;
+            compute_seek_range(&ranges, pos, content_length, incomplete)?;
+            StreamingHeaderParams {
+                seekable: false,
+                content_type,
+                length: None,
+                range: None,
+                user,
+            }
+        }
+        None => {
+            let range = compute_content_range(&ranges, content_length, incomplete, seekable)?;
+            let length = compute_content_length(content_length, incomplete, range.as_ref());
+            StreamingHeaderParams {
+                seekable,
+                content_type,
+                length,
+                range,
+                user,
+            }
+        }
     };
 
     do_head_stream(&params)
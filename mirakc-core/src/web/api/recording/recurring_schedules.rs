@@ -0,0 +1,134 @@
+use super::*;
+
+use crate::recording::RecurringScheduleId;
+
+/// Lists recurring schedules.
+#[utoipa::path(
+    get,
+    path = "/recording/recurring-schedules",
+    responses(
+        (status = 200, description = "OK", body = [WebRecurringSchedule]),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "getRecurringSchedules",
+)]
+pub(in crate::web::api) async fn list<R>(
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+) -> Result<Json<Vec<WebRecurringSchedule>>, Error>
+where
+    R: Call<recording::QueryRecurringSchedules>,
+{
+    let recurring_schedules = recording_manager
+        .call(recording::QueryRecurringSchedules)
+        .await?;
+    Ok(Json(
+        recurring_schedules
+            .into_iter()
+            .map(WebRecurringSchedule::from)
+            .collect(),
+    ))
+}
+
+/// Gets a recurring schedule.
+#[utoipa::path(
+    get,
+    path = "/recording/recurring-schedules/{id}",
+    params(
+        ("id" = u32, Path, description = "Recurring schedule ID"),
+    ),
+    responses(
+        (status = 200, description = "OK", body = WebRecurringSchedule),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "getRecurringSchedule",
+)]
+pub(in crate::web::api) async fn get<R>(
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+    Path(id): Path<RecurringScheduleId>,
+) -> Result<Json<WebRecurringSchedule>, Error>
+where
+    R: Call<recording::QueryRecurringSchedule>,
+{
+    let recurring_schedule = recording_manager
+        .call(recording::QueryRecurringSchedule { id })
+        .await??;
+    Ok(Json(recurring_schedule.into()))
+}
+
+/// Creates a recurring schedule.
+///
+/// Recording schedules are added for start times specified by the cron expression within the
+/// next few days.  They are added when the recurring schedule is created and every time EPG is
+/// updated.  Recording schedules added by the recurring schedule have the `recurringScheduleId`
+/// property.
+///
+/// When `duration` is specified, a time-range recording schedule is added for each start time.
+/// Otherwise, a recording schedule for the TV program on air at each start time is added.
+///
+/// A recording schedule added by the recurring schedule won't be added again once it's deleted by
+/// users.
+///
+/// `options.contentPath` must not be specified.  An auto-generated filename will be used for each
+/// content file.
+#[utoipa::path(
+    post,
+    path = "/recording/recurring-schedules",
+    request_body = WebRecurringScheduleInput,
+    responses(
+        (status = 201, description = "Created", body = WebRecurringSchedule),
+        (status = 401, description = "Bad Request"),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "createRecurringSchedule",
+)]
+pub(in crate::web::api) async fn create<R>(
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+    Json(input): Json<WebRecurringScheduleInput>,
+) -> Result<(StatusCode, Json<WebRecurringSchedule>), Error>
+where
+    R: Call<recording::AddRecurringSchedule>,
+{
+    input.validate()?;
+    let msg = recording::AddRecurringSchedule {
+        service_id: input.service_id,
+        cron: input.cron,
+        duration: input.duration,
+        name: input.name,
+        options: input.options,
+        tags: input.tags,
+    };
+    let recurring_schedule = recording_manager.call(msg).await??;
+    Ok((StatusCode::CREATED, Json(recurring_schedule.into())))
+}
+
+/// Deletes a recurring schedule.
+///
+/// Recording schedules added by the recurring schedule will be also deleted except for the
+/// recording schedules described in `updateRecordingRule`.
+#[utoipa::path(
+    delete,
+    path = "/recording/recurring-schedules/{id}",
+    params(
+        ("id" = u32, Path, description = "Recurring schedule ID"),
+    ),
+    responses(
+        (status = 200, description = "OK"),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "deleteRecurringSchedule",
+)]
+pub(in crate::web::api) async fn delete<R>(
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+    Path(id): Path<RecurringScheduleId>,
+) -> Result<(), Error>
+where
+    R: Call<recording::RemoveRecurringSchedule>,
+{
+    recording_manager
+        .call(recording::RemoveRecurringSchedule { id })
+        .await??;
+    Ok(())
+}
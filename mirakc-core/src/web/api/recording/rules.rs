@@ -0,0 +1,164 @@
+use super::*;
+
+use crate::recording::RecordingRuleId;
+
+/// Lists recording rules.
+#[utoipa::path(
+    get,
+    path = "/recording/rules",
+    responses(
+        (status = 200, description = "OK", body = [WebRecordingRule]),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "getRecordingRules",
+)]
+pub(in crate::web::api) async fn list<R>(
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+) -> Result<Json<Vec<WebRecordingRule>>, Error>
+where
+    R: Call<recording::QueryRecordingRules>,
+{
+    let rules = recording_manager
+        .call(recording::QueryRecordingRules)
+        .await?;
+    Ok(Json(
+        rules.into_iter().map(WebRecordingRule::from).collect(),
+    ))
+}
+
+/// Gets a recording rule.
+#[utoipa::path(
+    get,
+    path = "/recording/rules/{id}",
+    params(
+        ("id" = u32, Path, description = "Rule ID"),
+    ),
+    responses(
+        (status = 200, description = "OK", body = WebRecordingRule),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "getRecordingRule",
+)]
+pub(in crate::web::api) async fn get<R>(
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+    Path(id): Path<RecordingRuleId>,
+) -> Result<Json<WebRecordingRule>, Error>
+where
+    R: Call<recording::QueryRecordingRule>,
+{
+    let rule = recording_manager
+        .call(recording::QueryRecordingRule { id })
+        .await??;
+    Ok(Json(rule.into()))
+}
+
+/// Creates a recording rule.
+///
+/// Recording schedules will be added automatically for TV programs matching the rule when the
+/// rule is created and every time EPG is updated.  Recording schedules added by the rule have the
+/// `ruleId` property.
+///
+/// A recording schedule added by the rule won't be added again once it's deleted by users.
+///
+/// `options.contentPath` must not be specified.  An auto-generated filename will be used for each
+/// content file.
+#[utoipa::path(
+    post,
+    path = "/recording/rules",
+    request_body = WebRecordingRuleInput,
+    responses(
+        (status = 201, description = "Created", body = WebRecordingRule),
+        (status = 401, description = "Bad Request"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "createRecordingRule",
+)]
+pub(in crate::web::api) async fn create<R>(
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+    Json(input): Json<WebRecordingRuleInput>,
+) -> Result<(StatusCode, Json<WebRecordingRule>), Error>
+where
+    R: Call<recording::AddRecordingRule>,
+{
+    input.validate()?;
+    let msg = recording::AddRecordingRule {
+        conditions: input.conditions,
+        options: input.options,
+        tags: input.tags,
+    };
+    let rule = recording_manager.call(msg).await??;
+    Ok((StatusCode::CREATED, Json(rule.into())))
+}
+
+/// Updates a recording rule.
+///
+/// Recording schedules added by the rule will be deleted and then added again according to the
+/// updated rule.  Recording schedules that meet any of the following conditions won't be deleted:
+///
+///   * Recording schedules in the `tracking` or `recording` state
+///   * Recording schedules in the `scheduled` state and will start recording
+///     soon
+#[utoipa::path(
+    put,
+    path = "/recording/rules/{id}",
+    params(
+        ("id" = u32, Path, description = "Rule ID"),
+    ),
+    request_body = WebRecordingRuleInput,
+    responses(
+        (status = 200, description = "OK", body = WebRecordingRule),
+        (status = 401, description = "Bad Request"),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "updateRecordingRule",
+)]
+pub(in crate::web::api) async fn update<R>(
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+    Path(id): Path<RecordingRuleId>,
+    Json(input): Json<WebRecordingRuleInput>,
+) -> Result<Json<WebRecordingRule>, Error>
+where
+    R: Call<recording::UpdateRecordingRule>,
+{
+    input.validate()?;
+    let msg = recording::UpdateRecordingRule {
+        id,
+        conditions: input.conditions,
+        options: input.options,
+        tags: input.tags,
+    };
+    let rule = recording_manager.call(msg).await??;
+    Ok(Json(rule.into()))
+}
+
+/// Deletes a recording rule.
+///
+/// Recording schedules added by the rule will be also deleted except for the recording schedules
+/// described in `updateRecordingRule`.
+#[utoipa::path(
+    delete,
+    path = "/recording/rules/{id}",
+    params(
+        ("id" = u32, Path, description = "Rule ID"),
+    ),
+    responses(
+        (status = 200, description = "OK"),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "deleteRecordingRule",
+)]
+pub(in crate::web::api) async fn delete<R>(
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+    Path(id): Path<RecordingRuleId>,
+) -> Result<(), Error>
+where
+    R: Call<recording::RemoveRecordingRule>,
+{
+    recording_manager
+        .call(recording::RemoveRecordingRule { id })
+        .await??;
+    Ok(())
+}
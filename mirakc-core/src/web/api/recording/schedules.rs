@@ -104,6 +104,56 @@ where
     Ok((StatusCode::CREATED, Json(schedule.into())))
 }
 
+/// Books a time-range recording schedule.
+///
+/// A time-range recording schedule records a service in a specified time range regardless of TV
+/// programs in EPG.  It can be used when EIT is missing or wrong.
+///
+/// The service-filter is used instead of the program-filter.  The recording starts and ends at the
+/// specified times in consideration of `options.startMargin` and `options.endMargin`.
+///
+/// A synthetic TV program is created for the recording schedule.  Its program ID can be used in
+/// other endpoints such as `deleteRecordingSchedule` like other recording schedules.  The
+/// `timeRange` property of the recording schedule is `true`.
+#[utoipa::path(
+    post,
+    path = "/recording/schedules/time-range",
+    request_body = WebTimeRangeRecordingScheduleInput,
+    responses(
+        (status = 201, description = "Created", body = WebRecordingSchedule),
+        (status = 401, description = "Bad Request"),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "createTimeRangeRecordingSchedule",
+)]
+pub(in crate::web::api) async fn create_time_range<E, R>(
+    State(ConfigExtractor(config)): State<ConfigExtractor>,
+    State(EpgExtractor(epg)): State<EpgExtractor<E>>,
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+    Json(input): Json<WebTimeRangeRecordingScheduleInput>,
+) -> Result<(StatusCode, Json<WebRecordingSchedule>), Error>
+where
+    E: Call<epg::QueryService>,
+    R: Call<recording::AddTimeRangeRecordingSchedule>,
+{
+    input.validate(&config)?;
+    let msg = epg::QueryService {
+        service_id: input.service_id,
+    };
+    let service = epg.call(msg).await??;
+    let msg = recording::AddTimeRangeRecordingSchedule {
+        service,
+        start_at: input.start_at,
+        end_at: input.end_at,
+        name: input.name,
+        options: input.options,
+        tags: input.tags,
+    };
+    let schedule = recording_manager.call(msg).await??;
+    Ok((StatusCode::CREATED, Json(schedule.into())))
+}
+
 /// Deletes a recording schedule.
 #[utoipa::path(
     delete,
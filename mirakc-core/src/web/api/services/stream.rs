@@ -124,6 +124,7 @@ where
             channel: channel.clone(),
             user: user.clone(),
             stream_id: None,
+            excluded_tuners: vec![],
         })
         .await??;
 
@@ -80,6 +80,35 @@ pub(in crate::web::api) fn compute_content_range(
     }
 }
 
+// Computes a range from a position for time-based seeking.
+//
+// The range is used only for reading the content from the position.  It must not be used for
+// building response headers because the response is not a partial content.
+pub(in crate::web::api) fn compute_seek_range(
+    ranges: &Option<TypedHeader<axum_extra::headers::Range>>,
+    pos: u64,
+    content_length: u64,
+    incomplete: bool,
+) -> Result<Option<ContentRange>, Error> {
+    if ranges.is_some() {
+        return Err(Error::InvalidRequest(
+            "Range requests cannot be used together with time-based seeking",
+        ));
+    }
+    if pos >= content_length {
+        return Err(Error::OutOfRange);
+    }
+    if pos == 0 {
+        return Ok(None);
+    }
+    let last = content_length - 1; // 0-based index
+    if incomplete {
+        Ok(Some(ContentRange::without_size(pos, last)?))
+    } else {
+        Ok(Some(ContentRange::with_size(pos, last, content_length)?))
+    }
+}
+
 pub(in crate::web::api) fn compute_content_length(
     size: u64,
     incomplete: bool,
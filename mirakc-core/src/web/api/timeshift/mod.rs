@@ -69,7 +69,84 @@ where
         .map(Json::from)
 }
 
+/// Stops a timeshift recorder.
+///
+/// The recording pipeline is terminated and the tuner used for the recording is released.  Records
+/// are still available while the recorder is stopped.  The stopped state is saved in the data-file
+/// and the recorder stays stopped after mirakc restarts.
+#[utoipa::path(
+    post,
+    path = "/timeshift/{recorder}/stop",
+    params(
+        ("recorder" = String, Path, description = "Timeshift recorder name"),
+    ),
+    responses(
+        (status = 200, description = "OK", body = WebTimeshiftRecorder),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "stopTimeshiftRecorder",
+)]
+pub(in crate::web::api) async fn stop<S>(
+    State(TimeshiftManagerExtractor(timeshift_manager)): State<TimeshiftManagerExtractor<S>>,
+    Path(recorder): Path<String>,
+) -> Result<Json<WebTimeshiftRecorder>, Error>
+where
+    S: Call<timeshift::StopTimeshiftRecorder>,
+{
+    let msg = timeshift::StopTimeshiftRecorder {
+        recorder: TimeshiftRecorderQuery::ByName(recorder),
+    };
+    timeshift_manager
+        .call(msg)
+        .await?
+        .map(WebTimeshiftRecorder::from)
+        .map(Json::from)
+}
+
+/// Starts a timeshift recorder stopped by
+/// [/timeshift/{recorder}/stop](#/timeshift/stopTimeshiftRecorder).
+///
+/// The recording resumes at the end of the timeshift timeline.  The recording may start
+/// asynchronously after the response.
+#[utoipa::path(
+    post,
+    path = "/timeshift/{recorder}/start",
+    params(
+        ("recorder" = String, Path, description = "Timeshift recorder name"),
+    ),
+    responses(
+        (status = 200, description = "OK", body = WebTimeshiftRecorder),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "startTimeshiftRecorder",
+)]
+pub(in crate::web::api) async fn start<S>(
+    State(TimeshiftManagerExtractor(timeshift_manager)): State<TimeshiftManagerExtractor<S>>,
+    Path(recorder): Path<String>,
+) -> Result<Json<WebTimeshiftRecorder>, Error>
+where
+    S: Call<timeshift::StartTimeshiftRecorder>,
+{
+    let msg = timeshift::StartTimeshiftRecorder {
+        recorder: TimeshiftRecorderQuery::ByName(recorder),
+    };
+    timeshift_manager
+        .call(msg)
+        .await?
+        .map(WebTimeshiftRecorder::from)
+        .map(Json::from)
+}
+
 /// Gets a live stream of a timeshift record.
+///
+/// The stream starts at the beginning of the specified record, or the oldest data in the timeshift
+/// file if no record is specified.  The `start-time` or `offset` query parameter can be used for
+/// starting the stream at a specific time.  `offset` is relative to the start time of the record,
+/// or the start time of the timeshift recorder if no record is specified.  When a record is
+/// specified, the time must be within the record.
+#[allow(clippy::too_many_arguments)]
 #[utoipa::path(
     get,
     path = "/timeshift/{recorder}/stream",
@@ -78,10 +155,13 @@ where
         ("pre-filters" = Option<[String]>, Query, description = "Pre-filters"),
         ("post-filters" = Option<[String]>, Query, description = "post-filters"),
         ("record" = Option<u32>, Query, description = "Timeshift record ID"),
+        SeekQuery,
     ),
     responses(
         (status = 200, description = "OK"),
+        (status = 400, description = "Bad Request"),
         (status = 404, description = "Not Found"),
+        (status = 416, description = "Range Not Satisfiable"),
         (status = 500, description = "Internal Server Error"),
         (status = 503, description = "Tuner Resource Unavailable"),
     ),
@@ -95,9 +175,11 @@ pub(in crate::web::api) async fn stream<S, W>(
     user: TunerUser,
     Qs(filter_setting): Qs<FilterSetting>,
     Query(TimeshiftRecordQuery { record: record_id }): Query<TimeshiftRecordQuery>,
+    Query(seek): Query<SeekQuery>,
 ) -> Result<Response, Error>
 where
     S: Call<timeshift::CreateTimeshiftLiveStreamSource>,
+    S: Call<timeshift::QueryTimeshiftRecord>,
     S: Call<timeshift::QueryTimeshiftRecorder>,
     W: Spawn,
 {
@@ -106,9 +188,22 @@ where
     };
     let recorder = timeshift_manager.call(msg).await??;
 
+    let base_time = match (record_id, seek.offset) {
+        (Some(record_id), Some(_)) => {
+            let msg = timeshift::QueryTimeshiftRecord {
+                recorder: TimeshiftRecorderQuery::ByName(recorder_id.clone()),
+                record_id,
+            };
+            Some(timeshift_manager.call(msg).await??.start_time)
+        }
+        _ => recorder.start_time,
+    };
+    let start_time = seek.resolve(base_time)?;
+
     let msg = timeshift::CreateTimeshiftLiveStreamSource {
         recorder: TimeshiftRecorderQuery::ByName(recorder_id.clone()),
         record_id,
+        start_time,
     };
     let src = timeshift_manager.call(msg).await??;
 
@@ -1,11 +1,15 @@
 use super::*;
 
 use crate::models::TunerUser;
+use crate::recording;
 use crate::timeshift::TimeshiftRecordModel;
 use crate::timeshift::TimeshiftRecorderModel;
+use crate::web::api::hls::HlsSegment;
+use crate::web::api::hls::build_playlist;
 use crate::web::api::stream::StreamingHeaderParams;
 use crate::web::api::stream::compute_content_length;
 use crate::web::api::stream::compute_content_range;
+use crate::web::api::stream::compute_seek_range;
 use crate::web::api::stream::streaming;
 
 /// Lists timeshift records.
@@ -76,7 +80,185 @@ where
         .map(Json::from)
 }
 
+/// Gets an HLS playlist of a timeshift record.
+///
+/// Each segment in the playlist is a byte range of the stream provided by
+/// `getTimeshiftRecordStream` and corresponds to a chunk of the timeshift recorder.  No
+/// re-encoding is performed.
+///
+/// The playlist has no `#EXT-X-ENDLIST` tag while the record is being recorded.
+#[utoipa::path(
+    get,
+    path = "/timeshift/{recorder}/records/{id}/playlist.m3u8",
+    params(
+        TimeshiftRecordPath,
+    ),
+    responses(
+        (status = 200, description = "OK", content_type = "application/vnd.apple.mpegurl", body = String),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "getTimeshiftRecordPlaylist",
+)]
+pub(in crate::web::api) async fn playlist<S>(
+    State(TimeshiftManagerExtractor(timeshift_manager)): State<TimeshiftManagerExtractor<S>>,
+    Path(path): Path<TimeshiftRecordPath>,
+) -> Result<Response<String>, Error>
+where
+    S: Call<timeshift::QueryTimeshiftRecord>,
+    S: Call<timeshift::QueryTimeshiftRecordSegments>,
+{
+    let msg = timeshift::QueryTimeshiftRecord {
+        recorder: TimeshiftRecorderQuery::ByName(path.recorder.clone()),
+        record_id: path.id,
+    };
+    let record = timeshift_manager.call(msg).await??;
+
+    let msg = timeshift::QueryTimeshiftRecordSegments {
+        recorder: TimeshiftRecorderQuery::ByName(path.recorder),
+        record_id: path.id,
+    };
+    let segments: Vec<HlsSegment> = timeshift_manager
+        .call(msg)
+        .await??
+        .into_iter()
+        .map(HlsSegment::from)
+        .collect();
+
+    // The URI is relative to the URL of the playlist.
+    build_playlist("stream", &segments, record.recording)
+}
+
+/// Pins a timeshift record.
+///
+/// A pinned record is never purged.  The timeshift recorder exports the pinned record into a record
+/// when it is going to be overwritten, and then unpins it.  The timeshift recorder stops recording
+/// if the pinned record cannot be exported, and restarts recording when the record is unpinned.
+#[utoipa::path(
+    put,
+    path = "/timeshift/{recorder}/records/{id}/pin",
+    params(
+        TimeshiftRecordPath,
+    ),
+    responses(
+        (status = 200, description = "OK", body = WebTimeshiftRecord),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "pinTimeshiftRecord",
+)]
+pub(in crate::web::api) async fn pin<S>(
+    State(TimeshiftManagerExtractor(timeshift_manager)): State<TimeshiftManagerExtractor<S>>,
+    Path(path): Path<TimeshiftRecordPath>,
+) -> Result<Json<WebTimeshiftRecord>, Error>
+where
+    S: Call<timeshift::PinTimeshiftRecord>,
+{
+    do_pin(timeshift_manager, path, true).await
+}
+
+/// Unpins a timeshift record.
+#[utoipa::path(
+    delete,
+    path = "/timeshift/{recorder}/records/{id}/pin",
+    params(
+        TimeshiftRecordPath,
+    ),
+    responses(
+        (status = 200, description = "OK", body = WebTimeshiftRecord),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "unpinTimeshiftRecord",
+)]
+pub(in crate::web::api) async fn unpin<S>(
+    State(TimeshiftManagerExtractor(timeshift_manager)): State<TimeshiftManagerExtractor<S>>,
+    Path(path): Path<TimeshiftRecordPath>,
+) -> Result<Json<WebTimeshiftRecord>, Error>
+where
+    S: Call<timeshift::PinTimeshiftRecord>,
+{
+    do_pin(timeshift_manager, path, false).await
+}
+
+async fn do_pin<S>(
+    timeshift_manager: S,
+    path: TimeshiftRecordPath,
+    pinned: bool,
+) -> Result<Json<WebTimeshiftRecord>, Error>
+where
+    S: Call<timeshift::PinTimeshiftRecord>,
+{
+    let msg = timeshift::PinTimeshiftRecord {
+        recorder: TimeshiftRecorderQuery::ByName(path.recorder),
+        record_id: path.id,
+        pinned,
+    };
+    timeshift_manager
+        .call(msg)
+        .await?
+        .map(WebTimeshiftRecord::from)
+        .map(Json::from)
+}
+
+/// Exports a timeshift record into a record.
+///
+/// The content of the timeshift record is copied into a new content file in
+/// `config.recording.basedir` and a record is created in `config.recording.records-dir`.  The
+/// record will be kept even after the timeshift record is purged.
+///
+/// The copy is performed in the background.  The status of the record is `recording` while
+/// copying.  A `recording.record-saved` event will be sent when the copy finishes.
+///
+/// A timeshift record currently recording cannot be exported.
+#[utoipa::path(
+    post,
+    path = "/timeshift/{recorder}/records/{id}/export",
+    params(
+        TimeshiftRecordPath,
+    ),
+    request_body = WebTimeshiftRecordExportInput,
+    responses(
+        (status = 201, description = "Created", body = WebRecord),
+        (status = 400, description = "Bad Request"),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "exportTimeshiftRecord",
+)]
+pub(in crate::web::api) async fn export<R, S>(
+    State(ConfigExtractor(config)): State<ConfigExtractor>,
+    State(RecordingManagerExtractor(recording_manager)): State<RecordingManagerExtractor<R>>,
+    State(TimeshiftManagerExtractor(timeshift_manager)): State<TimeshiftManagerExtractor<S>>,
+    Path(path): Path<TimeshiftRecordPath>,
+    Json(input): Json<WebTimeshiftRecordExportInput>,
+) -> Result<(StatusCode, Json<WebRecord>), Error>
+where
+    R: Call<recording::ImportTimeshiftRecord>,
+    S: Call<timeshift::ExportTimeshiftRecord>,
+{
+    input.validate(&config)?;
+    let msg = timeshift::ExportTimeshiftRecord {
+        recorder: TimeshiftRecorderQuery::ByName(path.recorder),
+        record_id: path.id,
+    };
+    let export = timeshift_manager.call(msg).await??;
+    let msg = recording::ImportTimeshiftRecord {
+        export,
+        options: input.recording_options(),
+        tags: input.tags,
+    };
+    let record = recording_manager.call(msg).await??;
+    Ok((StatusCode::CREATED, Json((record, None).into())))
+}
+
 /// Gets a media stream of a timeshift record.
+///
+/// The `start-time` or `offset` query parameter can be used for starting the stream at a specific
+/// time.  `offset` is relative to the start time of the record.  The time is mapped to a position
+/// in the record by using chunk points in the timeshift file.  The response for such a request is
+/// not seekable and a range request for it always causes an error response with status code 400.
+#[allow(clippy::too_many_arguments)]
 #[utoipa::path(
     get,
     path = "/timeshift/{recorder}/records/{id}/stream",
@@ -84,10 +266,14 @@ where
         TimeshiftRecordPath,
         ("pre-filters" = Option<[String]>, Query, description = "Pre-filters"),
         ("post-filters" = Option<[String]>, Query, description = "post-filters"),
+        SeekQuery,
     ),
     responses(
         (status = 200, description = "OK"),
+        (status = 206, description = "Partial Content"),
+        (status = 400, description = "Bad Request"),
         (status = 404, description = "Not Found"),
+        (status = 416, description = "Range Not Satisfiable"),
         (status = 500, description = "Internal Server Error"),
         (status = 503, description = "Tuner Resource Unavailable"),
     ),
@@ -101,10 +287,12 @@ pub(in crate::web::api) async fn stream<S, W>(
     ranges: Option<TypedHeader<axum_extra::headers::Range>>,
     user: TunerUser,
     Qs(filter_setting): Qs<FilterSetting>,
+    Query(seek): Query<SeekQuery>,
 ) -> Result<Response, Error>
 where
     S: Call<timeshift::CreateTimeshiftRecordStreamSource>,
     S: Call<timeshift::QueryTimeshiftRecord>,
+    S: Call<timeshift::QueryTimeshiftRecordOffset>,
     S: Call<timeshift::QueryTimeshiftRecorder>,
     W: Spawn,
 {
@@ -121,25 +309,47 @@ where
 
     let (filters, content_type, seekable) =
         build_filters(&config, &filter_setting, &recorder, &record)?;
-    let range = compute_content_range(&ranges, record.size, record.recording, seekable)?;
-    let length = compute_content_length(record.size, record.recording, range.as_ref());
+
+    let (src_range, params) = match seek.resolve(Some(record.start_time))? {
+        Some(time) => {
+            let msg = timeshift::QueryTimeshiftRecordOffset {
+                recorder: TimeshiftRecorderQuery::ByName(path.recorder.clone()),
+                record_id: path.id,
+                time,
+            };
+            let pos = timeshift_manager.call(msg).await??;
+            let range = compute_seek_range(&ranges, pos, record.size, record.recording)?;
+            let params = StreamingHeaderParams {
+                seekable: false,
+                content_type,
+                length: None,
+                range: None,
+                user,
+            };
+            (range, params)
+        }
+        None => {
+            let range = compute_content_range(&ranges, record.size, record.recording, seekable)?;
+            let length = compute_content_length(record.size, record.recording, range.as_ref());
+            let params = StreamingHeaderParams {
+                seekable,
+                content_type,
+                length,
+                range: range.clone(),
+                user,
+            };
+            (range, params)
+        }
+    };
 
     let msg = timeshift::CreateTimeshiftRecordStreamSource {
         recorder: TimeshiftRecorderQuery::ByName(path.recorder.clone()),
         record_id: path.id,
-        range: range.clone(),
+        range: src_range,
     };
     let src = timeshift_manager.call(msg).await??;
 
-    let (stream, stop_trigger) = src.create_stream(seekable).await?;
-
-    let params = StreamingHeaderParams {
-        seekable,
-        content_type,
-        length,
-        range,
-        user,
-    };
+    let (stream, stop_trigger) = src.create_stream(params.seekable).await?;
 
     streaming(&config, &spawner, stream, filters, &params, stop_trigger).await
 }
@@ -51,3 +51,119 @@ where
     let tuner = tuner_manager.call(tuner::QueryTuner(index)).await??;
     Ok(Json(tuner))
 }
+
+/// Gets statistics of the current session of a tuner.
+///
+/// The statistics are collected while the tuner is active.  The signal level is included only
+/// when `signal-level.command` is specified in the tuner config.
+#[utoipa::path(
+    get,
+    path = "/tuners/{index}/stats",
+    params(
+        ("index" = usize, Path, description = "Tuner index"),
+    ),
+    responses(
+        (status = 200, description = "OK", body = TunerSessionStats),
+        (status = 204, description = "The tuner is not active"),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+)]
+pub(super) async fn stats<T>(
+    State(TunerManagerExtractor(tuner_manager)): State<TunerManagerExtractor<T>>,
+    Path(index): Path<usize>,
+) -> Result<Json<TunerSessionStats>, Error>
+where
+    T: Call<tuner::QueryTunerStats>,
+{
+    let stats = tuner_manager.call(tuner::QueryTunerStats(index)).await??;
+    Ok(Json(stats))
+}
+
+/// Lists tuner reservations.
+///
+/// Expired reservations are not included.
+#[utoipa::path(
+    get,
+    path = "/tuners/reservations",
+    responses(
+        (status = 200, description = "OK", body = [TunerReservation]),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "getTunerReservations",
+)]
+pub(super) async fn list_reservations<T>(
+    State(TunerManagerExtractor(tuner_manager)): State<TunerManagerExtractor<T>>,
+) -> Result<Json<Vec<TunerReservation>>, Error>
+where
+    T: Call<tuner::QueryTunerReservations>,
+{
+    let reservations = tuner_manager.call(tuner::QueryTunerReservations).await?;
+    Ok(Json(reservations))
+}
+
+/// Creates a tuner reservation.
+///
+/// While the reservation is active, the reserved tuner is not allocated to other users having a
+/// priority lower than or equal to the priority of the reservation.  `userId` is compared with the
+/// ID of a tuner user shown in the tuner model.  For example, a streaming client can use the
+/// reserved tuner by specifying the same ID in the `X-Mirakurun-Tuner-User-Id` header.
+///
+/// Reservations are saved in `config.tuner-reservations.basedir` if it's specified.  Otherwise,
+/// they are kept in memory and lost when mirakc restarts.
+#[utoipa::path(
+    post,
+    path = "/tuners/reservations",
+    request_body = WebTunerReservationInput,
+    responses(
+        (status = 201, description = "Created", body = TunerReservation),
+        (status = 400, description = "Bad Request"),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "createTunerReservation",
+)]
+pub(super) async fn create_reservation<T>(
+    State(TunerManagerExtractor(tuner_manager)): State<TunerManagerExtractor<T>>,
+    Json(input): Json<WebTunerReservationInput>,
+) -> Result<(StatusCode, Json<TunerReservation>), Error>
+where
+    T: Call<tuner::AddTunerReservation>,
+{
+    let msg = tuner::AddTunerReservation {
+        tuner_index: input.tuner_index,
+        user_id: input.user_id,
+        priority: input.priority.into(),
+        start_at: input.start_at,
+        end_at: input.end_at,
+    };
+    let reservation = tuner_manager.call(msg).await??;
+    Ok((StatusCode::CREATED, Json(reservation)))
+}
+
+/// Deletes a tuner reservation.
+#[utoipa::path(
+    delete,
+    path = "/tuners/reservations/{id}",
+    params(
+        ("id" = u64, Path, description = "Reservation ID"),
+    ),
+    responses(
+        (status = 200, description = "OK"),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal Server Error"),
+    ),
+    operation_id = "deleteTunerReservation",
+)]
+pub(super) async fn delete_reservation<T>(
+    State(TunerManagerExtractor(tuner_manager)): State<TunerManagerExtractor<T>>,
+    Path(id): Path<u64>,
+) -> Result<(), Error>
+where
+    T: Call<tuner::RemoveTunerReservation>,
+{
+    tuner_manager
+        .call(tuner::RemoveTunerReservation { id })
+        .await??;
+    Ok(())
+}
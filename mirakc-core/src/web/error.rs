@@ -49,6 +49,10 @@ impl IntoResponse for Error {
             Error::RecordNotFound => error_response!(StatusCode::NOT_FOUND),
             Error::ScheduleNotFound => error_response!(StatusCode::NOT_FOUND),
             Error::RecorderNotFound => error_response!(StatusCode::NOT_FOUND),
+            Error::RuleNotFound => error_response!(StatusCode::NOT_FOUND),
+            Error::RecurringScheduleNotFound => error_response!(StatusCode::NOT_FOUND),
+            Error::PostProcessJobNotFound => error_response!(StatusCode::NOT_FOUND),
+            Error::ReservationNotFound => error_response!(StatusCode::NOT_FOUND),
             Error::OutOfRange => error_response!(StatusCode::RANGE_NOT_SATISFIABLE),
             Error::NoContent => error_response!(StatusCode::NO_CONTENT),
             Error::NoLogoData => {
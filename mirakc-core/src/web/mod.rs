@@ -61,7 +61,11 @@ pub async fn serve<T, E, R, S, O, W>(
 where
     T: Clone + Send + Sync + 'static,
     T: Call<crate::tuner::QueryTuner>,
+    T: Call<crate::tuner::AddTunerReservation>,
+    T: Call<crate::tuner::QueryTunerReservations>,
+    T: Call<crate::tuner::QueryTunerStats>,
     T: Call<crate::tuner::QueryTuners>,
+    T: Call<crate::tuner::RemoveTunerReservation>,
     T: Call<crate::tuner::RegisterEmitter>,
     T: Call<crate::tuner::StartStreaming>,
     T: TriggerFactory<crate::tuner::StopStreaming>,
@@ -77,28 +81,49 @@ where
     E: Call<crate::epg::RegisterEmitter>,
     E: TriggerFactory<crate::epg::UnregisterEmitter>,
     R: Clone + Send + Sync + 'static,
+    R: Call<crate::recording::AddRecordingRule>,
     R: Call<crate::recording::AddRecordingSchedule>,
+    R: Call<crate::recording::AddRecurringSchedule>,
+    R: Call<crate::recording::AddTimeRangeRecordingSchedule>,
+    R: Call<crate::recording::ImportTimeshiftRecord>,
     R: Call<crate::recording::OpenContent>,
     R: Call<crate::recording::QueryRecord>,
+    R: Call<crate::recording::QueryRecordOffset>,
+    R: Call<crate::recording::QueryRecordContentIndex>,
+    R: Call<crate::recording::QueryRecordingConflicts>,
     R: Call<crate::recording::QueryRecordingRecorder>,
     R: Call<crate::recording::QueryRecordingRecorders>,
+    R: Call<crate::recording::QueryRecordingRule>,
+    R: Call<crate::recording::QueryRecordingRules>,
     R: Call<crate::recording::QueryRecordingSchedule>,
     R: Call<crate::recording::QueryRecordingSchedules>,
     R: Call<crate::recording::QueryRecords>,
+    R: Call<crate::recording::QueryRecurringSchedule>,
+    R: Call<crate::recording::QueryRecurringSchedules>,
     R: Call<crate::recording::RegisterEmitter>,
     R: Call<crate::recording::RemoveRecord>,
+    R: Call<crate::recording::RemoveRecordingRule>,
     R: Call<crate::recording::RemoveRecordingSchedule>,
     R: Call<crate::recording::RemoveRecordingSchedules>,
+    R: Call<crate::recording::RemoveRecurringSchedule>,
+    R: Call<crate::recording::RunPostProcessJob>,
     R: Call<crate::recording::StartRecording>,
     R: Call<crate::recording::StopRecording>,
+    R: Call<crate::recording::UpdateRecordingRule>,
     R: TriggerFactory<crate::recording::UnregisterEmitter>,
     S: Clone + Send + Sync + 'static,
     S: Call<crate::timeshift::CreateTimeshiftLiveStreamSource>,
     S: Call<crate::timeshift::CreateTimeshiftRecordStreamSource>,
+    S: Call<crate::timeshift::ExportTimeshiftRecord>,
+    S: Call<crate::timeshift::PinTimeshiftRecord>,
     S: Call<crate::timeshift::QueryTimeshiftRecord>,
+    S: Call<crate::timeshift::QueryTimeshiftRecordOffset>,
+    S: Call<crate::timeshift::QueryTimeshiftRecordSegments>,
     S: Call<crate::timeshift::QueryTimeshiftRecords>,
     S: Call<crate::timeshift::QueryTimeshiftRecorder>,
     S: Call<crate::timeshift::QueryTimeshiftRecorders>,
+    S: Call<crate::timeshift::StartTimeshiftRecorder>,
+    S: Call<crate::timeshift::StopTimeshiftRecorder>,
     S: Call<crate::timeshift::RegisterEmitter>,
     S: TriggerFactory<crate::timeshift::UnregisterEmitter>,
     O: Clone + Send + Sync + 'static,
@@ -136,7 +161,11 @@ fn build_app<T, E, R, S, O, W>(config: Arc<Config>) -> Router<Arc<AppState<T, E,
 where
     T: Clone + Send + Sync + 'static,
     T: Call<crate::tuner::QueryTuner>,
+    T: Call<crate::tuner::AddTunerReservation>,
+    T: Call<crate::tuner::QueryTunerReservations>,
+    T: Call<crate::tuner::QueryTunerStats>,
     T: Call<crate::tuner::QueryTuners>,
+    T: Call<crate::tuner::RemoveTunerReservation>,
     T: Call<crate::tuner::RegisterEmitter>,
     T: Call<crate::tuner::StartStreaming>,
     T: TriggerFactory<crate::tuner::StopStreaming>,
@@ -152,28 +181,49 @@ where
     E: Call<crate::epg::RegisterEmitter>,
     E: TriggerFactory<crate::epg::UnregisterEmitter>,
     R: Clone + Send + Sync + 'static,
+    R: Call<crate::recording::AddRecordingRule>,
     R: Call<crate::recording::AddRecordingSchedule>,
+    R: Call<crate::recording::AddRecurringSchedule>,
+    R: Call<crate::recording::AddTimeRangeRecordingSchedule>,
+    R: Call<crate::recording::ImportTimeshiftRecord>,
     R: Call<crate::recording::OpenContent>,
     R: Call<crate::recording::QueryRecord>,
+    R: Call<crate::recording::QueryRecordOffset>,
+    R: Call<crate::recording::QueryRecordContentIndex>,
+    R: Call<crate::recording::QueryRecordingConflicts>,
     R: Call<crate::recording::QueryRecordingRecorder>,
     R: Call<crate::recording::QueryRecordingRecorders>,
+    R: Call<crate::recording::QueryRecordingRule>,
+    R: Call<crate::recording::QueryRecordingRules>,
     R: Call<crate::recording::QueryRecordingSchedule>,
     R: Call<crate::recording::QueryRecordingSchedules>,
     R: Call<crate::recording::QueryRecords>,
+    R: Call<crate::recording::QueryRecurringSchedule>,
+    R: Call<crate::recording::QueryRecurringSchedules>,
     R: Call<crate::recording::RegisterEmitter>,
     R: Call<crate::recording::RemoveRecord>,
+    R: Call<crate::recording::RemoveRecordingRule>,
     R: Call<crate::recording::RemoveRecordingSchedule>,
     R: Call<crate::recording::RemoveRecordingSchedules>,
+    R: Call<crate::recording::RemoveRecurringSchedule>,
+    R: Call<crate::recording::RunPostProcessJob>,
     R: Call<crate::recording::StartRecording>,
     R: Call<crate::recording::StopRecording>,
+    R: Call<crate::recording::UpdateRecordingRule>,
     R: TriggerFactory<crate::recording::UnregisterEmitter>,
     S: Clone + Send + Sync + 'static,
     S: Call<crate::timeshift::CreateTimeshiftLiveStreamSource>,
     S: Call<crate::timeshift::CreateTimeshiftRecordStreamSource>,
+    S: Call<crate::timeshift::ExportTimeshiftRecord>,
+    S: Call<crate::timeshift::PinTimeshiftRecord>,
     S: Call<crate::timeshift::QueryTimeshiftRecord>,
+    S: Call<crate::timeshift::QueryTimeshiftRecordOffset>,
+    S: Call<crate::timeshift::QueryTimeshiftRecordSegments>,
     S: Call<crate::timeshift::QueryTimeshiftRecords>,
     S: Call<crate::timeshift::QueryTimeshiftRecorder>,
     S: Call<crate::timeshift::QueryTimeshiftRecorders>,
+    S: Call<crate::timeshift::StartTimeshiftRecorder>,
+    S: Call<crate::timeshift::StopTimeshiftRecorder>,
     S: Call<crate::timeshift::RegisterEmitter>,
     S: TriggerFactory<crate::timeshift::UnregisterEmitter>,
     O: Clone + Send + Sync + 'static,
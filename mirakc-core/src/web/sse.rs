@@ -9,7 +9,7 @@ use futures::stream::Stream;
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 
-use crate::events::*;
+use crate::events::EventMessage;
 
 pub(super) async fn events<T, E, R, S, O>(
     State(ConfigExtractor(config)): State<ConfigExtractor>,
@@ -145,7 +145,7 @@ macro_rules! impl_emit {
         #[async_trait]
         impl Emit<$msg> for EventFeeder {
             async fn emit(&self, msg: $msg) {
-                if let Err(_) = self.0.send(Ok(msg.into())).await {
+                if let Err(_) = self.0.send(Ok(EventMessage::from(msg).into())).await {
                     tracing::warn!("Client disconnected");
                 }
             }
@@ -159,222 +159,22 @@ macro_rules! impl_emit {
     };
 }
 
-// tuner events
-
 impl_emit! {crate::tuner::Event}
-
-impl From<crate::tuner::Event> for Event {
-    fn from(val: crate::tuner::Event) -> Self {
-        match val {
-            crate::tuner::Event::StatusChanged(tuner_index) => Self::default()
-                .event("tuner.status-changed")
-                .json_data(TunerStatusChanged { tuner_index })
-                .unwrap(),
-        }
-    }
-}
-
-// epg events
-
 impl_emit! {crate::epg::ProgramsUpdated}
-
-impl From<crate::epg::ProgramsUpdated> for Event {
-    fn from(val: crate::epg::ProgramsUpdated) -> Self {
-        Self::default()
-            .event("epg.programs-updated")
-            .json_data(EpgProgramsUpdated {
-                service_id: val.service_id,
-            })
-            .unwrap()
-    }
-}
-
-// recording events
-
 impl_emit! {crate::recording::RecordingStarted}
-
-impl From<crate::recording::RecordingStarted> for Event {
-    fn from(val: crate::recording::RecordingStarted) -> Self {
-        Self::default()
-            .event("recording.started")
-            .json_data(RecordingStarted {
-                program_id: val.program_id,
-            })
-            .unwrap()
-    }
-}
-
 impl_emit! {crate::recording::RecordingStopped}
-
-impl From<crate::recording::RecordingStopped> for Event {
-    fn from(val: crate::recording::RecordingStopped) -> Self {
-        Self::default()
-            .event("recording.stopped")
-            .json_data(RecordingStopped {
-                program_id: val.program_id,
-            })
-            .unwrap()
-    }
-}
-
 impl_emit! {crate::recording::RecordingFailed}
-
-impl From<crate::recording::RecordingFailed> for Event {
-    fn from(val: crate::recording::RecordingFailed) -> Self {
-        Self::default()
-            .event("recording.failed")
-            .json_data(RecordingFailed {
-                program_id: val.program_id,
-                reason: val.reason,
-            })
-            .unwrap()
-    }
-}
-
 impl_emit! {crate::recording::RecordingRescheduled}
-
-impl From<crate::recording::RecordingRescheduled> for Event {
-    fn from(val: crate::recording::RecordingRescheduled) -> Self {
-        Self::default()
-            .event("recording.rescheduled")
-            .json_data(RecordingRescheduled {
-                program_id: val.program_id,
-            })
-            .unwrap()
-    }
-}
-
-// record events
-
 impl_emit! {crate::recording::RecordSaved}
-
-impl From<crate::recording::RecordSaved> for Event {
-    fn from(val: crate::recording::RecordSaved) -> Self {
-        Self::default()
-            .event("recording.record-saved")
-            .json_data(RecordSaved {
-                record_id: val.record_id,
-                recording_status: val.recording_status.into(),
-            })
-            .unwrap()
-    }
-}
-
 impl_emit! {crate::recording::RecordBroken}
-
-impl From<crate::recording::RecordBroken> for Event {
-    fn from(val: crate::recording::RecordBroken) -> Self {
-        Self::default()
-            .event("recording.record-broken")
-            .json_data(RecordBroken {
-                record_id: val.record_id,
-                reason: val.reason,
-            })
-            .unwrap()
-    }
-}
-
 impl_emit! {crate::recording::RecordRemoved}
-
-impl From<crate::recording::RecordRemoved> for Event {
-    fn from(val: crate::recording::RecordRemoved) -> Self {
-        Self::default()
-            .event("recording.record-removed")
-            .json_data(RecordRemoved {
-                record_id: val.record_id,
-            })
-            .unwrap()
-    }
-}
-
 impl_emit! {crate::recording::ContentRemoved}
-
-impl From<crate::recording::ContentRemoved> for Event {
-    fn from(val: crate::recording::ContentRemoved) -> Self {
-        Self::default()
-            .event("recording.content-removed")
-            .json_data(ContentRemoved {
-                record_id: val.record_id,
-            })
-            .unwrap()
-    }
-}
-
-// timeshift events
-
 impl_emit! {crate::timeshift::TimeshiftEvent}
-
-impl From<crate::timeshift::TimeshiftEvent> for Event {
-    fn from(val: crate::timeshift::TimeshiftEvent) -> Self {
-        match val {
-            crate::timeshift::TimeshiftEvent::Timeline {
-                recorder,
-                start_time,
-                end_time,
-                duration,
-            } => Self::default()
-                .event("timeshift.timeline")
-                .json_data(TimeshiftTimeline {
-                    recorder,
-                    start_time,
-                    end_time,
-                    duration,
-                })
-                .unwrap(),
-            crate::timeshift::TimeshiftEvent::Started { recorder } => Self::default()
-                .event("timeshift.started")
-                .json_data(TimeshiftStarted { recorder })
-                .unwrap(),
-            crate::timeshift::TimeshiftEvent::Stopped { recorder } => Self::default()
-                .event("timeshift.stopped")
-                .json_data(TimeshiftStopped { recorder })
-                .unwrap(),
-            crate::timeshift::TimeshiftEvent::RecordStarted {
-                recorder,
-                record_id,
-            } => Self::default()
-                .event("timeshift.record-started")
-                .json_data(TimeshiftRecordStarted {
-                    recorder,
-                    record_id,
-                })
-                .unwrap(),
-            crate::timeshift::TimeshiftEvent::RecordUpdated {
-                recorder,
-                record_id,
-            } => Self::default()
-                .event("timeshift.record-updated")
-                .json_data(TimeshiftRecordUpdated {
-                    recorder,
-                    record_id,
-                })
-                .unwrap(),
-            crate::timeshift::TimeshiftEvent::RecordEnded {
-                recorder,
-                record_id,
-            } => Self::default()
-                .event("timeshift.record-ended")
-                .json_data(TimeshiftRecordEnded {
-                    recorder,
-                    record_id,
-                })
-                .unwrap(),
-        }
-    }
-}
-
-// on-air events
-
 impl_emit! {crate::onair::OnairProgramChanged}
 
-impl From<crate::onair::OnairProgramChanged> for Event {
-    fn from(val: crate::onair::OnairProgramChanged) -> Self {
-        Event::default()
-            .event("onair.program-changed")
-            .json_data(OnairProgramChanged {
-                service_id: val.service_id,
-            })
-            .unwrap()
+impl From<EventMessage> for Event {
+    fn from(val: EventMessage) -> Self {
+        Self::default().event(val.name).json_data(val.data).unwrap()
     }
 }
 
@@ -14,6 +14,8 @@ use axum::http::header::CONTENT_TYPE;
 use axum::http::header::HOST;
 use axum::http::header::LOCATION;
 use axum::response::Response;
+use chrono::Duration;
+use chrono_jst::Jst;
 use http_body_util::BodyExt; // for `collect`
 use mime::APPLICATION_JSON;
 use serde_json::json;
@@ -23,7 +25,10 @@ use tower::ServiceExt;
 use crate::epg::stub::EpgStub;
 use crate::models::TunerUserPriority;
 use crate::onair::stub::OnairProgramManagerStub;
+use crate::recording::PostProcessJob;
+use crate::recording::PostProcessJobState;
 use crate::recording::RecordingOptions;
+use crate::recording::RecordingRuleConditions;
 use crate::recording::stub::RecordingManagerStub;
 use crate::timeshift::stub::TimeshiftManagerStub;
 use crate::tuner::stub::TunerManagerStub;
@@ -148,6 +153,71 @@ async fn test_get_tuner() {
     assert_eq!(res.status(), StatusCode::NOT_FOUND);
 }
 
+#[test(tokio::test)]
+async fn test_get_tuner_stats() {
+    let res = get("/api/tuners/2/stats").await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = get("/api/tuners/1/stats").await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    let res = get("/api/tuners/0/stats").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test(tokio::test)]
+async fn test_get_tuner_reservations() {
+    let res = get("/api/tuners/reservations").await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[test(tokio::test)]
+async fn test_create_tuner_reservation() {
+    let now = Jst::now();
+
+    // Ok
+    let input = WebTunerReservationInput {
+        tuner_index: 2,
+        user_id: "test".to_string(),
+        priority: 0,
+        start_at: now,
+        end_at: now + Duration::try_hours(1).unwrap(),
+    };
+    let res = post("/api/tuners/reservations", input).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    // Error::AlreadyExists
+    let input = WebTunerReservationInput {
+        tuner_index: 1,
+        user_id: "test".to_string(),
+        priority: 0,
+        start_at: now,
+        end_at: now + Duration::try_hours(1).unwrap(),
+    };
+    let res = post("/api/tuners/reservations", input).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // Error::TunerNotFound
+    let input = WebTunerReservationInput {
+        tuner_index: 0,
+        user_id: "test".to_string(),
+        priority: 0,
+        start_at: now,
+        end_at: now + Duration::try_hours(1).unwrap(),
+    };
+    let res = post("/api/tuners/reservations", input).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test(tokio::test)]
+async fn test_delete_tuner_reservation() {
+    let res = delete("/api/tuners/reservations/1").await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = delete("/api/tuners/reservations/0").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
 #[test(tokio::test)]
 async fn test_get_channel_stream() {
     let res = get("/api/channels/GR/ch/stream").await;
@@ -424,6 +494,59 @@ async fn test_create_recording_schedule() {
     assert_eq!(res.status(), StatusCode::CREATED);
 }
 
+#[test(tokio::test)]
+async fn test_create_time_range_recording_schedule() {
+    let now = Jst::now();
+
+    // Error::ServiceNotFound
+    let input = WebTimeRangeRecordingScheduleInput {
+        service_id: (0, 0).into(),
+        start_at: now + Duration::try_hours(1).unwrap(),
+        end_at: now + Duration::try_hours(2).unwrap(),
+        name: None,
+        options: recording_options!(1),
+        tags: Default::default(),
+    };
+    let res = post("/api/recording/schedules/time-range", input).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // Error::InvalidRequest
+    let input = WebTimeRangeRecordingScheduleInput {
+        service_id: (0, 1).into(),
+        start_at: now + Duration::try_hours(2).unwrap(),
+        end_at: now + Duration::try_hours(1).unwrap(),
+        name: None,
+        options: recording_options!(1),
+        tags: Default::default(),
+    };
+    let res = post("/api/recording/schedules/time-range", input).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // Error::ProgramEnded
+    let input = WebTimeRangeRecordingScheduleInput {
+        service_id: (0, 1).into(),
+        start_at: now - Duration::try_hours(2).unwrap(),
+        end_at: now - Duration::try_hours(1).unwrap(),
+        name: None,
+        options: recording_options!(1),
+        tags: Default::default(),
+    };
+    let res = post("/api/recording/schedules/time-range", input).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // Ok
+    let input = WebTimeRangeRecordingScheduleInput {
+        service_id: (0, 1).into(),
+        start_at: now + Duration::try_hours(1).unwrap(),
+        end_at: now + Duration::try_hours(2).unwrap(),
+        name: Some("test".to_string()),
+        options: recording_options!(1),
+        tags: Default::default(),
+    };
+    let res = post("/api/recording/schedules/time-range", input).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+}
+
 #[test(tokio::test)]
 async fn test_get_recording_schedule() {
     let res = get("/api/recording/schedules/1").await;
@@ -451,6 +574,176 @@ async fn test_delete_recording_schedules() {
     assert_eq!(res.status(), StatusCode::OK);
 }
 
+#[test(tokio::test)]
+async fn test_get_recording_conflicts() {
+    let res = get("/api/recording/conflicts").await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[test(tokio::test)]
+async fn test_get_recording_rules() {
+    let res = get("/api/recording/rules").await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[test(tokio::test)]
+async fn test_get_recording_rule() {
+    let res = get("/api/recording/rules/1").await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = get("/api/recording/rules/0").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test(tokio::test)]
+async fn test_create_recording_rule() {
+    // Ok
+    let input = WebRecordingRuleInput {
+        conditions: RecordingRuleConditions {
+            title: Some("test".to_string()),
+            ..Default::default()
+        },
+        options: recording_options!(1),
+        tags: Default::default(),
+    };
+    let res = post("/api/recording/rules", input).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    // Error::InvalidRequest
+    let input = WebRecordingRuleInput {
+        conditions: Default::default(),
+        options: recording_options!(1),
+        tags: Default::default(),
+    };
+    let res = post("/api/recording/rules", input).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // Error::InvalidRequest
+    let input = WebRecordingRuleInput {
+        conditions: RecordingRuleConditions {
+            title: Some("test".to_string()),
+            ..Default::default()
+        },
+        options: recording_options!("1.m2ts", 1),
+        tags: Default::default(),
+    };
+    let res = post("/api/recording/rules", input).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test(tokio::test)]
+async fn test_update_recording_rule() {
+    let input = WebRecordingRuleInput {
+        conditions: RecordingRuleConditions {
+            title: Some("test".to_string()),
+            ..Default::default()
+        },
+        options: recording_options!(1),
+        tags: Default::default(),
+    };
+    let res = put("/api/recording/rules/1", &input).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = put("/api/recording/rules/0", &input).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test(tokio::test)]
+async fn test_delete_recording_rule() {
+    let res = delete("/api/recording/rules/1").await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = delete("/api/recording/rules/0").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test(tokio::test)]
+async fn test_get_recurring_schedules() {
+    let res = get("/api/recording/recurring-schedules").await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[test(tokio::test)]
+async fn test_get_recurring_schedule() {
+    let res = get("/api/recording/recurring-schedules/1").await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = get("/api/recording/recurring-schedules/0").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test(tokio::test)]
+async fn test_create_recurring_schedule() {
+    // Ok
+    let input = WebRecurringScheduleInput {
+        service_id: (0, 1).into(),
+        cron: "0 0 21 * * Mon-Fri".to_string(),
+        duration: None,
+        name: None,
+        options: recording_options!(1),
+        tags: Default::default(),
+    };
+    let res = post("/api/recording/recurring-schedules", input).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    // Ok
+    let input = WebRecurringScheduleInput {
+        service_id: (0, 1).into(),
+        cron: "0 0 21 * * Mon-Fri".to_string(),
+        duration: Some(Duration::try_minutes(30).unwrap()),
+        name: Some("test".to_string()),
+        options: recording_options!(1),
+        tags: Default::default(),
+    };
+    let res = post("/api/recording/recurring-schedules", input).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    // Error::ServiceNotFound
+    let input = WebRecurringScheduleInput {
+        service_id: (0, 0).into(),
+        cron: "0 0 21 * * Mon-Fri".to_string(),
+        duration: None,
+        name: None,
+        options: recording_options!(1),
+        tags: Default::default(),
+    };
+    let res = post("/api/recording/recurring-schedules", input).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // Error::InvalidRequest
+    let input = WebRecurringScheduleInput {
+        service_id: (0, 1).into(),
+        cron: "invalid".to_string(),
+        duration: None,
+        name: None,
+        options: recording_options!(1),
+        tags: Default::default(),
+    };
+    let res = post("/api/recording/recurring-schedules", input).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // Error::InvalidRequest
+    let input = WebRecurringScheduleInput {
+        service_id: (0, 1).into(),
+        cron: "0 0 21 * * Mon-Fri".to_string(),
+        duration: None,
+        name: None,
+        options: recording_options!("1.m2ts", 1),
+        tags: Default::default(),
+    };
+    let res = post("/api/recording/recurring-schedules", input).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test(tokio::test)]
+async fn test_delete_recurring_schedule() {
+    let res = delete("/api/recording/recurring-schedules/1").await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = delete("/api/recording/recurring-schedules/0").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
 #[test(tokio::test)]
 async fn test_get_recording_recorders() {
     let res = get("/api/recording/recorders").await;
@@ -571,6 +864,41 @@ async fn test_delete_recording_record() {
     assert!(!result.content_removed);
 }
 
+#[test(tokio::test)]
+async fn test_get_recording_record_post_processes() {
+    let res = get("/api/recording/records/not-found/post-processes").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = get("/api/recording/records/finished/post-processes").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let json = into_text(res).await;
+    let jobs: Vec<PostProcessJob> = serde_json::from_str(&json).unwrap();
+    assert!(jobs.is_empty());
+}
+
+#[test(tokio::test)]
+async fn test_run_recording_record_post_process() {
+    let res = post("/api/recording/records/not-found/post-processes/job", ()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = post("/api/recording/records/finished/post-processes/unknown", ()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = post("/api/recording/records/finished/post-processes/running", ()).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = post("/api/recording/records/recording/post-processes/job", ()).await;
+    // Jobs cannot run while recording.
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = post("/api/recording/records/finished/post-processes/job", ()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let json = into_text(res).await;
+    let job: PostProcessJob = serde_json::from_str(&json).unwrap();
+    assert_eq!(job.name, "job");
+    assert_matches!(job.state, PostProcessJobState::Pending);
+}
+
 #[test(tokio::test)]
 async fn test_get_recording_record_stream() {
     // non-existent record
@@ -1011,6 +1339,76 @@ async fn test_head_recording_record_stream() {
     assert_eq!(res.status(), StatusCode::BAD_REQUEST);
 }
 
+#[test(tokio::test)]
+async fn test_get_recording_record_stream_seek() {
+    // offset
+    let res = get("/api/recording/records/finished/stream?offset=1000").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_matches!(res.headers().get(ACCEPT_RANGES), Some(v) => {
+        assert_eq!(v, "none");
+    });
+    assert_matches!(res.headers().get(CONTENT_LENGTH), None);
+    assert_matches!(res.headers().get(CONTENT_RANGE), None);
+    let content = into_text(res).await;
+    assert_eq!(content, "56789");
+
+    // start-time
+    let res = get("/api/recording/records/recording/stream?start-time=0").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let content = into_text(res).await;
+    assert_eq!(content, "56789");
+
+    // head
+    let res = head("/api/recording/records/finished/stream?offset=1000").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_matches!(res.headers().get(ACCEPT_RANGES), Some(v) => {
+        assert_eq!(v, "none");
+    });
+    assert_matches!(res.headers().get(CONTENT_LENGTH), None);
+
+    // w/ range
+    let res = get_with_test_config(
+        "/api/recording/records/finished/stream?offset=1000",
+        maplit::hashmap! {
+            "request_headers" => to_json!([("range", "bytes=1-3")]),
+        },
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // both start-time and offset
+    let res = get("/api/recording/records/finished/stream?start-time=0&offset=0").await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // negative offset
+    let res = get("/api/recording/records/finished/stream?offset=-1").await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = get("/api/recording/records/no-content/stream?offset=0").await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    let res = get("/api/recording/records/not-found/stream?offset=0").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test(tokio::test)]
+async fn test_get_recording_record_playlist() {
+    let res = get("/api/recording/records/finished/playlist.m3u8").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_matches!(res.headers().get(CONTENT_TYPE), Some(v) => {
+        assert_eq!(v, "application/vnd.apple.mpegurl");
+    });
+
+    let res = get("/api/recording/records/recording/playlist.m3u8").await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = get("/api/recording/records/no-content/playlist.m3u8").await;
+    assert_eq!(res.status(), StatusCode::NO_CONTENT);
+
+    let res = get("/api/recording/records/unknown/playlist.m3u8").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
 #[test(tokio::test)]
 async fn test_get_timeshift_recorders() {
     let res = get("/api/timeshift").await;
@@ -1026,6 +1424,28 @@ async fn test_get_timeshift_recorder() {
     assert_eq!(res.status(), StatusCode::NOT_FOUND);
 }
 
+#[test(tokio::test)]
+async fn test_stop_timeshift_recorder() {
+    let res = post("/api/timeshift/test/stop", ()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let recorder: serde_json::Value = serde_json::from_str(&into_text(res).await).unwrap();
+    assert_eq!(recorder["paused"], true);
+
+    let res = post("/api/timeshift/not_found/stop", ()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test(tokio::test)]
+async fn test_start_timeshift_recorder() {
+    let res = post("/api/timeshift/test/start", ()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let recorder: serde_json::Value = serde_json::from_str(&into_text(res).await).unwrap();
+    assert_eq!(recorder["paused"], false);
+
+    let res = post("/api/timeshift/not_found/start", ()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
 #[test(tokio::test)]
 async fn test_get_timeshift_records() {
     let res = get("/api/timeshift/test/records").await;
@@ -1041,6 +1461,64 @@ async fn test_get_timeshift_record() {
     assert_eq!(res.status(), StatusCode::NOT_FOUND);
 }
 
+#[test(tokio::test)]
+async fn test_pin_timeshift_record() {
+    let res = put("/api/timeshift/test/records/1/pin", ()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = put("/api/timeshift/test/records/2/pin", ()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = put("/api/timeshift/not_found/records/1/pin", ()).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = delete("/api/timeshift/test/records/1/pin").await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = delete("/api/timeshift/test/records/2/pin").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
+#[test(tokio::test)]
+async fn test_export_timeshift_record() {
+    let input = WebTimeshiftRecordExportInput {
+        content_path: None,
+        post_filters: vec![],
+        tags: Default::default(),
+    };
+    let res = post("/api/timeshift/test/records/1/export", &input).await;
+    assert_eq!(res.status(), StatusCode::CREATED);
+
+    // Error::InvalidRequest
+    let res = post("/api/timeshift/test/records/0/export", &input).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    // Error::RecordNotFound
+    let res = post("/api/timeshift/test/records/2/export", &input).await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // Error::InvalidPath
+    let input = WebTimeshiftRecordExportInput {
+        content_path: Some("/1.m2ts".into()),
+        post_filters: vec![],
+        tags: Default::default(),
+    };
+    let res = post("/api/timeshift/test/records/1/export", &input).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[test(tokio::test)]
+async fn test_get_timeshift_record_playlist() {
+    let res = get("/api/timeshift/test/records/1/playlist.m3u8").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_matches!(res.headers().get(CONTENT_TYPE), Some(v) => {
+        assert_eq!(v, "application/vnd.apple.mpegurl");
+    });
+
+    let res = get("/api/timeshift/test/records/2/playlist.m3u8").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
 #[test(tokio::test)]
 async fn test_get_timeshift_stream() {
     let res = get("/api/timeshift/test/stream").await;
@@ -1074,6 +1552,27 @@ async fn test_get_timeshift_stream() {
     assert_eq!(res.status(), StatusCode::NOT_FOUND);
 }
 
+#[test(tokio::test)]
+async fn test_get_timeshift_stream_seek() {
+    let res = get("/api/timeshift/test/stream?offset=1000").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_matches!(res.headers().get(ACCEPT_RANGES), Some(v) => {
+        assert_eq!(v, "none");
+    });
+
+    let res = get("/api/timeshift/test/stream?record=1&offset=1000").await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = get("/api/timeshift/test/stream?record=1&start-time=0").await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = get("/api/timeshift/test/stream?record=2&offset=1000").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    let res = get("/api/timeshift/test/stream?start-time=0&offset=0").await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
 #[test(tokio::test)]
 async fn test_get_timeshift_record_stream() {
     // recording
@@ -1287,6 +1786,35 @@ async fn test_get_timeshift_record_stream() {
     assert_eq!(res.status(), StatusCode::BAD_REQUEST);
 }
 
+#[test(tokio::test)]
+async fn test_get_timeshift_record_stream_seek() {
+    let res = get("/api/timeshift/test/records/1/stream?offset=1000").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_matches!(res.headers().get(ACCEPT_RANGES), Some(v) => {
+        assert_eq!(v, "none");
+    });
+    assert_matches!(res.headers().get(CONTENT_LENGTH), None);
+    assert_matches!(res.headers().get(CONTENT_RANGE), None);
+
+    let res = get("/api/timeshift/test/records/0/stream?start-time=0").await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = get_with_test_config(
+        "/api/timeshift/test/records/1/stream?offset=1000",
+        maplit::hashmap! {
+            "request_headers" => to_json!([("range", "bytes=1-3")]),
+        },
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = get("/api/timeshift/test/records/1/stream?start-time=0&offset=0").await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+
+    let res = get("/api/timeshift/test/records/2/stream?offset=0").await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+}
+
 #[test(tokio::test)]
 async fn test_get_iptv_playlist() {
     test_get_iptv_playlist_("/api/iptv/playlist").await;
@@ -1933,6 +2461,20 @@ where
     app.oneshot(req).await.unwrap()
 }
 
+async fn put<T>(endpoint: &str, data: T) -> Response
+where
+    T: serde::Serialize,
+{
+    let app = create_app(&Default::default());
+    // The axum_extract::Host requires an HTTP Host request header for tests to work properly.
+    let req = Request::put(endpoint)
+        .header(HOST, "mirakc:40772")
+        .header(CONTENT_TYPE, APPLICATION_JSON.as_ref())
+        .body(Body::from(serde_json::to_vec(&json!(data)).unwrap()))
+        .unwrap();
+    app.oneshot(req).await.unwrap()
+}
+
 async fn delete(endpoint: &str) -> Response {
     let app = create_app(&Default::default());
     // The axum_extract::Host requires an HTTP Host request header for tests to work properly.
@@ -0,0 +1,1049 @@
+use std::collections::VecDeque;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use actlet::prelude::*;
+use chrono::DateTime;
+use chrono::Duration;
+use chrono_jst::Jst;
+use chrono_jst::serde::ts_milliseconds;
+use hmac::Hmac;
+use hmac::Mac;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Sha256;
+use tokio_util::sync::CancellationToken;
+use url::Url;
+
+use crate::config::Config;
+use crate::epg;
+use crate::events::EventMessage;
+use crate::file_util;
+use crate::onair;
+use crate::recording;
+use crate::timeshift;
+use crate::tuner;
+
+pub const EVENT_HEADER: &str = "X-Mirakc-Event";
+pub const DELIVERY_HEADER: &str = "X-Mirakc-Delivery";
+pub const SIGNATURE_HEADER: &str = "X-Mirakc-Signature";
+
+// Changes of the outbox are saved together after this delay in order to reduce writes to the disk.
+const SAVE_OUTBOX_DELAY: std::time::Duration = std::time::Duration::from_secs(1);
+
+pub struct WebhookDispatcher<T, E, R, S, O> {
+    config: Arc<Config>,
+    tuner_manager: T,
+    epg: E,
+    recording_manager: R,
+    timeshift_manager: S,
+    onair_manager: O,
+    client: reqwest::Client,
+    outbox: VecDeque<Delivery>,
+    last_delivery_id: u64,
+    // The ID of the delivery in flight.
+    delivering: Option<u64>,
+    timer_token: Option<CancellationToken>,
+    // `true` while the outbox has changes not saved yet.
+    outbox_dirty: bool,
+}
+
+impl<T, E, R, S, O> WebhookDispatcher<T, E, R, S, O> {
+    pub fn new(
+        config: Arc<Config>,
+        tuner_manager: T,
+        epg: E,
+        recording_manager: R,
+        timeshift_manager: S,
+        onair_manager: O,
+    ) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(config.webhook.timeout)
+            .build()
+            .unwrap();
+        WebhookDispatcher {
+            config,
+            tuner_manager,
+            epg,
+            recording_manager,
+            timeshift_manager,
+            onair_manager,
+            client,
+            outbox: Default::default(),
+            last_delivery_id: 0,
+            delivering: None,
+            timer_token: None,
+            outbox_dirty: false,
+        }
+    }
+
+    fn load_outbox(&mut self) {
+        fn do_load(path: &Path) -> Result<Vec<Delivery>, crate::error::Error> {
+            let file = std::fs::File::open(path)?;
+            Ok(serde_json::from_reader(file)?)
+        }
+
+        let path = match make_outbox_path(&self.config) {
+            Some(path) if path.exists() => path,
+            _ => return,
+        };
+
+        match do_load(&path) {
+            Ok(deliveries) => {
+                tracing::info!(?path, "Loaded");
+                for delivery in deliveries.into_iter() {
+                    if self.find_endpoint(&delivery.url).is_none() {
+                        // The endpoint has been removed from config.yml.
+                        tracing::warn!(delivery.id, %delivery.url, "No such endpoint, discard");
+                        continue;
+                    }
+                    self.last_delivery_id = self.last_delivery_id.max(delivery.id);
+                    self.outbox.push_back(delivery);
+                }
+            }
+            Err(err) => {
+                tracing::warn!(%err, ?path, "Failed to load");
+            }
+        }
+    }
+
+    // Saves the outbox after `SAVE_OUTBOX_DELAY`.  Changes made before that are saved together.
+    fn request_save_outbox<C>(&mut self, ctx: &C)
+    where
+        C: Spawn + EmitterFactory<SaveOutbox>,
+    {
+        if self.outbox_dirty {
+            return;
+        }
+        self.outbox_dirty = true;
+        let emitter = ctx.emitter();
+        ctx.spawn_task(async move {
+            tokio::time::sleep(SAVE_OUTBOX_DELAY).await;
+            emitter.emit(SaveOutbox).await;
+        });
+    }
+
+    fn save_outbox(&mut self) {
+        self.outbox_dirty = false;
+        let path = match make_outbox_path(&self.config) {
+            Some(path) => path,
+            None => return,
+        };
+
+        if file_util::save_json(&self.outbox, &path) {
+            tracing::debug!(outbox.len = self.outbox.len(), "Saved outbox");
+        } else {
+            tracing::error!("Failed to save outbox");
+        }
+    }
+
+    fn find_endpoint(&self, url: &Url) -> Option<&crate::config::WebhookEndpointConfig> {
+        self.config
+            .webhook
+            .endpoints
+            .iter()
+            .find(|endpoint| endpoint.url == *url)
+    }
+
+    fn next_delivery_id(&mut self, now: DateTime<Jst>) -> u64 {
+        // Use the timestamp so that IDs are unique even after the outbox becomes empty and mirakc
+        // restarts.
+        let id = (now.timestamp_micros() as u64).max(self.last_delivery_id + 1);
+        self.last_delivery_id = id;
+        id
+    }
+
+    fn enqueue<C>(&mut self, event: EventMessage, ctx: &C)
+    where
+        C: Spawn
+            + EmitterFactory<Dispatch>
+            + EmitterFactory<DeliveryFinished>
+            + EmitterFactory<SaveOutbox>,
+    {
+        let now = Jst::now();
+        let urls: Vec<Url> = self
+            .config
+            .webhook
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.subscribes(&event.name))
+            .map(|endpoint| endpoint.url.clone())
+            .collect();
+        if urls.is_empty() {
+            return;
+        }
+        for url in urls.into_iter() {
+            let id = self.next_delivery_id(now);
+            tracing::debug!(id, %url, event = event.name, "Enqueue");
+            self.outbox.push_back(Delivery {
+                id,
+                url,
+                event: event.clone(),
+                created_at: now,
+                attempts: 0,
+                next_attempt_at: now,
+            });
+        }
+        self.request_save_outbox(ctx);
+        self.dispatch(ctx);
+    }
+
+    fn dispatch<C>(&mut self, ctx: &C)
+    where
+        C: Spawn + EmitterFactory<Dispatch> + EmitterFactory<DeliveryFinished>,
+    {
+        if self.delivering.is_some() {
+            // Deliveries are processed one by one in order to keep the order of events.
+            return;
+        }
+
+        if let Some(token) = self.timer_token.take() {
+            token.cancel();
+        }
+
+        let now = Jst::now();
+        let delivery = self
+            .outbox
+            .iter()
+            .find(|delivery| delivery.next_attempt_at <= now);
+        if let Some(delivery) = delivery {
+            let secret = self
+                .find_endpoint(&delivery.url)
+                .and_then(|endpoint| endpoint.secret.clone());
+            let request = make_request(&self.client, delivery, secret.as_deref());
+            let id = delivery.id;
+            tracing::debug!(id, %delivery.url, event = delivery.event.name, "Deliver");
+            self.delivering = Some(id);
+            let emitter = ctx.emitter();
+            ctx.spawn_task(async move {
+                let result = match request.send().await {
+                    Ok(res) if res.status().is_success() => Ok(()),
+                    Ok(res) => Err(format!("Status code: {}", res.status())),
+                    Err(err) => Err(err.to_string()),
+                };
+                emitter.emit(DeliveryFinished { id, result }).await;
+            });
+        } else if let Some(expires_at) = self
+            .outbox
+            .iter()
+            .map(|delivery| delivery.next_attempt_at)
+            .min()
+        {
+            let duration = (expires_at - now).to_std().unwrap_or_default();
+            tracing::debug!(%expires_at, "Set timer");
+            let emitter = ctx.emitter();
+            let (_, token) = ctx.spawn_task(async move {
+                tokio::time::sleep(duration).await;
+                emitter.emit(Dispatch).await;
+            });
+            self.timer_token = Some(token);
+        }
+    }
+
+    // Computes the interval before the next attempt using the exponential backoff.
+    fn compute_retry_interval(&self, attempts: u32) -> Duration {
+        let config = &self.config.webhook;
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        let interval = config
+            .retry_interval
+            .saturating_mul(factor)
+            .min(config.max_retry_interval);
+        Duration::from_std(interval).unwrap()
+    }
+}
+
+// actor
+
+#[async_trait]
+impl<T, E, R, S, O> Actor for WebhookDispatcher<T, E, R, S, O>
+where
+    T: Send + Sync + 'static,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<epg::RegisterEmitter>,
+    R: Send + Sync + 'static,
+    R: Call<recording::RegisterEmitter>,
+    S: Send + Sync + 'static,
+    S: Call<timeshift::RegisterEmitter>,
+    O: Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn started(&mut self, ctx: &mut Context<Self>) {
+        tracing::debug!("Started");
+
+        if !self.config.webhook.is_enabled() {
+            tracing::info!("No webhook endpoint is defined");
+            return;
+        }
+
+        self.load_outbox();
+
+        macro_rules! register_emitter {
+            ($target:expr, $register:expr, $name:literal) => {
+                if let Err(err) = $target.call($register).await {
+                    tracing::error!(?err, concat!("Failed to register emitter for ", $name));
+                }
+            };
+        }
+
+        macro_rules! subscribes {
+            ($($event:literal),+) => {
+                [$($event),+].iter().any(|event| self.config.webhook.subscribes(event))
+            };
+        }
+
+        if subscribes!(
+            "tuner.status-changed",
+            "tuner.reservations-changed",
+            "tuner.preempted"
+        ) {
+            register_emitter!(
+                self.tuner_manager,
+                tuner::RegisterEmitter(ctx.emitter()),
+                "tuner::Event"
+            );
+        }
+
+        if subscribes!("epg.programs-updated") {
+            register_emitter!(
+                self.epg,
+                epg::RegisterEmitter::ProgramsUpdated(ctx.emitter()),
+                "epg::ProgramsUpdated"
+            );
+        }
+
+        if self.config.recording.is_enabled() {
+            if subscribes!("recording.started") {
+                register_emitter!(
+                    self.recording_manager,
+                    recording::RegisterEmitter::RecordingStarted(ctx.emitter()),
+                    "recording::RecordingStarted"
+                );
+            }
+            if subscribes!("recording.stopped") {
+                register_emitter!(
+                    self.recording_manager,
+                    recording::RegisterEmitter::RecordingStopped(ctx.emitter()),
+                    "recording::RecordingStopped"
+                );
+            }
+            if subscribes!("recording.failed") {
+                register_emitter!(
+                    self.recording_manager,
+                    recording::RegisterEmitter::RecordingFailed(ctx.emitter()),
+                    "recording::RecordingFailed"
+                );
+            }
+            if subscribes!("recording.rescheduled") {
+                register_emitter!(
+                    self.recording_manager,
+                    recording::RegisterEmitter::RecordingRescheduled(ctx.emitter()),
+                    "recording::RecordingRescheduled"
+                );
+            }
+        }
+
+        if self.config.recording.is_records_api_enabled() {
+            if subscribes!("recording.record-saved") {
+                register_emitter!(
+                    self.recording_manager,
+                    recording::RegisterEmitter::RecordSaved(ctx.emitter()),
+                    "recording::RecordSaved"
+                );
+            }
+            if subscribes!("recording.record-broken") {
+                register_emitter!(
+                    self.recording_manager,
+                    recording::RegisterEmitter::RecordBroken(ctx.emitter()),
+                    "recording::RecordBroken"
+                );
+            }
+            if subscribes!("recording.record-removed") {
+                register_emitter!(
+                    self.recording_manager,
+                    recording::RegisterEmitter::RecordRemoved(ctx.emitter()),
+                    "recording::RecordRemoved"
+                );
+            }
+            if subscribes!("recording.content-removed") {
+                register_emitter!(
+                    self.recording_manager,
+                    recording::RegisterEmitter::ContentRemoved(ctx.emitter()),
+                    "recording::ContentRemoved"
+                );
+            }
+        }
+
+        if self.config.timeshift.is_enabled()
+            && subscribes!(
+                "timeshift.timeline",
+                "timeshift.started",
+                "timeshift.stopped",
+                "timeshift.record-started",
+                "timeshift.record-updated",
+                "timeshift.record-ended",
+                "timeshift.gap"
+            )
+        {
+            register_emitter!(
+                self.timeshift_manager,
+                timeshift::RegisterEmitter(ctx.emitter()),
+                "timeshift::TimeshiftEvent"
+            );
+        }
+
+        if self.config.has_onair_program_trackers() && subscribes!("onair.program-changed") {
+            register_emitter!(
+                self.onair_manager,
+                onair::RegisterEmitter(ctx.emitter()),
+                "onair::OnairProgramChanged"
+            );
+        }
+
+        // Resume deliveries which have not been completed before the last shutdown.
+        self.dispatch(ctx);
+    }
+
+    async fn stopping(&mut self, _ctx: &mut Context<Self>) {
+        tracing::debug!("Stopping...");
+        if self.outbox_dirty {
+            self.save_outbox();
+        }
+    }
+
+    async fn stopped(&mut self, _ctx: &mut Context<Self>) {
+        tracing::debug!("Stopped");
+    }
+}
+
+// events
+
+macro_rules! impl_event_handler {
+    ($msg:path) => {
+        #[async_trait]
+        impl<T, E, R, S, O> Handler<$msg> for WebhookDispatcher<T, E, R, S, O>
+        where
+            T: Send + Sync + 'static,
+            T: Call<tuner::RegisterEmitter>,
+            E: Send + Sync + 'static,
+            E: Call<epg::RegisterEmitter>,
+            R: Send + Sync + 'static,
+            R: Call<recording::RegisterEmitter>,
+            S: Send + Sync + 'static,
+            S: Call<timeshift::RegisterEmitter>,
+            O: Send + Sync + 'static,
+            O: Call<onair::RegisterEmitter>,
+        {
+            async fn handle(&mut self, msg: $msg, ctx: &mut Context<Self>) {
+                tracing::debug!(msg.name = stringify!($msg));
+                self.enqueue(msg.into(), ctx);
+            }
+        }
+    };
+}
+
+impl_event_handler! {tuner::Event}
+impl_event_handler! {epg::ProgramsUpdated}
+impl_event_handler! {recording::RecordingStarted}
+impl_event_handler! {recording::RecordingStopped}
+impl_event_handler! {recording::RecordingFailed}
+impl_event_handler! {recording::RecordingRescheduled}
+impl_event_handler! {recording::RecordSaved}
+impl_event_handler! {recording::RecordBroken}
+impl_event_handler! {recording::RecordRemoved}
+impl_event_handler! {recording::ContentRemoved}
+impl_event_handler! {timeshift::TimeshiftEvent}
+impl_event_handler! {onair::OnairProgramChanged}
+
+// dispatch
+
+#[derive(Message)]
+struct Dispatch;
+
+#[async_trait]
+impl<T, E, R, S, O> Handler<Dispatch> for WebhookDispatcher<T, E, R, S, O>
+where
+    T: Send + Sync + 'static,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<epg::RegisterEmitter>,
+    R: Send + Sync + 'static,
+    R: Call<recording::RegisterEmitter>,
+    S: Send + Sync + 'static,
+    S: Call<timeshift::RegisterEmitter>,
+    O: Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, _msg: Dispatch, ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "Dispatch");
+        self.dispatch(ctx);
+    }
+}
+
+// delivery finished
+
+#[derive(Message)]
+struct DeliveryFinished {
+    id: u64,
+    result: Result<(), String>,
+}
+
+#[async_trait]
+impl<T, E, R, S, O> Handler<DeliveryFinished> for WebhookDispatcher<T, E, R, S, O>
+where
+    T: Send + Sync + 'static,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<epg::RegisterEmitter>,
+    R: Send + Sync + 'static,
+    R: Call<recording::RegisterEmitter>,
+    S: Send + Sync + 'static,
+    S: Call<timeshift::RegisterEmitter>,
+    O: Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, msg: DeliveryFinished, ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "DeliveryFinished", msg.id, ?msg.result);
+        debug_assert_eq!(self.delivering, Some(msg.id));
+        self.delivering = None;
+
+        let index = self
+            .outbox
+            .iter()
+            .position(|delivery| delivery.id == msg.id)
+            .unwrap();
+        match msg.result {
+            Ok(_) => {
+                let delivery = self.outbox.remove(index).unwrap();
+                tracing::info!(delivery.id, %delivery.url, event = delivery.event.name, "Delivered");
+            }
+            Err(err) => {
+                let attempts = self.outbox[index].attempts + 1;
+                if attempts >= self.config.webhook.max_attempts {
+                    let delivery = self.outbox.remove(index).unwrap();
+                    tracing::error!(
+                        err,
+                        delivery.id,
+                        %delivery.url,
+                        event = delivery.event.name,
+                        attempts,
+                        "Gave up delivering",
+                    );
+                } else {
+                    let interval = self.compute_retry_interval(attempts);
+                    let delivery = &mut self.outbox[index];
+                    delivery.attempts = attempts;
+                    delivery.next_attempt_at = Jst::now() + interval;
+                    tracing::warn!(
+                        err,
+                        delivery.id,
+                        %delivery.url,
+                        event = delivery.event.name,
+                        attempts,
+                        %delivery.next_attempt_at,
+                        "Failed delivering, retry later",
+                    );
+                }
+            }
+        }
+        self.request_save_outbox(ctx);
+        self.dispatch(ctx);
+    }
+}
+
+// save outbox
+
+#[derive(Message)]
+struct SaveOutbox;
+
+#[async_trait]
+impl<T, E, R, S, O> Handler<SaveOutbox> for WebhookDispatcher<T, E, R, S, O>
+where
+    T: Send + Sync + 'static,
+    T: Call<tuner::RegisterEmitter>,
+    E: Send + Sync + 'static,
+    E: Call<epg::RegisterEmitter>,
+    R: Send + Sync + 'static,
+    R: Call<recording::RegisterEmitter>,
+    S: Send + Sync + 'static,
+    S: Call<timeshift::RegisterEmitter>,
+    O: Send + Sync + 'static,
+    O: Call<onair::RegisterEmitter>,
+{
+    async fn handle(&mut self, _msg: SaveOutbox, _ctx: &mut Context<Self>) {
+        tracing::debug!(msg.name = "SaveOutbox");
+        if self.outbox_dirty {
+            self.save_outbox();
+        }
+    }
+}
+
+// models
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Delivery {
+    id: u64,
+    url: Url,
+    event: EventMessage,
+    #[serde(with = "ts_milliseconds")]
+    created_at: DateTime<Jst>,
+    attempts: u32,
+    #[serde(with = "ts_milliseconds")]
+    next_attempt_at: DateTime<Jst>,
+}
+
+/// The body of a webhook request.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookPayload {
+    /// The delivery ID.
+    ///
+    /// The same ID is used when the delivery is retried.
+    pub id: u64,
+
+    /// The name of the event.
+    pub event: String,
+
+    /// The time when the event occurred in UNIX time (milliseconds).
+    #[serde(with = "ts_milliseconds")]
+    pub timestamp: DateTime<Jst>,
+
+    /// The event data.  This is the same as the data of the SSE event.
+    pub data: serde_json::Value,
+}
+
+// helpers
+
+fn make_outbox_path(config: &Config) -> Option<PathBuf> {
+    config
+        .webhook
+        .basedir
+        .as_ref()
+        .map(|basedir| basedir.join("outbox.v1.json"))
+}
+
+fn make_request(
+    client: &reqwest::Client,
+    delivery: &Delivery,
+    secret: Option<&str>,
+) -> reqwest::RequestBuilder {
+    let body = serde_json::to_vec(&WebhookPayload {
+        id: delivery.id,
+        event: delivery.event.name.clone(),
+        timestamp: delivery.created_at,
+        data: delivery.event.data.clone(),
+    })
+    .unwrap();
+    let mut request = client
+        .post(delivery.url.clone())
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &delivery.event.name)
+        .header(DELIVERY_HEADER, delivery.id.to_string());
+    if let Some(secret) = secret {
+        request = request.header(SIGNATURE_HEADER, compute_signature(secret, &body));
+    }
+    request.body(body)
+}
+
+// Returns `sha256=<HMAC-SHA256 of the body in hex>`.
+pub fn compute_signature(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::WebhookEndpointConfig;
+    use crate::epg::stub::EpgStub;
+    use crate::onair::stub::OnairProgramManagerStub;
+    use crate::recording::stub::RecordingManagerStub;
+    use crate::timeshift::stub::TimeshiftManagerStub;
+    use crate::tuner::stub::TunerManagerStub;
+    use axum::http::HeaderMap;
+    use axum::http::StatusCode;
+    use bytes::Bytes;
+    use std::sync::Mutex;
+    use tempfile::TempDir;
+    use test_log::test;
+
+    macro_rules! webhook_dispatcher {
+        ($config:expr) => {
+            WebhookDispatcher::new(
+                $config,
+                TunerManagerStub::default(),
+                EpgStub,
+                RecordingManagerStub,
+                TimeshiftManagerStub,
+                OnairProgramManagerStub,
+            )
+        };
+    }
+
+    #[test]
+    fn test_compute_signature() {
+        // Test case 2 in RFC 4231.
+        assert_eq!(
+            compute_signature("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_compute_retry_interval() {
+        let mut config = Config::default();
+        config.webhook.retry_interval = std::time::Duration::from_secs(10);
+        config.webhook.max_retry_interval = std::time::Duration::from_secs(60);
+        let dispatcher = webhook_dispatcher!(Arc::new(config));
+        assert_eq!(
+            dispatcher.compute_retry_interval(1),
+            Duration::try_seconds(10).unwrap()
+        );
+        assert_eq!(
+            dispatcher.compute_retry_interval(2),
+            Duration::try_seconds(20).unwrap()
+        );
+        assert_eq!(
+            dispatcher.compute_retry_interval(3),
+            Duration::try_seconds(40).unwrap()
+        );
+        assert_eq!(
+            dispatcher.compute_retry_interval(4),
+            Duration::try_seconds(60).unwrap()
+        );
+        assert_eq!(
+            dispatcher.compute_retry_interval(100),
+            Duration::try_seconds(60).unwrap()
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_deliver() {
+        let stand_in = StandIn::spawn(vec![]).await;
+        let config = config_for_test(stand_in.url.clone(), None, |_| ());
+
+        let system = System::new();
+        {
+            let dispatcher = system.spawn_actor(webhook_dispatcher!(config)).await;
+            dispatcher
+                .emit(tuner::Event::StatusChanged(1, Default::default()))
+                .await;
+            stand_in.wait(1).await;
+        }
+        system.shutdown().await;
+
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 1);
+        let (headers, body) = &requests[0];
+        assert_eq!(headers[EVENT_HEADER], "tuner.status-changed");
+        assert!(headers.get(SIGNATURE_HEADER).is_none());
+        let payload: WebhookPayload = serde_json::from_slice(body).unwrap();
+        assert_eq!(headers[DELIVERY_HEADER], payload.id.to_string());
+        assert_eq!(payload.event, "tuner.status-changed");
+        assert_eq!(
+            payload.data,
+            serde_json::json!({
+                "tunerIndex": 1,
+                "health": {
+                    "consecutiveFailures": 0,
+                    "noDataTimeouts": 0,
+                    "quarantinedUntil": null,
+                },
+            })
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_deliver_with_signature() {
+        let stand_in = StandIn::spawn(vec![]).await;
+        let config = config_for_test(stand_in.url.clone(), Some("secret"), |_| ());
+
+        let system = System::new();
+        {
+            let dispatcher = system.spawn_actor(webhook_dispatcher!(config)).await;
+            dispatcher
+                .emit(tuner::Event::StatusChanged(1, Default::default()))
+                .await;
+            stand_in.wait(1).await;
+        }
+        system.shutdown().await;
+
+        let requests = stand_in.requests();
+        let (headers, body) = &requests[0];
+        assert_eq!(
+            headers[SIGNATURE_HEADER],
+            compute_signature("secret", body).as_str()
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_deliver_event_filter() {
+        let stand_in = StandIn::spawn(vec![]).await;
+        let config = config_for_test(stand_in.url.clone(), None, |config| {
+            config.webhook.endpoints[0].events = vec!["onair.*".to_string()];
+        });
+
+        let system = System::new();
+        {
+            let dispatcher = system.spawn_actor(webhook_dispatcher!(config)).await;
+            dispatcher
+                .emit(tuner::Event::StatusChanged(1, Default::default()))
+                .await;
+            dispatcher
+                .emit(onair::OnairProgramChanged {
+                    service_id: (0, 1).into(),
+                    current: None,
+                    next: None,
+                })
+                .await;
+            stand_in.wait(1).await;
+        }
+        system.shutdown().await;
+
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].0[EVENT_HEADER], "onair.program-changed");
+    }
+
+    #[test(tokio::test)]
+    async fn test_deliver_retry() {
+        let stand_in = StandIn::spawn(vec![StatusCode::INTERNAL_SERVER_ERROR]).await;
+        let config = config_for_test(stand_in.url.clone(), None, |_| ());
+
+        let system = System::new();
+        {
+            let dispatcher = system.spawn_actor(webhook_dispatcher!(config)).await;
+            dispatcher
+                .emit(tuner::Event::StatusChanged(1, Default::default()))
+                .await;
+            stand_in.wait(2).await;
+            let (sender, receiver) = tokio::sync::oneshot::channel();
+            dispatcher
+                .inspect(move |dispatcher| {
+                    sender.send(dispatcher.outbox.len()).unwrap();
+                })
+                .await
+                .unwrap();
+            assert_eq!(receiver.await.unwrap(), 0);
+        }
+        system.shutdown().await;
+
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 2);
+        // The same delivery ID is used.
+        assert_eq!(
+            requests[0].0[DELIVERY_HEADER],
+            requests[1].0[DELIVERY_HEADER]
+        );
+    }
+
+    #[test(tokio::test)]
+    async fn test_deliver_give_up() {
+        let stand_in = StandIn::spawn(vec![StatusCode::INTERNAL_SERVER_ERROR; 3]).await;
+        let config = config_for_test(stand_in.url.clone(), None, |config| {
+            config.webhook.max_attempts = 2;
+        });
+
+        let system = System::new();
+        {
+            let dispatcher = system.spawn_actor(webhook_dispatcher!(config)).await;
+            dispatcher
+                .emit(tuner::Event::StatusChanged(1, Default::default()))
+                .await;
+            stand_in.wait(2).await;
+            // Wait for the second attempt to be processed.
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            let (sender, receiver) = tokio::sync::oneshot::channel();
+            dispatcher
+                .inspect(move |dispatcher| {
+                    sender.send(dispatcher.outbox.len()).unwrap();
+                })
+                .await
+                .unwrap();
+            assert_eq!(receiver.await.unwrap(), 0);
+        }
+        system.shutdown().await;
+
+        assert_eq!(stand_in.requests().len(), 2);
+    }
+
+    #[test(tokio::test)]
+    async fn test_outbox() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let stand_in = StandIn::spawn(vec![StatusCode::INTERNAL_SERVER_ERROR]).await;
+        let config = config_for_test(stand_in.url.clone(), None, |config| {
+            config.webhook.basedir = Some(temp_dir.path().to_owned());
+            // Retry after restart.
+            config.webhook.retry_interval = std::time::Duration::from_secs(3600);
+            config.webhook.max_retry_interval = std::time::Duration::from_secs(3600);
+        });
+        let outbox_path = make_outbox_path(&config).unwrap();
+
+        let system = System::new();
+        {
+            let dispatcher = system
+                .spawn_actor(webhook_dispatcher!(config.clone()))
+                .await;
+            dispatcher
+                .emit(tuner::Event::StatusChanged(1, Default::default()))
+                .await;
+            stand_in.wait(1).await;
+        }
+        system.shutdown().await;
+
+        let file = std::fs::File::open(&outbox_path).unwrap();
+        let mut deliveries: Vec<Delivery> = serde_json::from_reader(file).unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].attempts, 1);
+
+        // Make the delivery ready for the next attempt.
+        deliveries[0].next_attempt_at = Jst::now();
+        assert!(file_util::save_json(&deliveries, &outbox_path));
+
+        let system = System::new();
+        {
+            let _dispatcher = system
+                .spawn_actor(webhook_dispatcher!(config.clone()))
+                .await;
+            stand_in.wait(2).await;
+            // Wait for the outbox to be saved.
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+        system.shutdown().await;
+
+        let requests = stand_in.requests();
+        assert_eq!(requests[1].0[DELIVERY_HEADER], deliveries[0].id.to_string());
+
+        let file = std::fs::File::open(&outbox_path).unwrap();
+        let deliveries: Vec<Delivery> = serde_json::from_reader(file).unwrap();
+        assert!(deliveries.is_empty());
+    }
+
+    #[test(tokio::test)]
+    async fn test_outbox_batched_writes() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let stand_in = StandIn::spawn(vec![StatusCode::INTERNAL_SERVER_ERROR; 10]).await;
+        let config = config_for_test(stand_in.url.clone(), None, |config| {
+            config.webhook.basedir = Some(temp_dir.path().to_owned());
+            config.webhook.retry_interval = std::time::Duration::from_secs(3600);
+            config.webhook.max_retry_interval = std::time::Duration::from_secs(3600);
+        });
+        let outbox_path = make_outbox_path(&config).unwrap();
+
+        let system = System::new();
+        {
+            let dispatcher = system
+                .spawn_actor(webhook_dispatcher!(config.clone()))
+                .await;
+            for i in 0..10 {
+                dispatcher
+                    .emit(tuner::Event::StatusChanged(i, Default::default()))
+                    .await;
+            }
+            stand_in.wait(1).await;
+            // Not saved yet.
+            assert!(!outbox_path.exists());
+            tokio::time::sleep(SAVE_OUTBOX_DELAY * 2).await;
+            let file = std::fs::File::open(&outbox_path).unwrap();
+            let deliveries: Vec<Delivery> = serde_json::from_reader(file).unwrap();
+            assert_eq!(deliveries.len(), 10);
+            std::fs::remove_file(&outbox_path).unwrap();
+
+            dispatcher
+                .emit(tuner::Event::StatusChanged(10, Default::default()))
+                .await;
+        }
+        // Changes not saved yet are saved when the dispatcher stops.
+        system.shutdown().await;
+
+        let file = std::fs::File::open(&outbox_path).unwrap();
+        let deliveries: Vec<Delivery> = serde_json::from_reader(file).unwrap();
+        assert_eq!(deliveries.len(), 11);
+    }
+
+    fn config_for_test<F>(url: Url, secret: Option<&str>, modify: F) -> Arc<Config>
+    where
+        F: FnOnce(&mut Config),
+    {
+        let mut config = Config::default();
+        config.webhook.timeout = std::time::Duration::from_secs(1);
+        config.webhook.retry_interval = std::time::Duration::from_millis(10);
+        config.webhook.max_retry_interval = std::time::Duration::from_millis(10);
+        config.webhook.endpoints = vec![WebhookEndpointConfig {
+            url,
+            events: vec![],
+            secret: secret.map(str::to_string),
+        }];
+        // Enable on-air events.
+        config.onair_program_trackers.insert(
+            "test".to_string(),
+            crate::config::OnairProgramTrackerConfig::Remote(Default::default()),
+        );
+        modify(&mut config);
+        Arc::new(config)
+    }
+
+    // A local HTTP server which records requests.
+    struct StandIn {
+        url: Url,
+        state: Arc<Mutex<StandInState>>,
+    }
+
+    #[derive(Default)]
+    struct StandInState {
+        // Status codes returned for the requests in order.  200 is returned after all status codes
+        // have been used.
+        statuses: VecDeque<StatusCode>,
+        requests: Vec<(HeaderMap, Bytes)>,
+    }
+
+    impl StandIn {
+        async fn spawn(statuses: Vec<StatusCode>) -> Self {
+            async fn handle(
+                axum::extract::State(state): axum::extract::State<Arc<Mutex<StandInState>>>,
+                headers: HeaderMap,
+                body: Bytes,
+            ) -> StatusCode {
+                let mut state = state.lock().unwrap();
+                state.requests.push((headers, body));
+                state.statuses.pop_front().unwrap_or(StatusCode::OK)
+            }
+
+            let state = Arc::new(Mutex::new(StandInState {
+                statuses: statuses.into(),
+                requests: vec![],
+            }));
+            let app = axum::Router::new()
+                .route("/", axum::routing::post(handle))
+                .with_state(state.clone());
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}/", listener.local_addr().unwrap());
+            tokio::spawn(async move {
+                axum::serve(listener, app).await.unwrap();
+            });
+            StandIn {
+                url: Url::parse(&url).unwrap(),
+                state,
+            }
+        }
+
+        fn requests(&self) -> Vec<(HeaderMap, Bytes)> {
+            self.state.lock().unwrap().requests.clone()
+        }
+
+        async fn wait(&self, n: usize) {
+            for _ in 0..200 {
+                if self.state.lock().unwrap().requests.len() >= n {
+                    return;
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            }
+            panic!("Timed out");
+        }
+    }
+}
@@ -3,7 +3,6 @@ use std::collections::hash_map::Entry;
 use std::ffi::OsStr;
 use std::fmt;
 use std::fs::File;
-use std::io::BufReader;
 use std::io::Read;
 use std::io::Seek;
 use std::io::SeekFrom;
@@ -336,9 +335,7 @@ impl TimeshiftFilesystem {
     }
 
     fn load_data(config: &TimeshiftRecorderConfig) -> Result<TimeshiftRecorderData, Error> {
-        let file = File::open(&config.data_file)?;
-        let reader = BufReader::new(file);
-        let data: TimeshiftRecorderData = serde_json::from_reader(reader)?;
+        let data = load_recorder_data(&config.data_file)?.ok_or(Error::NoContent)?;
         if data.service.id == config.service_id
             && data.chunk_size == config.chunk_size
             && data.max_chunks == config.max_chunks()
@@ -1,5 +1,6 @@
 mod migrate;
 mod openapi;
+mod rebuild_record_index;
 mod rebuild_timeshift;
 mod serve;
 
@@ -43,6 +44,7 @@ enum LogFormat {
 enum Command {
     Migrate(migrate::CommandLine),
     Openapi(openapi::CommandLine),
+    RebuildRecordIndex(rebuild_record_index::CommandLine),
     RebuildTimeshift(rebuild_timeshift::CommandLine),
 }
 
@@ -63,6 +65,7 @@ async fn main() {
     match cl.command {
         Some(Command::Migrate(ref cl)) => migrate::main(&config, cl).await,
         Some(Command::Openapi(ref cl)) => openapi::main(config, cl).await,
+        Some(Command::RebuildRecordIndex(ref cl)) => rebuild_record_index::main(config, cl).await,
         Some(Command::RebuildTimeshift(ref cl)) => rebuild_timeshift::main(config, cl).await,
         None => {
             if auto_migrate() {
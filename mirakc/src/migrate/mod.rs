@@ -1,3 +1,4 @@
+mod timeshift_data;
 mod v4;
 
 use mirakc_core::*;
@@ -12,4 +13,5 @@ pub struct CommandLine {
 
 pub async fn main(config: &config::Config, cl: &CommandLine) {
     v4::migrate(config, cl);
+    timeshift_data::migrate(config, cl);
 }
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod tests;
+
+use super::*;
+
+use mirakc_core::timeshift::TimeshiftDataFormat;
+use mirakc_core::timeshift::load_recorder_data;
+use mirakc_core::timeshift::save_recorder_data;
+
+pub fn migrate(config: &config::Config, cl: &CommandLine) {
+    for (name, recorder) in config.timeshift.recorders.iter() {
+        migrate_data_file(name, recorder, cl);
+    }
+}
+
+// Converts a data-file in the other format into `config.data_file`.
+//
+// The data-file in the other format is located in the same folder and has the same file stem.
+// For example, `/path/to/data.json` is converted into `/path/to/data.journal` when
+// `/path/to/data.journal` is specified in `config.data_file`.
+pub fn migrate_data_file(
+    name: &str,
+    config: &config::TimeshiftRecorderConfig,
+    cl: &CommandLine,
+) -> bool {
+    let new_path = &config.data_file;
+    let format = match TimeshiftDataFormat::from_path(new_path) {
+        Some(format) => format,
+        None => {
+            tracing::error!(recorder.name = name, file = ?new_path, "Unsupported format");
+            return false;
+        }
+    };
+
+    let old_format = match format {
+        TimeshiftDataFormat::Json => TimeshiftDataFormat::Journal,
+        TimeshiftDataFormat::Journal => TimeshiftDataFormat::Json,
+    };
+    let old_path = new_path.with_extension(old_format.extension());
+    if !old_path.is_file() {
+        tracing::info!(recorder.name = name, file = ?old_path, "File not found");
+        return false;
+    }
+
+    let has_data = matches!(new_path.metadata(), Ok(metadata) if metadata.len() > 0);
+    if !cl.force && has_data {
+        tracing::info!(recorder.name = name, file = ?new_path, "Already migrated");
+        return false;
+    }
+
+    let data = match load_recorder_data(&old_path) {
+        Ok(Some(data)) => data,
+        Ok(None) => {
+            tracing::info!(recorder.name = name, file = ?old_path, "No data");
+            return false;
+        }
+        Err(err) => {
+            tracing::error!(%err, recorder.name = name, file = ?old_path, "Failed to load");
+            return false;
+        }
+    };
+
+    tracing::info!(
+        recorder.name = name,
+        file = ?old_path,
+        reason = "feat(timeshift): add the journal format for data-file",
+        "Migrating...",
+    );
+    if save_recorder_data(new_path, &data) {
+        tracing::info!(
+            recorder.name = name,
+            ?old_path,
+            ?new_path,
+            "Migrated successfully"
+        );
+        true
+    } else {
+        tracing::error!(recorder.name = name, ?new_path, "Failed to save");
+        false
+    }
+}
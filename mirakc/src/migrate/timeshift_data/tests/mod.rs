@@ -0,0 +1,83 @@
+use super::*;
+use std::path::Path;
+use tempfile::TempDir;
+use test_log::test;
+
+#[test]
+fn test_migrate_data_file_no_old_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = recorder_config(&temp_dir.as_ref().join("data.journal"));
+
+    let migrated = migrate_data_file("test", &config, &Default::default());
+    assert!(!migrated);
+}
+
+#[test]
+fn test_migrate_data_file_already_migrated() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let old_path = temp_dir.as_ref().join("data.json");
+    std::fs::write(&old_path, include_bytes!("data.json")).unwrap();
+
+    let new_path = temp_dir.as_ref().join("data.journal");
+    std::fs::write(&new_path, b"[]\n").unwrap();
+
+    let config = recorder_config(&new_path);
+    let migrated = migrate_data_file("test", &config, &Default::default());
+    assert!(!migrated);
+    assert_eq!(std::fs::read(&new_path).unwrap(), b"[]\n");
+}
+
+#[test]
+fn test_migrate_data_file_json_to_journal() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let old_path = temp_dir.as_ref().join("data.json");
+    std::fs::write(&old_path, include_bytes!("data.json")).unwrap();
+
+    // An empty data-file is created in advance.
+    let new_path = temp_dir.as_ref().join("data.journal");
+    std::fs::write(&new_path, b"").unwrap();
+
+    let config = recorder_config(&new_path);
+    let migrated = migrate_data_file("test", &config, &Default::default());
+    assert!(migrated);
+    assert!(old_path.exists());
+    let data = load_recorder_data(&new_path).unwrap().unwrap();
+    assert_eq!(data.service.id, 100001.into());
+    assert_eq!(data.chunk_size, 8192);
+    assert_eq!(data.max_chunks, 9);
+    assert!(data.records.is_empty());
+    assert_eq!(data.points.len(), 2);
+    assert_eq!(data.points[1].pos, 8192);
+
+    // Migrate back to the JSON format.
+    std::fs::remove_file(&old_path).unwrap();
+    let config = recorder_config(&old_path);
+    let migrated = migrate_data_file("test", &config, &Default::default());
+    assert!(migrated);
+    let data = load_recorder_data(&old_path).unwrap().unwrap();
+    assert_eq!(data.points.len(), 2);
+
+    // Always migrate if forced.
+    let cl = CommandLine { force: true };
+    let migrated = migrate_data_file("test", &config, &cl);
+    assert!(migrated);
+}
+
+fn recorder_config(data_file: &Path) -> config::TimeshiftRecorderConfig {
+    serde_norway::from_str(&format!(
+        r#"
+        service-id: 100001
+        ts-file: /dev/null
+        data-file: {}
+        num-chunks: 10
+        uses:
+          tuner: tuner
+          channel-type: GR
+          channel: '1'
+        "#,
+        data_file.display()
+    ))
+    .unwrap()
+}
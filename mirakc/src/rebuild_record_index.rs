@@ -0,0 +1,85 @@
+use std::sync::Arc;
+
+use clap::Args;
+use mirakc_core::*;
+
+/// Rebuild content index files of records.
+///
+/// This subcommand rebuilds `<RECORD_ID>.index.jsonl` in `config.recording.records-dir` by
+/// scanning PCRs in the content file of each record.
+///
+/// The content index is used for time-based seeking in a record stream.  Rebuilding is needed
+/// in the following situations:
+///
+///   * Records were recorded by older versions which don't create the content index
+///   * The content index has been broken or removed
+///
+/// Records currently recording are skipped.
+#[derive(Args)]
+#[clap(verbatim_doc_comment)]
+pub struct CommandLine {
+    /// Target record ID.
+    ///
+    /// All records in `config.recording.records-dir` are processed if no record ID is specified.
+    #[arg(long = "record", value_name = "RECORD_ID")]
+    records: Vec<String>,
+}
+
+pub async fn main(config: Arc<config::Config>, cl: &CommandLine) {
+    let records_dir = match config.recording.records_dir {
+        Some(ref records_dir) => records_dir,
+        None => {
+            tracing::error!("config.recording.records-dir is not specified");
+            std::process::exit(1);
+        }
+    };
+
+    let record_ids = if cl.records.is_empty() {
+        collect_record_ids(records_dir)
+    } else {
+        cl.records.clone()
+    };
+
+    let mut failed = false;
+    for record_id in record_ids.into_iter() {
+        let record_id = recording::RecordId::from(record_id);
+        match recording::rebuild_content_index(&config, &record_id).await {
+            Ok(Some(index)) => {
+                tracing::info!(%record_id, points = index.points().len(), "Rebuilt");
+            }
+            Ok(None) => {
+                tracing::info!(%record_id, "Skipped, now recording");
+            }
+            Err(err) => {
+                tracing::error!(%err, %record_id, "Failed to rebuild");
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        std::process::exit(1);
+    }
+}
+
+fn collect_record_ids(records_dir: &std::path::Path) -> Vec<String> {
+    let entries = match std::fs::read_dir(records_dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            tracing::error!(%err, ?records_dir, "Failed to read records-dir");
+            std::process::exit(1);
+        }
+    };
+    let mut record_ids: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_suffix(".record.json"))
+                .map(|id| id.to_string())
+        })
+        .collect();
+    record_ids.sort();
+    record_ids
+}
@@ -264,6 +264,7 @@ async fn do_recording(config: Arc<config::Config>) -> bool {
             config.clone(),
             tuner_manager.clone(),
             epg.clone(),
+            None,
         ))
         .await;
 
@@ -64,6 +64,18 @@ async fn serve(config: Arc<config::Config>, system: &System) -> Result<(), error
             config.clone(),
             tuner_manager.clone(),
             epg.clone(),
+            Some(Caller::new(recording_manager.clone())),
+        ))
+        .await;
+
+    let _webhook_dispatcher = system
+        .spawn_actor(webhook::WebhookDispatcher::new(
+            config.clone(),
+            tuner_manager.clone(),
+            epg.clone(),
+            recording_manager.clone(),
+            timeshift_manager.clone(),
+            onair_manager.clone(),
         ))
         .await;
 